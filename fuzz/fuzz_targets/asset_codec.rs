@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use asset::types::{Allowance, AllowanceIndex, Asset, AssetBalance, LegacyAssetBalance};
+use bytes::Bytes;
+use protocol::fixed_codec::FixedCodec;
+
+fuzz_target!(|data: &[u8]| {
+    let bytes = Bytes::from(data.to_vec());
+
+    let _ = Asset::decode_fixed(bytes.clone());
+    let _ = AssetBalance::decode_fixed(bytes.clone());
+    let _ = LegacyAssetBalance::decode_fixed(bytes.clone());
+    let _ = Allowance::decode_fixed(bytes.clone());
+    let _ = AllowanceIndex::decode_fixed(bytes);
+});
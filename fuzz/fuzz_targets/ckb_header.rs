@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use crosschain::types::{CkbHeaderInner, UpdateHeadersPayload};
+use protocol::fixed_codec::FixedCodec;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(payload) = serde_json::from_slice::<UpdateHeadersPayload>(data) {
+        for h in payload.headers.into_iter() {
+            if let Ok(inner) = CkbHeaderInner::from(h) {
+                let bytes = inner.encode_fixed().unwrap();
+                CkbHeaderInner::decode_fixed(bytes).unwrap();
+            }
+        }
+    }
+});
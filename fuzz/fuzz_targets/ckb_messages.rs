@@ -0,0 +1,25 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use crosschain::molecule::{decode_transaction, TxView};
+use crosschain::proof::unpack_tx_proof;
+use crosschain::types::MessagePayload;
+use crosschain::{parse_deposit, parse_raw_deposit};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(payload) = serde_json::from_slice::<MessagePayload>(data) {
+        for m in payload.messages.iter() {
+            if let Some(tx) = &m.tx {
+                let _ = parse_deposit(tx);
+            }
+        }
+    }
+
+    if let Ok((tx, _)) = decode_transaction(data) {
+        let _ = parse_deposit(&tx);
+    }
+    if let Ok(view) = TxView::parse(data) {
+        let _ = parse_raw_deposit(&view);
+    }
+    let _ = unpack_tx_proof(data);
+});
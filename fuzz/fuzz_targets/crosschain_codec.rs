@@ -0,0 +1,24 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use bytes::Bytes;
+use crosschain::types::{
+    BridgeStats, BurnQuota, BurnQuotaUsage, BurnRecord, CkbHeaderInner, MirrorAsset, MmrState,
+    ProcessedDeposit, SupplyCap, SupportedLocks,
+};
+use protocol::fixed_codec::FixedCodec;
+
+fuzz_target!(|data: &[u8]| {
+    let bytes = Bytes::from(data.to_vec());
+
+    let _ = MirrorAsset::decode_fixed(bytes.clone());
+    let _ = BurnRecord::decode_fixed(bytes.clone());
+    let _ = BurnQuota::decode_fixed(bytes.clone());
+    let _ = BurnQuotaUsage::decode_fixed(bytes.clone());
+    let _ = BridgeStats::decode_fixed(bytes.clone());
+    let _ = SupportedLocks::decode_fixed(bytes.clone());
+    let _ = SupplyCap::decode_fixed(bytes.clone());
+    let _ = ProcessedDeposit::decode_fixed(bytes.clone());
+    let _ = MmrState::decode_fixed(bytes.clone());
+    let _ = CkbHeaderInner::decode_fixed(bytes);
+});
@@ -0,0 +1,89 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::json;
+
+use asset::types::Asset;
+use asset::AssetService;
+use protocol::traits::Service;
+use protocol::types::Address;
+use testing::{new_sdk, ContextBuilder, MockServiceSDK};
+
+const GRANTEES: u64 = 1_000;
+// enough for any number of iterations to spend, and still a json number
+const PLENTY: u64 = 1 << 60;
+
+fn address(n: u64) -> Address {
+    Address::from_hex(&format!("0x{:040x}", n)).unwrap()
+}
+
+fn write(
+    service: &mut AssetService<MockServiceSDK>,
+    caller: &Address,
+    method: &str,
+    payload: serde_json::Value,
+) -> String {
+    let ctx = ContextBuilder::new(caller.clone())
+        .method(method, payload.to_string())
+        .build();
+    service.write_(ctx).unwrap()
+}
+
+/// A service holding one asset, its whole supply owned by `issuer`.
+fn asset_service(issuer: &Address) -> (AssetService<MockServiceSDK>, Asset) {
+    let mut service = AssetService::new(new_sdk()).unwrap();
+    let asset = write(
+        &mut service,
+        issuer,
+        "create_asset",
+        json!({ "name": "bench", "supply": PLENTY }),
+    );
+
+    (service, serde_json::from_str(&asset).unwrap())
+}
+
+fn bench_transfer(c: &mut Criterion) {
+    let issuer = address(1);
+    let (mut service, asset) = asset_service(&issuer);
+    let payload = json!({ "asset_id": asset.id, "to": address(2), "value": 1 });
+
+    c.bench_function("transfer", |b| {
+        b.iter(|| {
+            write(
+                &mut service,
+                &issuer,
+                "transfer",
+                black_box(payload.clone()),
+            )
+        })
+    });
+}
+
+fn bench_transfer_from(c: &mut Criterion) {
+    let issuer = address(1);
+    let (mut service, asset) = asset_service(&issuer);
+    // allowances are indexed per grantor, the spent one sits among many
+    for n in 0..GRANTEES {
+        let payload = json!({ "asset_id": asset.id, "to": address(100 + n), "value": PLENTY });
+        write(&mut service, &issuer, "approve", payload);
+    }
+    let grantee = address(100 + GRANTEES / 2);
+    let payload = json!({
+        "asset_id": asset.id,
+        "sender": issuer,
+        "recipient": address(2),
+        "value": 1,
+    });
+
+    c.bench_function("transfer_from with 1000 allowances", |b| {
+        b.iter(|| {
+            write(
+                &mut service,
+                &grantee,
+                "transfer_from",
+                black_box(payload.clone()),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, bench_transfer, bench_transfer_from);
+criterion_main!(benches);
@@ -1,59 +1,296 @@
+mod math;
+mod meta_tx;
+pub mod params;
+pub mod schema;
+pub mod snapshot;
 #[cfg(test)]
 mod tests;
 pub mod types;
+pub mod view;
 
-use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 
 use bytes::Bytes;
 use derive_more::{Display, From};
 
-use binding_macro::{cycles, genesis, service, write};
-use protocol::traits::{ExecutorParams, ServiceSDK, StoreMap};
+use binding_macro::{cycles, genesis, hook_before, service, write};
+use common::amount::{Amount, MAX_DECIMALS};
+use common::cache::CachedMap;
+use common::migration::{self, Migration, Step, MIGRATION_CURSOR_KEY, SCHEMA_VERSION_KEY};
+use common::read_value;
+use common::versioned::Versioned;
+use common::view::{flatten, MigrationStatusView, ServiceInfoView, ValueView};
+use protocol::traits::{ExecutorParams, ServiceSDK};
 use protocol::types::{Address, Hash, ServiceContext};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
+use crate::math::{checked_add_u128, checked_sub_u128, mul_div};
+use crate::meta_tx::verify_meta_tx;
+use crate::params::WHOLE_BPS;
 use crate::types::{
-    ApproveEvent, ApprovePayload, Asset, AssetBalance, BurnTokenPayload, CreateAssetPayload,
-    GetAllowancePayload, GetAllowanceResponse, GetAssetPayload, GetBalancePayload,
-    GetBalanceResponse, InitGenesisPayload, MintTokenPayload, TransferEvent, TransferFromEvent,
-    TransferFromPayload, TransferPayload,
+    AccessSet, Allowance, AllowanceChangedEvent, AllowanceIndex, ApproveEvent, ApprovePayload,
+    Asset, AssetBalance, AssetMetadata, AssetMetadataEvent, AuthorizeSessionKeyPayload,
+    BalanceOfPayload, BurnEvent, BurnFromEvent, BurnFromPayload, BurnTokenPayload, ChainBinding,
+    Checkpoint, ClaimDistributionEvent, ClaimDistributionPayload, ClaimUnlockedEvent,
+    ClaimUnlockedPayload, CreateAssetPayload, CreateMirrorAssetPayload, DistributeEvent,
+    DistributePayload, Distribution, DistributionClaim, ExecuteHoldPayload, GetAccessSetPayload,
+    GetAllowancePayload, GetAllowanceResponse, GetAllowancesPayload, GetAllowancesResponse,
+    GetAssetMetadataPayload, GetAssetPayload, GetAssetsByIssuerPayload, GetAssetsByIssuerResponse,
+    GetBalancePayload, GetBalanceResponse, GetDistributionClaimPayload, GetDistributionPayload,
+    GetHoldPayload, GetIssuanceLogPayload, GetLockedBalancePayload, GetMetaNoncePayload,
+    GetSchemaPayload, GetSessionKeyPayload, GetSessionKeyResponse, GetSnapshotBalancePayload,
+    GetViewPayload, GrantedAllowance, HasRolePayload, HasRoleResponse, Hold, HoldEvent,
+    HoldPayload, HoldServices, InitGenesisPayload, IssuanceLog, IssuanceRecord, IssuedAssets,
+    LegacyAssetBalance, Lock, LockedBalanceResponse, Locks, MetaNonceResponse, MetaTransferEvent,
+    MetaTxPayload, MigratePayload, MigrationEvent, MigrationStatus, MintEvent, MintTokenPayload,
+    Minters, OnTransferPayload, Params, ParamsEvent, ReadView, ReleaseHoldPayload,
+    ResolveSymbolPayload, RevokeAllApprovalsEvent, RevokeAllApprovalsPayload,
+    RevokeSessionKeyPayload, ServiceInfo, SessionKey, SessionKeyEvent, SessionTransferEvent,
+    SessionTransferPayload, SetAssetMetadataPayload, SetParamsPayload, SnapshotBalance,
+    SnapshotEvent, StateKey, TakeSnapshotPayload, TransferAndCallPayload, TransferEvent,
+    TransferFee, TransferFeeChargedEvent, TransferFeeEvent, TransferFromEvent, TransferFromPayload,
+    TransferLockedEvent, TransferLockedPayload, TransferPayload, ZeroValuePolicy,
+    UNLIMITED_ALLOWANCE,
 };
+use crate::view::{
+    AccessSetView, AllowanceView, AllowancesView, AssetMetadataView, AssetView, AssetsByIssuerView,
+    BalanceView, DistributionClaimView, DistributionView, HoldView, IssuanceLogView,
+    LockedBalanceView, MetaNonceView, ParamsView, ServicesView, SessionKeyView,
+    SnapshotBalanceView, TransferFeeView,
+};
+
+pub use crate::params::{MAX_METADATA_VALUE_LEN, MAX_TRANSFER_FEE_BPS};
 
 const NATIVE_ASSET_KEY: &str = "native_asset";
+// names this service to the services `transfer_and_call` calls, in the extra
+// of the call
+static SERVICE_NAME: Bytes = Bytes::from_static(b"asset");
+const ADMIN_KEY: &str = "admin";
+const SELF_TRANSFER_NOOP_KEY: &str = "self_transfer_noop";
+const ZERO_VALUE_POLICY_KEY: &str = "zero_value_policy";
+const CHAIN_BINDING_KEY: &str = "chain_binding";
+const UNIQUE_SYMBOLS_KEY: &str = "unique_symbols";
+const RBAC_KEY: &str = "rbac";
+const ALLOWANCE_INDEX_PREFIX: &str = "allowance_index";
+const SESSION_KEY_PREFIX: &str = "session_key";
+const META_NONCE_KEY: &str = "meta_nonce";
+const SERVICE_ADDRESS_PREFIX: &str = "service_address";
+const DISTRIBUTION_POOL_PREFIX: &str = "distribution_pool";
+const DISTRIBUTION_CLAIM_PREFIX: &str = "distribution_claim";
+const DISTRIBUTION_COUNT_KEY: &str = "distribution_count";
+const LOCK_POOL_PREFIX: &str = "lock_pool";
+const LOCKS_PREFIX: &str = "locks";
+const HOLD_POOL_PREFIX: &str = "hold_pool";
+const HOLD_COUNT_KEY: &str = "hold_count";
+/// Most pending unlock heights an account has on one asset.
+pub const MAX_LOCKS: usize = 32;
+const ASSET_METADATA_PREFIX: &str = "asset_metadata";
+const ISSUANCE_LOG_PREFIX: &str = "issuance_log";
+/// Longest asset metadata key, in bytes.
+pub const MAX_METADATA_KEY_LEN: usize = 64;
+// charged by metadata writes on top of their fixed cost for each byte stored
+const METADATA_CYCLES_PER_BYTE: u64 = 1_00;
+/// Metadata entry holding the decimals amounts of the asset are displayed
+/// with, a number up to `MAX_DECIMALS`.
+pub const DECIMALS_METADATA_KEY: &str = "decimals";
+/// Most entries a paginated read returns in one call.
+pub const MAX_PAGE_LIMIT: u64 = 100;
+// charged by reads on top of their fixed cost for each entry they return
+const READ_CYCLES_PER_ITEM: u64 = 10_00;
+const SERVICE_VERSION: &str = env!("CARGO_PKG_VERSION");
+const PAYLOAD_VERSIONS: &[u32] = &[1];
+/// Schema version of the state this release writes.
+pub const SCHEMA_VERSION: u64 = 1;
+// entries a batched migration rewrites before each block
+const MIGRATION_BATCH: u64 = 100;
 
 pub struct AssetService<SDK> {
-    sdk:    SDK,
-    assets: Box<dyn StoreMap<Hash, Asset>>,
+    sdk:           SDK,
+    assets:        CachedMap<Hash, Asset>,
+    // asset ids by issuer
+    issued:        CachedMap<Address, IssuedAssets>,
+    // first asset of each symbol, by the symbol in lower case
+    symbols:       CachedMap<String, Hash>,
+    // fees of the assets taking one on transfers, by asset id
+    fees:          CachedMap<Hash, TransferFee>,
+    // funded distributions by id from 1
+    distributions: CachedMap<u64, Distribution>,
+    // open holds by id from 1
+    holds:         CachedMap<u64, Hold>,
+    // balances read or written by this call, by account and asset
+    balances:      RefCell<HashMap<(Address, Hash), u128>>,
 }
 
 #[service]
 impl<SDK: ServiceSDK> AssetService<SDK> {
     pub fn new(mut sdk: SDK) -> ProtocolResult<Self> {
-        let assets: Box<dyn StoreMap<Hash, Asset>> = sdk.alloc_or_recover_map("assets")?;
-
-        Ok(Self { sdk, assets })
+        let assets: CachedMap<Hash, Asset> = CachedMap::new(sdk.alloc_or_recover_map("assets")?);
+        let issued: CachedMap<Address, IssuedAssets> =
+            CachedMap::new(sdk.alloc_or_recover_map("issued_assets")?);
+        let symbols: CachedMap<String, Hash> = CachedMap::new(sdk.alloc_or_recover_map("symbols")?);
+        let fees: CachedMap<Hash, TransferFee> =
+            CachedMap::new(sdk.alloc_or_recover_map("transfer_fees")?);
+        let distributions: CachedMap<u64, Distribution> =
+            CachedMap::new(sdk.alloc_or_recover_map("distributions")?);
+        let holds: CachedMap<u64, Hold> = CachedMap::new(sdk.alloc_or_recover_map("holds")?);
+
+        Ok(Self {
+            sdk,
+            assets,
+            issued,
+            symbols,
+            fees,
+            distributions,
+            holds,
+            balances: RefCell::new(HashMap::new()),
+        })
     }
 
     #[genesis]
     fn init_genesis(&mut self, payload: InitGenesisPayload) -> ProtocolResult<()> {
         let asset = Asset {
-            id:     payload.id.clone(),
-            name:   payload.name,
-            supply: payload.supply,
-            issuer: payload.issuer.clone(),
+            id:           payload.id.clone(),
+            name:         payload.name,
+            supply:       Amount(payload.supply),
+            issuer:       payload.issuer.clone(),
+            max_supply:   payload.max_supply,
+            transferable: true,
         };
+        check_max_supply(&asset)?;
+
+        self.sdk.set_value(
+            SELF_TRANSFER_NOOP_KEY.to_owned(),
+            payload.self_transfer_noop,
+        )?;
+        self.sdk
+            .set_value(UNIQUE_SYMBOLS_KEY.to_owned(), payload.unique_symbols)?;
+        self.sdk.set_value(RBAC_KEY.to_owned(), payload.rbac)?;
+        self.sdk
+            .set_value(ZERO_VALUE_POLICY_KEY.to_owned(), payload.zero_value_policy)?;
+        if let Some(binding) = payload.chain_binding {
+            self.sdk.set_value(CHAIN_BINDING_KEY.to_owned(), binding)?;
+        }
 
+        self.register_symbol(&asset)?;
         self.assets.insert(asset.id.clone(), asset.clone())?;
+        self.index_issued(&asset)?;
         self.sdk
             .set_value(NATIVE_ASSET_KEY.to_owned(), payload.id.clone())?;
+        let admin = payload.admin.unwrap_or_else(|| payload.issuer.clone());
+        self.sdk.set_value(ADMIN_KEY.to_owned(), admin)?;
+        params::store(&mut self.sdk, SetParamsPayload {
+            minters: Some(payload.minters),
+            ..SetParamsPayload::default()
+        })?;
+        self.sdk
+            .set_value(SCHEMA_VERSION_KEY.to_owned(), SCHEMA_VERSION)?;
+        self.log_issuance(issuance(
+            &asset.id,
+            "create",
+            "genesis",
+            &asset.issuer,
+            payload.supply,
+            0,
+        ))?;
+
+        self.set_balance(0, &asset.issuer, &asset.id, payload.supply)
+    }
 
-        let asset_balance = AssetBalance {
-            value:     payload.supply,
-            allowance: BTreeMap::new(),
-        };
+    #[cycles(100_00)]
+    #[read]
+    fn get_service_info(&self, ctx: ServiceContext) -> ProtocolResult<ServiceInfo> {
+        let mut features = vec![
+            "allowance_pages".to_owned(),
+            "unlimited_allowance".to_owned(),
+            "transfer_and_call".to_owned(),
+            "read_views".to_owned(),
+            "issuer_index".to_owned(),
+            "session_keys".to_owned(),
+            "balance_of".to_owned(),
+            "migrations".to_owned(),
+            "transfer_fees".to_owned(),
+            "soulbound_assets".to_owned(),
+            "balance_snapshots".to_owned(),
+            "distributions".to_owned(),
+            "asset_metadata".to_owned(),
+            "access_sets".to_owned(),
+            "formatted_amounts".to_owned(),
+            "burn_from".to_owned(),
+            "params".to_owned(),
+            "locked_transfers".to_owned(),
+            "holds".to_owned(),
+            "issuance_log".to_owned(),
+            "zero_value_policy".to_owned(),
+            "meta_transfers".to_owned(),
+        ];
+        if self.self_transfer_noop()? {
+            features.push("self_transfer_noop".to_owned());
+        }
+        if self.unique_symbols()? {
+            features.push("unique_symbols".to_owned());
+        }
+        if self.rbac_enabled()? {
+            features.push("rbac_roles".to_owned());
+        }
 
-        self.sdk
-            .set_account_value(&asset.issuer, asset.id, asset_balance)
+        Ok(ServiceInfo {
+            version: SERVICE_VERSION.to_owned(),
+            features,
+            payload_versions: PAYLOAD_VERSIONS.to_vec(),
+            chain: self.chain_binding()?,
+        })
+    }
+
+    /// JSON schema of a payload, event or response type, by type name.
+    #[cycles(100_00)]
+    #[read]
+    fn get_schema(&self, ctx: ServiceContext, payload: GetSchemaPayload) -> ProtocolResult<String> {
+        let schema = schema::schema(&payload.type_name).ok_or(ServiceError::UnknownSchema {
+            type_name: payload.type_name,
+        })?;
+        serde_json::to_string(&schema).map_err(|e| ServiceError::JsonParse(e).into())
+    }
+
+    /// The response of any read as its view, flattened to string fields by
+    /// path for graphql clients. Collections past the item bound are cut and
+    /// listed.
+    #[cycles(100_00)]
+    #[read]
+    fn get_view(&self, ctx: ServiceContext, payload: GetViewPayload) -> ProtocolResult<ReadView> {
+        let value = read_value!(self, ctx, payload.method.as_str(), &payload.payload, {
+            get_service_info => ServiceInfoView,
+            get_schema: GetSchemaPayload => ValueView,
+            get_native_asset => AssetView,
+            get_params => ParamsView,
+            get_asset: GetAssetPayload => AssetView,
+            resolve_symbol: ResolveSymbolPayload => AssetView,
+            get_balance: GetBalancePayload => BalanceView,
+            balance_of: BalanceOfPayload => ValueView,
+            get_allowance: GetAllowancePayload => AllowanceView,
+            get_allowances: GetAllowancesPayload => AllowancesView,
+            get_assets_by_issuer: GetAssetsByIssuerPayload => AssetsByIssuerView,
+            get_session_key: GetSessionKeyPayload => SessionKeyView,
+            get_meta_nonce: GetMetaNoncePayload => MetaNonceView,
+            get_minters => ServicesView,
+            get_transfer_fee: GetAssetPayload => TransferFeeView,
+            get_snapshot_balance: GetSnapshotBalancePayload => SnapshotBalanceView,
+            get_distribution: GetDistributionPayload => DistributionView,
+            get_distribution_claim: GetDistributionClaimPayload => DistributionClaimView,
+            get_asset_metadata: GetAssetMetadataPayload => AssetMetadataView,
+            get_access_set: GetAccessSetPayload => AccessSetView,
+            get_locked_balance: GetLockedBalancePayload => LockedBalanceView,
+            get_issuance_log: GetIssuanceLogPayload => IssuanceLogView,
+            get_hold_services => ServicesView,
+            get_hold: GetHoldPayload => HoldView,
+            get_migration_status => MigrationStatusView,
+        });
+        Ok(flatten(
+            payload.method,
+            &value,
+            payload.max_items,
+            payload.max_depth,
+        ))
     }
 
     #[cycles(100_00)]
@@ -74,6 +311,24 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
         Ok(asset)
     }
 
+    /// The first asset created with the symbol, ignoring case.
+    #[cycles(100_00)]
+    #[read]
+    fn resolve_symbol(
+        &self,
+        ctx: ServiceContext,
+        payload: ResolveSymbolPayload,
+    ) -> ProtocolResult<Asset> {
+        let key = symbol_key(&payload.symbol);
+        if !self.symbols.contains(&key)? {
+            return Err(ServiceError::UnknownSymbol {
+                symbol: payload.symbol,
+            }
+            .into());
+        }
+        self.assets.get(&self.symbols.get(&key)?)
+    }
+
     #[cycles(100_00)]
     #[read]
     fn get_balance(
@@ -88,21 +343,40 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             .into());
         }
 
-        let asset_balance = self
-            .sdk
-            .get_account_value(&payload.user, &payload.asset_id)?
-            .unwrap_or(AssetBalance {
-                value:     0,
-                allowance: BTreeMap::new(),
-            });
+        let balance = self.load_balance(&payload.user, &payload.asset_id)?;
+        let formatted_balance = if payload.formatted {
+            let decimals = self.asset_decimals(&payload.asset_id)?.unwrap_or(0);
+            Some(Amount(balance).to_display(decimals))
+        } else {
+            None
+        };
 
         Ok(GetBalanceResponse {
             asset_id: payload.asset_id,
-            user:     payload.user,
-            balance:  asset_balance.value,
+            user: payload.user,
+            balance,
+            formatted_balance,
         })
     }
 
+    /// The balance alone, as a decimal string, for other services to read
+    /// through `sdk.read`. Its payload and response are an interface between
+    /// services and keep their json shape across versions: the payload is
+    /// `{"asset_id": "0x...", "user": "0x..."}` and the response a string such
+    /// as `"1024"`.
+    #[cycles(100_00)]
+    #[read]
+    fn balance_of(&self, ctx: ServiceContext, payload: BalanceOfPayload) -> ProtocolResult<Amount> {
+        if !self.assets.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.asset_id,
+            }
+            .into());
+        }
+
+        Ok(Amount(self.load_balance(&payload.user, &payload.asset_id)?))
+    }
+
     #[cycles(100_00)]
     #[read]
     fn get_allowance(
@@ -117,331 +391,2566 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             .into());
         }
 
-        let opt_asset_balance: Option<AssetBalance> = self
-            .sdk
-            .get_account_value(&payload.grantor, &payload.asset_id)?;
-
-        if let Some(v) = opt_asset_balance {
-            let allowance = v.allowance.get(&payload.grantee).unwrap_or(&0);
+        let value = self.allowance_of(&payload.grantor, &payload.asset_id, &payload.grantee)?;
 
-            Ok(GetAllowanceResponse {
-                asset_id: payload.asset_id,
-                grantor:  payload.grantor,
-                grantee:  payload.grantee,
-                value:    *allowance,
-            })
-        } else {
-            Ok(GetAllowanceResponse {
-                asset_id: payload.asset_id,
-                grantor:  payload.grantor,
-                grantee:  payload.grantee,
-                value:    0,
-            })
-        }
+        Ok(GetAllowanceResponse {
+            asset_id: payload.asset_id,
+            grantor: payload.grantor,
+            grantee: payload.grantee,
+            value,
+        })
     }
 
-    #[write]
-    fn mint_token(&mut self, ctx: ServiceContext, payload: MintTokenPayload) -> ProtocolResult<()> {
-        if ctx.get_extra().is_none() {
-            return Err(ServiceError::NoPermission.into());
+    #[cycles(100_00)]
+    #[read]
+    fn get_allowances(
+        &self,
+        ctx: ServiceContext,
+        payload: GetAllowancesPayload,
+    ) -> ProtocolResult<GetAllowancesResponse> {
+        if !self.assets.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.asset_id,
+            }
+            .into());
         }
-
-        let token_id = payload.token_id;
-
-        if !self.assets.contains(&token_id)? {
-            let asset = Asset {
-                id:     token_id.clone(),
-                name:   "ckb-image_token".to_owned() + &token_id.as_hex().as_str()[2..7],
-                supply: 0,
-                issuer: Address::from_hex("0xc4b0000000000000000000000000000000000000")?,
-            };
-            self.assets.insert(token_id.clone(), asset.clone())?;
-            let asset_balance = AssetBalance {
-                value:     payload.amount,
-                allowance: BTreeMap::new(),
-            };
-            self.sdk
-                .set_account_value(&payload.receiver, asset.id.clone(), asset_balance)?;
-        } else {
-            let mut receiver_balance: AssetBalance = self
-                .sdk
-                .get_account_value(&payload.receiver, &token_id)?
-                .unwrap_or(AssetBalance {
-                    value:     0,
-                    allowance: BTreeMap::new(),
-                });
-
-            let (v, overflow) = receiver_balance.value.overflowing_add(payload.amount);
-            if overflow {
-                return Err(ServiceError::U128Overflow.into());
+        if payload.limit > MAX_PAGE_LIMIT {
+            return Err(ServiceError::PageLimitExceeded {
+                limit: payload.limit,
+                max:   MAX_PAGE_LIMIT,
             }
-            receiver_balance.value = v;
+            .into());
+        }
 
-            self.sdk
-                .set_account_value(&payload.receiver, token_id.clone(), receiver_balance)?;
+        let grantees = self.grantees_of(&payload.grantor, &payload.asset_id)?;
+
+        let mut allowances = vec![];
+        for grantee in grantees
+            .into_iter()
+            .skip(payload.offset as usize)
+            .take(payload.limit as usize)
+        {
+            let value = self.allowance_of(&payload.grantor, &payload.asset_id, &grantee)?;
+            allowances.push(GrantedAllowance { grantee, value });
         }
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * allowances.len() as u64)?;
 
-        Ok(())
+        Ok(GetAllowancesResponse {
+            asset_id: payload.asset_id,
+            grantor: payload.grantor,
+            allowances,
+        })
     }
 
-    #[write]
-    fn burn_token(&mut self, ctx: ServiceContext, payload: BurnTokenPayload) -> ProtocolResult<()> {
-        if ctx.get_extra().is_none() {
-            return Err(ServiceError::NoPermission.into());
-        }
-        if !self.assets.contains(&payload.token_id)? {
+    /// The nonce the next signed transfer of the user must carry.
+    #[cycles(100_00)]
+    #[read]
+    fn get_meta_nonce(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMetaNoncePayload,
+    ) -> ProtocolResult<MetaNonceResponse> {
+        let nonce = self.meta_nonce(&payload.user)?;
+        Ok(MetaNonceResponse {
+            user: payload.user,
+            nonce,
+        })
+    }
+
+    /// Changes of the supply of an asset, oldest first, for auditors to check
+    /// they add up to it. Assets created before the log began have their
+    /// changes since then only.
+    #[cycles(100_00)]
+    #[read]
+    fn get_issuance_log(
+        &self,
+        ctx: ServiceContext,
+        payload: GetIssuanceLogPayload,
+    ) -> ProtocolResult<IssuanceLog> {
+        if !self.assets.contains(&payload.asset_id)? {
             return Err(ServiceError::NotFoundAsset {
-                id: payload.token_id,
+                id: payload.asset_id,
             }
             .into());
         }
-
-        let mut user_asset_balance: AssetBalance = self
-            .sdk
-            .get_account_value(&payload.user, &payload.token_id)?
-            .unwrap_or(AssetBalance {
-                value:     0,
-                allowance: BTreeMap::new(),
-            });
-        let user_balance = user_asset_balance.value;
-
-        if user_balance < payload.amount {
-            return Err(ServiceError::LackOfBalance {
-                expect: payload.amount,
-                real:   user_balance,
+        if payload.limit > MAX_PAGE_LIMIT {
+            return Err(ServiceError::PageLimitExceeded {
+                limit: payload.limit,
+                max:   MAX_PAGE_LIMIT,
             }
             .into());
         }
 
-        user_asset_balance.value = user_balance - payload.amount;
-        self.sdk
-            .set_account_value(&payload.user, payload.token_id.clone(), user_asset_balance)
+        let len = self.issuance_len(&payload.asset_id)?;
+        let first = payload.offset.saturating_add(1);
+        let last = payload.offset.saturating_add(payload.limit).min(len);
+        let mut records = vec![];
+        for seq in first..=last {
+            let record: Option<IssuanceRecord> =
+                self.sdk.get_value(&issuance_key(&payload.asset_id, seq))?;
+            records.extend(record);
+        }
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * records.len() as u64)?;
+
+        Ok(IssuanceLog {
+            asset_id: payload.asset_id,
+            len,
+            records,
+        })
     }
 
-    #[cycles(210_00)]
-    #[write]
-    fn create_asset(
-        &mut self,
+    /// Assets the issuer created, oldest first.
+    #[cycles(100_00)]
+    #[read]
+    fn get_assets_by_issuer(
+        &self,
         ctx: ServiceContext,
-        payload: CreateAssetPayload,
-    ) -> ProtocolResult<Asset> {
-        let caller = ctx.get_caller();
-        let payload_str = serde_json::to_string(&payload).map_err(ServiceError::JsonParse)?;
+        payload: GetAssetsByIssuerPayload,
+    ) -> ProtocolResult<GetAssetsByIssuerResponse> {
+        if payload.limit > MAX_PAGE_LIMIT {
+            return Err(ServiceError::PageLimitExceeded {
+                limit: payload.limit,
+                max:   MAX_PAGE_LIMIT,
+            }
+            .into());
+        }
 
-        let id = Hash::digest(Bytes::from(payload_str + &caller.as_hex()));
+        let asset_ids = self.issued_by(&payload.issuer)?;
 
-        if self.assets.contains(&id)? {
-            return Err(ServiceError::Exists { id }.into());
+        let mut assets = vec![];
+        for id in asset_ids
+            .iter()
+            .skip(payload.offset as usize)
+            .take(payload.limit as usize)
+        {
+            assets.push(self.assets.get(id)?);
         }
-        let asset = Asset {
-            id:     id.clone(),
-            name:   payload.name,
-            supply: payload.supply,
-            issuer: caller,
-        };
-        self.assets.insert(id, asset.clone())?;
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * assets.len() as u64)?;
 
-        let asset_balance = AssetBalance {
-            value:     payload.supply,
-            allowance: BTreeMap::new(),
-        };
+        Ok(GetAssetsByIssuerResponse {
+            issuer: payload.issuer,
+            total: asset_ids.len() as u64,
+            assets,
+        })
+    }
 
-        self.sdk
-            .set_account_value(&asset.issuer, asset.id.clone(), asset_balance)?;
+    #[cycles(100_00)]
+    #[read]
+    fn get_session_key(
+        &self,
+        ctx: ServiceContext,
+        payload: GetSessionKeyPayload,
+    ) -> ProtocolResult<GetSessionKeyResponse> {
+        let session = self
+            .session_key_of(&payload.authorizer, &payload.asset_id, &payload.session_key)?
+            .unwrap_or(SessionKey {
+                budget:     0,
+                expires_at: 0,
+            });
 
-        let event_str = serde_json::to_string(&asset).map_err(ServiceError::JsonParse)?;
-        ctx.emit_event(event_str)?;
+        Ok(GetSessionKeyResponse {
+            asset_id:    payload.asset_id,
+            authorizer:  payload.authorizer,
+            session_key: payload.session_key,
+            budget:      session.budget,
+            expires_at:  session.expires_at,
+        })
+    }
 
-        Ok(asset)
+    #[cycles(100_00)]
+    #[read]
+    fn get_minters(&self, ctx: ServiceContext) -> ProtocolResult<Minters> {
+        self.minters()
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_params(&self, ctx: ServiceContext) -> ProtocolResult<Params> {
+        self.params()
     }
 
+    /// Changes the parameters the payload sets. Fees and metadata already
+    /// set stay as they are, the new bounds apply to later changes. The
+    /// minters and hold services are replaced as a whole.
     #[cycles(210_00)]
     #[write]
-    fn transfer(&mut self, ctx: ServiceContext, payload: TransferPayload) -> ProtocolResult<()> {
-        let sender = if let Some(addr_hex) = ctx.get_extra() {
-            Address::from_hex(&String::from_utf8(addr_hex.to_vec()).expect("extra should be hex"))?
-        } else {
-            ctx.get_caller()
-        };
-
-        let asset_id = payload.asset_id.clone();
-        let value = payload.value;
-        let to = payload.to;
-
-        if !self.assets.contains(&asset_id)? {
-            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+    fn set_params(&mut self, ctx: ServiceContext, payload: SetParamsPayload) -> ProtocolResult<()> {
+        self.verify_admin(&ctx)?;
+        if let Some(name) = params::invalid(&payload) {
+            return Err(ServiceError::InvalidParam {
+                name: name.to_owned(),
+            }
+            .into());
         }
 
-        self._transfer(sender.clone(), to.clone(), asset_id.clone(), value)?;
+        let changed = params::store(&mut self.sdk, payload)?;
 
-        let event = TransferEvent {
-            asset_id,
-            from: sender,
-            to,
-            value,
+        let event = ParamsEvent {
+            changed,
+            params: self.params()?,
+            topic: "set_params".to_owned(),
         };
         let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
         ctx.emit_event(event_str)
     }
 
-    #[cycles(210_00)]
-    #[write]
-    fn approve(&mut self, ctx: ServiceContext, payload: ApprovePayload) -> ProtocolResult<()> {
-        let caller = ctx.get_caller();
-        let asset_id = payload.asset_id.clone();
-        let value = payload.value;
-        let to = payload.to;
+    #[cycles(100_00)]
+    #[read]
+    fn get_hold_services(&self, ctx: ServiceContext) -> ProtocolResult<HoldServices> {
+        self.hold_services()
+    }
 
-        if caller == to {
-            return Err(ServiceError::ApproveToYourself.into());
+    /// The transfer fee of an asset, a zero rate paid to the issuer when it
+    /// takes none.
+    #[cycles(100_00)]
+    #[read]
+    fn get_transfer_fee(
+        &self,
+        ctx: ServiceContext,
+        payload: GetAssetPayload,
+    ) -> ProtocolResult<TransferFee> {
+        if self.fees.contains(&payload.id)? {
+            return self.fees.get(&payload.id);
         }
-
-        if !self.assets.contains(&asset_id)? {
-            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        if !self.assets.contains(&payload.id)? {
+            return Err(ServiceError::NotFoundAsset { id: payload.id }.into());
         }
 
-        let mut caller_asset_balance: AssetBalance = self
-            .sdk
-            .get_account_value(&caller, &asset_id)?
-            .unwrap_or(AssetBalance {
-                value:     0,
-                allowance: BTreeMap::new(),
-            });
-        caller_asset_balance
-            .allowance
-            .entry(to.clone())
-            .and_modify(|e| *e = value)
-            .or_insert(value);
-
-        self.sdk
-            .set_account_value(&caller, asset_id.clone(), caller_asset_balance)?;
-
-        let event = ApproveEvent {
-            asset_id,
-            grantor: caller,
-            grantee: to,
-            value,
-        };
-        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
-        ctx.emit_event(event_str)
+        Ok(TransferFee {
+            collector: self.assets.get(&payload.id)?.issuer,
+            asset_id:  payload.id,
+            rate_bps:  0,
+            exempt:    vec![],
+        })
     }
 
+    /// Sets the fee transfers of an asset pay, for its issuer. A zero rate
+    /// turns the fee off.
     #[cycles(210_00)]
     #[write]
-    fn transfer_from(
+    fn set_transfer_fee(
         &mut self,
         ctx: ServiceContext,
-        payload: TransferFromPayload,
+        payload: Versioned<TransferFee>,
     ) -> ProtocolResult<()> {
-        let caller = if let Some(addr_hex) = ctx.get_extra() {
-            Address::from_hex(&String::from_utf8(addr_hex.to_vec()).expect("extra should be hex"))?
-        } else {
-            ctx.get_caller()
-        };
-        let sender = payload.sender;
-        let recipient = payload.recipient;
-        let asset_id = payload.asset_id;
-        let value = payload.value;
+        let mut payload = payload.into_inner();
+        let asset_id = payload.asset_id.clone();
 
         if !self.assets.contains(&asset_id)? {
             return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
         }
-
-        let mut sender_asset_balance: AssetBalance = self
-            .sdk
-            .get_account_value(&sender, &asset_id)?
-            .unwrap_or(AssetBalance {
-                value:     0,
-                allowance: BTreeMap::new(),
-            });
-        let sender_allowance = sender_asset_balance
-            .allowance
-            .entry(caller.clone())
-            .or_insert(0);
-        if *sender_allowance < value {
-            return Err(ServiceError::LackOfBalance {
-                expect: value,
-                real:   *sender_allowance,
+        if self.assets.get(&asset_id)?.issuer != ctx.get_caller() {
+            return Err(ServiceError::NoPermission.into());
+        }
+        let max = self.params()?.max_transfer_fee_bps;
+        if payload.rate_bps > max {
+            return Err(ServiceError::FeeRateTooHigh {
+                rate_bps: payload.rate_bps,
+                max,
             }
             .into());
         }
-        let after_sender_allowance = *sender_allowance - value;
-        sender_asset_balance
-            .allowance
-            .entry(caller.clone())
-            .and_modify(|e| *e = after_sender_allowance)
-            .or_insert(after_sender_allowance);
-        self.sdk
-            .set_account_value(&sender, asset_id.clone(), sender_asset_balance)?;
 
-        self._transfer(sender.clone(), recipient.clone(), asset_id.clone(), value)?;
+        payload.exempt.sort();
+        payload.exempt.dedup();
+        if payload.rate_bps == 0 {
+            if self.fees.contains(&asset_id)? {
+                self.fees.remove(&asset_id)?;
+            }
+        } else {
+            self.fees.insert(asset_id.clone(), payload.clone())?;
+        }
 
-        let event = TransferFromEvent {
+        let event = TransferFeeEvent {
             asset_id,
-            caller,
-            sender,
-            recipient,
-            value,
+            rate_bps: payload.rate_bps,
+            collector: payload.collector,
+            exempt: payload.exempt,
+            topic: "set_transfer_fee".to_owned(),
         };
         let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
         ctx.emit_event(event_str)
     }
 
-    fn _transfer(
-        &mut self,
-        sender: Address,
-        recipient: Address,
-        asset_id: Hash,
-        value: u128,
-    ) -> ProtocolResult<()> {
-        if sender == recipient {
-            return Err(ServiceError::RecipientIsSender.into());
-        }
+    /// What `payload.method` would touch if `payload.caller` sent it now.
+    /// Transfers, transfers from an allowance and approvals declare their
+    /// balances and allowances, other writes are exclusive. A transfer fee
+    /// set on the asset counts its collector as credited.
+    #[cycles(100_00)]
+    #[read]
+    fn get_access_set(
+        &self,
+        ctx: ServiceContext,
+        payload: GetAccessSetPayload,
+    ) -> ProtocolResult<AccessSet> {
+        let caller = payload.caller;
+        let (asset_id, sender, recipient, grantor) = match payload.method.as_str() {
+            "transfer" => {
+                let transfer: Versioned<TransferPayload> =
+                    serde_json::from_str(&payload.payload).map_err(ServiceError::JsonParse)?;
+                let transfer = transfer.into_inner();
+                (transfer.asset_id, caller, Some(transfer.to), None)
+            }
+            "transfer_from" => {
+                let transfer: Versioned<TransferFromPayload> =
+                    serde_json::from_str(&payload.payload).map_err(ServiceError::JsonParse)?;
+                let transfer = transfer.into_inner();
+                let grantor = transfer.sender.clone();
+                (
+                    transfer.asset_id,
+                    transfer.sender,
+                    Some(transfer.recipient),
+                    Some(grantor),
+                )
+            }
+            "approve" => {
+                let approve: Versioned<ApprovePayload> =
+                    serde_json::from_str(&payload.payload).map_err(ServiceError::JsonParse)?;
+                let asset_id = approve.into_inner().asset_id;
+                (asset_id, caller.clone(), None, Some(caller))
+            }
+            _ => return Ok(AccessSet::exclusive()),
+        };
 
-        let mut sender_asset_balance: AssetBalance = self
-            .sdk
-            .get_account_value(&sender, &asset_id)?
-            .unwrap_or(AssetBalance {
-                value:     0,
-                allowance: BTreeMap::new(),
+        let mut set = AccessSet {
+            exclusive: false,
+            reads:     vec![
+                StateKey::Asset {
+                    asset_id: asset_id.clone(),
+                },
+                StateKey::TransferFee {
+                    asset_id: asset_id.clone(),
+                },
+                StateKey::Config,
+            ],
+            writes:    vec![],
+        };
+        if let Some(grantor) = grantor {
+            set.writes.push(StateKey::Allowances {
+                asset_id: asset_id.clone(),
+                grantor,
             });
-        let sender_balance = sender_asset_balance.value;
-
+        }
+        let recipient = match recipient {
+            Some(recipient) => recipient,
+            None => return Ok(set),
+        };
+
+        let mut credited = vec![sender, recipient];
+        if self.fees.contains(&asset_id)? {
+            credited.push(self.fees.get(&asset_id)?.collector);
+        }
+        for account in credited {
+            let key = StateKey::Balance {
+                asset_id: asset_id.clone(),
+                account,
+            };
+            if !set.writes.contains(&key) {
+                set.writes.push(key);
+            }
+        }
+        Ok(set)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_asset_metadata(
+        &self,
+        ctx: ServiceContext,
+        payload: GetAssetMetadataPayload,
+    ) -> ProtocolResult<AssetMetadata> {
+        if !self.assets.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.asset_id,
+            }
+            .into());
+        }
+
+        let value: Option<String> = self
+            .sdk
+            .get_value(&asset_metadata_key(&payload.asset_id, &payload.key))?;
+        Ok(AssetMetadata {
+            asset_id: payload.asset_id,
+            key:      payload.key,
+            value:    value.unwrap_or_default(),
+        })
+    }
+
+    /// Sets an entry of an asset's metadata, for its issuer. Writes pay for
+    /// each byte they store.
+    #[cycles(210_00)]
+    #[write]
+    fn set_asset_metadata(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<SetAssetMetadataPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let asset_id = payload.asset_id;
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        if self.assets.get(&asset_id)?.issuer != ctx.get_caller() {
+            return Err(ServiceError::NoPermission.into());
+        }
+        if payload.key.is_empty() || payload.key.len() > MAX_METADATA_KEY_LEN {
+            return Err(ServiceError::InvalidMetadataKey { key: payload.key }.into());
+        }
+        let max = self.params()?.max_metadata_value_len as usize;
+        if payload.value.len() > max {
+            return Err(ServiceError::MetadataTooLarge {
+                len: payload.value.len(),
+                max,
+            }
+            .into());
+        }
+        if payload.key == DECIMALS_METADATA_KEY && !payload.value.is_empty() {
+            parse_decimals(&payload.value)?;
+        }
+        ctx.sub_cycles(
+            METADATA_CYCLES_PER_BYTE * (payload.key.len() + payload.value.len()) as u64,
+        )?;
+
+        self.sdk.set_value(
+            asset_metadata_key(&asset_id, &payload.key),
+            payload.value.clone(),
+        )?;
+
+        let event = AssetMetadataEvent {
+            asset_id,
+            key: payload.key,
+            value: payload.value,
+            topic: "set_asset_metadata".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Keeps the balances of an asset at the end of the current block, for
+    /// distributions to be paid over. Only the issuer takes snapshots, as
+    /// each one has the next write of every balance kept for good. Returns
+    /// the snapshot height.
+    #[cycles(210_00)]
+    #[write]
+    fn take_snapshot(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<TakeSnapshotPayload>,
+    ) -> ProtocolResult<u64> {
+        let payload = payload.into_inner();
+        let height = ctx.get_current_height();
+
+        if !self.assets.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.asset_id,
+            }
+            .into());
+        }
+        if self.assets.get(&payload.asset_id)?.issuer != ctx.get_caller() {
+            return Err(ServiceError::NoPermission.into());
+        }
+        if self.latest_snapshot(&payload.asset_id)? == Some(height) {
+            return Ok(height);
+        }
+
+        self.sdk
+            .set_value(snapshot::snapshot_key(&payload.asset_id, height), true)?;
+        self.sdk
+            .set_value(snapshot::latest_snapshot_key(&payload.asset_id), height)?;
+
+        let event = SnapshotEvent {
+            asset_id: payload.asset_id,
+            height,
+            topic: "snapshot".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        Ok(height)
+    }
+
+    /// Balance of a user and supply of an asset at a snapshot.
+    #[cycles(100_00)]
+    #[read]
+    fn get_snapshot_balance(
+        &self,
+        ctx: ServiceContext,
+        payload: GetSnapshotBalancePayload,
+    ) -> ProtocolResult<SnapshotBalance> {
+        if !self.assets.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.asset_id,
+            }
+            .into());
+        }
+        self.check_snapshot(&payload.asset_id, payload.height)?;
+
+        Ok(SnapshotBalance {
+            balance:  self.balance_at(&payload.user, &payload.asset_id, payload.height)?,
+            supply:   self.supply_at(&payload.asset_id, payload.height)?,
+            asset_id: payload.asset_id,
+            user:     payload.user,
+            height:   payload.height,
+        })
+    }
+
+    /// Funds a distribution of `total_amount` of `funding_asset` from the
+    /// caller to the holders of `asset_id` at a snapshot of a past block,
+    /// each claiming a share in proportion to their balance then.
+    #[cycles(210_00)]
+    #[write]
+    fn distribute(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<DistributePayload>,
+    ) -> ProtocolResult<u64> {
+        let payload = payload.into_inner();
+        let funder = ctx.get_caller();
+        let height = ctx.get_current_height();
+
+        for id in [&payload.asset_id, &payload.funding_asset].iter() {
+            if !self.assets.contains(id)? {
+                return Err(ServiceError::NotFoundAsset { id: (*id).clone() }.into());
+            }
+        }
+        // balances of the current block can still change
+        if payload.snapshot_height >= height {
+            return Err(ServiceError::SnapshotNotFinal {
+                height: payload.snapshot_height,
+            }
+            .into());
+        }
+        self.check_snapshot(&payload.asset_id, payload.snapshot_height)?;
+        let supply = self.held_supply_at(&payload.asset_id, payload.snapshot_height)?;
+        if supply == 0 || payload.total_amount == 0 {
+            return Err(ServiceError::EmptyDistribution.into());
+        }
+        self.check_transferable(&payload.funding_asset, &funder)?;
+
+        self.move_balance(
+            height,
+            &funder,
+            &distribution_pool()?,
+            &payload.funding_asset,
+            payload.total_amount,
+        )?;
+
+        let id = self.distribution_count()? + 1;
+        self.sdk.set_value(DISTRIBUTION_COUNT_KEY.to_owned(), id)?;
+        let distribution = Distribution {
+            id,
+            asset_id: payload.asset_id,
+            funding_asset: payload.funding_asset,
+            funder,
+            total_amount: payload.total_amount,
+            snapshot_height: payload.snapshot_height,
+            supply,
+            claimed: 0,
+        };
+        self.distributions.insert(id, distribution.clone())?;
+
+        let event = DistributeEvent {
+            id,
+            asset_id: distribution.asset_id,
+            funding_asset: distribution.funding_asset,
+            funder: distribution.funder,
+            total_amount: distribution.total_amount,
+            snapshot_height: distribution.snapshot_height,
+            topic: "distribute".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        Ok(id)
+    }
+
+    /// Pays the caller their share of a distribution, once.
+    #[cycles(210_00)]
+    #[write]
+    fn claim_distribution(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ClaimDistributionPayload,
+    ) -> ProtocolResult<()> {
+        let holder = ctx.get_caller();
+        if !self.distributions.contains(&payload.id)? {
+            return Err(ServiceError::NotFoundDistribution { id: payload.id }.into());
+        }
+        let mut distribution = self.distributions.get(&payload.id)?;
+        if self.distribution_claimed(&holder, payload.id)? {
+            return Err(ServiceError::AlreadyClaimed { id: payload.id }.into());
+        }
+        let amount = self.distribution_share(&distribution, &holder)?;
+        if amount == 0 {
+            return Err(ServiceError::NothingToClaim { id: payload.id }.into());
+        }
+
+        self.move_balance(
+            ctx.get_current_height(),
+            &distribution_pool()?,
+            &holder,
+            &distribution.funding_asset,
+            amount,
+        )?;
+        self.sdk
+            .set_account_value(&holder, distribution_claim_key(payload.id), true)?;
+        distribution.claimed = checked_add_u128(distribution.claimed, amount)?;
+        self.distributions.insert(payload.id, distribution)?;
+
+        let event = ClaimDistributionEvent {
+            id: payload.id,
+            holder,
+            amount,
+            topic: "claim_distribution".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_distribution(
+        &self,
+        ctx: ServiceContext,
+        payload: GetDistributionPayload,
+    ) -> ProtocolResult<Distribution> {
+        if !self.distributions.contains(&payload.id)? {
+            return Err(ServiceError::NotFoundDistribution { id: payload.id }.into());
+        }
+        self.distributions.get(&payload.id)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_distribution_claim(
+        &self,
+        ctx: ServiceContext,
+        payload: GetDistributionClaimPayload,
+    ) -> ProtocolResult<DistributionClaim> {
+        if !self.distributions.contains(&payload.id)? {
+            return Err(ServiceError::NotFoundDistribution { id: payload.id }.into());
+        }
+        let distribution = self.distributions.get(&payload.id)?;
+
+        Ok(DistributionClaim {
+            id:      payload.id,
+            amount:  self.distribution_share(&distribution, &payload.holder)?,
+            claimed: self.distribution_claimed(&payload.holder, payload.id)?,
+            holder:  payload.holder,
+        })
+    }
+
+    /// Transfers `value` to `to` held until block `unlock_height`, when the
+    /// recipient can claim it into their balance. A transfer fee is charged
+    /// as the transfer is made.
+    #[cycles(210_00)]
+    #[write]
+    fn transfer_locked_until(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<TransferLockedPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let sender = ctx.get_caller();
+        let height = ctx.get_current_height();
+
+        if !self.assets.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.asset_id,
+            }
+            .into());
+        }
+        if payload.unlock_height <= height {
+            return Err(ServiceError::UnlockHeightPassed {
+                unlock_height: payload.unlock_height,
+                height,
+            }
+            .into());
+        }
+        if payload.to == sender {
+            return Err(ServiceError::RecipientIsSender.into());
+        }
+        if self.skip_zero_value(payload.value)? {
+            return Ok(());
+        }
+        let mut locks = self.locks_of(&payload.to, &payload.asset_id)?;
+        let merged = locks
+            .locks
+            .iter()
+            .any(|lock| lock.unlock_height == payload.unlock_height);
+        if !merged && locks.locks.len() >= MAX_LOCKS {
+            return Err(ServiceError::TooManyLocks { max: MAX_LOCKS }.into());
+        }
+
+        let received = self._transfer(
+            &ctx,
+            sender.clone(),
+            payload.to.clone(),
+            payload.asset_id.clone(),
+            payload.value,
+        )?;
+        self.move_balance(
+            height,
+            &payload.to,
+            &lock_pool()?,
+            &payload.asset_id,
+            received,
+        )?;
+
+        if merged {
+            for lock in locks.locks.iter_mut() {
+                if lock.unlock_height == payload.unlock_height {
+                    lock.value = checked_add_u128(lock.value, received)?;
+                }
+            }
+        } else {
+            locks.locks.push(Lock {
+                value:         received,
+                unlock_height: payload.unlock_height,
+            });
+            locks.locks.sort_by_key(|lock| lock.unlock_height);
+        }
+        self.sdk
+            .set_account_value(&payload.to, locks_key(&payload.asset_id), locks)?;
+
+        let event = TransferLockedEvent {
+            asset_id:      payload.asset_id,
+            from:          sender,
+            to:            payload.to,
+            value:         received,
+            unlock_height: payload.unlock_height,
+            topic:         "transfer_locked".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Moves every lock of the caller on the asset that unlocked by the
+    /// current block into their balance.
+    #[cycles(210_00)]
+    #[write]
+    fn claim_unlocked(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ClaimUnlockedPayload,
+    ) -> ProtocolResult<()> {
+        let holder = ctx.get_caller();
+        let height = ctx.get_current_height();
+
+        let locks = self.locks_of(&holder, &payload.asset_id)?;
+        let (unlocked, locked): (Vec<Lock>, Vec<Lock>) = locks
+            .locks
+            .into_iter()
+            .partition(|lock| lock.unlock_height <= height);
+        if unlocked.is_empty() {
+            return Err(ServiceError::NothingUnlocked {
+                asset_id: payload.asset_id,
+            }
+            .into());
+        }
+        let value = unlocked
+            .iter()
+            .try_fold(0, |sum, lock| checked_add_u128(sum, lock.value))?;
+
+        self.move_balance(height, &lock_pool()?, &holder, &payload.asset_id, value)?;
+        self.sdk
+            .set_account_value(&holder, locks_key(&payload.asset_id), Locks {
+                locks: locked,
+            })?;
+
+        let event = ClaimUnlockedEvent {
+            asset_id: payload.asset_id,
+            holder,
+            value,
+            topic: "claim_unlocked".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// A balance split into what is spendable, unlocked but unclaimed, and
+    /// still locked as of the current block.
+    #[cycles(100_00)]
+    #[read]
+    fn get_locked_balance(
+        &self,
+        ctx: ServiceContext,
+        payload: GetLockedBalancePayload,
+    ) -> ProtocolResult<LockedBalanceResponse> {
+        let height = ctx.get_current_height();
+        let locks = self.locks_of(&payload.user, &payload.asset_id)?.locks;
+
+        let mut claimable = 0;
+        let mut locked = 0;
+        for lock in locks.iter() {
+            if lock.unlock_height <= height {
+                claimable = checked_add_u128(claimable, lock.value)?;
+            } else {
+                locked = checked_add_u128(locked, lock.value)?;
+            }
+        }
+
+        Ok(LockedBalanceResponse {
+            liquid: self.load_balance(&payload.user, &payload.asset_id)?,
+            asset_id: payload.asset_id,
+            user: payload.user,
+            claimable,
+            locked,
+            locks,
+        })
+    }
+
+    /// Reserves `amount` of the owner's balance for the calling service, out
+    /// of the owner's reach until the service releases or executes it. The
+    /// owner must be the sender of the transaction.
+    #[cycles(210_00)]
+    #[write]
+    fn hold(&mut self, ctx: ServiceContext, payload: HoldPayload) -> ProtocolResult<u64> {
+        let service = self.verify_hold_service(&ctx)?;
+        if ctx.get_caller() != payload.owner {
+            return Err(ServiceError::NoPermission.into());
+        }
+        if !self.assets.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.asset_id,
+            }
+            .into());
+        }
+        // an empty hold has nothing to release or execute, so it is refused
+        // whatever the zero value policy
+        if payload.amount == 0 {
+            return Err(ServiceError::EmptyHold.into());
+        }
+        self.check_transferable(&payload.asset_id, &payload.owner)?;
+
+        self.move_balance(
+            ctx.get_current_height(),
+            &payload.owner,
+            &hold_pool()?,
+            &payload.asset_id,
+            payload.amount,
+        )?;
+
+        let id = self.hold_count()? + 1;
+        self.sdk.set_value(HOLD_COUNT_KEY.to_owned(), id)?;
+        let hold = Hold {
+            id,
+            asset_id: payload.asset_id,
+            owner: payload.owner,
+            service,
+            amount: payload.amount,
+        };
+        self.holds.insert(id, hold.clone())?;
+
+        self.emit_hold_event(&ctx, hold, None, "hold")?;
+        Ok(id)
+    }
+
+    /// Returns held funds to their owner, for the service holding them.
+    #[cycles(210_00)]
+    #[write]
+    fn release_hold(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ReleaseHoldPayload,
+    ) -> ProtocolResult<()> {
+        let hold = self.take_hold(&ctx, payload.id)?;
+
+        self.move_balance(
+            ctx.get_current_height(),
+            &hold_pool()?,
+            &hold.owner,
+            &hold.asset_id,
+            hold.amount,
+        )?;
+
+        self.emit_hold_event(&ctx, hold, None, "release_hold")
+    }
+
+    /// Pays held funds to `to` as a transfer from their owner, for the
+    /// service holding them. A transfer fee is charged as on any transfer.
+    #[cycles(210_00)]
+    #[write]
+    fn execute_hold(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ExecuteHoldPayload,
+    ) -> ProtocolResult<()> {
+        let hold = self.take_hold(&ctx, payload.id)?;
+
+        self.move_balance(
+            ctx.get_current_height(),
+            &hold_pool()?,
+            &hold.owner,
+            &hold.asset_id,
+            hold.amount,
+        )?;
+        self._transfer(
+            &ctx,
+            hold.owner.clone(),
+            payload.to.clone(),
+            hold.asset_id.clone(),
+            hold.amount,
+        )?;
+
+        self.emit_hold_event(&ctx, hold, Some(payload.to), "execute_hold")
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_hold(&self, ctx: ServiceContext, payload: GetHoldPayload) -> ProtocolResult<Hold> {
+        if !self.holds.contains(&payload.id)? {
+            return Err(ServiceError::NotFoundHold { id: payload.id }.into());
+        }
+        self.holds.get(&payload.id)
+    }
+
+    /// Runs the pending migrations of the state, for the admin to finish a
+    /// batched one faster than the blocks do.
+    #[cycles(210_00)]
+    #[write]
+    fn migrate(&mut self, ctx: ServiceContext, payload: MigratePayload) -> ProtocolResult<()> {
+        self.verify_admin(&ctx)?;
+
+        let from_version = self.schema_version()?;
+        let (version, cursor) = self.run_migrations(payload.limit)?;
+
+        let event = MigrationEvent {
+            from_version,
+            version,
+            cursor,
+            topic: "migrate".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_migration_status(&self, ctx: ServiceContext) -> ProtocolResult<MigrationStatus> {
+        let schema_version = self.schema_version()?;
+        Ok(MigrationStatus {
+            schema_version,
+            target_version: SCHEMA_VERSION,
+            pending: migration::pending(&Self::migrations(), schema_version),
+            cursor: self.migration_cursor()?,
+        })
+    }
+
+    // Migrations left by an upgrade run in the first blocks after it. One
+    // failing must not fail the block, the admin sees its error by running
+    // it through `migrate`.
+    #[hook_before]
+    fn before_block(&mut self, params: &ExecutorParams) -> ProtocolResult<()> {
+        if self.schema_version()? < SCHEMA_VERSION {
+            let _ = self.run_migrations(MIGRATION_BATCH);
+        }
+        Ok(())
+    }
+
+    #[write]
+    fn mint_token(&mut self, ctx: ServiceContext, payload: MintTokenPayload) -> ProtocolResult<()> {
+        let minter = self.verify_minter(&ctx)?;
+
+        let token_id = payload.token_id;
+
+        if !self.assets.contains(&token_id)? {
+            return Err(ServiceError::NotFoundAsset { id: token_id }.into());
+        }
+
+        let mut asset = self.assets.get(&token_id)?;
+        asset.supply = Amount(checked_add_u128(asset.supply.0, payload.amount)?);
+        check_max_supply(&asset)?;
+
+        let receiver_balance = self.load_balance(&payload.receiver, &token_id)?;
+        let v = checked_add_u128(receiver_balance, payload.amount)?;
+
+        let height = ctx.get_current_height();
+        self.set_balance(height, &payload.receiver, &token_id, v)?;
+        self.checkpoint_supply(height, &token_id)?;
+        self.assets.insert(token_id.clone(), asset)?;
+        let mut record = issuance(
+            &token_id,
+            "mint",
+            &minter,
+            &payload.receiver,
+            payload.amount,
+            height,
+        );
+        record.source_tx = payload.source_tx;
+        self.log_issuance(record)?;
+
+        let event = MintEvent {
+            asset_id: token_id,
+            receiver: payload.receiver,
+            amount: payload.amount,
+            memo: payload.memo,
+            minter,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[write]
+    fn create_mirror_asset(
+        &mut self,
+        ctx: ServiceContext,
+        payload: CreateMirrorAssetPayload,
+    ) -> ProtocolResult<Asset> {
+        let minter = self.verify_minter(&ctx)?;
+
+        if self.assets.contains(&payload.id)? {
+            return Err(ServiceError::Exists { id: payload.id }.into());
+        }
+
+        let decimals = payload.decimals.map(|decimals| decimals.to_string());
+        if let Some(decimals) = &decimals {
+            parse_decimals(decimals)?;
+        }
+
+        let asset = Asset {
+            id:           payload.id.clone(),
+            name:         payload.name,
+            supply:       Amount(0),
+            issuer:       payload.issuer,
+            max_supply:   None,
+            transferable: true,
+        };
+        self.register_symbol(&asset)?;
+        if let Some(decimals) = decimals {
+            self.sdk.set_value(
+                asset_metadata_key(&payload.id, DECIMALS_METADATA_KEY),
+                decimals,
+            )?;
+        }
+        self.assets.insert(payload.id, asset.clone())?;
+        self.index_issued(&asset)?;
+        self.log_issuance(issuance(
+            &asset.id,
+            "create",
+            &minter,
+            &asset.issuer,
+            0,
+            ctx.get_current_height(),
+        ))?;
+
+        let event_str = serde_json::to_string(&asset).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        Ok(asset)
+    }
+
+    #[write]
+    fn burn_token(&mut self, ctx: ServiceContext, payload: BurnTokenPayload) -> ProtocolResult<()> {
+        let burner = self.verify_minter(&ctx)?;
+        if !self.assets.contains(&payload.token_id)? {
+            return Err(ServiceError::NotFoundAsset {
+                id: payload.token_id,
+            }
+            .into());
+        }
+        if self.skip_zero_value(payload.amount)? {
+            return Ok(());
+        }
+
+        self._burn(&ctx, &payload.user, &payload.token_id, payload.amount)?;
+        self.log_issuance(issuance(
+            &payload.token_id,
+            "burn",
+            &burner,
+            &payload.user,
+            payload.amount,
+            ctx.get_current_height(),
+        ))?;
+
+        let event = BurnEvent {
+            asset_id: payload.token_id,
+            user: payload.user,
+            amount: payload.amount,
+            burner,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Creates an asset held by the caller. Its id is the hash of the payload
+    /// json followed by the caller's hex, so it can be worked out before the
+    /// call and the same caller can't create the same asset twice.
+    #[cycles(210_00)]
+    #[write]
+    fn create_asset(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<CreateAssetPayload>,
+    ) -> ProtocolResult<Asset> {
+        let payload = payload.into_inner();
+        let caller = ctx.get_caller();
+        let id = asset_id(&payload, &caller)?;
+
+        if self.assets.contains(&id)? {
+            return Err(ServiceError::Exists { id }.into());
+        }
+        let asset = Asset {
+            id:           id.clone(),
+            name:         payload.name,
+            supply:       Amount(payload.supply),
+            issuer:       caller,
+            max_supply:   payload.max_supply,
+            transferable: payload.transferable,
+        };
+        check_max_supply(&asset)?;
+        self.register_symbol(&asset)?;
+        // a supply of 0 before the block creating it
+        let height = ctx.get_current_height();
+        self.checkpoint_supply(height, &id)?;
+        self.assets.insert(id, asset.clone())?;
+        self.index_issued(&asset)?;
+
+        self.set_balance(height, &asset.issuer, &asset.id, payload.supply)?;
+        self.log_issuance(issuance(
+            &asset.id,
+            "create",
+            "issuer",
+            &asset.issuer,
+            payload.supply,
+            height,
+        ))?;
+
+        let event_str = serde_json::to_string(&asset).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        Ok(asset)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn transfer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<TransferPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let sender = if let Some(addr_hex) = ctx.get_extra() {
+            Address::from_hex(&String::from_utf8(addr_hex.to_vec()).expect("extra should be hex"))?
+        } else {
+            ctx.get_caller()
+        };
+
+        let asset_id = payload.asset_id.clone();
+        let value = payload.value;
+        let to = payload.to;
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        if self.skip_zero_value(value)? {
+            return Ok(());
+        }
+
+        self._transfer(&ctx, sender.clone(), to.clone(), asset_id.clone(), value)?;
+
+        let formatted_value = self
+            .asset_decimals(&asset_id)?
+            .map(|decimals| Amount(value).to_display(decimals));
+        let event = TransferEvent {
+            asset_id,
+            from: sender,
+            to,
+            value,
+            formatted_value,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Transfers to a service and calls its `on_transfer` in one go,
+    /// returning what the service returned. Nothing is kept if the call
+    /// fails. The service gets an `OnTransferPayload` and this service's name
+    /// in extra, so it can tell a paid call from one a user made directly.
+    #[cycles(210_00)]
+    #[write]
+    fn transfer_and_call(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<TransferAndCallPayload>,
+    ) -> ProtocolResult<String> {
+        let payload = payload.into_inner();
+        let sender = ctx.get_caller();
+        let asset_id = payload.asset_id;
+        let value = payload.value;
+        // calls into these would carry this service's name to the services
+        // trusting it
+        if payload.to_service == "crosschain"
+            || payload.to_service.as_bytes() == SERVICE_NAME.as_ref()
+        {
+            return Err(ServiceError::ReservedCallTarget {
+                service: payload.to_service,
+            }
+            .into());
+        }
+        let to = service_address(&payload.to_service)?;
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+
+        // a silently skipped zero value leaves out the transfer, the call is
+        // still made, telling the service nothing was paid
+        let paid = if self.skip_zero_value(value)? {
+            0
+        } else {
+            let received =
+                self._transfer(&ctx, sender.clone(), to.clone(), asset_id.clone(), value)?;
+
+            let formatted_value = self
+                .asset_decimals(&asset_id)?
+                .map(|decimals| Amount(value).to_display(decimals));
+            let event = TransferEvent {
+                asset_id: asset_id.clone(),
+                from: sender.clone(),
+                to,
+                value,
+                formatted_value,
+            };
+            let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+            ctx.emit_event(event_str)?;
+            received
+        };
+
+        let call_payload = OnTransferPayload {
+            asset_id,
+            sender,
+            value: paid,
+            payload: payload.payload,
+        };
+        let payload_string =
+            serde_json::to_string(&call_payload).map_err(ServiceError::JsonParse)?;
+        self.sdk.write(
+            &ctx,
+            Some(SERVICE_NAME.clone()),
+            &payload.to_service,
+            "on_transfer",
+            &payload_string,
+        )
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn approve(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<ApprovePayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let caller = ctx.get_caller();
+        let asset_id = payload.asset_id.clone();
+        let value = payload.value;
+        let to = payload.to;
+
+        if caller == to {
+            return Err(ServiceError::ApproveToYourself.into());
+        }
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        // approving 0 over an allowance revokes it, which is always allowed
+        if value == 0
+            && self.allowance_of(&caller, &asset_id, &to)? == 0
+            && self.skip_zero_value(value)?
+        {
+            return Ok(());
+        }
+        if value > 0 {
+            self.check_transferable(&asset_id, &caller)?;
+        }
+
+        self.set_allowance(&ctx, &caller, &asset_id, &to, value, "approve")?;
+
+        let event = ApproveEvent {
+            asset_id,
+            grantor: caller,
+            grantee: to,
+            value,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn revoke_all_approvals(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<RevokeAllApprovalsPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let caller = ctx.get_caller();
+        let asset_id = payload.asset_id;
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+
+        let grantees = self.grantees_of(&caller, &asset_id)?;
+        for grantee in grantees.iter() {
+            self.set_allowance(&ctx, &caller, &asset_id, grantee, 0, "revoke")?;
+        }
+
+        let event = RevokeAllApprovalsEvent {
+            asset_id,
+            grantor: caller,
+            grantees,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn transfer_from(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<TransferFromPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let caller = if let Some(addr_hex) = ctx.get_extra() {
+            Address::from_hex(&String::from_utf8(addr_hex.to_vec()).expect("extra should be hex"))?
+        } else {
+            ctx.get_caller()
+        };
+        let sender = payload.sender;
+        let recipient = payload.recipient;
+        let asset_id = payload.asset_id;
+        let value = payload.value;
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        if self.skip_zero_value(value)? {
+            return Ok(());
+        }
+
+        // Only read until the transfer is known to go through, a failed
+        // attempt must not leave anything behind.
+        let sender_allowance = self.allowance_of(&sender, &asset_id, &caller)?;
+        if sender_allowance < value {
+            return Err(ServiceError::LackOfBalance {
+                expect: value,
+                real:   sender_allowance,
+            }
+            .into());
+        }
+        let remaining_allowance = if sender_allowance == UNLIMITED_ALLOWANCE {
+            sender_allowance
+        } else {
+            checked_sub_u128(sender_allowance, value)?
+        };
+
+        self._transfer(
+            &ctx,
+            sender.clone(),
+            recipient.clone(),
+            asset_id.clone(),
+            value,
+        )?;
+        self.set_allowance(
+            &ctx,
+            &sender,
+            &asset_id,
+            &caller,
+            remaining_allowance,
+            "transfer_from",
+        )?;
+
+        let event = TransferFromEvent {
+            asset_id,
+            caller,
+            sender,
+            recipient,
+            value,
+            remaining_allowance,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Burns from an account out of the allowance it granted the caller, as
+    /// `transfer_from` spends it.
+    #[cycles(210_00)]
+    #[write]
+    fn burn_from(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<BurnFromPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let caller = if let Some(addr_hex) = ctx.get_extra() {
+            let addr_hex =
+                String::from_utf8(addr_hex.to_vec()).map_err(|_| ServiceError::NoPermission)?;
+            Address::from_hex(&addr_hex)?
+        } else {
+            ctx.get_caller()
+        };
+        let owner = payload.owner;
+        let asset_id = payload.asset_id;
+        let amount = payload.amount;
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        if self.skip_zero_value(amount)? {
+            return Ok(());
+        }
+
+        let owner_allowance = self.allowance_of(&owner, &asset_id, &caller)?;
+        if owner_allowance < amount {
+            return Err(ServiceError::LackOfBalance {
+                expect: amount,
+                real:   owner_allowance,
+            }
+            .into());
+        }
+        let remaining_allowance = if owner_allowance == UNLIMITED_ALLOWANCE {
+            owner_allowance
+        } else {
+            checked_sub_u128(owner_allowance, amount)?
+        };
+
+        self._burn(&ctx, &owner, &asset_id, amount)?;
+        self.log_issuance(issuance(
+            &asset_id,
+            "burn",
+            "burn_from",
+            &owner,
+            amount,
+            ctx.get_current_height(),
+        ))?;
+        self.set_allowance(
+            &ctx,
+            &owner,
+            &asset_id,
+            &caller,
+            remaining_allowance,
+            "burn_from",
+        )?;
+
+        let event = BurnFromEvent {
+            asset_id,
+            caller,
+            owner,
+            amount,
+            remaining_allowance,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn authorize_session_key(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<AuthorizeSessionKeyPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let caller = ctx.get_caller();
+        let asset_id = payload.asset_id;
+
+        if caller == payload.session_key {
+            return Err(ServiceError::ApproveToYourself.into());
+        }
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        self.check_transferable(&asset_id, &caller)?;
+        if payload.expires_at <= ctx.get_current_height() {
+            return Err(ServiceError::SessionKeyExpired {
+                expires_at: payload.expires_at,
+            }
+            .into());
+        }
+
+        let session = SessionKey {
+            budget:     payload.budget,
+            expires_at: payload.expires_at,
+        };
+        self.write_session_key(&ctx, &caller, &asset_id, &payload.session_key, session)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn revoke_session_key(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<RevokeSessionKeyPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let caller = ctx.get_caller();
+
+        if self
+            .session_key_of(&caller, &payload.asset_id, &payload.session_key)?
+            .is_none()
+        {
+            return Err(ServiceError::NoSessionKey {
+                session_key: payload.session_key,
+            }
+            .into());
+        }
+
+        let session = SessionKey {
+            budget:     0,
+            expires_at: 0,
+        };
+        self.write_session_key(
+            &ctx,
+            &caller,
+            &payload.asset_id,
+            &payload.session_key,
+            session,
+        )
+    }
+
+    /// Transfers from the authorizer of the calling session key, within the
+    /// key's budget and before it expires.
+    #[cycles(210_00)]
+    #[write]
+    fn session_transfer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<SessionTransferPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let session_key = ctx.get_caller();
+        let asset_id = payload.asset_id;
+        let value = payload.value;
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        let mut session = match self.session_key_of(&payload.authorizer, &asset_id, &session_key)? {
+            Some(session) => session,
+            None => return Err(ServiceError::NoSessionKey { session_key }.into()),
+        };
+        if session.expires_at <= ctx.get_current_height() {
+            return Err(ServiceError::SessionKeyExpired {
+                expires_at: session.expires_at,
+            }
+            .into());
+        }
+        if session.budget < value {
+            return Err(ServiceError::LackOfBalance {
+                expect: value,
+                real:   session.budget,
+            }
+            .into());
+        }
+        if self.skip_zero_value(value)? {
+            return Ok(());
+        }
+
+        self._transfer(
+            &ctx,
+            payload.authorizer.clone(),
+            payload.to.clone(),
+            asset_id.clone(),
+            value,
+        )?;
+        session.budget -= value;
+        self.sdk.set_account_value(
+            &payload.authorizer,
+            session_key_key(&asset_id, &session_key),
+            session.clone(),
+        )?;
+
+        let event = SessionTransferEvent {
+            asset_id,
+            session_key,
+            authorizer: payload.authorizer,
+            to: payload.to,
+            value,
+            remaining_budget: session.budget,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Transfers as `from` signed off chain, paying the caller the signed
+    /// fee out of the same asset. Each meta nonce of `from` is used once, in
+    /// order, so a signed transfer can't be sent twice. Refused while the
+    /// service is unbound, as the signature would hold on any unbound chain.
+    #[cycles(210_00)]
+    #[write]
+    fn transfer_by_signature(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<MetaTxPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let relayer = ctx.get_caller();
+        let asset_id = payload.asset_id.clone();
+        let from = payload.from.clone();
+
+        if !self.assets.contains(&asset_id)? {
+            return Err(ServiceError::NotFoundAsset { id: asset_id }.into());
+        }
+        if payload.deadline < ctx.get_current_height() {
+            return Err(ServiceError::MetaTxExpired {
+                deadline: payload.deadline,
+            }
+            .into());
+        }
+        let chain_id = match self.chain_binding()? {
+            Some(binding) => binding.dest_chain_id,
+            None => return Err(ServiceError::ChainNotBound.into()),
+        };
+        if !verify_meta_tx(&payload, &chain_id, &ctx.get_service_name()) {
+            return Err(ServiceError::InvalidSignature.into());
+        }
+        let nonce = self.meta_nonce(&from)?;
+        if payload.nonce != nonce {
+            return Err(ServiceError::InvalidMetaNonce {
+                expect: nonce,
+                real:   payload.nonce,
+            }
+            .into());
+        }
+        // A rejected zero value fails here, a silent one skips only the
+        // transfer leg, as the nonce is used and the fee owed either way.
+        let skip_transfer = self.skip_zero_value(payload.value)?;
+
+        // Both transfers are checked against the balance before either is
+        // made, so the value never goes through without the fee.
+        let total = checked_add_u128(payload.value, payload.fee)?;
+        let balance = self.load_balance(&from, &asset_id)?;
+        if balance < total {
+            return Err(ServiceError::LackOfBalance {
+                expect: total,
+                real:   balance,
+            }
+            .into());
+        }
+
+        self.sdk
+            .set_account_value(&from, META_NONCE_KEY.to_owned(), nonce + 1)?;
+        if !skip_transfer {
+            self._transfer(
+                &ctx,
+                from.clone(),
+                payload.to.clone(),
+                asset_id.clone(),
+                payload.value,
+            )?;
+        }
+        if payload.fee > 0 && relayer != from {
+            self._transfer(
+                &ctx,
+                from.clone(),
+                relayer.clone(),
+                asset_id.clone(),
+                payload.fee,
+            )?;
+        }
+        if skip_transfer && payload.fee == 0 {
+            return Ok(());
+        }
+
+        let event = MetaTransferEvent {
+            asset_id,
+            from,
+            to: payload.to,
+            value: payload.value,
+            relayer,
+            fee: payload.fee,
+            nonce,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    // Returns what the recipient received, the value less any fee.
+    fn _transfer(
+        &mut self,
+        ctx: &ServiceContext,
+        sender: Address,
+        recipient: Address,
+        asset_id: Hash,
+        value: u128,
+    ) -> ProtocolResult<u128> {
+        if sender == recipient && !self.self_transfer_noop()? {
+            return Err(ServiceError::RecipientIsSender.into());
+        }
+        self.check_transferable(&asset_id, &sender)?;
+
+        let sender_balance = self.load_balance(&sender, &asset_id)?;
+
         if sender_balance < value {
             return Err(ServiceError::LackOfBalance {
                 expect: value,
-                real:   sender_balance,
+                real:   sender_balance,
+            }
+            .into());
+        }
+
+        // Balance is checked above so a self-transfer never succeeds beyond what
+        // the sender actually holds, but nothing needs to be written.
+        if sender == recipient {
+            return Ok(value);
+        }
+
+        let fee = self.transfer_fee_of(&asset_id, &sender, &recipient, value)?;
+        let received = checked_sub_u128(value, fee.as_ref().map_or(0, |(_, fee)| *fee))?;
+
+        let height = ctx.get_current_height();
+        let to_balance = self.load_balance(&recipient, &asset_id)?;
+
+        let v = checked_add_u128(to_balance, received)?;
+        self.set_balance(height, &recipient, &asset_id, v)?;
+
+        let v = checked_sub_u128(sender_balance, value)?;
+        self.set_balance(height, &sender, &asset_id, v)?;
+
+        if let Some((collector, fee)) = fee {
+            let collector_balance = self.load_balance(&collector, &asset_id)?;
+            let v = checked_add_u128(collector_balance, fee)?;
+            self.set_balance(height, &collector, &asset_id, v)?;
+
+            let event = TransferFeeChargedEvent {
+                asset_id,
+                from: sender,
+                to: recipient,
+                collector,
+                fee,
+                topic: "transfer_fee".to_owned(),
+            };
+            let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+            ctx.emit_event(event_str)?;
+        }
+
+        Ok(received)
+    }
+
+    // Soulbound assets only move from their issuer.
+    fn check_transferable(&self, asset_id: &Hash, owner: &Address) -> ProtocolResult<()> {
+        let asset = self.assets.get(asset_id)?;
+        if !asset.transferable && asset.issuer != *owner {
+            return Err(ServiceError::NonTransferable {
+                id: asset_id.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// The collector of the asset's transfer fee and what a transfer of
+    /// `value` pays it, `None` when the transfer pays nothing.
+    fn transfer_fee_of(
+        &self,
+        asset_id: &Hash,
+        sender: &Address,
+        recipient: &Address,
+        value: u128,
+    ) -> ProtocolResult<Option<(Address, u128)>> {
+        if !self.fees.contains(asset_id)? {
+            return Ok(None);
+        }
+        let fee = self.fees.get(asset_id)?;
+        let exempt =
+            |addr: &Address| *addr == fee.collector || fee.exempt.binary_search(addr).is_ok();
+        if exempt(sender) || exempt(recipient) {
+            return Ok(None);
+        }
+
+        // split so that `value * rate` can't overflow
+        let rate = u128::from(fee.rate_bps);
+        let bps = u128::from(WHOLE_BPS);
+        let amount = value / bps * rate + value % bps * rate / bps;
+        if amount == 0 {
+            return Ok(None);
+        }
+        Ok(Some((fee.collector, amount)))
+    }
+
+    /// Migrations of the stored state, by the schema version they lead to.
+    fn migrations() -> Vec<Migration<Self>> {
+        vec![
+            // allowances kept in the balance entry move to an entry of their
+            // own when the balance is next written
+            Migration {
+                version: 1,
+                name:    "allowance_entries",
+                step:    Step::Lazy,
+            },
+        ]
+    }
+
+    // State from before schema versions were kept is at 0.
+    fn schema_version(&self) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_value(&SCHEMA_VERSION_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    fn migration_cursor(&self) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_value(&MIGRATION_CURSOR_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    fn run_migrations(&mut self, limit: u64) -> ProtocolResult<(u64, u64)> {
+        let (version, cursor) = (self.schema_version()?, self.migration_cursor()?);
+        let (version, cursor) = migration::run(self, &Self::migrations(), version, cursor, limit)?;
+        self.sdk.set_value(SCHEMA_VERSION_KEY.to_owned(), version)?;
+        self.sdk
+            .set_value(MIGRATION_CURSOR_KEY.to_owned(), cursor)?;
+        Ok((version, cursor))
+    }
+
+    fn load_balance(&self, user: &Address, asset_id: &Hash) -> ProtocolResult<u128> {
+        let key = (user.clone(), asset_id.clone());
+        if let Some(value) = self.balances.borrow().get(&key) {
+            return Ok(*value);
+        }
+
+        let asset_balance: Option<AssetBalance> = self.sdk.get_account_value(user, asset_id)?;
+        let value = asset_balance.map(|b| b.value.0).unwrap_or(0);
+        self.balances.borrow_mut().insert(key, value);
+        Ok(value)
+    }
+
+    // Appends a change of supply to the log of its asset, numbered after the
+    // last and with the supply it left.
+    fn log_issuance(&mut self, mut record: IssuanceRecord) -> ProtocolResult<()> {
+        let asset_id = record.asset_id.clone();
+        let seq = self.issuance_len(&asset_id)? + 1;
+        record.seq = seq;
+        record.supply = self.assets.get(&asset_id)?.supply.0;
+
+        self.sdk.set_value(issuance_key(&asset_id, seq), record)?;
+        self.sdk.set_value(issuance_key(&asset_id, 0), seq)
+    }
+
+    fn issuance_len(&self, asset_id: &Hash) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_value(&issuance_key(asset_id, 0))?
+            .unwrap_or_default())
+    }
+
+    // Takes `amount` out of `user`'s balance and the supply of the asset.
+    fn _burn(
+        &mut self,
+        ctx: &ServiceContext,
+        user: &Address,
+        asset_id: &Hash,
+        amount: u128,
+    ) -> ProtocolResult<()> {
+        let user_balance = self.load_balance(user, asset_id)?;
+        if user_balance < amount {
+            return Err(ServiceError::LackOfBalance {
+                expect: amount,
+                real:   user_balance,
+            }
+            .into());
+        }
+
+        let mut asset = self.assets.get(asset_id)?;
+        asset.supply = Amount(checked_sub_u128(asset.supply.0, amount)?);
+
+        let v = checked_sub_u128(user_balance, amount)?;
+        let height = ctx.get_current_height();
+        self.set_balance(height, user, asset_id, v)?;
+        self.checkpoint_supply(height, asset_id)?;
+        self.assets.insert(asset_id.clone(), asset)
+    }
+
+    /// Decimals set in the metadata of the asset, `None` when it has none.
+    fn asset_decimals(&self, asset_id: &Hash) -> ProtocolResult<Option<u32>> {
+        let decimals: Option<String> = self
+            .sdk
+            .get_value(&asset_metadata_key(asset_id, DECIMALS_METADATA_KEY))?;
+        Ok(decimals.and_then(|decimals| parse_decimals(&decimals).ok()))
+    }
+
+    /// Writes a balance in block `height`, keeping the value it replaces for
+    /// snapshots.
+    fn set_balance(
+        &mut self,
+        height: u64,
+        user: &Address,
+        asset_id: &Hash,
+        value: u128,
+    ) -> ProtocolResult<()> {
+        let previous = self.load_balance(user, asset_id)?;
+        if previous != value {
+            self.checkpoint_balance(height, user, asset_id, previous)?;
+        }
+        // Writing the new layout would drop allowances still kept in a legacy
+        // balance, so move them out first.
+        self.migrate_legacy_allowances(user, asset_id)?;
+
+        self.sdk
+            .set_account_value(user, asset_id.clone(), AssetBalance {
+                value: Amount(value),
+            })?;
+        self.balances
+            .borrow_mut()
+            .insert((user.clone(), asset_id.clone()), value);
+        Ok(())
+    }
+
+    // The first write of a balance after the latest snapshot keeps the value
+    // it replaces.
+    fn checkpoint_balance(
+        &mut self,
+        height: u64,
+        user: &Address,
+        asset_id: &Hash,
+        previous: u128,
+    ) -> ProtocolResult<()> {
+        let latest = match self.latest_snapshot(asset_id)? {
+            Some(latest) if height > latest => latest,
+            _ => return Ok(()),
+        };
+        let len_key = snapshot::balance_len_key(asset_id);
+        let len: u64 = self.sdk.get_account_value(user, &len_key)?.unwrap_or(0);
+        if len > 0 {
+            let last: Option<Checkpoint> = self
+                .sdk
+                .get_account_value(user, &snapshot::balance_key(asset_id, len - 1))?;
+            if last.map_or(false, |last| last.height > latest) {
+                return Ok(());
+            }
+        }
+
+        self.sdk
+            .set_account_value(user, snapshot::balance_key(asset_id, len), Checkpoint {
+                height,
+                value: previous,
+            })?;
+        self.sdk.set_account_value(user, len_key, len + 1)
+    }
+
+    // Like `checkpoint_balance`, with the supply stored before the write.
+    fn checkpoint_supply(&mut self, height: u64, asset_id: &Hash) -> ProtocolResult<()> {
+        let previous = if self.assets.contains(asset_id)? {
+            self.assets.get(asset_id)?.supply.0
+        } else {
+            0
+        };
+        let latest = match self.latest_snapshot(asset_id)? {
+            Some(latest) if height > latest => latest,
+            _ => return Ok(()),
+        };
+        let len_key = snapshot::supply_len_key(asset_id);
+        let len: u64 = self.sdk.get_value(&len_key)?.unwrap_or(0);
+        if len > 0 {
+            let last: Option<Checkpoint> = self
+                .sdk
+                .get_value(&snapshot::supply_key(asset_id, len - 1))?;
+            if last.map_or(false, |last| last.height > latest) {
+                return Ok(());
+            }
+        }
+
+        self.sdk
+            .set_value(snapshot::supply_key(asset_id, len), Checkpoint {
+                height,
+                value: previous,
+            })?;
+        self.sdk.set_value(len_key, len + 1)
+    }
+
+    fn latest_snapshot(&self, asset_id: &Hash) -> ProtocolResult<Option<u64>> {
+        self.sdk.get_value(&snapshot::latest_snapshot_key(asset_id))
+    }
+
+    // Balances are only known at the heights snapshots were taken at.
+    fn check_snapshot(&self, asset_id: &Hash, height: u64) -> ProtocolResult<()> {
+        let taken: Option<bool> = self
+            .sdk
+            .get_value(&snapshot::snapshot_key(asset_id, height))?;
+        if !taken.unwrap_or(false) {
+            return Err(ServiceError::SnapshotNotTaken { height }.into());
+        }
+        Ok(())
+    }
+
+    fn balance_at(&self, user: &Address, asset_id: &Hash, height: u64) -> ProtocolResult<u128> {
+        let len: u64 = self
+            .sdk
+            .get_account_value(user, &snapshot::balance_len_key(asset_id))?
+            .unwrap_or(0);
+        let current = self.load_balance(user, asset_id)?;
+        snapshot::value_at(len, height, current, |index| {
+            let checkpoint: Option<Checkpoint> = self
+                .sdk
+                .get_account_value(user, &snapshot::balance_key(asset_id, index))?;
+            Ok(checkpoint.expect("checkpoints below the count should exist"))
+        })
+    }
+
+    fn supply_at(&self, asset_id: &Hash, height: u64) -> ProtocolResult<u128> {
+        let len: u64 = self
+            .sdk
+            .get_value(&snapshot::supply_len_key(asset_id))?
+            .unwrap_or(0);
+        let current = self.assets.get(asset_id)?.supply.0;
+        snapshot::value_at(len, height, current, |index| {
+            let checkpoint: Option<Checkpoint> =
+                self.sdk.get_value(&snapshot::supply_key(asset_id, index))?;
+            Ok(checkpoint.expect("checkpoints below the count should exist"))
+        })
+    }
+
+    // The supply at a height less what the pools held then, which no holder
+    // claims a share of.
+    fn held_supply_at(&self, asset_id: &Hash, height: u64) -> ProtocolResult<u128> {
+        let mut supply = self.supply_at(asset_id, height)?;
+        for pool in [distribution_pool()?, lock_pool()?, hold_pool()?].iter() {
+            supply = checked_sub_u128(supply, self.balance_at(pool, asset_id, height)?)?;
+        }
+        Ok(supply)
+    }
+
+    // Moves a balance without the checks and fee of a transfer, for the
+    // distribution pool.
+    fn move_balance(
+        &mut self,
+        height: u64,
+        from: &Address,
+        to: &Address,
+        asset_id: &Hash,
+        value: u128,
+    ) -> ProtocolResult<()> {
+        let from_balance = self.load_balance(from, asset_id)?;
+        if from_balance < value {
+            return Err(ServiceError::LackOfBalance {
+                expect: value,
+                real:   from_balance,
+            }
+            .into());
+        }
+        let v = checked_sub_u128(from_balance, value)?;
+        self.set_balance(height, from, asset_id, v)?;
+
+        let to_balance = self.load_balance(to, asset_id)?;
+        let v = checked_add_u128(to_balance, value)?;
+        self.set_balance(height, to, asset_id, v)
+    }
+
+    // A holder's share of a distribution, by their balance at the snapshot.
+    fn distribution_share(
+        &self,
+        distribution: &Distribution,
+        holder: &Address,
+    ) -> ProtocolResult<u128> {
+        let balance =
+            self.balance_at(holder, &distribution.asset_id, distribution.snapshot_height)?;
+        mul_div(distribution.total_amount, balance, distribution.supply)
+            .ok_or_else(|| ServiceError::U128Overflow.into())
+    }
+
+    fn distribution_claimed(&self, holder: &Address, id: u64) -> ProtocolResult<bool> {
+        let claimed: Option<bool> = self
+            .sdk
+            .get_account_value(holder, &distribution_claim_key(id))?;
+        Ok(claimed.unwrap_or(false))
+    }
+
+    fn locks_of(&self, user: &Address, asset_id: &Hash) -> ProtocolResult<Locks> {
+        let locks: Option<Locks> = self.sdk.get_account_value(user, &locks_key(asset_id))?;
+        Ok(locks.unwrap_or_default())
+    }
+
+    fn allowance_of(
+        &self,
+        grantor: &Address,
+        asset_id: &Hash,
+        grantee: &Address,
+    ) -> ProtocolResult<u128> {
+        let allowance: Option<Allowance> = self
+            .sdk
+            .get_account_value(grantor, &allowance_key(asset_id, grantee))?;
+        if let Some(allowance) = allowance {
+            return Ok(allowance.value);
+        }
+
+        let legacy = self.legacy_allowances(grantor, asset_id)?;
+        Ok(*legacy.get(grantee).unwrap_or(&0))
+    }
+
+    /// Writes an allowance, reporting the change by `reason`, the method
+    /// making it. The value already held is neither written nor reported, so
+    /// no entry is created for an allowance that stays zero.
+    fn set_allowance(
+        &mut self,
+        ctx: &ServiceContext,
+        grantor: &Address,
+        asset_id: &Hash,
+        grantee: &Address,
+        value: u128,
+        reason: &str,
+    ) -> ProtocolResult<()> {
+        let previous = self.allowance_of(grantor, asset_id, grantee)?;
+        if previous == value {
+            return Ok(());
+        }
+        self.migrate_legacy_allowances(grantor, asset_id)?;
+        self.write_allowance(grantor, asset_id, grantee, value)?;
+
+        let event = AllowanceChangedEvent {
+            asset_id: asset_id.clone(),
+            grantor: grantor.clone(),
+            grantee: grantee.clone(),
+            previous,
+            value,
+            reason: reason.to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Points the symbol of the asset at it. Only the first asset of a
+    /// symbol resolves, later ones are rejected when symbols are unique.
+    fn register_symbol(&mut self, asset: &Asset) -> ProtocolResult<()> {
+        let key = symbol_key(&asset.name);
+        if !self.symbols.contains(&key)? {
+            return self.symbols.insert(key, asset.id.clone());
+        }
+        if self.unique_symbols()? {
+            return Err(ServiceError::SymbolTaken {
+                symbol: asset.name.clone(),
+                id:     self.symbols.get(&key)?,
             }
             .into());
         }
+        Ok(())
+    }
 
-        let mut to_asset_balance: AssetBalance = self
+    // A revoked key, with nothing left to spend, counts as none.
+    fn session_key_of(
+        &self,
+        authorizer: &Address,
+        asset_id: &Hash,
+        session_key: &Address,
+    ) -> ProtocolResult<Option<SessionKey>> {
+        let session: Option<SessionKey> = self
             .sdk
-            .get_account_value(&recipient, &asset_id)?
-            .unwrap_or(AssetBalance {
-                value:     0,
-                allowance: BTreeMap::new(),
-            });
+            .get_account_value(authorizer, &session_key_key(asset_id, session_key))?;
+        Ok(session.filter(|s| s.budget > 0))
+    }
 
-        let (v, overflow) = to_asset_balance.value.overflowing_add(value);
-        if overflow {
-            return Err(ServiceError::U128Overflow.into());
+    fn write_session_key(
+        &mut self,
+        ctx: &ServiceContext,
+        authorizer: &Address,
+        asset_id: &Hash,
+        session_key: &Address,
+        session: SessionKey,
+    ) -> ProtocolResult<()> {
+        self.sdk.set_account_value(
+            authorizer,
+            session_key_key(asset_id, session_key),
+            session.clone(),
+        )?;
+
+        let event = SessionKeyEvent {
+            asset_id:    asset_id.clone(),
+            authorizer:  authorizer.clone(),
+            session_key: session_key.clone(),
+            budget:      session.budget,
+            expires_at:  session.expires_at,
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn issued_by(&self, issuer: &Address) -> ProtocolResult<Vec<Hash>> {
+        if !self.issued.contains(issuer)? {
+            return Ok(vec![]);
+        }
+        Ok(self.issued.get(issuer)?.asset_ids)
+    }
+
+    fn index_issued(&mut self, asset: &Asset) -> ProtocolResult<()> {
+        let mut asset_ids = self.issued_by(&asset.issuer)?;
+        asset_ids.push(asset.id.clone());
+        self.issued
+            .insert(asset.issuer.clone(), IssuedAssets { asset_ids })
+    }
+
+    /// Grantees holding an allowance from the grantor, ordered by address.
+    fn grantees_of(&self, grantor: &Address, asset_id: &Hash) -> ProtocolResult<Vec<Address>> {
+        let index: AllowanceIndex = self
+            .sdk
+            .get_account_value(grantor, &allowance_index_key(asset_id))?
+            .unwrap_or(AllowanceIndex { grantees: vec![] });
+        let mut grantees = index.grantees;
+
+        for grantee in self
+            .legacy_allowances(grantor, asset_id)?
+            .into_iter()
+            .map(|(g, _)| g)
+        {
+            if let Err(pos) = grantees.binary_search(&grantee) {
+                grantees.insert(pos, grantee);
+            }
+        }
+
+        Ok(grantees)
+    }
+
+    fn write_allowance(
+        &mut self,
+        grantor: &Address,
+        asset_id: &Hash,
+        grantee: &Address,
+        value: u128,
+    ) -> ProtocolResult<()> {
+        self.sdk
+            .set_account_value(grantor, allowance_key(asset_id, grantee), Allowance {
+                value,
+            })?;
+
+        let mut index: AllowanceIndex = self
+            .sdk
+            .get_account_value(grantor, &allowance_index_key(asset_id))?
+            .unwrap_or(AllowanceIndex { grantees: vec![] });
+        match (index.grantees.binary_search(grantee), value) {
+            (Ok(pos), 0) => {
+                index.grantees.remove(pos);
+            }
+            (Err(pos), v) if v > 0 => index.grantees.insert(pos, grantee.clone()),
+            _ => return Ok(()),
         }
-        to_asset_balance.value = v;
 
         self.sdk
-            .set_account_value(&recipient, asset_id.clone(), to_asset_balance)?;
+            .set_account_value(grantor, allowance_index_key(asset_id), index)
+    }
+
+    fn legacy_allowances(
+        &self,
+        user: &Address,
+        asset_id: &Hash,
+    ) -> ProtocolResult<BTreeMap<Address, u128>> {
+        let legacy: Option<LegacyAssetBalance> = self.sdk.get_account_value(user, asset_id)?;
+        Ok(legacy.map(|b| b.allowance).unwrap_or_default())
+    }
+
+    /// Balances used to carry every allowance of the account in the same
+    /// value. Those are moved to their own entries the first time the balance
+    /// or an allowance of the account is written, which leaves the balance in
+    /// the new layout.
+    fn migrate_legacy_allowances(&mut self, user: &Address, asset_id: &Hash) -> ProtocolResult<()> {
+        let legacy: Option<LegacyAssetBalance> = self.sdk.get_account_value(user, asset_id)?;
+        let legacy = match legacy {
+            Some(legacy) if !legacy.allowance.is_empty() => legacy,
+            _ => return Ok(()),
+        };
 
-        let (v, overflow) = sender_balance.overflowing_sub(value);
-        if overflow {
-            return Err(ServiceError::U128Overflow.into());
+        for (grantee, value) in legacy.allowance.iter() {
+            self.write_allowance(user, asset_id, grantee, *value)?;
         }
-        sender_asset_balance.value = v;
+
         self.sdk
-            .set_account_value(&sender, asset_id, sender_asset_balance)?;
+            .set_account_value(user, asset_id.clone(), AssetBalance {
+                value: Amount(legacy.value),
+            })
+    }
+
+    fn minters(&self) -> ProtocolResult<Minters> {
+        Ok(Minters {
+            services: self.params()?.minters,
+        })
+    }
+
+    /// The caller must hold the admin role on the asset service in the rbac
+    /// service when the asset defers to it, else be the admin.
+    fn verify_admin(&self, ctx: &ServiceContext) -> ProtocolResult<()> {
+        let is_admin = if self.rbac_enabled()? {
+            let payload = HasRolePayload {
+                service: "asset".to_owned(),
+                role:    "admin".to_owned(),
+                address: ctx.get_caller(),
+            };
+            let payload_str = serde_json::to_string(&payload).map_err(ServiceError::JsonParse)?;
+            let resp_str = self.sdk.read(ctx, None, "rbac", "has_role", &payload_str)?;
+            let resp: HasRoleResponse =
+                serde_json::from_str(&resp_str).map_err(ServiceError::JsonParse)?;
+            resp.has_role
+        } else {
+            let admin: Address = self
+                .sdk
+                .get_value(&ADMIN_KEY.to_owned())?
+                .expect("Admin should not be none");
+            ctx.get_caller() == admin
+        };
 
+        if !is_admin {
+            return Err(ServiceError::NoPermission.into());
+        }
         Ok(())
     }
+
+    fn params(&self) -> ProtocolResult<Params> {
+        params::load(&self.sdk)
+    }
+
+    fn rbac_enabled(&self) -> ProtocolResult<bool> {
+        let enabled: Option<bool> = self.sdk.get_value(&RBAC_KEY.to_owned())?;
+        Ok(enabled.unwrap_or(false))
+    }
+
+    // The name is the one the calling service puts in the extra of
+    // `sdk.write`. Muta doesn't tell a service which service called it, so
+    // nothing checks the name: any deployed service can claim to be a
+    // minter. The list only narrows the names honoured, every service of the
+    // chain is trusted not to claim another's. Returns the claimed name.
+    fn verify_minter(&self, ctx: &ServiceContext) -> ProtocolResult<String> {
+        let extra = ctx.get_extra().ok_or(ServiceError::NoPermission)?;
+        let service = String::from_utf8(extra.to_vec()).map_err(|_| ServiceError::NoPermission)?;
+
+        if !self.minters()?.services.contains(&service) {
+            return Err(ServiceError::NotMinter { service }.into());
+        }
+        Ok(service)
+    }
+
+    // The name is claimed by the caller, as in `verify_minter`.
+    fn verify_hold_service(&self, ctx: &ServiceContext) -> ProtocolResult<String> {
+        let extra = ctx.get_extra().ok_or(ServiceError::NoPermission)?;
+        let service = String::from_utf8(extra.to_vec()).map_err(|_| ServiceError::NoPermission)?;
+
+        if !self.hold_services()?.services.contains(&service) {
+            return Err(ServiceError::NotHoldService { service }.into());
+        }
+        Ok(service)
+    }
+
+    fn hold_services(&self) -> ProtocolResult<HoldServices> {
+        Ok(HoldServices {
+            services: self.params()?.hold_services,
+        })
+    }
+
+    // Removes an open hold for the service that placed it to settle.
+    fn take_hold(&mut self, ctx: &ServiceContext, id: u64) -> ProtocolResult<Hold> {
+        let service = self.verify_hold_service(ctx)?;
+        if !self.holds.contains(&id)? {
+            return Err(ServiceError::NotFoundHold { id }.into());
+        }
+        let hold = self.holds.get(&id)?;
+        if hold.service != service {
+            return Err(ServiceError::NotHoldService { service }.into());
+        }
+        self.holds.remove(&id)?;
+        Ok(hold)
+    }
+
+    fn emit_hold_event(
+        &self,
+        ctx: &ServiceContext,
+        hold: Hold,
+        to: Option<Address>,
+        topic: &str,
+    ) -> ProtocolResult<()> {
+        let event = HoldEvent {
+            id: hold.id,
+            asset_id: hold.asset_id,
+            owner: hold.owner,
+            service: hold.service,
+            amount: hold.amount,
+            to,
+            topic: topic.to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn hold_count(&self) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_value(&HOLD_COUNT_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    fn distribution_count(&self) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_value(&DISTRIBUTION_COUNT_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    fn self_transfer_noop(&self) -> ProtocolResult<bool> {
+        let noop: Option<bool> = self.sdk.get_value(&SELF_TRANSFER_NOOP_KEY.to_owned())?;
+        Ok(noop.unwrap_or(false))
+    }
+
+    fn chain_binding(&self) -> ProtocolResult<Option<ChainBinding>> {
+        self.sdk.get_value(&CHAIN_BINDING_KEY.to_owned())
+    }
+
+    fn meta_nonce(&self, user: &Address) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_account_value(user, &META_NONCE_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    fn zero_value_policy(&self) -> ProtocolResult<ZeroValuePolicy> {
+        Ok(self
+            .sdk
+            .get_value(&ZERO_VALUE_POLICY_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    // Whether a call moving `value` should return early without doing
+    // anything, failing it instead when zero values are rejected.
+    fn skip_zero_value(&self, value: u128) -> ProtocolResult<bool> {
+        if value > 0 {
+            return Ok(false);
+        }
+        match self.zero_value_policy()? {
+            ZeroValuePolicy::Reject => Err(ServiceError::ZeroValue.into()),
+            ZeroValuePolicy::AllowSilent => Ok(true),
+            ZeroValuePolicy::AllowWithEvent => Ok(false),
+        }
+    }
+
+    fn unique_symbols(&self) -> ProtocolResult<bool> {
+        let unique: Option<bool> = self.sdk.get_value(&UNIQUE_SYMBOLS_KEY.to_owned())?;
+        Ok(unique.unwrap_or(false))
+    }
+}
+
+/// Id of the asset `issuer` creates with `payload`.
+pub fn asset_id(payload: &CreateAssetPayload, issuer: &Address) -> ProtocolResult<Hash> {
+    let payload_str = serde_json::to_string(payload).map_err(ServiceError::JsonParse)?;
+    Ok(Hash::digest(Bytes::from(payload_str + &issuer.as_hex())))
+}
+
+/// Account holding the assets of a service, which the service spends
+/// through the asset service like any other account.
+pub fn service_address(service: &str) -> ProtocolResult<Address> {
+    let hash = Hash::digest(Bytes::from(SERVICE_ADDRESS_PREFIX.to_owned() + service));
+    Address::from_bytes(hash.as_bytes().slice(..20))
+}
+
+/// Account holding the funds of distributions until holders claim them.
+pub fn distribution_pool() -> ProtocolResult<Address> {
+    let hash = Hash::digest(Bytes::from(DISTRIBUTION_POOL_PREFIX));
+    Address::from_bytes(hash.as_bytes().slice(..20))
+}
+
+/// Account holding locked transfers until their recipients claim them.
+pub fn lock_pool() -> ProtocolResult<Address> {
+    let hash = Hash::digest(Bytes::from(LOCK_POOL_PREFIX));
+    Address::from_bytes(hash.as_bytes().slice(..20))
+}
+
+/// Account holding held funds until their service settles them.
+pub fn hold_pool() -> ProtocolResult<Address> {
+    let hash = Hash::digest(Bytes::from(HOLD_POOL_PREFIX));
+    Address::from_bytes(hash.as_bytes().slice(..20))
+}
+
+fn check_max_supply(asset: &Asset) -> ProtocolResult<()> {
+    match asset.max_supply {
+        Some(max_supply) if asset.supply > max_supply => Err(ServiceError::OverMaxSupply {
+            supply:     asset.supply.0,
+            max_supply: max_supply.0,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+fn symbol_key(symbol: &str) -> String {
+    symbol.trim().to_lowercase()
+}
+
+fn allowance_key(asset_id: &Hash, grantee: &Address) -> Hash {
+    let mut key = asset_id.as_bytes().to_vec();
+    key.extend_from_slice(grantee.as_bytes().as_ref());
+    Hash::digest(Bytes::from(key))
+}
+
+fn session_key_key(asset_id: &Hash, session_key: &Address) -> Hash {
+    let mut key = SESSION_KEY_PREFIX.as_bytes().to_vec();
+    key.extend_from_slice(asset_id.as_bytes().as_ref());
+    key.extend_from_slice(session_key.as_bytes().as_ref());
+    Hash::digest(Bytes::from(key))
+}
+
+// Decimals as a metadata value holds them.
+fn parse_decimals(value: &str) -> Result<u32, ServiceError> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|decimals| *decimals <= MAX_DECIMALS)
+        .ok_or_else(|| ServiceError::InvalidDecimals {
+            value: value.to_owned(),
+            max:   MAX_DECIMALS,
+        })
+}
+
+fn asset_metadata_key(asset_id: &Hash, key: &str) -> Hash {
+    let mut bytes = ASSET_METADATA_PREFIX.as_bytes().to_vec();
+    bytes.extend_from_slice(asset_id.as_bytes().as_ref());
+    bytes.extend_from_slice(key.as_bytes());
+    Hash::digest(Bytes::from(bytes))
+}
+
+fn distribution_claim_key(id: u64) -> Hash {
+    let mut key = DISTRIBUTION_CLAIM_PREFIX.as_bytes().to_vec();
+    key.extend_from_slice(&id.to_be_bytes());
+    Hash::digest(Bytes::from(key))
+}
+
+// Key of an issuance record of an asset, the record count under seq 0.
+fn issuance_key(asset_id: &Hash, seq: u64) -> Hash {
+    let mut key = ISSUANCE_LOG_PREFIX.as_bytes().to_vec();
+    key.extend_from_slice(asset_id.as_bytes().as_ref());
+    key.extend_from_slice(&seq.to_be_bytes());
+    Hash::digest(Bytes::from(key))
+}
+
+// A supply change, `log_issuance` numbering it and filling in the supply.
+fn issuance(
+    asset_id: &Hash,
+    action: &str,
+    cause: &str,
+    account: &Address,
+    amount: u128,
+    height: u64,
+) -> IssuanceRecord {
+    IssuanceRecord {
+        seq: 0,
+        asset_id: asset_id.clone(),
+        action: action.to_owned(),
+        cause: cause.to_owned(),
+        account: account.clone(),
+        amount,
+        supply: 0,
+        height,
+        source_tx: None,
+    }
+}
+
+fn locks_key(asset_id: &Hash) -> Hash {
+    let mut key = LOCKS_PREFIX.as_bytes().to_vec();
+    key.extend_from_slice(asset_id.as_bytes().as_ref());
+    Hash::digest(Bytes::from(key))
+}
+
+fn allowance_index_key(asset_id: &Hash) -> Hash {
+    let mut key = ALLOWANCE_INDEX_PREFIX.as_bytes().to_vec();
+    key.extend_from_slice(asset_id.as_bytes().as_ref());
+    Hash::digest(Bytes::from(key))
 }
 
 #[derive(Debug, Display, From)]
@@ -454,6 +2963,50 @@ pub enum ServiceError {
         id: Hash,
     },
 
+    #[display(fmt = "No session key {:?}", session_key)]
+    NoSessionKey {
+        session_key: Address,
+    },
+
+    #[display(fmt = "Session key expired at block {}", expires_at)]
+    SessionKeyExpired {
+        expires_at: u64,
+    },
+
+    #[display(fmt = "Meta transaction expired at block {}", deadline)]
+    MetaTxExpired {
+        deadline: u64,
+    },
+
+    #[display(fmt = "Invalid meta transaction signature")]
+    InvalidSignature,
+
+    #[display(fmt = "Service is bound to no chain")]
+    ChainNotBound,
+
+    #[display(fmt = "Invalid meta nonce, expect {} real {}", expect, real)]
+    InvalidMetaNonce {
+        expect: u64,
+        real:   u64,
+    },
+
+    #[display(fmt = "Supply {} over the maximum of {}", supply, max_supply)]
+    OverMaxSupply {
+        supply:     u128,
+        max_supply: u128,
+    },
+
+    #[display(fmt = "Symbol {} is taken by asset {:?}", symbol, id)]
+    SymbolTaken {
+        symbol: String,
+        id:     Hash,
+    },
+
+    #[display(fmt = "No asset with symbol {}", symbol)]
+    UnknownSymbol {
+        symbol: String,
+    },
+
     #[display(fmt = "Not found asset, id {:?}", id)]
     NotFoundAsset {
         id: Hash,
@@ -469,11 +3022,132 @@ pub enum ServiceError {
 
     U128Overflow,
 
+    U128Underflow,
+
     RecipientIsSender,
 
     ApproveToYourself,
 
+    #[display(fmt = "Zero value transfers, approvals and burns are rejected")]
+    ZeroValue,
+
     NoPermission,
+
+    #[display(fmt = "Service {} can't hold this", service)]
+    NotHoldService {
+        service: String,
+    },
+
+    #[display(fmt = "Not found hold {}", id)]
+    NotFoundHold {
+        id: u64,
+    },
+
+    #[display(fmt = "A hold needs a positive amount")]
+    EmptyHold,
+
+    #[display(fmt = "Service {} can't be called with a transfer", service)]
+    ReservedCallTarget {
+        service: String,
+    },
+
+    #[display(fmt = "Service {} is not a minter", service)]
+    NotMinter {
+        service: String,
+    },
+
+    #[display(fmt = "Metadata key {:?} is empty or too long", key)]
+    InvalidMetadataKey {
+        key: String,
+    },
+
+    #[display(fmt = "Metadata value of {} bytes over the limit of {}", len, max)]
+    MetadataTooLarge {
+        len: usize,
+        max: usize,
+    },
+
+    #[display(fmt = "Decimals {:?} are not a number up to {}", value, max)]
+    InvalidDecimals {
+        value: String,
+        max:   u32,
+    },
+
+    #[display(fmt = "Block {} is not final yet", height)]
+    SnapshotNotFinal {
+        height: u64,
+    },
+
+    #[display(fmt = "No snapshot was taken at block {}", height)]
+    SnapshotNotTaken {
+        height: u64,
+    },
+
+    #[display(fmt = "Nothing to distribute")]
+    EmptyDistribution,
+
+    #[display(fmt = "Not found distribution {}", id)]
+    NotFoundDistribution {
+        id: u64,
+    },
+
+    #[display(fmt = "Distribution {} already claimed", id)]
+    AlreadyClaimed {
+        id: u64,
+    },
+
+    #[display(fmt = "No share of distribution {}", id)]
+    NothingToClaim {
+        id: u64,
+    },
+
+    #[display(fmt = "Unlock height {} is not past height {}", unlock_height, height)]
+    UnlockHeightPassed {
+        unlock_height: u64,
+        height:        u64,
+    },
+
+    #[display(fmt = "An account holds at most {} locks of an asset", max)]
+    TooManyLocks {
+        max: usize,
+    },
+
+    #[display(fmt = "Nothing of asset {:?} has unlocked", asset_id)]
+    NothingUnlocked {
+        asset_id: Hash,
+    },
+
+    #[display(fmt = "Asset {:?} is not transferable", id)]
+    NonTransferable {
+        id: Hash,
+    },
+
+    #[display(fmt = "Parameter {} is out of range", name)]
+    InvalidParam {
+        name: String,
+    },
+
+    #[display(fmt = "Transfer fee of {} bps over the maximum of {}", rate_bps, max)]
+    FeeRateTooHigh {
+        rate_bps: u32,
+        max:      u32,
+    },
+
+    #[display(fmt = "Page limit {} over the maximum of {}", limit, max)]
+    PageLimitExceeded {
+        limit: u64,
+        max:   u64,
+    },
+
+    #[display(fmt = "No schema for type {}", type_name)]
+    UnknownSchema {
+        type_name: String,
+    },
+
+    #[display(fmt = "No read method {}", method)]
+    UnknownReadMethod {
+        method: String,
+    },
 }
 
 impl std::error::Error for ServiceError {}
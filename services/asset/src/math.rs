@@ -0,0 +1,38 @@
+use crate::ServiceError;
+
+pub fn checked_add_u128(a: u128, b: u128) -> Result<u128, ServiceError> {
+    a.checked_add(b).ok_or(ServiceError::U128Overflow)
+}
+
+pub fn checked_sub_u128(a: u128, b: u128) -> Result<u128, ServiceError> {
+    a.checked_sub(b).ok_or(ServiceError::U128Underflow)
+}
+
+/// `a * b / c` rounded down, the product taken in 256 bits so it can't
+/// overflow. `None` when `c` is 0 or the quotient doesn't fit.
+pub fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    const LOW: u128 = u64::max_value() as u128;
+
+    let (a_hi, a_lo) = (a >> 64, a & LOW);
+    let (b_hi, b_lo) = (b >> 64, b & LOW);
+    let (ll, lh, hl, hh) = (a_lo * b_lo, a_lo * b_hi, a_hi * b_lo, a_hi * b_hi);
+    let mid = (ll >> 64) + (lh & LOW) + (hl & LOW);
+    let lo = (ll & LOW) | (mid << 64);
+    let hi = hh + (lh >> 64) + (hl >> 64) + (mid >> 64);
+    if c == 0 || hi >= c {
+        return None;
+    }
+
+    // long division of `hi:lo`, keeping the remainder below `c`
+    let (mut rem, mut quotient) = (hi, 0u128);
+    for bit in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || rem >= c {
+            rem = rem.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
+}
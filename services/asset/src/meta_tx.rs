@@ -0,0 +1,52 @@
+//! Transfers signed by the owner off chain and sent by anyone else, the
+//! relayer, who is paid a fee out of the transferred asset.
+
+use bytes::Bytes;
+use common_crypto::{Crypto, Secp256k1};
+
+use common::amount::Amount;
+use protocol::types::{Address, Hash};
+
+use crate::types::MetaTxPayload;
+
+// keeps a signed intent from passing as a signature over anything else
+const META_TX_DOMAIN: &str = "asset_meta_tx";
+
+/// The hash the owner signs, over the intent but not the signature itself.
+/// It names the chain the service is bound to and the service, so the
+/// signature holds on neither another bound chain nor another service of the
+/// chain. An unbound service has no chain to name and takes no signatures.
+pub fn meta_tx_hash(payload: &MetaTxPayload, chain_id: &Hash, service: &str) -> Hash {
+    let mut stream = rlp::RlpStream::new_list(9);
+    stream
+        .append(&META_TX_DOMAIN.to_owned())
+        .append(chain_id)
+        .append(&service.to_owned())
+        .append(&payload.asset_id)
+        .append(&payload.to)
+        .append(&Amount(payload.value))
+        .append(&Amount(payload.fee))
+        .append(&payload.nonce)
+        .append(&payload.deadline);
+    Hash::digest(Bytes::from(stream.out()))
+}
+
+/// Whether the payload is signed by the key of `from` for the chain and
+/// service.
+pub fn verify_meta_tx(payload: &MetaTxPayload, chain_id: &Hash, service: &str) -> bool {
+    let pubkey = match hex::decode(payload.pubkey.as_string_trim0x()) {
+        Ok(pubkey) => pubkey,
+        Err(_) => return false,
+    };
+    let signature = match hex::decode(payload.signature.as_string_trim0x()) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    match Address::from_pubkey_bytes(Bytes::from(pubkey.clone())) {
+        Ok(address) if address == payload.from => (),
+        _ => return false,
+    }
+
+    let hash = meta_tx_hash(payload, chain_id, service);
+    Secp256k1::verify_signature(hash.as_bytes().as_ref(), &signature, &pubkey).is_ok()
+}
@@ -0,0 +1,78 @@
+//! Tunables of the asset service the admin may change on chain. Each is
+//! stored under a key of its own and reads as the default the service shipped
+//! with until it is first set, so state written before the store keeps its
+//! behavior. The minters and hold services keep the keys they were stored
+//! under before the store.
+
+use protocol::traits::ServiceSDK;
+use protocol::ProtocolResult;
+
+use crate::types::{default_minters, HoldServices, Minters, Params, SetParamsPayload};
+
+/// Basis points of a whole amount, the highest transfer fee there can be.
+pub const WHOLE_BPS: u32 = 10_000;
+/// Highest transfer fee, in basis points, an issuer may set.
+pub const MAX_TRANSFER_FEE_BPS: u32 = WHOLE_BPS;
+/// Longest asset metadata value, in bytes.
+pub const MAX_METADATA_VALUE_LEN: usize = 1024;
+
+const MAX_TRANSFER_FEE_BPS_KEY: &str = "param_max_transfer_fee_bps";
+const MAX_METADATA_VALUE_LEN_KEY: &str = "param_max_metadata_value_len";
+const MINTERS_KEY: &str = "minters";
+const HOLD_SERVICES_KEY: &str = "hold_services";
+
+/// Every parameter, stored or default.
+pub fn load<SDK: ServiceSDK>(sdk: &SDK) -> ProtocolResult<Params> {
+    let max_transfer_fee_bps: Option<u64> = sdk.get_value(&MAX_TRANSFER_FEE_BPS_KEY.to_owned())?;
+    let max_metadata_value_len: Option<u64> =
+        sdk.get_value(&MAX_METADATA_VALUE_LEN_KEY.to_owned())?;
+    let minters: Option<Minters> = sdk.get_value(&MINTERS_KEY.to_owned())?;
+    let hold_services: Option<HoldServices> = sdk.get_value(&HOLD_SERVICES_KEY.to_owned())?;
+
+    Ok(Params {
+        max_transfer_fee_bps:   max_transfer_fee_bps.map_or(MAX_TRANSFER_FEE_BPS, |bps| bps as u32),
+        max_metadata_value_len: max_metadata_value_len.unwrap_or(MAX_METADATA_VALUE_LEN as u64),
+        minters:                minters.map_or_else(default_minters, |minters| minters.services),
+        hold_services:          hold_services.unwrap_or_default().services,
+    })
+}
+
+/// The first parameter the payload sets out of range. Fees can't pass the
+/// whole amount, and a metadata value must have room for something.
+pub fn invalid(payload: &SetParamsPayload) -> Option<&'static str> {
+    if payload
+        .max_transfer_fee_bps
+        .map_or(false, |bps| bps > WHOLE_BPS)
+    {
+        return Some("max_transfer_fee_bps");
+    }
+    if payload.max_metadata_value_len == Some(0) {
+        return Some("max_metadata_value_len");
+    }
+    None
+}
+
+/// Stores the parameters the payload sets and returns their names.
+pub fn store<SDK: ServiceSDK>(
+    sdk: &mut SDK,
+    payload: SetParamsPayload,
+) -> ProtocolResult<Vec<String>> {
+    let mut changed = vec![];
+    if let Some(bps) = payload.max_transfer_fee_bps {
+        sdk.set_value(MAX_TRANSFER_FEE_BPS_KEY.to_owned(), u64::from(bps))?;
+        changed.push("max_transfer_fee_bps".to_owned());
+    }
+    if let Some(len) = payload.max_metadata_value_len {
+        sdk.set_value(MAX_METADATA_VALUE_LEN_KEY.to_owned(), len)?;
+        changed.push("max_metadata_value_len".to_owned());
+    }
+    if let Some(services) = payload.minters {
+        sdk.set_value(MINTERS_KEY.to_owned(), Minters { services })?;
+        changed.push("minters".to_owned());
+    }
+    if let Some(services) = payload.hold_services {
+        sdk.set_value(HOLD_SERVICES_KEY.to_owned(), HoldServices { services })?;
+        changed.push("hold_services".to_owned());
+    }
+    Ok(changed)
+}
@@ -0,0 +1,182 @@
+//! JSON schemas of the types in the service interface, derived from their
+//! definitions so clients can generate bindings instead of mirroring the
+//! Rust structs by hand.
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use common::view::{MigrationStatusView, ServiceInfoView, ValueView};
+
+use crate::types::{
+    AccessSet, AllowanceChangedEvent, ApproveEvent, ApprovePayload, Asset, AssetMetadata,
+    AssetMetadataEvent, AuthorizeSessionKeyPayload, BalanceOfPayload, BurnEvent, BurnFromEvent,
+    BurnFromPayload, BurnTokenPayload, ChainBinding, ClaimDistributionEvent,
+    ClaimDistributionPayload, ClaimUnlockedEvent, ClaimUnlockedPayload, CreateAssetPayload,
+    CreateMirrorAssetPayload, DistributeEvent, DistributePayload, Distribution, DistributionClaim,
+    ExecuteHoldPayload, GetAccessSetPayload, GetAllowancePayload, GetAllowanceResponse,
+    GetAllowancesPayload, GetAllowancesResponse, GetAssetMetadataPayload, GetAssetPayload,
+    GetAssetsByIssuerPayload, GetAssetsByIssuerResponse, GetBalancePayload, GetBalanceResponse,
+    GetDistributionClaimPayload, GetDistributionPayload, GetHoldPayload, GetIssuanceLogPayload,
+    GetLockedBalancePayload, GetMetaNoncePayload, GetSchemaPayload, GetSessionKeyPayload,
+    GetSessionKeyResponse, GetSnapshotBalancePayload, GetViewPayload, GrantedAllowance,
+    HasRolePayload, HasRoleResponse, Hold, HoldEvent, HoldPayload, HoldServices,
+    InitGenesisPayload, IssuanceLog, IssuanceRecord, Lock, LockedBalanceResponse,
+    MetaNonceResponse, MetaTransferEvent, MetaTxPayload, MigratePayload, MigrationEvent,
+    MigrationStatus, MintEvent, MintTokenPayload, Minters, OnTransferPayload, Params, ParamsEvent,
+    ReadView, ReleaseHoldPayload, ResolveSymbolPayload, RevokeAllApprovalsEvent,
+    RevokeAllApprovalsPayload, RevokeSessionKeyPayload, ServiceInfo, SessionKeyEvent,
+    SessionTransferEvent, SessionTransferPayload, SetAssetMetadataPayload, SetParamsPayload,
+    SnapshotBalance, SnapshotEvent, StateKey, TakeSnapshotPayload, TransferAndCallPayload,
+    TransferEvent, TransferFee, TransferFeeChargedEvent, TransferFeeEvent, TransferFromEvent,
+    TransferFromPayload, TransferLockedEvent, TransferLockedPayload, TransferPayload,
+    TruncatedField, ViewField, ZeroValuePolicy,
+};
+use crate::view::{
+    AccessSetView, AllowanceView, AllowancesView, AssetMetadataView, AssetView, AssetsByIssuerView,
+    BalanceView, DistributionClaimView, DistributionView, GrantedAllowanceView, HoldView,
+    IssuanceLogView, IssuanceRecordView, LockView, LockedBalanceView, MetaNonceView, ParamsView,
+    ServicesView, SessionKeyView, SnapshotBalanceView, StateKeyView, TransferFeeView,
+};
+
+macro_rules! schemas {
+    ($($ty:ident),* $(,)?) => {
+        /// Names `schema` knows about.
+        pub const SCHEMA_TYPES: &[&str] = &[$(stringify!($ty)),*];
+
+        /// Schema of the named type, `None` for unknown names.
+        pub fn schema(type_name: &str) -> Option<RootSchema> {
+            match type_name {
+                $(stringify!($ty) => Some(schema_for!($ty)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+schemas! {
+    AccessSet,
+    AccessSetView,
+    AllowanceChangedEvent,
+    AllowanceView,
+    AllowancesView,
+    ApproveEvent,
+    ApprovePayload,
+    Asset,
+    AssetMetadata,
+    AssetMetadataEvent,
+    AssetMetadataView,
+    AssetView,
+    AssetsByIssuerView,
+    AuthorizeSessionKeyPayload,
+    BalanceOfPayload,
+    BalanceView,
+    BurnEvent,
+    BurnFromEvent,
+    BurnFromPayload,
+    BurnTokenPayload,
+    ChainBinding,
+    ClaimDistributionEvent,
+    ClaimDistributionPayload,
+    ClaimUnlockedEvent,
+    ClaimUnlockedPayload,
+    CreateAssetPayload,
+    CreateMirrorAssetPayload,
+    DistributeEvent,
+    DistributePayload,
+    Distribution,
+    DistributionClaim,
+    DistributionClaimView,
+    DistributionView,
+    ExecuteHoldPayload,
+    GetAccessSetPayload,
+    GetAllowancePayload,
+    GetAllowanceResponse,
+    GetAllowancesPayload,
+    GetAllowancesResponse,
+    GetAssetMetadataPayload,
+    GetAssetPayload,
+    GetAssetsByIssuerPayload,
+    GetAssetsByIssuerResponse,
+    GetBalancePayload,
+    GetBalanceResponse,
+    GetDistributionClaimPayload,
+    GetDistributionPayload,
+    GetHoldPayload,
+    GetIssuanceLogPayload,
+    GetLockedBalancePayload,
+    GetMetaNoncePayload,
+    GetSchemaPayload,
+    GetSessionKeyPayload,
+    GetSessionKeyResponse,
+    GetSnapshotBalancePayload,
+    GetViewPayload,
+    GrantedAllowance,
+    GrantedAllowanceView,
+    HasRolePayload,
+    HasRoleResponse,
+    Hold,
+    HoldEvent,
+    HoldPayload,
+    HoldServices,
+    HoldView,
+    InitGenesisPayload,
+    IssuanceLog,
+    IssuanceLogView,
+    IssuanceRecord,
+    IssuanceRecordView,
+    Lock,
+    LockView,
+    LockedBalanceResponse,
+    LockedBalanceView,
+    MetaNonceResponse,
+    MetaNonceView,
+    MetaTransferEvent,
+    MetaTxPayload,
+    MigratePayload,
+    MigrationEvent,
+    MigrationStatus,
+    MigrationStatusView,
+    MintEvent,
+    MintTokenPayload,
+    Minters,
+    OnTransferPayload,
+    Params,
+    ParamsEvent,
+    ParamsView,
+    ReadView,
+    ReleaseHoldPayload,
+    ResolveSymbolPayload,
+    RevokeAllApprovalsEvent,
+    RevokeAllApprovalsPayload,
+    RevokeSessionKeyPayload,
+    ServiceInfo,
+    ServiceInfoView,
+    ServicesView,
+    SessionKeyEvent,
+    SessionKeyView,
+    SessionTransferEvent,
+    SessionTransferPayload,
+    SetAssetMetadataPayload,
+    SetParamsPayload,
+    SnapshotBalance,
+    SnapshotBalanceView,
+    SnapshotEvent,
+    StateKey,
+    StateKeyView,
+    TakeSnapshotPayload,
+    TransferAndCallPayload,
+    TransferEvent,
+    TransferFee,
+    TransferFeeChargedEvent,
+    TransferFeeEvent,
+    TransferFeeView,
+    TransferFromEvent,
+    TransferFromPayload,
+    TransferLockedEvent,
+    TransferLockedPayload,
+    TransferPayload,
+    TruncatedField,
+    ValueView,
+    ViewField,
+    ZeroValuePolicy,
+}
@@ -0,0 +1,79 @@
+//! Balances and supplies as they were at the end of a block the issuer took
+//! a snapshot in. The first write changing a value after the latest
+//! snapshot records the value it replaces as a checkpoint numbered from 0,
+//! so an account keeps at most one checkpoint per snapshot. The value at a
+//! snapshot is then the one the first checkpoint after it replaced, or the
+//! current value when none came after.
+//!
+//! Values written before checkpoints were kept count as held since genesis.
+
+use bytes::Bytes;
+
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+use crate::types::Checkpoint;
+
+const BALANCE_CHECKPOINT_PREFIX: &str = "balance_checkpoint";
+const SUPPLY_CHECKPOINT_PREFIX: &str = "supply_checkpoint";
+const SNAPSHOT_PREFIX: &str = "snapshot";
+
+/// The value at the end of block `height`, given the `len` checkpoints
+/// `checkpoint` reads by number and the `current` value.
+pub fn value_at<F>(len: u64, height: u64, current: u128, checkpoint: F) -> ProtocolResult<u128>
+where
+    F: Fn(u64) -> ProtocolResult<Checkpoint>,
+{
+    // first checkpoint recorded after `height`
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if checkpoint(mid)?.height > height {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    if low == len {
+        return Ok(current);
+    }
+    Ok(checkpoint(low)?.value)
+}
+
+/// Key of the number of balance checkpoints of an asset, in the holder's
+/// account.
+pub fn balance_len_key(asset_id: &Hash) -> Hash {
+    checkpoint_key(BALANCE_CHECKPOINT_PREFIX, asset_id, None)
+}
+
+pub fn balance_key(asset_id: &Hash, index: u64) -> Hash {
+    checkpoint_key(BALANCE_CHECKPOINT_PREFIX, asset_id, Some(index))
+}
+
+pub fn supply_len_key(asset_id: &Hash) -> Hash {
+    checkpoint_key(SUPPLY_CHECKPOINT_PREFIX, asset_id, None)
+}
+
+pub fn supply_key(asset_id: &Hash, index: u64) -> Hash {
+    checkpoint_key(SUPPLY_CHECKPOINT_PREFIX, asset_id, Some(index))
+}
+
+/// Key of the height of the latest snapshot of an asset.
+pub fn latest_snapshot_key(asset_id: &Hash) -> Hash {
+    checkpoint_key(SNAPSHOT_PREFIX, asset_id, None)
+}
+
+/// Key of whether a snapshot of an asset was taken at a height.
+pub fn snapshot_key(asset_id: &Hash, height: u64) -> Hash {
+    checkpoint_key(SNAPSHOT_PREFIX, asset_id, Some(height))
+}
+
+fn checkpoint_key(prefix: &str, asset_id: &Hash, index: Option<u64>) -> Hash {
+    let mut key = prefix.as_bytes().to_vec();
+    key.extend_from_slice(asset_id.as_bytes().as_ref());
+    if let Some(index) = index {
+        key.extend_from_slice(&index.to_be_bytes());
+    }
+    Hash::digest(Bytes::from(key))
+}
@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+
+use common::amount::Amount;
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{Address, Bytes, Hash};
+
+use crate::types::{
+    Allowance, AllowanceIndex, Asset, AssetBalance, Checkpoint, Distribution, IssuedAssets,
+    LegacyAssetBalance, Minters, SessionKey, TransferFee, UNLIMITED_ALLOWANCE,
+};
+
+fn round_trip<T: FixedCodec>(value: &T) -> T {
+    let bytes = value.encode_fixed().unwrap();
+    let decoded = T::decode_fixed(bytes.clone()).unwrap();
+    assert_eq!(decoded.encode_fixed().unwrap(), bytes);
+    decoded
+}
+
+fn address(b: u8) -> Address {
+    Address::from_bytes(Bytes::from(vec![b; 20])).unwrap()
+}
+
+fn hash(b: u8) -> Hash {
+    Hash::from_bytes(Bytes::from(vec![b; 32])).unwrap()
+}
+
+// Asserts the stored bytes of `value` and that they decode back to them.
+fn assert_golden<T: FixedCodec>(value: &T, golden: &str) {
+    let bytes = value.encode_fixed().unwrap();
+    let encoded: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(encoded, golden);
+    round_trip(value);
+}
+
+#[test]
+fn test_asset_round_trip() {
+    let asset = Asset {
+        id:           Hash::digest(Bytes::from("asset")),
+        name:         "test".to_owned(),
+        supply:       Amount(u128::max_value()),
+        issuer:       address(1),
+        max_supply:   None,
+        transferable: true,
+    };
+    let decoded = round_trip(&asset);
+
+    assert_eq!(decoded.id, asset.id);
+    assert_eq!(decoded.name, asset.name);
+    assert_eq!(decoded.supply, asset.supply);
+    assert_eq!(decoded.issuer, asset.issuer);
+    assert_eq!(decoded.max_supply, None);
+
+    // an uncapped asset keeps the layout from before caps
+    let mut s = rlp::RlpStream::new_list(4);
+    s.append(&asset.id)
+        .append(&asset.name)
+        .append(&asset.supply)
+        .append(&asset.issuer);
+    assert_eq!(asset.encode_fixed().unwrap(), Bytes::from(s.out()));
+
+    let capped = Asset {
+        max_supply: Some(Amount(1024)),
+        ..asset.clone()
+    };
+    assert_eq!(round_trip(&capped), capped);
+
+    // soulbound assets add the flag, with or without a cap
+    let soulbound = Asset {
+        transferable: false,
+        ..asset
+    };
+    assert_eq!(round_trip(&soulbound), soulbound);
+    let soulbound = Asset {
+        max_supply: Some(Amount(1024)),
+        ..soulbound
+    };
+    assert_eq!(round_trip(&soulbound), soulbound);
+}
+
+#[test]
+fn test_balance_round_trip() {
+    let balance = round_trip(&AssetBalance {
+        value: Amount(u128::max_value() - 1),
+    });
+    assert_eq!(balance.value, Amount(u128::max_value() - 1));
+
+    let allowance = round_trip(&Allowance { value: 42 });
+    assert_eq!(allowance.value, 42);
+}
+
+#[test]
+fn test_amount_codec() {
+    let amount = round_trip(&Amount(u128::max_value()));
+    assert_eq!(amount, Amount(u128::max_value()));
+
+    let data = Amount(0x0102).to_sudt_data();
+    assert_eq!(data.len(), 16);
+    assert_eq!(&data[..3], &[0x02, 0x01, 0x00]);
+    let mut extended = data.to_vec();
+    extended.extend_from_slice(b"extension");
+    assert_eq!(Amount::from_sudt_data(&extended), Some(Amount(0x0102)));
+    assert_eq!(Amount::from_sudt_data(&data[..15]), None);
+
+    let json = serde_json::to_value(&Amount(u128::max_value())).unwrap();
+    assert_eq!(json, "340282366920938463463374607431768211455");
+    let decoded: Amount = serde_json::from_value(serde_json::json!(7)).unwrap();
+    assert_eq!(decoded, Amount(7));
+
+    // a short or long amount is corrupted state
+    for len in &[15usize, 17] {
+        let bytes = Bytes::from(rlp::encode(&vec![0u8; *len]));
+        assert!(Amount::decode_fixed(bytes).is_err());
+    }
+}
+
+#[test]
+fn test_legacy_balance_round_trip() {
+    let mut allowance = BTreeMap::new();
+    allowance.insert(address(1), 1);
+    allowance.insert(address(2), u128::max_value());
+    let legacy = round_trip(&LegacyAssetBalance {
+        value:     7,
+        allowance: allowance.clone(),
+    });
+
+    assert_eq!(legacy.value, 7);
+    assert_eq!(legacy.allowance, allowance);
+
+    // A legacy entry without allowances decodes as the current layout.
+    let balance = AssetBalance { value: Amount(7) };
+    let legacy = LegacyAssetBalance::decode_fixed(balance.encode_fixed().unwrap()).unwrap();
+    assert_eq!(legacy.value, 7);
+    assert!(legacy.allowance.is_empty());
+}
+
+#[test]
+fn test_unlimited_allowance_round_trip() {
+    let allowance = Allowance {
+        value: UNLIMITED_ALLOWANCE,
+    };
+    assert_eq!(allowance.encode_fixed().unwrap(), Bytes::from(vec![0xc0]));
+    assert_eq!(round_trip(&allowance).value, UNLIMITED_ALLOWANCE);
+
+    let allowance = round_trip(&Allowance {
+        value: UNLIMITED_ALLOWANCE - 1,
+    });
+    assert_eq!(allowance.value, UNLIMITED_ALLOWANCE - 1);
+}
+
+#[test]
+fn test_allowance_index_round_trip() {
+    let index = round_trip(&AllowanceIndex {
+        grantees: vec![address(1), address(2)],
+    });
+    assert_eq!(index.grantees, vec![address(1), address(2)]);
+
+    let empty = round_trip(&AllowanceIndex { grantees: vec![] });
+    assert!(empty.grantees.is_empty());
+}
+
+#[test]
+fn test_malformed_u128_rejected() {
+    for len in &[0usize, 15, 17, 32] {
+        let mut s = rlp::RlpStream::new_list(1);
+        s.append(&vec![0u8; *len]);
+        let bytes = Bytes::from(s.out());
+
+        assert!(AssetBalance::decode_fixed(bytes.clone()).is_err());
+        assert!(Allowance::decode_fixed(bytes.clone()).is_err());
+        assert!(LegacyAssetBalance::decode_fixed(bytes).is_err());
+    }
+
+    assert!(Asset::decode_fixed(Bytes::from(vec![0xc0])).is_err());
+}
+
+#[test]
+fn test_minters_round_trip() {
+    let minters = Minters {
+        services: vec!["crosschain".to_owned(), "other".to_owned()],
+    };
+    assert_eq!(round_trip(&minters), minters);
+}
+
+// Stored bytes of every state type. These are part of the state root, a
+// vector changing means stored state no longer decodes the same and calls
+// for a migration, not for a new vector.
+#[test]
+fn test_golden_vectors() {
+    let asset = Asset {
+        id:           hash(1),
+        name:         "test".to_owned(),
+        supply:       Amount(1000),
+        issuer:       address(2),
+        max_supply:   None,
+        transferable: true,
+    };
+    assert_golden(
+        &asset,
+        concat!(
+            "f84ee1a0010101010101010101010101010101010101010101010101010101010101010184746573",
+            "7490e8030000000000000000000000000000d5940202020202020202020202020202020202020202",
+        ),
+    );
+    let capped = Asset {
+        max_supply: Some(Amount(2000)),
+        ..asset.clone()
+    };
+    assert_golden(
+        &capped,
+        concat!(
+            "f85fe1a0010101010101010101010101010101010101010101010101010101010101010184746573",
+            "7490e8030000000000000000000000000000d5940202020202020202020202020202020202020202",
+            "90d0070000000000000000000000000000",
+        ),
+    );
+    let soulbound = Asset {
+        transferable: false,
+        ..asset
+    };
+    assert_golden(
+        &soulbound,
+        concat!(
+            "f850e1a0010101010101010101010101010101010101010101010101010101010101010184746573",
+            "7490e8030000000000000000000000000000d5940202020202020202020202020202020202020202",
+            "c000",
+        ),
+    );
+
+    assert_golden(
+        &AssetBalance { value: Amount(7) },
+        "d19007000000000000000000000000000000",
+    );
+    assert_golden(
+        &Allowance { value: 42 },
+        "d1902a000000000000000000000000000000",
+    );
+    assert_golden(
+        &Allowance {
+            value: UNLIMITED_ALLOWANCE,
+        },
+        "c0",
+    );
+    assert_golden(
+        &Amount(u128::max_value()),
+        "90ffffffffffffffffffffffffffffffff",
+    );
+    assert_golden(
+        &SessionKey {
+            budget:     500,
+            expires_at: 100,
+        },
+        "d290f401000000000000000000000000000064",
+    );
+    assert_golden(
+        &Checkpoint {
+            height: 12,
+            value:  300,
+        },
+        "d20c902c010000000000000000000000000000",
+    );
+    assert_golden(
+        &Minters {
+            services: vec!["crosschain".to_owned()],
+        },
+        "cb8a63726f7373636861696e",
+    );
+    assert_golden(
+        &AllowanceIndex {
+            grantees: vec![address(1), address(2)],
+        },
+        concat!(
+            "ecd5940101010101010101010101010101010101010101d594020202020202020202020202020202",
+            "0202020202",
+        ),
+    );
+    assert_golden(
+        &IssuedAssets {
+            asset_ids: vec![hash(1), hash(3)],
+        },
+        concat!(
+            "f844e1a00101010101010101010101010101010101010101010101010101010101010101e1a00303",
+            "030303030303030303030303030303030303030303030303030303030303",
+        ),
+    );
+
+    let mut allowance = BTreeMap::new();
+    allowance.insert(address(1), 1);
+    allowance.insert(address(2), u128::max_value());
+    assert_golden(
+        &LegacyAssetBalance {
+            value: 7,
+            allowance,
+        },
+        concat!(
+            "f8639007000000000000000000000000000000f850e7d59401010101010101010101010101010101",
+            "010101019001000000000000000000000000000000e7d59402020202020202020202020202020202",
+            "0202020290ffffffffffffffffffffffffffffffff",
+        ),
+    );
+
+    assert_golden(
+        &Distribution {
+            id:              1,
+            asset_id:        hash(1),
+            funding_asset:   hash(3),
+            funder:          address(2),
+            total_amount:    1000,
+            snapshot_height: 12,
+            supply:          4000,
+            claimed:         250,
+        },
+        concat!(
+            "f88f01e1a00101010101010101010101010101010101010101010101010101010101010101e1a003",
+            "03030303030303030303030303030303030303030303030303030303030303d59402020202020202",
+            "0202020202020202020202020290e80300000000000000000000000000000c90a00f000000000000",
+            "000000000000000090fa000000000000000000000000000000",
+        ),
+    );
+    assert_golden(
+        &TransferFee {
+            asset_id:  hash(1),
+            rate_bps:  100,
+            collector: address(2),
+            exempt:    vec![address(1)],
+        },
+        concat!(
+            "f850e1a0010101010101010101010101010101010101010101010101010101010101010164d59402",
+            "02020202020202020202020202020202020202d6d594010101010101010101010101010101010101",
+            "0101",
+        ),
+    );
+}
+
+// Maps encode in address byte order, so the bytes don't depend on the order
+// entries were inserted in nor on the platform.
+#[test]
+fn test_allowance_map_encoding_order() {
+    let grantees: Vec<Address> = [7u8, 1, 0xff, 3, 0x80]
+        .iter()
+        .cloned()
+        .map(address)
+        .collect();
+    let legacy = |order: &mut dyn Iterator<Item = &Address>| LegacyAssetBalance {
+        value:     7,
+        allowance: order.map(|grantee| (grantee.clone(), 1)).collect(),
+    };
+    let forward = legacy(&mut grantees.iter());
+    let backward = legacy(&mut grantees.iter().rev());
+    assert_eq!(
+        forward.encode_fixed().unwrap(),
+        backward.encode_fixed().unwrap()
+    );
+
+    let decoded = round_trip(&backward);
+    let order: Vec<u8> = decoded
+        .allowance
+        .keys()
+        .map(|grantee| grantee.as_bytes()[0])
+        .collect();
+    assert_eq!(order, vec![1, 3, 7, 0x80, 0xff]);
+}
@@ -1,23 +1,40 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::sync::Arc;
-
-use async_trait::async_trait;
-use cita_trie::MemoryDB;
-
-use framework::binding::sdk::{DefalutServiceSDK, DefaultChainQuerier};
-use framework::binding::state::{GeneralServiceState, MPTTrie};
-use protocol::traits::{NoopDispatcher, Storage};
-use protocol::types::{
-    Address, Block, Hash, Proof, Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
-};
-use protocol::{types::Bytes, ProtocolResult};
+mod codec;
+mod prop;
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
+use bytes::Bytes;
+use common::amount::{Amount, MAX_DECIMALS};
+use common::versioned::Versioned;
+use common_crypto::{Crypto, PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey, ToPublicKey};
+use protocol::traits::{ExecutorParams, ServiceSDK};
+use protocol::types::{Address, Hash, Hex, ServiceContext};
+use testing::{new_sdk, ContextBuilder, MockServiceSDK};
+
+use crate::math::{checked_add_u128, checked_sub_u128, mul_div};
+use crate::meta_tx::meta_tx_hash;
+use crate::snapshot;
 use crate::types::{
-    ApprovePayload, CreateAssetPayload, GetAllowancePayload, GetAssetPayload, GetBalancePayload,
-    TransferFromPayload, TransferPayload,
+    Allowance, AllowanceChangedEvent, ApprovePayload, AuthorizeSessionKeyPayload, BalanceOfPayload,
+    BurnFromEvent, BurnFromPayload, BurnTokenPayload, ChainBinding, ClaimDistributionPayload,
+    ClaimUnlockedPayload, CreateAssetPayload, DistributePayload, ExecuteHoldPayload,
+    GetAccessSetPayload, GetAllowancePayload, GetAllowancesPayload, GetAssetMetadataPayload,
+    GetAssetPayload, GetAssetsByIssuerPayload, GetBalancePayload, GetDistributionClaimPayload,
+    GetDistributionPayload, GetHoldPayload, GetIssuanceLogPayload, GetLockedBalancePayload,
+    GetMetaNoncePayload, GetSchemaPayload, GetSessionKeyPayload, GetSnapshotBalancePayload,
+    GetViewPayload, HoldEvent, HoldPayload, HoldServices, InitGenesisPayload, LegacyAssetBalance,
+    Lock, MetaTxPayload, MigratePayload, MigrationEvent, MigrationStatus, MintEvent,
+    MintTokenPayload, Minters, Params, ReleaseHoldPayload, ResolveSymbolPayload,
+    RevokeAllApprovalsPayload, RevokeSessionKeyPayload, SessionKeyEvent, SessionTransferEvent,
+    SessionTransferPayload, SetAssetMetadataPayload, SetParamsPayload, TakeSnapshotPayload,
+    TransferEvent, TransferFee, TransferFeeChargedEvent, TransferFromEvent, TransferFromPayload,
+    TransferLockedPayload, TransferPayload, ZeroValuePolicy, UNLIMITED_ALLOWANCE,
+};
+use crate::{
+    allowance_key, hold_pool, lock_pool, AssetService, DECIMALS_METADATA_KEY,
+    MAX_METADATA_VALUE_LEN, MAX_PAGE_LIMIT, MAX_TRANSFER_FEE_BPS, SCHEMA_VERSION,
 };
-use crate::AssetService;
 
 #[test]
 fn test_create_asset() {
@@ -32,12 +49,17 @@ fn test_create_asset() {
 
     // test create_asset
     let asset = service
-        .create_asset(context.clone(), CreateAssetPayload {
-            name: "test".to_owned(),
-            symbol: "test".to_owned(),
-            supply,
-            precision,
-        })
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name: "test".to_owned(),
+                symbol: "test".to_owned(),
+                supply,
+                precision,
+                max_supply: None,
+                transferable: true,
+            }),
+        )
         .unwrap();
 
     let new_asset = service
@@ -49,8 +71,9 @@ fn test_create_asset() {
 
     let balance_res = service
         .get_balance(context, GetBalancePayload {
-            asset_id: asset.id.clone(),
-            user:     caller,
+            asset_id:  asset.id.clone(),
+            user:      caller,
+            formatted: false,
         })
         .unwrap();
     assert_eq!(balance_res.balance, supply);
@@ -69,27 +92,36 @@ fn test_transfer() {
     let precision = 2;
     // test create_asset
     let asset = service
-        .create_asset(context.clone(), CreateAssetPayload {
-            name: "test".to_owned(),
-            symbol: "test".to_owned(),
-            supply,
-            precision,
-        })
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name: "test".to_owned(),
+                symbol: "test".to_owned(),
+                supply,
+                precision,
+                max_supply: None,
+                transferable: true,
+            }),
+        )
         .unwrap();
 
     let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
     service
-        .transfer(context.clone(), TransferPayload {
-            asset_id: asset.id.clone(),
-            to:       to_address.clone(),
-            value:    1024,
-        })
+        .transfer(
+            context.clone(),
+            Versioned(TransferPayload {
+                asset_id: asset.id.clone(),
+                to:       to_address.clone(),
+                value:    1024,
+            }),
+        )
         .unwrap();
 
     let balance_res = service
         .get_balance(context, GetBalancePayload {
-            asset_id: asset.id.clone(),
-            user:     caller,
+            asset_id:  asset.id.clone(),
+            user:      caller,
+            formatted: false,
         })
         .unwrap();
     assert_eq!(balance_res.balance, supply - 1024);
@@ -97,13 +129,60 @@ fn test_transfer() {
     let context = mock_context(cycles_limit, to_address.clone());
     let balance_res = service
         .get_balance(context, GetBalancePayload {
-            asset_id: asset.id,
-            user:     to_address,
+            asset_id:  asset.id,
+            user:      to_address,
+            formatted: false,
         })
         .unwrap();
     assert_eq!(balance_res.balance, 1024);
 }
 
+#[test]
+fn test_balance_of_interface() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1024,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    // the json other services send and get back, which must not change
+    let payload: BalanceOfPayload = serde_json::from_str(&format!(
+        r#"{{"asset_id":"{}","user":"{}"}}"#,
+        asset.id.as_hex(),
+        caller.as_hex()
+    ))
+    .unwrap();
+    let balance = service.balance_of(context.clone(), payload).unwrap();
+    assert_eq!(serde_json::to_string(&balance).unwrap(), r#""1024""#);
+
+    let stranger = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let balance = service
+        .balance_of(context.clone(), BalanceOfPayload {
+            asset_id: asset.id,
+            user:     stranger.clone(),
+        })
+        .unwrap();
+    assert_eq!(balance, Amount(0));
+
+    assert!(service
+        .balance_of(context, BalanceOfPayload {
+            asset_id: Hash::digest(Bytes::from("unknown")),
+            user:     stranger,
+        })
+        .is_err());
+}
+
 #[test]
 fn test_approve() {
     let cycles_limit = 1024 * 1024 * 1024; // 1073741824
@@ -115,21 +194,29 @@ fn test_approve() {
     let supply = 1024 * 1024;
     let precision = 2;
     let asset = service
-        .create_asset(context.clone(), CreateAssetPayload {
-            name: "test".to_owned(),
-            symbol: "test".to_owned(),
-            supply,
-            precision,
-        })
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name: "test".to_owned(),
+                symbol: "test".to_owned(),
+                supply,
+                precision,
+                max_supply: None,
+                transferable: true,
+            }),
+        )
         .unwrap();
 
     let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
     service
-        .approve(context.clone(), ApprovePayload {
-            asset_id: asset.id.clone(),
-            to:       to_address.clone(),
-            value:    1024,
-        })
+        .approve(
+            context.clone(),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       to_address.clone(),
+                value:    1024,
+            }),
+        )
         .unwrap();
 
     let allowance_res = service
@@ -144,6 +231,376 @@ fn test_approve() {
     assert_eq!(allowance_res.value, 1024);
 }
 
+#[test]
+fn test_get_allowances() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1024 * 1024,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    let grantees = vec![
+        Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap(),
+        Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap(),
+        Address::from_hex("0x888cdba6ae4f479f7164792b318b2a06c759833b").unwrap(),
+    ];
+    for (i, grantee) in grantees.iter().enumerate() {
+        service
+            .approve(
+                context.clone(),
+                Versioned(ApprovePayload {
+                    asset_id: asset.id.clone(),
+                    to:       grantee.clone(),
+                    value:    (i as u128 + 1) * 100,
+                }),
+            )
+            .unwrap();
+    }
+
+    let allowances_res = service
+        .get_allowances(context.clone(), GetAllowancesPayload {
+            asset_id: asset.id.clone(),
+            grantor:  caller.clone(),
+            offset:   0,
+            limit:    10,
+        })
+        .unwrap();
+    assert_eq!(allowances_res.allowances.len(), 3);
+    assert_eq!(allowances_res.allowances[0].grantee, grantees[0]);
+    assert_eq!(allowances_res.allowances[2].value, 300);
+
+    let allowances_res = service
+        .get_allowances(context.clone(), GetAllowancesPayload {
+            asset_id: asset.id.clone(),
+            grantor:  caller.clone(),
+            offset:   1,
+            limit:    1,
+        })
+        .unwrap();
+    assert_eq!(allowances_res.allowances.len(), 1);
+    assert_eq!(allowances_res.allowances[0].grantee, grantees[1]);
+    assert_eq!(allowances_res.allowances[0].value, 200);
+
+    assert!(service
+        .get_allowances(context.clone(), GetAllowancesPayload {
+            asset_id: asset.id.clone(),
+            grantor:  caller.clone(),
+            offset:   0,
+            limit:    MAX_PAGE_LIMIT + 1,
+        })
+        .is_err());
+
+    service
+        .revoke_all_approvals(
+            context.clone(),
+            Versioned(RevokeAllApprovalsPayload {
+                asset_id: asset.id.clone(),
+            }),
+        )
+        .unwrap();
+
+    let allowances_res = service
+        .get_allowances(context, GetAllowancesPayload {
+            asset_id: asset.id,
+            grantor:  caller,
+            offset:   0,
+            limit:    10,
+        })
+        .unwrap();
+    assert!(allowances_res.allowances.is_empty());
+}
+
+// The grantee index is kept in address order, whatever order approvals came
+// in, so listings and the stored index are the same on every node.
+#[test]
+fn test_allowance_index_order() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+    let grantees: Vec<Address> = ["0x99", "0x11", "0xee", "0x55"]
+        .iter()
+        .map(|prefix| Address::from_hex(&format!("{:0<42}", prefix)).unwrap())
+        .collect();
+
+    let listing = |order: &[Address]| {
+        let mut service = new_asset_service();
+        let asset = service
+            .create_asset(
+                context.clone(),
+                Versioned(CreateAssetPayload {
+                    name:         "test".to_owned(),
+                    supply:       1024,
+                    max_supply:   None,
+                    transferable: true,
+                }),
+            )
+            .unwrap();
+        for grantee in order {
+            service
+                .approve(
+                    context.clone(),
+                    Versioned(ApprovePayload {
+                        asset_id: asset.id.clone(),
+                        to:       grantee.clone(),
+                        value:    1,
+                    }),
+                )
+                .unwrap();
+        }
+        service
+            .get_allowances(context.clone(), GetAllowancesPayload {
+                asset_id: asset.id,
+                grantor:  caller.clone(),
+                offset:   0,
+                limit:    10,
+            })
+            .unwrap()
+            .allowances
+            .into_iter()
+            .map(|a| a.grantee)
+            .collect::<Vec<_>>()
+    };
+
+    let forward = listing(&grantees);
+    let reversed: Vec<Address> = grantees.iter().rev().cloned().collect();
+    assert_eq!(forward, listing(&reversed));
+    let mut sorted = grantees.clone();
+    sorted.sort_by(|a, b| a.as_bytes().cmp(&b.as_bytes()));
+    assert_eq!(forward, sorted);
+}
+
+#[test]
+fn test_get_assets_by_issuer() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, issuer.clone());
+
+    let mut service = new_asset_service();
+
+    let mut created = vec![];
+    for name in &["first", "second", "third"] {
+        let asset = service
+            .create_asset(
+                context.clone(),
+                Versioned(CreateAssetPayload {
+                    name:         (*name).to_owned(),
+                    supply:       1024,
+                    max_supply:   None,
+                    transferable: true,
+                }),
+            )
+            .unwrap();
+        created.push(asset);
+    }
+    // an asset of someone else isn't listed
+    let other = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    service
+        .create_asset(
+            mock_context(cycles_limit, other),
+            Versioned(CreateAssetPayload {
+                name:         "first".to_owned(),
+                supply:       1024,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    // the id is known before the asset is created
+    let payload_str = serde_json::to_string(&CreateAssetPayload {
+        name:         "first".to_owned(),
+        supply:       1024,
+        max_supply:   None,
+        transferable: true,
+    })
+    .unwrap();
+    assert_eq!(
+        created[0].id,
+        Hash::digest(Bytes::from(payload_str + &issuer.as_hex()))
+    );
+
+    let page = service
+        .get_assets_by_issuer(context.clone(), GetAssetsByIssuerPayload {
+            issuer: issuer.clone(),
+            offset: 1,
+            limit:  10,
+        })
+        .unwrap();
+    assert_eq!(page.total, 3);
+    assert_eq!(page.assets, created[1..].to_vec());
+
+    let none = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let page = service
+        .get_assets_by_issuer(context.clone(), GetAssetsByIssuerPayload {
+            issuer: none,
+            offset: 0,
+            limit:  10,
+        })
+        .unwrap();
+    assert_eq!(page.total, 0);
+    assert!(page.assets.is_empty());
+
+    assert!(service
+        .get_assets_by_issuer(context, GetAssetsByIssuerPayload {
+            issuer,
+            offset: 0,
+            limit: MAX_PAGE_LIMIT + 1,
+        })
+        .is_err());
+}
+
+#[test]
+fn test_get_service_info() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let service = new_asset_service();
+
+    let info = service
+        .get_service_info(mock_context(1024 * 1024, caller))
+        .unwrap();
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert!(info.features.contains(&"unlimited_allowance".to_owned()));
+    assert!(!info.features.contains(&"self_transfer_noop".to_owned()));
+    assert_eq!(info.payload_versions, vec![1]);
+}
+
+#[test]
+fn test_versioned_payload() {
+    let bare = r#"{"asset_id":"0x0000000000000000000000000000000000000000000000000000000000000000","to":"0x666cdba6ae4f479f7164792b318b2a06c759833b","value":1}"#;
+    let payload: Versioned<TransferPayload> = serde_json::from_str(bare).unwrap();
+    assert_eq!(payload.0.value, 1);
+
+    let envelope = serde_json::to_string(&payload).unwrap();
+    assert!(envelope.starts_with(r#"{"version":1,"payload":"#));
+    let payload: Versioned<TransferPayload> = serde_json::from_str(&envelope).unwrap();
+    assert_eq!(payload.0.value, 1);
+
+    let unsupported = format!(r#"{{"version":9,"payload":{}}}"#, bare);
+    assert!(serde_json::from_str::<Versioned<TransferPayload>>(&unsupported).is_err());
+}
+
+#[test]
+fn test_u128_as_string() {
+    let payload = TransferPayload {
+        asset_id: Hash::digest(Bytes::from("asset")),
+        to:       Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap(),
+        value:    u128::max_value(),
+    };
+    let json = serde_json::to_value(&payload).unwrap();
+    assert_eq!(json["value"], "340282366920938463463374607431768211455");
+    let payload: TransferPayload = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(payload.value, u128::max_value());
+
+    // numbers are still read
+    let mut number = json.clone();
+    number["value"] = serde_json::json!(1);
+    let payload: TransferPayload = serde_json::from_value(number).unwrap();
+    assert_eq!(payload.value, 1);
+
+    for bad in vec![
+        serde_json::json!(-1),
+        serde_json::json!("-1"),
+        serde_json::json!("0x10"),
+        serde_json::json!("340282366920938463463374607431768211456"),
+        serde_json::json!(1.5),
+    ] {
+        let mut bad_json = json.clone();
+        bad_json["value"] = bad;
+        assert!(serde_json::from_value::<TransferPayload>(bad_json).is_err());
+    }
+}
+
+#[test]
+fn test_get_schema() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let service = new_asset_service();
+    let context = mock_context(1024 * 1024, caller);
+
+    let schema = service
+        .get_schema(context.clone(), GetSchemaPayload {
+            type_name: "TransferPayload".to_owned(),
+        })
+        .unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&schema).unwrap();
+    assert_eq!(schema["properties"]["asset_id"]["type"], "string");
+    assert_eq!(schema["properties"]["to"]["type"], "string");
+    assert_eq!(schema["properties"]["value"]["type"], "string");
+
+    assert!(service
+        .get_schema(context, GetSchemaPayload {
+            type_name: "Unknown".to_owned(),
+        })
+        .is_err());
+}
+
+#[test]
+fn test_get_view() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(1024 * 1024 * 1024, caller.clone());
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1024,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    let view_of = |method: &str, payload: String| {
+        service.get_view(context.clone(), GetViewPayload {
+            method: method.to_owned(),
+            payload,
+            max_items: 0,
+            max_depth: 0,
+        })
+    };
+
+    let view = view_of("get_asset", format!(r#"{{"id": "{}"}}"#, asset.id.as_hex())).unwrap();
+    assert_eq!(view.method, "get_asset");
+    let field = |path: &str| {
+        view.fields
+            .iter()
+            .find(|f| f.path == path)
+            .map(|f| f.value.clone())
+    };
+    assert_eq!(field("id"), Some(asset.id.as_hex()));
+    assert_eq!(field("supply"), Some("1024".to_owned()));
+    assert_eq!(field("issuer"), Some(caller.as_hex()));
+    assert_eq!(field("transferable"), Some("true".to_owned()));
+    assert!(view.truncated.is_empty());
+
+    // nested responses come out as the flat fields of their view
+    let view = view_of("get_service_info", String::new()).unwrap();
+    assert!(view.fields.iter().all(|f| f.path.split('.').count() <= 2));
+    assert!(view.fields.iter().any(|f| f.path == "payload_versions.0"));
+
+    // a read taking no payload, and one returning a single value
+    let view = view_of("get_minters", String::new()).unwrap();
+    assert_eq!(view.fields[0].path, "services.0");
+    let view = view_of("get_schema", r#"{"type_name": "Asset"}"#.to_owned()).unwrap();
+    assert_eq!(view.fields.len(), 1);
+    assert_eq!(view.fields[0].path, "value");
+
+    assert!(view_of("get_asset", String::new()).is_err());
+    for method in &["transfer", "get_view", "unknown"] {
+        assert!(view_of(method, String::new()).is_err());
+    }
+}
+
 #[test]
 fn test_transfer_from() {
     let cycles_limit = 1024 * 1024 * 1024; // 1073741824
@@ -155,32 +612,43 @@ fn test_transfer_from() {
     let supply = 1024 * 1024;
     let precision = 2;
     let asset = service
-        .create_asset(context.clone(), CreateAssetPayload {
-            name: "test".to_owned(),
-            symbol: "test".to_owned(),
-            supply,
-            precision,
-        })
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name: "test".to_owned(),
+                symbol: "test".to_owned(),
+                supply,
+                precision,
+                max_supply: None,
+                transferable: true,
+            }),
+        )
         .unwrap();
 
     let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
     service
-        .approve(context.clone(), ApprovePayload {
-            asset_id: asset.id.clone(),
-            to:       to_address.clone(),
-            value:    1024,
-        })
+        .approve(
+            context.clone(),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       to_address.clone(),
+                value:    1024,
+            }),
+        )
         .unwrap();
 
     let to_context = mock_context(cycles_limit, to_address.clone());
 
     service
-        .transfer_from(to_context.clone(), TransferFromPayload {
-            asset_id:  asset.id.clone(),
-            sender:    caller.clone(),
-            recipient: to_address.clone(),
-            value:     24,
-        })
+        .transfer_from(
+            to_context.clone(),
+            Versioned(TransferFromPayload {
+                asset_id:  asset.id.clone(),
+                sender:    caller.clone(),
+                recipient: to_address.clone(),
+                value:     24,
+            }),
+        )
         .unwrap();
 
     let allowance_res = service
@@ -194,120 +662,2282 @@ fn test_transfer_from() {
     assert_eq!(allowance_res.grantee, to_address.clone());
     assert_eq!(allowance_res.value, 1000);
 
+    let events = to_context.get_events();
+    assert_eq!(events.len(), 2);
+    let changed: AllowanceChangedEvent = serde_json::from_str(&events[0].data).unwrap();
+    assert_eq!(changed.grantor, caller);
+    assert_eq!(changed.grantee, to_address);
+    assert_eq!((changed.previous, changed.value), (1024, 1000));
+    assert_eq!(changed.reason, "transfer_from");
+    let spent: TransferFromEvent = serde_json::from_str(&events[1].data).unwrap();
+    assert_eq!(spent.value, 24);
+    assert_eq!(spent.remaining_allowance, 1000);
+
+    // approving anew reports the change too, revoking each allowance as well
+    let events = context.get_events().len();
+    service
+        .approve(
+            context.clone(),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       to_address.clone(),
+                value:    500,
+            }),
+        )
+        .unwrap();
+    assert_eq!(context.get_events().len(), events + 2);
+    service
+        .revoke_all_approvals(
+            context.clone(),
+            Versioned(RevokeAllApprovalsPayload {
+                asset_id: asset.id.clone(),
+            }),
+        )
+        .unwrap();
+    let all_events = context.get_events();
+    assert_eq!(all_events.len(), events + 4);
+    let changed: AllowanceChangedEvent = serde_json::from_str(&all_events[events].data).unwrap();
+    assert_eq!((changed.previous, changed.value), (1000, 500));
+    assert_eq!(changed.reason, "approve");
+    let changed: AllowanceChangedEvent =
+        serde_json::from_str(&all_events[events + 2].data).unwrap();
+    assert_eq!((changed.previous, changed.value), (500, 0));
+    assert_eq!(changed.reason, "revoke");
+
     let balance_res = service
         .get_balance(context, GetBalancePayload {
-            asset_id: asset.id.clone(),
-            user:     caller,
+            asset_id:  asset.id.clone(),
+            user:      caller,
+            formatted: false,
         })
         .unwrap();
     assert_eq!(balance_res.balance, supply - 24);
 
     let balance_res = service
         .get_balance(to_context, GetBalancePayload {
-            asset_id: asset.id,
-            user:     to_address,
+            asset_id:  asset.id,
+            user:      to_address,
+            formatted: false,
         })
         .unwrap();
     assert_eq!(balance_res.balance, 24);
 }
 
-fn new_asset_service() -> AssetService<
-    DefalutServiceSDK<
-        GeneralServiceState<MemoryDB>,
-        DefaultChainQuerier<MockStorage>,
-        NoopDispatcher,
-    >,
-> {
-    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
-    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
-    let state = GeneralServiceState::new(trie);
-
-    let sdk = DefalutServiceSDK::new(
-        Rc::new(RefCell::new(state)),
-        Rc::new(chain_db),
-        NoopDispatcher {},
-    );
+#[test]
+fn test_transfer_from_unlimited_allowance() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
 
-    AssetService::new(sdk).unwrap()
-}
+    let mut service = new_asset_service();
 
-fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
-    let params = ServiceContextParams {
-        tx_hash: None,
-        nonce: None,
-        cycles_limit,
-        cycles_price: 1,
-        cycles_used: Rc::new(RefCell::new(0)),
-        caller,
-        height: 1,
-        timestamp: 0,
-        service_name: "service_name".to_owned(),
-        service_method: "service_method".to_owned(),
-        service_payload: "service_payload".to_owned(),
-        extra: None,
-        events: Rc::new(RefCell::new(vec![])),
-    };
-
-    ServiceContext::new(params)
-}
-
-struct MockStorage;
-
-#[async_trait]
-impl Storage for MockStorage {
-    async fn insert_transactions(&self, _: Vec<SignedTransaction>) -> ProtocolResult<()> {
-        unimplemented!()
-    }
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1024,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
 
-    async fn insert_block(&self, _: Block) -> ProtocolResult<()> {
-        unimplemented!()
-    }
+    let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    service
+        .approve(
+            context.clone(),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       to_address.clone(),
+                value:    UNLIMITED_ALLOWANCE,
+            }),
+        )
+        .unwrap();
 
-    async fn insert_receipts(&self, _: Vec<Receipt>) -> ProtocolResult<()> {
-        unimplemented!()
+    let to_context = mock_context(cycles_limit, to_address.clone());
+    for _ in 0..2 {
+        service
+            .transfer_from(
+                to_context.clone(),
+                Versioned(TransferFromPayload {
+                    asset_id:  asset.id.clone(),
+                    sender:    caller.clone(),
+                    recipient: to_address.clone(),
+                    value:     24,
+                }),
+            )
+            .unwrap();
     }
 
-    async fn update_latest_proof(&self, _: Proof) -> ProtocolResult<()> {
-        unimplemented!()
-    }
+    let allowance_res = service
+        .get_allowance(context, GetAllowancePayload {
+            asset_id: asset.id.clone(),
+            grantor:  caller,
+            grantee:  to_address.clone(),
+        })
+        .unwrap();
+    assert_eq!(allowance_res.value, UNLIMITED_ALLOWANCE);
 
-    async fn get_transaction_by_hash(&self, _: Hash) -> ProtocolResult<SignedTransaction> {
-        unimplemented!()
-    }
+    // an unlimited allowance is left as is, so only the transfers are reported
+    let events = to_context.get_events();
+    assert_eq!(events.len(), 2);
+    let spent: TransferFromEvent = serde_json::from_str(&events[1].data).unwrap();
+    assert_eq!(spent.remaining_allowance, UNLIMITED_ALLOWANCE);
 
-    async fn get_transactions(&self, _: Vec<Hash>) -> ProtocolResult<Vec<SignedTransaction>> {
-        unimplemented!()
-    }
+    let balance_res = service
+        .get_balance(to_context, GetBalancePayload {
+            asset_id:  asset.id,
+            user:      to_address,
+            formatted: false,
+        })
+        .unwrap();
+    assert_eq!(balance_res.balance, 48);
+}
 
-    async fn get_latest_block(&self) -> ProtocolResult<Block> {
-        unimplemented!()
-    }
+#[test]
+fn test_burn_from() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let owner = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let spender = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, owner.clone());
 
-    async fn get_block_by_height(&self, _: u64) -> ProtocolResult<Block> {
-        unimplemented!()
-    }
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1_000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    let burn = |amount: u128| {
+        Versioned(BurnFromPayload {
+            asset_id: asset.id.clone(),
+            owner: owner.clone(),
+            amount,
+        })
+    };
 
-    async fn get_block_by_hash(&self, _: Hash) -> ProtocolResult<Block> {
-        unimplemented!()
-    }
+    // nothing burns without an allowance
+    let spender_context = mock_context(cycles_limit, spender.clone());
+    assert!(service.burn_from(spender_context.clone(), burn(1)).is_err());
 
-    async fn get_receipt(&self, _: Hash) -> ProtocolResult<Receipt> {
-        unimplemented!()
-    }
+    service
+        .approve(
+            context.clone(),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       spender.clone(),
+                value:    100,
+            }),
+        )
+        .unwrap();
+    assert!(service
+        .burn_from(spender_context.clone(), burn(101))
+        .is_err());
+    // an extra naming no address fails rather than panics
+    let garbled = ContextBuilder::new(spender.clone())
+        .cycles_limit(cycles_limit)
+        .extra(Bytes::from(vec![0xffu8, 0xfe]))
+        .build();
+    assert!(service.burn_from(garbled, burn(1)).is_err());
+    service
+        .burn_from(spender_context.clone(), burn(40))
+        .unwrap();
 
-    async fn get_receipts(&self, _: Vec<Hash>) -> ProtocolResult<Vec<Receipt>> {
-        unimplemented!()
-    }
+    let events = spender_context.get_events();
+    assert_eq!(events.len(), 2);
+    let changed: AllowanceChangedEvent = serde_json::from_str(&events[0].data).unwrap();
+    assert_eq!((changed.previous, changed.value), (100, 60));
+    assert_eq!(changed.reason, "burn_from");
+    let burned: BurnFromEvent = serde_json::from_str(&events[1].data).unwrap();
+    assert_eq!(burned.caller, spender);
+    assert_eq!(burned.owner, owner);
+    assert_eq!((burned.amount, burned.remaining_allowance), (40, 60));
 
-    async fn get_latest_proof(&self) -> ProtocolResult<Proof> {
-        unimplemented!()
-    }
+    let balance = service
+        .get_balance(context.clone(), GetBalancePayload {
+            asset_id:  asset.id.clone(),
+            user:      owner.clone(),
+            formatted: false,
+        })
+        .unwrap()
+        .balance;
+    assert_eq!(balance, 960);
+    let asset = service
+        .get_asset(context, GetAssetPayload { id: asset.id })
+        .unwrap();
+    assert_eq!(asset.supply, Amount(960));
+}
+
+#[test]
+fn test_failed_transfer_from_leaves_no_state() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       100,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    let grantee = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    service
+        .approve(
+            context.clone(),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       grantee.clone(),
+                value:    1000,
+            }),
+        )
+        .unwrap();
 
-    async fn update_overlord_wal(&self, _info: Bytes) -> ProtocolResult<()> {
-        unimplemented!()
+    // no allowance at all, a zero value one, more than the allowance, and
+    // within the allowance but beyond the grantor's balance
+    let spammer = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let attempts = vec![
+        (spammer.clone(), 24),
+        (spammer.clone(), 0),
+        (grantee.clone(), 1024),
+        (grantee.clone(), 200),
+    ];
+    for (spender, value) in attempts {
+        let spender_context = mock_context(cycles_limit, spender.clone());
+        let _ = service.transfer_from(
+            spender_context.clone(),
+            Versioned(TransferFromPayload {
+                asset_id: asset.id.clone(),
+                sender: caller.clone(),
+                recipient: spender,
+                value,
+            }),
+        );
+        assert!(spender_context.get_events().is_empty());
     }
 
-    async fn load_overlord_wal(&self) -> ProtocolResult<Bytes> {
-        unimplemented!()
+    let stored: Option<Allowance> = service
+        .sdk
+        .get_account_value(&caller, &allowance_key(&asset.id, &spammer))
+        .unwrap();
+    assert!(stored.is_none());
+
+    let allowances_res = service
+        .get_allowances(context.clone(), GetAllowancesPayload {
+            asset_id: asset.id.clone(),
+            grantor:  caller.clone(),
+            offset:   0,
+            limit:    10,
+        })
+        .unwrap();
+    assert_eq!(allowances_res.allowances.len(), 1);
+    assert_eq!(allowances_res.allowances[0].grantee, grantee);
+    assert_eq!(allowances_res.allowances[0].value, 1000);
+
+    let balance_res = service
+        .get_balance(context, GetBalancePayload {
+            asset_id:  asset.id,
+            user:      caller,
+            formatted: false,
+        })
+        .unwrap();
+    assert_eq!(balance_res.balance, 100);
+}
+
+#[test]
+fn test_unique_symbols() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let native_id =
+        Hash::from_hex("0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c")
+            .unwrap();
+    service
+        .init_genesis(InitGenesisPayload {
+            id:                 native_id.clone(),
+            name:               "Bitcoin".to_owned(),
+            supply:             1024,
+            issuer:             caller.clone(),
+            max_supply:         None,
+            self_transfer_noop: false,
+            unique_symbols:     true,
+            admin:              None,
+            minters:            vec![],
+            rbac:               false,
+            zero_value_policy:  ZeroValuePolicy::AllowWithEvent,
+            chain_binding:      None,
+        })
+        .unwrap();
+
+    let phisher = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let phisher_context = mock_context(cycles_limit, phisher);
+    for name in &["Bitcoin", "BITCOIN", " bitcoin "] {
+        assert!(service
+            .create_asset(
+                phisher_context.clone(),
+                Versioned(CreateAssetPayload {
+                    name:         (*name).to_owned(),
+                    supply:       1024,
+                    max_supply:   None,
+                    transferable: true,
+                }),
+            )
+            .is_err());
     }
+    let other = service
+        .create_asset(
+            phisher_context,
+            Versioned(CreateAssetPayload {
+                name:         "Other".to_owned(),
+                supply:       1024,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    let resolve = |symbol: &str| {
+        service.resolve_symbol(context.clone(), ResolveSymbolPayload {
+            symbol: symbol.to_owned(),
+        })
+    };
+    assert_eq!(resolve("bitcoin").unwrap().id, native_id);
+    assert_eq!(resolve("OTHER").unwrap().id, other.id);
+    assert!(resolve("ether").is_err());
+}
+
+#[test]
+fn test_duplicate_symbols_allowed() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller);
+
+    let mut service = new_asset_service();
+
+    let mut created = vec![];
+    for supply in &[1024, 2048] {
+        let asset = service
+            .create_asset(
+                context.clone(),
+                Versioned(CreateAssetPayload {
+                    name:         "test".to_owned(),
+                    supply:       *supply,
+                    max_supply:   None,
+                    transferable: true,
+                }),
+            )
+            .unwrap();
+        created.push(asset);
+    }
+
+    // without the genesis flag the first one keeps the symbol
+    let resolved = service
+        .resolve_symbol(context, ResolveSymbolPayload {
+            symbol: "TEST".to_owned(),
+        })
+        .unwrap();
+    assert_eq!(resolved, created[0]);
+}
+
+#[test]
+fn test_session_keys() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let player = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let session_key = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let shop = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let at_height = |caller: &Address, height: u64| {
+        ContextBuilder::new(caller.clone())
+            .cycles_limit(cycles_limit)
+            .height(height)
+            .build()
+    };
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            at_height(&player, 1),
+            Versioned(CreateAssetPayload {
+                name:         "gold".to_owned(),
+                supply:       1000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    let authorize = |expires_at: u64| {
+        Versioned(AuthorizeSessionKeyPayload {
+            asset_id: asset.id.clone(),
+            session_key: session_key.clone(),
+            budget: 100,
+            expires_at,
+        })
+    };
+    assert!(service
+        .authorize_session_key(at_height(&player, 5), authorize(5))
+        .is_err());
+    let ctx = at_height(&player, 5);
+    service
+        .authorize_session_key(ctx.clone(), authorize(10))
+        .unwrap();
+    let event: SessionKeyEvent = serde_json::from_str(&ctx.get_events()[0].data).unwrap();
+    assert_eq!((event.budget, event.expires_at), (100, 10));
+
+    let spend = |value: u128| {
+        Versioned(SessionTransferPayload {
+            asset_id: asset.id.clone(),
+            authorizer: player.clone(),
+            to: shop.clone(),
+            value,
+        })
+    };
+    let ctx = at_height(&session_key, 6);
+    service.session_transfer(ctx.clone(), spend(60)).unwrap();
+    let event: SessionTransferEvent = serde_json::from_str(&ctx.get_events()[0].data).unwrap();
+    assert_eq!((event.value, event.remaining_budget), (60, 40));
+    // over the budget, and sent by someone else than the key
+    assert!(service
+        .session_transfer(at_height(&session_key, 6), spend(50))
+        .is_err());
+    assert!(service
+        .session_transfer(at_height(&shop, 6), spend(10))
+        .is_err());
+
+    let session = service
+        .get_session_key(at_height(&player, 6), GetSessionKeyPayload {
+            asset_id:    asset.id.clone(),
+            authorizer:  player.clone(),
+            session_key: session_key.clone(),
+        })
+        .unwrap();
+    assert_eq!((session.budget, session.expires_at), (40, 10));
+
+    // expired
+    assert!(service
+        .session_transfer(at_height(&session_key, 10), spend(10))
+        .is_err());
+
+    service
+        .authorize_session_key(at_height(&player, 10), authorize(20))
+        .unwrap();
+    let revoke = || {
+        Versioned(RevokeSessionKeyPayload {
+            asset_id:    asset.id.clone(),
+            session_key: session_key.clone(),
+        })
+    };
+    service
+        .revoke_session_key(at_height(&player, 11), revoke())
+        .unwrap();
+    assert!(service
+        .session_transfer(at_height(&session_key, 11), spend(10))
+        .is_err());
+    assert!(service
+        .revoke_session_key(at_height(&player, 11), revoke())
+        .is_err());
+
+    let balance_of = |user: &Address| {
+        service
+            .get_balance(at_height(user, 11), GetBalancePayload {
+                asset_id:  asset.id.clone(),
+                user:      user.clone(),
+                formatted: false,
+            })
+            .unwrap()
+            .balance
+    };
+    assert_eq!(balance_of(&player), 940);
+    assert_eq!(balance_of(&shop), 60);
+}
+
+#[test]
+fn test_self_transfer_noop() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let supply = 1024 * 1024;
+    let asset_id =
+        Hash::from_hex("0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c")
+            .unwrap();
+    service
+        .init_genesis(InitGenesisPayload {
+            id: asset_id.clone(),
+            name: "test".to_owned(),
+            supply,
+            issuer: caller.clone(),
+            max_supply: None,
+            self_transfer_noop: true,
+            unique_symbols: false,
+            admin: None,
+            minters: vec![],
+            rbac: false,
+            zero_value_policy: ZeroValuePolicy::AllowWithEvent,
+            chain_binding: None,
+        })
+        .unwrap();
+
+    service
+        .transfer(
+            context.clone(),
+            Versioned(TransferPayload {
+                asset_id: asset_id.clone(),
+                to:       caller.clone(),
+                value:    1024,
+            }),
+        )
+        .unwrap();
+    assert_eq!(context.get_events().len(), 1);
+
+    let balance_res = service
+        .get_balance(context.clone(), GetBalancePayload {
+            asset_id:  asset_id.clone(),
+            user:      caller.clone(),
+            formatted: false,
+        })
+        .unwrap();
+    assert_eq!(balance_res.balance, supply);
+
+    let res = service.transfer(
+        context,
+        Versioned(TransferPayload {
+            asset_id,
+            to: caller,
+            value: supply + 1,
+        }),
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_zero_value_policy() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let spender = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let asset_id =
+        Hash::from_hex("0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c")
+            .unwrap();
+    let genesis = |zero_value_policy| {
+        let mut service = new_asset_service();
+        service
+            .init_genesis(InitGenesisPayload {
+                id: asset_id.clone(),
+                name: "test".to_owned(),
+                supply: 1_000,
+                issuer: caller.clone(),
+                max_supply: None,
+                self_transfer_noop: false,
+                unique_symbols: false,
+                admin: None,
+                minters: vec![],
+                rbac: false,
+                zero_value_policy,
+                chain_binding: None,
+            })
+            .unwrap();
+        service
+    };
+    let transfer = |value| {
+        Versioned(TransferPayload {
+            asset_id: asset_id.clone(),
+            to: spender.clone(),
+            value,
+        })
+    };
+    let approve = |value| {
+        Versioned(ApprovePayload {
+            asset_id: asset_id.clone(),
+            to: spender.clone(),
+            value,
+        })
+    };
+
+    let mut service = genesis(ZeroValuePolicy::Reject);
+    let context = mock_context(cycles_limit, caller.clone());
+    let info = service.get_service_info(context.clone()).unwrap();
+    assert!(info.features.contains(&"zero_value_policy".to_owned()));
+    assert!(service.transfer(context.clone(), transfer(0)).is_err());
+    assert!(service.approve(context.clone(), approve(0)).is_err());
+    assert!(service
+        .transfer_from(
+            mock_context(cycles_limit, spender.clone()),
+            Versioned(TransferFromPayload {
+                asset_id:  asset_id.clone(),
+                sender:    caller.clone(),
+                recipient: spender.clone(),
+                value:     0,
+            }),
+        )
+        .is_err());
+    assert!(service
+        .burn_from(
+            mock_context(cycles_limit, spender.clone()),
+            Versioned(BurnFromPayload {
+                asset_id: asset_id.clone(),
+                owner:    caller.clone(),
+                amount:   0,
+            }),
+        )
+        .is_err());
+    assert!(service
+        .transfer_locked_until(
+            context.clone(),
+            Versioned(TransferLockedPayload {
+                asset_id:      asset_id.clone(),
+                to:            spender.clone(),
+                value:         0,
+                unlock_height: 10,
+            }),
+        )
+        .is_err());
+    assert!(context.get_events().is_empty());
+
+    // setting a granted allowance back to 0 still revokes it
+    service.approve(context.clone(), approve(10)).unwrap();
+    service.approve(context.clone(), approve(0)).unwrap();
+    let allowance = service
+        .get_allowance(context.clone(), GetAllowancePayload {
+            asset_id: asset_id.clone(),
+            grantor:  caller.clone(),
+            grantee:  spender.clone(),
+        })
+        .unwrap();
+    assert_eq!(allowance.value, 0);
+
+    let mut service = genesis(ZeroValuePolicy::AllowSilent);
+    let context = mock_context(cycles_limit, caller.clone());
+    service.transfer(context.clone(), transfer(0)).unwrap();
+    service.approve(context.clone(), approve(0)).unwrap();
+    assert!(context.get_events().is_empty());
+    service.transfer(context.clone(), transfer(1)).unwrap();
+    assert_eq!(context.get_events().len(), 1);
+
+    let mut service = genesis(ZeroValuePolicy::AllowWithEvent);
+    let context = mock_context(cycles_limit, caller.clone());
+    service.transfer(context.clone(), transfer(0)).unwrap();
+    assert_eq!(context.get_events().len(), 1);
+}
+
+#[test]
+fn test_legacy_allowance_migration() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let supply = 1024 * 1024;
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name: "test".to_owned(),
+                supply,
+                max_supply: None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    // Balance and allowances stored together, as before the storage split.
+    let grantee = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let mut allowance = BTreeMap::new();
+    allowance.insert(grantee.clone(), 1024);
+    service
+        .sdk
+        .set_account_value(&caller, asset.id.clone(), LegacyAssetBalance {
+            value: supply,
+            allowance,
+        })
+        .unwrap();
+
+    let allowance_res = service
+        .get_allowance(context.clone(), GetAllowancePayload {
+            asset_id: asset.id.clone(),
+            grantor:  caller.clone(),
+            grantee:  grantee.clone(),
+        })
+        .unwrap();
+    assert_eq!(allowance_res.value, 1024);
+
+    // Writing the balance migrates the allowances out of it.
+    service
+        .transfer(
+            context.clone(),
+            Versioned(TransferPayload {
+                asset_id: asset.id.clone(),
+                to:       grantee.clone(),
+                value:    24,
+            }),
+        )
+        .unwrap();
+
+    let legacy: LegacyAssetBalance = service
+        .sdk
+        .get_account_value(&caller, &asset.id)
+        .unwrap()
+        .unwrap();
+    assert!(legacy.allowance.is_empty());
+    assert_eq!(legacy.value, supply - 24);
+
+    let allowances_res = service
+        .get_allowances(context, GetAllowancesPayload {
+            asset_id: asset.id,
+            grantor:  caller,
+            offset:   0,
+            limit:    10,
+        })
+        .unwrap();
+    assert_eq!(allowances_res.allowances.len(), 1);
+    assert_eq!(allowances_res.allowances[0].grantee, grantee);
+    assert_eq!(allowances_res.allowances[0].value, 1024);
+}
+
+#[test]
+fn test_minters() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let user = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let mut service = new_asset_service();
+    let asset_id =
+        Hash::from_hex("0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c")
+            .unwrap();
+    service
+        .init_genesis(InitGenesisPayload {
+            id:                 asset_id.clone(),
+            name:               "test".to_owned(),
+            supply:             1024,
+            issuer:             admin.clone(),
+            max_supply:         None,
+            self_transfer_noop: false,
+            unique_symbols:     false,
+            admin:              None,
+            minters:            vec!["crosschain".to_owned()],
+            rbac:               false,
+            zero_value_policy:  ZeroValuePolicy::AllowWithEvent,
+            chain_binding:      None,
+        })
+        .unwrap();
+
+    let service_ctx = |name: &'static str| {
+        ContextBuilder::new(user.clone())
+            .cycles_limit(cycles_limit)
+            .extra(Bytes::from(name))
+            .build()
+    };
+    let mint = MintTokenPayload {
+        token_id:  asset_id.clone(),
+        receiver:  user.clone(),
+        amount:    10,
+        memo:      String::new(),
+        source_tx: None,
+    };
+
+    // users can't mint, nor can services outside the minters
+    assert!(service
+        .mint_token(mock_context(cycles_limit, user.clone()), mint.clone())
+        .is_err());
+    assert!(service
+        .mint_token(service_ctx("other"), mint.clone())
+        .is_err());
+    let ctx = service_ctx("crosschain");
+    service.mint_token(ctx.clone(), mint.clone()).unwrap();
+    let events = ctx.get_events();
+    assert_eq!(events.len(), 1);
+    let event: MintEvent = serde_json::from_str(&events[0].data).unwrap();
+    assert_eq!(event.minter, "crosschain");
+    assert_eq!(event.amount, 10);
+
+    let minters = Minters {
+        services: vec!["other".to_owned()],
+    };
+    let set_minters = SetParamsPayload {
+        minters: Some(minters.services.clone()),
+        ..SetParamsPayload::default()
+    };
+    assert!(service
+        .set_params(
+            mock_context(cycles_limit, user.clone()),
+            set_minters.clone()
+        )
+        .is_err());
+    service
+        .set_params(mock_context(cycles_limit, admin), set_minters)
+        .unwrap();
+    assert_eq!(
+        service
+            .get_minters(mock_context(cycles_limit, user.clone()))
+            .unwrap(),
+        minters
+    );
+
+    assert!(service
+        .mint_token(service_ctx("crosschain"), mint.clone())
+        .is_err());
+    service.mint_token(service_ctx("other"), mint).unwrap();
+
+    let balance_res = service
+        .get_balance(
+            mock_context(cycles_limit, user.clone()),
+            GetBalancePayload {
+                asset_id,
+                user,
+                formatted: false,
+            },
+        )
+        .unwrap();
+    assert_eq!(balance_res.balance, 20);
+}
+
+#[test]
+fn test_transfer_fee() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let user = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let other = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let collector = Address::from_hex("0x888cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, issuer.clone());
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "taxed".to_owned(),
+                symbol:       "tax".to_owned(),
+                supply:       1_000_000,
+                precision:    2,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    let balance = |service: &AssetService<_>, user: &Address| {
+        service
+            .get_balance(context.clone(), GetBalancePayload {
+                asset_id:  asset.id.clone(),
+                user:      user.clone(),
+                formatted: false,
+            })
+            .unwrap()
+            .balance
+    };
+    let transfer = |to: &Address, value: u128| {
+        Versioned(TransferPayload {
+            asset_id: asset.id.clone(),
+            to: to.clone(),
+            value,
+        })
+    };
+
+    let fee = TransferFee {
+        asset_id:  asset.id.clone(),
+        rate_bps:  250,
+        collector: collector.clone(),
+        exempt:    vec![issuer.clone()],
+    };
+    // only the issuer sets the fee, at most the whole transfer
+    assert!(service
+        .set_transfer_fee(
+            mock_context(cycles_limit, user.clone()),
+            Versioned(fee.clone())
+        )
+        .is_err());
+    assert!(service
+        .set_transfer_fee(
+            context.clone(),
+            Versioned(TransferFee {
+                rate_bps: MAX_TRANSFER_FEE_BPS + 1,
+                ..fee.clone()
+            })
+        )
+        .is_err());
+    service
+        .set_transfer_fee(context.clone(), Versioned(fee.clone()))
+        .unwrap();
+    assert_eq!(
+        service
+            .get_transfer_fee(context.clone(), GetAssetPayload {
+                id: asset.id.clone(),
+            })
+            .unwrap(),
+        fee
+    );
+
+    // the exempt issuer pays none
+    service
+        .transfer(context.clone(), transfer(&user, 10_000))
+        .unwrap();
+    assert_eq!(balance(&service, &user), 10_000);
+
+    let user_ctx = mock_context(cycles_limit, user.clone());
+    service
+        .transfer(user_ctx.clone(), transfer(&other, 1_000))
+        .unwrap();
+    assert_eq!(balance(&service, &user), 9_000);
+    assert_eq!(balance(&service, &other), 975);
+    assert_eq!(balance(&service, &collector), 25);
+    let events = user_ctx.get_events();
+    let event: TransferFeeChargedEvent = serde_json::from_str(&events[0].data).unwrap();
+    assert_eq!(event.fee, 25);
+    assert_eq!(event.collector, collector);
+
+    // fees rounding to nothing aren't charged
+    service
+        .transfer(user_ctx.clone(), transfer(&other, 39))
+        .unwrap();
+    assert_eq!(balance(&service, &other), 1_014);
+    assert_eq!(balance(&service, &collector), 25);
+
+    // a zero rate turns the fee off
+    service
+        .set_transfer_fee(
+            context.clone(),
+            Versioned(TransferFee { rate_bps: 0, ..fee }),
+        )
+        .unwrap();
+    service.transfer(user_ctx, transfer(&other, 1_000)).unwrap();
+    assert_eq!(balance(&service, &other), 2_014);
+    assert_eq!(balance(&service, &collector), 25);
+}
+
+#[test]
+fn test_soulbound_asset() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let user = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let other = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, issuer.clone());
+    let user_ctx = mock_context(cycles_limit, user.clone());
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "badge".to_owned(),
+                supply:       10,
+                max_supply:   None,
+                transferable: false,
+            }),
+        )
+        .unwrap();
+    assert!(!asset.transferable);
+    let transfer = |to: &Address| {
+        Versioned(TransferPayload {
+            asset_id: asset.id.clone(),
+            to:       to.clone(),
+            value:    1,
+        })
+    };
+
+    // the issuer hands it out, holders can't pass it on
+    service.transfer(context.clone(), transfer(&user)).unwrap();
+    assert!(service
+        .transfer(user_ctx.clone(), transfer(&other))
+        .is_err());
+    assert!(service
+        .approve(
+            user_ctx.clone(),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       other.clone(),
+                value:    1,
+            })
+        )
+        .is_err());
+    assert!(service
+        .authorize_session_key(
+            user_ctx,
+            Versioned(AuthorizeSessionKeyPayload {
+                asset_id:    asset.id.clone(),
+                session_key: other.clone(),
+                budget:      1,
+                expires_at:  100,
+            })
+        )
+        .is_err());
+
+    // an allowance from the issuer still moves its own balance
+    service
+        .approve(
+            context,
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       other.clone(),
+                value:    1,
+            }),
+        )
+        .unwrap();
+    service
+        .transfer_from(
+            mock_context(cycles_limit, other.clone()),
+            Versioned(TransferFromPayload {
+                asset_id:  asset.id.clone(),
+                sender:    issuer,
+                recipient: user.clone(),
+                value:     1,
+            }),
+        )
+        .unwrap();
+
+    let balance_res = service
+        .get_balance(
+            mock_context(cycles_limit, user.clone()),
+            GetBalancePayload {
+                asset_id: asset.id,
+                user,
+                formatted: false,
+            },
+        )
+        .unwrap();
+    assert_eq!(balance_res.balance, 2);
+}
+
+#[test]
+fn test_distribution() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let alice = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let bob = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let at = |caller: &Address, height: u64| {
+        ContextBuilder::new(caller.clone())
+            .cycles_limit(cycles_limit)
+            .height(height)
+            .build()
+    };
+
+    let mut service = new_asset_service();
+    let create = |name: &str| {
+        Versioned(CreateAssetPayload {
+            name:         name.to_owned(),
+            supply:       1_000,
+            max_supply:   None,
+            transferable: true,
+        })
+    };
+    let shares = service
+        .create_asset(at(&issuer, 1), create("share"))
+        .unwrap();
+    let dividend = service.create_asset(at(&issuer, 1), create("usd")).unwrap();
+    let transfer = |to: &Address, value: u128| {
+        Versioned(TransferPayload {
+            asset_id: shares.id.clone(),
+            to: to.clone(),
+            value,
+        })
+    };
+    let take_snapshot = || {
+        Versioned(TakeSnapshotPayload {
+            asset_id: shares.id.clone(),
+        })
+    };
+    assert!(service
+        .take_snapshot(at(&alice, 1), take_snapshot())
+        .is_err());
+    assert_eq!(
+        service
+            .take_snapshot(at(&issuer, 1), take_snapshot())
+            .unwrap(),
+        1
+    );
+
+    // at the end of block 2 alice holds 300 and bob 100 of the shares
+    service
+        .transfer(at(&issuer, 2), transfer(&alice, 300))
+        .unwrap();
+    service
+        .transfer(at(&issuer, 2), transfer(&bob, 100))
+        .unwrap();
+    // shares in the lock pool are no holder's, so they take no share
+    service
+        .transfer_locked_until(
+            at(&issuer, 2),
+            Versioned(TransferLockedPayload {
+                asset_id:      shares.id.clone(),
+                to:            bob.clone(),
+                value:         100,
+                unlock_height: 10,
+            }),
+        )
+        .unwrap();
+    service
+        .take_snapshot(at(&issuer, 2), take_snapshot())
+        .unwrap();
+    // later moves don't change the snapshot
+    service
+        .transfer(at(&alice, 3), transfer(&bob, 300))
+        .unwrap();
+    service
+        .transfer(at(&bob, 3), transfer(&alice, 100))
+        .unwrap();
+    // only the first write after the snapshot is kept
+    let checkpoints: Option<u64> = service
+        .sdk
+        .get_account_value(&alice, &snapshot::balance_len_key(&shares.id))
+        .unwrap();
+    assert_eq!(checkpoints, Some(2));
+    // and balances at other blocks are not known
+    assert!(service
+        .get_snapshot_balance(at(&alice, 4), GetSnapshotBalancePayload {
+            asset_id: shares.id.clone(),
+            user:     alice.clone(),
+            height:   3,
+        })
+        .is_err());
+
+    let snapshot = service
+        .get_snapshot_balance(at(&alice, 4), GetSnapshotBalancePayload {
+            asset_id: shares.id.clone(),
+            user:     alice.clone(),
+            height:   2,
+        })
+        .unwrap();
+    assert_eq!((snapshot.balance, snapshot.supply), (300, 1_000));
+    let snapshot = service
+        .get_snapshot_balance(at(&alice, 4), GetSnapshotBalancePayload {
+            asset_id: shares.id.clone(),
+            user:     alice.clone(),
+            height:   1,
+        })
+        .unwrap();
+    assert_eq!(snapshot.balance, 0);
+
+    let distribute = |height: u64| {
+        Versioned(DistributePayload {
+            asset_id:        shares.id.clone(),
+            funding_asset:   dividend.id.clone(),
+            total_amount:    100,
+            snapshot_height: height,
+        })
+    };
+    // the snapshot must be of a past block
+    assert!(service.distribute(at(&issuer, 4), distribute(4)).is_err());
+    let id = service.distribute(at(&issuer, 4), distribute(2)).unwrap();
+
+    let balance = |service: &AssetService<_>, user: &Address| {
+        service
+            .get_balance(at(user, 5), GetBalancePayload {
+                asset_id:  dividend.id.clone(),
+                user:      user.clone(),
+                formatted: false,
+            })
+            .unwrap()
+            .balance
+    };
+    service
+        .claim_distribution(at(&alice, 5), ClaimDistributionPayload { id })
+        .unwrap();
+    assert_eq!(balance(&service, &alice), 33);
+    assert!(service
+        .claim_distribution(at(&alice, 5), ClaimDistributionPayload { id })
+        .is_err());
+
+    let claim = service
+        .get_distribution_claim(at(&bob, 5), GetDistributionClaimPayload {
+            id,
+            holder: bob.clone(),
+        })
+        .unwrap();
+    assert_eq!((claim.amount, claim.claimed), (11, false));
+    service
+        .claim_distribution(at(&bob, 5), ClaimDistributionPayload { id })
+        .unwrap();
+    assert_eq!(balance(&service, &bob), 11);
+
+    let distribution = service
+        .get_distribution(at(&bob, 5), GetDistributionPayload { id })
+        .unwrap();
+    assert_eq!(distribution.claimed, 44);
+    assert_eq!(balance(&service, &issuer), 900);
+}
+
+#[test]
+fn test_max_supply() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, issuer.clone());
+
+    let mut service = new_asset_service();
+
+    let create = |supply: u128, max_supply: Option<u128>| {
+        Versioned(CreateAssetPayload {
+            name: "capped".to_owned(),
+            supply,
+            max_supply: max_supply.map(Amount),
+            transferable: true,
+        })
+    };
+    assert!(service
+        .create_asset(context.clone(), create(200, Some(100)))
+        .is_err());
+    let asset = service
+        .create_asset(context.clone(), create(100, Some(150)))
+        .unwrap();
+    assert_eq!(asset.max_supply, Some(Amount(150)));
+    // a cap changes the id, none keeps the id it had before caps existed
+    let uncapped = service
+        .create_asset(context.clone(), create(100, None))
+        .unwrap();
+    assert_ne!(asset.id, uncapped.id);
+    let payload_json = serde_json::to_string(&create(100, None).into_inner()).unwrap();
+    assert_eq!(payload_json, r#"{"name":"capped","supply":"100"}"#);
+
+    let minter_ctx = ContextBuilder::new(issuer.clone())
+        .cycles_limit(cycles_limit)
+        .extra(Bytes::from("crosschain"))
+        .build();
+    let mint = |token_id: &Hash, amount: u128| MintTokenPayload {
+        token_id: token_id.clone(),
+        receiver: issuer.clone(),
+        amount,
+        memo: String::new(),
+        source_tx: None,
+    };
+    service
+        .mint_token(minter_ctx.clone(), mint(&asset.id, 50))
+        .unwrap();
+    assert!(service
+        .mint_token(minter_ctx.clone(), mint(&asset.id, 1))
+        .is_err());
+    service
+        .mint_token(minter_ctx.clone(), mint(&uncapped.id, 1_000_000))
+        .unwrap();
+
+    let asset = service
+        .get_asset(context, GetAssetPayload { id: asset.id })
+        .unwrap();
+    assert_eq!(asset.supply, Amount(150));
+}
+
+#[test]
+fn test_checked_math() {
+    assert_eq!(
+        checked_add_u128(u128::max_value() - 1, 1).unwrap(),
+        u128::max_value()
+    );
+    assert!(checked_add_u128(u128::max_value(), 1).is_err());
+    assert_eq!(checked_sub_u128(1, 1).unwrap(), 0);
+    assert!(checked_sub_u128(0, 1).is_err());
+
+    let max = u128::max_value();
+    assert_eq!(mul_div(7, 3, 2), Some(10));
+    assert_eq!(mul_div(max, max, max), Some(max));
+    assert_eq!(mul_div(max, 3, 4), Some(max / 4 * 3 + 2));
+    assert_eq!(mul_div(max, 2, 1), None);
+    assert_eq!(mul_div(1, 1, 0), None);
+}
+
+#[test]
+fn test_transfer_max_value() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+    let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let to_context = mock_context(cycles_limit, to_address.clone());
+
+    let mut service = new_asset_service();
+
+    let asset = service
+        .create_asset(
+            context.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       u128::max_value(),
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    service
+        .transfer(
+            context.clone(),
+            Versioned(TransferPayload {
+                asset_id: asset.id.clone(),
+                to:       to_address.clone(),
+                value:    u128::max_value(),
+            }),
+        )
+        .unwrap();
+
+    let res = service.transfer(
+        to_context,
+        Versioned(TransferPayload {
+            asset_id: asset.id.clone(),
+            to:       caller.clone(),
+            value:    u128::max_value(),
+        }),
+    );
+    assert!(res.is_ok());
+
+    let balance_res = service
+        .get_balance(context, GetBalancePayload {
+            asset_id:  asset.id,
+            user:      caller,
+            formatted: false,
+        })
+        .unwrap();
+    assert_eq!(balance_res.balance, u128::max_value());
+}
+
+#[test]
+fn test_schema_migrations() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let user = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, admin.clone());
+    let params = ExecutorParams {
+        state_root:   Hash::from_empty(),
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+    };
+
+    // state from before schema versions were kept migrates in the next block
+    let mut service = new_asset_service();
+    let status = service.get_migration_status(context.clone()).unwrap();
+    assert_eq!(status, MigrationStatus {
+        schema_version: 0,
+        target_version: SCHEMA_VERSION,
+        pending:        vec!["allowance_entries".to_owned()],
+        cursor:         0,
+    });
+    service.before_block(&params).unwrap();
+    let status = service.get_migration_status(context.clone()).unwrap();
+    assert_eq!(status.schema_version, SCHEMA_VERSION);
+    assert!(status.pending.is_empty());
+
+    // a new chain starts at the current version
+    let mut service = new_asset_service();
+    service
+        .init_genesis(InitGenesisPayload {
+            id:                 Hash::digest(Bytes::from("asset")),
+            name:               "test".to_owned(),
+            supply:             1024,
+            issuer:             admin,
+            max_supply:         None,
+            self_transfer_noop: false,
+            unique_symbols:     false,
+            admin:              None,
+            minters:            vec![],
+            rbac:               false,
+            zero_value_policy:  ZeroValuePolicy::AllowWithEvent,
+            chain_binding:      None,
+        })
+        .unwrap();
+    let status = service.get_migration_status(context.clone()).unwrap();
+    assert_eq!(status.schema_version, SCHEMA_VERSION);
+
+    assert!(service
+        .migrate(mock_context(cycles_limit, user), MigratePayload {
+            limit: 10,
+        })
+        .is_err());
+    service
+        .migrate(context.clone(), MigratePayload { limit: 10 })
+        .unwrap();
+    let events = context.get_events();
+    let event: MigrationEvent = serde_json::from_str(&events[0].data).unwrap();
+    assert_eq!(
+        (event.from_version, event.version),
+        (SCHEMA_VERSION, SCHEMA_VERSION)
+    );
+}
+
+fn new_asset_service() -> AssetService<MockServiceSDK> {
+    AssetService::new(new_sdk()).unwrap()
+}
+
+fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    ContextBuilder::new(caller)
+        .cycles_limit(cycles_limit)
+        .build()
+}
+
+#[test]
+fn test_asset_metadata() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let alice = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            mock_context(cycles_limit, issuer.clone()),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1_000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    let set = |key: &str, value: &str| {
+        Versioned(SetAssetMetadataPayload {
+            asset_id: asset.id.clone(),
+            key:      key.to_owned(),
+            value:    value.to_owned(),
+        })
+    };
+    let get = |service: &AssetService<_>| {
+        service
+            .get_asset_metadata(
+                mock_context(cycles_limit, alice.clone()),
+                GetAssetMetadataPayload {
+                    asset_id: asset.id.clone(),
+                    key:      "icon".to_owned(),
+                },
+            )
+            .unwrap()
+            .value
+    };
+
+    assert_eq!(get(&service), "");
+
+    // only the issuer sets metadata, within the size limits
+    assert!(service
+        .set_asset_metadata(
+            mock_context(cycles_limit, alice.clone()),
+            set("icon", "https://x")
+        )
+        .is_err());
+    assert!(service
+        .set_asset_metadata(mock_context(cycles_limit, issuer.clone()), set("", "x"))
+        .is_err());
+    let too_large = "x".repeat(MAX_METADATA_VALUE_LEN + 1);
+    assert!(service
+        .set_asset_metadata(
+            mock_context(cycles_limit, issuer.clone()),
+            set("icon", &too_large)
+        )
+        .is_err());
+
+    // writes pay for each byte stored
+    let short = mock_context(cycles_limit, issuer.clone());
+    service
+        .set_asset_metadata(short.clone(), set("icon", "a"))
+        .unwrap();
+    let long = mock_context(cycles_limit, issuer.clone());
+    service
+        .set_asset_metadata(long.clone(), set("icon", "https://example.com/icon.png"))
+        .unwrap();
+    assert!(long.get_cycles_used() > short.get_cycles_used());
+    assert_eq!(get(&service), "https://example.com/icon.png");
+    assert_eq!(long.get_events().len(), 1);
+
+    // an empty value clears the entry
+    service
+        .set_asset_metadata(mock_context(cycles_limit, issuer.clone()), set("icon", ""))
+        .unwrap();
+    assert_eq!(get(&service), "");
+}
+
+#[test]
+fn test_formatted_amounts() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let alice = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    assert_eq!(Amount(1050).to_display(2), "10.50");
+    assert_eq!(Amount(5).to_display(3), "0.005");
+    assert_eq!(Amount(42).to_display(0), "42");
+    assert_eq!(
+        Amount(u128::max_value()).to_display(MAX_DECIMALS),
+        "3.40282366920938463463374607431768211455"
+    );
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            mock_context(cycles_limit, issuer.clone()),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1_000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    let balance = |service: &AssetService<_>, formatted: bool| {
+        service
+            .get_balance(
+                mock_context(cycles_limit, alice.clone()),
+                GetBalancePayload {
+                    asset_id: asset.id.clone(),
+                    user: issuer.clone(),
+                    formatted,
+                },
+            )
+            .unwrap()
+            .formatted_balance
+    };
+
+    // without decimals set amounts are whole tokens
+    assert_eq!(balance(&service, false), None);
+    assert_eq!(balance(&service, true), Some("1000".to_owned()));
+
+    let set_decimals = |value: &str| {
+        Versioned(SetAssetMetadataPayload {
+            asset_id: asset.id.clone(),
+            key:      DECIMALS_METADATA_KEY.to_owned(),
+            value:    value.to_owned(),
+        })
+    };
+    for invalid in &["two", "-1", "39"] {
+        assert!(service
+            .set_asset_metadata(
+                mock_context(cycles_limit, issuer.clone()),
+                set_decimals(invalid)
+            )
+            .is_err());
+    }
+    service
+        .set_asset_metadata(
+            mock_context(cycles_limit, issuer.clone()),
+            set_decimals("2"),
+        )
+        .unwrap();
+    assert_eq!(balance(&service, true), Some("10.00".to_owned()));
+
+    let context = mock_context(cycles_limit, issuer.clone());
+    service
+        .transfer(
+            context.clone(),
+            Versioned(TransferPayload {
+                asset_id: asset.id.clone(),
+                to:       alice.clone(),
+                value:    5,
+            }),
+        )
+        .unwrap();
+    let event: TransferEvent = serde_json::from_str(&context.get_events()[0].data).unwrap();
+    assert_eq!(event.formatted_value, Some("0.05".to_owned()));
+}
+
+#[test]
+fn test_access_sets() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let alice = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let bob = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let carol = Address::from_hex("0x888cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let dave = Address::from_hex("0x999cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            mock_context(cycles_limit, issuer.clone()),
+            Versioned(CreateAssetPayload {
+                name:         "test".to_owned(),
+                supply:       1_000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    let access = |service: &AssetService<_>, method: &str, caller: &Address, payload| {
+        service
+            .get_access_set(
+                mock_context(cycles_limit, caller.clone()),
+                GetAccessSetPayload {
+                    method:  method.to_owned(),
+                    caller:  caller.clone(),
+                    payload: serde_json::to_string(&payload).unwrap(),
+                },
+            )
+            .unwrap()
+    };
+    let transfer =
+        |to: &Address| serde_json::json!({ "asset_id": asset.id, "to": to, "value": "1" });
+
+    // transfers between other accounts run side by side, sharing one doesn't
+    let alice_to_bob = access(&service, "transfer", &alice, transfer(&bob));
+    let carol_to_dave = access(&service, "transfer", &carol, transfer(&dave));
+    let dave_to_bob = access(&service, "transfer", &dave, transfer(&bob));
+    assert!(!alice_to_bob.conflicts(&carol_to_dave));
+    assert!(alice_to_bob.conflicts(&dave_to_bob));
+    assert!(carol_to_dave.conflicts(&dave_to_bob));
+
+    // spending an allowance conflicts with approving it again
+    let approve = access(&service, "approve", &alice, transfer(&carol));
+    let spend = access(
+        &service,
+        "transfer_from",
+        &carol,
+        serde_json::json!({
+            "asset_id": asset.id,
+            "sender": alice,
+            "recipient": dave,
+            "value": "1",
+        }),
+    );
+    assert!(approve.conflicts(&spend));
+    assert!(!approve.conflicts(&carol_to_dave));
+
+    // writes without a declared set run alone
+    let create = access(&service, "create_asset", &alice, serde_json::json!({}));
+    assert!(create.exclusive);
+    assert!(create.conflicts(&alice_to_bob));
+
+    // a transfer fee credits its collector in every transfer of the asset
+    service
+        .set_transfer_fee(
+            mock_context(cycles_limit, issuer.clone()),
+            Versioned(TransferFee {
+                asset_id:  asset.id.clone(),
+                rate_bps:  100,
+                collector: issuer.clone(),
+                exempt:    vec![],
+            }),
+        )
+        .unwrap();
+    let alice_to_bob = access(&service, "transfer", &alice, transfer(&bob));
+    let carol_to_dave = access(&service, "transfer", &carol, transfer(&dave));
+    assert!(alice_to_bob.conflicts(&carol_to_dave));
+}
+
+#[test]
+fn test_params() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let issuer = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let admin_ctx = mock_context(cycles_limit, admin.clone());
+    let issuer_ctx = mock_context(cycles_limit, issuer.clone());
+
+    let mut service = new_asset_service();
+    service
+        .init_genesis(InitGenesisPayload {
+            id:                 Hash::digest(Bytes::from("native")),
+            name:               "native".to_owned(),
+            supply:             1024,
+            issuer:             admin.clone(),
+            max_supply:         None,
+            self_transfer_noop: false,
+            unique_symbols:     false,
+            admin:              None,
+            minters:            vec![],
+            rbac:               false,
+            zero_value_policy:  ZeroValuePolicy::AllowWithEvent,
+            chain_binding:      None,
+        })
+        .unwrap();
+    let asset = service
+        .create_asset(
+            issuer_ctx.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "tunable".to_owned(),
+                supply:       1_000_000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    let params = service.get_params(admin_ctx.clone()).unwrap();
+    assert_eq!(params.max_transfer_fee_bps, MAX_TRANSFER_FEE_BPS);
+    assert_eq!(params.max_metadata_value_len, MAX_METADATA_VALUE_LEN as u64);
+
+    let lower = SetParamsPayload {
+        max_transfer_fee_bps: Some(100),
+        max_metadata_value_len: Some(4),
+        ..SetParamsPayload::default()
+    };
+    // only the admin sets parameters, within their range
+    assert!(service
+        .set_params(issuer_ctx.clone(), lower.clone())
+        .is_err());
+    assert!(service
+        .set_params(admin_ctx.clone(), SetParamsPayload {
+            max_transfer_fee_bps: Some(MAX_TRANSFER_FEE_BPS + 1),
+            ..SetParamsPayload::default()
+        })
+        .is_err());
+    assert!(service
+        .set_params(admin_ctx.clone(), SetParamsPayload {
+            max_metadata_value_len: Some(0),
+            ..SetParamsPayload::default()
+        })
+        .is_err());
+
+    service.set_params(admin_ctx.clone(), lower).unwrap();
+    assert_eq!(service.get_params(admin_ctx.clone()).unwrap(), Params {
+        max_transfer_fee_bps:   100,
+        max_metadata_value_len: 4,
+        minters:                vec![],
+        hold_services:          vec![],
+    });
+
+    let fee = |rate_bps: u32| {
+        Versioned(TransferFee {
+            asset_id: asset.id.clone(),
+            rate_bps,
+            collector: issuer.clone(),
+            exempt: vec![],
+        })
+    };
+    assert!(service
+        .set_transfer_fee(issuer_ctx.clone(), fee(250))
+        .is_err());
+    service
+        .set_transfer_fee(issuer_ctx.clone(), fee(100))
+        .unwrap();
+
+    let metadata = |value: &str| {
+        Versioned(SetAssetMetadataPayload {
+            asset_id: asset.id.clone(),
+            key:      "icon".to_owned(),
+            value:    value.to_owned(),
+        })
+    };
+    assert!(service
+        .set_asset_metadata(issuer_ctx.clone(), metadata("https://x"))
+        .is_err());
+    service
+        .set_asset_metadata(issuer_ctx, metadata("x"))
+        .unwrap();
+
+    // a changed parameter stays changed
+    service
+        .set_params(admin_ctx.clone(), SetParamsPayload {
+            max_metadata_value_len: Some(8),
+            ..SetParamsPayload::default()
+        })
+        .unwrap();
+    let params = service.get_params(admin_ctx).unwrap();
+    assert_eq!(params.max_transfer_fee_bps, 100);
+    assert_eq!(params.max_metadata_value_len, 8);
+}
+
+#[test]
+fn test_locked_transfer() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let alice = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let at = |caller: &Address, height: u64| {
+        ContextBuilder::new(caller.clone())
+            .cycles_limit(cycles_limit)
+            .height(height)
+            .build()
+    };
+
+    let mut service = new_asset_service();
+    let asset = service
+        .create_asset(
+            at(&issuer, 1),
+            Versioned(CreateAssetPayload {
+                name:         "launch".to_owned(),
+                supply:       1_000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+    let lock = |value: u128, unlock_height: u64| {
+        Versioned(TransferLockedPayload {
+            asset_id: asset.id.clone(),
+            to: alice.clone(),
+            value,
+            unlock_height,
+        })
+    };
+    let locked_balance = |service: &AssetService<MockServiceSDK>, height: u64| {
+        service
+            .get_locked_balance(at(&alice, height), GetLockedBalancePayload {
+                asset_id: asset.id.clone(),
+                user:     alice.clone(),
+            })
+            .unwrap()
+    };
+
+    // the unlock height must be ahead of the chain
+    assert!(service
+        .transfer_locked_until(at(&issuer, 5), lock(100, 5))
+        .is_err());
+    assert!(service
+        .transfer_locked_until(at(&issuer, 5), lock(2_000, 10))
+        .is_err());
+
+    service
+        .transfer_locked_until(at(&issuer, 5), lock(100, 10))
+        .unwrap();
+    service
+        .transfer_locked_until(at(&issuer, 6), lock(50, 20))
+        .unwrap();
+    // a second lock at a height adds to the first
+    service
+        .transfer_locked_until(at(&issuer, 6), lock(20, 10))
+        .unwrap();
+
+    let balance = locked_balance(&service, 9);
+    assert_eq!(
+        (balance.liquid, balance.claimable, balance.locked),
+        (0, 0, 170)
+    );
+    assert_eq!(balance.locks.len(), 2);
+    assert_eq!(
+        service
+            .get_balance(at(&issuer, 9), GetBalancePayload {
+                asset_id:  asset.id.clone(),
+                user:      lock_pool().unwrap(),
+                formatted: false,
+            })
+            .unwrap()
+            .balance,
+        170
+    );
+
+    let claim = ClaimUnlockedPayload {
+        asset_id: asset.id.clone(),
+    };
+    assert!(service
+        .claim_unlocked(at(&alice, 9), claim.clone())
+        .is_err());
+
+    let balance = locked_balance(&service, 10);
+    assert_eq!(
+        (balance.liquid, balance.claimable, balance.locked),
+        (0, 120, 50)
+    );
+    service
+        .claim_unlocked(at(&alice, 10), claim.clone())
+        .unwrap();
+    let balance = locked_balance(&service, 10);
+    assert_eq!(
+        (balance.liquid, balance.claimable, balance.locked),
+        (120, 0, 50)
+    );
+    assert_eq!(balance.locks, vec![Lock {
+        value:         50,
+        unlock_height: 20,
+    }]);
+    assert!(service
+        .claim_unlocked(at(&alice, 15), claim.clone())
+        .is_err());
+
+    service.claim_unlocked(at(&alice, 25), claim).unwrap();
+    let balance = locked_balance(&service, 25);
+    assert_eq!(
+        (balance.liquid, balance.claimable, balance.locked),
+        (170, 0, 0)
+    );
+    assert!(balance.locks.is_empty());
+}
+
+#[test]
+fn test_holds() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let alice = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let bob = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let asset_id = Hash::digest(Bytes::from("native"));
+
+    let mut service = new_asset_service();
+    service
+        .init_genesis(InitGenesisPayload {
+            id:                 asset_id.clone(),
+            name:               "native".to_owned(),
+            supply:             1_000,
+            issuer:             admin.clone(),
+            max_supply:         None,
+            self_transfer_noop: false,
+            unique_symbols:     false,
+            admin:              None,
+            minters:            vec![],
+            rbac:               false,
+            zero_value_policy:  ZeroValuePolicy::AllowWithEvent,
+            chain_binding:      None,
+        })
+        .unwrap();
+    let service_ctx = |caller: &Address, name: &'static str| {
+        ContextBuilder::new(caller.clone())
+            .cycles_limit(cycles_limit)
+            .extra(Bytes::from(name))
+            .build()
+    };
+    let balance = |service: &AssetService<MockServiceSDK>, user: &Address| {
+        service
+            .get_balance(
+                mock_context(cycles_limit, admin.clone()),
+                GetBalancePayload {
+                    asset_id:  asset_id.clone(),
+                    user:      user.clone(),
+                    formatted: false,
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    let hold = |amount: u128| HoldPayload {
+        asset_id: asset_id.clone(),
+        owner: admin.clone(),
+        amount,
+    };
+
+    // only services the admin allows hold funds
+    assert!(service.hold(service_ctx(&admin, "dex"), hold(100)).is_err());
+    let hold_services = HoldServices {
+        services: vec!["dex".to_owned()],
+    };
+    let set_hold_services = SetParamsPayload {
+        hold_services: Some(hold_services.services.clone()),
+        ..SetParamsPayload::default()
+    };
+    assert!(service
+        .set_params(
+            mock_context(cycles_limit, alice.clone()),
+            set_hold_services.clone()
+        )
+        .is_err());
+    service
+        .set_params(mock_context(cycles_limit, admin.clone()), set_hold_services)
+        .unwrap();
+    assert_eq!(
+        service
+            .get_hold_services(mock_context(cycles_limit, alice.clone()))
+            .unwrap(),
+        hold_services
+    );
+
+    // the owner sends the transaction placing the hold
+    assert!(service.hold(service_ctx(&alice, "dex"), hold(100)).is_err());
+    assert!(service
+        .hold(service_ctx(&admin, "dex"), hold(2_000))
+        .is_err());
+    let first = service.hold(service_ctx(&admin, "dex"), hold(100)).unwrap();
+    let second = service.hold(service_ctx(&admin, "dex"), hold(300)).unwrap();
+    assert_eq!((first, second), (1, 2));
+    assert_eq!(balance(&service, &admin), 600);
+    assert_eq!(balance(&service, &hold_pool().unwrap()), 400);
+
+    // held funds can't be spent
+    assert!(service
+        .transfer(
+            mock_context(cycles_limit, admin.clone()),
+            Versioned(TransferPayload {
+                asset_id: asset_id.clone(),
+                to:       alice.clone(),
+                value:    700,
+            })
+        )
+        .is_err());
+
+    // only the holding service settles a hold, once
+    assert!(service
+        .release_hold(
+            mock_context(cycles_limit, admin.clone()),
+            ReleaseHoldPayload { id: first }
+        )
+        .is_err());
+    service
+        .release_hold(service_ctx(&bob, "dex"), ReleaseHoldPayload { id: first })
+        .unwrap();
+    assert!(service
+        .release_hold(service_ctx(&bob, "dex"), ReleaseHoldPayload { id: first })
+        .is_err());
+    assert_eq!(balance(&service, &admin), 700);
+
+    let execute = ExecuteHoldPayload {
+        id: second,
+        to: bob.clone(),
+    };
+    let ctx = service_ctx(&alice, "dex");
+    service.execute_hold(ctx.clone(), execute.clone()).unwrap();
+    let event: HoldEvent = serde_json::from_str(&ctx.get_events().last().unwrap().data).unwrap();
+    assert_eq!((event.amount, event.to), (300, Some(bob.clone())));
+    assert!(service
+        .execute_hold(service_ctx(&alice, "dex"), execute)
+        .is_err());
+    assert!(service
+        .get_hold(ctx, GetHoldPayload { id: second })
+        .is_err());
+
+    assert_eq!(balance(&service, &admin), 700);
+    assert_eq!(balance(&service, &bob), 300);
+    assert_eq!(balance(&service, &hold_pool().unwrap()), 0);
+}
+
+#[test]
+fn test_issuance_log() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let user = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let ctx = mock_context(cycles_limit, issuer.clone());
+    let bridge_ctx = ContextBuilder::new(user.clone())
+        .cycles_limit(cycles_limit)
+        .extra(Bytes::from("crosschain"))
+        .build();
+
+    let mut service = new_asset_service();
+    service
+        .init_genesis(InitGenesisPayload {
+            id:                 Hash::digest(Bytes::from("native")),
+            name:               "native".to_owned(),
+            supply:             1024,
+            issuer:             issuer.clone(),
+            max_supply:         None,
+            self_transfer_noop: false,
+            unique_symbols:     false,
+            admin:              None,
+            minters:            vec!["crosschain".to_owned()],
+            rbac:               false,
+            zero_value_policy:  ZeroValuePolicy::AllowWithEvent,
+            chain_binding:      None,
+        })
+        .unwrap();
+    let asset = service
+        .create_asset(
+            ctx.clone(),
+            Versioned(CreateAssetPayload {
+                name:         "audited".to_owned(),
+                supply:       1_000,
+                max_supply:   None,
+                transferable: true,
+            }),
+        )
+        .unwrap();
+
+    let source_tx = Hash::digest(Bytes::from("ckb tx"));
+    service
+        .mint_token(bridge_ctx.clone(), MintTokenPayload {
+            token_id:  asset.id.clone(),
+            receiver:  user.clone(),
+            amount:    500,
+            memo:      String::new(),
+            source_tx: Some(source_tx.clone()),
+        })
+        .unwrap();
+    service
+        .burn_token(bridge_ctx, BurnTokenPayload {
+            token_id: asset.id.clone(),
+            user:     user.clone(),
+            amount:   200,
+        })
+        .unwrap();
+    service
+        .approve(
+            mock_context(cycles_limit, user.clone()),
+            Versioned(ApprovePayload {
+                asset_id: asset.id.clone(),
+                to:       issuer.clone(),
+                value:    100,
+            }),
+        )
+        .unwrap();
+    service
+        .burn_from(
+            ctx.clone(),
+            Versioned(BurnFromPayload {
+                asset_id: asset.id.clone(),
+                owner:    user.clone(),
+                amount:   100,
+            }),
+        )
+        .unwrap();
+
+    let log = |offset: u64, limit: u64| {
+        service
+            .get_issuance_log(ctx.clone(), GetIssuanceLogPayload {
+                asset_id: asset.id.clone(),
+                offset,
+                limit,
+            })
+            .unwrap()
+    };
+    let all = log(0, 10);
+    assert_eq!(all.len, 4);
+    let summary: Vec<_> = all
+        .records
+        .iter()
+        .map(|r| {
+            (
+                r.seq,
+                r.action.as_str(),
+                r.cause.as_str(),
+                r.amount,
+                r.supply,
+            )
+        })
+        .collect();
+    assert_eq!(summary, vec![
+        (1, "create", "issuer", 1_000, 1_000),
+        (2, "mint", "crosschain", 500, 1_500),
+        (3, "burn", "crosschain", 200, 1_300),
+        (4, "burn", "burn_from", 100, 1_200),
+    ]);
+    assert_eq!(all.records[1].source_tx, Some(source_tx));
+    assert_eq!(all.records[3].account, user);
+
+    let page = log(1, 2);
+    assert_eq!(
+        page.records.iter().map(|r| r.seq).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert!(log(4, 10).records.is_empty());
+
+    // the native asset's log starts at genesis
+    let native = service
+        .get_issuance_log(ctx, GetIssuanceLogPayload {
+            asset_id: Hash::digest(Bytes::from("native")),
+            offset:   0,
+            limit:    10,
+        })
+        .unwrap();
+    assert_eq!(native.records[0].cause, "genesis");
+    assert_eq!(native.records[0].supply, 1024);
+}
+
+#[test]
+fn test_transfer_by_signature() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let priv_key = Secp256k1PrivateKey::try_from([1u8; 32].as_ref()).unwrap();
+    let pubkey = priv_key.pub_key().to_bytes();
+    let owner = Address::from_pubkey_bytes(pubkey.clone()).unwrap();
+    let relayer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let to = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let asset_id =
+        Hash::from_hex("0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c")
+            .unwrap();
+    let chain_id = Hash::digest(Bytes::from("muta"));
+
+    let genesis = |zero_value_policy, chain_binding| {
+        let mut service = new_asset_service();
+        service
+            .init_genesis(InitGenesisPayload {
+                id: asset_id.clone(),
+                name: "test".to_owned(),
+                supply: 1_000,
+                issuer: owner.clone(),
+                max_supply: None,
+                self_transfer_noop: false,
+                unique_symbols: false,
+                admin: None,
+                minters: vec![],
+                rbac: false,
+                zero_value_policy,
+                chain_binding,
+            })
+            .unwrap();
+        service
+    };
+    let hex = |bytes: &[u8]| Hex::from_string(format!("0x{}", hex::encode(bytes))).unwrap();
+    let signed = |value, nonce, deadline, chain_id: &Hash| {
+        let mut payload = MetaTxPayload {
+            asset_id: asset_id.clone(),
+            from: owner.clone(),
+            to: to.clone(),
+            value,
+            fee: 10,
+            nonce,
+            deadline,
+            pubkey: hex(pubkey.as_ref()),
+            signature: hex(&[]),
+        };
+        let hash = meta_tx_hash(&payload, chain_id, "service_name");
+        let signature =
+            Secp256k1::sign_message(hash.as_bytes().as_ref(), priv_key.to_bytes().as_ref())
+                .unwrap();
+        payload.signature = hex(signature.as_ref());
+        Versioned(payload)
+    };
+    let relay = |height| {
+        ContextBuilder::new(relayer.clone())
+            .cycles_limit(cycles_limit)
+            .height(height)
+            .build()
+    };
+    let balance_of = |service: &AssetService<MockServiceSDK>, user: &Address| {
+        service
+            .get_balance(
+                mock_context(cycles_limit, user.clone()),
+                GetBalancePayload {
+                    asset_id:  asset_id.clone(),
+                    user:      user.clone(),
+                    formatted: false,
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    let nonce_of = |service: &AssetService<MockServiceSDK>| {
+        service
+            .get_meta_nonce(relay(5), GetMetaNoncePayload {
+                user: owner.clone(),
+            })
+            .unwrap()
+            .nonce
+    };
+
+    let binding = Some(ChainBinding {
+        source_chain_id: Hash::digest(Bytes::from("ckb")),
+        dest_chain_id:   chain_id.clone(),
+    });
+    let other_chain_id = Hash::digest(Bytes::from("other muta"));
+
+    // an unbound service takes no signature, one would hold on every
+    // unbound chain
+    let mut service = genesis(ZeroValuePolicy::AllowWithEvent, None);
+    assert!(service
+        .transfer_by_signature(relay(5), signed(100, 0, 5, &chain_id))
+        .is_err());
+
+    let mut service = genesis(ZeroValuePolicy::AllowWithEvent, binding.clone());
+    let context = relay(5);
+    service
+        .transfer_by_signature(context.clone(), signed(100, 0, 5, &chain_id))
+        .unwrap();
+    assert_eq!(context.get_events().len(), 1);
+    assert_eq!(balance_of(&service, &owner), 890);
+    assert_eq!(balance_of(&service, &to), 100);
+    assert_eq!(balance_of(&service, &relayer), 10);
+    assert_eq!(nonce_of(&service), 1);
+
+    // replayed, expired, signed for another chain, or changed after signing
+    assert!(service
+        .transfer_by_signature(relay(5), signed(100, 0, 5, &chain_id))
+        .is_err());
+    assert!(service
+        .transfer_by_signature(relay(6), signed(100, 1, 5, &chain_id))
+        .is_err());
+    assert!(service
+        .transfer_by_signature(relay(5), signed(100, 1, 5, &other_chain_id))
+        .is_err());
+    let mut tampered = signed(100, 1, 5, &chain_id).into_inner();
+    tampered.value = 200;
+    assert!(service
+        .transfer_by_signature(relay(5), Versioned(tampered))
+        .is_err());
+    assert_eq!(balance_of(&service, &owner), 890);
+
+    service
+        .transfer_by_signature(relay(5), signed(100, 1, 5, &chain_id))
+        .unwrap();
+    assert_eq!(balance_of(&service, &relayer), 20);
+
+    // a silent zero value transfer still uses the nonce and pays the relayer
+    let mut service = genesis(ZeroValuePolicy::AllowSilent, binding);
+    service
+        .transfer_by_signature(relay(5), signed(0, 0, 5, &chain_id))
+        .unwrap();
+    assert_eq!(nonce_of(&service), 1);
+    assert_eq!(balance_of(&service, &to), 0);
+    assert_eq!(balance_of(&service, &relayer), 10);
 }
@@ -0,0 +1,116 @@
+use proptest::prelude::*;
+
+use common::versioned::Versioned;
+use protocol::types::{Address, Bytes};
+use testing::strategy::{address_from_pool, pool_address};
+use testing::ContextBuilder;
+
+use crate::types::{
+    BurnTokenPayload, CreateAssetPayload, GetAssetPayload, GetBalancePayload, MintTokenPayload,
+    TransferPayload,
+};
+
+use super::new_asset_service;
+
+const POOL_SIZE: usize = 4;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Transfer {
+        from:  Address,
+        to:    Address,
+        value: u128,
+    },
+    Mint {
+        to:     Address,
+        amount: u128,
+    },
+    Burn {
+        from:   Address,
+        amount: u128,
+    },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let amount = 0..1_000_000u128;
+    prop_oneof![
+        (
+            address_from_pool(POOL_SIZE),
+            address_from_pool(POOL_SIZE),
+            amount.clone()
+        )
+            .prop_map(|(from, to, value)| Op::Transfer { from, to, value }),
+        (address_from_pool(POOL_SIZE), amount.clone())
+            .prop_map(|(to, amount)| Op::Mint { to, amount }),
+        (address_from_pool(POOL_SIZE), amount).prop_map(|(from, amount)| Op::Burn { from, amount }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn prop_supply_equals_sum_of_balances(
+        issuer in address_from_pool(POOL_SIZE),
+        supply in 0..10_000_000u128,
+        ops in prop::collection::vec(op(), 0..32),
+    ) {
+        let mut service = new_asset_service();
+        let asset = service
+            .create_asset(
+                ContextBuilder::new(issuer.clone()).build(),
+                Versioned(CreateAssetPayload {
+                    name: "test".to_owned(),
+                    supply,
+                    max_supply: None,
+                    transferable: true,
+                }),
+            )
+            .unwrap();
+
+        // The crosschain service is the default minter.
+        let bridge_ctx = || {
+            ContextBuilder::new(issuer.clone())
+                .extra(Bytes::from("crosschain"))
+                .build()
+        };
+
+        for op in ops {
+            // Failed operations must leave state untouched, so errors are
+            // deliberately ignored here.
+            let _ = match op {
+                Op::Transfer { from, to, value } => service.transfer(
+                    ContextBuilder::new(from).build(),
+                    Versioned(TransferPayload { asset_id: asset.id.clone(), to, value }),
+                ),
+                Op::Mint { to, amount } => service.mint_token(bridge_ctx(), MintTokenPayload {
+                    token_id: asset.id.clone(),
+                    receiver: to,
+                    amount,
+                    memo: String::new(),
+                    source_tx: None,
+                }),
+                Op::Burn { from, amount } => service.burn_token(bridge_ctx(), BurnTokenPayload {
+                    token_id: asset.id.clone(),
+                    user: from,
+                    amount,
+                }),
+            };
+        }
+
+        let ctx = ContextBuilder::new(issuer.clone()).build();
+        let mut total = 0u128;
+        for i in 0..POOL_SIZE {
+            let user = pool_address(i);
+            total += service
+                .get_balance(ctx.clone(), GetBalancePayload { asset_id: asset.id.clone(), user, formatted: false })
+                .unwrap()
+                .balance;
+        }
+        let supply = service
+            .get_asset(ctx, GetAssetPayload { id: asset.id })
+            .unwrap()
+            .supply
+            .0;
+
+        prop_assert_eq!(total, supply);
+    }
+}
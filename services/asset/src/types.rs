@@ -1,132 +1,713 @@
 use std::collections::BTreeMap;
-use std::mem;
 
-use byteorder::{ByteOrder, LittleEndian};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use bytes::Bytes;
 
+use common::amount::Amount;
+use common::versioned::PayloadVersion;
 use protocol::fixed_codec::{FixedCodec, FixedCodecError};
-use protocol::types::{Address, Hash};
+use protocol::types::{Address, Hash, Hex};
 use protocol::ProtocolResult;
 
+pub use common::chain::ChainBinding;
+pub use common::view::{GetViewPayload, ReadView, TruncatedField, ViewField};
+
 /// Payload
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct InitGenesisPayload {
-    pub id:     Hash,
-    pub name:   String,
-    pub supply: u128,
-    pub issuer: Address,
+    #[schemars(with = "String")]
+    pub id:                 Hash,
+    pub name:               String,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub supply:             u128,
+    #[schemars(with = "String")]
+    pub issuer:             Address,
+    #[serde(default)]
+    pub max_supply:         Option<Amount>,
+    // treat transfers to oneself as successful no-ops instead of failing
+    #[serde(default)]
+    pub self_transfer_noop: bool,
+    // reject assets named like an existing one, ignoring case
+    #[serde(default)]
+    pub unique_symbols:     bool,
+    // may change the minters, the issuer when unset
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub admin:              Option<Address>,
+    #[serde(default = "default_minters")]
+    pub minters:            Vec<String>,
+    // checks the admin against the rbac service's admin role
+    #[serde(default)]
+    pub rbac:               bool,
+    // what transfers, approvals and burns of nothing do
+    #[serde(default)]
+    pub zero_value_policy:  ZeroValuePolicy,
+    // the muta chain meta transactions are signed for, unbound when unset,
+    // which refuses them
+    #[serde(default)]
+    pub chain_binding:      Option<ChainBinding>,
+}
+
+/// How a transfer, approval or burn of a zero value is treated. Such calls
+/// change nothing but still emit events, which indexers would show. Holds
+/// of nothing are refused under any policy, and `transfer_and_call` makes
+/// its call even when the transfer is skipped.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZeroValuePolicy {
+    // fail the call
+    Reject,
+    // succeed without writing or emitting anything
+    AllowSilent,
+    // succeed and emit the usual event
+    AllowWithEvent,
+}
+
+impl Default for ZeroValuePolicy {
+    fn default() -> Self {
+        ZeroValuePolicy::AllowWithEvent
+    }
+}
+
+/// Service trusted to mint and burn when genesis names none.
+pub const DEFAULT_MINTER: &str = "crosschain";
+
+pub fn default_minters() -> Vec<String> {
+    vec![DEFAULT_MINTER.to_owned()]
+}
+
+pub fn default_transferable() -> bool {
+    true
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+fn is_transferable(transferable: &bool) -> bool {
+    *transferable
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct MintTokenPayload {
-    pub token_id: Hash,
-    pub receiver: Address,
-    pub amount:   u128,
+    #[schemars(with = "String")]
+    pub token_id:  Hash,
+    #[schemars(with = "String")]
+    pub receiver:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:    u128,
+    #[serde(default)]
+    pub memo:      String,
+    // source chain tx the mint relays, kept in the issuance log
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub source_tx: Option<Hash>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CreateMirrorAssetPayload {
+    #[schemars(with = "String")]
+    pub id:       Hash,
+    pub name:     String,
+    #[schemars(with = "String")]
+    pub issuer:   Address,
+    // stored as the asset's "decimals" metadata
+    #[serde(default)]
+    pub decimals: Option<u64>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct BurnTokenPayload {
+    #[schemars(with = "String")]
     pub token_id: Hash,
+    #[schemars(with = "String")]
     pub user:     Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub amount:   u128,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CreateAssetPayload {
-    pub name:   String,
-    pub supply: u128,
+    pub name:         String,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub supply:       u128,
+    // most the supply may grow to by minting, uncapped when unset; left out
+    // of the json when unset so the ids of uncapped assets stay as they were
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_supply:   Option<Amount>,
+    // false makes the asset soulbound, only its issuer may move it; left out
+    // of the json when true for the same reason as `max_supply`
+    #[serde(
+        default = "default_transferable",
+        skip_serializing_if = "is_transferable"
+    )]
+    pub transferable: bool,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct GetAssetPayload {
+    #[schemars(with = "String")]
     pub id: Hash,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct TransferPayload {
+    #[schemars(with = "String")]
     pub asset_id: Hash,
+    #[schemars(with = "String")]
     pub to:       Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub value:    u128,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct TransferEvent {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[schemars(with = "String")]
+    pub from:            Address,
+    #[schemars(with = "String")]
+    pub to:              Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:           u128,
+    // the value in whole tokens, for assets whose issuer set their decimals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted_value: Option<String>,
+}
+
+/// Moves `value` to the account of `to_service` then calls its `on_transfer`
+/// with `payload`, both in the caller's transaction.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct TransferAndCallPayload {
+    #[schemars(with = "String")]
+    pub asset_id:   Hash,
+    pub to_service: String,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:      u128,
+    pub payload:    String,
+}
+
+/// What the service called by `transfer_and_call` gets, after `value` of
+/// `asset_id` reached its account from `sender`, net of any transfer fee.
+/// `payload` is the one the sender gave.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OnTransferPayload {
+    #[schemars(with = "String")]
     pub asset_id: Hash,
-    pub from:     Address,
-    pub to:       Address,
+    #[schemars(with = "String")]
+    pub sender:   Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub value:    u128,
+    pub payload:  String,
 }
 
 pub type ApprovePayload = TransferPayload;
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct ApproveEvent {
+    #[schemars(with = "String")]
     pub asset_id: Hash,
+    #[schemars(with = "String")]
     pub grantor:  Address,
+    #[schemars(with = "String")]
     pub grantee:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub value:    u128,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RevokeAllApprovalsPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RevokeAllApprovalsEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub grantor:  Address,
+    #[schemars(with = "Vec<String>")]
+    pub grantees: Vec<Address>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct TransferFromPayload {
+    #[schemars(with = "String")]
     pub asset_id:  Hash,
+    #[schemars(with = "String")]
     pub sender:    Address,
+    #[schemars(with = "String")]
     pub recipient: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub value:     u128,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct TransferFromEvent {
+    #[schemars(with = "String")]
+    pub asset_id:            Hash,
+    #[schemars(with = "String")]
+    pub caller:              Address,
+    #[schemars(with = "String")]
+    pub sender:              Address,
+    #[schemars(with = "String")]
+    pub recipient:           Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:               u128,
+    // allowance of the caller left after the spend
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub remaining_allowance: u128,
+}
+
+/// Emitted whenever an allowance changes, by approve, transfer_from,
+/// burn_from or revoke_all_approvals, which `reason` names.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct AllowanceChangedEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub grantor:  Address,
+    #[schemars(with = "String")]
+    pub grantee:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub previous: u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:    u128,
+    pub reason:   String,
+}
+
+/// Lets `session_key` transfer up to `budget` of the caller's asset until
+/// block `expires_at`. Authorizing a key again replaces its budget and
+/// expiry.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct AuthorizeSessionKeyPayload {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub session_key: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub budget:      u128,
+    pub expires_at:  u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RevokeSessionKeyPayload {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub session_key: Address,
+}
+
+/// Emitted when a session key is authorized or revoked, a revoked key
+/// having no budget left.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SessionKeyEvent {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub authorizer:  Address,
+    #[schemars(with = "String")]
+    pub session_key: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub budget:      u128,
+    pub expires_at:  u64,
+}
+
+/// A transfer from `authorizer`, sent by one of its session keys.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SessionTransferPayload {
+    #[schemars(with = "String")]
+    pub asset_id:   Hash,
+    #[schemars(with = "String")]
+    pub authorizer: Address,
+    #[schemars(with = "String")]
+    pub to:         Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:      u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SessionTransferEvent {
+    #[schemars(with = "String")]
+    pub asset_id:         Hash,
+    #[schemars(with = "String")]
+    pub session_key:      Address,
+    #[schemars(with = "String")]
+    pub authorizer:       Address,
+    #[schemars(with = "String")]
+    pub to:               Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:            u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub remaining_budget: u128,
+}
+
+/// A transfer the owner `from` signed with `pubkey`, sent by a relayer who
+/// takes `fee` of the asset. Valid until block `deadline`, with the next
+/// meta nonce of `from`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MetaTxPayload {
+    #[schemars(with = "String")]
     pub asset_id:  Hash,
-    pub caller:    Address,
-    pub sender:    Address,
-    pub recipient: Address,
+    #[schemars(with = "String")]
+    pub from:      Address,
+    #[schemars(with = "String")]
+    pub to:        Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub value:     u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub fee:       u128,
+    pub nonce:     u64,
+    pub deadline:  u64,
+    #[schemars(with = "String")]
+    pub pubkey:    Hex,
+    #[schemars(with = "String")]
+    pub signature: Hex,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct GetBalancePayload {
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MetaTransferEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub from:     Address,
+    #[schemars(with = "String")]
+    pub to:       Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:    u128,
+    #[schemars(with = "String")]
+    pub relayer:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub fee:      u128,
+    pub nonce:    u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMetaNoncePayload {
+    #[schemars(with = "String")]
+    pub user: Address,
+}
+
+/// The nonce the next meta transaction of `user` must carry.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MetaNonceResponse {
+    #[schemars(with = "String")]
+    pub user:  Address,
+    pub nonce: u64,
+}
+
+/// Emitted for every mint, whichever minter service made it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MintEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub receiver: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+    pub memo:     String,
+    pub minter:   String,
+}
+
+/// Burns `amount` of `owner`'s asset out of the allowance `owner` granted
+/// the caller.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnFromPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub owner:    Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnFromEvent {
+    #[schemars(with = "String")]
+    pub asset_id:            Hash,
+    #[schemars(with = "String")]
+    pub caller:              Address,
+    #[schemars(with = "String")]
+    pub owner:               Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:              u128,
+    // allowance of the caller left after the burn
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub remaining_allowance: u128,
+}
+
+/// A change of the supply of an asset, numbered from 1 for each asset in the
+/// order they happened, so the log adds up to the supply.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct IssuanceRecord {
+    pub seq:       u64,
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    pub action:    String, // "create", "mint" or "burn"
+    // "genesis", "issuer" for assets created by their issuer, "burn_from",
+    // else the minter service minting or burning
+    pub cause:     String,
+    // account credited or debited
+    #[schemars(with = "String")]
+    pub account:   Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:    u128,
+    // supply once the change is made
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub supply:    u128,
+    pub height:    u64,
+    #[schemars(with = "Option<String>")]
+    pub source_tx: Option<Hash>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetIssuanceLogPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    pub offset:   u64,
+    pub limit:    u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct IssuanceLog {
+    #[schemars(with = "String")]
     pub asset_id: Hash,
+    // records of the asset in all
+    pub len:      u64,
+    pub records:  Vec<IssuanceRecord>,
+}
+
+/// Emitted for every burn, whichever minter service made it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
     pub user:     Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+    pub burner:   String,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct GetBalanceResponse {
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetBalancePayload {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    #[schemars(with = "String")]
+    pub user:      Address,
+    // adds the balance in whole tokens, by the asset's decimals
+    #[serde(default)]
+    pub formatted: bool,
+}
+
+/// Payload of `balance_of`, read by other services. Fields are only ever
+/// added with a serde default, never renamed or removed.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BalanceOfPayload {
+    #[schemars(with = "String")]
     pub asset_id: Hash,
+    #[schemars(with = "String")]
     pub user:     Address,
-    pub balance:  u128,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetBalanceResponse {
+    #[schemars(with = "String")]
+    pub asset_id:          Hash,
+    #[schemars(with = "String")]
+    pub user:              Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub balance:           u128,
+    // set when the payload asks for it, "10.50" for 1050 of 2 decimals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted_balance: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct GetAllowancePayload {
+    #[schemars(with = "String")]
     pub asset_id: Hash,
+    #[schemars(with = "String")]
     pub grantor:  Address,
+    #[schemars(with = "String")]
     pub grantee:  Address,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct GetAllowanceResponse {
+    #[schemars(with = "String")]
     pub asset_id: Hash,
+    #[schemars(with = "String")]
     pub grantor:  Address,
+    #[schemars(with = "String")]
     pub grantee:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub value:    u128,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
-pub struct Asset {
-    pub id:     Hash,
-    pub name:   String,
-    pub supply: u128,
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetAllowancesPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub grantor:  Address,
+    pub offset:   u64,
+    pub limit:    u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GrantedAllowance {
+    #[schemars(with = "String")]
+    pub grantee: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:   u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetAllowancesResponse {
+    #[schemars(with = "String")]
+    pub asset_id:   Hash,
+    #[schemars(with = "String")]
+    pub grantor:    Address,
+    pub allowances: Vec<GrantedAllowance>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetSessionKeyPayload {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub authorizer:  Address,
+    #[schemars(with = "String")]
+    pub session_key: Address,
+}
+
+/// A key never authorized, or revoked, has no budget.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetSessionKeyResponse {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub authorizer:  Address,
+    #[schemars(with = "String")]
+    pub session_key: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub budget:      u128,
+    pub expires_at:  u64,
+}
+
+/// Assets carry their symbol as their name.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ResolveSymbolPayload {
+    pub symbol: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetAssetsByIssuerPayload {
+    #[schemars(with = "String")]
     pub issuer: Address,
+    pub offset: u64,
+    pub limit:  u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetAssetsByIssuerResponse {
+    #[schemars(with = "String")]
+    pub issuer: Address,
+    /// Assets issued in all, of which `assets` is a page.
+    pub total:  u64,
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Asset {
+    #[schemars(with = "String")]
+    pub id:           Hash,
+    pub name:         String,
+    pub supply:       Amount,
+    #[schemars(with = "String")]
+    pub issuer:       Address,
+    /// Cap on the supply, mirror assets have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_supply:   Option<Amount>,
+    /// Whether holders may transfer and approve it. Soulbound assets only
+    /// move from their issuer, by transfers or mints, and back by burns.
+    #[serde(default = "default_transferable")]
+    pub transferable: bool,
 }
 
 pub struct AssetBalance {
+    pub value: Amount,
+}
+
+/// Balance layout from before allowances got their own entries, only decoded
+/// to migrate existing state.
+pub struct LegacyAssetBalance {
     pub value:     u128,
+    // encoded in address byte order, never in insertion order
     pub allowance: BTreeMap<Address, u128>,
 }
 
+/// An allowance of `UNLIMITED_ALLOWANCE` is never spent down by
+/// `transfer_from`, and is stored as an empty list.
+pub struct Allowance {
+    pub value: u128,
+}
+
+pub const UNLIMITED_ALLOWANCE: u128 = u128::max_value();
+
+/// Grantees of one grantor for one asset, kept sorted by address.
+pub struct AllowanceIndex {
+    pub grantees: Vec<Address>,
+}
+
+/// What a session key may still spend of its authorizer's asset, and the
+/// block from which it can't.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionKey {
+    pub budget:     u128,
+    pub expires_at: u64,
+}
+
+/// Ids of the assets an address issued, in the order they were created.
+#[derive(Clone)]
+pub struct IssuedAssets {
+    pub asset_ids: Vec<Hash>,
+}
+
 struct AllowanceCodec {
     pub addr:  Address,
     pub total: u128,
@@ -134,22 +715,45 @@ struct AllowanceCodec {
 
 impl rlp::Decodable for Asset {
     fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        let buf: Vec<u8> = rlp.at(2)?.as_val()?;
         Ok(Self {
-            id:     rlp.at(0)?.as_val()?,
-            name:   rlp.at(1)?.as_val()?,
-            supply: LittleEndian::read_u128(&buf),
-            issuer: rlp.at(3)?.as_val()?,
+            id:           rlp.at(0)?.as_val()?,
+            name:         rlp.at(1)?.as_val()?,
+            supply:       rlp.val_at(2)?,
+            issuer:       rlp.at(3)?.as_val()?,
+            // assets stored before the cap existed have four items, and
+            // transferable ones the cap alone after them
+            max_supply:   match rlp.item_count()? {
+                4 => None,
+                5 => Some(rlp.val_at(4)?),
+                _ => rlp.list_at(4)?.pop(),
+            },
+            transferable: rlp.item_count()? < 6 || rlp.val_at(5)?,
         })
     }
 }
 
 impl rlp::Encodable for Asset {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(4).append(&self.id).append(&self.name);
-        let mut buf = [0u8; mem::size_of::<u128>()];
-        LittleEndian::write_u128(&mut buf, self.supply);
-        s.append(&buf.to_vec()).append(&self.issuer);
+        if !self.transferable {
+            let max_supply: Vec<Amount> = self.max_supply.iter().cloned().collect();
+            s.begin_list(6)
+                .append(&self.id)
+                .append(&self.name)
+                .append(&self.supply)
+                .append(&self.issuer)
+                .append_list(&max_supply)
+                .append(&self.transferable);
+            return;
+        }
+
+        s.begin_list(4 + self.max_supply.is_some() as usize)
+            .append(&self.id)
+            .append(&self.name)
+            .append(&self.supply)
+            .append(&self.issuer);
+        if let Some(max_supply) = &self.max_supply {
+            s.append(max_supply);
+        }
     }
 }
 
@@ -165,43 +769,63 @@ impl FixedCodec for Asset {
 
 impl rlp::Decodable for AllowanceCodec {
     fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        let buf: Vec<u8> = rlp.at(1)?.as_val()?;
         Ok(Self {
             addr:  rlp.at(0)?.as_val()?,
-            total: LittleEndian::read_u128(&buf),
+            total: rlp.val_at::<Amount>(1)?.0,
         })
     }
 }
 
 impl rlp::Encodable for AllowanceCodec {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(2).append(&self.addr);
-        let mut buf = [0u8; mem::size_of::<u128>()];
-        LittleEndian::write_u128(&mut buf, self.total);
-        s.append(&buf.to_vec());
+        s.begin_list(2)
+            .append(&self.addr)
+            .append(&Amount(self.total));
     }
 }
 
 impl rlp::Decodable for AssetBalance {
     fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        let buf: Vec<u8> = rlp.at(0)?.as_val()?;
-        let value = LittleEndian::read_u128(&buf);
-        let codec_list: Vec<AllowanceCodec> = rlp::decode_list(rlp.at(1)?.as_raw());
+        Ok(AssetBalance {
+            value: rlp.val_at(0)?,
+        })
+    }
+}
+
+impl rlp::Encodable for AssetBalance {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(1).append(&self.value);
+    }
+}
+
+impl FixedCodec for AssetBalance {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for LegacyAssetBalance {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let value = rlp.val_at::<Amount>(0)?.0;
         let mut allowance = BTreeMap::new();
-        for v in codec_list {
-            allowance.insert(v.addr, v.total);
+        if rlp.item_count()? > 1 {
+            let codec_list: Vec<AllowanceCodec> = rlp.at(1)?.as_list()?;
+            for v in codec_list {
+                allowance.insert(v.addr, v.total);
+            }
         }
 
-        Ok(AssetBalance { value, allowance })
+        Ok(LegacyAssetBalance { value, allowance })
     }
 }
 
-impl rlp::Encodable for AssetBalance {
+impl rlp::Encodable for LegacyAssetBalance {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(2);
-        let mut buf = [0u8; mem::size_of::<u128>()];
-        LittleEndian::write_u128(&mut buf, self.value);
-        s.append(&buf.to_vec());
+        s.begin_list(2).append(&Amount(self.value));
 
         let mut codec_list = Vec::with_capacity(self.allowance.len());
 
@@ -218,7 +842,141 @@ impl rlp::Encodable for AssetBalance {
     }
 }
 
-impl FixedCodec for AssetBalance {
+impl FixedCodec for LegacyAssetBalance {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for Allowance {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if rlp.item_count()? == 0 {
+            return Ok(Allowance {
+                value: UNLIMITED_ALLOWANCE,
+            });
+        }
+
+        Ok(Allowance {
+            value: rlp.val_at::<Amount>(0)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for Allowance {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        if self.value == UNLIMITED_ALLOWANCE {
+            s.begin_list(0);
+            return;
+        }
+
+        s.begin_list(1).append(&Amount(self.value));
+    }
+}
+
+impl FixedCodec for Allowance {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for SessionKey {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(SessionKey {
+            budget:     rlp.val_at::<Amount>(0)?.0,
+            expires_at: rlp.val_at(1)?,
+        })
+    }
+}
+
+impl rlp::Encodable for SessionKey {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&Amount(self.budget))
+            .append(&self.expires_at);
+    }
+}
+
+impl FixedCodec for SessionKey {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for AllowanceIndex {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(AllowanceIndex {
+            grantees: rlp.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for AllowanceIndex {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_list(&self.grantees);
+    }
+}
+
+impl FixedCodec for AllowanceIndex {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for IssuedAssets {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(IssuedAssets {
+            asset_ids: rlp.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for IssuedAssets {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_list(&self.asset_ids);
+    }
+}
+
+impl FixedCodec for IssuedAssets {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for Minters {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Minters {
+            services: rlp.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for Minters {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_list::<String, String>(&self.services);
+    }
+}
+
+impl FixedCodec for Minters {
     fn encode_fixed(&self) -> ProtocolResult<Bytes> {
         Ok(Bytes::from(rlp::encode(self)))
     }
@@ -227,3 +985,864 @@ impl FixedCodec for AssetBalance {
         Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
     }
 }
+
+/// Services allowed to mint and burn assets, by the name a service gives
+/// itself when calling, which isn't verified.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Minters {
+    pub services: Vec<String>,
+}
+
+/// Services allowed to hold assets of their users, by the name a service
+/// gives itself when calling, which isn't verified.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+pub struct HoldServices {
+    pub services: Vec<String>,
+}
+
+/// Funds of `owner` a service reserved, numbered from 1, which the service
+/// later releases back to the owner or executes to a recipient.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Hold {
+    pub id:       u64,
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub owner:    Address,
+    pub service:  String,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HoldPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub owner:    Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ReleaseHoldPayload {
+    pub id: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ExecuteHoldPayload {
+    pub id: u64,
+    #[schemars(with = "String")]
+    pub to: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetHoldPayload {
+    pub id: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HoldEvent {
+    pub id:       u64,
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub owner:    Address,
+    pub service:  String,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+    // recipient of an executed hold
+    #[schemars(with = "Option<String>")]
+    pub to:       Option<Address>,
+    pub topic:    String, // "hold", "release_hold" or "execute_hold"
+}
+
+/// The asset service's tunables, each as stored or as the service shipped it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Params {
+    pub max_transfer_fee_bps:   u32,
+    pub max_metadata_value_len: u64,
+    // services allowed to mint and burn
+    pub minters:                Vec<String>,
+    // services allowed to hold assets of their users
+    pub hold_services:          Vec<String>,
+}
+
+/// Parameters to change, those left unset keep their value.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default)]
+pub struct SetParamsPayload {
+    #[serde(default)]
+    pub max_transfer_fee_bps:   Option<u32>,
+    #[serde(default)]
+    pub max_metadata_value_len: Option<u64>,
+    #[serde(default)]
+    pub minters:                Option<Vec<String>>,
+    #[serde(default)]
+    pub hold_services:          Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ParamsEvent {
+    pub changed: Vec<String>,
+    pub params:  Params,
+    pub topic:   String, // "set_params"
+}
+
+/// The rbac service's `has_role` read, by which the admin is checked when
+/// the asset defers to it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HasRolePayload {
+    pub service: String,
+    pub role:    String,
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HasRoleResponse {
+    pub has_role: bool,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MigratePayload {
+    // most entries a batched migration rewrites in this call
+    pub limit: u64,
+}
+
+/// Schema version of the stored state, and the migrations taking it to the
+/// version of the deployed service.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MigrationStatus {
+    pub schema_version: u64,
+    pub target_version: u64,
+    pub pending:        Vec<String>,
+    // where the first pending migration resumes, if batched
+    pub cursor:         u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MigrationEvent {
+    pub from_version: u64,
+    pub version:      u64,
+    pub cursor:       u64,
+    pub topic:        String, // "migrate"
+}
+
+/// Fee the issuer takes on transfers of an asset: `rate_bps` basis points of
+/// each transfer, deducted from what the recipient gets and credited to
+/// `collector`. Transfers from or to an `exempt` address or the collector
+/// pay none.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct TransferFee {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    pub rate_bps:  u32,
+    #[schemars(with = "String")]
+    pub collector: Address,
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub exempt:    Vec<Address>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct TransferFeeEvent {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    pub rate_bps:  u32,
+    #[schemars(with = "String")]
+    pub collector: Address,
+    #[schemars(with = "Vec<String>")]
+    pub exempt:    Vec<Address>,
+    pub topic:     String, // "set_transfer_fee"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct TransferFeeChargedEvent {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    #[schemars(with = "String")]
+    pub from:      Address,
+    #[schemars(with = "String")]
+    pub to:        Address,
+    #[schemars(with = "String")]
+    pub collector: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub fee:       u128,
+    pub topic:     String, // "transfer_fee"
+}
+
+/// A value replaced by a write in block `height`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub height: u64,
+    pub value:  u128,
+}
+
+/// Keeps the balances of `asset_id` at the end of the current block, for
+/// distributions to be paid over.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct TakeSnapshotPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SnapshotEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    pub height:   u64,
+    pub topic:    String, // "snapshot"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetSnapshotBalancePayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub user:     Address,
+    pub height:   u64,
+}
+
+/// Balance of `user` and supply of the asset at the end of block `height`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct SnapshotBalance {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub user:     Address,
+    pub height:   u64,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub balance:  u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub supply:   u128,
+}
+
+/// Pays `total_amount` of `funding_asset` to the holders of `asset_id` in
+/// proportion to their balances at the snapshot taken at `snapshot_height`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct DistributePayload {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[schemars(with = "String")]
+    pub funding_asset:   Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub total_amount:    u128,
+    pub snapshot_height: u64,
+}
+
+/// A funded distribution, numbered from 1. Holders claim their share, which
+/// leaves the rounding dust of the shares unclaimed.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Distribution {
+    pub id:              u64,
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[schemars(with = "String")]
+    pub funding_asset:   Hash,
+    #[schemars(with = "String")]
+    pub funder:          Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub total_amount:    u128,
+    pub snapshot_height: u64,
+    // supply of `asset_id` at the snapshot outside the distribution, lock and
+    // hold pools, which the shares divide
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub supply:          u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub claimed:         u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct DistributeEvent {
+    pub id:              u64,
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[schemars(with = "String")]
+    pub funding_asset:   Hash,
+    #[schemars(with = "String")]
+    pub funder:          Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub total_amount:    u128,
+    pub snapshot_height: u64,
+    pub topic:           String, // "distribute"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetDistributionPayload {
+    pub id: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ClaimDistributionPayload {
+    pub id: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetDistributionClaimPayload {
+    pub id:     u64,
+    #[schemars(with = "String")]
+    pub holder: Address,
+}
+
+/// The share of a distribution a holder gets, and whether it was claimed.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct DistributionClaim {
+    pub id:      u64,
+    #[schemars(with = "String")]
+    pub holder:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:  u128,
+    pub claimed: bool,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ClaimDistributionEvent {
+    pub id:     u64,
+    #[schemars(with = "String")]
+    pub holder: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount: u128,
+    pub topic:  String, // "claim_distribution"
+}
+
+/// A transfer the recipient can only spend from `unlock_height` on, such as
+/// the lockup of a launched token.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct TransferLockedPayload {
+    #[schemars(with = "String")]
+    pub asset_id:      Hash,
+    #[schemars(with = "String")]
+    pub to:            Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:         u128,
+    pub unlock_height: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct TransferLockedEvent {
+    #[schemars(with = "String")]
+    pub asset_id:      Hash,
+    #[schemars(with = "String")]
+    pub from:          Address,
+    #[schemars(with = "String")]
+    pub to:            Address,
+    // what the recipient gets once unlocked, the value less any transfer fee
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:         u128,
+    pub unlock_height: u64,
+    pub topic:         String, // "transfer_locked"
+}
+
+/// Value held for a recipient until a block height.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Lock {
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:         u128,
+    pub unlock_height: u64,
+}
+
+/// Locks of an account on one asset, by unlock height, each height once.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Locks {
+    pub locks: Vec<Lock>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ClaimUnlockedPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ClaimUnlockedEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub holder:   Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:    u128,
+    pub topic:    String, // "claim_unlocked"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetLockedBalancePayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub user:     Address,
+}
+
+/// An account's balance split by what it can spend now.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct LockedBalanceResponse {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    #[schemars(with = "String")]
+    pub user:      Address,
+    // spendable, the balance `get_balance` reports
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub liquid:    u128,
+    // unlocked and waiting for `claim_unlocked`
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub claimable: u128,
+    // still locked
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub locked:    u128,
+    pub locks:     Vec<Lock>,
+}
+
+/// A write to plan, `payload` being its payload json as sent in the tx.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetAccessSetPayload {
+    pub method:  String,
+    #[schemars(with = "String")]
+    pub caller:  Address,
+    pub payload: String,
+}
+
+/// A part of the state a write reads or writes.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StateKey {
+    // the asset entry, with its supply
+    Asset {
+        #[schemars(with = "String")]
+        asset_id: Hash,
+    },
+    // a balance with its snapshot checkpoints
+    Balance {
+        #[schemars(with = "String")]
+        asset_id: Hash,
+        #[schemars(with = "String")]
+        account:  Address,
+    },
+    // every allowance a grantor gave on an asset
+    Allowances {
+        #[schemars(with = "String")]
+        asset_id: Hash,
+        #[schemars(with = "String")]
+        grantor:  Address,
+    },
+    TransferFee {
+        #[schemars(with = "String")]
+        asset_id: Hash,
+    },
+    // settings of the whole service, such as the self transfer policy
+    Config,
+}
+
+impl StateKey {
+    fn overlaps(&self, other: &StateKey) -> bool {
+        match (self, other) {
+            // a balance still in the legacy layout carries the allowances of
+            // its account, rewriting either rewrites both
+            (
+                StateKey::Balance { asset_id, account },
+                StateKey::Allowances {
+                    asset_id: other_id,
+                    grantor,
+                },
+            )
+            | (
+                StateKey::Allowances {
+                    asset_id: other_id,
+                    grantor,
+                },
+                StateKey::Balance { asset_id, account },
+            ) => asset_id == other_id && account == grantor,
+            _ => self == other,
+        }
+    }
+}
+
+/// What a write touches, for an executor to run writes side by side when
+/// their sets don't conflict. An exclusive write declares nothing and must
+/// run alone.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AccessSet {
+    pub exclusive: bool,
+    pub reads:     Vec<StateKey>,
+    pub writes:    Vec<StateKey>,
+}
+
+impl AccessSet {
+    pub fn exclusive() -> Self {
+        AccessSet {
+            exclusive: true,
+            reads:     vec![],
+            writes:    vec![],
+        }
+    }
+
+    /// Whether the writes must run one after the other: either is exclusive,
+    /// or one writes what the other touches.
+    pub fn conflicts(&self, other: &AccessSet) -> bool {
+        let writes_into = |writes: &[StateKey], set: &AccessSet| {
+            writes.iter().any(|w| {
+                set.reads
+                    .iter()
+                    .chain(set.writes.iter())
+                    .any(|key| w.overlaps(key))
+            })
+        };
+        self.exclusive
+            || other.exclusive
+            || writes_into(&self.writes, other)
+            || writes_into(&other.writes, self)
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetAssetMetadataPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    pub key:      String,
+    // an empty value removes the entry
+    pub value:    String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetAssetMetadataPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    pub key:      String,
+}
+
+/// An entry the issuer attached to an asset, such as its icon url or
+/// website, empty when unset.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AssetMetadata {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    pub key:      String,
+    pub value:    String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct AssetMetadataEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    pub key:      String,
+    pub value:    String,
+    pub topic:    String, // "set_asset_metadata"
+}
+
+impl rlp::Decodable for Checkpoint {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Checkpoint {
+            height: rlp.val_at(0)?,
+            value:  rlp.val_at::<Amount>(1)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for Checkpoint {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.height)
+            .append(&Amount(self.value));
+    }
+}
+
+impl FixedCodec for Checkpoint {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for Distribution {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Distribution {
+            id:              rlp.val_at(0)?,
+            asset_id:        rlp.val_at(1)?,
+            funding_asset:   rlp.val_at(2)?,
+            funder:          rlp.val_at(3)?,
+            total_amount:    rlp.val_at::<Amount>(4)?.0,
+            snapshot_height: rlp.val_at(5)?,
+            supply:          rlp.val_at::<Amount>(6)?.0,
+            claimed:         rlp.val_at::<Amount>(7)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for Distribution {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(8)
+            .append(&self.id)
+            .append(&self.asset_id)
+            .append(&self.funding_asset)
+            .append(&self.funder)
+            .append(&Amount(self.total_amount))
+            .append(&self.snapshot_height)
+            .append(&Amount(self.supply))
+            .append(&Amount(self.claimed));
+    }
+}
+
+impl FixedCodec for Distribution {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for HoldServices {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(HoldServices {
+            services: rlp.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for HoldServices {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_list::<String, String>(&self.services);
+    }
+}
+
+impl FixedCodec for HoldServices {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for Hold {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Hold {
+            id:       rlp.val_at(0)?,
+            asset_id: rlp.val_at(1)?,
+            owner:    rlp.val_at(2)?,
+            service:  rlp.val_at(3)?,
+            amount:   rlp.val_at::<Amount>(4)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for Hold {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5)
+            .append(&self.id)
+            .append(&self.asset_id)
+            .append(&self.owner)
+            .append(&self.service)
+            .append(&Amount(self.amount));
+    }
+}
+
+impl FixedCodec for Hold {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for ZeroValuePolicy {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        match rlp.as_val::<u8>()? {
+            0 => Ok(ZeroValuePolicy::Reject),
+            1 => Ok(ZeroValuePolicy::AllowSilent),
+            2 => Ok(ZeroValuePolicy::AllowWithEvent),
+            _ => Err(rlp::DecoderError::Custom("invalid zero value policy")),
+        }
+    }
+}
+
+impl rlp::Encodable for ZeroValuePolicy {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let policy: u8 = match self {
+            ZeroValuePolicy::Reject => 0,
+            ZeroValuePolicy::AllowSilent => 1,
+            ZeroValuePolicy::AllowWithEvent => 2,
+        };
+        s.append(&policy);
+    }
+}
+
+impl FixedCodec for ZeroValuePolicy {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for IssuanceRecord {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(IssuanceRecord {
+            seq:       rlp.val_at(0)?,
+            asset_id:  rlp.val_at(1)?,
+            action:    rlp.val_at(2)?,
+            cause:     rlp.val_at(3)?,
+            account:   rlp.val_at(4)?,
+            amount:    rlp.val_at::<Amount>(5)?.0,
+            supply:    rlp.val_at::<Amount>(6)?.0,
+            height:    rlp.val_at(7)?,
+            source_tx: rlp.list_at(8)?.pop(),
+        })
+    }
+}
+
+impl rlp::Encodable for IssuanceRecord {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let source_tx: Vec<Hash> = self.source_tx.iter().cloned().collect();
+        s.begin_list(9)
+            .append(&self.seq)
+            .append(&self.asset_id)
+            .append(&self.action)
+            .append(&self.cause)
+            .append(&self.account)
+            .append(&Amount(self.amount))
+            .append(&Amount(self.supply))
+            .append(&self.height)
+            .append_list(&source_tx);
+    }
+}
+
+impl FixedCodec for IssuanceRecord {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for Lock {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Lock {
+            value:         rlp.val_at::<Amount>(0)?.0,
+            unlock_height: rlp.val_at(1)?,
+        })
+    }
+}
+
+impl rlp::Encodable for Lock {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&Amount(self.value))
+            .append(&self.unlock_height);
+    }
+}
+
+impl rlp::Decodable for Locks {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Locks {
+            locks: rlp.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for Locks {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_list(&self.locks);
+    }
+}
+
+impl FixedCodec for Locks {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for TransferFee {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(TransferFee {
+            asset_id:  rlp.val_at(0)?,
+            rate_bps:  rlp.val_at(1)?,
+            collector: rlp.val_at(2)?,
+            exempt:    rlp.list_at(3)?,
+        })
+    }
+}
+
+impl rlp::Encodable for TransferFee {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(4)
+            .append(&self.asset_id)
+            .append(&self.rate_bps)
+            .append(&self.collector)
+            .append_list(&self.exempt);
+    }
+}
+
+impl FixedCodec for TransferFee {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+/// What the deployed service supports, for clients to adapt to it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ServiceInfo {
+    pub version:          String,
+    pub features:         Vec<String>,
+    pub payload_versions: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain:            Option<ChainBinding>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetSchemaPayload {
+    pub type_name: String,
+}
+
+impl PayloadVersion for CreateAssetPayload {}
+impl PayloadVersion for TransferPayload {}
+impl PayloadVersion for TransferAndCallPayload {}
+impl PayloadVersion for RevokeAllApprovalsPayload {}
+impl PayloadVersion for TransferFromPayload {}
+impl PayloadVersion for BurnFromPayload {}
+impl PayloadVersion for AuthorizeSessionKeyPayload {}
+impl PayloadVersion for RevokeSessionKeyPayload {}
+impl PayloadVersion for SessionTransferPayload {}
+impl PayloadVersion for MetaTxPayload {}
+impl PayloadVersion for TransferFee {}
+impl PayloadVersion for DistributePayload {}
+impl PayloadVersion for TakeSnapshotPayload {}
+impl PayloadVersion for TransferLockedPayload {}
+impl PayloadVersion for SetAssetMetadataPayload {}
@@ -0,0 +1,492 @@
+//! The views `get_view` flattens, one per read, with every field a string
+//! and nested structs folded into the fields of the view. Collections stay
+//! lists of flat items, for `get_view` to truncate.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use common::view::{MigrationStatusView, ServiceInfoView};
+
+use crate::types::{
+    AccessSet, Asset, AssetMetadata, Distribution, DistributionClaim, GetAllowanceResponse,
+    GetAllowancesResponse, GetAssetsByIssuerResponse, GetBalanceResponse, GetSessionKeyResponse,
+    GrantedAllowance, Hold, HoldServices, IssuanceLog, IssuanceRecord, Lock, LockedBalanceResponse,
+    MetaNonceResponse, MigrationStatus, Minters, Params, ServiceInfo, SnapshotBalance, StateKey,
+    TransferFee,
+};
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AssetView {
+    pub id:           String,
+    pub name:         String,
+    pub supply:       String,
+    pub issuer:       String,
+    pub max_supply:   Option<String>,
+    pub transferable: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BalanceView {
+    pub asset_id:          String,
+    pub user:              String,
+    pub balance:           String,
+    pub formatted_balance: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AllowanceView {
+    pub asset_id: String,
+    pub grantor:  String,
+    pub grantee:  String,
+    pub value:    String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AllowancesView {
+    pub asset_id:   String,
+    pub grantor:    String,
+    pub allowances: Vec<GrantedAllowanceView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct GrantedAllowanceView {
+    pub grantee: String,
+    pub value:   String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MetaNonceView {
+    pub user:  String,
+    pub nonce: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct IssuanceLogView {
+    pub asset_id: String,
+    pub len:      String,
+    pub records:  Vec<IssuanceRecordView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct IssuanceRecordView {
+    pub seq:       String,
+    pub asset_id:  String,
+    pub action:    String,
+    pub cause:     String,
+    pub account:   String,
+    pub amount:    String,
+    pub supply:    String,
+    pub height:    String,
+    pub source_tx: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AssetsByIssuerView {
+    pub issuer: String,
+    pub total:  String,
+    pub assets: Vec<AssetView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct SessionKeyView {
+    pub asset_id:    String,
+    pub authorizer:  String,
+    pub session_key: String,
+    pub budget:      String,
+    pub expires_at:  String,
+}
+
+/// View of `get_minters` and `get_hold_services`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ServicesView {
+    pub services: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ParamsView {
+    pub max_transfer_fee_bps:   String,
+    pub max_metadata_value_len: String,
+    pub minters:                Vec<String>,
+    pub hold_services:          Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct TransferFeeView {
+    pub asset_id:  String,
+    pub rate_bps:  String,
+    pub collector: String,
+    pub exempt:    Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AccessSetView {
+    pub exclusive: String,
+    pub reads:     Vec<StateKeyView>,
+    pub writes:    Vec<StateKeyView>,
+}
+
+/// A state key by its kind, with the ids the kind has.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct StateKeyView {
+    pub kind:     String,
+    pub asset_id: Option<String>,
+    pub account:  Option<String>,
+    pub grantor:  Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AssetMetadataView {
+    pub asset_id: String,
+    pub key:      String,
+    pub value:    String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct SnapshotBalanceView {
+    pub asset_id: String,
+    pub user:     String,
+    pub height:   String,
+    pub balance:  String,
+    pub supply:   String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct DistributionView {
+    pub id:              String,
+    pub asset_id:        String,
+    pub funding_asset:   String,
+    pub funder:          String,
+    pub total_amount:    String,
+    pub snapshot_height: String,
+    pub supply:          String,
+    pub claimed:         String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct DistributionClaimView {
+    pub id:      String,
+    pub holder:  String,
+    pub amount:  String,
+    pub claimed: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct LockedBalanceView {
+    pub asset_id:  String,
+    pub user:      String,
+    pub liquid:    String,
+    pub claimable: String,
+    pub locked:    String,
+    pub locks:     Vec<LockView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct LockView {
+    pub value:         String,
+    pub unlock_height: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct HoldView {
+    pub id:       String,
+    pub asset_id: String,
+    pub owner:    String,
+    pub service:  String,
+    pub amount:   String,
+}
+
+impl From<ServiceInfo> for ServiceInfoView {
+    fn from(info: ServiceInfo) -> Self {
+        ServiceInfoView::new(
+            info.version,
+            info.features,
+            &info.payload_versions,
+            info.chain.as_ref(),
+        )
+    }
+}
+
+impl From<MigrationStatus> for MigrationStatusView {
+    fn from(status: MigrationStatus) -> Self {
+        MigrationStatusView::new(
+            status.schema_version,
+            status.target_version,
+            status.pending,
+            status.cursor,
+        )
+    }
+}
+
+impl From<Asset> for AssetView {
+    fn from(asset: Asset) -> Self {
+        AssetView {
+            id:           asset.id.as_hex(),
+            name:         asset.name,
+            supply:       asset.supply.0.to_string(),
+            issuer:       asset.issuer.as_hex(),
+            max_supply:   asset.max_supply.map(|max| max.0.to_string()),
+            transferable: asset.transferable.to_string(),
+        }
+    }
+}
+
+impl From<GetBalanceResponse> for BalanceView {
+    fn from(resp: GetBalanceResponse) -> Self {
+        BalanceView {
+            asset_id:          resp.asset_id.as_hex(),
+            user:              resp.user.as_hex(),
+            balance:           resp.balance.to_string(),
+            formatted_balance: resp.formatted_balance,
+        }
+    }
+}
+
+impl From<GetAllowanceResponse> for AllowanceView {
+    fn from(resp: GetAllowanceResponse) -> Self {
+        AllowanceView {
+            asset_id: resp.asset_id.as_hex(),
+            grantor:  resp.grantor.as_hex(),
+            grantee:  resp.grantee.as_hex(),
+            value:    resp.value.to_string(),
+        }
+    }
+}
+
+impl From<GetAllowancesResponse> for AllowancesView {
+    fn from(resp: GetAllowancesResponse) -> Self {
+        AllowancesView {
+            asset_id:   resp.asset_id.as_hex(),
+            grantor:    resp.grantor.as_hex(),
+            allowances: resp.allowances.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<GrantedAllowance> for GrantedAllowanceView {
+    fn from(allowance: GrantedAllowance) -> Self {
+        GrantedAllowanceView {
+            grantee: allowance.grantee.as_hex(),
+            value:   allowance.value.to_string(),
+        }
+    }
+}
+
+impl From<MetaNonceResponse> for MetaNonceView {
+    fn from(resp: MetaNonceResponse) -> Self {
+        MetaNonceView {
+            user:  resp.user.as_hex(),
+            nonce: resp.nonce.to_string(),
+        }
+    }
+}
+
+impl From<IssuanceLog> for IssuanceLogView {
+    fn from(log: IssuanceLog) -> Self {
+        IssuanceLogView {
+            asset_id: log.asset_id.as_hex(),
+            len:      log.len.to_string(),
+            records:  log.records.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<IssuanceRecord> for IssuanceRecordView {
+    fn from(record: IssuanceRecord) -> Self {
+        IssuanceRecordView {
+            seq:       record.seq.to_string(),
+            asset_id:  record.asset_id.as_hex(),
+            action:    record.action,
+            cause:     record.cause,
+            account:   record.account.as_hex(),
+            amount:    record.amount.to_string(),
+            supply:    record.supply.to_string(),
+            height:    record.height.to_string(),
+            source_tx: record.source_tx.map(|tx| tx.as_hex()),
+        }
+    }
+}
+
+impl From<GetAssetsByIssuerResponse> for AssetsByIssuerView {
+    fn from(resp: GetAssetsByIssuerResponse) -> Self {
+        AssetsByIssuerView {
+            issuer: resp.issuer.as_hex(),
+            total:  resp.total.to_string(),
+            assets: resp.assets.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<GetSessionKeyResponse> for SessionKeyView {
+    fn from(resp: GetSessionKeyResponse) -> Self {
+        SessionKeyView {
+            asset_id:    resp.asset_id.as_hex(),
+            authorizer:  resp.authorizer.as_hex(),
+            session_key: resp.session_key.as_hex(),
+            budget:      resp.budget.to_string(),
+            expires_at:  resp.expires_at.to_string(),
+        }
+    }
+}
+
+impl From<Minters> for ServicesView {
+    fn from(minters: Minters) -> Self {
+        ServicesView {
+            services: minters.services,
+        }
+    }
+}
+
+impl From<HoldServices> for ServicesView {
+    fn from(hold_services: HoldServices) -> Self {
+        ServicesView {
+            services: hold_services.services,
+        }
+    }
+}
+
+impl From<Params> for ParamsView {
+    fn from(params: Params) -> Self {
+        ParamsView {
+            max_transfer_fee_bps:   params.max_transfer_fee_bps.to_string(),
+            max_metadata_value_len: params.max_metadata_value_len.to_string(),
+            minters:                params.minters,
+            hold_services:          params.hold_services,
+        }
+    }
+}
+
+impl From<TransferFee> for TransferFeeView {
+    fn from(fee: TransferFee) -> Self {
+        TransferFeeView {
+            asset_id:  fee.asset_id.as_hex(),
+            rate_bps:  fee.rate_bps.to_string(),
+            collector: fee.collector.as_hex(),
+            exempt:    fee.exempt.iter().map(|account| account.as_hex()).collect(),
+        }
+    }
+}
+
+impl From<AccessSet> for AccessSetView {
+    fn from(set: AccessSet) -> Self {
+        AccessSetView {
+            exclusive: set.exclusive.to_string(),
+            reads:     set.reads.into_iter().map(Into::into).collect(),
+            writes:    set.writes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<StateKey> for StateKeyView {
+    fn from(key: StateKey) -> Self {
+        let mut view = StateKeyView {
+            kind:     String::new(),
+            asset_id: None,
+            account:  None,
+            grantor:  None,
+        };
+        match key {
+            StateKey::Asset { asset_id } => {
+                view.kind = "asset".to_owned();
+                view.asset_id = Some(asset_id.as_hex());
+            }
+            StateKey::Balance { asset_id, account } => {
+                view.kind = "balance".to_owned();
+                view.asset_id = Some(asset_id.as_hex());
+                view.account = Some(account.as_hex());
+            }
+            StateKey::Allowances { asset_id, grantor } => {
+                view.kind = "allowances".to_owned();
+                view.asset_id = Some(asset_id.as_hex());
+                view.grantor = Some(grantor.as_hex());
+            }
+            StateKey::TransferFee { asset_id } => {
+                view.kind = "transfer_fee".to_owned();
+                view.asset_id = Some(asset_id.as_hex());
+            }
+            StateKey::Config => view.kind = "config".to_owned(),
+        }
+        view
+    }
+}
+
+impl From<AssetMetadata> for AssetMetadataView {
+    fn from(metadata: AssetMetadata) -> Self {
+        AssetMetadataView {
+            asset_id: metadata.asset_id.as_hex(),
+            key:      metadata.key,
+            value:    metadata.value,
+        }
+    }
+}
+
+impl From<SnapshotBalance> for SnapshotBalanceView {
+    fn from(snapshot: SnapshotBalance) -> Self {
+        SnapshotBalanceView {
+            asset_id: snapshot.asset_id.as_hex(),
+            user:     snapshot.user.as_hex(),
+            height:   snapshot.height.to_string(),
+            balance:  snapshot.balance.to_string(),
+            supply:   snapshot.supply.to_string(),
+        }
+    }
+}
+
+impl From<Distribution> for DistributionView {
+    fn from(distribution: Distribution) -> Self {
+        DistributionView {
+            id:              distribution.id.to_string(),
+            asset_id:        distribution.asset_id.as_hex(),
+            funding_asset:   distribution.funding_asset.as_hex(),
+            funder:          distribution.funder.as_hex(),
+            total_amount:    distribution.total_amount.to_string(),
+            snapshot_height: distribution.snapshot_height.to_string(),
+            supply:          distribution.supply.to_string(),
+            claimed:         distribution.claimed.to_string(),
+        }
+    }
+}
+
+impl From<DistributionClaim> for DistributionClaimView {
+    fn from(claim: DistributionClaim) -> Self {
+        DistributionClaimView {
+            id:      claim.id.to_string(),
+            holder:  claim.holder.as_hex(),
+            amount:  claim.amount.to_string(),
+            claimed: claim.claimed.to_string(),
+        }
+    }
+}
+
+impl From<LockedBalanceResponse> for LockedBalanceView {
+    fn from(resp: LockedBalanceResponse) -> Self {
+        LockedBalanceView {
+            asset_id:  resp.asset_id.as_hex(),
+            user:      resp.user.as_hex(),
+            liquid:    resp.liquid.to_string(),
+            claimable: resp.claimable.to_string(),
+            locked:    resp.locked.to_string(),
+            locks:     resp.locks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Lock> for LockView {
+    fn from(lock: Lock) -> Self {
+        LockView {
+            value:         lock.value.to_string(),
+            unlock_height: lock.unlock_height.to_string(),
+        }
+    }
+}
+
+impl From<Hold> for HoldView {
+    fn from(hold: Hold) -> Self {
+        HoldView {
+            id:       hold.id.to_string(),
+            asset_id: hold.asset_id.as_hex(),
+            owner:    hold.owner.as_hex(),
+            service:  hold.service,
+            amount:   hold.amount.to_string(),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Token amounts as the services store and exchange them. A u128 in memory,
+//! 16 little endian bytes in rlp state and in sudt cell data, and a decimal
+//! string in json.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
+
+use byteorder::{ByteOrder, LittleEndian};
+use bytes::Bytes;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::ProtocolResult;
+
+pub const AMOUNT_SIZE: usize = mem::size_of::<u128>();
+/// Most decimals an amount is displayed with, a u128 has 39 digits.
+pub const MAX_DECIMALS: u32 = 38;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(pub u128);
+
+impl Amount {
+    pub fn to_le_bytes(self) -> [u8; AMOUNT_SIZE] {
+        let mut buf = [0u8; AMOUNT_SIZE];
+        LittleEndian::write_u128(&mut buf, self.0);
+        buf
+    }
+
+    /// Reads the amount a sudt cell carries in the first 16 bytes of its
+    /// data, anything after them is left to the token's own extensions.
+    pub fn from_sudt_data(data: &[u8]) -> Option<Self> {
+        if data.len() < AMOUNT_SIZE {
+            return None;
+        }
+        Some(Amount(LittleEndian::read_u128(&data[..AMOUNT_SIZE])))
+    }
+
+    /// The data of a sudt cell holding the amount.
+    pub fn to_sudt_data(self) -> Bytes {
+        Bytes::from(self.to_le_bytes().to_vec())
+    }
+
+    /// The amount in whole tokens of `decimals` digits after the point, for
+    /// display: 1050 with 2 decimals is "10.50".
+    pub fn to_display(self, decimals: u32) -> String {
+        let digits = self.0.to_string();
+        let decimals = decimals.min(MAX_DECIMALS) as usize;
+        if decimals == 0 {
+            return digits;
+        }
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (whole, fraction) = padded.split_at(padded.len() - decimals);
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+impl From<u128> for Amount {
+    fn from(value: u128) -> Self {
+        Amount(value)
+    }
+}
+
+impl From<Amount> for u128 {
+    fn from(amount: Amount) -> Self {
+        amount.0
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::u128_string::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::u128_string::deserialize(deserializer).map(Amount)
+    }
+}
+
+impl JsonSchema for Amount {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+impl rlp::Encodable for Amount {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append(&self.to_le_bytes().to_vec());
+    }
+}
+
+/// Any length but 16 bytes means the state is corrupted, so it is rejected
+/// instead of being truncated or padded.
+impl rlp::Decodable for Amount {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let buf: Vec<u8> = rlp.as_val()?;
+        match buf.len().cmp(&AMOUNT_SIZE) {
+            Ordering::Less => Err(rlp::DecoderError::RlpIsTooShort),
+            Ordering::Greater => Err(rlp::DecoderError::RlpIsTooBig),
+            Ordering::Equal => Ok(Amount(LittleEndian::read_u128(&buf))),
+        }
+    }
+}
+
+impl FixedCodec for Amount {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
@@ -0,0 +1,70 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::StoreMap;
+use protocol::ProtocolResult;
+
+/// A store map remembering the entries it read or wrote, so a call touching
+/// the same key again doesn't read and decode it from the state once more.
+/// Writes go through to the store right away.
+///
+/// The cache lives as long as the service instance, which is a single call.
+/// It would miss writes made to the same map by another instance of the
+/// service, which only happens if a service this one calls calls back into
+/// it.
+pub struct CachedMap<K: FixedCodec + PartialEq, V: FixedCodec> {
+    inner: Box<dyn StoreMap<K, V>>,
+    // `None` for keys known to be absent
+    cache: RefCell<HashMap<K, Option<V>>>,
+}
+
+impl<K, V> CachedMap<K, V>
+where
+    K: FixedCodec + PartialEq + Eq + Hash + Clone,
+    V: FixedCodec + Clone,
+{
+    pub fn new(inner: Box<dyn StoreMap<K, V>>) -> Self {
+        CachedMap {
+            inner,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> ProtocolResult<V> {
+        if let Some(Some(value)) = self.cache.borrow().get(key) {
+            return Ok(value.clone());
+        }
+
+        let value = self.inner.get(key)?;
+        self.cache
+            .borrow_mut()
+            .insert(key.clone(), Some(value.clone()));
+        Ok(value)
+    }
+
+    pub fn contains(&self, key: &K) -> ProtocolResult<bool> {
+        if let Some(cached) = self.cache.borrow().get(key) {
+            return Ok(cached.is_some());
+        }
+
+        let contains = self.inner.contains(key)?;
+        if !contains {
+            self.cache.borrow_mut().insert(key.clone(), None);
+        }
+        Ok(contains)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> ProtocolResult<()> {
+        self.inner.insert(key.clone(), value.clone())?;
+        self.cache.borrow_mut().insert(key, Some(value));
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &K) -> ProtocolResult<()> {
+        self.inner.remove(key)?;
+        self.cache.borrow_mut().insert(key.clone(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+//! The networks a deployment belongs to, so that what is signed or emitted
+//! for one network can't be replayed on another.
+
+use bytes::Bytes;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+/// The ckb network a bridge takes deposits from, identified by its genesis
+/// block hash, and the muta chain it mints on, by its chain id. Bridge
+/// events carry it and submitted messages and burns must match it, so
+/// events of one network can't be replayed on another.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ChainBinding {
+    #[schemars(with = "String")]
+    pub source_chain_id: Hash,
+    #[schemars(with = "String")]
+    pub dest_chain_id:   Hash,
+}
+
+impl rlp::Decodable for ChainBinding {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            source_chain_id: rlp.at(0)?.as_val()?,
+            dest_chain_id:   rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for ChainBinding {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.source_chain_id)
+            .append(&self.dest_chain_id);
+    }
+}
+
+impl FixedCodec for ChainBinding {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
@@ -0,0 +1,11 @@
+//! Helpers shared by the asset and crosschain services.
+
+pub mod amount;
+pub mod cache;
+pub mod chain;
+pub mod migration;
+#[cfg(test)]
+mod tests;
+pub mod u128_string;
+pub mod versioned;
+pub mod view;
@@ -0,0 +1,68 @@
+//! Upgrades of the state a service keeps. The service stores the schema
+//! version its state is at, state written before versions were kept being at
+//! 0, and registers a migration for each version its layout moved to.
+//!
+//! A lazy migration leaves entries in their old layout: the service reads
+//! both layouts and rewrites an entry when it next writes it. A batched one
+//! rewrites the entries itself, a bounded number per run from a cursor kept
+//! in the state, so a large migration spreads over many blocks.
+//!
+//! The version moves past a migration only in the run finishing it, so a
+//! run failing midway leaves the version and cursor where they were and the
+//! next run redoes its batch. Batched steps must be idempotent per entry for
+//! that, and readers must take both layouts until the version moved past.
+
+use protocol::ProtocolResult;
+
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+pub const MIGRATION_CURSOR_KEY: &str = "migration_cursor";
+
+pub enum Step<S> {
+    Lazy,
+    /// Migrates at most `limit` entries from the cursor on and returns the
+    /// cursor of the next run, `None` once all are migrated.
+    Batched(fn(&mut S, u64, u64) -> ProtocolResult<Option<u64>>),
+}
+
+pub struct Migration<S> {
+    /// Schema version the state is at once the migration is done.
+    pub version: u64,
+    pub name:    &'static str,
+    pub step:    Step<S>,
+}
+
+/// Runs the `migrations` past `version` in order, at most one batch of
+/// `limit` entries, and returns the version and cursor the state is at after.
+pub fn run<S>(
+    service: &mut S,
+    migrations: &[Migration<S>],
+    mut version: u64,
+    mut cursor: u64,
+    limit: u64,
+) -> ProtocolResult<(u64, u64)> {
+    let mut batched = false;
+    for migration in migrations.iter().filter(|m| m.version > version) {
+        if let Step::Batched(step) = migration.step {
+            if batched || limit == 0 {
+                break;
+            }
+            batched = true;
+            if let Some(next) = step(service, cursor, limit)? {
+                return Ok((version, next));
+            }
+        }
+        version = migration.version;
+        cursor = 0;
+    }
+
+    Ok((version, cursor))
+}
+
+/// Names of the `migrations` past `version`, in the order they run.
+pub fn pending<S>(migrations: &[Migration<S>], version: u64) -> Vec<String> {
+    migrations
+        .iter()
+        .filter(|m| m.version > version)
+        .map(|m| m.name.to_owned())
+        .collect()
+}
@@ -0,0 +1,87 @@
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::migration::{pending, run, Migration, Step};
+
+// Entries in an old or new layout, and the entry a step fails at.
+struct Store {
+    migrated: Vec<bool>,
+    fail_at:  Option<u64>,
+}
+
+fn migrate_entries(store: &mut Store, cursor: u64, limit: u64) -> ProtocolResult<Option<u64>> {
+    let end = (cursor + limit).min(store.migrated.len() as u64);
+    for index in cursor..end {
+        if store.fail_at == Some(index) {
+            return Err(ProtocolError::new(
+                ProtocolErrorKind::Service,
+                Box::new(std::fmt::Error),
+            ));
+        }
+        store.migrated[index as usize] = true;
+    }
+    Ok(if end < store.migrated.len() as u64 {
+        Some(end)
+    } else {
+        None
+    })
+}
+
+fn migrations() -> Vec<Migration<Store>> {
+    vec![
+        Migration {
+            version: 1,
+            name:    "lazy",
+            step:    Step::Lazy,
+        },
+        Migration {
+            version: 2,
+            name:    "entries",
+            step:    Step::Batched(migrate_entries),
+        },
+        Migration {
+            version: 3,
+            name:    "after",
+            step:    Step::Lazy,
+        },
+    ]
+}
+
+#[test]
+fn test_run_migrations() {
+    let migrations = migrations();
+    let mut store = Store {
+        migrated: vec![false; 5],
+        fail_at:  None,
+    };
+    assert_eq!(pending(&migrations, 0), ["lazy", "entries", "after"]);
+
+    // a batched migration takes as many runs as its entries need
+    assert_eq!(run(&mut store, &migrations, 0, 0, 2).unwrap(), (1, 2));
+    assert_eq!(run(&mut store, &migrations, 1, 2, 2).unwrap(), (1, 4));
+    assert_eq!(store.migrated, [true, true, true, true, false]);
+    assert_eq!(run(&mut store, &migrations, 1, 4, 2).unwrap(), (3, 0));
+    assert!(store.migrated.iter().all(|m| *m));
+    assert!(pending(&migrations, 3).is_empty());
+
+    // migrated state has nothing left to run
+    assert_eq!(run(&mut store, &migrations, 3, 0, 2).unwrap(), (3, 0));
+    // without a budget only lazy migrations pass
+    assert_eq!(run(&mut store, &migrations, 0, 0, 0).unwrap(), (1, 0));
+}
+
+#[test]
+fn test_failed_migration_resumes() {
+    let migrations = migrations();
+    let mut store = Store {
+        migrated: vec![false; 4],
+        fail_at:  Some(3),
+    };
+
+    assert_eq!(run(&mut store, &migrations, 1, 0, 2).unwrap(), (1, 2));
+    // the failing run leaves the stored version and cursor as they were, and
+    // the next redoes the batch
+    assert!(run(&mut store, &migrations, 1, 2, 2).is_err());
+    store.fail_at = None;
+    assert_eq!(run(&mut store, &migrations, 1, 2, 2).unwrap(), (3, 0));
+    assert!(store.migrated.iter().all(|m| *m));
+}
@@ -0,0 +1,65 @@
+mod migration;
+
+use bytes::Bytes;
+use protocol::traits::ServiceSDK;
+use protocol::types::Hash;
+use testing::new_sdk;
+
+use crate::cache::CachedMap;
+use crate::view::{flatten, TruncatedField, MAX_VIEW_ITEMS};
+
+#[test]
+fn test_cached_map() {
+    let mut sdk = new_sdk();
+    let key = Hash::digest(Bytes::from("key"));
+    let mut map: CachedMap<Hash, u64> = CachedMap::new(sdk.alloc_or_recover_map("map").unwrap());
+
+    assert!(!map.contains(&key).unwrap());
+    map.insert(key.clone(), 1).unwrap();
+    assert!(map.contains(&key).unwrap());
+    assert_eq!(map.get(&key).unwrap(), 1);
+
+    // writes go through to the store
+    let other: CachedMap<Hash, u64> = CachedMap::new(sdk.alloc_or_recover_map("map").unwrap());
+    assert_eq!(other.get(&key).unwrap(), 1);
+
+    map.remove(&key).unwrap();
+    assert!(!map.contains(&key).unwrap());
+    let other: CachedMap<Hash, u64> = CachedMap::new(sdk.alloc_or_recover_map("map").unwrap());
+    assert!(!other.contains(&key).unwrap());
+}
+
+#[test]
+fn test_flatten_view() {
+    let value = serde_json::json!({
+        "items": [1, 2, 3],
+        "nested": { "a": { "b": true } },
+        "empty": [],
+        "unset": null,
+    });
+
+    let view = flatten("m".to_owned(), &value, 2, 2);
+    let mut fields: Vec<(&str, &str)> = view
+        .fields
+        .iter()
+        .map(|f| (f.path.as_str(), f.value.as_str()))
+        .collect();
+    fields.sort();
+    assert_eq!(fields, vec![
+        ("empty", "[]"),
+        ("items.0", "1"),
+        ("items.1", "2"),
+        ("nested.a", r#"{"b":true}"#),
+    ]);
+    assert_eq!(view.truncated, vec![TruncatedField {
+        path:  "items".to_owned(),
+        total: 3,
+    }]);
+
+    // zero takes the defaults, the maximum caps the bounds
+    let many = serde_json::Value::Array(vec![0.into(); MAX_VIEW_ITEMS as usize + 1]);
+    assert_eq!(flatten("m".to_owned(), &many, 0, 0).fields.len(), 100);
+    let view = flatten("m".to_owned(), &many, u32::max_value(), 0);
+    assert_eq!(view.fields.len(), MAX_VIEW_ITEMS as usize);
+    assert_eq!(view.truncated[0].path, "value");
+}
@@ -0,0 +1,72 @@
+//! Serde for u128 amounts as decimal strings. JSON numbers lose precision
+//! above 2^53 in javascript clients, so amounts are written as strings. Plain
+//! numbers are still read, as clients built against older versions send them.
+
+use std::fmt;
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+    deserializer.deserialize_any(U128Visitor)
+}
+
+struct U128Visitor;
+
+impl<'de> Visitor<'de> for U128Visitor {
+    type Value = u128;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a u128 as a decimal string or a number")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u128, E> {
+        Ok(u128::from(value))
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<u128, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u128, E> {
+        if value < 0 {
+            return Err(E::invalid_value(de::Unexpected::Signed(value), &self));
+        }
+        Ok(value as u128)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u128, E> {
+        value
+            .parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// The same for an optional amount, `null` for `None`.
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<u128>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u128>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapper(#[serde(with = "super")] u128);
+
+        let value: Option<Wrapper> = Option::deserialize(deserializer)?;
+        Ok(value.map(|Wrapper(value)| value))
+    }
+}
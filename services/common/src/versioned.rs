@@ -0,0 +1,82 @@
+//! Envelope letting write payloads evolve without breaking clients built
+//! against older shapes. A payload is accepted either bare, in its current
+//! shape, or as `{ "version": n, "payload": ... }` with `n` a version its type
+//! still reads.
+
+use std::convert::TryFrom;
+
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+pub trait PayloadVersion: DeserializeOwned {
+    /// Version of the current shape.
+    const VERSION: u32 = 1;
+
+    /// Reads a payload in an older shape, types override this once their
+    /// shape changed.
+    fn from_version(version: u32, _payload: Value) -> Result<Self, String> {
+        Err(format!("unsupported payload version {}", version))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Versioned<T>(pub T);
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    payload: &'a T,
+}
+
+impl<T> Versioned<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Versioned<T> {
+    fn from(payload: T) -> Self {
+        Versioned(payload)
+    }
+}
+
+impl<'de, T: PayloadVersion> Deserialize<'de> for Versioned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let envelope = match value.as_object() {
+            Some(object) if object.len() == 2 => {
+                match (
+                    object.get("version").and_then(Value::as_u64),
+                    object.get("payload"),
+                ) {
+                    (Some(version), Some(payload)) => Some((version, payload)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        let payload = match envelope {
+            Some((version, payload)) if version == u64::from(T::VERSION) => {
+                T::deserialize(payload.clone()).map_err(D::Error::custom)?
+            }
+            Some((version, payload)) => {
+                let version = u32::try_from(version).map_err(D::Error::custom)?;
+                T::from_version(version, payload.clone()).map_err(D::Error::custom)?
+            }
+            None => T::deserialize(value).map_err(D::Error::custom)?,
+        };
+        Ok(Versioned(payload))
+    }
+}
+
+impl<T: PayloadVersion + Serialize> Serialize for Versioned<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Envelope {
+            version: T::VERSION,
+            payload: &self.0,
+        }
+        .serialize(serializer)
+    }
+}
@@ -0,0 +1,237 @@
+//! Read responses flattened for muta's graphql `queryService`, which hands
+//! them to clients as one json string. Each read has a view of flat string
+//! fields, and every leaf of it becomes a field under its dotted path, so
+//! clients read fields instead of walking nested structs.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use protocol::types::Hash;
+
+use crate::amount::Amount;
+use crate::chain::ChainBinding;
+
+pub const DEFAULT_VIEW_ITEMS: u32 = 100;
+pub const MAX_VIEW_ITEMS: u32 = 1_000;
+pub const DEFAULT_VIEW_DEPTH: u32 = 8;
+pub const MAX_VIEW_DEPTH: u32 = 16;
+
+// Path of a response that is a single value, or an empty collection.
+const ROOT_PATH: &str = "value";
+
+/// Calls the named read, with `payload` parsed when it takes one, and
+/// returns its response converted into the view of the read, as json. The
+/// calling crate provides the `ServiceError` the parse and lookup fail with.
+#[macro_export]
+macro_rules! read_value {
+    ($service:expr, $ctx:expr, $method:expr, $payload:expr, {
+        $($name:ident $(: $ty:ty)? => $view:ty),* $(,)?
+    }) => {
+        match $method {
+            $(stringify!($name) => {
+                let resp = $service.$name(
+                    $ctx.clone()
+                    $(, serde_json::from_str::<$ty>($payload).map_err(ServiceError::JsonParse)?)?
+                )?;
+                serde_json::to_value(<$view>::from(resp)).map_err(ServiceError::JsonParse)?
+            })*
+            method => {
+                return Err(ServiceError::UnknownReadMethod {
+                    method: method.to_owned(),
+                }
+                .into())
+            }
+        }
+    };
+}
+
+/// A read method and its payload, whose response `get_view` flattens.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetViewPayload {
+    pub method:    String,
+    // json payload of the read, empty for reads taking none
+    #[serde(default)]
+    pub payload:   String,
+    // items kept of each array or map, 0 for the default
+    #[serde(default)]
+    pub max_items: u32,
+    // levels descended before a subtree is kept as json, 0 for the default
+    #[serde(default)]
+    pub max_depth: u32,
+}
+
+/// A read response as string fields by dotted path, `a.0.b` being field `b`
+/// of the first item of `a`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ReadView {
+    pub method:    String,
+    pub fields:    Vec<ViewField>,
+    pub truncated: Vec<TruncatedField>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ViewField {
+    pub path:  String,
+    pub value: String,
+}
+
+/// A collection cut down to the view's item bound, of `total` items.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct TruncatedField {
+    pub path:  String,
+    pub total: u64,
+}
+
+/// View of a read returning a single value.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ValueView {
+    pub value: String,
+}
+
+/// View of `get_service_info`, the chain ids set when the service is bound
+/// to its networks.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ServiceInfoView {
+    pub version:          String,
+    pub features:         Vec<String>,
+    pub payload_versions: Vec<String>,
+    pub source_chain_id:  Option<String>,
+    pub dest_chain_id:    Option<String>,
+}
+
+impl ServiceInfoView {
+    pub fn new(
+        version: String,
+        features: Vec<String>,
+        payload_versions: &[u32],
+        chain: Option<&ChainBinding>,
+    ) -> Self {
+        ServiceInfoView {
+            version,
+            features,
+            payload_versions: payload_versions.iter().map(u32::to_string).collect(),
+            source_chain_id: chain.map(|chain| chain.source_chain_id.as_hex()),
+            dest_chain_id: chain.map(|chain| chain.dest_chain_id.as_hex()),
+        }
+    }
+}
+
+/// View of `get_migration_status`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MigrationStatusView {
+    pub schema_version: String,
+    pub target_version: String,
+    pub pending:        Vec<String>,
+    pub cursor:         String,
+}
+
+impl MigrationStatusView {
+    pub fn new(
+        schema_version: u64,
+        target_version: u64,
+        pending: Vec<String>,
+        cursor: u64,
+    ) -> Self {
+        MigrationStatusView {
+            schema_version: schema_version.to_string(),
+            target_version: target_version.to_string(),
+            pending,
+            cursor: cursor.to_string(),
+        }
+    }
+}
+
+impl From<String> for ValueView {
+    fn from(value: String) -> Self {
+        ValueView { value }
+    }
+}
+
+impl From<Hash> for ValueView {
+    fn from(value: Hash) -> Self {
+        ValueView {
+            value: value.as_hex(),
+        }
+    }
+}
+
+impl From<Amount> for ValueView {
+    fn from(value: Amount) -> Self {
+        ValueView {
+            value: value.0.to_string(),
+        }
+    }
+}
+
+/// Flattens `value`, keeping the first `max_items` items of each array or
+/// map and descending `max_depth` levels, a subtree below that is kept as
+/// its json. Zero takes the default of either bound, the maximum caps them.
+pub fn flatten(method: String, value: &Value, max_items: u32, max_depth: u32) -> ReadView {
+    let mut view = ReadView {
+        method,
+        fields: vec![],
+        truncated: vec![],
+    };
+    let max_items = bound(max_items, DEFAULT_VIEW_ITEMS, MAX_VIEW_ITEMS) as usize;
+    let max_depth = bound(max_depth, DEFAULT_VIEW_DEPTH, MAX_VIEW_DEPTH) as usize;
+    flatten_into(&mut view, "", value, max_items, max_depth);
+    view
+}
+
+fn bound(value: u32, default: u32, max: u32) -> u32 {
+    match value {
+        0 => default,
+        value => value.min(max),
+    }
+}
+
+fn flatten_into(view: &mut ReadView, path: &str, value: &Value, max_items: usize, depth: usize) {
+    let children: Vec<(String, &Value)> = match value {
+        // an unset option is left out rather than rendered
+        Value::Null => return,
+        Value::Bool(b) => return push(view, path, b.to_string()),
+        Value::Number(n) => return push(view, path, n.to_string()),
+        Value::String(s) => return push(view, path, s.clone()),
+        Value::Array(items) if !items.is_empty() && depth > 0 => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i.to_string(), item))
+            .collect(),
+        Value::Object(map) if !map.is_empty() && depth > 0 => {
+            map.iter().map(|(k, v)| (k.clone(), v)).collect()
+        }
+        // empty, or too deep to descend
+        _ => return push(view, path, value.to_string()),
+    };
+
+    if children.len() > max_items {
+        view.truncated.push(TruncatedField {
+            path:  or_root(path).to_owned(),
+            total: children.len() as u64,
+        });
+    }
+    for (key, child) in children.into_iter().take(max_items) {
+        let child_path = if path.is_empty() {
+            key
+        } else {
+            format!("{}.{}", path, key)
+        };
+        flatten_into(view, &child_path, child, max_items, depth - 1);
+    }
+}
+
+fn push(view: &mut ReadView, path: &str, value: String) {
+    view.fields.push(ViewField {
+        path: or_root(path).to_owned(),
+        value,
+    });
+}
+
+fn or_root(path: &str) -> &str {
+    if path.is_empty() {
+        ROOT_PATH
+    } else {
+        path
+    }
+}
@@ -0,0 +1,124 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{merge, H256};
+
+/// A ckb complete binary merkle tree in array layout, the root first and
+/// leaf `i` at index `i + n - 1` of the `2n - 1` nodes.
+pub struct CbmtTree {
+    nodes:  Vec<H256>,
+    leaves: usize,
+}
+
+impl CbmtTree {
+    pub fn build(leaves: &[H256]) -> Self {
+        let n = leaves.len();
+        if n == 0 {
+            return CbmtTree {
+                nodes:  vec![],
+                leaves: 0,
+            };
+        }
+
+        let mut nodes = vec![[0u8; 32]; 2 * n - 1];
+        nodes[n - 1..].copy_from_slice(leaves);
+        for i in (0..n - 1).rev() {
+            nodes[i] = merge(&nodes[2 * i + 1], &nodes[2 * i + 2]);
+        }
+        CbmtTree { nodes, leaves: n }
+    }
+
+    /// The zero hash for no leaves, as in ckb.
+    pub fn root(&self) -> H256 {
+        self.nodes.first().copied().unwrap_or([0u8; 32])
+    }
+
+    /// Index of leaf `leaf` in the tree, what ckb proofs name the leaf by.
+    pub fn tree_index(&self, leaf: usize) -> Option<u32> {
+        if leaf >= self.leaves {
+            return None;
+        }
+        Some((leaf + self.leaves - 1) as u32)
+    }
+
+    /// Siblings of the path from leaf `leaf` to the root, from the leaf up,
+    /// the lemmas `cbmt_root` takes.
+    pub fn lemmas(&self, leaf: usize) -> Option<Vec<H256>> {
+        let mut index = self.tree_index(leaf)? as usize;
+        let mut lemmas = vec![];
+        while index > 0 {
+            lemmas.push(self.nodes[((index + 1) ^ 1) - 1]);
+            index = (index - 1) / 2;
+        }
+        Some(lemmas)
+    }
+}
+
+/// Computes the root of a ckb complete binary merkle tree from a single leaf,
+/// its index in the tree (as returned by ckb `get_transaction_proof`) and the
+/// sibling lemmas ordered from the leaf up.
+pub fn cbmt_root(leaf: &H256, tree_index: u32, lemmas: &[H256]) -> H256 {
+    let mut node = *leaf;
+    let mut index = tree_index;
+
+    for lemma in lemmas.iter() {
+        if index == 0 {
+            break;
+        }
+
+        // Odd indices are left children in the array layout of the tree.
+        node = if index % 2 == 1 {
+            merge(&node, lemma)
+        } else {
+            merge(lemma, &node)
+        };
+        index = (index - 1) / 2;
+    }
+
+    node
+}
+
+/// Computes the root of the tree holding all of `leaves`.
+pub fn cbmt_build_root(leaves: &[H256]) -> H256 {
+    CbmtTree::build(leaves).root()
+}
+
+/// Computes the root of a ckb complete binary merkle tree from several leaves
+/// at once, as proven by ckb `get_transaction_proof` for several txs. Each
+/// lemma is a node none of the leaves lead to, in the order the leaves climb
+/// the tree from the deepest. `None` when the proof doesn't fit the leaves.
+pub fn cbmt_multi_root(leaves: &[(u32, H256)], lemmas: &[H256]) -> Option<H256> {
+    let mut sorted = leaves.to_vec();
+    sorted.sort_by(|a, b| b.0.cmp(&a.0));
+    sorted.dedup_by(|a, b| a.0 == b.0);
+    if sorted.is_empty() || sorted.len() != leaves.len() {
+        return None;
+    }
+
+    let mut queue: VecDeque<(u32, H256)> = sorted.into_iter().collect();
+    let mut lemmas = lemmas.iter();
+    while let Some((index, node)) = queue.pop_front() {
+        if index == 0 {
+            if lemmas.next().is_none() && queue.is_empty() {
+                return Some(node);
+            }
+            return None;
+        }
+
+        let sibling_index = ((index + 1) ^ 1) - 1;
+        let sibling = match queue.front() {
+            Some((front, _)) if *front == sibling_index => queue.pop_front().map(|(_, h)| h),
+            _ => lemmas.next().copied(),
+        }?;
+
+        let parent = if index % 2 == 1 {
+            merge(&node, &sibling)
+        } else {
+            merge(&sibling, &node)
+        };
+        queue.push_back(((index - 1) / 2, parent));
+    }
+
+    None
+}
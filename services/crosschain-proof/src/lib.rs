@@ -0,0 +1,50 @@
+//! The ckb merkle proofs and deposit witnesses the bridge reads, built and
+//! checked over plain 32 byte hashes, so the service, relayers and ckb
+//! scripts share one implementation.
+//!
+//! Without the default `std` feature the crate needs only `alloc`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod cbmt;
+#[cfg(test)]
+mod tests;
+mod tx_proof;
+mod witness;
+
+pub use cbmt::{cbmt_build_root, cbmt_multi_root, cbmt_root, CbmtTree};
+pub use tx_proof::{build_tx_proofs, transactions_root, TxProof, PACKED_PROOF_HEADER_LEN};
+pub use witness::{
+    parse_tag_witness, receiver_witness, split_receiver_witness, tag_witness, MUTA_ADDRESS_LEN,
+    TAG_LEN, TAG_PREFIX,
+};
+
+use blake2b_simd::Params;
+
+pub type H256 = [u8; 32];
+
+pub const HASH_LEN: usize = 32;
+const CKB_HASH_PERSONALIZATION: &[u8] = b"ckb-default-hash";
+
+/// Blake2b-256 with ckb personalization, the hash function used across ckb.
+pub fn ckb_blake2b(data: &[u8]) -> H256 {
+    let hash = Params::new()
+        .hash_length(HASH_LEN)
+        .personal(CKB_HASH_PERSONALIZATION)
+        .hash(data);
+
+    let mut out = [0u8; HASH_LEN];
+    out.copy_from_slice(hash.as_bytes());
+    out
+}
+
+/// Merges two nodes of a ckb complete binary merkle tree.
+pub fn merge(left: &H256, right: &H256) -> H256 {
+    let mut data = [0u8; 2 * HASH_LEN];
+    data[..HASH_LEN].copy_from_slice(left);
+    data[HASH_LEN..].copy_from_slice(right);
+
+    ckb_blake2b(&data)
+}
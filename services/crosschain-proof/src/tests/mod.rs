@@ -0,0 +1,98 @@
+use crate::{
+    build_tx_proofs, cbmt_build_root, cbmt_multi_root, cbmt_root, ckb_blake2b, merge,
+    parse_tag_witness, receiver_witness, split_receiver_witness, tag_witness, CbmtTree, TxProof,
+    H256,
+};
+
+fn leaf(n: u8) -> H256 {
+    ckb_blake2b(&[n])
+}
+
+#[test]
+fn test_ckb_blake2b_empty() {
+    // blake2b-256 of nothing with ckb personalization, as ckb documents it
+    assert_eq!(
+        hex::encode(ckb_blake2b(&[])),
+        "44f4c69744d5f8c55d642062949dcae49bc4e7ef43d388c5a12f42b5633d163e"
+    );
+}
+
+#[test]
+fn test_tree_lemmas_prove_every_leaf() {
+    for n in 1..=9u8 {
+        let leaves: Vec<H256> = (0..n).map(leaf).collect();
+        let tree = CbmtTree::build(&leaves);
+        for (i, leaf) in leaves.iter().enumerate() {
+            let index = tree.tree_index(i).unwrap();
+            let lemmas = tree.lemmas(i).unwrap();
+            assert_eq!(cbmt_root(leaf, index, &lemmas), tree.root());
+        }
+        assert!(tree.lemmas(n as usize).is_none());
+        assert_eq!(cbmt_build_root(&leaves), tree.root());
+    }
+    assert_eq!(cbmt_build_root(&[]), [0u8; 32]);
+}
+
+#[test]
+fn test_multi_root_matches_tree() {
+    let leaves: Vec<H256> = (0..5).map(leaf).collect();
+    let tree = CbmtTree::build(&leaves);
+    // leaves 1 and 2, tree indices 5 and 6, share a parent, whose sibling
+    // holds leaves 3, 4 and 0 and is the only lemma
+    let proven = vec![(5, leaves[1]), (6, leaves[2])];
+    let lemmas = vec![merge(&merge(&leaves[3], &leaves[4]), &leaves[0])];
+    assert_eq!(cbmt_multi_root(&proven, &lemmas), Some(tree.root()));
+    assert_eq!(cbmt_multi_root(&proven, &[]), None);
+    assert_eq!(cbmt_multi_root(&[], &lemmas), None);
+}
+
+#[test]
+fn test_tx_proofs_pack_and_verify() {
+    let txs: Vec<(H256, H256)> = (0..3).map(|n| (leaf(n), leaf(n + 100))).collect();
+    let (root, proofs) = build_tx_proofs(&txs);
+
+    assert_eq!(proofs.len(), 3);
+    for ((tx_hash, witness_hash), proof) in txs.iter().zip(proofs.iter()) {
+        assert_eq!(proof.transactions_root(tx_hash), root);
+        assert!(proof.verify_witnesses(witness_hash));
+        assert!(!proof.verify_witnesses(tx_hash));
+
+        let packed = proof.pack();
+        assert_eq!(TxProof::unpack(&packed).as_ref(), Some(proof));
+        // a lemma short or a flag out of range doesn't unpack
+        assert!(TxProof::unpack(&packed[..packed.len() - 32]).is_none());
+        let mut flagged = packed.clone();
+        flagged[4] = 2;
+        assert!(TxProof::unpack(&flagged).is_none());
+    }
+
+    let without_witnesses = TxProof {
+        witness_lemmas: None,
+        ..proofs[0].clone()
+    };
+    assert_eq!(
+        TxProof::unpack(&without_witnesses.pack()),
+        Some(without_witnesses.clone())
+    );
+    assert!(!without_witnesses.verify_witnesses(&txs[0].1));
+}
+
+#[test]
+fn test_deposit_witnesses() {
+    let receiver = [7u8; 20];
+    let witness = receiver_witness(&receiver, "memo");
+    assert_eq!(
+        split_receiver_witness(&witness),
+        Some((&receiver[..], &b"memo"[..]))
+    );
+    assert_eq!(split_receiver_witness(&receiver[..19]), None);
+    assert_eq!(parse_tag_witness(&witness), None);
+
+    let witness = tag_witness(0x0102_0304_0506, "memo");
+    assert_eq!(&witness[..4], b"tag:");
+    assert_eq!(
+        parse_tag_witness(&witness),
+        Some((0x0102_0304_0506, &b"memo"[..]))
+    );
+    assert_eq!(parse_tag_witness(&witness[..9]), None);
+}
@@ -0,0 +1,130 @@
+use alloc::vec::Vec;
+
+use crate::cbmt::{cbmt_root, CbmtTree};
+use crate::{merge, H256, HASH_LEN};
+
+/// Tree index, witness flag and witnesses root, ahead of the lemmas.
+pub const PACKED_PROOF_HEADER_LEN: usize = 4 + 1 + HASH_LEN;
+
+/// Proof of a tx, and optionally of its witnesses, against the transactions
+/// root of its block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxProof {
+    pub index:          u32, // index of the tx in the merkle tree
+    pub lemmas:         Vec<H256>,
+    pub witnesses_root: H256,
+    pub witness_lemmas: Option<Vec<H256>>,
+}
+
+impl TxProof {
+    /// Packs the proof in the binary form of v2 `submit_messages` payloads:
+    /// the tree index as 4 big endian bytes, a byte set to 1 when witness
+    /// lemmas follow, the witnesses root, then the lemmas and the witness
+    /// lemmas, 32 bytes each. Lemma counts aren't written, the proof of the
+    /// node at tree index `i` has `floor(log2(i + 1))` of each, the depth of
+    /// the node.
+    pub fn pack(&self) -> Vec<u8> {
+        let witness_lemmas = self.witness_lemmas.as_deref().unwrap_or_default();
+        let mut data = Vec::with_capacity(
+            PACKED_PROOF_HEADER_LEN + (self.lemmas.len() + witness_lemmas.len()) * HASH_LEN,
+        );
+        data.extend_from_slice(&self.index.to_be_bytes());
+        data.push(self.witness_lemmas.is_some() as u8);
+        data.extend_from_slice(&self.witnesses_root);
+        for lemma in self.lemmas.iter().chain(witness_lemmas.iter()) {
+            data.extend_from_slice(lemma);
+        }
+        data
+    }
+
+    /// Reads a packed proof, `None` unless it has exactly the lemmas its
+    /// tree index calls for.
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() < PACKED_PROOF_HEADER_LEN {
+            return None;
+        }
+        let mut index = [0u8; 4];
+        index.copy_from_slice(&data[..4]);
+        let index = u32::from_be_bytes(index);
+        let with_witnesses = match data[4] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+
+        let depth = cbmt_depth(index);
+        let lemma_count = if with_witnesses { 2 * depth } else { depth };
+        if data.len() != PACKED_PROOF_HEADER_LEN + lemma_count * HASH_LEN {
+            return None;
+        }
+        let mut hashes = data[5..].chunks(HASH_LEN).map(|chunk| {
+            let mut hash = [0u8; HASH_LEN];
+            hash.copy_from_slice(chunk);
+            hash
+        });
+        let witnesses_root = hashes.next()?;
+        let lemmas = hashes.by_ref().take(depth).collect();
+        let witness_lemmas = if with_witnesses {
+            Some(hashes.collect())
+        } else {
+            None
+        };
+
+        Some(TxProof {
+            index,
+            lemmas,
+            witnesses_root,
+            witness_lemmas,
+        })
+    }
+
+    /// The transactions root the proof leads `tx_hash` to.
+    pub fn transactions_root(&self, tx_hash: &H256) -> H256 {
+        transactions_root(tx_hash, self.index, &self.lemmas, &self.witnesses_root)
+    }
+
+    /// Whether the witness lemmas lead `witness_hash` to the witnesses root,
+    /// only meaningful once the tx is proven.
+    pub fn verify_witnesses(&self, witness_hash: &H256) -> bool {
+        match &self.witness_lemmas {
+            Some(lemmas) => cbmt_root(witness_hash, self.index, lemmas) == self.witnesses_root,
+            None => false,
+        }
+    }
+}
+
+/// A ckb header commits to `merge(raw_transactions_root, witnesses_root)`.
+pub fn transactions_root(
+    tx_hash: &H256,
+    tree_index: u32,
+    lemmas: &[H256],
+    witnesses_root: &H256,
+) -> H256 {
+    let raw_transactions_root = cbmt_root(tx_hash, tree_index, lemmas);
+    merge(&raw_transactions_root, witnesses_root)
+}
+
+/// The transactions root of a block of txs, given by their hashes and
+/// witness hashes in block order, and a proof of each tx and its witnesses.
+pub fn build_tx_proofs(txs: &[(H256, H256)]) -> (H256, Vec<TxProof>) {
+    let tx_hashes: Vec<H256> = txs.iter().map(|(tx_hash, _)| *tx_hash).collect();
+    let witness_hashes: Vec<H256> = txs.iter().map(|(_, witness_hash)| *witness_hash).collect();
+    let tx_tree = CbmtTree::build(&tx_hashes);
+    let witness_tree = CbmtTree::build(&witness_hashes);
+
+    let proofs = (0..txs.len())
+        .map(|leaf| TxProof {
+            index:          tx_tree.tree_index(leaf).unwrap(),
+            lemmas:         tx_tree.lemmas(leaf).unwrap(),
+            witnesses_root: witness_tree.root(),
+            witness_lemmas: witness_tree.lemmas(leaf),
+        })
+        .collect();
+
+    (merge(&tx_tree.root(), &witness_tree.root()), proofs)
+}
+
+// Depth of the node at `index` in the array layout of a tree, the root at 0.
+fn cbmt_depth(index: u32) -> usize {
+    (63 - (u64::from(index) + 1).leading_zeros()) as usize
+}
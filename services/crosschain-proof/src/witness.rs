@@ -0,0 +1,48 @@
+use alloc::vec::Vec;
+
+/// Bytes of a muta address.
+pub const MUTA_ADDRESS_LEN: usize = 20;
+/// Starts a deposit witness naming a deposit tag instead of the receiver.
+pub const TAG_PREFIX: &[u8] = b"tag:";
+/// Bytes of a deposit tag, few enough for the tag to be a safe json integer.
+pub const TAG_LEN: usize = 6;
+
+/// The witness of a deposit to `receiver`, its address followed by the utf8
+/// memo.
+pub fn receiver_witness(receiver: &[u8; MUTA_ADDRESS_LEN], memo: &str) -> Vec<u8> {
+    let mut witness = receiver.to_vec();
+    witness.extend_from_slice(memo.as_bytes());
+    witness
+}
+
+/// The witness of a deposit to whoever registered `tag`, `TAG_PREFIX`
+/// followed by the big endian tag and the utf8 memo. Only the low `TAG_LEN`
+/// bytes of the tag are written.
+pub fn tag_witness(tag: u64, memo: &str) -> Vec<u8> {
+    let mut witness = TAG_PREFIX.to_vec();
+    witness.extend_from_slice(&tag.to_be_bytes()[8 - TAG_LEN..]);
+    witness.extend_from_slice(memo.as_bytes());
+    witness
+}
+
+/// Splits a binary receiver witness into the address bytes and the memo
+/// bytes, `None` when too short to hold an address.
+pub fn split_receiver_witness(witness: &[u8]) -> Option<(&[u8], &[u8])> {
+    if witness.len() < MUTA_ADDRESS_LEN {
+        return None;
+    }
+    Some(witness.split_at(MUTA_ADDRESS_LEN))
+}
+
+/// Reads a tag witness into the tag and the memo bytes, `None` when the
+/// witness doesn't name a tag.
+pub fn parse_tag_witness(witness: &[u8]) -> Option<(u64, &[u8])> {
+    if !witness.starts_with(TAG_PREFIX) || witness.len() < TAG_PREFIX.len() + TAG_LEN {
+        return None;
+    }
+    let (tag, memo) = witness[TAG_PREFIX.len()..].split_at(TAG_LEN);
+    let tag = tag
+        .iter()
+        .fold(0, |tag, byte| (tag << 8) | u64::from(*byte));
+    Some((tag, memo))
+}
@@ -0,0 +1,237 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use serde_json::{json, Value};
+
+use crosschain::molecule::{
+    decode_transaction, encode_raw_transaction, encode_transaction, TxView,
+};
+use crosschain::proof::{ckb_blake2b, merge, pack_tx_proof};
+use crosschain::types::{CkbTx, CkbTxProof, EpochView, MessagePayload};
+use crosschain::{parse_deposit, parse_raw_deposit, CrosschainService};
+use protocol::traits::Service;
+use protocol::types::{Address, Bytes, Hash};
+use testing::{new_sdk, ContextBuilder, MockServiceSDK};
+
+// A sudt deposit of 1000 to 0x0101..01, as a ckb json rpc renders it. The
+// type script code hash is the one the service accepts.
+const DEPOSIT_TX: &str = r#"{
+    "version": "0x0",
+    "cell_deps": [],
+    "header_deps": [],
+    "inputs": [{
+        "since": "0x0",
+        "previous_output": {
+            "tx_hash": "0xa98c57135830e1b91345948df6c4b8870828199a786b26f09f7dec4bc27a73da",
+            "index": "0x1"
+        }
+    }],
+    "outputs": [{
+        "capacity": "0x34e62ce00",
+        "lock": {
+            "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+            "hash_type": "type",
+            "args": "0x"
+        },
+        "type": {
+            "code_hash": "0x57dd0067814dab356e05c6def0d094bb79776711e68ffdfad2df6a7f877f7db6",
+            "hash_type": "data",
+            "args": "0xa98c57135830e1b91345948df6c4b8870828199a786b26f09f7dec4bc27a73da"
+        }
+    }],
+    "outputs_data": ["0xe8030000000000000000000000000000"],
+    "witnesses": ["0x0101010101010101010101010101010101010101"]
+}"#;
+
+fn deposit_tx() -> CkbTx {
+    serde_json::from_str(DEPOSIT_TX).unwrap()
+}
+
+fn bench_parse_deposit(c: &mut Criterion) {
+    let tx = deposit_tx();
+    let data = encode_transaction(&tx).unwrap();
+    assert!(parse_deposit(&tx).is_ok());
+
+    c.bench_function("parse_deposit decoded", |b| {
+        b.iter(|| {
+            let (tx, _) = decode_transaction(black_box(&data)).unwrap();
+            parse_deposit(&tx).unwrap()
+        })
+    });
+    c.bench_function("parse_deposit in place", |b| {
+        b.iter(|| {
+            let view = TxView::parse(black_box(&data)).unwrap();
+            parse_raw_deposit(&view).unwrap()
+        })
+    });
+}
+
+fn bench_message_payload(c: &mut Criterion) {
+    let data = encode_transaction(&deposit_tx()).unwrap();
+    let message = format!(r#"{{"raw_tx": "0x{}"}}"#, hex::encode(&data));
+    let payload = format!(
+        r#"{{"height": 1, "messages": [{}]}}"#,
+        vec![message; 100].join(",")
+    );
+
+    c.bench_function("message payload of 100 raw txs", |b| {
+        b.iter(|| serde_json::from_str::<MessagePayload>(black_box(&payload)).unwrap())
+    });
+}
+
+fn address(n: u64) -> Address {
+    Address::from_hex(&format!("0x{:040x}", n)).unwrap()
+}
+
+fn write(
+    service: &mut CrosschainService<MockServiceSDK>,
+    caller: &Address,
+    method: &str,
+    payload: Value,
+) {
+    let ctx = ContextBuilder::new(caller.clone())
+        .method(method, payload.to_string())
+        .build();
+    service.write_(ctx).unwrap();
+}
+
+/// A service whose admin, `address(1)`, is also its relayer.
+fn crosschain_service() -> CrosschainService<MockServiceSDK> {
+    let admin = address(1);
+    let mut service = CrosschainService::new(new_sdk()).unwrap();
+    let _ = service.genesis_(json!({ "admin": admin }).to_string());
+    write(
+        &mut service,
+        &admin,
+        "add_relayer",
+        json!({ "address": admin }),
+    );
+    service
+}
+
+fn header(number: u64, transactions_root: &Hash) -> Value {
+    let epoch = EpochView {
+        number: 0,
+        index:  number,
+        length: 1_000,
+    };
+    let zero = Hash::digest(Bytes::from("zero"));
+    json!({
+        "compact_target": "0x1",
+        "version": "0x0",
+        "timestamp": "0x0",
+        "number": format!("0x{:x}", number),
+        "epoch": format!("0x{:x}", epoch.to_packed()),
+        "parent_hash": zero,
+        "transactions_root": transactions_root,
+        "proposals_hash": zero,
+        "uncles_hash": zero,
+        "dao": zero,
+        "nonce": "0x0",
+    })
+}
+
+// A ckb merkle tree of the leaves in array layout, the root first.
+fn cbmt(leaves: &[Hash]) -> Vec<Hash> {
+    let n = leaves.len();
+    let mut nodes = vec![leaves[0].clone(); 2 * n - 1];
+    nodes[n - 1..].clone_from_slice(leaves);
+    for i in (0..n - 1).rev() {
+        nodes[i] = merge(&nodes[2 * i + 1], &nodes[2 * i + 2]).unwrap();
+    }
+    nodes
+}
+
+fn lemmas(nodes: &[Hash], mut index: usize) -> Vec<Hash> {
+    let mut lemmas = vec![];
+    while index > 0 {
+        lemmas.push(nodes[((index + 1) ^ 1) - 1].clone());
+        index = (index - 1) / 2;
+    }
+    lemmas
+}
+
+/// The header of a block holding `count` deposits, and the messages proving
+/// them. The deposits are of a sudt the service doesn't know, so they go
+/// through verification and are rejected before minting, which needs the
+/// asset service.
+fn block_of_deposits(count: usize) -> (Value, Vec<Value>) {
+    let mut raws = vec![];
+    let mut tx_hashes = vec![];
+    let mut witness_hashes = vec![];
+    for n in 0..count {
+        let mut tx = deposit_tx();
+        tx.inputs[0].previous_output.index =
+            serde_json::from_value(json!(format!("0x{:x}", n))).unwrap();
+        let raw = encode_transaction(&tx).unwrap();
+        tx_hashes.push(ckb_blake2b(&encode_raw_transaction(&tx).unwrap()).unwrap());
+        witness_hashes.push(ckb_blake2b(&raw).unwrap());
+        raws.push(raw);
+    }
+
+    let tx_tree = cbmt(&tx_hashes);
+    let witness_tree = cbmt(&witness_hashes);
+    let transactions_root = merge(&tx_tree[0], &witness_tree[0]).unwrap();
+    let messages = raws
+        .iter()
+        .enumerate()
+        .map(|(n, raw)| {
+            let index = n + count - 1;
+            let proof = CkbTxProof {
+                index:          index as u32,
+                lemmas:         lemmas(&tx_tree, index),
+                witnesses_root: witness_tree[0].clone(),
+                witness_lemmas: Some(lemmas(&witness_tree, index)),
+            };
+            json!({
+                "raw_tx": format!("0x{}", hex::encode(raw)),
+                "proof": format!("0x{}", hex::encode(pack_tx_proof(&proof))),
+            })
+        })
+        .collect();
+
+    (header(1, &transactions_root), messages)
+}
+
+fn bench_submit_messages(c: &mut Criterion) {
+    let relayer = address(1);
+    for count in [1, 10, 100].iter() {
+        let (header, messages) = block_of_deposits(*count);
+        let payload = json!({ "height": 1, "messages": messages });
+
+        c.bench_function(&format!("submit_messages of {}", count), |b| {
+            b.iter_batched(
+                || {
+                    let mut service = crosschain_service();
+                    let headers = json!({ "headers": [header.clone()] });
+                    write(&mut service, &relayer, "update_headers", headers);
+                    service
+                },
+                |mut service| write(&mut service, &relayer, "submit_messages", payload.clone()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+fn bench_update_headers(c: &mut Criterion) {
+    let relayer = address(1);
+    let root = Hash::digest(Bytes::from("root"));
+    let headers: Vec<Value> = (1..=100).map(|number| header(number, &root)).collect();
+    let payload = json!({ "headers": headers });
+
+    c.bench_function("update_headers of 100", |b| {
+        b.iter_batched(
+            crosschain_service,
+            |mut service| write(&mut service, &relayer, "update_headers", payload.clone()),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_parse_deposit,
+    bench_message_payload,
+    bench_submit_messages,
+    bench_update_headers
+);
+criterion_main!(benches);
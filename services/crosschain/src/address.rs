@@ -0,0 +1,147 @@
+//! Address formats the bridge accepts from users: ckb addresses, bech32 or the
+//! hex of their payload, as burn receivers, and muta addresses, hex or bech32,
+//! as deposit receivers.
+
+use bech32::FromBase32;
+use bytes::Bytes;
+use derive_more::Display;
+
+use protocol::types::{Address, Hash};
+
+use crate::types::{Script, ScriptHashType};
+
+const CKB_MAINNET_HRP: &str = "ckb";
+const CKB_TESTNET_HRP: &str = "ckt";
+pub const MUTA_HRP: &str = "muta";
+
+const SHORT_FORMAT: u8 = 0x01;
+const FULL_DATA_FORMAT: u8 = 0x02;
+const FULL_TYPE_FORMAT: u8 = 0x04;
+
+// Short format code hash indexes, both scripts share their code hash across
+// mainnet and testnet.
+const SECP256K1_BLAKE160_INDEX: u8 = 0x00;
+const SECP256K1_MULTISIG_INDEX: u8 = 0x01;
+pub const SECP256K1_BLAKE160_CODE_HASH: &str =
+    "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8";
+pub const SECP256K1_MULTISIG_CODE_HASH: &str =
+    "0x5c5069eb0857efc65e1bca0c07df34c31663b3622fd3876c876320fc9634e2a8";
+const BLAKE160_LEN: usize = 20;
+const MUTA_ADDRESS_LEN: usize = 20;
+
+#[derive(Debug, Display, Clone, PartialEq)]
+pub enum AddressError {
+    #[display(fmt = "not bech32 nor hex")]
+    Encoding,
+
+    #[display(fmt = "unknown prefix {}", _0)]
+    Prefix(String),
+
+    #[display(fmt = "unknown payload format {}", _0)]
+    Format(u8),
+
+    #[display(fmt = "unknown code hash index {}", _0)]
+    CodeHashIndex(u8),
+
+    #[display(fmt = "invalid payload length {}", _0)]
+    Length(usize),
+}
+
+/// A ckb address, down to the lock script it stands for.
+#[derive(Clone, Debug)]
+pub struct CkbAddress {
+    pub lock: Script,
+}
+
+impl CkbAddress {
+    /// Parses a bech32 address, short or full format, or the `0x` hex of its
+    /// payload.
+    pub fn parse(address: &str) -> Result<Self, AddressError> {
+        let payload = if address.starts_with("0x") {
+            hex::decode(&address[2..]).map_err(|_| AddressError::Encoding)?
+        } else {
+            let (hrp, payload) = decode_bech32(address)?;
+            if hrp != CKB_MAINNET_HRP && hrp != CKB_TESTNET_HRP {
+                return Err(AddressError::Prefix(hrp));
+            }
+            payload
+        };
+
+        Self::from_payload(&payload)
+    }
+
+    fn from_payload(payload: &[u8]) -> Result<Self, AddressError> {
+        let (format, rest) = payload
+            .split_first()
+            .ok_or(AddressError::Length(payload.len()))?;
+
+        let (code_hash, hash_type, args) = match *format {
+            SHORT_FORMAT => {
+                let (index, args) = rest
+                    .split_first()
+                    .ok_or(AddressError::Length(payload.len()))?;
+                let code_hash = match *index {
+                    SECP256K1_BLAKE160_INDEX => SECP256K1_BLAKE160_CODE_HASH,
+                    SECP256K1_MULTISIG_INDEX => SECP256K1_MULTISIG_CODE_HASH,
+                    index => return Err(AddressError::CodeHashIndex(index)),
+                };
+                if args.len() != BLAKE160_LEN {
+                    return Err(AddressError::Length(payload.len()));
+                }
+                let code_hash = Hash::from_hex(code_hash).expect("valid code hash");
+                (code_hash, ScriptHashType::Type, args)
+            }
+            FULL_DATA_FORMAT | FULL_TYPE_FORMAT => {
+                if rest.len() < 32 {
+                    return Err(AddressError::Length(payload.len()));
+                }
+                let (code_hash, args) = rest.split_at(32);
+                let code_hash = Hash::from_bytes(Bytes::from(code_hash.to_vec()))
+                    .map_err(|_| AddressError::Length(payload.len()))?;
+                let hash_type = if *format == FULL_TYPE_FORMAT {
+                    ScriptHashType::Type
+                } else {
+                    ScriptHashType::data
+                };
+                (code_hash, hash_type, args)
+            }
+            format => return Err(AddressError::Format(format)),
+        };
+
+        // `Hex::from_string` rejects an empty payload, serde takes it.
+        let args = serde_json::from_value(serde_json::Value::String(
+            "0x".to_owned() + &hex::encode(args),
+        ))
+        .map_err(|_| AddressError::Encoding)?;
+
+        Ok(CkbAddress {
+            lock: Script {
+                code_hash,
+                hash_type,
+                args,
+            },
+        })
+    }
+}
+
+/// Parses a muta address, `0x` hex or bech32 with the `muta` prefix.
+pub fn parse_muta_address(address: &str) -> Result<Address, AddressError> {
+    if address.starts_with("0x") {
+        return Address::from_hex(address).map_err(|_| AddressError::Encoding);
+    }
+
+    let (hrp, payload) = decode_bech32(address)?;
+    if hrp != MUTA_HRP {
+        return Err(AddressError::Prefix(hrp));
+    }
+    if payload.len() != MUTA_ADDRESS_LEN {
+        return Err(AddressError::Length(payload.len()));
+    }
+    Address::from_bytes(Bytes::from(payload)).map_err(|_| AddressError::Encoding)
+}
+
+fn decode_bech32(address: &str) -> Result<(String, Vec<u8>), AddressError> {
+    let (hrp, data) = bech32::decode(address).map_err(|_| AddressError::Encoding)?;
+    let payload = Vec::<u8>::from_base32(&data).map_err(|_| AddressError::Encoding)?;
+    Ok((hrp, payload))
+}
@@ -0,0 +1,87 @@
+//! Generic calls relayed from ckb. A message tx whose first output holds
+//! `CALL_PREFIX` followed by `<service> <method> <payload>` asks the bridge to
+//! call that method of a muta service, once proven. The type script of the
+//! output, which only its contract lets a tx create, tells the service who
+//! sent the call.
+
+use bytes::Bytes;
+use serde_json::Value;
+
+use protocol::types::Hash;
+
+use crate::molecule;
+use crate::types::{CkbTx, CrossCall, MessageError, RejectReason};
+
+/// Starts the data of a cell carrying a call.
+pub const CALL_PREFIX: &[u8] = b"call:";
+/// Longest payload, in bytes, a call may carry.
+pub const MAX_CALL_PAYLOAD_LEN: usize = 1024;
+
+// Fields of a call tx named in rejections.
+const SENDER_FIELD: &str = "outputs[0].type";
+const DATA_FIELD: &str = "outputs_data[0]";
+
+/// The call a ckb tx carries, `None` if it carries none.
+pub fn parse_call(tx: &CkbTx) -> Option<Result<CrossCall, MessageError>> {
+    let data = hex::decode(tx.outputs_data.get(0)?.as_string_trim0x()).ok()?;
+    if !data.starts_with(CALL_PREFIX) {
+        return None;
+    }
+    let sender = tx
+        .outputs
+        .get(0)
+        .and_then(|output| output.type_.as_ref())
+        .and_then(|script| {
+            let args = hex::decode(script.args.as_string_trim0x()).ok()?;
+            Some((script.code_hash.as_bytes().to_vec(), args))
+        });
+
+    Some(match sender {
+        Some((code_hash, args)) => call_from_parts(&code_hash, &args, &data),
+        None => Err(RejectReason::InvalidTx.at(SENDER_FIELD)),
+    })
+}
+
+/// `parse_call` of a tx in its molecule encoding.
+pub fn parse_raw_call(view: &molecule::TxView) -> Option<Result<CrossCall, MessageError>> {
+    let data = view.output_data(0).ok()??;
+    if !data.starts_with(CALL_PREFIX) {
+        return None;
+    }
+
+    Some(match view.output_type(0) {
+        Ok(Some((code_hash, args))) => call_from_parts(code_hash, args, data),
+        _ => Err(RejectReason::InvalidTx.at(SENDER_FIELD)),
+    })
+}
+
+fn call_from_parts(code_hash: &[u8], args: &[u8], data: &[u8]) -> Result<CrossCall, MessageError> {
+    let code_hash = Hash::from_bytes(Bytes::from(code_hash.to_vec()))
+        .map_err(|_| RejectReason::InvalidTx.at(SENDER_FIELD))?;
+    let text = std::str::from_utf8(&data[CALL_PREFIX.len()..])
+        .map_err(|_| RejectReason::InvalidTx.at(DATA_FIELD))?;
+    let mut parts = text.splitn(3, ' ');
+    let (service, method) = match (parts.next(), parts.next()) {
+        (Some(service), Some(method)) if !service.is_empty() && !method.is_empty() => {
+            (service, method)
+        }
+        _ => return Err(RejectReason::InvalidTx.at(DATA_FIELD)),
+    };
+    let payload = parts.next().unwrap_or_default();
+    if payload.len() > MAX_CALL_PAYLOAD_LEN {
+        return Err(RejectReason::InvalidTx.at(DATA_FIELD));
+    }
+
+    // `Hex::from_string` rejects the empty args a script may have, serde
+    // takes them.
+    let args = serde_json::from_value(Value::String(format!("0x{}", hex::encode(args))))
+        .map_err(|_| RejectReason::InvalidTx.at(SENDER_FIELD))?;
+
+    Ok(CrossCall {
+        service: service.to_owned(),
+        method: method.to_owned(),
+        payload: payload.to_owned(),
+        code_hash,
+        args,
+    })
+}
@@ -0,0 +1,324 @@
+//! Turning a ckb deposit tx into what the bridge mints. Everything here is
+//! pure: parsing reads only the tx, and `process` only the deposit and the
+//! bridge config it is given, so the service is left with the state reads and
+//! the mints.
+
+use bytes::Bytes;
+
+use common::amount::Amount;
+use protocol::types::{Address, Hash, Hex};
+
+use crate::address;
+use crate::molecule;
+use crate::params;
+use crate::types::{
+    CkbTx, Deposit, FeeBeneficiary, FeeSchedule, MessageError, MirrorAsset, RejectReason,
+};
+use crate::MAX_MEMO_LEN;
+
+pub use crosschain_proof::TAG_PREFIX;
+
+/// Basis points of a whole amount.
+pub const FEE_BPS: u16 = 10_000;
+// bounds the schedule every deposit reads
+pub const MAX_FEE_TIERS: usize = 16;
+
+// Fields of a deposit tx named in rejections.
+const OUTPUT_FIELD: &str = "outputs[0]";
+const TYPE_SCRIPT_FIELD: &str = "outputs[0].type";
+const CODE_HASH_FIELD: &str = "outputs[0].type.code_hash";
+const SUDT_ARGS_FIELD: &str = "outputs[0].type.args";
+const AMOUNT_FIELD: &str = "outputs_data[0]";
+const WITNESS_FIELD: &str = "witnesses";
+
+/// Parses the sudt deposit carried by the first output of a ckb tx, the
+/// receiver on muta is taken from the last witness.
+pub fn parse_deposit(tx: &CkbTx) -> Result<Deposit, MessageError> {
+    parse_deposit_of(tx, &params::default_sudt_code_hash())
+}
+
+/// `parse_deposit` of the sudt whose type script has `sudt_code_hash`, as
+/// the bridge's parameters name it.
+pub fn parse_deposit_of(tx: &CkbTx, sudt_code_hash: &Hash) -> Result<Deposit, MessageError> {
+    let output = tx
+        .outputs
+        .get(0)
+        .ok_or_else(|| RejectReason::InvalidTx.at(OUTPUT_FIELD))?;
+    let type_script = output
+        .type_
+        .as_ref()
+        .ok_or_else(|| RejectReason::InvalidTx.at(TYPE_SCRIPT_FIELD))?;
+    let args = hex::decode(type_script.args.as_string_trim0x().as_str())
+        .map_err(|_| RejectReason::InvalidTx.at(SUDT_ARGS_FIELD))?;
+    let output_data = tx
+        .outputs_data
+        .get(0)
+        .ok_or_else(|| RejectReason::InvalidTx.at(AMOUNT_FIELD))?;
+    let amount_bytes = hex::decode(output_data.as_string_trim0x().as_str())
+        .map_err(|_| RejectReason::InvalidAmount.at(AMOUNT_FIELD))?;
+    let witness = tx
+        .witnesses
+        .last()
+        .ok_or_else(|| RejectReason::InvalidTx.at(WITNESS_FIELD))?;
+    let witness = hex::decode(witness.as_string_trim0x().as_str()).ok();
+
+    deposit_from_parts(
+        sudt_code_hash,
+        type_script.code_hash.as_bytes().as_ref(),
+        &args,
+        &amount_bytes,
+        witness.as_deref(),
+    )
+}
+
+/// `parse_deposit` on a serialized tx, reading only the fields it needs.
+pub fn parse_raw_deposit(view: &molecule::TxView) -> Result<Deposit, MessageError> {
+    parse_raw_deposit_of(view, &params::default_sudt_code_hash())
+}
+
+/// `parse_deposit_of` on a serialized tx.
+pub fn parse_raw_deposit_of(
+    view: &molecule::TxView,
+    sudt_code_hash: &Hash,
+) -> Result<Deposit, MessageError> {
+    let (code_hash, args) = view
+        .output_type(0)
+        .map_err(|_| RejectReason::InvalidTx.at(OUTPUT_FIELD))?
+        .ok_or_else(|| RejectReason::InvalidTx.at(TYPE_SCRIPT_FIELD))?;
+    let amount_bytes = view
+        .output_data(0)
+        .map_err(|_| RejectReason::InvalidAmount.at(AMOUNT_FIELD))?
+        .ok_or_else(|| RejectReason::InvalidTx.at(AMOUNT_FIELD))?;
+    let witness = view
+        .last_witness()
+        .map_err(|_| RejectReason::InvalidTx.at(WITNESS_FIELD))?
+        .ok_or_else(|| RejectReason::InvalidTx.at(WITNESS_FIELD))?;
+
+    deposit_from_parts(sudt_code_hash, code_hash, args, amount_bytes, Some(witness))
+}
+
+// A witness that can't be read sends the deposit to the recovery account.
+fn deposit_from_parts(
+    sudt_code_hash: &Hash,
+    code_hash: &[u8],
+    args: &[u8],
+    amount_bytes: &[u8],
+    witness: Option<&[u8]>,
+) -> Result<Deposit, MessageError> {
+    if code_hash != sudt_code_hash.as_bytes().as_ref() {
+        return Err(RejectReason::InvalidTx.at(CODE_HASH_FIELD));
+    }
+    let sudt_args = Hash::from_bytes(Bytes::from(args.to_vec()))
+        .map_err(|_| RejectReason::InvalidTx.at(SUDT_ARGS_FIELD))?;
+
+    let sudt_amount = Amount::from_sudt_data(amount_bytes)
+        .ok_or_else(|| RejectReason::InvalidAmount.at(AMOUNT_FIELD))?
+        .0;
+
+    let (receiver, tag, memo) = match witness.and_then(parse_tag) {
+        Some((tag, memo)) => (None, Some(tag), memo),
+        None => {
+            let (receiver, memo) = witness
+                .and_then(parse_receiver)
+                .unwrap_or((None, String::new()));
+            (receiver, None, memo)
+        }
+    };
+
+    Ok(Deposit {
+        sudt_args,
+        sudt_amount,
+        receiver,
+        tag,
+        memo,
+    })
+}
+
+/// Reads a deposit witness, either the receiver address followed by an
+/// optional utf8 memo, or the text of a bech32 receiver optionally followed by
+/// a space and the memo. A deposit whose memo can't be read is recovered like
+/// a bad receiver since the receiver may need it to credit the deposit.
+pub(crate) fn parse_receiver(witness: &[u8]) -> Option<(Option<Address>, String)> {
+    let bech32_prefix = address::MUTA_HRP.to_owned() + "1";
+    let (receiver, memo) = if witness.starts_with(bech32_prefix.as_bytes()) {
+        let text = String::from_utf8(witness.to_vec()).ok()?;
+        let mut parts = text.splitn(2, ' ');
+        let receiver = address::parse_muta_address(parts.next()?).ok();
+        (receiver, parts.next().unwrap_or("").to_owned())
+    } else {
+        let (receiver, memo) = crosschain_proof::split_receiver_witness(witness)?;
+        (
+            Address::from_bytes(Bytes::from(receiver.to_vec())).ok(),
+            String::from_utf8(memo.to_vec()).ok()?,
+        )
+    };
+    if memo.len() > MAX_MEMO_LEN {
+        return None;
+    }
+
+    Some((receiver, memo))
+}
+
+/// The deposit tag of `address`, the first bytes of its hash.
+pub fn deposit_tag(address: &Address) -> u64 {
+    let hash = Hash::digest(address.as_bytes());
+    hash.as_bytes()[..crosschain_proof::TAG_LEN]
+        .iter()
+        .fold(0, |tag, byte| (tag << 8) | u64::from(*byte))
+}
+
+/// The start of a deposit witness naming `tag`, the memo follows it.
+pub fn tag_witness(tag: u64) -> Vec<u8> {
+    crosschain_proof::tag_witness(tag, "")
+}
+
+/// Reads a deposit witness naming a deposit tag, `TAG_PREFIX` followed by
+/// the big endian tag and an optional utf8 memo.
+pub(crate) fn parse_tag(witness: &[u8]) -> Option<(u64, String)> {
+    let (tag, memo) = crosschain_proof::parse_tag_witness(witness)?;
+    let memo = String::from_utf8(memo.to_vec()).ok()?;
+    if memo.len() > MAX_MEMO_LEN {
+        return None;
+    }
+
+    Some((tag, memo))
+}
+
+/// Share of a minted deposit paid to the relayer that proved it, at the rate
+/// of the schedule tier the deposit falls in.
+pub fn relay_fee(schedule: &FeeSchedule, amount: u128) -> u128 {
+    bps_of(amount, fee_bps(schedule, amount))
+}
+
+/// Rate of the last tier whose threshold `amount` reaches.
+pub fn fee_bps(schedule: &FeeSchedule, amount: u128) -> u16 {
+    schedule
+        .tiers
+        .iter()
+        .take_while(|tier| tier.threshold <= amount)
+        .last()
+        .map_or(0, |tier| tier.bps)
+}
+
+/// Whether every amount falls in exactly one tier at a rate of at most the
+/// whole amount.
+pub fn valid_schedule(schedule: &FeeSchedule) -> bool {
+    let tiers = &schedule.tiers;
+    tiers.len() <= MAX_FEE_TIERS
+        && tiers.first().map_or(false, |tier| tier.threshold == 0)
+        && tiers.windows(2).all(|w| w[0].threshold < w[1].threshold)
+        && tiers.iter().all(|tier| tier.bps <= FEE_BPS)
+}
+
+// `bps` basis points of `amount`, rounded down. The quotient and remainder
+// are scaled apart so the product can't overflow.
+fn bps_of(amount: u128, bps: u16) -> u128 {
+    let (bps, whole) = (u128::from(bps), u128::from(FEE_BPS));
+    amount / whole * bps + amount % whole * bps / whole
+}
+
+/// What the bridge config says about a deposit being processed.
+pub struct DepositConfig<'a> {
+    pub mirror_asset:     &'a MirrorAsset,
+    pub fee_schedule:     &'a FeeSchedule,
+    // credited instead of a receiver that can't be read
+    pub recovery_account: &'a Address,
+}
+
+/// How a deposit is credited.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepositOutcome {
+    pub receiver:  Address,
+    pub memo:      String,
+    // the receiver was malformed, the deposit goes to the recovery account
+    pub recovered: bool,
+    // mirror units the deposit is worth, relay fee included
+    pub minted:    u128,
+    // source units below one mirror unit, left locked on the source chain
+    pub dust:      u128,
+    pub relay_fee: u128,
+    // what the receiver gets, `minted` less the relay fee
+    pub credited:  u128,
+}
+
+/// Splits a parsed ckb deposit into the mints it makes.
+pub fn process(deposit: Deposit, config: &DepositConfig) -> DepositOutcome {
+    match deposit.receiver {
+        Some(receiver) => outcome(
+            config.mirror_asset,
+            config.fee_schedule,
+            receiver,
+            deposit.memo,
+            deposit.sudt_amount,
+            false,
+        ),
+        None => outcome(
+            config.mirror_asset,
+            config.fee_schedule,
+            config.recovery_account.clone(),
+            String::new(),
+            deposit.sudt_amount,
+            true,
+        ),
+    }
+}
+
+/// Splits `source_amount` raw units on the source chain into the mints made
+/// to `receiver` and the relayer.
+pub fn outcome(
+    mirror_asset: &MirrorAsset,
+    fee_schedule: &FeeSchedule,
+    receiver: Address,
+    memo: String,
+    source_amount: u128,
+    recovered: bool,
+) -> DepositOutcome {
+    let (minted, dust) = mirror_asset.to_mirror_amount(source_amount);
+    let relay_fee = relay_fee(fee_schedule, minted);
+
+    DepositOutcome {
+        receiver,
+        memo,
+        recovered,
+        minted,
+        dust,
+        relay_fee,
+        credited: minted - relay_fee,
+    }
+}
+
+/// Whether the shares of a split relay fee add up to the whole fee.
+pub fn valid_beneficiary(beneficiary: &FeeBeneficiary) -> bool {
+    match beneficiary {
+        FeeBeneficiary::Split {
+            caller_bps,
+            treasury_bps,
+            ..
+        } => u32::from(*caller_bps) + u32::from(*treasury_bps) == u32::from(FEE_BPS),
+        _ => true,
+    }
+}
+
+/// Splits the relay fee of a deposit between the relayer that submitted it
+/// and the treasury, in the order they are minted. Shares sum to `fee`.
+pub fn fee_shares(
+    beneficiary: &FeeBeneficiary,
+    relayer: &Address,
+    fee: u128,
+) -> Vec<(Address, u128)> {
+    match beneficiary {
+        FeeBeneficiary::TxCaller => vec![(relayer.clone(), fee)],
+        FeeBeneficiary::FixedAddress(treasury) => vec![(treasury.clone(), fee)],
+        FeeBeneficiary::Split {
+            treasury,
+            treasury_bps,
+            ..
+        } => {
+            let to_treasury = bps_of(fee, *treasury_bps);
+            vec![
+                (relayer.clone(), fee - to_treasury),
+                (treasury.clone(), to_treasury),
+            ]
+        }
+    }
+}
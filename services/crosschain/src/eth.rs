@@ -0,0 +1,229 @@
+//! Ethereum data structures the bridge reads: block headers, receipts with
+//! their Merkle-Patricia proofs and the bridge contract `Lock` event.
+
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use tiny_keccak::{Hasher, Keccak};
+
+use protocol::types::{Address, Hash};
+
+use crate::types::EthHeader;
+
+pub const ETH_ADDRESS_LEN: usize = 20;
+const WORD_LEN: usize = 32;
+
+/// `Lock(address indexed token, address indexed sender, address receiver,
+/// uint256 amount)`, emitted by the bridge contract, `receiver` being a muta
+/// address.
+pub const LOCK_EVENT_SIGNATURE: &str = "Lock(address,address,address,uint256)";
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    let mut output = [0u8; 32];
+    keccak.update(data);
+    keccak.finalize(&mut output);
+    output
+}
+
+pub fn lock_event_topic() -> [u8; 32] {
+    keccak256(LOCK_EVENT_SIGNATURE.as_bytes())
+}
+
+/// Key under which an erc20 token is stored, its address left padded to 32
+/// bytes like an abi encoded address.
+pub fn token_key(token: &[u8]) -> Hash {
+    let mut word = [0u8; WORD_LEN];
+    word[WORD_LEN - token.len()..].copy_from_slice(token);
+    Hash::from_bytes(Bytes::from(word.to_vec())).expect("32 bytes hash")
+}
+
+/// Decodes the fields the bridge needs out of an rlp encoded block header,
+/// which works across forks since they only append fields.
+pub fn decode_header(raw: &[u8]) -> Result<EthHeader, rlp::DecoderError> {
+    let rlp = rlp::Rlp::new(raw);
+    if rlp.item_count()? < 15 {
+        return Err(rlp::DecoderError::RlpIncorrectListLen);
+    }
+
+    Ok(EthHeader {
+        hash:          hash(&keccak256(raw))?,
+        parent_hash:   hash(rlp.at(0)?.data()?)?,
+        receipts_root: hash(rlp.at(5)?.data()?)?,
+        difficulty:    big_endian_u128(rlp.at(7)?.data()?)?,
+        number:        rlp.at(8)?.as_val()?,
+    })
+}
+
+/// A `Lock` event out of a receipt.
+#[derive(Clone, Debug)]
+pub struct Lock {
+    pub token:    Hash,
+    pub receiver: Address,
+    pub amount:   u128,
+}
+
+/// Reads the `Lock` events emitted by `bridge_contract` from an encoded
+/// receipt, typed (EIP-2718) or legacy. Events whose receiver or amount can't
+/// be represented on muta are skipped.
+pub fn lock_events(receipt: &[u8], bridge_contract: &[u8]) -> Result<Vec<Lock>, rlp::DecoderError> {
+    let body = match receipt.first() {
+        Some(tx_type) if *tx_type < 0x80 => &receipt[1..],
+        _ => receipt,
+    };
+    let rlp = rlp::Rlp::new(body);
+    let topic = lock_event_topic();
+
+    let mut locks = vec![];
+    for log in rlp.at(3)?.iter() {
+        if log.at(0)?.data()? != bridge_contract {
+            continue;
+        }
+        let topics = log.at(1)?;
+        if topics.item_count()? != 3 || topics.at(0)?.data()? != topic {
+            continue;
+        }
+        let data = log.at(2)?.data()?;
+        if data.len() != WORD_LEN * 2 {
+            continue;
+        }
+
+        let (receiver_word, amount_word) = data.split_at(WORD_LEN);
+        if receiver_word[..WORD_LEN - ETH_ADDRESS_LEN]
+            .iter()
+            .any(|b| *b != 0)
+        {
+            continue;
+        }
+        let amount = match big_endian_u128(amount_word) {
+            Ok(amount) => amount,
+            Err(_) => continue,
+        };
+        let receiver = match Address::from_bytes(Bytes::from(
+            receiver_word[WORD_LEN - ETH_ADDRESS_LEN..].to_vec(),
+        )) {
+            Ok(receiver) => receiver,
+            Err(_) => continue,
+        };
+
+        locks.push(Lock {
+            token: hash(topics.at(1)?.data()?)?,
+            receiver,
+            amount,
+        });
+    }
+
+    Ok(locks)
+}
+
+/// Looks `key` up in the Merkle-Patricia trie with the given root, using the
+/// proof nodes as the only available part of the trie. `None` when the proof
+/// shows the key is absent or doesn't connect to the root.
+pub fn verify_proof(
+    root: &Hash,
+    key: &[u8],
+    nodes: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, rlp::DecoderError> {
+    let nodes: BTreeMap<[u8; 32], &[u8]> = nodes
+        .iter()
+        .map(|node| (keccak256(node), node.as_slice()))
+        .collect();
+    let path: Vec<u8> = key.iter().flat_map(|b| vec![b >> 4, b & 0x0f]).collect();
+
+    let mut root_hash = [0u8; 32];
+    root_hash.copy_from_slice(root.as_bytes().as_ref());
+    let mut node = match nodes.get(&root_hash) {
+        Some(node) => rlp::Rlp::new(node),
+        None => return Ok(None),
+    };
+    let mut pos = 0;
+
+    loop {
+        let child = match node.item_count()? {
+            17 => {
+                if pos == path.len() {
+                    let value = node.at(16)?.data()?;
+                    return Ok(if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.to_vec())
+                    });
+                }
+                pos += 1;
+                node.at(path[pos - 1] as usize)?
+            }
+            2 => {
+                let (partial, is_leaf) = decode_hex_prefix(node.at(0)?.data()?)?;
+                let rest = &path[pos..];
+                if is_leaf {
+                    return Ok(if rest == partial.as_slice() {
+                        Some(node.at(1)?.data()?.to_vec())
+                    } else {
+                        None
+                    });
+                }
+                if !rest.starts_with(&partial) {
+                    return Ok(None);
+                }
+                pos += partial.len();
+                node.at(1)?
+            }
+            _ => return Err(rlp::DecoderError::RlpIncorrectListLen),
+        };
+
+        // Children shorter than a hash are embedded in their parent.
+        node = if child.is_list() {
+            child
+        } else {
+            let child_hash = child.data()?;
+            if child_hash.len() != 32 {
+                return Ok(None);
+            }
+            let mut key = [0u8; 32];
+            key.copy_from_slice(child_hash);
+            match nodes.get(&key) {
+                Some(node) => rlp::Rlp::new(node),
+                None => return Ok(None),
+            }
+        };
+    }
+}
+
+/// Decodes a hex prefix encoded path into nibbles and the leaf flag.
+fn decode_hex_prefix(data: &[u8]) -> Result<(Vec<u8>, bool), rlp::DecoderError> {
+    let first = *data.first().ok_or(rlp::DecoderError::RlpIsTooShort)?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(rlp::DecoderError::Custom("invalid hex prefix"));
+    }
+
+    let mut nibbles = vec![];
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    for b in data[1..].iter() {
+        nibbles.push(b >> 4);
+        nibbles.push(b & 0x0f);
+    }
+
+    Ok((nibbles, flag & 2 == 2))
+}
+
+fn big_endian_u128(data: &[u8]) -> Result<u128, rlp::DecoderError> {
+    let start = data
+        .iter()
+        .position(|b| *b != 0)
+        .unwrap_or_else(|| data.len());
+    if data.len() - start > 16 {
+        return Err(rlp::DecoderError::RlpIsTooBig);
+    }
+
+    Ok(data[start..]
+        .iter()
+        .fold(0u128, |acc, b| (acc << 8) | u128::from(*b)))
+}
+
+fn hash(data: &[u8]) -> Result<Hash, rlp::DecoderError> {
+    Hash::from_bytes(Bytes::from(data.to_vec()))
+        .map_err(|_| rlp::DecoderError::Custom("invalid hash length"))
+}
@@ -1,206 +1,5055 @@
+pub mod address;
+pub mod call;
+pub mod deposit;
+pub mod eth;
+pub mod light_client;
+mod math;
+pub mod mmr;
+pub mod molecule;
+pub mod muta;
+pub mod params;
+pub mod proof;
+pub mod schema;
+#[cfg(test)]
+mod tests;
 pub mod types;
+pub mod view;
 
 use std::collections::BTreeMap;
 
-use byteorder::{ByteOrder, LittleEndian};
 use bytes::Bytes;
 use derive_more::{Display, From};
 
-use binding_macro::{cycles, genesis, service, write};
+use binding_macro::{cycles, genesis, hook_after, hook_before, service, write};
+use common::amount::Amount;
+use common::cache::CachedMap;
+use common::migration::{self, Migration, Step, MIGRATION_CURSOR_KEY, SCHEMA_VERSION_KEY};
+use common::read_value;
+use common::versioned::Versioned;
+use common::view::{flatten, MigrationStatusView, ServiceInfoView, ValueView};
 use protocol::traits::{ExecutorParams, ServiceSDK, StoreMap, StoreUint64};
-use protocol::types::{Address, Hash, ServiceContext};
+use protocol::types::{Address, Hash, Hex, ServiceContext};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
+use crate::address::{AddressError, CkbAddress};
+use crate::deposit::{DepositConfig, DepositOutcome};
+use crate::light_client::{CkbLightClient, EthLightClient, LightClient, MutaLightClient};
+use crate::math::checked_add_u128;
 use crate::types::{
-    BurnCallAssetPayload, BurnPayload, BurnTokenEvent, CkbHeader, CkbHeaderInner, CkbTx,
-    MessagePayload, MintTokenEvent, MintTokenPayload, UpdateHeadersPayload,
+    AckBurnPayload, BatchMessagePayload, BlockEventHashes, BlockMintUsage, BridgeLimits,
+    BridgePausedEvent, BridgeResumedEvent, BridgeStats, BridgedSupply, BurnAckedEvent, BurnBatch,
+    BurnBatchEvent, BurnCallAssetPayload, BurnCancelledEvent, BurnPayload, BurnQuota,
+    BurnQuotaUsage, BurnQuote, BurnRecord, BurnStatus, BurnToEthEvent, BurnToEthPayload,
+    BurnToMutaEvent, BurnToMutaPayload, BurnTokenEvent, CallTarget, CallTargetEvent,
+    CallTargetPayload, CancelBurnPayload, ChainBinding, CkbHeader, CkbHeaderInner, CkbMessage,
+    CkbTx, CreateMirrorAssetPayload, CrossCall, CrossCallEvent, Deposit, DepositQuote,
+    DepositReassignedEvent, DepositRecoveredEvent, DepositTag, DepositTagEvent, EpochSummary,
+    EthConfig, EthHeader, EventDigest, EventDigests, FeeBeneficiary, FeeSchedule, FeeWaiverEvent,
+    FeeWaiverPayload, FieldError, ForceProcessMessagePayload, GetBurnBatchPayload,
+    GetBurnLimitResponse, GetBurnQuotaResponse, GetBurnRecordPayload, GetDepositByCkbTxPayload,
+    GetDepositTagPayload, GetEpochSummaryPayload, GetHeaderPayload, GetHeaderResponse,
+    GetMirrorAssetByIdPayload, GetMirrorAssetBySudtPayload, GetMirrorAssetIdResponse,
+    GetMissingHeaderRangesPayload, GetMmrResponse, GetMutaHeaderPayload,
+    GetMutaValidatorSetPayload, GetOutboundBlockPayload, GetOutboundMessagePayload,
+    GetRecoveredDepositPayload, GetSchemaPayload, GetViewPayload, HasRolePayload, HasRoleResponse,
+    HeaderBounds, HeaderConflict, HeaderConflictEvent, HeaderConflictResolvedEvent, HeightRange,
+    InitGenesisPayload, MessageError, MessageForcedEvent, MessagePayload, MessageRejectedEvent,
+    MessageTtl, MessageTx, MessagesPendingEvent, MigratePayload, MigrationEvent, MigrationStatus,
+    MintCallbackEvent, MintCallbackFailedEvent, MintCallbackPayload, MintTokenEvent,
+    MintTokenPayload, MirrorAsset, MirrorAssetIssuer, MissingHeaderRanges, MmrMessagePayload,
+    MmrProof, MmrState, MmrUpdatedEvent, MutaConfig, MutaHeader, MutaValidatorSet,
+    MutaValidatorSetEvent, MutaValidatorUpdate, OnCrossCallPayload, OnCrossMintPayload,
+    OutboundBlock, OutboundMessage, OutboundMessageEvent, OutboundSender, OutboundSenderEvent,
+    OutboundSenderPayload, Params, ParamsEvent, PayoutAddress, PayoutAddressEvent, PendingMessage,
+    PendingMessages, ProcessPendingPayload, ProcessedDeposit, QuotaExemptionEvent,
+    QuotaExemptionPayload, QuoteBurnPayload, QuoteDepositPayload, ReadView, ReassignDepositPayload,
+    RecoveredDeposit, RegisterEthAssetPayload, RegisterMintCallbackPayload,
+    RegisterMirrorAssetEvent, RegisterMirrorAssetPayload, RegisterMutaAssetPayload, RejectReason,
+    RelayerEvent, RelayerPayload, RelayerPayout, ResolveHeaderConflictPayload, ResumeBridgePayload,
+    SendMessageToCkbPayload, ServiceInfo, SetBurnLimitEvent, SetBurnLimitPayload,
+    SetBurnQuotaEvent, SetBurnQuotaPayload, SetParamsPayload, SetPayoutAddressPayload,
+    SetSupplyCapEvent, SetSupplyCapPayload, SetWithdrawalFeeEvent, SetWithdrawalFeePayload,
+    SubmitEthReceiptsPayload, SubmitMutaReceiptsPayload, SudtDustEvent, SupplyCap, SupportedLocks,
+    SupportedLocksEvent, TransferCallAssetPayload, UpdateEthHeadersPayload, UpdateHeadersPayload,
+    UpdateMmrPayload, UpdateMutaHeadersPayload, WithdrawalFeeConfig, WithdrawalFeeEstimate,
+    WithdrawalFeeEvent,
+};
+use crate::view::{
+    BridgeLimitsView, BridgeStatsView, BridgedSupplyView, BurnBatchView, BurnLimitView,
+    BurnQuotaView, BurnQuoteView, BurnRecordView, CallTargetView, DepositQuoteView, DepositTagView,
+    EpochSummaryView, EthConfigView, EventDigestsView, FeeBeneficiaryView, FeeScheduleView,
+    HeaderConflictView, HeaderView, MessageTtlView, MirrorAssetIdView, MirrorAssetIssuerView,
+    MirrorAssetView, MissingHeaderRangesView, MmrView, MutaConfigView, MutaHeaderView,
+    MutaValidatorSetView, OutboundBlockView, OutboundMessageView, OutboundSenderView, ParamsView,
+    PayoutAddressView, ProcessedDepositView, RawHeaderView, RecoveredDepositView,
+    SupportedLocksView, WithdrawalFeeConfigView, WithdrawalFeeEstimateView,
+};
+
+pub use crate::deposit::{parse_deposit, parse_raw_deposit, relay_fee};
+pub use crate::params::{
+    BURN_EPOCH_LENGTH, MAX_OUTBOUND_PAYLOAD_LEN, MAX_PENDING_MESSAGES, MAX_STAGED_HEADERS,
+    MIRROR_ASSET_ISSUER, PAYOUT_COOLDOWN, RECOVERY_ACCOUNT, STAGED_HEADERS_PER_BLOCK,
+    SUDT_CODE_HASH,
 };
 
-static ADMISSION_TOKEN: Bytes = Bytes::from_static(b"crosschain");
-static SUDT_CODE_HASH: &str = "0x57dd0067814dab356e05c6def0d094bb79776711e68ffdfad2df6a7f877f7db6";
+const ADMIN_KEY: &str = "admin";
+const BRIDGE_STATS_KEY: &str = "bridge_stats";
+// names this service to the asset service, which only lets its minters mint
+// and burn
+static SERVICE_NAME: Bytes = Bytes::from_static(b"crosschain");
+static MIRROR_ASSET_ID_PREFIX: &str = "ckb_mirror_asset";
+static ETH_MIRROR_ASSET_ID_PREFIX: &str = "eth_mirror_asset";
+const ETH_CONFIG_KEY: &str = "eth_config";
+static MUTA_MIRROR_ASSET_ID_PREFIX: &str = "muta_mirror_asset";
+const MUTA_CONFIG_KEY: &str = "muta_config";
+const HEADER_RELAYER_KEY: &str = "header_relayer";
+const SUPPORTED_LOCKS_KEY: &str = "supported_locks";
+const HEADER_BOUNDS_KEY: &str = "header_bounds";
+const CHAIN_BINDING_KEY: &str = "chain_binding";
+const RBAC_KEY: &str = "rbac";
+const BLOCK_MINT_USAGE_KEY: &str = "block_mint_usage";
+const BLOCK_EVENTS_KEY: &str = "block_events";
+const MMR_KEY: &str = "mmr";
+// last header appended to the range, which the next one must follow
+const MMR_TIP_KEY: &str = "mmr_tip";
+// charged by reads on top of their fixed cost for each entry they return
+const READ_CYCLES_PER_ITEM: u64 = 10_00;
+// bound the work of one `get_missing_header_ranges` call
+const MAX_HEADER_RANGES: u32 = 100;
+const MAX_HEADER_SCAN: u64 = 10_000;
+// heights one `get_event_digest` call looks at
+const MAX_DIGEST_SCAN: u64 = 1_000;
+const SERVICE_VERSION: &str = env!("CARGO_PKG_VERSION");
+const PAYLOAD_VERSIONS: &[u32] = &[1, 2];
+/// Schema version of the state this release writes.
+pub const SCHEMA_VERSION: u64 = 1;
+// entries a batched migration rewrites before each block
+const MIGRATION_BATCH: u64 = 100;
+/// Most headers a branch resolving a header conflict holds.
+pub const MAX_FORK_BRANCH_LEN: usize = 64;
+/// Longest memo, in bytes, a deposit witness may carry after the receiver.
+pub const MAX_MEMO_LEN: usize = 64;
 
 pub struct CrosschainService<SDK> {
-    sdk:             SDK,
-    headers:         Box<dyn StoreMap<u64, CkbHeaderInner>>,
-    effected_proofs: Box<dyn StoreMap<Hash, bool>>,
-    nonce:           Box<dyn StoreUint64>,
+    sdk:                 SDK,
+    headers:             CachedMap<u64, CkbHeaderInner>,
+    // headers as submitted, kept to audit parsing disputes
+    raw_headers:         Box<dyn StoreMap<u64, CkbHeader>>,
+    // open conflicts with the stored headers, by height
+    header_conflicts:    Box<dyn StoreMap<u64, HeaderConflict>>,
+    effected_proofs:     CachedMap<Hash, bool>,
+    nonce:               Box<dyn StoreUint64>,
+    mirror_assets:       CachedMap<Hash, MirrorAsset>,
+    sudt_ids:            Box<dyn StoreMap<Hash, Hash>>,
+    burn_records:        Box<dyn StoreMap<u64, BurnRecord>>,
+    // ckb txs acked as paying a burn out, to the burn nonce, apart from the
+    // proofs of inbound messages
+    acked_burn_txs:      Box<dyn StoreMap<Hash, u64>>,
+    relayers:            CachedMap<Address, bool>,
+    burn_quotas:         Box<dyn StoreMap<Hash, BurnQuota>>,
+    quota_exempt:        Box<dyn StoreMap<Address, bool>>,
+    // native asset units burn_sudt pays per mirror asset
+    withdrawal_fees:     Box<dyn StoreMap<Hash, Amount>>,
+    fee_waived:          Box<dyn StoreMap<Address, bool>>,
+    light_client:        CkbLightClient,
+    eth_headers:         Box<dyn StoreMap<u64, EthHeader>>,
+    // erc20 mirror assets, keyed by `eth::token_key` which also fills their
+    // `sudt_args`
+    eth_assets:          Box<dyn StoreMap<Hash, MirrorAsset>>,
+    eth_asset_ids:       Box<dyn StoreMap<Hash, Hash>>,
+    muta_headers:        Box<dyn StoreMap<u64, MutaHeader>>,
+    // mirrors of counterpart muta assets, keyed by their asset id there
+    muta_assets:         Box<dyn StoreMap<Hash, MirrorAsset>>,
+    muta_asset_ids:      Box<dyn StoreMap<Hash, Hash>>,
+    // validator sets of the counterpart chain by epoch, counted from the
+    // seeded set
+    muta_validator_sets: Box<dyn StoreMap<u64, MutaValidatorSet>>,
+    muta_epoch:          Box<dyn StoreUint64>,
+    // headers waiting for the after block hook, by arrival order
+    staged_headers:      Box<dyn StoreMap<u64, CkbHeader>>,
+    staged_len:          Box<dyn StoreUint64>,
+    // first staged header the after block hook hasn't taken yet
+    staged_start:        Box<dyn StoreUint64>,
+    pending_messages:    Box<dyn StoreMap<u64, PendingMessages>>,
+    // keyed by ckb tx hash
+    recovered_deposits:  Box<dyn StoreMap<Hash, RecoveredDeposit>>,
+    // receiver address to the name of the service called after minting to it
+    mint_callbacks:      CachedMap<Address, String>,
+    // methods calls relayed from ckb may call, by service
+    call_targets:        CachedMap<String, CallTarget>,
+    // roots of the header range, by leaf count
+    mmr_roots:           Box<dyn StoreMap<u64, Hash>>,
+    // processed ckb deposits, keyed by ckb tx hash
+    deposits:            Box<dyn StoreMap<Hash, ProcessedDeposit>>,
+    // collateral caps of mirror assets, by asset id
+    supply_caps:         CachedMap<Hash, SupplyCap>,
+    // digests of the bridge events of each block emitting any, by height
+    event_digests:       Box<dyn StoreMap<u64, EventDigest>>,
+    // burns to ckb summed by burn epoch, and the epoch of each burn nonce
+    burn_epochs:         Box<dyn StoreMap<u64, EpochSummary>>,
+    burn_nonce_epochs:   Box<dyn StoreMap<u64, u64>>,
+    // burn batches by id from 1, the last may still be open, and the batch of
+    // each batched burn nonce
+    burn_batches:        Box<dyn StoreMap<u64, BurnBatch>>,
+    burn_batch_len:      Box<dyn StoreUint64>,
+    burn_nonce_batches:  Box<dyn StoreMap<u64, u64>>,
+    // muta addresses by the deposit tag they registered
+    deposit_tags:        Box<dyn StoreMap<u64, Address>>,
+    // services allowed to send messages to ckb
+    outbound_senders:    CachedMap<String, bool>,
+    // messages to ckb by nonce from 1, and their roots by block height
+    outbound_messages:   Box<dyn StoreMap<u64, OutboundMessage>>,
+    outbound_len:        Box<dyn StoreUint64>,
+    outbound_blocks:     Box<dyn StoreMap<u64, OutboundBlock>>,
+    // where relayers that set one are paid their relay fees
+    payout_addresses:    Box<dyn StoreMap<Address, RelayerPayout>>,
+}
+
+// What storing a header did.
+enum StoredHeader {
+    Stored(u64),
+    Conflict(HeaderConflict),
+}
+
+// Bridge events a block digest counts.
+enum BridgeEvent {
+    Mint,
+    Burn,
 }
 
 #[service]
 impl<SDK: ServiceSDK> CrosschainService<SDK> {
     pub fn new(mut sdk: SDK) -> ProtocolResult<Self> {
-        let headers: Box<dyn StoreMap<u64, CkbHeaderInner>> =
-            sdk.alloc_or_recover_map("headers")?;
-        let effected_proofs: Box<dyn StoreMap<Hash, bool>> =
-            sdk.alloc_or_recover_map("effected_proofs")?;
+        let headers: CachedMap<u64, CkbHeaderInner> =
+            CachedMap::new(sdk.alloc_or_recover_map("headers")?);
+        let raw_headers: Box<dyn StoreMap<u64, CkbHeader>> =
+            sdk.alloc_or_recover_map("raw_headers")?;
+        let header_conflicts: Box<dyn StoreMap<u64, HeaderConflict>> =
+            sdk.alloc_or_recover_map("header_conflicts")?;
+        let effected_proofs: CachedMap<Hash, bool> =
+            CachedMap::new(sdk.alloc_or_recover_map("effected_proofs")?);
         let nonce: Box<dyn StoreUint64> = sdk.alloc_or_recover_uint64("nonce")?;
+        let mirror_assets: CachedMap<Hash, MirrorAsset> =
+            CachedMap::new(sdk.alloc_or_recover_map("mirror_assets")?);
+        let sudt_ids: Box<dyn StoreMap<Hash, Hash>> = sdk.alloc_or_recover_map("sudt_ids")?;
+        let burn_records: Box<dyn StoreMap<u64, BurnRecord>> =
+            sdk.alloc_or_recover_map("burn_records")?;
+        let acked_burn_txs: Box<dyn StoreMap<Hash, u64>> =
+            sdk.alloc_or_recover_map("acked_burn_txs")?;
+        let relayers: CachedMap<Address, bool> =
+            CachedMap::new(sdk.alloc_or_recover_map("relayers")?);
+        let burn_quotas: Box<dyn StoreMap<Hash, BurnQuota>> =
+            sdk.alloc_or_recover_map("burn_quotas")?;
+        let quota_exempt: Box<dyn StoreMap<Address, bool>> =
+            sdk.alloc_or_recover_map("quota_exempt")?;
+        let withdrawal_fees: Box<dyn StoreMap<Hash, Amount>> =
+            sdk.alloc_or_recover_map("withdrawal_fees")?;
+        let fee_waived: Box<dyn StoreMap<Address, bool>> =
+            sdk.alloc_or_recover_map("fee_waived")?;
+        let eth_headers: Box<dyn StoreMap<u64, EthHeader>> =
+            sdk.alloc_or_recover_map("eth_headers")?;
+        let eth_assets: Box<dyn StoreMap<Hash, MirrorAsset>> =
+            sdk.alloc_or_recover_map("eth_assets")?;
+        let eth_asset_ids: Box<dyn StoreMap<Hash, Hash>> =
+            sdk.alloc_or_recover_map("eth_asset_ids")?;
+        let muta_headers: Box<dyn StoreMap<u64, MutaHeader>> =
+            sdk.alloc_or_recover_map("muta_headers")?;
+        let muta_assets: Box<dyn StoreMap<Hash, MirrorAsset>> =
+            sdk.alloc_or_recover_map("muta_assets")?;
+        let muta_asset_ids: Box<dyn StoreMap<Hash, Hash>> =
+            sdk.alloc_or_recover_map("muta_asset_ids")?;
+        let muta_validator_sets: Box<dyn StoreMap<u64, MutaValidatorSet>> =
+            sdk.alloc_or_recover_map("muta_validator_sets")?;
+        let muta_epoch: Box<dyn StoreUint64> = sdk.alloc_or_recover_uint64("muta_epoch")?;
+        let staged_headers: Box<dyn StoreMap<u64, CkbHeader>> =
+            sdk.alloc_or_recover_map("staged_headers")?;
+        let staged_len: Box<dyn StoreUint64> = sdk.alloc_or_recover_uint64("staged_len")?;
+        let staged_start: Box<dyn StoreUint64> = sdk.alloc_or_recover_uint64("staged_start")?;
+        let pending_messages: Box<dyn StoreMap<u64, PendingMessages>> =
+            sdk.alloc_or_recover_map("pending_messages")?;
+        let recovered_deposits: Box<dyn StoreMap<Hash, RecoveredDeposit>> =
+            sdk.alloc_or_recover_map("recovered_deposits")?;
+        let mint_callbacks: CachedMap<Address, String> =
+            CachedMap::new(sdk.alloc_or_recover_map("mint_callbacks")?);
+        let call_targets: CachedMap<String, CallTarget> =
+            CachedMap::new(sdk.alloc_or_recover_map("call_targets")?);
+        let mmr_roots: Box<dyn StoreMap<u64, Hash>> = sdk.alloc_or_recover_map("mmr_roots")?;
+        let deposits: Box<dyn StoreMap<Hash, ProcessedDeposit>> =
+            sdk.alloc_or_recover_map("deposits")?;
+        let supply_caps: CachedMap<Hash, SupplyCap> =
+            CachedMap::new(sdk.alloc_or_recover_map("supply_caps")?);
+        let event_digests: Box<dyn StoreMap<u64, EventDigest>> =
+            sdk.alloc_or_recover_map("event_digests")?;
+        let burn_epochs: Box<dyn StoreMap<u64, EpochSummary>> =
+            sdk.alloc_or_recover_map("burn_epochs")?;
+        let burn_nonce_epochs: Box<dyn StoreMap<u64, u64>> =
+            sdk.alloc_or_recover_map("burn_nonce_epochs")?;
+        let burn_batches: Box<dyn StoreMap<u64, BurnBatch>> =
+            sdk.alloc_or_recover_map("burn_batches")?;
+        let burn_batch_len: Box<dyn StoreUint64> = sdk.alloc_or_recover_uint64("burn_batch_len")?;
+        let burn_nonce_batches: Box<dyn StoreMap<u64, u64>> =
+            sdk.alloc_or_recover_map("burn_nonce_batches")?;
+        let deposit_tags: Box<dyn StoreMap<u64, Address>> =
+            sdk.alloc_or_recover_map("deposit_tags")?;
+        let outbound_senders: CachedMap<String, bool> =
+            CachedMap::new(sdk.alloc_or_recover_map("outbound_senders")?);
+        let outbound_messages: Box<dyn StoreMap<u64, OutboundMessage>> =
+            sdk.alloc_or_recover_map("outbound_messages")?;
+        let outbound_len: Box<dyn StoreUint64> = sdk.alloc_or_recover_uint64("outbound_len")?;
+        let outbound_blocks: Box<dyn StoreMap<u64, OutboundBlock>> =
+            sdk.alloc_or_recover_map("outbound_blocks")?;
+        let payout_addresses: Box<dyn StoreMap<Address, RelayerPayout>> =
+            sdk.alloc_or_recover_map("payout_addresses")?;
 
         Ok(Self {
             sdk,
             headers,
+            raw_headers,
+            header_conflicts,
             effected_proofs,
             nonce,
+            mirror_assets,
+            sudt_ids,
+            burn_records,
+            acked_burn_txs,
+            relayers,
+            burn_quotas,
+            quota_exempt,
+            withdrawal_fees,
+            fee_waived,
+            light_client: CkbLightClient,
+            eth_headers,
+            eth_assets,
+            eth_asset_ids,
+            muta_headers,
+            muta_assets,
+            muta_asset_ids,
+            muta_validator_sets,
+            muta_epoch,
+            staged_headers,
+            staged_len,
+            staged_start,
+            pending_messages,
+            recovered_deposits,
+            mint_callbacks,
+            call_targets,
+            mmr_roots,
+            deposits,
+            supply_caps,
+            event_digests,
+            burn_epochs,
+            burn_nonce_epochs,
+            burn_batches,
+            burn_batch_len,
+            burn_nonce_batches,
+            deposit_tags,
+            outbound_senders,
+            outbound_messages,
+            outbound_len,
+            outbound_blocks,
+            payout_addresses,
         })
     }
 
     #[genesis]
-    fn init_genesis(&mut self) -> ProtocolResult<()> {
+    fn init_genesis(&mut self, payload: InitGenesisPayload) -> ProtocolResult<()> {
+        self.sdk.set_value(ADMIN_KEY.to_owned(), payload.admin)?;
+        self.sdk
+            .set_value(SCHEMA_VERSION_KEY.to_owned(), SCHEMA_VERSION)?;
+        if let Some(config) = payload.muta_config {
+            self.sdk.set_value(MUTA_CONFIG_KEY.to_owned(), config)?;
+        }
+        if let Some(validator_set) = payload.muta_validator_set {
+            self.seed_muta_validator_set(validator_set)?;
+        }
+        if !payload.supported_locks.is_empty() {
+            self.sdk
+                .set_value(SUPPORTED_LOCKS_KEY.to_owned(), SupportedLocks {
+                    code_hashes: payload.supported_locks,
+                })?;
+        }
+        if let Some(binding) = payload.chain_binding {
+            self.sdk.set_value(CHAIN_BINDING_KEY.to_owned(), binding)?;
+        }
+        if payload.rbac {
+            self.sdk.set_value(RBAC_KEY.to_owned(), true)?;
+        }
+        let genesis_params = SetParamsPayload {
+            mirror_asset_issuer: payload.mirror_asset_issuer,
+            message_ttl: Some(payload.message_ttl).filter(|ttl| *ttl > 0),
+            bridge_limits: payload.bridge_limits,
+            fee_beneficiary: payload.fee_beneficiary,
+            fee_schedule: payload.fee_schedule,
+            burn_batch_window: Some(payload.burn_batch_window).filter(|window| *window > 0),
+            ..SetParamsPayload::default()
+        };
+        if let Some(name) = params::invalid(&genesis_params) {
+            return Err(ServiceError::InvalidParam {
+                name: name.to_owned(),
+            }
+            .into());
+        }
+        params::store(&mut self.sdk, genesis_params)?;
+        params::store_genesis(
+            &mut self.sdk,
+            payload.recovery_account,
+            payload.burn_epoch_length,
+        )?;
         self.nonce.set(0)
     }
 
+    #[cycles(100_00)]
+    #[read]
+    fn get_service_info(&self, ctx: ServiceContext) -> ProtocolResult<ServiceInfo> {
+        let mut features = vec![
+            "ckb_bridge".to_owned(),
+            "staged_headers".to_owned(),
+            "pending_messages".to_owned(),
+            "deposit_recovery".to_owned(),
+            "mint_callbacks".to_owned(),
+            "supply_caps".to_owned(),
+            "mmr_headers".to_owned(),
+            "message_batches".to_owned(),
+            "mirror_asset_issuer".to_owned(),
+            "header_gaps".to_owned(),
+            "message_ttl".to_owned(),
+            "bridge_limits".to_owned(),
+            "read_views".to_owned(),
+            "event_digests".to_owned(),
+            "fee_beneficiary".to_owned(),
+            "fee_tiers".to_owned(),
+            "burn_epochs".to_owned(),
+            "burn_batches".to_owned(),
+            "chain_binding".to_owned(),
+            "deposit_tags".to_owned(),
+            "migrations".to_owned(),
+            "cross_calls".to_owned(),
+            "outbound_messages".to_owned(),
+            "packed_message_proofs".to_owned(),
+            "payout_addresses".to_owned(),
+            "formatted_quotes".to_owned(),
+            "withdrawal_fees".to_owned(),
+            "params".to_owned(),
+            "header_conflicts".to_owned(),
+        ];
+        let eth_config: Option<EthConfig> = self.sdk.get_value(&ETH_CONFIG_KEY.to_owned())?;
+        if eth_config.is_some() {
+            features.push("eth_bridge".to_owned());
+        }
+        let muta_config: Option<MutaConfig> = self.sdk.get_value(&MUTA_CONFIG_KEY.to_owned())?;
+        if muta_config.is_some() {
+            features.push("muta_bridge".to_owned());
+        }
+        if self.rbac_enabled()? {
+            features.push("rbac_roles".to_owned());
+        }
+
+        Ok(ServiceInfo {
+            version: SERVICE_VERSION.to_owned(),
+            features,
+            payload_versions: PAYLOAD_VERSIONS.to_vec(),
+            chain: self.chain_binding()?,
+        })
+    }
+
+    /// JSON schema of a payload, event or response type, by type name.
+    #[cycles(100_00)]
+    #[read]
+    fn get_schema(&self, ctx: ServiceContext, payload: GetSchemaPayload) -> ProtocolResult<String> {
+        let schema = schema::schema(&payload.type_name).ok_or(ServiceError::UnknownSchema {
+            type_name: payload.type_name,
+        })?;
+        serde_json::to_string(&schema).map_err(|e| ServiceError::JsonParse(e).into())
+    }
+
+    /// The response of any read as its view, flattened to string fields by
+    /// path for graphql clients. Collections past the item bound are cut and
+    /// listed.
+    #[cycles(100_00)]
+    #[read]
+    fn get_view(&self, ctx: ServiceContext, payload: GetViewPayload) -> ProtocolResult<ReadView> {
+        let value = read_value!(self, ctx, payload.method.as_str(), &payload.payload, {
+            get_service_info => ServiceInfoView,
+            get_schema: GetSchemaPayload => ValueView,
+            get_bridge_stats => BridgeStatsView,
+            get_bridged_supply: GetMirrorAssetByIdPayload => BridgedSupplyView,
+            quote_deposit: QuoteDepositPayload => DepositQuoteView,
+            quote_burn: QuoteBurnPayload => BurnQuoteView,
+            get_header: GetHeaderPayload => HeaderView,
+            get_header_conflict: GetHeaderPayload => HeaderConflictView,
+            get_missing_header_ranges: GetMissingHeaderRangesPayload => MissingHeaderRangesView,
+            get_event_digest: HeightRange => EventDigestsView,
+            get_fee_beneficiary => FeeBeneficiaryView,
+            get_fee_schedule => FeeScheduleView,
+            get_epoch_summary: GetEpochSummaryPayload => EpochSummaryView,
+            get_burn_batch: GetBurnBatchPayload => BurnBatchView,
+            get_deposit_tag: GetDepositTagPayload => DepositTagView,
+            get_migration_status => MigrationStatusView,
+            get_header_raw: GetHeaderPayload => RawHeaderView,
+            get_mirror_asset_by_sudt: GetMirrorAssetBySudtPayload => MirrorAssetView,
+            get_mirror_asset_by_id: GetMirrorAssetByIdPayload => MirrorAssetView,
+            get_mirror_asset_id: GetMirrorAssetBySudtPayload => MirrorAssetIdView,
+            get_sudt_args: GetMirrorAssetByIdPayload => ValueView,
+            get_burn_limit: GetMirrorAssetByIdPayload => BurnLimitView,
+            get_burn_record: GetBurnRecordPayload => BurnRecordView,
+            get_burn_quota: GetMirrorAssetByIdPayload => BurnQuotaView,
+            get_withdrawal_fee_config => WithdrawalFeeConfigView,
+            estimate_withdrawal_fee: GetMirrorAssetByIdPayload => WithdrawalFeeEstimateView,
+            get_mint_callback: MintCallbackPayload => ValueView,
+            get_call_target: CallTargetPayload => CallTargetView,
+            get_outbound_sender: OutboundSenderPayload => OutboundSenderView,
+            get_outbound_message: GetOutboundMessagePayload => OutboundMessageView,
+            get_outbound_block: GetOutboundBlockPayload => OutboundBlockView,
+            get_payout_address: RelayerPayload => PayoutAddressView,
+            get_supported_locks => SupportedLocksView,
+            get_mirror_asset_issuer => MirrorAssetIssuerView,
+            get_message_ttl => MessageTtlView,
+            get_params => ParamsView,
+            get_bridge_limits => BridgeLimitsView,
+            get_mmr => MmrView,
+            get_recovered_deposit: GetRecoveredDepositPayload => RecoveredDepositView,
+            get_deposit_by_ckb_tx: GetDepositByCkbTxPayload => ProcessedDepositView,
+            get_eth_config => EthConfigView,
+            get_muta_config => MutaConfigView,
+            get_muta_validator_set: GetMutaValidatorSetPayload => MutaValidatorSetView,
+            get_muta_header: GetMutaHeaderPayload => MutaHeaderView,
+        });
+        Ok(flatten(
+            payload.method,
+            &value,
+            payload.max_items,
+            payload.max_depth,
+        ))
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_bridge_stats(&self, ctx: ServiceContext) -> ProtocolResult<BridgeStats> {
+        let stats = self.bridge_stats()?;
+        let items = stats.volumes.len() + stats.rejected_messages.len();
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * items as u64)?;
+        Ok(stats)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_bridged_supply(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetByIdPayload,
+    ) -> ProtocolResult<BridgedSupply> {
+        let (minted, burned) = self
+            .bridge_stats()?
+            .volumes
+            .into_iter()
+            .find(|v| v.asset_id == payload.asset_id)
+            .map_or((0, 0), |v| (v.minted, v.burned));
+        let supply_cap = self.supply_cap(&payload.asset_id)?;
+
+        Ok(BridgedSupply {
+            asset_id: payload.asset_id,
+            minted,
+            burned,
+            outstanding: minted.saturating_sub(burned),
+            cap: supply_cap.cap,
+            paused: supply_cap.paused,
+        })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn quote_deposit(
+        &self,
+        ctx: ServiceContext,
+        payload: QuoteDepositPayload,
+    ) -> ProtocolResult<DepositQuote> {
+        let mirror_asset = self.get_mirror_asset(&payload.sudt_args)?;
+        let (amount, dust) = mirror_asset.to_mirror_amount(payload.sudt_amount);
+        let schedule = self.fee_schedule()?;
+        let fee = relay_fee(&schedule, amount);
+        let supply_cap = self.supply_cap(&mirror_asset.asset_id)?;
+        let outstanding = self.bridge_stats()?.outstanding(&mirror_asset.asset_id);
+        let (formatted_amount, formatted_credited) = if payload.formatted {
+            (
+                mirror_asset.format_amount(amount),
+                mirror_asset.format_amount(amount - fee),
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(DepositQuote {
+            asset_id: mirror_asset.asset_id,
+            sudt_amount: payload.sudt_amount,
+            scale_factor: mirror_asset.scale_factor,
+            dust,
+            amount,
+            relay_fee: fee,
+            relay_fee_bps: deposit::fee_bps(&schedule, amount),
+            credited: amount - fee,
+            paused: supply_cap.paused || !within_cap(supply_cap.cap, outstanding, amount),
+            formatted_amount,
+            formatted_credited,
+        })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn quote_burn(
+        &self,
+        ctx: ServiceContext,
+        payload: QuoteBurnPayload,
+    ) -> ProtocolResult<BurnQuote> {
+        let mirror_asset = self.get_mirror_asset_by_asset_id(&payload.asset_id)?;
+        let sudt_amount = mirror_asset
+            .to_sudt_amount(payload.amount)
+            .ok_or(ServiceError::U128Overflow)?;
+        let quota_remaining = self
+            .burn_quota_usage(&ctx, &payload.asset_id)?
+            .map(|(quota, _, used)| quota.quota.saturating_sub(used));
+        let formatted_amount = if payload.formatted {
+            mirror_asset.format_amount(payload.amount)
+        } else {
+            None
+        };
+
+        Ok(BurnQuote {
+            asset_id: payload.asset_id.clone(),
+            amount: payload.amount,
+            min_burn_amount: mirror_asset.min_burn_amount,
+            scale_factor: mirror_asset.scale_factor,
+            sudt_amount,
+            quota_remaining,
+            paused: self.supply_cap(&payload.asset_id)?.paused,
+            formatted_amount,
+        })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_header(
+        &self,
+        ctx: ServiceContext,
+        payload: GetHeaderPayload,
+    ) -> ProtocolResult<GetHeaderResponse> {
+        if !self.headers.contains(&payload.height)? {
+            return Err(ServiceError::NotFoundHeader {
+                height: payload.height,
+            }
+            .into());
+        }
+        let header = self.headers.get(&payload.height)?;
+
+        Ok(GetHeaderResponse {
+            epoch: header.epoch_view(),
+            header,
+        })
+    }
+
+    /// Heights missing between the lowest and highest stored headers, for a
+    /// relayer to fill in after a crash. A call scans a bounded number of
+    /// heights, `next` tells where to resume when it stopped early.
+    #[cycles(100_00)]
+    #[read]
+    fn get_missing_header_ranges(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMissingHeaderRangesPayload,
+    ) -> ProtocolResult<MissingHeaderRanges> {
+        let bounds = match self.header_bounds()? {
+            Some(bounds) => bounds,
+            None => return Ok(MissingHeaderRanges::default()),
+        };
+
+        let from = payload.from.unwrap_or(bounds.lowest).max(bounds.lowest);
+        let limit = payload.limit.max(1).min(MAX_HEADER_RANGES) as usize;
+        let (ranges, next) = header_gaps(from, bounds.highest, limit, MAX_HEADER_SCAN, |height| {
+            self.headers.contains(&height)
+        })?;
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * ranges.len() as u64)?;
+
+        Ok(MissingHeaderRanges {
+            lowest: Some(bounds.lowest),
+            highest: Some(bounds.highest),
+            ranges,
+            next,
+        })
+    }
+
+    /// Digests of the bridge events emitted in the blocks of `payload`, for
+    /// light clients to check the events they got are all there are.
+    #[cycles(100_00)]
+    #[read]
+    fn get_event_digest(
+        &self,
+        ctx: ServiceContext,
+        payload: HeightRange,
+    ) -> ProtocolResult<EventDigests> {
+        let mut digests = EventDigests::default();
+        if payload.start > payload.end {
+            return Ok(digests);
+        }
+
+        let last = payload
+            .end
+            .min(payload.start.saturating_add(MAX_DIGEST_SCAN - 1));
+        for height in payload.start..=last {
+            if self.event_digests.contains(&height)? {
+                digests.digests.push(self.event_digests.get(&height)?);
+            }
+        }
+        if last < payload.end {
+            digests.next = Some(last + 1);
+        }
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * digests.digests.len() as u64)?;
+
+        Ok(digests)
+    }
+
+    /// The header at `height` as the relayer submitted it, before parsing.
+    #[cycles(100_00)]
+    #[read]
+    fn get_header_raw(
+        &self,
+        ctx: ServiceContext,
+        payload: GetHeaderPayload,
+    ) -> ProtocolResult<CkbHeader> {
+        if !self.raw_headers.contains(&payload.height)? {
+            return Err(ServiceError::NotFoundHeader {
+                height: payload.height,
+            }
+            .into());
+        }
+        self.raw_headers.get(&payload.height)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_mirror_asset_by_sudt(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetBySudtPayload,
+    ) -> ProtocolResult<MirrorAsset> {
+        self.get_mirror_asset(&payload.sudt_args)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_mirror_asset_by_id(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetByIdPayload,
+    ) -> ProtocolResult<MirrorAsset> {
+        self.get_mirror_asset_by_asset_id(&payload.asset_id)
+    }
+
+    /// Muta asset id a sudt maps to, derived the same way whether it is
+    /// registered yet or not.
+    #[cycles(100_00)]
+    #[read]
+    fn get_mirror_asset_id(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetBySudtPayload,
+    ) -> ProtocolResult<GetMirrorAssetIdResponse> {
+        Ok(GetMirrorAssetIdResponse {
+            asset_id:   mirror_asset_id(&payload.sudt_args),
+            registered: self.mirror_assets.contains(&payload.sudt_args)?,
+        })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_sudt_args(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetByIdPayload,
+    ) -> ProtocolResult<Hash> {
+        if !self.sudt_ids.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotMirrorAsset {
+                asset_id: payload.asset_id,
+            }
+            .into());
+        }
+        self.sudt_ids.get(&payload.asset_id)
+    }
+
+    #[cycles(210_00)]
     #[write]
-    fn update_headers(
+    fn register_mirror_asset(
         &mut self,
-        _ctx: ServiceContext,
-        payload: UpdateHeadersPayload,
+        ctx: ServiceContext,
+        payload: RegisterMirrorAssetPayload,
+    ) -> ProtocolResult<MirrorAsset> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        if self.mirror_assets.contains(&payload.sudt_args)? {
+            return Err(ServiceError::MirrorAssetExists {
+                sudt_args: payload.sudt_args,
+            }
+            .into());
+        }
+
+        if payload.scale_factor == 0 {
+            return Err(ServiceError::InvalidScaleFactor.into());
+        }
+
+        let mirror_asset = MirrorAsset {
+            asset_id:        mirror_asset_id(&payload.sudt_args),
+            sudt_args:       payload.sudt_args,
+            name:            payload.name,
+            symbol:          payload.symbol,
+            decimals:        payload.decimals,
+            scale_factor:    payload.scale_factor,
+            min_burn_amount: payload.min_burn_amount,
+        };
+
+        self.create_mirror_asset(&ctx, &mirror_asset)?;
+
+        self.mirror_assets
+            .insert(mirror_asset.sudt_args.clone(), mirror_asset.clone())?;
+        self.sudt_ids.insert(
+            mirror_asset.asset_id.clone(),
+            mirror_asset.sudt_args.clone(),
+        )?;
+
+        let event = RegisterMirrorAssetEvent {
+            asset_id:     mirror_asset.asset_id.clone(),
+            sudt_args:    mirror_asset.sudt_args.clone(),
+            name:         mirror_asset.name.clone(),
+            symbol:       mirror_asset.symbol.clone(),
+            decimals:     mirror_asset.decimals,
+            scale_factor: mirror_asset.scale_factor,
+            topic:        "register_mirror_asset".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        Ok(mirror_asset)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_burn_limit(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetByIdPayload,
+    ) -> ProtocolResult<GetBurnLimitResponse> {
+        let mirror_asset = self.get_mirror_asset_by_asset_id(&payload.asset_id)?;
+
+        Ok(GetBurnLimitResponse {
+            asset_id:        mirror_asset.asset_id,
+            min_burn_amount: mirror_asset.min_burn_amount,
+        })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_burn_record(
+        &self,
+        ctx: ServiceContext,
+        payload: GetBurnRecordPayload,
+    ) -> ProtocolResult<BurnRecord> {
+        self.get_burn_record_by_nonce(payload.nonce)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn set_burn_limit(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetBurnLimitPayload,
     ) -> ProtocolResult<()> {
-        for h in payload.headers.into_iter() {
-            let inner_header =
-                CkbHeaderInner::from(h).map_err(|_| ServiceError::InvalidCrossHeader)?;
-            let height = inner_header.number;
-            self.headers.insert(height, inner_header)?;
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
         }
 
-        Ok(())
+        let mut mirror_asset = self.get_mirror_asset_by_asset_id(&payload.asset_id)?;
+        mirror_asset.min_burn_amount = payload.min_burn_amount;
+        self.mirror_assets
+            .insert(mirror_asset.sudt_args.clone(), mirror_asset)?;
+
+        let event = SetBurnLimitEvent {
+            asset_id:        payload.asset_id,
+            min_burn_amount: payload.min_burn_amount,
+            topic:           "set_burn_limit".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_burn_quota(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetByIdPayload,
+    ) -> ProtocolResult<GetBurnQuotaResponse> {
+        let mirror_asset = self.get_mirror_asset_by_asset_id(&payload.asset_id)?;
+        let quota = if self.burn_quotas.contains(&mirror_asset.asset_id)? {
+            self.burn_quotas.get(&mirror_asset.asset_id)?
+        } else {
+            BurnQuota {
+                quota:  0,
+                window: 0,
+            }
+        };
+
+        Ok(GetBurnQuotaResponse {
+            asset_id: mirror_asset.asset_id,
+            quota:    quota.quota,
+            window:   quota.window,
+        })
     }
 
+    #[cycles(210_00)]
     #[write]
-    fn submit_messages(
+    fn set_burn_quota(
         &mut self,
         ctx: ServiceContext,
-        payload: MessagePayload,
+        payload: SetBurnQuotaPayload,
     ) -> ProtocolResult<()> {
-        for m in payload.messages.into_iter() {
-            let tx = m.tx;
-            self.check_tx(&tx)?;
-            let token_id = Hash::from_hex(
-                &tx.outputs[0]
-                    .clone()
-                    .type_
-                    .unwrap()
-                    .args
-                    .as_string()
-                    .as_str(),
-            )?;
-            let amount_bytes = hex::decode(tx.outputs_data[0].as_string_trim0x().as_str())
-                .map_err(|_| ServiceError::InvalidCrossTx)?;
-            let mut amount: u128 = LittleEndian::read_u128(amount_bytes.as_slice());
-            let receiver: Address =
-                Address::from_hex(&tx.witnesses.last().unwrap().clone().as_string().as_str())?;
-
-            let amount_relay = amount / 100;
-            amount -= amount_relay;
-            let mint_payload = MintTokenPayload {
-                token_id: token_id.clone(),
-                receiver: receiver.clone(),
-                amount,
-            };
-            let payload_string =
-                serde_json::to_string(&mint_payload).map_err(ServiceError::JsonParse)?;
-            self.sdk.write(
-                &ctx,
-                Some(ADMISSION_TOKEN.clone()),
-                "asset",
-                "mint_token",
-                &payload_string,
-            )?;
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
 
-            let event = MintTokenEvent {
-                asset_id: token_id.clone(),
-                asset_name: "ckb_image_token".to_owned() + &token_id.as_hex().as_str()[2..7],
-                receiver: receiver.clone(),
-                amount,
-                kind: "cross_to_muta".to_owned(),
-                topic: "mint_asset".to_owned(),
-            };
-            let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
-            ctx.emit_event(event_str)?;
+        let mirror_asset = self.get_mirror_asset_by_asset_id(&payload.asset_id)?;
+        if payload.quota == 0 || payload.window == 0 {
+            if self.burn_quotas.contains(&mirror_asset.asset_id)? {
+                self.burn_quotas.remove(&mirror_asset.asset_id)?;
+            }
+        } else {
+            self.burn_quotas.insert(mirror_asset.asset_id, BurnQuota {
+                quota:  payload.quota,
+                window: payload.window,
+            })?;
+        }
 
-            let mint_relay_payload = MintTokenPayload {
-                token_id: token_id.clone(),
-                receiver: ctx.get_caller(),
-                amount:   amount_relay,
-            };
-            let relay_payload_string =
-                serde_json::to_string(&mint_relay_payload).map_err(ServiceError::JsonParse)?;
-            self.sdk.write(
-                &ctx,
-                Some(ADMISSION_TOKEN.clone()),
-                "asset",
-                "mint_token",
-                &relay_payload_string,
-            )?;
+        let event = SetBurnQuotaEvent {
+            asset_id: payload.asset_id,
+            quota:    payload.quota,
+            window:   payload.window,
+            topic:    "set_burn_quota".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
 
-            let event_relay = MintTokenEvent {
-                asset_id:   token_id.clone(),
-                asset_name: "ckb_image_token".to_owned() + &token_id.as_hex().as_str()[2..7],
-                receiver:   ctx.get_caller(),
-                amount:     amount_relay,
-                kind:       "cross_to_muta".to_owned(),
-                topic:      "mint_asset".to_owned(),
-            };
-            let event_relay_str =
-                serde_json::to_string(&event_relay).map_err(ServiceError::JsonParse)?;
-            ctx.emit_event(event_relay_str)?;
+    /// Caps the outstanding bridged supply of an asset at the collateral
+    /// locked on the other chain.
+    #[cycles(210_00)]
+    #[write]
+    fn set_supply_cap(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetSupplyCapPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
         }
 
-        Ok(())
+        let mut supply_cap = self.supply_cap(&payload.asset_id)?;
+        supply_cap.cap = payload.cap;
+        self.supply_caps
+            .insert(payload.asset_id.clone(), supply_cap)?;
+
+        let event = SetSupplyCapEvent {
+            asset_id: payload.asset_id,
+            cap:      payload.cap,
+            topic:    "set_supply_cap".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
     }
 
+    /// Lifts the pause crossing the supply cap put on an asset, once the
+    /// admin has ruled out a bad proof.
+    #[cycles(210_00)]
     #[write]
-    fn burn_sudt(&mut self, ctx: ServiceContext, payload: BurnPayload) -> ProtocolResult<()> {
-        let call_asset_payload = BurnCallAssetPayload {
-            token_id: payload.token_id.clone(),
-            user:     ctx.get_caller(),
-            amount:   payload.amount,
+    fn resume_bridge(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ResumeBridgePayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let mut supply_cap = self.supply_cap(&payload.asset_id)?;
+        supply_cap.paused = false;
+        self.supply_caps
+            .insert(payload.asset_id.clone(), supply_cap)?;
+
+        let event = BridgeResumedEvent {
+            asset_id: payload.asset_id,
+            topic:    "bridge_resumed".to_owned(),
         };
-        let payload_string =
-            serde_json::to_string(&call_asset_payload).map_err(ServiceError::JsonParse)?;
-        self.sdk.write(
-            &ctx,
-            Some(ADMISSION_TOKEN.clone()),
-            "asset",
-            "burn_token",
-            &payload_string,
-        )?;
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
 
-        self.nonce.add(1)?;
+    #[cycles(210_00)]
+    #[write]
+    fn add_quota_exemption(
+        &mut self,
+        ctx: ServiceContext,
+        payload: QuotaExemptionPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
 
-        let event = BurnTokenEvent {
-            asset_id:     payload.token_id.clone(),
-            muta_sender:  ctx.get_caller(),
-            ckb_receiver: payload.receiver.clone(),
-            amount:       payload.amount,
-            nonce:        self.nonce.get()?,
-            kind:         "cross_to_ckb".to_owned(),
-            topic:        "burn_asset".to_owned(),
+        self.quota_exempt.insert(payload.address.clone(), true)?;
+
+        let event = QuotaExemptionEvent {
+            address: payload.address,
+            topic:   "add_quota_exemption".to_owned(),
         };
         let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
-        ctx.emit_event(event_str)?;
-        Ok(())
+        ctx.emit_event(event_str)
     }
 
-    fn check_tx(&self, tx: &CkbTx) -> ProtocolResult<()> {
-        let output = &tx.outputs[0];
-        if output.type_.is_none()
-            || (output.type_.clone().unwrap().code_hash != Hash::from_hex(SUDT_CODE_HASH)?)
-            || tx.witnesses.is_empty()
-        {
-            return Err(ServiceError::InvalidCrossTx.into());
+    #[cycles(210_00)]
+    #[write]
+    fn remove_quota_exemption(
+        &mut self,
+        ctx: ServiceContext,
+        payload: QuotaExemptionPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
         }
 
-        Ok(())
+        if self.quota_exempt.contains(&payload.address)? {
+            self.quota_exempt.remove(&payload.address)?;
+        }
+
+        let event = QuotaExemptionEvent {
+            address: payload.address,
+            topic:   "remove_quota_exemption".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
     }
-}
 
-#[derive(Debug, Display, From)]
-pub enum ServiceError {
-    #[display(fmt = "Parsing payload to json failed {:?}", _0)]
-    JsonParse(serde_json::Error),
+    #[cycles(100_00)]
+    #[read]
+    fn get_withdrawal_fee_config(
+        &self,
+        ctx: ServiceContext,
+    ) -> ProtocolResult<WithdrawalFeeConfig> {
+        self.withdrawal_fee_config()?
+            .ok_or_else(|| ServiceError::WithdrawalFeeNotConfigured.into())
+    }
 
-    InvalidCrossTx,
+    /// Sets the native asset fee each burn of a mirror asset pays, which
+    /// covers the ckb costs of unlocking it.
+    #[cycles(210_00)]
+    #[write]
+    fn set_withdrawal_fee(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetWithdrawalFeePayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
 
-    InvalidCrossHeader,
+        let mirror_asset = self.get_mirror_asset_by_asset_id(&payload.asset_id)?;
+        if payload.fee == 0 {
+            if self.withdrawal_fees.contains(&mirror_asset.asset_id)? {
+                self.withdrawal_fees.remove(&mirror_asset.asset_id)?;
+            }
+        } else {
+            if self.withdrawal_fee_config()?.is_none() {
+                return Err(ServiceError::WithdrawalFeeNotConfigured.into());
+            }
+            self.withdrawal_fees
+                .insert(mirror_asset.asset_id, Amount(payload.fee))?;
+        }
+
+        let event = SetWithdrawalFeeEvent {
+            asset_id: payload.asset_id,
+            fee:      payload.fee,
+            topic:    "set_withdrawal_fee".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn add_fee_waiver(
+        &mut self,
+        ctx: ServiceContext,
+        payload: FeeWaiverPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.fee_waived.insert(payload.address.clone(), true)?;
+
+        let event = FeeWaiverEvent {
+            address: payload.address,
+            topic:   "add_fee_waiver".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn remove_fee_waiver(
+        &mut self,
+        ctx: ServiceContext,
+        payload: FeeWaiverPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        if self.fee_waived.contains(&payload.address)? {
+            self.fee_waived.remove(&payload.address)?;
+        }
+
+        let event = FeeWaiverEvent {
+            address: payload.address,
+            topic:   "remove_fee_waiver".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// The withdrawal fee a burn of the asset by the caller would pay.
+    #[cycles(100_00)]
+    #[read]
+    fn estimate_withdrawal_fee(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMirrorAssetByIdPayload,
+    ) -> ProtocolResult<WithdrawalFeeEstimate> {
+        let mirror_asset = self.get_mirror_asset_by_asset_id(&payload.asset_id)?;
+        let fee = self.withdrawal_fee(&mirror_asset.asset_id)?;
+        let waived = fee > 0 && self.fee_waived.contains(&ctx.get_caller())?;
+        let config = if fee > 0 && !waived {
+            self.withdrawal_fee_config()?
+        } else {
+            None
+        };
+
+        Ok(WithdrawalFeeEstimate {
+            asset_id: mirror_asset.asset_id,
+            fee,
+            native_asset_id: config.as_ref().map(|c| c.native_asset_id.clone()),
+            treasury: config.map(|c| c.treasury),
+            waived,
+        })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn add_relayer(&mut self, ctx: ServiceContext, payload: RelayerPayload) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.relayers.insert(payload.address.clone(), true)?;
+
+        let event = RelayerEvent {
+            address: payload.address,
+            topic:   "add_relayer".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn remove_relayer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RelayerPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        if self.relayers.contains(&payload.address)? {
+            self.relayers.remove(&payload.address)?;
+        }
+
+        let event = RelayerEvent {
+            address: payload.address,
+            topic:   "remove_relayer".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Has `on_cross_mint` of `service` called whenever a deposit is minted to
+    /// `address`, so services like a dex pool can act on deposits.
+    #[cycles(210_00)]
+    #[write]
+    fn register_mint_callback(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RegisterMintCallbackPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.mint_callbacks
+            .insert(payload.address.clone(), payload.service.clone())?;
+
+        let event = MintCallbackEvent {
+            address: payload.address,
+            service: payload.service,
+            topic:   "register_mint_callback".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn unregister_mint_callback(
+        &mut self,
+        ctx: ServiceContext,
+        payload: MintCallbackPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        if !self.mint_callbacks.contains(&payload.address)? {
+            return Err(ServiceError::NoMintCallback {
+                address: payload.address,
+            }
+            .into());
+        }
+
+        let service = self.mint_callbacks.get(&payload.address)?;
+        self.mint_callbacks.remove(&payload.address)?;
+
+        let event = MintCallbackEvent {
+            address: payload.address,
+            service,
+            topic: "unregister_mint_callback".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_mint_callback(
+        &self,
+        ctx: ServiceContext,
+        payload: MintCallbackPayload,
+    ) -> ProtocolResult<String> {
+        if !self.mint_callbacks.contains(&payload.address)? {
+            return Err(ServiceError::NoMintCallback {
+                address: payload.address,
+            }
+            .into());
+        }
+        self.mint_callbacks.get(&payload.address)
+    }
+
+    /// Lets calls relayed from ckb call `methods` of `service`, which take an
+    /// `OnCrossCallPayload`. Replaces the methods registered before.
+    #[cycles(210_00)]
+    #[write]
+    fn register_call_target(
+        &mut self,
+        ctx: ServiceContext,
+        payload: CallTarget,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        // calls into these would act with the bridge's own rights
+        if payload.service == "asset" || payload.service.as_bytes() == SERVICE_NAME.as_ref() {
+            return Err(ServiceError::ReservedCallTarget {
+                service: payload.service,
+            }
+            .into());
+        }
+
+        self.call_targets
+            .insert(payload.service.clone(), payload.clone())?;
+
+        let event = CallTargetEvent {
+            service: payload.service,
+            methods: payload.methods,
+            topic:   "register_call_target".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn unregister_call_target(
+        &mut self,
+        ctx: ServiceContext,
+        payload: CallTargetPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        if !self.call_targets.contains(&payload.service)? {
+            return Err(ServiceError::NoCallTarget {
+                service: payload.service,
+            }
+            .into());
+        }
+
+        let target = self.call_targets.get(&payload.service)?;
+        self.call_targets.remove(&payload.service)?;
+
+        let event = CallTargetEvent {
+            service: target.service,
+            methods: target.methods,
+            topic:   "unregister_call_target".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// The methods of a service calls relayed from ckb may call, none if it
+    /// isn't registered.
+    #[cycles(100_00)]
+    #[read]
+    fn get_call_target(
+        &self,
+        ctx: ServiceContext,
+        payload: CallTargetPayload,
+    ) -> ProtocolResult<CallTarget> {
+        if !self.call_targets.contains(&payload.service)? {
+            return Ok(CallTarget {
+                service: payload.service,
+                methods: vec![],
+            });
+        }
+        self.call_targets.get(&payload.service)
+    }
+
+    /// Lets `service` send messages to ckb.
+    #[cycles(210_00)]
+    #[write]
+    fn add_outbound_sender(
+        &mut self,
+        ctx: ServiceContext,
+        payload: OutboundSenderPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.outbound_senders
+            .insert(payload.service.clone(), true)?;
+
+        let event = OutboundSenderEvent {
+            service: payload.service,
+            topic:   "add_outbound_sender".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn remove_outbound_sender(
+        &mut self,
+        ctx: ServiceContext,
+        payload: OutboundSenderPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        if self.outbound_senders.contains(&payload.service)? {
+            self.outbound_senders.remove(&payload.service)?;
+        }
+
+        let event = OutboundSenderEvent {
+            service: payload.service,
+            topic:   "remove_outbound_sender".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_outbound_sender(
+        &self,
+        ctx: ServiceContext,
+        payload: OutboundSenderPayload,
+    ) -> ProtocolResult<OutboundSender> {
+        Ok(OutboundSender {
+            allowed: self.outbound_senders.contains(&payload.service)?,
+            service: payload.service,
+        })
+    }
+
+    /// Queues a message for the contract of `target_script` on ckb, which the
+    /// relayer delivers along with a proof against the outbound root of the
+    /// block. Only allowed services may send, naming themselves in the extra
+    /// of `sdk.write`.
+    #[cycles(210_00)]
+    #[write]
+    fn send_message_to_ckb(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SendMessageToCkbPayload,
+    ) -> ProtocolResult<()> {
+        let sender = self.verify_outbound_sender(&ctx)?;
+        let len = payload.payload.as_string_trim0x().len() / 2;
+        let max = self.params()?.max_outbound_payload_len as usize;
+        if len > max {
+            return Err(ServiceError::OutboundPayloadTooLarge { len, max }.into());
+        }
+
+        self.outbound_len.add(1)?;
+        let nonce = self.outbound_len.get()?;
+        let height = ctx.get_current_height();
+        let message = OutboundMessage {
+            nonce,
+            sender,
+            target_script: payload.target_script,
+            payload: payload.payload,
+            height,
+        };
+
+        let event = OutboundMessageEvent {
+            nonce,
+            sender: message.sender.clone(),
+            target_script: message.target_script.clone(),
+            payload: message.payload.clone(),
+            height,
+            topic: "send_message_to_ckb".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        self.accumulate_outbound(height, nonce, &event_str)?;
+        self.outbound_messages.insert(nonce, message)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_outbound_message(
+        &self,
+        ctx: ServiceContext,
+        payload: GetOutboundMessagePayload,
+    ) -> ProtocolResult<OutboundMessage> {
+        if !self.outbound_messages.contains(&payload.nonce)? {
+            return Err(ServiceError::NotFoundOutboundMessage {
+                nonce: payload.nonce,
+            }
+            .into());
+        }
+        self.outbound_messages.get(&payload.nonce)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_outbound_block(
+        &self,
+        ctx: ServiceContext,
+        payload: GetOutboundBlockPayload,
+    ) -> ProtocolResult<OutboundBlock> {
+        if !self.outbound_blocks.contains(&payload.height)? {
+            return Err(ServiceError::NotFoundOutboundBlock {
+                height: payload.height,
+            }
+            .into());
+        }
+        self.outbound_blocks.get(&payload.height)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_supported_locks(&self, ctx: ServiceContext) -> ProtocolResult<SupportedLocks> {
+        self.supported_locks()
+    }
+
+    /// Replaces the lock code hashes burn receivers may use.
+    #[cycles(210_00)]
+    #[write]
+    fn set_supported_locks(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SupportedLocks,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.sdk
+            .set_value(SUPPORTED_LOCKS_KEY.to_owned(), payload.clone())?;
+
+        let event = SupportedLocksEvent {
+            code_hashes: payload.code_hashes,
+            topic:       "set_supported_locks".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_mirror_asset_issuer(&self, ctx: ServiceContext) -> ProtocolResult<MirrorAssetIssuer> {
+        Ok(MirrorAssetIssuer {
+            issuer: self.mirror_asset_issuer()?,
+        })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_message_ttl(&self, ctx: ServiceContext) -> ProtocolResult<MessageTtl> {
+        Ok(MessageTtl {
+            blocks: self.message_ttl()?,
+        })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_params(&self, ctx: ServiceContext) -> ProtocolResult<Params> {
+        self.params()
+    }
+
+    /// Changes the parameters the payload sets, from the next call reading
+    /// them on.
+    #[cycles(210_00)]
+    #[write]
+    fn set_params(&mut self, ctx: ServiceContext, payload: SetParamsPayload) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        if let Some(name) = params::invalid(&payload) {
+            return Err(ServiceError::InvalidParam {
+                name: name.to_owned(),
+            }
+            .into());
+        }
+
+        let changed = params::store(&mut self.sdk, payload)?;
+
+        let event = ParamsEvent {
+            changed,
+            params: self.params()?,
+            topic: "set_params".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_bridge_limits(&self, ctx: ServiceContext) -> ProtocolResult<BridgeLimits> {
+        self.bridge_limits()
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_fee_beneficiary(&self, ctx: ServiceContext) -> ProtocolResult<FeeBeneficiary> {
+        self.fee_beneficiary()
+    }
+
+    /// Relay fee rates by deposit size, the flat 1% unless the admin set
+    /// tiers.
+    #[cycles(100_00)]
+    #[read]
+    fn get_fee_schedule(&self, ctx: ServiceContext) -> ProtocolResult<FeeSchedule> {
+        self.fee_schedule()
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn set_header_relayer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RelayerPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.sdk
+            .set_value(HEADER_RELAYER_KEY.to_owned(), payload.address.clone())?;
+
+        let event = RelayerEvent {
+            address: payload.address,
+            topic:   "set_header_relayer".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[write]
+    fn update_headers(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<UpdateHeadersPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        if !self.is_relayer(&ctx)? && !self.is_header_relayer(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let mut stats = self.bridge_stats()?;
+        let mut heights = vec![];
+        for (index, h) in payload.headers.into_iter().enumerate() {
+            match self.store_header(index, h, &mut stats)? {
+                StoredHeader::Stored(height) => heights.push(height),
+                StoredHeader::Conflict(conflict) => {
+                    let event = HeaderConflictEvent {
+                        height:           conflict.height,
+                        stored_hash:      conflict.stored_hash,
+                        conflicting_hash: conflict.conflicting_hash,
+                        relayer:          ctx.get_caller(),
+                        topic:            "header_conflict".to_owned(),
+                    };
+                    let event_str =
+                        serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+                    ctx.emit_event(event_str)?;
+                }
+            }
+        }
+        self.set_bridge_stats(stats)?;
+
+        for height in heights.into_iter() {
+            self.process_pending_at(&ctx, height)?;
+        }
+
+        Ok(())
+    }
+
+    /// Settles the conflict at `payload.height` by fork choice: the longer of
+    /// the stored branch and the conflicting one, each linked by parent
+    /// hash, keeps the height.
+    #[cycles(210_00)]
+    #[write]
+    fn resolve_header_conflict(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ResolveHeaderConflictPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        let height = payload.height;
+        if !self.header_conflicts.contains(&height)? {
+            return Err(ServiceError::NotFoundHeaderConflict { height }.into());
+        }
+        let conflict = self.header_conflicts.get(&height)?;
+        if payload.branch.len() > MAX_FORK_BRANCH_LEN {
+            return Err(ServiceError::ForkBranchTooLong {
+                max: MAX_FORK_BRANCH_LEN,
+            }
+            .into());
+        }
+
+        let mut branch = Vec::with_capacity(payload.branch.len());
+        for (index, raw) in payload.branch.into_iter().enumerate() {
+            let header = CkbHeaderInner::from(raw.clone())
+                .map_err(|e| ServiceError::InvalidHeaderField { index, error: e })?;
+            branch.push((header, raw));
+        }
+        if let Some((first, _)) = branch.first() {
+            if proof::header_hash(first)? != conflict.conflicting_hash {
+                return Err(ServiceError::InvalidForkBranch { height }.into());
+            }
+        }
+        self.verify_branch(height, &branch)?;
+
+        // the conflicting branch is its header alone unless given
+        let branch_len = branch.len().max(1) as u64;
+        let stored_len = self.stored_branch_len(height, branch_len + 1)?;
+        let (kept_hash, dropped_hash, kept_len) = if branch_len > stored_len {
+            if branch.is_empty() {
+                let header = CkbHeaderInner::from(conflict.header.clone())
+                    .map_err(|e| ServiceError::InvalidHeaderField { index: 0, error: e })?;
+                branch.push((header, conflict.header.clone()));
+            }
+            let mut stats = self.bridge_stats()?;
+            for (header, raw) in branch.into_iter() {
+                self.put_header(header, raw, &mut stats)?;
+            }
+            self.set_bridge_stats(stats)?;
+            (conflict.conflicting_hash, conflict.stored_hash, branch_len)
+        } else if stored_len > branch_len {
+            (conflict.stored_hash, conflict.conflicting_hash, stored_len)
+        } else {
+            return Err(ServiceError::UndecidedFork { height }.into());
+        };
+        self.header_conflicts.remove(&height)?;
+
+        let event = HeaderConflictResolvedEvent {
+            height,
+            kept_hash,
+            dropped_hash,
+            branch_len: kept_len,
+            topic: "resolve_header_conflict".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        self.process_pending_at(&ctx, height)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_header_conflict(
+        &self,
+        ctx: ServiceContext,
+        payload: GetHeaderPayload,
+    ) -> ProtocolResult<HeaderConflict> {
+        if !self.header_conflicts.contains(&payload.height)? {
+            return Err(ServiceError::NotFoundHeaderConflict {
+                height: payload.height,
+            }
+            .into());
+        }
+        self.header_conflicts.get(&payload.height)
+    }
+
+    /// Queues headers from the header relayer, they are verified and stored
+    /// by the after block hook. No cycles are charged, so the relayer node
+    /// can feed every block for free; the queue is bounded instead, as is
+    /// what one block takes from it.
+    #[write]
+    fn stage_headers(
+        &mut self,
+        ctx: ServiceContext,
+        payload: UpdateHeadersPayload,
+    ) -> ProtocolResult<()> {
+        if !self.is_header_relayer(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        for (index, h) in payload.headers.iter().enumerate() {
+            CkbHeaderInner::from(h.clone())
+                .map_err(|e| ServiceError::InvalidHeaderField { index, error: e })?;
+        }
+        let max = self.params()?.max_staged_headers;
+        let staged = self.staged_len.get()? - self.staged_start.get()?;
+        if staged + payload.headers.len() as u64 > max {
+            return Err(ServiceError::StagedHeadersFull { max }.into());
+        }
+        for h in payload.headers.into_iter() {
+            let index = self.staged_len.get()?;
+            self.staged_headers.insert(index, h)?;
+            self.staged_len.add(1)?;
+        }
+
+        Ok(())
+    }
+
+    // Migrations left by an upgrade run in the first blocks after it. One
+    // failing must not fail the block, the admin sees its error by running
+    // it through `migrate`.
+    #[hook_before]
+    fn before_block(&mut self, params: &ExecutorParams) -> ProtocolResult<()> {
+        if self.schema_version()? < SCHEMA_VERSION {
+            let _ = self.run_migrations(MIGRATION_BATCH);
+        }
+        Ok(())
+    }
+
+    #[hook_after]
+    fn after_block(&mut self, params: &ExecutorParams) -> ProtocolResult<()> {
+        self.seal_burn_batch(params.height)?;
+        self.apply_staged_headers()
+    }
+
+    // Takes at most `staged_headers_per_block` headers, oldest first, the
+    // rest wait for the next blocks.
+    fn apply_staged_headers(&mut self) -> ProtocolResult<()> {
+        let (start, len) = (self.staged_start.get()?, self.staged_len.get()?);
+        if start == len {
+            return Ok(());
+        }
+        let end = len.min(start + self.params()?.staged_headers_per_block);
+
+        let mut stats = self.bridge_stats()?;
+        for index in start..end {
+            let header = self.staged_headers.get(&index)?;
+            self.staged_headers.remove(&index)?;
+            // A header failing verification is dropped, it must not fail the
+            // block.
+            let _ = self.store_header((index - start) as usize, header, &mut stats);
+        }
+        if end == len {
+            self.staged_start.set(0)?;
+            self.staged_len.set(0)?;
+        } else {
+            self.staged_start.set(end)?;
+        }
+
+        self.set_bridge_stats(stats)
+    }
+
+    /// Appends headers to the header range without storing them, the relayer
+    /// keeps the range and proves deposits against it. The relayer's root
+    /// must match the one computed here.
+    #[write]
+    fn update_mmr(&mut self, ctx: ServiceContext, payload: UpdateMmrPayload) -> ProtocolResult<()> {
+        if !self.is_relayer(&ctx)? && !self.is_header_relayer(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let mut range = self.mmr_range()?;
+        let mut tip: Option<CkbHeaderInner> = self.sdk.get_value(&MMR_TIP_KEY.to_owned())?;
+        for (index, raw) in payload.headers.into_iter().enumerate() {
+            let header = CkbHeaderInner::from(raw)
+                .map_err(|e| ServiceError::InvalidHeaderField { index, error: e })?;
+            match &tip {
+                Some(parent) => {
+                    if header.number != parent.number + 1
+                        || header.parent_hash != proof::header_hash(parent)?
+                    {
+                        return Err(ServiceError::MmrNotContinuous {
+                            height: header.number,
+                        }
+                        .into());
+                    }
+                }
+                None => range.start = header.number,
+            }
+            self.light_client.verify_header(&header, tip.as_ref())?;
+
+            mmr::append(&mut range.peaks, range.leaves, proof::header_hash(&header)?)?;
+            range.leaves += 1;
+            tip = Some(header);
+        }
+
+        if mmr::bag_peaks(&range.peaks)?.as_ref() != Some(&payload.root) {
+            return Err(ServiceError::MmrRootMismatch.into());
+        }
+        self.mmr_roots.insert(range.leaves, payload.root.clone())?;
+        if let Some(tip) = tip {
+            self.sdk.set_value(MMR_TIP_KEY.to_owned(), tip)?;
+        }
+        let leaves = range.leaves;
+        self.sdk.set_value(MMR_KEY.to_owned(), range)?;
+
+        let event = MmrUpdatedEvent {
+            leaves,
+            root: payload.root,
+            topic: "mmr_updated".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_mmr(&self, ctx: ServiceContext) -> ProtocolResult<GetMmrResponse> {
+        let range = self.mmr_range()?;
+        Ok(GetMmrResponse {
+            start:  range.start,
+            leaves: range.leaves,
+            root:   mmr::bag_peaks(&range.peaks)?,
+        })
+    }
+
+    /// Deposits proven against a header of the header range, which comes
+    /// with the message instead of being stored.
+    #[write]
+    fn submit_mmr_messages(
+        &mut self,
+        ctx: ServiceContext,
+        payload: MmrMessagePayload,
+    ) -> ProtocolResult<()> {
+        self.check_batch_size(payload.messages.len())?;
+        let relayer = ctx.get_caller();
+        for (index, m) in payload.messages.into_iter().enumerate() {
+            let header = self.mmr_header(m.header, &m.mmr_proof)?;
+            self.process_message(&ctx, &relayer, header.as_ref(), Some(index), m.message)?;
+        }
+
+        Ok(())
+    }
+
+    /// Mints the deposits of the messages of a stored header. A message
+    /// failing with an error rather than being rejected fails the whole
+    /// batch: the executor reverts every write of the tx, the asset service's
+    /// mints for the messages before it included, so the batch can be
+    /// submitted again as it was.
+    #[write]
+    fn submit_messages(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<MessagePayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        self.check_source_chain(&payload.source_chain_id)?;
+        self.check_batch_size(payload.messages.len())?;
+        let mut messages = Vec::with_capacity(payload.messages.len());
+        for (index, m) in payload.messages.into_iter().enumerate() {
+            match m.unpack() {
+                Ok(m) => messages.push((index, m)),
+                Err(error) => self.reject_message(&ctx, error.in_message(index))?,
+            }
+        }
+        // Messages ahead of the stored headers wait for theirs instead of
+        // failing the batch, as do those of a disputed header.
+        if !self.headers.contains(&payload.height)?
+            || self.header_conflicts.contains(&payload.height)?
+        {
+            let messages = messages.into_iter().map(|(_, m)| m).collect();
+            return self.queue_messages(&ctx, payload.height, messages);
+        }
+
+        let relayer = ctx.get_caller();
+        let header = self.stored_header(payload.height)?;
+        for (index, m) in messages.into_iter() {
+            self.process_message(&ctx, &relayer, header.as_ref(), Some(index), m)?;
+        }
+
+        Ok(())
+    }
+
+    /// Messages of the same block proven at once, which shares the tree
+    /// nodes their proofs have in common. The header must be stored already.
+    /// A batch whose proof doesn't hold is rejected as a whole.
+    #[write]
+    fn submit_message_batch(
+        &mut self,
+        ctx: ServiceContext,
+        payload: BatchMessagePayload,
+    ) -> ProtocolResult<()> {
+        self.check_source_chain(&payload.source_chain_id)?;
+        self.check_batch_size(payload.messages.len())?;
+        let header = self
+            .stored_header(payload.height)?
+            .ok_or(ServiceError::NotFoundHeader {
+                height: payload.height,
+            })?;
+        if self.is_expired(payload.height)? {
+            for index in 0..payload.messages.len() {
+                self.reject_message(
+                    &ctx,
+                    MessageError::from(RejectReason::Expired).in_message(index),
+                )?;
+            }
+            return Ok(());
+        }
+
+        let sudt_code_hash = self.params()?.sudt_code_hash;
+        let mut decoded = Vec::with_capacity(payload.messages.len());
+        for m in payload.messages.into_iter() {
+            decoded.push(decode_message_tx(m.raw_tx, m.tx, &sudt_code_hash)?);
+        }
+        let mut txs = Vec::with_capacity(decoded.len());
+        for message_tx in decoded.iter().filter_map(|d| d.as_ref().ok()) {
+            txs.push((message_tx.tx_hash.clone(), message_tx.witness_hash.clone()));
+        }
+        let proven = txs.len() == decoded.len()
+            && self
+                .light_client
+                .verify_batch(&header, &txs, &payload.proof)?;
+        if !proven {
+            for (index, d) in decoded.into_iter().enumerate() {
+                let error = d
+                    .err()
+                    .unwrap_or_else(|| RejectReason::InvalidProof.at("proof"));
+                self.reject_message(&ctx, error.in_message(index))?;
+            }
+            return Ok(());
+        }
+
+        let relayer = ctx.get_caller();
+        for (index, message_tx) in decoded.into_iter().filter_map(Result::ok).enumerate() {
+            if self.effected_proofs.contains(&message_tx.tx_hash)? {
+                let error = MessageError::from(RejectReason::DuplicateTx);
+                self.reject_message(&ctx, error.in_message(index))?;
+                continue;
+            }
+            self.apply_message(&ctx, &relayer, Some(index), message_tx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes the messages queued for `height` once its header is stored.
+    /// `update_headers` does it by itself, this is for headers stored by the
+    /// after block hook, which can't mint.
+    #[write]
+    fn process_pending(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ProcessPendingPayload,
+    ) -> ProtocolResult<()> {
+        if !self.headers.contains(&payload.height)? {
+            return Err(ServiceError::NotFoundHeader {
+                height: payload.height,
+            }
+            .into());
+        }
+
+        self.process_pending_at(&ctx, payload.height)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_recovered_deposit(
+        &self,
+        ctx: ServiceContext,
+        payload: GetRecoveredDepositPayload,
+    ) -> ProtocolResult<RecoveredDeposit> {
+        if !self.recovered_deposits.contains(&payload.tx_hash)? {
+            return Err(ServiceError::NotRecoveredDeposit {
+                tx_hash: payload.tx_hash,
+            }
+            .into());
+        }
+        self.recovered_deposits.get(&payload.tx_hash)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_deposit_by_ckb_tx(
+        &self,
+        ctx: ServiceContext,
+        payload: GetDepositByCkbTxPayload,
+    ) -> ProtocolResult<ProcessedDeposit> {
+        if !self.deposits.contains(&payload.tx_hash)? {
+            return Err(ServiceError::NotFoundDeposit {
+                tx_hash: payload.tx_hash,
+            }
+            .into());
+        }
+        self.deposits.get(&payload.tx_hash)
+    }
+
+    /// Processes a verified message that can't go through as submitted, with
+    /// the admin correcting its receiver or memo. The message must still be
+    /// proven and not yet processed, but may have expired.
+    #[cycles(210_00)]
+    #[write]
+    fn force_process_message(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ForceProcessMessagePayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let rejected = |error: MessageError| ServiceError::ForcedMessageRejected {
+            reason: error.to_string(),
+        };
+        let message_tx = self
+            .message_tx(
+                self.stored_header(payload.height)?.as_ref(),
+                payload.message,
+            )?
+            .map_err(rejected)?;
+        let tx_hash = message_tx.tx_hash;
+        let mut deposit = message_tx.deposit.map_err(rejected)?;
+        if !self.mirror_assets.contains(&deposit.sudt_args)? {
+            return Err(rejected(RejectReason::UnregisteredSudt.into()).into());
+        }
+        if let Some(receiver) = payload.receiver.clone() {
+            deposit.receiver = Some(receiver);
+        }
+        if let Some(memo) = payload.memo.clone() {
+            deposit.memo = memo;
+        }
+
+        self.credit_message(&ctx, &ctx.get_caller(), None, tx_hash.clone(), deposit)?;
+        // credit_message only rejects a mint over the supply cap, which a
+        // forced message fails on
+        if !self.effected_proofs.contains(&tx_hash)? {
+            return Err(rejected(RejectReason::BridgePaused.into()).into());
+        }
+
+        let event = MessageForcedEvent {
+            tx_hash,
+            height: payload.height,
+            receiver: payload.receiver,
+            memo: payload.memo,
+            admin: ctx.get_caller(),
+            topic: "force_process_message".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Moves a deposit held by the recovery account to its rightful receiver.
+    #[cycles(210_00)]
+    #[write]
+    fn reassign_deposit(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ReassignDepositPayload,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        if !self.recovered_deposits.contains(&payload.tx_hash)? {
+            return Err(ServiceError::NotRecoveredDeposit {
+                tx_hash: payload.tx_hash,
+            }
+            .into());
+        }
+        let mut deposit = self.recovered_deposits.get(&payload.tx_hash)?;
+        if deposit.reassigned_to.is_some() {
+            return Err(ServiceError::NotRecoveredDeposit {
+                tx_hash: payload.tx_hash,
+            }
+            .into());
+        }
+
+        self.burn_token_from(
+            &ctx,
+            deposit.asset_id.clone(),
+            self.params()?.recovery_account,
+            deposit.amount,
+        )?;
+        self.mint_token(
+            &ctx,
+            deposit.asset_id.clone(),
+            payload.receiver.clone(),
+            deposit.amount,
+            String::new(),
+            Some(payload.tx_hash.clone()),
+        )?;
+        deposit.reassigned_to = Some(payload.receiver.clone());
+        self.recovered_deposits
+            .insert(payload.tx_hash.clone(), deposit.clone())?;
+
+        let event = DepositReassignedEvent {
+            tx_hash:  payload.tx_hash,
+            asset_id: deposit.asset_id,
+            amount:   deposit.amount,
+            receiver: payload.receiver,
+            admin:    ctx.get_caller(),
+            topic:    "deposit_reassigned".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[write]
+    fn burn_sudt(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<BurnPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        self.check_source_chain(&payload.source_chain_id)?;
+        let receiver = CkbAddress::parse(&payload.receiver)?;
+        if !self
+            .supported_locks()?
+            .code_hashes
+            .contains(&receiver.lock.code_hash)
+        {
+            return Err(ServiceError::UnsupportedReceiverLock {
+                code_hash: receiver.lock.code_hash,
+            }
+            .into());
+        }
+        let mirror_asset = self.get_mirror_asset_by_asset_id(&payload.token_id)?;
+        if payload.amount < mirror_asset.min_burn_amount {
+            return Err(ServiceError::MinimumBurnNotMet {
+                min:  mirror_asset.min_burn_amount,
+                real: payload.amount,
+            }
+            .into());
+        }
+        let sudt_args = mirror_asset.sudt_args.clone();
+        let sudt_amount = mirror_asset
+            .to_sudt_amount(payload.amount)
+            .ok_or(ServiceError::U128Overflow)?;
+        self.ensure_not_paused(&payload.token_id)?;
+        self.consume_burn_quota(&ctx, &payload.token_id, payload.amount)?;
+        self.charge_withdrawal_fee(&ctx, &payload.token_id)?;
+
+        self.burn_token(&ctx, payload.token_id.clone(), payload.amount)?;
+
+        self.nonce.add(1)?;
+        let nonce = self.nonce.get()?;
+
+        let record = BurnRecord {
+            nonce,
+            asset_id: payload.token_id.clone(),
+            sudt_args: sudt_args.clone(),
+            muta_sender: ctx.get_caller(),
+            ckb_receiver: payload.receiver.clone(),
+            amount: payload.amount,
+            sudt_amount,
+            status: BurnStatus::Pending,
+            ckb_tx_hash: None,
+        };
+        self.add_epoch_burn(&ctx, &record, &receiver.lock.code_hash)?;
+        self.burn_records.insert(nonce, record)?;
+
+        let mut stats = self.bridge_stats()?;
+        stats.total_burns += 1;
+        let volume = stats.volume_mut(&payload.token_id);
+        volume.burned = checked_add_u128(volume.burned, payload.amount)?;
+        self.set_bridge_stats(stats)?;
+
+        let event = BurnTokenEvent {
+            asset_id: payload.token_id.clone(),
+            sudt_args,
+            ckb_receiver: payload.receiver.clone(),
+            sudt_amount,
+            nonce,
+            chain: self.chain_binding()?,
+            kind: "cross_to_ckb".to_owned(),
+            topic: "burn_asset".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        self.digest_event(&ctx, BridgeEvent::Burn, &event_str)?;
+        self.batch_burn(&ctx, nonce, &event_str)?;
+        ctx.emit_event(event_str)?;
+        Ok(())
+    }
+
+    /// Burns to ckb of a burn epoch, which ckb operators can withdraw in one
+    /// tx once it is closed.
+    #[cycles(100_00)]
+    #[read]
+    fn get_epoch_summary(
+        &self,
+        ctx: ServiceContext,
+        payload: GetEpochSummaryPayload,
+    ) -> ProtocolResult<EpochSummary> {
+        let mut summary = self.epoch_summary(payload.epoch)?;
+        summary.closed = ctx.get_current_height() > summary.end_height;
+        Ok(summary)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_burn_batch(
+        &self,
+        ctx: ServiceContext,
+        payload: GetBurnBatchPayload,
+    ) -> ProtocolResult<BurnBatch> {
+        if !self.burn_batches.contains(&payload.batch)? {
+            return Err(ServiceError::NotFoundBurnBatch {
+                batch: payload.batch,
+            }
+            .into());
+        }
+        self.burn_batches.get(&payload.batch)
+    }
+
+    /// The deposit tag of an address, and whether the address registered it.
+    #[cycles(100_00)]
+    #[read]
+    fn get_deposit_tag(
+        &self,
+        ctx: ServiceContext,
+        payload: GetDepositTagPayload,
+    ) -> ProtocolResult<DepositTag> {
+        let tag = deposit::deposit_tag(&payload.address);
+        let registered =
+            self.deposit_tags.contains(&tag)? && self.deposit_tags.get(&tag)? == payload.address;
+        Ok(DepositTag {
+            address: payload.address,
+            tag,
+            registered,
+            witness: Hex::from_string(format!("0x{}", hex::encode(deposit::tag_witness(tag))))?,
+        })
+    }
+
+    /// Registers the deposit tag of the caller, so deposits naming the tag
+    /// are minted to it.
+    #[cycles(210_00)]
+    #[write]
+    fn register_deposit_tag(&mut self, ctx: ServiceContext) -> ProtocolResult<()> {
+        let caller = ctx.get_caller();
+        let tag = deposit::deposit_tag(&caller);
+        if self.deposit_tags.contains(&tag)? {
+            return Err(ServiceError::DepositTagTaken {
+                tag,
+                owner: self.deposit_tags.get(&tag)?,
+            }
+            .into());
+        }
+        self.deposit_tags.insert(tag, caller.clone())?;
+
+        let event = DepositTagEvent {
+            address: caller,
+            tag,
+            topic: "register_deposit_tag".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_payout_address(
+        &self,
+        ctx: ServiceContext,
+        payload: RelayerPayload,
+    ) -> ProtocolResult<PayoutAddress> {
+        let relayer = payload.address;
+        if !self.payout_addresses.contains(&relayer)? {
+            return Ok(PayoutAddress {
+                payout: relayer.clone(),
+                relayer,
+                changeable_at: 0,
+            });
+        }
+
+        let stored = self.payout_addresses.get(&relayer)?;
+        Ok(PayoutAddress {
+            relayer,
+            payout: stored.payout,
+            changeable_at: stored.set_at + self.params()?.payout_cooldown,
+        })
+    }
+
+    /// Sets where the caller's relay fees are minted, from the next deposit
+    /// it relays on. Once set, it can't change again for the blocks of the
+    /// `payout_cooldown` parameter.
+    #[cycles(210_00)]
+    #[write]
+    fn set_payout_address(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetPayoutAddressPayload,
+    ) -> ProtocolResult<()> {
+        let relayer = ctx.get_caller();
+        let height = ctx.get_current_height();
+        if self.payout_addresses.contains(&relayer)? {
+            let until =
+                self.payout_addresses.get(&relayer)?.set_at + self.params()?.payout_cooldown;
+            if height < until {
+                return Err(ServiceError::PayoutCooldown { until }.into());
+            }
+        }
+        self.payout_addresses
+            .insert(relayer.clone(), RelayerPayout {
+                payout: payload.payout.clone(),
+                set_at: height,
+            })?;
+
+        let event = PayoutAddressEvent {
+            relayer,
+            payout: payload.payout,
+            topic: "set_payout_address".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Runs the pending migrations of the state, for the admin to finish a
+    /// batched one faster than the blocks do.
+    #[cycles(210_00)]
+    #[write]
+    fn migrate(&mut self, ctx: ServiceContext, payload: MigratePayload) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let from_version = self.schema_version()?;
+        let (version, cursor) = self.run_migrations(payload.limit)?;
+
+        let event = MigrationEvent {
+            from_version,
+            version,
+            cursor,
+            topic: "migrate".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_migration_status(&self, ctx: ServiceContext) -> ProtocolResult<MigrationStatus> {
+        let schema_version = self.schema_version()?;
+        Ok(MigrationStatus {
+            schema_version,
+            target_version: SCHEMA_VERSION,
+            pending: migration::pending(&Self::migrations(), schema_version),
+            cursor: self.migration_cursor()?,
+        })
+    }
+
+    /// Announces the last burn batch if it was sealed and not announced yet.
+    /// A batch is otherwise announced by the first burn after it, so a batch
+    /// sealed before a quiet period waits for a relayer to call this, or to
+    /// poll `get_burn_batch`.
+    #[cycles(210_00)]
+    #[write]
+    fn announce_burn_batches(&mut self, ctx: ServiceContext) -> ProtocolResult<()> {
+        if !self.is_relayer(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        match self.last_burn_batch()? {
+            Some(mut batch) if batch.sealed && !batch.announced => {
+                self.announce_burn_batch(&ctx, &mut batch)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Completes a pending burn on a ckb tx, included under a stored header,
+    /// that pays the burn's sudt amount to its receiver.
+    #[cycles(210_00)]
+    #[write]
+    fn ack_burn(&mut self, ctx: ServiceContext, payload: AckBurnPayload) -> ProtocolResult<()> {
+        if !self.is_relayer(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let mut record = self.get_burn_record_by_nonce(payload.nonce)?;
+        if record.status != BurnStatus::Pending {
+            return Err(ServiceError::BurnNotPending {
+                nonce: payload.nonce,
+            }
+            .into());
+        }
+
+        if self.acked_burn_txs.contains(&payload.ckb_tx_hash)? {
+            return Err(ServiceError::ProofAlreadyUsed {
+                tx_hash: payload.ckb_tx_hash,
+            }
+            .into());
+        }
+
+        let tx = match decode_burn_tx(&payload.raw_tx) {
+            Some((tx, tx_hash)) if tx_hash == payload.ckb_tx_hash => tx,
+            _ => return Err(ServiceError::InvalidCrossTx.into()),
+        };
+        if !pays_burn(&tx, &record, &self.params()?.sudt_code_hash) {
+            return Err(ServiceError::BurnNotPaid {
+                nonce: payload.nonce,
+            }
+            .into());
+        }
+
+        if !self.headers.contains(&payload.height)? {
+            return Err(ServiceError::NotFoundHeader {
+                height: payload.height,
+            }
+            .into());
+        }
+        let header = self.headers.get(&payload.height)?;
+        if !self
+            .light_client
+            .verify_inclusion(&header, &payload.ckb_tx_hash, &payload.proof)?
+        {
+            return Err(ServiceError::InvalidProof.into());
+        }
+
+        self.acked_burn_txs
+            .insert(payload.ckb_tx_hash.clone(), payload.nonce)?;
+        record.status = BurnStatus::Completed;
+        record.ckb_tx_hash = Some(payload.ckb_tx_hash.clone());
+        self.burn_records.insert(payload.nonce, record.clone())?;
+
+        let event = BurnAckedEvent {
+            nonce:       record.nonce,
+            asset_id:    record.asset_id,
+            ckb_tx_hash: payload.ckb_tx_hash,
+            relayer:     ctx.get_caller(),
+            topic:       "burn_acked".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn cancel_burn(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<CancelBurnPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        let mut record = self.get_burn_record_by_nonce(payload.nonce)?;
+
+        if record.muta_sender != ctx.get_caller() {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        if record.status != BurnStatus::Pending {
+            return Err(ServiceError::BurnNotPending {
+                nonce: payload.nonce,
+            }
+            .into());
+        }
+        // the batch root the relayer withdraws against already holds it
+        if self.burn_nonce_batches.contains(&payload.nonce)? {
+            return Err(ServiceError::BurnBatched {
+                nonce: payload.nonce,
+            }
+            .into());
+        }
+
+        self.mint_token(
+            &ctx,
+            record.asset_id.clone(),
+            record.muta_sender.clone(),
+            record.amount,
+            String::new(),
+            None,
+        )?;
+
+        record.status = BurnStatus::Cancelled;
+        self.burn_records.insert(payload.nonce, record.clone())?;
+        self.remove_epoch_burn(&record)?;
+
+        // A cancelled burn never left the chain, so it is taken out of the
+        // outbound totals again.
+        let mut stats = self.bridge_stats()?;
+        stats.total_burns = stats.total_burns.saturating_sub(1);
+        let volume = stats.volume_mut(&record.asset_id);
+        volume.burned = volume.burned.saturating_sub(record.amount);
+        self.set_bridge_stats(stats)?;
+
+        let event = BurnCancelledEvent {
+            nonce:       record.nonce,
+            asset_id:    record.asset_id,
+            muta_sender: record.muta_sender,
+            amount:      record.amount,
+            topic:       "burn_cancelled".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn set_eth_config(&mut self, ctx: ServiceContext, payload: EthConfig) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        parse_eth_address(&payload.bridge_contract)?;
+
+        self.sdk.set_value(ETH_CONFIG_KEY.to_owned(), payload)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_eth_config(&self, ctx: ServiceContext) -> ProtocolResult<EthConfig> {
+        self.eth_config()
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn register_eth_asset(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RegisterEthAssetPayload,
+    ) -> ProtocolResult<MirrorAsset> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let token = eth::token_key(&parse_eth_address(&payload.token)?);
+        if self.eth_assets.contains(&token)? {
+            return Err(ServiceError::MirrorAssetExists { sudt_args: token }.into());
+        }
+        if payload.scale_factor == 0 {
+            return Err(ServiceError::InvalidScaleFactor.into());
+        }
+
+        let mirror_asset = MirrorAsset {
+            asset_id:        eth_mirror_asset_id(&token),
+            sudt_args:       token,
+            name:            payload.name,
+            symbol:          payload.symbol,
+            decimals:        payload.decimals,
+            scale_factor:    payload.scale_factor,
+            min_burn_amount: payload.min_burn_amount,
+        };
+        self.create_mirror_asset(&ctx, &mirror_asset)?;
+
+        self.eth_assets
+            .insert(mirror_asset.sudt_args.clone(), mirror_asset.clone())?;
+        self.eth_asset_ids.insert(
+            mirror_asset.asset_id.clone(),
+            mirror_asset.sudt_args.clone(),
+        )?;
+
+        let event = RegisterMirrorAssetEvent {
+            asset_id:     mirror_asset.asset_id.clone(),
+            sudt_args:    mirror_asset.sudt_args.clone(),
+            name:         mirror_asset.name.clone(),
+            symbol:       mirror_asset.symbol.clone(),
+            decimals:     mirror_asset.decimals,
+            scale_factor: mirror_asset.scale_factor,
+            topic:        "register_eth_asset".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        Ok(mirror_asset)
+    }
+
+    #[write]
+    fn update_eth_headers(
+        &mut self,
+        ctx: ServiceContext,
+        payload: UpdateEthHeadersPayload,
+    ) -> ProtocolResult<()> {
+        // Seals aren't verified on chain, see `EthLightClient`.
+        if !self.is_relayer(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        let light_client = EthLightClient {
+            mode: self.eth_config()?.mode,
+        };
+
+        for raw in payload.headers.into_iter() {
+            let data = hex::decode(raw.as_string_trim0x())
+                .map_err(|_| ServiceError::InvalidCrossHeader)?;
+            let header = eth::decode_header(&data).map_err(|_| ServiceError::InvalidCrossHeader)?;
+            let height = light_client.height(&header);
+            let parent = if height > 0 && self.eth_headers.contains(&(height - 1))? {
+                Some(self.eth_headers.get(&(height - 1))?)
+            } else {
+                None
+            };
+            light_client.verify_header(&header, parent.as_ref())?;
+
+            self.eth_headers.insert(height, header)?;
+        }
+
+        Ok(())
+    }
+
+    #[write]
+    fn submit_eth_receipts(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SubmitEthReceiptsPayload,
+    ) -> ProtocolResult<()> {
+        let config = self.eth_config()?;
+        let bridge_contract = parse_eth_address(&config.bridge_contract)?;
+        let light_client = EthLightClient { mode: config.mode };
+
+        // Like ckb messages, a bad receipt is skipped and counted.
+        for proof in payload.receipts.into_iter() {
+            let receipt = match hex::decode(proof.receipt.as_string_trim0x()) {
+                Ok(receipt) => receipt,
+                Err(_) => {
+                    self.reject_message(&ctx, RejectReason::InvalidTx)?;
+                    continue;
+                }
+            };
+            if !self.eth_headers.contains(&proof.height)? {
+                self.reject_message(&ctx, RejectReason::InvalidProof)?;
+                continue;
+            }
+            let header = self.eth_headers.get(&proof.height)?;
+            if !light_client.verify_inclusion(&header, &receipt, &proof)? {
+                self.reject_message(&ctx, RejectReason::InvalidProof)?;
+                continue;
+            }
+
+            let receipt_id = eth_receipt_id(&header.hash, proof.tx_index);
+            if self.effected_proofs.contains(&receipt_id)? {
+                self.reject_message(&ctx, RejectReason::DuplicateTx)?;
+                continue;
+            }
+            let locks = match eth::lock_events(&receipt, &bridge_contract) {
+                Ok(locks) => locks,
+                Err(_) => {
+                    self.reject_message(&ctx, RejectReason::InvalidTx)?;
+                    continue;
+                }
+            };
+            // left unmarked so the receipt can be submitted again once all
+            // its tokens are registered
+            if !self.eth_locks_registered(&locks)? {
+                self.reject_message(&ctx, RejectReason::UnregisteredToken)?;
+                continue;
+            }
+            if !self.admit_eth_locks(&ctx, &locks)? {
+                self.reject_message(&ctx, RejectReason::BridgePaused)?;
+                continue;
+            }
+            self.effected_proofs.insert(receipt_id, true)?;
+
+            let schedule = self.fee_schedule()?;
+            for lock in locks.into_iter() {
+                let mirror_asset = self.eth_assets.get(&lock.token)?;
+                let outcome = deposit::outcome(
+                    &mirror_asset,
+                    &schedule,
+                    lock.receiver,
+                    String::new(),
+                    lock.amount,
+                    false,
+                );
+                self.credit_deposit(&ctx, &mirror_asset, &outcome, &ctx.get_caller(), None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn burn_to_eth(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<BurnToEthPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        if !self.eth_asset_ids.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotMirrorAsset {
+                asset_id: payload.asset_id,
+            }
+            .into());
+        }
+        let token = self.eth_asset_ids.get(&payload.asset_id)?;
+        let mirror_asset = self.eth_assets.get(&token)?;
+        parse_eth_address(&payload.receiver)?;
+
+        if payload.amount < mirror_asset.min_burn_amount {
+            return Err(ServiceError::MinimumBurnNotMet {
+                min:  mirror_asset.min_burn_amount,
+                real: payload.amount,
+            }
+            .into());
+        }
+        let token_amount = mirror_asset
+            .to_sudt_amount(payload.amount)
+            .ok_or(ServiceError::U128Overflow)?;
+        self.ensure_not_paused(&payload.asset_id)?;
+        self.consume_burn_quota(&ctx, &payload.asset_id, payload.amount)?;
+
+        self.burn_token(&ctx, payload.asset_id.clone(), payload.amount)?;
+
+        self.nonce.add(1)?;
+        let nonce = self.nonce.get()?;
+
+        let mut stats = self.bridge_stats()?;
+        stats.total_burns += 1;
+        let volume = stats.volume_mut(&payload.asset_id);
+        volume.burned = checked_add_u128(volume.burned, payload.amount)?;
+        self.set_bridge_stats(stats)?;
+
+        let event = BurnToEthEvent {
+            asset_id: payload.asset_id,
+            token,
+            muta_sender: ctx.get_caller(),
+            eth_receiver: payload.receiver,
+            amount: payload.amount,
+            token_amount,
+            nonce,
+            chain: self.chain_binding()?,
+            topic: "burn_to_eth".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn set_muta_config(&mut self, ctx: ServiceContext, payload: MutaConfig) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.sdk.set_value(MUTA_CONFIG_KEY.to_owned(), payload)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_muta_config(&self, ctx: ServiceContext) -> ProtocolResult<MutaConfig> {
+        self.muta_config()
+    }
+
+    /// Seeds the first validator set when the mode wasn't set up at genesis,
+    /// later sets can only come from `update_muta_validators`.
+    #[cycles(210_00)]
+    #[write]
+    fn set_muta_validator_set(
+        &mut self,
+        ctx: ServiceContext,
+        payload: MutaValidatorSet,
+    ) -> ProtocolResult<()> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        if self.muta_validator_sets.contains(&0)? {
+            return Err(ServiceError::InvalidMutaValidatorSet.into());
+        }
+
+        self.seed_muta_validator_set(payload)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_muta_validator_set(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMutaValidatorSetPayload,
+    ) -> ProtocolResult<MutaValidatorSet> {
+        let validator_set = self.muta_validator_set_at(payload.height)?;
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * validator_set.validators.len() as u64)?;
+        Ok(validator_set)
+    }
+
+    /// Moves to the next validator set once the header announcing it is
+    /// confirmed.
+    #[write]
+    fn update_muta_validators(
+        &mut self,
+        ctx: ServiceContext,
+        payload: MutaValidatorUpdate,
+    ) -> ProtocolResult<()> {
+        let next = payload.validator_set.clone();
+        let header = match next.start_height.checked_sub(1) {
+            Some(height) => self.confirmed_muta_header(height)?,
+            None => None,
+        };
+        let header = header.ok_or(ServiceError::InvalidValidatorUpdate {
+            version: next.version,
+        })?;
+        let light_client = MutaLightClient {
+            config:        self.muta_config()?,
+            validator_set: self.current_muta_validator_set()?,
+        };
+        light_client.verify_validator_update(&header, &payload)?;
+
+        self.muta_epoch.add(1)?;
+        let epoch = self.muta_epoch.get()?;
+        self.muta_validator_sets.insert(epoch, next.clone())?;
+
+        let event = MutaValidatorSetEvent {
+            version:      next.version,
+            start_height: next.start_height,
+            validators:   next.validators,
+            topic:        "muta_validator_set".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_muta_header(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMutaHeaderPayload,
+    ) -> ProtocolResult<MutaHeader> {
+        if !self.muta_headers.contains(&payload.height)? {
+            return Err(ServiceError::NotFoundHeader {
+                height: payload.height,
+            }
+            .into());
+        }
+        let header = self.muta_headers.get(&payload.height)?;
+        ctx.sub_cycles(READ_CYCLES_PER_ITEM * header.validators.len() as u64)?;
+        Ok(header)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn register_muta_asset(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RegisterMutaAssetPayload,
+    ) -> ProtocolResult<MirrorAsset> {
+        if !self.verify_authority(&ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        let origin_asset_id = payload.asset_id;
+        if self.muta_assets.contains(&origin_asset_id)? {
+            return Err(ServiceError::MirrorAssetExists {
+                sudt_args: origin_asset_id,
+            }
+            .into());
+        }
+        if payload.scale_factor == 0 {
+            return Err(ServiceError::InvalidScaleFactor.into());
+        }
+
+        let mirror_asset = MirrorAsset {
+            asset_id:        muta_mirror_asset_id(&origin_asset_id),
+            sudt_args:       origin_asset_id,
+            name:            payload.name,
+            symbol:          payload.symbol,
+            decimals:        payload.decimals,
+            scale_factor:    payload.scale_factor,
+            min_burn_amount: payload.min_burn_amount,
+        };
+        self.create_mirror_asset(&ctx, &mirror_asset)?;
+
+        self.muta_assets
+            .insert(mirror_asset.sudt_args.clone(), mirror_asset.clone())?;
+        self.muta_asset_ids.insert(
+            mirror_asset.asset_id.clone(),
+            mirror_asset.sudt_args.clone(),
+        )?;
+
+        let event = RegisterMirrorAssetEvent {
+            asset_id:     mirror_asset.asset_id.clone(),
+            sudt_args:    mirror_asset.sudt_args.clone(),
+            name:         mirror_asset.name.clone(),
+            symbol:       mirror_asset.symbol.clone(),
+            decimals:     mirror_asset.decimals,
+            scale_factor: mirror_asset.scale_factor,
+            topic:        "register_muta_asset".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+
+        Ok(mirror_asset)
+    }
+
+    /// Headers are verified against the validator signatures they carry, so
+    /// unlike the other chains anyone may submit them.
+    #[write]
+    fn update_muta_headers(
+        &mut self,
+        _ctx: ServiceContext,
+        payload: UpdateMutaHeadersPayload,
+    ) -> ProtocolResult<()> {
+        let config = self.muta_config()?;
+
+        for raw in payload.headers.into_iter() {
+            let data = hex::decode(raw.as_string_trim0x())
+                .map_err(|_| ServiceError::InvalidCrossHeader)?;
+            let header =
+                muta::decode_header(&data).map_err(|_| ServiceError::InvalidCrossHeader)?;
+            let height = header.height;
+            if height == 0 {
+                return Err(ServiceError::InvalidMutaHeader { height }.into());
+            }
+            // The proof is of the parent block, signed by the set of its
+            // height.
+            let light_client = MutaLightClient {
+                config:        config.clone(),
+                validator_set: self.muta_validator_set_at(height - 1)?,
+            };
+            let parent = if self.muta_headers.contains(&(height - 1))? {
+                Some(self.muta_headers.get(&(height - 1))?)
+            } else {
+                None
+            };
+            light_client.verify_header(&header, parent.as_ref())?;
+
+            // An unconfirmed header may be a fork nobody signed, it is
+            // replaced rather than left blocking the real one.
+            if self.confirmed_muta_header(height)?.is_some() {
+                let stored = self.muta_headers.get(&height)?;
+                if stored.hash != header.hash {
+                    return Err(ServiceError::InvalidMutaHeader { height }.into());
+                }
+                continue;
+            }
+            self.muta_headers.insert(height, header)?;
+        }
+
+        Ok(())
+    }
+
+    #[write]
+    fn submit_muta_receipts(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SubmitMutaReceiptsPayload,
+    ) -> ProtocolResult<()> {
+        let light_client = MutaLightClient {
+            config:        self.muta_config()?,
+            validator_set: self.current_muta_validator_set()?,
+        };
+
+        // Like ckb messages, a bad receipt is skipped and counted.
+        for proof in payload.receipts.into_iter() {
+            let receipt = match hex::decode(proof.receipt.as_string_trim0x()) {
+                Ok(receipt) => receipt,
+                Err(_) => {
+                    self.reject_message(&ctx, RejectReason::InvalidTx)?;
+                    continue;
+                }
+            };
+            let header = match self.confirmed_muta_header(proof.height)? {
+                Some(header) => header,
+                None => {
+                    self.reject_message(&ctx, RejectReason::InvalidProof)?;
+                    continue;
+                }
+            };
+            let receipt_hash = Hash::digest(Bytes::from(receipt.clone()));
+            if !light_client.verify_inclusion(&header, &receipt_hash, &proof)? {
+                self.reject_message(&ctx, RejectReason::InvalidProof)?;
+                continue;
+            }
+
+            if self.effected_proofs.contains(&receipt_hash)? {
+                self.reject_message(&ctx, RejectReason::DuplicateTx)?;
+                continue;
+            }
+            let locks = match muta::lock_events(&receipt, &light_client.config.bridge_service) {
+                Ok(locks) => locks,
+                Err(_) => {
+                    self.reject_message(&ctx, RejectReason::InvalidTx)?;
+                    continue;
+                }
+            };
+            // left unmarked so the receipt can be submitted again once all
+            // its assets are registered
+            if !self.muta_locks_registered(&locks)? {
+                self.reject_message(&ctx, RejectReason::UnregisteredToken)?;
+                continue;
+            }
+            if !self.admit_muta_locks(&ctx, &locks)? {
+                self.reject_message(&ctx, RejectReason::BridgePaused)?;
+                continue;
+            }
+            self.effected_proofs.insert(receipt_hash, true)?;
+
+            let schedule = self.fee_schedule()?;
+            for lock in locks.into_iter() {
+                let mirror_asset = self.muta_assets.get(&lock.asset_id)?;
+                let outcome = deposit::outcome(
+                    &mirror_asset,
+                    &schedule,
+                    lock.receiver,
+                    String::new(),
+                    lock.amount,
+                    false,
+                );
+                self.credit_deposit(&ctx, &mirror_asset, &outcome, &ctx.get_caller(), None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn burn_to_muta(
+        &mut self,
+        ctx: ServiceContext,
+        payload: Versioned<BurnToMutaPayload>,
+    ) -> ProtocolResult<()> {
+        let payload = payload.into_inner();
+        if !self.muta_asset_ids.contains(&payload.asset_id)? {
+            return Err(ServiceError::NotMirrorAsset {
+                asset_id: payload.asset_id,
+            }
+            .into());
+        }
+        let origin_asset_id = self.muta_asset_ids.get(&payload.asset_id)?;
+        let mirror_asset = self.muta_assets.get(&origin_asset_id)?;
+
+        if payload.amount < mirror_asset.min_burn_amount {
+            return Err(ServiceError::MinimumBurnNotMet {
+                min:  mirror_asset.min_burn_amount,
+                real: payload.amount,
+            }
+            .into());
+        }
+        let origin_amount = mirror_asset
+            .to_sudt_amount(payload.amount)
+            .ok_or(ServiceError::U128Overflow)?;
+        self.ensure_not_paused(&payload.asset_id)?;
+        self.consume_burn_quota(&ctx, &payload.asset_id, payload.amount)?;
+
+        self.burn_token(&ctx, payload.asset_id.clone(), payload.amount)?;
+
+        self.nonce.add(1)?;
+        let nonce = self.nonce.get()?;
+
+        let mut stats = self.bridge_stats()?;
+        stats.total_burns += 1;
+        let volume = stats.volume_mut(&payload.asset_id);
+        volume.burned = checked_add_u128(volume.burned, payload.amount)?;
+        self.set_bridge_stats(stats)?;
+
+        let event = BurnToMutaEvent {
+            asset_id: payload.asset_id,
+            origin_asset_id,
+            sender: ctx.get_caller(),
+            receiver: payload.receiver,
+            amount: payload.amount,
+            origin_amount,
+            nonce,
+            chain: self.chain_binding()?,
+            topic: "burn_to_muta".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Verifies a message against the header at `height` and mints its
+    /// deposit. A malformed message is skipped and counted instead of
+    /// reverting the whole batch.
+    fn process_message(
+        &mut self,
+        ctx: &ServiceContext,
+        relayer: &Address,
+        header: Option<&CkbHeaderInner>,
+        index: Option<usize>,
+        m: CkbMessage,
+    ) -> ProtocolResult<()> {
+        let in_message = |error: MessageError| match index {
+            Some(index) => error.in_message(index),
+            None => error,
+        };
+        if let Some(header) = header {
+            if self.is_expired(header.number)? {
+                return self.reject_message(ctx, in_message(RejectReason::Expired.into()));
+            }
+        }
+        let message_tx = match self.message_tx(header, m)? {
+            Ok(message_tx) => message_tx,
+            Err(error) => return self.reject_message(ctx, in_message(error)),
+        };
+        self.apply_message(ctx, relayer, index, message_tx)
+    }
+
+    /// Mints the deposit or relays the call of a proven message.
+    fn apply_message(
+        &mut self,
+        ctx: &ServiceContext,
+        relayer: &Address,
+        index: Option<usize>,
+        message_tx: MessageTx,
+    ) -> ProtocolResult<()> {
+        let in_message = |error: MessageError| match index {
+            Some(index) => error.in_message(index),
+            None => error,
+        };
+        match (message_tx.call, message_tx.deposit) {
+            (Some(Ok(call)), _) => self.relay_call(ctx, index, message_tx.tx_hash, call),
+            (Some(Err(error)), _) | (None, Err(error)) => {
+                self.reject_message(ctx, in_message(error))
+            }
+            (None, Ok(deposit)) => {
+                self.credit_message(ctx, relayer, index, message_tx.tx_hash, deposit)
+            }
+        }
+    }
+
+    /// Calls the target of a proven call when it is registered. A call that
+    /// fails is reported and dropped, like a failing mint callback, so the
+    /// target should fail before it writes anything.
+    fn relay_call(
+        &mut self,
+        ctx: &ServiceContext,
+        index: Option<usize>,
+        tx_hash: Hash,
+        call: CrossCall,
+    ) -> ProtocolResult<()> {
+        let allowed = self.call_targets.contains(&call.service)?
+            && self
+                .call_targets
+                .get(&call.service)?
+                .methods
+                .contains(&call.method);
+        if !allowed {
+            return self.reject_message(ctx, MessageError {
+                reason: RejectReason::CallNotAllowed,
+                index,
+                field: None,
+            });
+        }
+        self.effected_proofs.insert(tx_hash.clone(), true)?;
+
+        let call_payload = OnCrossCallPayload {
+            ckb_tx_hash: tx_hash.clone(),
+            code_hash:   call.code_hash.clone(),
+            args:        call.args.clone(),
+            payload:     call.payload,
+        };
+        let payload_string =
+            serde_json::to_string(&call_payload).map_err(ServiceError::JsonParse)?;
+        let error = self
+            .sdk
+            .write(
+                ctx,
+                Some(SERVICE_NAME.clone()),
+                &call.service,
+                &call.method,
+                &payload_string,
+            )
+            .err()
+            .map(|e| e.to_string());
+
+        let event = CrossCallEvent {
+            ckb_tx_hash: tx_hash,
+            service: call.service,
+            method: call.method,
+            code_hash: call.code_hash,
+            args: call.args,
+            error,
+            topic: "cross_call".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Mints the deposit of a verified message, to the recovery account when
+    /// its receiver is malformed.
+    fn credit_message(
+        &mut self,
+        ctx: &ServiceContext,
+        relayer: &Address,
+        index: Option<usize>,
+        tx_hash: Hash,
+        mut deposit: Deposit,
+    ) -> ProtocolResult<()> {
+        let rejected = |reason: RejectReason| MessageError {
+            reason,
+            index,
+            field: None,
+        };
+        if !self.mirror_assets.contains(&deposit.sudt_args)? {
+            return self.reject_message(ctx, rejected(RejectReason::UnregisteredSudt));
+        }
+        let mirror_asset = self.mirror_assets.get(&deposit.sudt_args)?;
+        // left unmarked so the message can be submitted again once resumed
+        if !self.admit_mint(ctx, &mirror_asset, deposit.sudt_amount)? {
+            return self.reject_message(ctx, rejected(RejectReason::BridgePaused));
+        }
+        self.effected_proofs.insert(tx_hash.clone(), true)?;
+
+        // a tag stands for the address that registered it, an unknown tag is
+        // recovered like a malformed receiver
+        if let (None, Some(tag)) = (&deposit.receiver, deposit.tag) {
+            if self.deposit_tags.contains(&tag)? {
+                deposit.receiver = Some(self.deposit_tags.get(&tag)?);
+            }
+        }
+        let recovery_account = self.params()?.recovery_account;
+        let outcome = deposit::process(deposit, &DepositConfig {
+            mirror_asset:     &mirror_asset,
+            fee_schedule:     &self.fee_schedule()?,
+            recovery_account: &recovery_account,
+        });
+        self.credit_deposit(ctx, &mirror_asset, &outcome, relayer, Some(tx_hash.clone()))?;
+
+        let amount = outcome.credited;
+        self.deposits.insert(tx_hash.clone(), ProcessedDeposit {
+            asset_id: mirror_asset.asset_id.clone(),
+            receiver: outcome.receiver,
+            amount,
+            relay_fee: outcome.relay_fee,
+            relayer: relayer.clone(),
+            memo: outcome.memo,
+        })?;
+        if !outcome.recovered {
+            return Ok(());
+        }
+
+        self.recovered_deposits
+            .insert(tx_hash.clone(), RecoveredDeposit {
+                asset_id: mirror_asset.asset_id.clone(),
+                amount,
+                reassigned_to: None,
+            })?;
+        let event = DepositRecoveredEvent {
+            tx_hash,
+            asset_id: mirror_asset.asset_id,
+            amount,
+            topic: "deposit_recovered".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn queue_messages(
+        &mut self,
+        ctx: &ServiceContext,
+        height: u64,
+        messages: Vec<CkbMessage>,
+    ) -> ProtocolResult<()> {
+        let mut pending = if self.pending_messages.contains(&height)? {
+            self.pending_messages.get(&height)?
+        } else {
+            PendingMessages::default()
+        };
+        let max = self.params()?.max_pending_messages as usize;
+        if pending.messages.len() + messages.len() > max {
+            return Err(ServiceError::PendingQueueFull { height }.into());
+        }
+
+        // unverified until their header comes, only relayers fill the queue
+        if !self.is_relayer(ctx)? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+        let relayer = ctx.get_caller();
+        let count = messages.len() as u64;
+        pending
+            .messages
+            .extend(messages.into_iter().map(|message| PendingMessage {
+                relayer: relayer.clone(),
+                message,
+            }));
+        self.pending_messages.insert(height, pending)?;
+
+        let event = MessagesPendingEvent {
+            height,
+            count,
+            topic: "messages_pending".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn process_pending_at(&mut self, ctx: &ServiceContext, height: u64) -> ProtocolResult<()> {
+        // messages of a disputed header wait for its resolution
+        if !self.pending_messages.contains(&height)? || self.header_conflicts.contains(&height)? {
+            return Ok(());
+        }
+        let pending = self.pending_messages.get(&height)?;
+        self.pending_messages.remove(&height)?;
+
+        // What the block can't mint any more stays queued for a later call of
+        // `process_pending`, a message over what is left of the budget
+        // holding back those after it.
+        let header = self.stored_header(height)?;
+        let mut messages = pending.messages.into_iter();
+        while let Some(p) = messages.next() {
+            if !self.block_budget_fits(ctx, self.message_mint(&p.message)?)? {
+                let mut rest = vec![p];
+                rest.extend(messages);
+                self.pending_messages
+                    .insert(height, PendingMessages { messages: rest })?;
+                break;
+            }
+            self.process_pending_message(ctx, header.as_ref(), p)?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes a queued message, rejecting it when its mint fails rather
+    /// than failing the call storing its header. What the bridge wrote for
+    /// it is put back and it is left to be submitted again; the asset
+    /// service checks a mint before writing it, so a failing one wrote
+    /// nothing there.
+    fn process_pending_message(
+        &mut self,
+        ctx: &ServiceContext,
+        header: Option<&CkbHeaderInner>,
+        p: PendingMessage,
+    ) -> ProtocolResult<()> {
+        let sudt_code_hash = self.params()?.sudt_code_hash;
+        let tx_hash = decode_message_tx(
+            p.message.raw_tx.clone(),
+            p.message.tx.clone(),
+            &sudt_code_hash,
+        )?
+        .ok()
+        .map(|message_tx| message_tx.tx_hash);
+        let effected = match &tx_hash {
+            Some(tx_hash) => self.effected_proofs.contains(tx_hash)?,
+            None => false,
+        };
+        let stats = self.bridge_stats()?;
+        let usage = self.block_mint_usage(ctx)?;
+
+        if self
+            .process_message(ctx, &p.relayer, header, None, p.message)
+            .is_ok()
+        {
+            return Ok(());
+        }
+        self.set_bridge_stats(stats)?;
+        self.sdk.set_value(BLOCK_MINT_USAGE_KEY.to_owned(), usage)?;
+        if let (Some(tx_hash), false) = (tx_hash, effected) {
+            if self.effected_proofs.contains(&tx_hash)? {
+                self.effected_proofs.remove(&tx_hash)?;
+            }
+        }
+        self.reject_message(ctx, RejectReason::MintFailed)
+    }
+
+    /// What a message would mint, relay fee included, zero for one minting
+    /// nothing. The message is not verified, only its deposit is read.
+    fn message_mint(&self, m: &CkbMessage) -> ProtocolResult<u128> {
+        let sudt_code_hash = self.params()?.sudt_code_hash;
+        let deposit = match decode_message_tx(m.raw_tx.clone(), m.tx.clone(), &sudt_code_hash)? {
+            Ok(MessageTx {
+                call: None,
+                deposit: Ok(deposit),
+                ..
+            }) => deposit,
+            _ => return Ok(0),
+        };
+        if !self.mirror_assets.contains(&deposit.sudt_args)? {
+            return Ok(0);
+        }
+        let mirror_asset = self.mirror_assets.get(&deposit.sudt_args)?;
+        Ok(mirror_asset.to_mirror_amount(deposit.sudt_amount).0)
+    }
+
+    /// Mints a verified deposit as `deposit::process` split it, the relay fee
+    /// to its beneficiary and the rest to the receiver.
+    fn credit_deposit(
+        &mut self,
+        ctx: &ServiceContext,
+        mirror_asset: &MirrorAsset,
+        outcome: &DepositOutcome,
+        relayer: &Address,
+        ckb_tx_hash: Option<Hash>,
+    ) -> ProtocolResult<()> {
+        // Dust below one mirror unit stays locked on the source chain, it is
+        // reported rather than silently dropped.
+        let (amount, dust) = (outcome.minted, outcome.dust);
+        if dust > 0 {
+            let event = SudtDustEvent {
+                asset_id: mirror_asset.asset_id.clone(),
+                sudt_args: mirror_asset.sudt_args.clone(),
+                dust,
+                topic: "sudt_dust".to_owned(),
+            };
+            let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+            ctx.emit_event(event_str)?;
+        }
+
+        let mut stats = self.bridge_stats()?;
+        // `admit_mint` pauses before this is reached, the check only backs it
+        // up for mints it can't foresee, like several locks in one receipt
+        let cap = self.supply_cap(&mirror_asset.asset_id)?.cap;
+        if !within_cap(cap, stats.outstanding(&mirror_asset.asset_id), amount) {
+            return Err(ServiceError::SupplyCapExceeded {
+                asset_id: mirror_asset.asset_id.clone(),
+                cap,
+            }
+            .into());
+        }
+        self.spend_block_budget(ctx, amount)?;
+        stats.total_mints += 1;
+        let volume = stats.volume_mut(&mirror_asset.asset_id);
+        volume.minted = checked_add_u128(volume.minted, amount)?;
+        self.set_bridge_stats(stats)?;
+
+        self.mint(
+            ctx,
+            mirror_asset,
+            outcome.receiver.clone(),
+            outcome.credited,
+            outcome.memo.clone(),
+            ckb_tx_hash.clone(),
+        )?;
+        let beneficiary = self.fee_beneficiary()?;
+        let payout = self.payout_address(relayer)?;
+        for (account, fee) in deposit::fee_shares(&beneficiary, &payout, outcome.relay_fee) {
+            self.mint(
+                ctx,
+                mirror_asset,
+                account,
+                fee,
+                String::new(),
+                ckb_tx_hash.clone(),
+            )?;
+        }
+        self.call_mint_callback(
+            ctx,
+            mirror_asset,
+            outcome.receiver.clone(),
+            outcome.credited,
+            outcome.memo.clone(),
+        )
+    }
+
+    fn mint(
+        &mut self,
+        ctx: &ServiceContext,
+        mirror_asset: &MirrorAsset,
+        receiver: Address,
+        amount: u128,
+        memo: String,
+        ckb_tx_hash: Option<Hash>,
+    ) -> ProtocolResult<()> {
+        self.mint_token(
+            ctx,
+            mirror_asset.asset_id.clone(),
+            receiver,
+            amount,
+            memo,
+            ckb_tx_hash.clone(),
+        )?;
+
+        let event = MintTokenEvent {
+            asset_id: mirror_asset.asset_id.clone(),
+            sudt_args: mirror_asset.sudt_args.clone(),
+            asset_name: mirror_asset.name.clone(),
+            ckb_tx_hash,
+            chain: self.chain_binding()?,
+            kind: "cross_to_muta".to_owned(),
+            topic: "mint_asset".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        self.digest_event(ctx, BridgeEvent::Mint, &event_str)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Calls the service registered for `receiver`, if any. A failing callback
+    /// doesn't undo the mint, it is reported so the deposit isn't stuck
+    /// behind a broken service.
+    fn call_mint_callback(
+        &mut self,
+        ctx: &ServiceContext,
+        mirror_asset: &MirrorAsset,
+        receiver: Address,
+        amount: u128,
+        memo: String,
+    ) -> ProtocolResult<()> {
+        if !self.mint_callbacks.contains(&receiver)? {
+            return Ok(());
+        }
+        let service = self.mint_callbacks.get(&receiver)?;
+
+        let callback_payload = OnCrossMintPayload {
+            asset_id: mirror_asset.asset_id.clone(),
+            receiver: receiver.clone(),
+            amount,
+            memo,
+        };
+        let payload_string =
+            serde_json::to_string(&callback_payload).map_err(ServiceError::JsonParse)?;
+        if let Err(e) = self.sdk.write(
+            ctx,
+            Some(SERVICE_NAME.clone()),
+            &service,
+            "on_cross_mint",
+            &payload_string,
+        ) {
+            let event = MintCallbackFailedEvent {
+                receiver,
+                service,
+                asset_id: mirror_asset.asset_id.clone(),
+                amount,
+                error: e.to_string(),
+                topic: "mint_callback_failed".to_owned(),
+            };
+            let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+            ctx.emit_event(event_str)?;
+        }
+
+        Ok(())
+    }
+
+    fn create_mirror_asset(
+        &mut self,
+        ctx: &ServiceContext,
+        mirror_asset: &MirrorAsset,
+    ) -> ProtocolResult<()> {
+        let create_payload = CreateMirrorAssetPayload {
+            id:       mirror_asset.asset_id.clone(),
+            name:     mirror_asset.name.clone(),
+            issuer:   self.mirror_asset_issuer()?,
+            decimals: mirror_asset.display_decimals().map(u64::from),
+        };
+        let payload_string =
+            serde_json::to_string(&create_payload).map_err(ServiceError::JsonParse)?;
+        self.sdk.write(
+            ctx,
+            Some(SERVICE_NAME.clone()),
+            "asset",
+            "create_mirror_asset",
+            &payload_string,
+        )?;
+
+        Ok(())
+    }
+
+    fn burn_token(
+        &mut self,
+        ctx: &ServiceContext,
+        asset_id: Hash,
+        amount: u128,
+    ) -> ProtocolResult<()> {
+        self.burn_token_from(ctx, asset_id, ctx.get_caller(), amount)
+    }
+
+    fn burn_token_from(
+        &mut self,
+        ctx: &ServiceContext,
+        asset_id: Hash,
+        user: Address,
+        amount: u128,
+    ) -> ProtocolResult<()> {
+        let call_asset_payload = BurnCallAssetPayload {
+            token_id: asset_id,
+            user,
+            amount,
+        };
+        let payload_string =
+            serde_json::to_string(&call_asset_payload).map_err(ServiceError::JsonParse)?;
+        self.sdk.write(
+            ctx,
+            Some(SERVICE_NAME.clone()),
+            "asset",
+            "burn_token",
+            &payload_string,
+        )?;
+
+        Ok(())
+    }
+
+    fn mint_token(
+        &mut self,
+        ctx: &ServiceContext,
+        asset_id: Hash,
+        receiver: Address,
+        amount: u128,
+        memo: String,
+        source_tx: Option<Hash>,
+    ) -> ProtocolResult<()> {
+        let mint_payload = MintTokenPayload {
+            token_id: asset_id,
+            receiver,
+            amount,
+            memo,
+            source_tx,
+        };
+        let payload_string =
+            serde_json::to_string(&mint_payload).map_err(ServiceError::JsonParse)?;
+        self.sdk.write(
+            ctx,
+            Some(SERVICE_NAME.clone()),
+            "asset",
+            "mint_token",
+            &payload_string,
+        )?;
+
+        Ok(())
+    }
+
+    fn burn_epoch_length(&self) -> ProtocolResult<u64> {
+        Ok(self.params()?.burn_epoch_length)
+    }
+
+    /// The stored summary of `epoch`, an empty one when it has no burns.
+    fn epoch_summary(&self, epoch: u64) -> ProtocolResult<EpochSummary> {
+        if self.burn_epochs.contains(&epoch)? {
+            return self.burn_epochs.get(&epoch);
+        }
+        let length = self.burn_epoch_length()?;
+        let start_height = epoch.saturating_mul(length);
+
+        Ok(EpochSummary {
+            epoch,
+            start_height,
+            end_height: start_height.saturating_add(length - 1),
+            closed: false,
+            burns: 0,
+            first_nonce: 0,
+            last_nonce: 0,
+            assets: vec![],
+        })
+    }
+
+    fn add_epoch_burn(
+        &mut self,
+        ctx: &ServiceContext,
+        record: &BurnRecord,
+        code_hash: &Hash,
+    ) -> ProtocolResult<()> {
+        let epoch = ctx.get_current_height() / self.burn_epoch_length()?;
+        let mut summary = self.epoch_summary(epoch)?;
+        summary.burns += 1;
+        if summary.first_nonce == 0 {
+            summary.first_nonce = record.nonce;
+        }
+        summary.last_nonce = record.nonce;
+
+        let asset = summary.asset_mut(&record.asset_id, &record.sudt_args);
+        asset.burns += 1;
+        asset.amount = checked_add_u128(asset.amount, record.amount)?;
+        asset.sudt_amount = checked_add_u128(asset.sudt_amount, record.sudt_amount)?;
+        let lock = asset.lock_mut(code_hash);
+        lock.burns += 1;
+        lock.sudt_amount = checked_add_u128(lock.sudt_amount, record.sudt_amount)?;
+
+        self.burn_epochs.insert(epoch, summary)?;
+        self.burn_nonce_epochs.insert(record.nonce, epoch)
+    }
+
+    // Burns made before epochs were kept have no epoch to be taken out of.
+    fn remove_epoch_burn(&mut self, record: &BurnRecord) -> ProtocolResult<()> {
+        if !self.burn_nonce_epochs.contains(&record.nonce)? {
+            return Ok(());
+        }
+        let epoch = self.burn_nonce_epochs.get(&record.nonce)?;
+        let code_hash = CkbAddress::parse(&record.ckb_receiver)?.lock.code_hash;
+        let mut summary = self.epoch_summary(epoch)?;
+        summary.burns = summary.burns.saturating_sub(1);
+
+        let asset = summary.asset_mut(&record.asset_id, &record.sudt_args);
+        asset.burns = asset.burns.saturating_sub(1);
+        asset.amount = asset.amount.saturating_sub(record.amount);
+        asset.sudt_amount = asset.sudt_amount.saturating_sub(record.sudt_amount);
+        let lock = asset.lock_mut(&code_hash);
+        lock.burns = lock.burns.saturating_sub(1);
+        lock.sudt_amount = lock.sudt_amount.saturating_sub(record.sudt_amount);
+
+        self.burn_epochs.insert(epoch, summary)
+    }
+
+    fn last_burn_batch(&self) -> ProtocolResult<Option<BurnBatch>> {
+        let len = self.burn_batch_len.get()?;
+        if len == 0 {
+            return Ok(None);
+        }
+        self.burn_batches.get(&len).map(Some)
+    }
+
+    /// Adds a burn to the open batch, opening one when batching is on. A
+    /// sealed batch not announced yet is announced first.
+    fn batch_burn(
+        &mut self,
+        ctx: &ServiceContext,
+        nonce: u64,
+        event_str: &str,
+    ) -> ProtocolResult<()> {
+        let mut open = None;
+        if let Some(mut batch) = self.last_burn_batch()? {
+            if !batch.sealed {
+                open = Some(batch);
+            } else if !batch.announced {
+                self.announce_burn_batch(ctx, &mut batch)?;
+            }
+        }
+
+        let mut batch = match open {
+            Some(batch) => batch,
+            None => {
+                let window = self.params()?.burn_batch_window;
+                if window == 0 {
+                    return Ok(());
+                }
+                self.burn_batch_len.add(1)?;
+                let height = ctx.get_current_height();
+                BurnBatch {
+                    batch:        self.burn_batch_len.get()?,
+                    start_height: height,
+                    end_height:   height.saturating_add(window - 1),
+                    first_nonce:  nonce,
+                    last_nonce:   nonce,
+                    leaves:       vec![],
+                    root:         Hash::from_empty(),
+                    sealed:       false,
+                    announced:    false,
+                }
+            }
+        };
+        batch.last_nonce = nonce;
+        batch.leaves.push(proof::ckb_blake2b(event_str.as_bytes())?);
+        batch.root = proof::cbmt_build_root(&batch.leaves)?;
+
+        self.burn_nonce_batches.insert(nonce, batch.batch)?;
+        self.burn_batches.insert(batch.batch, batch)
+    }
+
+    fn seal_burn_batch(&mut self, height: u64) -> ProtocolResult<()> {
+        match self.last_burn_batch()? {
+            Some(mut batch) if !batch.sealed && height >= batch.end_height => {
+                batch.sealed = true;
+                self.burn_batches.insert(batch.batch, batch)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn announce_burn_batch(
+        &mut self,
+        ctx: &ServiceContext,
+        batch: &mut BurnBatch,
+    ) -> ProtocolResult<()> {
+        batch.announced = true;
+        self.burn_batches.insert(batch.batch, batch.clone())?;
+
+        let event = BurnBatchEvent {
+            batch:       batch.batch,
+            end_height:  batch.end_height,
+            first_nonce: batch.first_nonce,
+            last_nonce:  batch.last_nonce,
+            burns:       batch.leaves.len() as u64,
+            root:        batch.root.clone(),
+            chain:       self.chain_binding()?,
+            topic:       "burn_batch".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn chain_binding(&self) -> ProtocolResult<Option<ChainBinding>> {
+        self.sdk.get_value(&CHAIN_BINDING_KEY.to_owned())
+    }
+
+    /// Checks a payload names the ckb network a bound bridge takes deposits
+    /// from. An unbound bridge takes any.
+    fn check_source_chain(&self, chain_id: &Option<Hash>) -> ProtocolResult<()> {
+        match self.chain_binding()? {
+            Some(binding) if chain_id.as_ref() != Some(&binding.source_chain_id) => {
+                Err(ServiceError::ChainMismatch {
+                    expected: binding.source_chain_id,
+                    found:    chain_id.clone(),
+                }
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn get_burn_record_by_nonce(&self, nonce: u64) -> ProtocolResult<BurnRecord> {
+        if !self.burn_records.contains(&nonce)? {
+            return Err(ServiceError::NotFoundBurnRecord { nonce }.into());
+        }
+
+        self.burn_records.get(&nonce)
+    }
+
+    fn get_mirror_asset(&self, sudt_args: &Hash) -> ProtocolResult<MirrorAsset> {
+        if !self.mirror_assets.contains(sudt_args)? {
+            return Err(ServiceError::UnregisteredSudt {
+                sudt_args: sudt_args.clone(),
+            }
+            .into());
+        }
+
+        self.mirror_assets.get(sudt_args)
+    }
+
+    fn get_mirror_asset_by_asset_id(&self, asset_id: &Hash) -> ProtocolResult<MirrorAsset> {
+        if !self.sudt_ids.contains(asset_id)? {
+            return Err(ServiceError::NotMirrorAsset {
+                asset_id: asset_id.clone(),
+            }
+            .into());
+        }
+
+        let sudt_args = self.sudt_ids.get(asset_id)?;
+        self.mirror_assets.get(&sudt_args)
+    }
+
+    /// Migrations of the stored state, by the schema version they lead to.
+    fn migrations() -> Vec<Migration<Self>> {
+        vec![
+            // burns made before epochs were kept stay out of the epoch
+            // summaries, cancelling one has no epoch to take it out of
+            Migration {
+                version: 1,
+                name:    "burn_epochs",
+                step:    Step::Lazy,
+            },
+        ]
+    }
+
+    // State from before schema versions were kept is at 0.
+    fn schema_version(&self) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_value(&SCHEMA_VERSION_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    fn migration_cursor(&self) -> ProtocolResult<u64> {
+        Ok(self
+            .sdk
+            .get_value(&MIGRATION_CURSOR_KEY.to_owned())?
+            .unwrap_or_default())
+    }
+
+    fn run_migrations(&mut self, limit: u64) -> ProtocolResult<(u64, u64)> {
+        let (version, cursor) = (self.schema_version()?, self.migration_cursor()?);
+        let (version, cursor) = migration::run(self, &Self::migrations(), version, cursor, limit)?;
+        self.sdk.set_value(SCHEMA_VERSION_KEY.to_owned(), version)?;
+        self.sdk
+            .set_value(MIGRATION_CURSOR_KEY.to_owned(), cursor)?;
+        Ok((version, cursor))
+    }
+
+    /// Whether the caller administers the bridge: holds the admin role in
+    /// the rbac service when the bridge defers to it, else is the admin.
+    fn verify_authority(&self, ctx: &ServiceContext) -> ProtocolResult<bool> {
+        if self.rbac_enabled()? {
+            return self.has_role(ctx, "admin");
+        }
+
+        let admin: Address = self
+            .sdk
+            .get_value(&ADMIN_KEY.to_owned())?
+            .expect("Admin should not be none");
+
+        Ok(ctx.get_caller() == admin)
+    }
+
+    /// Whether the caller may relay ckb blocks, by the relayer role in the
+    /// rbac service or the local relayer list.
+    fn is_relayer(&self, ctx: &ServiceContext) -> ProtocolResult<bool> {
+        if self.rbac_enabled()? {
+            return self.has_role(ctx, "relayer");
+        }
+        self.relayers.contains(&ctx.get_caller())
+    }
+
+    fn params(&self) -> ProtocolResult<Params> {
+        params::load(&self.sdk)
+    }
+
+    fn rbac_enabled(&self) -> ProtocolResult<bool> {
+        let enabled: Option<bool> = self.sdk.get_value(&RBAC_KEY.to_owned())?;
+        Ok(enabled.unwrap_or(false))
+    }
+
+    fn has_role(&self, ctx: &ServiceContext, role: &str) -> ProtocolResult<bool> {
+        let payload = HasRolePayload {
+            service: String::from_utf8_lossy(&SERVICE_NAME).into_owned(),
+            role:    role.to_owned(),
+            address: ctx.get_caller(),
+        };
+        let payload_str = serde_json::to_string(&payload).map_err(ServiceError::JsonParse)?;
+        let resp_str = self.sdk.read(ctx, None, "rbac", "has_role", &payload_str)?;
+        let resp: HasRoleResponse =
+            serde_json::from_str(&resp_str).map_err(ServiceError::JsonParse)?;
+
+        Ok(resp.has_role)
+    }
+
+    fn supply_cap(&self, asset_id: &Hash) -> ProtocolResult<SupplyCap> {
+        if self.supply_caps.contains(asset_id)? {
+            self.supply_caps.get(asset_id)
+        } else {
+            Ok(SupplyCap::default())
+        }
+    }
+
+    fn ensure_not_paused(&self, asset_id: &Hash) -> ProtocolResult<()> {
+        if self.supply_cap(asset_id)?.paused {
+            return Err(ServiceError::BridgePaused {
+                asset_id: asset_id.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Whether minting `source_amount` keeps the outstanding supply of the
+    /// asset within its cap. Crossing the cap pauses the asset, which then
+    /// admits nothing until the admin resumes it.
+    fn admit_mint(
+        &mut self,
+        ctx: &ServiceContext,
+        mirror_asset: &MirrorAsset,
+        source_amount: u128,
+    ) -> ProtocolResult<bool> {
+        let mut supply_cap = self.supply_cap(&mirror_asset.asset_id)?;
+        if supply_cap.paused {
+            return Ok(false);
+        }
+        let (amount, _) = mirror_asset.to_mirror_amount(source_amount);
+        let outstanding = self.bridge_stats()?.outstanding(&mirror_asset.asset_id);
+        if within_cap(supply_cap.cap, outstanding, amount) {
+            return Ok(true);
+        }
+
+        supply_cap.paused = true;
+        self.supply_caps
+            .insert(mirror_asset.asset_id.clone(), supply_cap.clone())?;
+
+        let event = BridgePausedEvent {
+            asset_id: mirror_asset.asset_id.clone(),
+            outstanding,
+            amount,
+            cap: supply_cap.cap,
+            topic: "bridge_paused".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)?;
+        Ok(false)
+    }
+
+    fn admit_eth_locks(
+        &mut self,
+        ctx: &ServiceContext,
+        locks: &[eth::Lock],
+    ) -> ProtocolResult<bool> {
+        for lock in locks.iter() {
+            let mirror_asset = self.eth_assets.get(&lock.token)?;
+            if !self.admit_mint(ctx, &mirror_asset, lock.amount)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn eth_locks_registered(&self, locks: &[eth::Lock]) -> ProtocolResult<bool> {
+        for lock in locks.iter() {
+            if !self.eth_assets.contains(&lock.token)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn admit_muta_locks(
+        &mut self,
+        ctx: &ServiceContext,
+        locks: &[muta::Lock],
+    ) -> ProtocolResult<bool> {
+        for lock in locks.iter() {
+            let mirror_asset = self.muta_assets.get(&lock.asset_id)?;
+            if !self.admit_mint(ctx, &mirror_asset, lock.amount)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn muta_locks_registered(&self, locks: &[muta::Lock]) -> ProtocolResult<bool> {
+        for lock in locks.iter() {
+            if !self.muta_assets.contains(&lock.asset_id)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn consume_burn_quota(
+        &mut self,
+        ctx: &ServiceContext,
+        asset_id: &Hash,
+        amount: u128,
+    ) -> ProtocolResult<()> {
+        let (quota, mut usage, used) = match self.burn_quota_usage(ctx, asset_id)? {
+            Some(usage) => usage,
+            None => return Ok(()),
+        };
+        if used.saturating_add(amount) > quota.quota {
+            return Err(ServiceError::BurnQuotaExceeded {
+                quota: quota.quota,
+                used,
+                real: amount,
+            }
+            .into());
+        }
+
+        usage.burns.push((ctx.get_current_height(), amount));
+        self.sdk
+            .set_account_value(&ctx.get_caller(), asset_id.clone(), usage)
+    }
+
+    // Moves the withdrawal fee of the asset from the caller to the treasury,
+    // unless the caller is waived.
+    fn charge_withdrawal_fee(
+        &mut self,
+        ctx: &ServiceContext,
+        asset_id: &Hash,
+    ) -> ProtocolResult<()> {
+        let fee = self.withdrawal_fee(asset_id)?;
+        let payer = ctx.get_caller();
+        if fee == 0 || self.fee_waived.contains(&payer)? {
+            return Ok(());
+        }
+        let config = self
+            .withdrawal_fee_config()?
+            .ok_or(ServiceError::WithdrawalFeeNotConfigured)?;
+
+        let transfer_payload = TransferCallAssetPayload {
+            asset_id: config.native_asset_id.clone(),
+            to:       config.treasury.clone(),
+            value:    fee,
+        };
+        let payload_string =
+            serde_json::to_string(&transfer_payload).map_err(ServiceError::JsonParse)?;
+        // the asset service moves funds of the account named in extra
+        self.sdk.write(
+            ctx,
+            Some(Bytes::from(payer.as_hex())),
+            "asset",
+            "transfer",
+            &payload_string,
+        )?;
+
+        let event = WithdrawalFeeEvent {
+            asset_id: asset_id.clone(),
+            payer,
+            native_asset_id: config.native_asset_id,
+            treasury: config.treasury,
+            fee,
+            topic: "withdrawal_fee".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn withdrawal_fee(&self, asset_id: &Hash) -> ProtocolResult<u128> {
+        if !self.withdrawal_fees.contains(asset_id)? {
+            return Ok(0);
+        }
+        Ok(self.withdrawal_fees.get(asset_id)?.0)
+    }
+
+    fn withdrawal_fee_config(&self) -> ProtocolResult<Option<WithdrawalFeeConfig>> {
+        Ok(self.params()?.withdrawal_fee_config)
+    }
+
+    /// The quota of the asset applying to the caller, with the caller's burns
+    /// inside its window and their sum. `None` when no quota applies.
+    fn burn_quota_usage(
+        &self,
+        ctx: &ServiceContext,
+        asset_id: &Hash,
+    ) -> ProtocolResult<Option<(BurnQuota, BurnQuotaUsage, u128)>> {
+        let caller = ctx.get_caller();
+        if !self.burn_quotas.contains(asset_id)? || self.quota_exempt.contains(&caller)? {
+            return Ok(None);
+        }
+        let quota = self.burn_quotas.get(asset_id)?;
+
+        // Only burns inside the rolling window count against the quota.
+        let height = ctx.get_current_height();
+        let mut usage: BurnQuotaUsage = self
+            .sdk
+            .get_account_value(&caller, asset_id)?
+            .unwrap_or(BurnQuotaUsage { burns: vec![] });
+        usage
+            .burns
+            .retain(|(burn_height, _)| burn_height + quota.window > height);
+
+        let used = usage
+            .burns
+            .iter()
+            .fold(0u128, |acc, (_, burned)| acc.saturating_add(*burned));
+        Ok(Some((quota, usage, used)))
+    }
+
+    /// Resolves the ckb tx carried by a message. The tx is hashed here from
+    /// exactly what was submitted, and both the tx and its witnesses are
+    /// checked against the stored header, so the deposit read out of them is
+    /// the one committed on ckb.
+    fn message_tx(
+        &self,
+        header: Option<&CkbHeaderInner>,
+        m: CkbMessage,
+    ) -> ProtocolResult<Result<MessageTx, MessageError>> {
+        let sudt_code_hash = self.params()?.sudt_code_hash;
+        let message_tx = match decode_message_tx(m.raw_tx, m.tx, &sudt_code_hash)? {
+            Ok(message_tx) => message_tx,
+            Err(error) => return Ok(Err(error)),
+        };
+        if self.effected_proofs.contains(&message_tx.tx_hash)? {
+            return Ok(Err(RejectReason::DuplicateTx.into()));
+        }
+        let tx_proof = match m.proof {
+            Some(tx_proof) => tx_proof,
+            None => return Ok(Err(RejectReason::InvalidProof.at("proof"))),
+        };
+        let header = match header {
+            Some(header) => header,
+            None => return Ok(Err(RejectReason::InvalidProof.into())),
+        };
+        if !self
+            .light_client
+            .verify_inclusion(header, &message_tx.tx_hash, &tx_proof)?
+        {
+            return Ok(Err(RejectReason::InvalidProof.at("proof.lemmas")));
+        }
+
+        if !self
+            .light_client
+            .verify_witnesses(&message_tx.witness_hash, &tx_proof)?
+        {
+            return Ok(Err(RejectReason::InvalidProof.at("proof.witnesses_root")));
+        }
+
+        Ok(Ok(message_tx))
+    }
+
+    fn stored_header(&self, height: u64) -> ProtocolResult<Option<CkbHeaderInner>> {
+        if !self.headers.contains(&height)? {
+            return Ok(None);
+        }
+        Ok(Some(self.headers.get(&height)?))
+    }
+
+    fn mmr_range(&self) -> ProtocolResult<MmrState> {
+        let range: Option<MmrState> = self.sdk.get_value(&MMR_KEY.to_owned())?;
+        Ok(range.unwrap_or_default())
+    }
+
+    /// The header of a message proven against the header range, `None` when
+    /// the header or its proof is invalid.
+    fn mmr_header(
+        &self,
+        raw: CkbHeader,
+        mmr_proof: &MmrProof,
+    ) -> ProtocolResult<Option<CkbHeaderInner>> {
+        let header = match CkbHeaderInner::from(raw) {
+            Ok(header) => header,
+            Err(_) => return Ok(None),
+        };
+        let range = self.mmr_range()?;
+        if header.number < range.start || !self.mmr_roots.contains(&mmr_proof.leaves)? {
+            return Ok(None);
+        }
+
+        let root = self.mmr_roots.get(&mmr_proof.leaves)?;
+        let leaf = proof::header_hash(&header)?;
+        if !mmr::verify(&root, &leaf, header.number - range.start, mmr_proof)? {
+            return Ok(None);
+        }
+        Ok(Some(header))
+    }
+
+    fn reject_message(
+        &mut self,
+        ctx: &ServiceContext,
+        error: impl Into<MessageError>,
+    ) -> ProtocolResult<()> {
+        let error = error.into();
+        let mut stats = self.bridge_stats()?;
+        *stats
+            .rejected_messages
+            .entry(error.reason.as_str().to_owned())
+            .or_insert(0) += 1;
+        self.set_bridge_stats(stats)?;
+
+        let event = MessageRejectedEvent {
+            reason: error.reason.as_str().to_owned(),
+            index:  error.index.map(|index| index as u64),
+            field:  error.field.map(str::to_owned),
+            topic:  "message_rejected".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn bridge_stats(&self) -> ProtocolResult<BridgeStats> {
+        let stats: Option<BridgeStats> = self.sdk.get_value(&BRIDGE_STATS_KEY.to_owned())?;
+        Ok(stats.unwrap_or_default())
+    }
+
+    fn set_bridge_stats(&mut self, stats: BridgeStats) -> ProtocolResult<()> {
+        self.sdk.set_value(BRIDGE_STATS_KEY.to_owned(), stats)
+    }
+
+    fn eth_config(&self) -> ProtocolResult<EthConfig> {
+        let config: Option<EthConfig> = self.sdk.get_value(&ETH_CONFIG_KEY.to_owned())?;
+        config.ok_or_else(|| ServiceError::EthNotConfigured.into())
+    }
+
+    /// Verifies and stores a header along with its submitted form, returns
+    /// its height.
+    fn store_header(
+        &mut self,
+        index: usize,
+        raw: CkbHeader,
+        stats: &mut BridgeStats,
+    ) -> ProtocolResult<StoredHeader> {
+        let header = CkbHeaderInner::from(raw.clone())
+            .map_err(|e| ServiceError::InvalidHeaderField { index, error: e })?;
+        let height = self.light_client.height(&header);
+        let parent = if height > 0 {
+            self.stored_header(height - 1)?
+        } else {
+            None
+        };
+        self.light_client.verify_header(&header, parent.as_ref())?;
+
+        // A different header at a stored height is recorded, not stored over
+        // the first, the first conflict of a height standing until resolved.
+        if let Some(stored) = self.stored_header(height)? {
+            let stored_hash = proof::header_hash(&stored)?;
+            let conflicting_hash = proof::header_hash(&header)?;
+            if stored_hash != conflicting_hash {
+                let conflict = HeaderConflict {
+                    height,
+                    stored_hash,
+                    conflicting_hash,
+                    header: raw,
+                };
+                if !self.header_conflicts.contains(&height)? {
+                    self.header_conflicts.insert(height, conflict.clone())?;
+                }
+                return Ok(StoredHeader::Conflict(conflict));
+            }
+        }
+
+        self.put_header(header, raw, stats)?;
+        Ok(StoredHeader::Stored(height))
+    }
+
+    fn put_header(
+        &mut self,
+        header: CkbHeaderInner,
+        raw: CkbHeader,
+        stats: &mut BridgeStats,
+    ) -> ProtocolResult<()> {
+        let height = self.light_client.height(&header);
+        if !self.headers.contains(&height)? {
+            stats.headers_stored += 1;
+        }
+        self.headers.insert(height, header)?;
+        self.raw_headers.insert(height, raw)?;
+
+        let bounds = match self.header_bounds()? {
+            Some(bounds) => HeaderBounds {
+                lowest:  bounds.lowest.min(height),
+                highest: bounds.highest.max(height),
+            },
+            None => HeaderBounds {
+                lowest:  height,
+                highest: height,
+            },
+        };
+        self.sdk.set_value(HEADER_BOUNDS_KEY.to_owned(), bounds)
+    }
+
+    // Checks the headers of a fork branch from `height` follow each other,
+    // and the stored header below when there is one, by number and parent
+    // hash.
+    fn verify_branch(
+        &self,
+        height: u64,
+        branch: &[(CkbHeaderInner, CkbHeader)],
+    ) -> ProtocolResult<()> {
+        let mut parent = if height > 0 {
+            self.stored_header(height - 1)?
+        } else {
+            None
+        };
+        for (offset, (header, _)) in branch.iter().enumerate() {
+            let linked = match parent.as_ref() {
+                Some(parent) => header.parent_hash == proof::header_hash(parent)?,
+                None => true,
+            };
+            if !linked || header.number != height + offset as u64 {
+                return Err(ServiceError::InvalidForkBranch { height }.into());
+            }
+            self.light_client.verify_header(header, parent.as_ref())?;
+            parent = Some(header.clone());
+        }
+        Ok(())
+    }
+
+    // Headers stored from `height` on, each the child of the one before,
+    // counted up to `max`.
+    fn stored_branch_len(&self, height: u64, max: u64) -> ProtocolResult<u64> {
+        let mut len = 0;
+        let mut parent: Option<CkbHeaderInner> = None;
+        while len < max {
+            let header = match self.stored_header(height + len)? {
+                Some(header) => header,
+                None => break,
+            };
+            if let Some(parent) = parent.as_ref() {
+                if header.parent_hash != proof::header_hash(parent)? {
+                    break;
+                }
+            }
+            parent = Some(header);
+            len += 1;
+        }
+        Ok(len)
+    }
+
+    fn message_ttl(&self) -> ProtocolResult<u64> {
+        Ok(self.params()?.message_ttl)
+    }
+
+    /// Whether deposits of the block at `height` are too old to submit,
+    /// going by the highest header stored or appended to the header range.
+    fn is_expired(&self, height: u64) -> ProtocolResult<bool> {
+        let ttl = self.message_ttl()?;
+        if ttl == 0 {
+            return Ok(false);
+        }
+
+        let stored_tip = self.header_bounds()?.map(|bounds| bounds.highest);
+        let mmr_tip: Option<CkbHeaderInner> = self.sdk.get_value(&MMR_TIP_KEY.to_owned())?;
+        let tip = stored_tip.max(mmr_tip.map(|tip| tip.number));
+        Ok(tip.map_or(false, |tip| expired(height, tip, ttl)))
+    }
+
+    fn bridge_limits(&self) -> ProtocolResult<BridgeLimits> {
+        Ok(self.params()?.bridge_limits)
+    }
+
+    fn fee_beneficiary(&self) -> ProtocolResult<FeeBeneficiary> {
+        Ok(self.params()?.fee_beneficiary)
+    }
+
+    fn payout_address(&self, relayer: &Address) -> ProtocolResult<Address> {
+        if !self.payout_addresses.contains(relayer)? {
+            return Ok(relayer.clone());
+        }
+        Ok(self.payout_addresses.get(relayer)?.payout)
+    }
+
+    fn fee_schedule(&self) -> ProtocolResult<FeeSchedule> {
+        Ok(self.params()?.fee_schedule)
+    }
+
+    fn check_batch_size(&self, len: usize) -> ProtocolResult<()> {
+        let max = self.bridge_limits()?.max_batch_messages;
+        if max > 0 && len > max as usize {
+            return Err(ServiceError::BatchTooLarge { len, max }.into());
+        }
+        Ok(())
+    }
+
+    /// Adds an event about to be emitted to the digest of the current block.
+    fn digest_event(
+        &mut self,
+        ctx: &ServiceContext,
+        kind: BridgeEvent,
+        event_str: &str,
+    ) -> ProtocolResult<()> {
+        let height = ctx.get_current_height();
+        let block: Option<BlockEventHashes> = self.sdk.get_value(&BLOCK_EVENTS_KEY.to_owned())?;
+        let mut block = block
+            .filter(|block| block.height == height)
+            .unwrap_or(BlockEventHashes {
+                height,
+                hashes: vec![],
+            });
+        block.hashes.push(proof::ckb_blake2b(event_str.as_bytes())?);
+        let root = proof::cbmt_build_root(&block.hashes)?;
+
+        let mut digest = if self.event_digests.contains(&height)? {
+            self.event_digests.get(&height)?
+        } else {
+            EventDigest {
+                height,
+                mints: 0,
+                burns: 0,
+                root: root.clone(),
+            }
+        };
+        match kind {
+            BridgeEvent::Mint => digest.mints += 1,
+            BridgeEvent::Burn => digest.burns += 1,
+        }
+        digest.root = root;
+
+        self.event_digests.insert(height, digest)?;
+        self.sdk.set_value(BLOCK_EVENTS_KEY.to_owned(), block)
+    }
+
+    /// Adds a message to ckb about to be announced to the outbound root of
+    /// its block.
+    fn accumulate_outbound(
+        &mut self,
+        height: u64,
+        nonce: u64,
+        event_str: &str,
+    ) -> ProtocolResult<()> {
+        let mut block = if self.outbound_blocks.contains(&height)? {
+            self.outbound_blocks.get(&height)?
+        } else {
+            OutboundBlock {
+                height,
+                first_nonce: nonce,
+                last_nonce: nonce,
+                leaves: vec![],
+                root: Hash::from_empty(),
+            }
+        };
+        block.last_nonce = nonce;
+        block.leaves.push(proof::ckb_blake2b(event_str.as_bytes())?);
+        block.root = proof::cbmt_build_root(&block.leaves)?;
+        self.outbound_blocks.insert(height, block)
+    }
+
+    // Like asset's minters, a service names itself in the extra of
+    // `sdk.write`. The name isn't verified, any deployed service can claim
+    // to be an outbound sender.
+    fn verify_outbound_sender(&self, ctx: &ServiceContext) -> ProtocolResult<String> {
+        let extra = ctx.get_extra().ok_or(ServiceError::NonAuthorized)?;
+        let service = String::from_utf8(extra.to_vec()).map_err(|_| ServiceError::NonAuthorized)?;
+
+        if !self.outbound_senders.contains(&service)? {
+            return Err(ServiceError::NotOutboundSender { service }.into());
+        }
+        Ok(service)
+    }
+
+    /// What the bridge minted so far in the current block.
+    fn block_mint_usage(&self, ctx: &ServiceContext) -> ProtocolResult<BlockMintUsage> {
+        let height = ctx.get_current_height();
+        let usage: Option<BlockMintUsage> = self.sdk.get_value(&BLOCK_MINT_USAGE_KEY.to_owned())?;
+        Ok(usage
+            .filter(|usage| usage.height == height)
+            .unwrap_or(BlockMintUsage { height, minted: 0 }))
+    }
+
+    /// Whether minting `amount` more keeps the block within its budget.
+    fn block_budget_fits(&self, ctx: &ServiceContext, amount: u128) -> ProtocolResult<bool> {
+        let budget = self.bridge_limits()?.block_mint_budget;
+        Ok(budget == 0 || self.block_mint_usage(ctx)?.minted.saturating_add(amount) <= budget)
+    }
+
+    /// Counts a mint against the block budget, failing when the mint would
+    /// take the block over it, so no block mints more than its budget.
+    fn spend_block_budget(&mut self, ctx: &ServiceContext, amount: u128) -> ProtocolResult<()> {
+        let budget = self.bridge_limits()?.block_mint_budget;
+        if budget == 0 {
+            return Ok(());
+        }
+        let mut usage = self.block_mint_usage(ctx)?;
+        if usage.minted.saturating_add(amount) > budget {
+            return Err(ServiceError::BudgetExceeded {
+                height: usage.height,
+                budget,
+            }
+            .into());
+        }
+        usage.minted = usage.minted.saturating_add(amount);
+        self.sdk.set_value(BLOCK_MINT_USAGE_KEY.to_owned(), usage)
+    }
+
+    fn header_bounds(&self) -> ProtocolResult<Option<HeaderBounds>> {
+        self.sdk.get_value(&HEADER_BOUNDS_KEY.to_owned())
+    }
+
+    fn is_header_relayer(&self, ctx: &ServiceContext) -> ProtocolResult<bool> {
+        let relayer: Option<Address> = self.sdk.get_value(&HEADER_RELAYER_KEY.to_owned())?;
+        Ok(relayer.map_or(false, |relayer| relayer == ctx.get_caller()))
+    }
+
+    /// The configured lock whitelist, else the secp256k1 single and multisig
+    /// locks every ckb wallet uses.
+    fn supported_locks(&self) -> ProtocolResult<SupportedLocks> {
+        let locks: Option<SupportedLocks> = self.sdk.get_value(&SUPPORTED_LOCKS_KEY.to_owned())?;
+        Ok(locks.unwrap_or_else(|| SupportedLocks {
+            code_hashes: vec![
+                Hash::from_hex(address::SECP256K1_BLAKE160_CODE_HASH).expect("valid code hash"),
+                Hash::from_hex(address::SECP256K1_MULTISIG_CODE_HASH).expect("valid code hash"),
+            ],
+        }))
+    }
+
+    fn mirror_asset_issuer(&self) -> ProtocolResult<Address> {
+        Ok(self.params()?.mirror_asset_issuer)
+    }
+
+    fn muta_config(&self) -> ProtocolResult<MutaConfig> {
+        let config: Option<MutaConfig> = self.sdk.get_value(&MUTA_CONFIG_KEY.to_owned())?;
+        config.ok_or_else(|| ServiceError::MutaNotConfigured.into())
+    }
+
+    fn seed_muta_validator_set(&mut self, validator_set: MutaValidatorSet) -> ProtocolResult<()> {
+        if validator_set.validators.is_empty() {
+            return Err(ServiceError::InvalidMutaValidatorSet.into());
+        }
+        self.muta_epoch.set(0)?;
+        self.muta_validator_sets.insert(0, validator_set)
+    }
+
+    fn current_muta_validator_set(&self) -> ProtocolResult<MutaValidatorSet> {
+        let epoch = self.muta_epoch.get()?;
+        if !self.muta_validator_sets.contains(&epoch)? {
+            return Err(ServiceError::MutaNotConfigured.into());
+        }
+        self.muta_validator_sets.get(&epoch)
+    }
+
+    /// The validator set signing the counterpart block at `height`, found by
+    /// walking back from the current epoch.
+    fn muta_validator_set_at(&self, height: u64) -> ProtocolResult<MutaValidatorSet> {
+        let mut epoch = self.muta_epoch.get()?;
+        loop {
+            if !self.muta_validator_sets.contains(&epoch)? {
+                return Err(ServiceError::MutaNotConfigured.into());
+            }
+            let validator_set = self.muta_validator_sets.get(&epoch)?;
+            if validator_set.start_height <= height {
+                return Ok(validator_set);
+            }
+            if epoch == 0 {
+                return Err(ServiceError::InvalidMutaHeader { height }.into());
+            }
+            epoch -= 1;
+        }
+    }
+
+    /// The header at `height` once its stored child carries its BFT proof.
+    fn confirmed_muta_header(&self, height: u64) -> ProtocolResult<Option<MutaHeader>> {
+        if !self.muta_headers.contains(&height)? || !self.muta_headers.contains(&(height + 1))? {
+            return Ok(None);
+        }
+        let header = self.muta_headers.get(&height)?;
+        let child = self.muta_headers.get(&(height + 1))?;
+        Ok(if child.prev_hash == header.hash {
+            Some(header)
+        } else {
+            None
+        })
+    }
+}
+
+/// The tx of a message, `raw_tx` taking precedence over `tx`. A serialized
+/// tx is read in place, without building a `CkbTx`. Its deposit must be of
+/// the sudt of `sudt_code_hash`.
+fn decode_message_tx(
+    raw_tx: Option<Hex>,
+    tx: Option<CkbTx>,
+    sudt_code_hash: &Hash,
+) -> ProtocolResult<Result<MessageTx, MessageError>> {
+    let message_tx = match (raw_tx, tx) {
+        (Some(raw_tx), _) => {
+            let data = match hex::decode(raw_tx.as_string_trim0x()) {
+                Ok(data) => data,
+                Err(_) => return Ok(Err(RejectReason::InvalidTx.at("raw_tx"))),
+            };
+            let view = match molecule::TxView::parse(&data) {
+                Ok(view) => view,
+                Err(_) => return Ok(Err(RejectReason::InvalidTx.at("raw_tx"))),
+            };
+            MessageTx {
+                tx_hash:      proof::ckb_blake2b(view.raw())?,
+                witness_hash: proof::ckb_blake2b(&data)?,
+                deposit:      deposit::parse_raw_deposit_of(&view, sudt_code_hash),
+                call:         call::parse_raw_call(&view),
+            }
+        }
+        (None, Some(tx)) => {
+            let encoded = molecule::encode_raw_transaction(&tx)
+                .and_then(|raw| Ok((raw, molecule::encode_transaction(&tx)?)));
+            let (raw, full) = match encoded {
+                Ok(encoded) => encoded,
+                Err(_) => return Ok(Err(RejectReason::InvalidTx.at("tx"))),
+            };
+            MessageTx {
+                tx_hash:      proof::ckb_blake2b(&raw)?,
+                witness_hash: proof::ckb_blake2b(&full)?,
+                deposit:      deposit::parse_deposit_of(&tx, sudt_code_hash),
+                call:         call::parse_call(&tx),
+            }
+        }
+        (None, None) => return Ok(Err(RejectReason::InvalidTx.at("tx"))),
+    };
+
+    Ok(Ok(message_tx))
+}
+
+/// A serialized ckb tx and its hash, `None` when it can't be read.
+fn decode_burn_tx(raw_tx: &Hex) -> Option<(CkbTx, Hash)> {
+    let data = hex::decode(raw_tx.as_string_trim0x()).ok()?;
+    let (tx, raw) = molecule::decode_transaction(&data).ok()?;
+    let tx_hash = proof::ckb_blake2b(raw).ok()?;
+    Some((tx, tx_hash))
+}
+
+/// Whether an output of `tx` unlocks the sudt amount of a burn to its
+/// receiver, a cell of the burn's sudt under the receiver's lock. Both locks
+/// spell their args in lowercase hex, so they compare as scripts.
+fn pays_burn(tx: &CkbTx, record: &BurnRecord, sudt_code_hash: &Hash) -> bool {
+    let lock = match CkbAddress::parse(&record.ckb_receiver) {
+        Ok(address) => address.lock,
+        Err(_) => return false,
+    };
+    let sudt_args = record.sudt_args.as_bytes();
+
+    tx.outputs
+        .iter()
+        .zip(tx.outputs_data.iter())
+        .any(|(output, data)| {
+            let sudt = match &output.type_ {
+                Some(sudt) => sudt,
+                None => return false,
+            };
+            let args = hex::decode(sudt.args.as_string_trim0x()).ok();
+            let amount = hex::decode(data.as_string_trim0x())
+                .ok()
+                .and_then(|data| Amount::from_sudt_data(&data));
+
+            output.lock == lock
+                && sudt.code_hash == *sudt_code_hash
+                && args.as_deref() == Some(sudt_args.as_ref())
+                && amount == Some(Amount(record.sudt_amount))
+        })
+}
+
+/// Ranges of heights from `from` to `to` that `stored` says are missing, at
+/// most `limit` of them and scanning at most `max_scan` heights. Returns the
+/// height to resume from if the scan stopped before `to`; a gap cut by the
+/// scan bound is reported up to where the scan stopped.
+pub fn header_gaps<F>(
+    from: u64,
+    to: u64,
+    limit: usize,
+    max_scan: u64,
+    mut stored: F,
+) -> ProtocolResult<(Vec<HeightRange>, Option<u64>)>
+where
+    F: FnMut(u64) -> ProtocolResult<bool>,
+{
+    let mut ranges = vec![];
+    if from > to || limit == 0 || max_scan == 0 {
+        return Ok((ranges, None));
+    }
+
+    let scan_end = to.min(from.saturating_add(max_scan - 1));
+    let mut gap_start = None;
+    for height in from..=scan_end {
+        if !stored(height)? {
+            gap_start = gap_start.or(Some(height));
+            continue;
+        }
+        if let Some(start) = gap_start.take() {
+            ranges.push(HeightRange {
+                start,
+                end: height - 1,
+            });
+            if ranges.len() == limit {
+                return Ok((ranges, Some(height).filter(|h| *h < to)));
+            }
+        }
+    }
+
+    if let Some(start) = gap_start {
+        ranges.push(HeightRange {
+            start,
+            end: scan_end,
+        });
+    }
+    let next = if scan_end < to {
+        Some(scan_end + 1)
+    } else {
+        None
+    };
+    Ok((ranges, next))
+}
+
+/// Whether a block at `height` lies more than `ttl` blocks behind `tip`.
+pub fn expired(height: u64, tip: u64, ttl: u64) -> bool {
+    tip.saturating_sub(height) > ttl
+}
+
+fn within_cap(cap: u128, outstanding: u128, amount: u128) -> bool {
+    cap == 0 || outstanding.checked_add(amount).map_or(false, |s| s <= cap)
+}
+
+/// Muta asset id of the mirror asset bridged from the erc20 token with the
+/// given key.
+pub fn eth_mirror_asset_id(token: &Hash) -> Hash {
+    Hash::digest(Bytes::from(
+        ETH_MIRROR_ASSET_ID_PREFIX.to_owned() + &token.as_hex(),
+    ))
+}
+
+/// Muta asset id of the mirror asset bridged from the counterpart muta asset
+/// with the given id.
+pub fn muta_mirror_asset_id(origin_asset_id: &Hash) -> Hash {
+    Hash::digest(Bytes::from(
+        MUTA_MIRROR_ASSET_ID_PREFIX.to_owned() + &origin_asset_id.as_hex(),
+    ))
+}
+
+/// Replay protection key of a receipt, unique per block and tx.
+fn eth_receipt_id(block_hash: &Hash, tx_index: u64) -> Hash {
+    let mut data = block_hash.as_bytes().to_vec();
+    data.extend_from_slice(&tx_index.to_be_bytes());
+    Hash::digest(Bytes::from(data))
+}
+
+fn parse_eth_address(address: &Hex) -> Result<Vec<u8>, ServiceError> {
+    match hex::decode(address.as_string_trim0x()) {
+        Ok(bytes) if bytes.len() == eth::ETH_ADDRESS_LEN => Ok(bytes),
+        _ => Err(ServiceError::InvalidEthAddress),
+    }
+}
+
+/// Muta asset id of the mirror asset bridged from the SUDT with the given type
+/// args.
+pub fn mirror_asset_id(sudt_args: &Hash) -> Hash {
+    Hash::digest(Bytes::from(
+        MIRROR_ASSET_ID_PREFIX.to_owned() + &sudt_args.as_hex(),
+    ))
+}
+
+#[derive(Debug, Display, From)]
+pub enum ServiceError {
+    #[display(fmt = "Parsing payload to json failed {:?}", _0)]
+    JsonParse(serde_json::Error),
+
+    InvalidCrossTx,
+
+    InvalidCrossHeader,
+
+    NonAuthorized,
+
+    InvalidScaleFactor,
+
+    U128Overflow,
+
+    U128Underflow,
+
+    #[display(fmt = "Mirror asset of sudt {:?} already exists", sudt_args)]
+    MirrorAssetExists {
+        sudt_args: Hash,
+    },
+
+    #[display(fmt = "Sudt {:?} is not registered as a mirror asset", sudt_args)]
+    UnregisteredSudt {
+        sudt_args: Hash,
+    },
+
+    #[display(fmt = "Asset {:?} is not a mirror asset", asset_id)]
+    NotMirrorAsset {
+        asset_id: Hash,
+    },
+
+    #[display(fmt = "Not found burn record, nonce {:?}", nonce)]
+    NotFoundBurnRecord {
+        nonce: u64,
+    },
+
+    #[display(fmt = "Burn record {:?} is not pending", nonce)]
+    BurnNotPending {
+        nonce: u64,
+    },
+
+    #[display(fmt = "Ckb tx does not pay out burn {:?}", nonce)]
+    BurnNotPaid {
+        nonce: u64,
+    },
+
+    #[display(fmt = "Epoch of ckb header {:?} is malformed or discontinuous", height)]
+    InvalidEpoch {
+        height: u64,
+    },
+
+    #[display(fmt = "Ethereum header {:?} failed verification", height)]
+    InvalidEthHeader {
+        height: u64,
+    },
+
+    EthNotConfigured,
+
+    InvalidEthAddress,
+
+    #[display(fmt = "Header {} has an {}", index, error)]
+    InvalidHeaderField {
+        index: usize,
+        error: FieldError,
+    },
+
+    #[display(fmt = "Forced message rejected: {}", reason)]
+    ForcedMessageRejected {
+        reason: String,
+    },
+
+    #[display(fmt = "Burn receiver lock {:?} is not supported", code_hash)]
+    UnsupportedReceiverLock {
+        code_hash: Hash,
+    },
+
+    #[display(fmt = "Invalid ckb address: {}", _0)]
+    InvalidCkbAddress(AddressError),
+
+    #[display(fmt = "Muta header {:?} failed verification", height)]
+    InvalidMutaHeader {
+        height: u64,
+    },
+
+    MutaNotConfigured,
+
+    InvalidMutaValidatorSet,
+
+    #[display(fmt = "Muta validator update to version {:?} is invalid", version)]
+    InvalidValidatorUpdate {
+        version: u64,
+    },
+
+    #[display(fmt = "Not found ckb header, height {:?}", height)]
+    NotFoundHeader {
+        height: u64,
+    },
+
+    #[display(fmt = "No schema for type {}", type_name)]
+    UnknownSchema {
+        type_name: String,
+    },
+
+    #[display(fmt = "No read method {}", method)]
+    UnknownReadMethod {
+        method: String,
+    },
+
+    #[display(fmt = "No call target registered for service {}", service)]
+    NoCallTarget {
+        service: String,
+    },
+
+    #[display(fmt = "Service {} can't be a call target", service)]
+    ReservedCallTarget {
+        service: String,
+    },
+
+    #[display(fmt = "No mint callback registered for {:?}", address)]
+    NoMintCallback {
+        address: Address,
+    },
+
+    #[display(fmt = "Ckb tx {:?} has no deposit awaiting reassignment", tx_hash)]
+    NotRecoveredDeposit {
+        tx_hash: Hash,
+    },
+
+    #[display(fmt = "Header {} does not follow the header range", height)]
+    MmrNotContinuous {
+        height: u64,
+    },
+
+    MmrRootMismatch,
+
+    #[display(fmt = "No deposit processed from ckb tx {:?}", tx_hash)]
+    NotFoundDeposit {
+        tx_hash: Hash,
+    },
+
+    #[display(fmt = "Too many messages pending for ckb height {:?}", height)]
+    PendingQueueFull {
+        height: u64,
+    },
+
+    #[display(fmt = "Over {} headers staged", max)]
+    StagedHeadersFull {
+        max: u64,
+    },
+
+    #[display(fmt = "Not found header conflict, height {}", height)]
+    NotFoundHeaderConflict {
+        height: u64,
+    },
+
+    #[display(fmt = "Fork branch at height {} does not link up", height)]
+    InvalidForkBranch {
+        height: u64,
+    },
+
+    #[display(fmt = "A fork branch holds at most {} headers", max)]
+    ForkBranchTooLong {
+        max: usize,
+    },
+
+    #[display(fmt = "Branches at height {} are as long", height)]
+    UndecidedFork {
+        height: u64,
+    },
+
+    #[display(fmt = "Proof of ckb tx {:?} has already been used", tx_hash)]
+    ProofAlreadyUsed {
+        tx_hash: Hash,
+    },
+
+    InvalidProof,
+
+    #[display(fmt = "Withdrawal fees need a native asset and treasury set first")]
+    WithdrawalFeeNotConfigured,
+
+    #[display(
+        fmt = "Burn quota exceeded, quota {:?} used {:?} real {:?}",
+        quota,
+        used,
+        real
+    )]
+    BurnQuotaExceeded {
+        quota: u128,
+        used:  u128,
+        real:  u128,
+    },
+
+    #[display(fmt = "Minimum burn amount not met, min {:?} real {:?}", min, real)]
+    MinimumBurnNotMet {
+        min:  u128,
+        real: u128,
+    },
+
+    #[display(fmt = "Bridge of asset {:?} is paused", asset_id)]
+    BridgePaused {
+        asset_id: Hash,
+    },
+
+    #[display(fmt = "Bridged supply of asset {:?} over its cap {:?}", asset_id, cap)]
+    SupplyCapExceeded {
+        asset_id: Hash,
+        cap:      u128,
+    },
+
+    #[display(fmt = "Batch of {} messages over the limit of {}", len, max)]
+    BatchTooLarge {
+        len: usize,
+        max: u32,
+    },
+
+    #[display(fmt = "Parameter {} is out of range", name)]
+    InvalidParam {
+        name: String,
+    },
+
+    #[display(fmt = "Burn {} is in a burn batch and can't be cancelled", nonce)]
+    BurnBatched {
+        nonce: u64,
+    },
+
+    #[display(
+        fmt = "Payload of chain {:?} sent to a bridge of chain {:?}",
+        found,
+        expected
+    )]
+    ChainMismatch {
+        expected: Hash,
+        found:    Option<Hash>,
+    },
+
+    #[display(fmt = "Deposit tag {} is registered to {:?}", tag, owner)]
+    DepositTagTaken {
+        tag:   u64,
+        owner: Address,
+    },
+
+    #[display(fmt = "Not found burn batch {}", batch)]
+    NotFoundBurnBatch {
+        batch: u64,
+    },
+
+    #[display(fmt = "Service {} may not send messages to ckb", service)]
+    NotOutboundSender {
+        service: String,
+    },
+
+    #[display(fmt = "Message payload of {} bytes over the limit of {}", len, max)]
+    OutboundPayloadTooLarge {
+        len: usize,
+        max: usize,
+    },
+
+    #[display(fmt = "Not found outbound message {}", nonce)]
+    NotFoundOutboundMessage {
+        nonce: u64,
+    },
+
+    #[display(fmt = "Payout address can't change before block {}", until)]
+    PayoutCooldown {
+        until: u64,
+    },
+
+    #[display(fmt = "Not found outbound block {}", height)]
+    NotFoundOutboundBlock {
+        height: u64,
+    },
+
+    #[display(fmt = "Block {} minted its bridge budget of {}", height, budget)]
+    BudgetExceeded {
+        height: u64,
+        budget: u128,
+    },
 }
 
 impl std::error::Error for ServiceError {}
@@ -0,0 +1,105 @@
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+use crate::light_client::LightClient;
+use crate::proof;
+use crate::types::{CkbHeaderInner, CkbMultiProof, CkbTxProof};
+use crate::ServiceError;
+
+pub struct CkbLightClient;
+
+impl LightClient for CkbLightClient {
+    type Header = CkbHeaderInner;
+    type Leaf = Hash;
+    type Proof = CkbTxProof;
+
+    fn height(&self, header: &CkbHeaderInner) -> u64 {
+        header.number
+    }
+
+    fn verify_header(
+        &self,
+        header: &CkbHeaderInner,
+        parent: Option<&CkbHeaderInner>,
+    ) -> ProtocolResult<()> {
+        let height = header.number;
+        let epoch = header.epoch_view();
+        if !epoch.is_valid() {
+            return Err(ServiceError::InvalidEpoch { height }.into());
+        }
+        if let Some(parent) = parent {
+            if !epoch.is_successor_of(&parent.epoch_view()) {
+                return Err(ServiceError::InvalidEpoch { height }.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_inclusion(
+        &self,
+        header: &CkbHeaderInner,
+        tx_hash: &Hash,
+        tx_proof: &CkbTxProof,
+    ) -> ProtocolResult<bool> {
+        let transactions_root = proof::transactions_root(
+            tx_hash,
+            tx_proof.index,
+            &tx_proof.lemmas,
+            &tx_proof.witnesses_root,
+        )?;
+
+        Ok(transactions_root == header.transactions_root)
+    }
+}
+
+impl CkbLightClient {
+    /// Ckb commits to witnesses in a tree of their own, whose root is part of
+    /// the tx proof. Only meaningful once the proof passed `verify_inclusion`.
+    pub fn verify_witnesses(
+        &self,
+        witness_hash: &Hash,
+        tx_proof: &CkbTxProof,
+    ) -> ProtocolResult<bool> {
+        let witness_lemmas = match &tx_proof.witness_lemmas {
+            Some(witness_lemmas) => witness_lemmas,
+            None => return Ok(false),
+        };
+        let witnesses_root = proof::cbmt_root(witness_hash, tx_proof.index, witness_lemmas)?;
+
+        Ok(witnesses_root == tx_proof.witnesses_root)
+    }
+
+    /// Verifies txs of the same block, and their witnesses, against a single
+    /// proof. `txs` holds the hash and witness hash of each tx, in the order
+    /// of the proof indices.
+    pub fn verify_batch(
+        &self,
+        header: &CkbHeaderInner,
+        txs: &[(Hash, Hash)],
+        multi_proof: &CkbMultiProof,
+    ) -> ProtocolResult<bool> {
+        if txs.len() != multi_proof.indices.len() {
+            return Ok(false);
+        }
+        let mut tx_leaves = Vec::with_capacity(txs.len());
+        let mut witness_leaves = Vec::with_capacity(txs.len());
+        for (index, (tx_hash, witness_hash)) in multi_proof.indices.iter().zip(txs.iter()) {
+            tx_leaves.push((*index, tx_hash.clone()));
+            witness_leaves.push((*index, witness_hash.clone()));
+        }
+
+        let raw_transactions_root = match proof::cbmt_multi_root(&tx_leaves, &multi_proof.lemmas)? {
+            Some(root) => root,
+            None => return Ok(false),
+        };
+        if proof::merge(&raw_transactions_root, &multi_proof.witnesses_root)?
+            != header.transactions_root
+        {
+            return Ok(false);
+        }
+
+        let witnesses_root = proof::cbmt_multi_root(&witness_leaves, &multi_proof.witness_lemmas)?;
+        Ok(witnesses_root.as_ref() == Some(&multi_proof.witnesses_root))
+    }
+}
@@ -0,0 +1,64 @@
+use protocol::ProtocolResult;
+
+use crate::eth;
+use crate::light_client::LightClient;
+use crate::types::{EthHeader, EthReceiptProof, EthSyncMode};
+use crate::ServiceError;
+
+/// Ethereum headers are linked by parent hash. Neither ethash seals nor sync
+/// committee signatures are checked on chain yet, so the mode only decides
+/// which difficulty a header must have, and the service only accepts headers
+/// from relayers.
+pub struct EthLightClient {
+    pub mode: EthSyncMode,
+}
+
+impl LightClient for EthLightClient {
+    type Header = EthHeader;
+    type Leaf = [u8];
+    type Proof = EthReceiptProof;
+
+    fn height(&self, header: &EthHeader) -> u64 {
+        header.number
+    }
+
+    fn verify_header(&self, header: &EthHeader, parent: Option<&EthHeader>) -> ProtocolResult<()> {
+        let height = header.number;
+        let difficulty_ok = match self.mode {
+            EthSyncMode::Pow => header.difficulty > 0,
+            EthSyncMode::SyncCommittee => header.difficulty == 0,
+        };
+        if !difficulty_ok {
+            return Err(ServiceError::InvalidEthHeader { height }.into());
+        }
+        if let Some(parent) = parent {
+            if header.parent_hash != parent.hash {
+                return Err(ServiceError::InvalidEthHeader { height }.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_inclusion(
+        &self,
+        header: &EthHeader,
+        receipt: &[u8],
+        proof: &EthReceiptProof,
+    ) -> ProtocolResult<bool> {
+        let mut nodes = Vec::with_capacity(proof.nodes.len());
+        for node in proof.nodes.iter() {
+            match hex::decode(node.as_string_trim0x()) {
+                Ok(node) => nodes.push(node),
+                Err(_) => return Ok(false),
+            }
+        }
+
+        // Receipts are keyed by the rlp encoded tx index.
+        let key = rlp::encode(&proof.tx_index);
+        match eth::verify_proof(&header.receipts_root, &key, &nodes) {
+            Ok(Some(value)) => Ok(value.as_slice() == receipt),
+            _ => Ok(false),
+        }
+    }
+}
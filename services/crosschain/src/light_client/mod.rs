@@ -0,0 +1,40 @@
+//! Source chain light clients. The service stores headers and runs the
+//! message and mint pipeline, a light client only decides which headers and
+//! inclusion proofs are valid, so other chains can be supported by adding a
+//! backend here.
+
+mod ckb;
+mod eth;
+mod muta;
+
+pub use ckb::CkbLightClient;
+pub use eth::EthLightClient;
+pub use muta::MutaLightClient;
+
+use protocol::ProtocolResult;
+
+pub trait LightClient {
+    type Header;
+    // what a proof commits to, a tx hash or the encoded receipt
+    type Leaf: ?Sized;
+    type Proof;
+
+    /// Height of the header on its chain, the key it is stored under.
+    fn height(&self, header: &Self::Header) -> u64;
+
+    /// Checks a header on its own and, when its parent is stored, that it can
+    /// follow it.
+    fn verify_header(
+        &self,
+        header: &Self::Header,
+        parent: Option<&Self::Header>,
+    ) -> ProtocolResult<()>;
+
+    /// Whether `proof` shows `leaf` is part of the block of `header`.
+    fn verify_inclusion(
+        &self,
+        header: &Self::Header,
+        leaf: &Self::Leaf,
+        proof: &Self::Proof,
+    ) -> ProtocolResult<bool>;
+}
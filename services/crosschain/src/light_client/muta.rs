@@ -0,0 +1,134 @@
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+use crate::light_client::LightClient;
+use crate::muta;
+use crate::types::{
+    MutaConfig, MutaHeader, MutaReceiptProof, MutaValidatorSet, MutaValidatorUpdate,
+};
+use crate::ServiceError;
+
+/// Muta headers carry the overlord proof of their parent: a precommit quorum
+/// of the validator set at the parent height, aggregated into one BLS
+/// signature. A header is therefore final only once a verified child links
+/// to it.
+pub struct MutaLightClient {
+    pub config:        MutaConfig,
+    // the set that signed the blocks being verified
+    pub validator_set: MutaValidatorSet,
+}
+
+impl MutaLightClient {
+    /// Checks `update` is the set announced by `header`, the confirmed header
+    /// before it starts, and that a quorum of the current set signed it.
+    pub fn verify_validator_update(
+        &self,
+        header: &MutaHeader,
+        update: &MutaValidatorUpdate,
+    ) -> ProtocolResult<()> {
+        let next = &update.validator_set;
+        let current = &self.validator_set;
+        let matches_header = header.height + 1 == next.start_height
+            && header.validator_version == next.version
+            && header.validators.len() == next.validators.len()
+            && header
+                .validators
+                .iter()
+                .zip(next.validators.iter())
+                .all(|(v, n)| v.address == n.address && v.vote_weight == n.vote_weight);
+        if !matches_header
+            || next.version <= current.version
+            || next.start_height <= current.start_height
+        {
+            return Err(ServiceError::InvalidValidatorUpdate {
+                version: next.version,
+            }
+            .into());
+        }
+
+        let signed = match (
+            hex::decode(update.signature.as_string_trim0x()),
+            hex::decode(update.bitmap.as_string_trim0x()),
+            self.config.common_ref_str(),
+        ) {
+            (Ok(signature), Ok(bitmap), Some(common_ref)) => muta::verify_quorum_signature(
+                &muta::validator_set_hash(next),
+                &signature,
+                &bitmap,
+                &current.validators,
+                &common_ref,
+            ),
+            _ => false,
+        };
+        if !signed {
+            return Err(ServiceError::InvalidValidatorUpdate {
+                version: next.version,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl LightClient for MutaLightClient {
+    type Header = MutaHeader;
+    // the receipt hash
+    type Leaf = Hash;
+    type Proof = MutaReceiptProof;
+
+    fn height(&self, header: &MutaHeader) -> u64 {
+        header.height
+    }
+
+    fn verify_header(
+        &self,
+        header: &MutaHeader,
+        parent: Option<&MutaHeader>,
+    ) -> ProtocolResult<()> {
+        let height = header.height;
+        let proof = &header.proof;
+        if header.chain_id != self.config.chain_id
+            || height == 0
+            || proof.height != height - 1
+            || proof.block_hash != header.prev_hash
+            || proof.height < self.validator_set.start_height
+        {
+            return Err(ServiceError::InvalidMutaHeader { height }.into());
+        }
+        if let Some(parent) = parent {
+            if parent.hash != header.prev_hash {
+                return Err(ServiceError::InvalidMutaHeader { height }.into());
+            }
+        }
+
+        let common_ref = self
+            .config
+            .common_ref_str()
+            .ok_or(ServiceError::InvalidMutaHeader { height })?;
+        if !muta::verify_quorum_signature(
+            &muta::vote_hash(proof),
+            proof.signature.as_ref(),
+            proof.bitmap.as_ref(),
+            &self.validator_set.validators,
+            &common_ref,
+        ) {
+            return Err(ServiceError::InvalidMutaHeader { height }.into());
+        }
+
+        Ok(())
+    }
+
+    fn verify_inclusion(
+        &self,
+        header: &MutaHeader,
+        receipt_hash: &Hash,
+        proof: &MutaReceiptProof,
+    ) -> ProtocolResult<bool> {
+        let root = match header.receipt_roots.get(proof.root_index as usize) {
+            Some(root) => root,
+            None => return Ok(false),
+        };
+        Ok(&muta::merkle_root(receipt_hash, &proof.path) == root)
+    }
+}
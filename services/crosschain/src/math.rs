@@ -0,0 +1,9 @@
+use crate::ServiceError;
+
+pub fn checked_add_u128(a: u128, b: u128) -> Result<u128, ServiceError> {
+    a.checked_add(b).ok_or(ServiceError::U128Overflow)
+}
+
+pub fn checked_sub_u128(a: u128, b: u128) -> Result<u128, ServiceError> {
+    a.checked_sub(b).ok_or(ServiceError::U128Underflow)
+}
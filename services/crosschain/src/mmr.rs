@@ -0,0 +1,85 @@
+//! Merkle mountain range over ckb header hashes. The chain keeps only the
+//! peaks of the range, the relayer keeps the whole range off-chain and proves
+//! a header belongs to it when a deposit relies on that header.
+//!
+//! Leaf `i` is the `i`th header appended. The range is a list of perfect
+//! binary trees, one for each bit set in the leaf count from the highest, and
+//! its root bags their peaks from the right: `merge(p0, merge(p1, p2))`.
+
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+use crate::proof::merge;
+use crate::types::MmrProof;
+
+/// Appends `leaf` to a range of `leaves` leaves with the given peaks.
+pub fn append(peaks: &mut Vec<Hash>, leaves: u64, leaf: Hash) -> ProtocolResult<()> {
+    let mut node = leaf;
+    let mut size = leaves;
+
+    // Each trailing one bit is a tree as high as the new node, they merge.
+    while size & 1 == 1 {
+        let left = peaks.pop().expect("a peak for each bit of the leaf count");
+        node = merge(&left, &node)?;
+        size >>= 1;
+    }
+    peaks.push(node);
+
+    Ok(())
+}
+
+/// Root of a range from its peaks, `None` for an empty range.
+pub fn bag_peaks(peaks: &[Hash]) -> ProtocolResult<Option<Hash>> {
+    let mut iter = peaks.iter().rev();
+    let mut root = match iter.next() {
+        Some(peak) => peak.clone(),
+        None => return Ok(None),
+    };
+    for peak in iter {
+        root = merge(peak, &root)?;
+    }
+
+    Ok(Some(root))
+}
+
+/// Whether `proof` shows `leaf` is leaf `leaf_index` of the range of
+/// `proof.leaves` leaves whose root is `root`.
+pub fn verify(root: &Hash, leaf: &Hash, leaf_index: u64, proof: &MmrProof) -> ProtocolResult<bool> {
+    if leaf_index >= proof.leaves || proof.peaks.len() != proof.leaves.count_ones() as usize {
+        return Ok(false);
+    }
+    if bag_peaks(&proof.peaks)?.as_ref() != Some(root) {
+        return Ok(false);
+    }
+
+    // Find the tree holding the leaf, trees come from the highest.
+    let mut offset = 0u64;
+    let mut peak = 0usize;
+    for height in (0..64).rev() {
+        let tree_size = 1u64 << height;
+        if proof.leaves & tree_size == 0 {
+            continue;
+        }
+        if leaf_index >= offset + tree_size {
+            offset += tree_size;
+            peak += 1;
+            continue;
+        }
+
+        if proof.siblings.len() != height {
+            return Ok(false);
+        }
+        let index = leaf_index - offset;
+        let mut node = leaf.clone();
+        for (level, sibling) in proof.siblings.iter().enumerate() {
+            node = if (index >> level) & 1 == 0 {
+                merge(&node, sibling)?
+            } else {
+                merge(sibling, &node)?
+            };
+        }
+        return Ok(node == proof.peaks[peak]);
+    }
+
+    Ok(false)
+}
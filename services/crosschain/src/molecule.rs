@@ -0,0 +1,485 @@
+//! Molecule serialization of ckb transactions, following the layouts in ckb's
+//! `blockchain.mol`.
+
+use byteorder::{ByteOrder, LittleEndian};
+use bytes::Bytes;
+use derive_more::Display;
+use serde_json::Value;
+
+use protocol::types::{Hash, Hex};
+
+use crate::types::{
+    CellDep, CellInput, CellOutput, CkbHeaderInner, CkbTx, DepType, OutPoint, Script,
+    ScriptHashType,
+};
+
+const NUMBER_SIZE: usize = 4;
+const HASH_SIZE: usize = 32;
+const OUT_POINT_SIZE: usize = HASH_SIZE + 4;
+const CELL_DEP_SIZE: usize = OUT_POINT_SIZE + 1;
+const CELL_INPUT_SIZE: usize = 8 + OUT_POINT_SIZE;
+
+#[derive(Debug, Display)]
+pub enum MoleculeError {
+    #[display(fmt = "Molecule data is shorter than its header")]
+    TooShort,
+
+    #[display(fmt = "Molecule header is inconsistent with the data")]
+    InvalidHeader,
+
+    #[display(fmt = "Molecule table has {} fields, expect {}", real, expect)]
+    FieldCount { real: usize, expect: usize },
+
+    #[display(fmt = "Invalid molecule enum value {}", _0)]
+    InvalidValue(u8),
+
+    #[display(fmt = "Invalid hex field {}", _0)]
+    InvalidHex(String),
+}
+
+/// Serializes the `RawTransaction` part of a tx, the preimage of its hash.
+pub fn encode_raw_transaction(tx: &CkbTx) -> Result<Vec<u8>, MoleculeError> {
+    let mut version = [0u8; NUMBER_SIZE];
+    LittleEndian::write_u32(&mut version, parse_u32(&tx.version)?);
+
+    let mut cell_deps = Vec::with_capacity(tx.cell_deps.len());
+    for dep in tx.cell_deps.iter() {
+        let mut item = encode_out_point(&dep.out_point)?;
+        item.push(match dep.dep_type {
+            DepType::code => 0,
+            DepType::depgroup => 1,
+        });
+        cell_deps.push(item);
+    }
+    let header_deps = tx
+        .header_deps
+        .iter()
+        .map(|h| h.as_bytes().to_vec())
+        .collect();
+    let mut inputs = Vec::with_capacity(tx.inputs.len());
+    for input in tx.inputs.iter() {
+        let mut item = vec![0u8; 8];
+        LittleEndian::write_u64(&mut item, parse_u64(&input.since)?);
+        item.extend(encode_out_point(&input.previous_output)?);
+        inputs.push(item);
+    }
+    let outputs = tx
+        .outputs
+        .iter()
+        .map(encode_cell_output)
+        .collect::<Result<Vec<_>, _>>()?;
+    let outputs_data = tx
+        .outputs_data
+        .iter()
+        .map(encode_bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(encode_dynvec(vec![
+        version.to_vec(),
+        encode_fixvec(cell_deps),
+        encode_fixvec(header_deps),
+        encode_fixvec(inputs),
+        encode_dynvec(outputs),
+        encode_dynvec(outputs_data),
+    ]))
+}
+
+/// Serializes a whole `Transaction`, the preimage of its witness hash.
+pub fn encode_transaction(tx: &CkbTx) -> Result<Vec<u8>, MoleculeError> {
+    let witnesses = tx
+        .witnesses
+        .iter()
+        .map(encode_bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(encode_dynvec(vec![
+        encode_raw_transaction(tx)?,
+        encode_dynvec(witnesses),
+    ]))
+}
+
+/// Serializes a `Header`, the preimage of the ckb block hash. Every field is
+/// fixed size, so it is the plain concatenation of the raw header and nonce.
+pub fn encode_header(header: &CkbHeaderInner) -> Vec<u8> {
+    let mut data = vec![0u8; 4 + 4 + 8 * 3];
+    LittleEndian::write_u32(&mut data[0..4], header.version);
+    LittleEndian::write_u32(&mut data[4..8], header.compact_target);
+    LittleEndian::write_u64(&mut data[8..16], header.timestamp);
+    LittleEndian::write_u64(&mut data[16..24], header.number);
+    LittleEndian::write_u64(&mut data[24..32], header.epoch);
+    for hash in [
+        &header.parent_hash,
+        &header.transactions_root,
+        &header.proposals_hash,
+        &header.uncles_hash,
+        &header.dao,
+    ]
+    .iter()
+    {
+        data.extend_from_slice(hash.as_bytes().as_ref());
+    }
+
+    let mut nonce = [0u8; 16];
+    LittleEndian::write_u128(&mut nonce, header.nonce);
+    data.extend_from_slice(&nonce);
+    data
+}
+
+/// Decodes a serialized `Transaction`, returning it together with the
+/// `RawTransaction` bytes the ckb tx hash is computed from.
+pub fn decode_transaction(data: &[u8]) -> Result<(CkbTx, &[u8]), MoleculeError> {
+    let fields = table(data, 2)?;
+    let raw = fields[0];
+    let witnesses = dynvec(fields[1])?
+        .into_iter()
+        .map(bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let raw_fields = table(raw, 6)?;
+    let version = fixed(raw_fields[0], NUMBER_SIZE)?;
+    let cell_deps = fixvec(raw_fields[1], CELL_DEP_SIZE)?
+        .into_iter()
+        .map(cell_dep)
+        .collect::<Result<Vec<_>, _>>()?;
+    let header_deps = fixvec(raw_fields[2], HASH_SIZE)?
+        .into_iter()
+        .map(hash)
+        .collect::<Result<Vec<_>, _>>()?;
+    let inputs = fixvec(raw_fields[3], CELL_INPUT_SIZE)?
+        .into_iter()
+        .map(cell_input)
+        .collect::<Result<Vec<_>, _>>()?;
+    let outputs = dynvec(raw_fields[4])?
+        .into_iter()
+        .map(cell_output)
+        .collect::<Result<Vec<_>, _>>()?;
+    let outputs_data = dynvec(raw_fields[5])?
+        .into_iter()
+        .map(bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tx = CkbTx {
+        version: number_hex(u64::from(LittleEndian::read_u32(version)))?,
+        cell_deps,
+        header_deps,
+        inputs,
+        outputs,
+        outputs_data,
+        witnesses,
+    };
+
+    Ok((tx, raw))
+}
+
+/// A serialized tx read in place, its fields are only split out and decoded
+/// when asked for. Cheaper than `decode_transaction` when only a few fields
+/// matter, as for deposits.
+pub struct TxView<'a> {
+    raw:          &'a [u8],
+    outputs:      Vec<&'a [u8]>,
+    outputs_data: Vec<&'a [u8]>,
+    witnesses:    Vec<&'a [u8]>,
+}
+
+impl<'a> TxView<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, MoleculeError> {
+        let fields = table(data, 2)?;
+        let raw = fields[0];
+        let raw_fields = table(raw, 6)?;
+
+        Ok(TxView {
+            raw,
+            outputs: dynvec(raw_fields[4])?,
+            outputs_data: dynvec(raw_fields[5])?,
+            witnesses: dynvec(fields[1])?,
+        })
+    }
+
+    /// The serialized `RawTransaction`, the preimage of the tx hash.
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Code hash and args of the type script of an output, `None` when the
+    /// output is missing or has no type script.
+    pub fn output_type(&self, index: usize) -> Result<Option<(&'a [u8], &'a [u8])>, MoleculeError> {
+        let output = match self.outputs.get(index) {
+            Some(output) => *output,
+            None => return Ok(None),
+        };
+        let fields = table(output, 3)?;
+        if fields[2].is_empty() {
+            return Ok(None);
+        }
+
+        let script_fields = table(fields[2], 3)?;
+        let code_hash = fixed(script_fields[0], HASH_SIZE)?;
+        Ok(Some((code_hash, bytes_slice(script_fields[2])?)))
+    }
+
+    pub fn output_data(&self, index: usize) -> Result<Option<&'a [u8]>, MoleculeError> {
+        self.outputs_data
+            .get(index)
+            .map(|data| bytes_slice(*data))
+            .transpose()
+    }
+
+    pub fn last_witness(&self) -> Result<Option<&'a [u8]>, MoleculeError> {
+        self.witnesses
+            .last()
+            .map(|data| bytes_slice(*data))
+            .transpose()
+    }
+}
+
+fn encode_out_point(out_point: &OutPoint) -> Result<Vec<u8>, MoleculeError> {
+    let mut data = out_point.tx_hash.as_bytes().to_vec();
+    let mut index = [0u8; NUMBER_SIZE];
+    LittleEndian::write_u32(&mut index, parse_u32(&out_point.index)?);
+    data.extend_from_slice(&index);
+    Ok(data)
+}
+
+fn encode_cell_output(output: &CellOutput) -> Result<Vec<u8>, MoleculeError> {
+    let mut capacity = [0u8; 8];
+    LittleEndian::write_u64(&mut capacity, parse_u64(&output.capacity)?);
+    let type_ = match &output.type_ {
+        Some(script) => encode_script(script)?,
+        None => vec![],
+    };
+
+    Ok(encode_dynvec(vec![
+        capacity.to_vec(),
+        encode_script(&output.lock)?,
+        type_,
+    ]))
+}
+
+fn encode_script(script: &Script) -> Result<Vec<u8>, MoleculeError> {
+    let hash_type = match script.hash_type {
+        ScriptHashType::data => 0,
+        ScriptHashType::Type => 1,
+    };
+
+    Ok(encode_dynvec(vec![
+        script.code_hash.as_bytes().to_vec(),
+        vec![hash_type],
+        encode_bytes(&script.args)?,
+    ]))
+}
+
+fn encode_bytes(hex: &Hex) -> Result<Vec<u8>, MoleculeError> {
+    let raw = hex::decode(hex.as_string_trim0x())
+        .map_err(|_| MoleculeError::InvalidHex(hex.as_string()))?;
+    let mut data = Vec::with_capacity(NUMBER_SIZE + raw.len());
+    data.extend(encode_number(raw.len()));
+    data.extend(raw);
+    Ok(data)
+}
+
+fn encode_fixvec(items: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut data = encode_number(items.len());
+    for item in items {
+        data.extend(item);
+    }
+    data
+}
+
+/// Tables share the dynvec layout, a total size and one offset per item
+/// followed by the items.
+fn encode_dynvec(items: Vec<Vec<u8>>) -> Vec<u8> {
+    let header_size = NUMBER_SIZE * (items.len() + 1);
+    let total_size = header_size + items.iter().map(Vec::len).sum::<usize>();
+
+    let mut data = Vec::with_capacity(total_size);
+    data.extend(encode_number(total_size));
+    let mut offset = header_size;
+    for item in items.iter() {
+        data.extend(encode_number(offset));
+        offset += item.len();
+    }
+    for item in items {
+        data.extend(item);
+    }
+    data
+}
+
+fn encode_number(n: usize) -> Vec<u8> {
+    let mut buf = [0u8; NUMBER_SIZE];
+    LittleEndian::write_u32(&mut buf, n as u32);
+    buf.to_vec()
+}
+
+fn parse_u32(hex: &Hex) -> Result<u32, MoleculeError> {
+    u32::from_str_radix(hex.as_string_trim0x().as_str(), 16)
+        .map_err(|_| MoleculeError::InvalidHex(hex.as_string()))
+}
+
+fn parse_u64(hex: &Hex) -> Result<u64, MoleculeError> {
+    u64::from_str_radix(hex.as_string_trim0x().as_str(), 16)
+        .map_err(|_| MoleculeError::InvalidHex(hex.as_string()))
+}
+
+fn cell_dep(data: &[u8]) -> Result<CellDep, MoleculeError> {
+    let dep_type = match data[OUT_POINT_SIZE] {
+        0 => DepType::code,
+        1 => DepType::depgroup,
+        v => return Err(MoleculeError::InvalidValue(v)),
+    };
+
+    Ok(CellDep {
+        out_point: out_point(&data[..OUT_POINT_SIZE])?,
+        dep_type,
+    })
+}
+
+fn cell_input(data: &[u8]) -> Result<CellInput, MoleculeError> {
+    Ok(CellInput {
+        since:           number_hex(LittleEndian::read_u64(&data[..8]))?,
+        previous_output: out_point(&data[8..])?,
+    })
+}
+
+fn out_point(data: &[u8]) -> Result<OutPoint, MoleculeError> {
+    Ok(OutPoint {
+        tx_hash: hash(&data[..HASH_SIZE])?,
+        index:   number_hex(u64::from(LittleEndian::read_u32(&data[HASH_SIZE..])))?,
+    })
+}
+
+fn cell_output(data: &[u8]) -> Result<CellOutput, MoleculeError> {
+    let fields = table(data, 3)?;
+    let capacity = fixed(fields[0], 8)?;
+    let type_ = if fields[2].is_empty() {
+        None
+    } else {
+        Some(script(fields[2])?)
+    };
+
+    Ok(CellOutput {
+        capacity: number_hex(LittleEndian::read_u64(capacity))?,
+        lock: script(fields[1])?,
+        type_,
+    })
+}
+
+fn script(data: &[u8]) -> Result<Script, MoleculeError> {
+    let fields = table(data, 3)?;
+    let hash_type = match fixed(fields[1], 1)?[0] {
+        0 => ScriptHashType::data,
+        1 => ScriptHashType::Type,
+        v => return Err(MoleculeError::InvalidValue(v)),
+    };
+
+    Ok(Script {
+        code_hash: hash(fixed(fields[0], HASH_SIZE)?)?,
+        hash_type,
+        args: bytes(fields[2])?,
+    })
+}
+
+fn hash(data: &[u8]) -> Result<Hash, MoleculeError> {
+    Hash::from_bytes(Bytes::from(data.to_vec())).map_err(|_| MoleculeError::InvalidHeader)
+}
+
+fn bytes(data: &[u8]) -> Result<Hex, MoleculeError> {
+    let items = fixvec(data, 1)?;
+    let raw: Vec<u8> = items.into_iter().map(|b| b[0]).collect();
+    to_hex("0x".to_owned() + &hex::encode(raw))
+}
+
+/// The content of a `Bytes` fixvec, borrowed.
+fn bytes_slice(data: &[u8]) -> Result<&[u8], MoleculeError> {
+    let len = read_number(data, 0)?;
+    if data.len() != NUMBER_SIZE + len {
+        return Err(MoleculeError::InvalidHeader);
+    }
+    Ok(&data[NUMBER_SIZE..])
+}
+
+/// Numbers are rendered like ckb json rpc does, padded to an even length.
+fn number_hex(n: u64) -> Result<Hex, MoleculeError> {
+    let mut digits = format!("{:x}", n);
+    if digits.len() % 2 == 1 {
+        digits.insert(0, '0');
+    }
+    to_hex("0x".to_owned() + &digits)
+}
+
+// `Hex::from_string` rejects the empty "0x" ckb uses for empty bytes, so the
+// value is built the same way a json payload is deserialized.
+fn to_hex(s: String) -> Result<Hex, MoleculeError> {
+    serde_json::from_value(Value::String(s)).map_err(|_| MoleculeError::InvalidHeader)
+}
+
+fn read_number(data: &[u8], pos: usize) -> Result<usize, MoleculeError> {
+    if data.len() < pos + NUMBER_SIZE {
+        return Err(MoleculeError::TooShort);
+    }
+    Ok(LittleEndian::read_u32(&data[pos..pos + NUMBER_SIZE]) as usize)
+}
+
+fn fixed(data: &[u8], size: usize) -> Result<&[u8], MoleculeError> {
+    if data.len() != size {
+        return Err(MoleculeError::InvalidHeader);
+    }
+    Ok(data)
+}
+
+/// Splits a fixvec into its items of `item_size` bytes.
+fn fixvec(data: &[u8], item_size: usize) -> Result<Vec<&[u8]>, MoleculeError> {
+    let count = read_number(data, 0)?;
+    let expect_len = count
+        .checked_mul(item_size)
+        .and_then(|len| len.checked_add(NUMBER_SIZE))
+        .ok_or(MoleculeError::InvalidHeader)?;
+    if data.len() != expect_len {
+        return Err(MoleculeError::InvalidHeader);
+    }
+
+    Ok(data[NUMBER_SIZE..].chunks(item_size).collect())
+}
+
+/// Splits a dynvec or table into its items using the offset header.
+fn dynvec(data: &[u8]) -> Result<Vec<&[u8]>, MoleculeError> {
+    let total_size = read_number(data, 0)?;
+    if total_size != data.len() {
+        return Err(MoleculeError::InvalidHeader);
+    }
+    if total_size == NUMBER_SIZE {
+        return Ok(vec![]);
+    }
+
+    let first_offset = read_number(data, NUMBER_SIZE)?;
+    if first_offset % NUMBER_SIZE != 0 || first_offset < NUMBER_SIZE * 2 {
+        return Err(MoleculeError::InvalidHeader);
+    }
+    let count = first_offset / NUMBER_SIZE - 1;
+    let mut offsets = Vec::with_capacity(count + 1);
+    for i in 0..count {
+        offsets.push(read_number(data, NUMBER_SIZE * (i + 1))?);
+    }
+    offsets.push(total_size);
+
+    let mut items = Vec::with_capacity(count);
+    for window in offsets.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        if start < first_offset || start > end || end > total_size {
+            return Err(MoleculeError::InvalidHeader);
+        }
+        items.push(&data[start..end]);
+    }
+
+    Ok(items)
+}
+
+fn table(data: &[u8], field_count: usize) -> Result<Vec<&[u8]>, MoleculeError> {
+    let fields = dynvec(data)?;
+    if fields.len() != field_count {
+        return Err(MoleculeError::FieldCount {
+            real:   fields.len(),
+            expect: field_count,
+        });
+    }
+    Ok(fields)
+}
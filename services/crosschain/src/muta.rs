@@ -0,0 +1,164 @@
+//! Muta data structures the sidechain bridge reads: block headers with the
+//! overlord BFT proof of their parent, and receipts with their merkle proofs.
+
+use std::convert::TryFrom;
+
+use bytes::Bytes;
+use common_crypto::{
+    BlsCommonReference, BlsPublicKey, BlsSignature, BlsSignatureVerify, HashValue,
+};
+use serde::Deserialize;
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{Address, BlockHeader, Hash, Proof, Receipt};
+use protocol::ProtocolResult;
+
+use crate::types::{MerkleStep, MutaHeader, MutaValidator, MutaValidatorSet};
+
+/// Overlord vote type of a precommit, a block is final once a quorum of
+/// validators precommitted it.
+const PRECOMMIT_VOTE_TYPE: u8 = 1;
+pub const LOCK_TO_MUTA_TOPIC: &str = "lock_to_muta";
+
+/// Decodes a fixed encoded muta block header, its hash being the hash of that
+/// encoding.
+pub fn decode_header(raw: &[u8]) -> ProtocolResult<MutaHeader> {
+    let raw = Bytes::from(raw.to_vec());
+    let header = BlockHeader::decode_fixed(raw.clone())?;
+
+    Ok(MutaHeader {
+        hash:              Hash::digest(raw),
+        chain_id:          header.chain_id,
+        height:            header.height,
+        exec_height:       header.exec_height,
+        prev_hash:         header.prev_hash,
+        receipt_roots:     header.receipt_root,
+        proof:             header.proof,
+        validator_version: header.validator_version,
+        validators:        header.validators,
+    })
+}
+
+/// Hash the validators sign for a precommit on `proof.block_hash`, the hash of
+/// the rlp encoded overlord vote.
+pub fn vote_hash(proof: &Proof) -> Hash {
+    let mut s = rlp::RlpStream::new_list(4);
+    s.append(&proof.height)
+        .append(&proof.round)
+        .append(&PRECOMMIT_VOTE_TYPE)
+        .append(&proof.block_hash.as_bytes().to_vec());
+    Hash::digest(Bytes::from(s.out()))
+}
+
+/// Validators whose bit is set in the proof bitmap, most significant bit
+/// first in validator list order.
+pub fn signers<'a>(bitmap: &[u8], validators: &'a [MutaValidator]) -> Vec<&'a MutaValidator> {
+    validators
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            bitmap
+                .get(i / 8)
+                .map_or(false, |byte| byte & (0x80 >> (i % 8)) != 0)
+        })
+        .map(|(_, validator)| validator)
+        .collect()
+}
+
+/// Whether the signers hold more than two thirds of the vote weight.
+pub fn has_quorum(signers: &[&MutaValidator], validators: &[MutaValidator]) -> bool {
+    let total: u64 = validators.iter().map(|v| u64::from(v.vote_weight)).sum();
+    let signed: u64 = signers.iter().map(|v| u64::from(v.vote_weight)).sum();
+    total > 0 && signed * 3 > total * 2
+}
+
+/// Whether the validators flagged in `bitmap` hold a quorum and signed
+/// `hash`.
+pub fn verify_quorum_signature(
+    hash: &Hash,
+    signature: &[u8],
+    bitmap: &[u8],
+    validators: &[MutaValidator],
+    common_ref: &str,
+) -> bool {
+    let signers = signers(bitmap, validators);
+    has_quorum(&signers, validators)
+        && verify_aggregated_signature(hash, signature, &signers, common_ref)
+}
+
+/// Hash a validator set update is signed over.
+pub fn validator_set_hash(validator_set: &MutaValidatorSet) -> Hash {
+    Hash::digest(Bytes::from(rlp::encode(validator_set)))
+}
+
+/// Verifies the aggregated BLS signature of the signers over `hash`.
+pub fn verify_aggregated_signature(
+    hash: &Hash,
+    signature: &[u8],
+    signers: &[&MutaValidator],
+    common_ref: &str,
+) -> bool {
+    let mut pub_keys = Vec::with_capacity(signers.len());
+    for signer in signers.iter() {
+        let key = match hex::decode(signer.bls_pub_key.as_string_trim0x()) {
+            Ok(key) => key,
+            Err(_) => return false,
+        };
+        match BlsPublicKey::try_from(key.as_slice()) {
+            Ok(key) => pub_keys.push(key),
+            Err(_) => return false,
+        }
+    }
+    let signature = match BlsSignature::try_from(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    let hash = match HashValue::try_from(hash.as_bytes().as_ref()) {
+        Ok(hash) => hash,
+        Err(_) => return false,
+    };
+
+    let common_ref: BlsCommonReference = common_ref.into();
+    signature
+        .verify(&hash, &BlsPublicKey::aggregate(pub_keys), &common_ref)
+        .is_ok()
+}
+
+/// Root of the receipt merkle tree computed from a leaf and its path.
+pub fn merkle_root(leaf: &Hash, path: &[MerkleStep]) -> Hash {
+    path.iter().fold(leaf.clone(), |node, step| {
+        let (left, right) = if step.left {
+            (&step.sibling, &node)
+        } else {
+            (&node, &step.sibling)
+        };
+        let mut data = left.as_bytes().to_vec();
+        data.extend_from_slice(right.as_bytes().as_ref());
+        Hash::digest(Bytes::from(data))
+    })
+}
+
+/// A lock emitted by the counterpart bridge service, asking for `amount` of
+/// its asset `asset_id` to be minted to `receiver` here.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Lock {
+    pub asset_id: Hash,
+    pub receiver: Address,
+    #[serde(with = "common::u128_string")]
+    pub amount:   u128,
+    pub topic:    String,
+}
+
+/// Reads the locks emitted by `bridge_service` from a fixed encoded receipt.
+/// Other events, and events that don't parse as a lock, are skipped.
+pub fn lock_events(receipt: &[u8], bridge_service: &str) -> ProtocolResult<Vec<Lock>> {
+    let receipt = Receipt::decode_fixed(Bytes::from(receipt.to_vec()))?;
+
+    Ok(receipt
+        .events
+        .iter()
+        .filter(|event| event.service == bridge_service)
+        .filter_map(|event| serde_json::from_str::<Lock>(&event.data).ok())
+        .filter(|lock| lock.topic == LOCK_TO_MUTA_TOPIC)
+        .collect())
+}
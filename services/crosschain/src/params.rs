@@ -0,0 +1,215 @@
+//! Tunables of the bridge the admin may change on chain. Each is stored under
+//! a key of its own and reads as the default the service shipped with until
+//! it is first set, so state written before the store keeps its behavior.
+//! Those the bridge had before the store keep the keys they were stored
+//! under.
+//!
+//! The recovery account and the burn epoch length are parameters only
+//! genesis sets. The one holds the deposits awaiting reassignment, which
+//! moving it would strand, the other numbers the epochs burns are summed in.
+
+use protocol::traits::ServiceSDK;
+use protocol::types::{Address, Hash};
+use protocol::ProtocolResult;
+
+use crate::deposit;
+use crate::types::{
+    BridgeLimits, FeeBeneficiary, FeeSchedule, Params, SetParamsPayload, WithdrawalFeeConfig,
+};
+
+pub const SUDT_CODE_HASH: &str =
+    "0x57dd0067814dab356e05c6def0d094bb79776711e68ffdfad2df6a7f877f7db6";
+/// Most messages waiting at one height for its header, which bounds what an
+/// unproven batch can make the chain store.
+pub const MAX_PENDING_MESSAGES: usize = 256;
+/// Muta blocks a relayer's payout address stays set before it can change.
+pub const PAYOUT_COOLDOWN: u64 = 1_000;
+/// Longest payload, in bytes, a message to ckb may carry.
+pub const MAX_OUTBOUND_PAYLOAD_LEN: usize = 1024;
+/// Most headers waiting for the after block hook, which the header relayer
+/// stages without paying cycles.
+pub const MAX_STAGED_HEADERS: usize = 256;
+/// Most staged headers one block verifies and stores, the rest wait for the
+/// next blocks.
+pub const STAGED_HEADERS_PER_BLOCK: usize = 32;
+/// Issues mirror assets unless genesis or the admin names another account.
+pub const MIRROR_ASSET_ISSUER: &str = "0xc4b0000000000000000000000000000000000000";
+/// Holds deposits whose receiver is malformed until they are reassigned.
+pub const RECOVERY_ACCOUNT: &str = "0xc4b0000000000000000000000000000000000001";
+/// Muta blocks whose burns ckb operators withdraw together.
+pub const BURN_EPOCH_LENGTH: u64 = 1_000;
+
+const SUDT_CODE_HASH_KEY: &str = "param_sudt_code_hash";
+const MAX_PENDING_MESSAGES_KEY: &str = "param_max_pending_messages";
+const PAYOUT_COOLDOWN_KEY: &str = "param_payout_cooldown";
+const MAX_OUTBOUND_PAYLOAD_LEN_KEY: &str = "param_max_outbound_payload_len";
+const MAX_STAGED_HEADERS_KEY: &str = "param_max_staged_headers";
+const STAGED_HEADERS_PER_BLOCK_KEY: &str = "param_staged_headers_per_block";
+const RECOVERY_ACCOUNT_KEY: &str = "param_recovery_account";
+const MIRROR_ASSET_ISSUER_KEY: &str = "mirror_asset_issuer";
+const MESSAGE_TTL_KEY: &str = "message_ttl";
+const BRIDGE_LIMITS_KEY: &str = "bridge_limits";
+const FEE_BENEFICIARY_KEY: &str = "fee_beneficiary";
+const FEE_SCHEDULE_KEY: &str = "fee_schedule";
+const WITHDRAWAL_FEE_CONFIG_KEY: &str = "withdrawal_fee_config";
+const BURN_EPOCH_LENGTH_KEY: &str = "burn_epoch_length";
+const BURN_BATCH_WINDOW_KEY: &str = "burn_batch_window";
+
+/// Every parameter, stored or default.
+pub fn load<SDK: ServiceSDK>(sdk: &SDK) -> ProtocolResult<Params> {
+    let sudt_code_hash: Option<Hash> = sdk.get_value(&SUDT_CODE_HASH_KEY.to_owned())?;
+    let max_pending_messages: Option<u64> = sdk.get_value(&MAX_PENDING_MESSAGES_KEY.to_owned())?;
+    let payout_cooldown: Option<u64> = sdk.get_value(&PAYOUT_COOLDOWN_KEY.to_owned())?;
+    let max_outbound_payload_len: Option<u64> =
+        sdk.get_value(&MAX_OUTBOUND_PAYLOAD_LEN_KEY.to_owned())?;
+    let max_staged_headers: Option<u64> = sdk.get_value(&MAX_STAGED_HEADERS_KEY.to_owned())?;
+    let staged_headers_per_block: Option<u64> =
+        sdk.get_value(&STAGED_HEADERS_PER_BLOCK_KEY.to_owned())?;
+    let mirror_asset_issuer: Option<Address> =
+        sdk.get_value(&MIRROR_ASSET_ISSUER_KEY.to_owned())?;
+    let recovery_account: Option<Address> = sdk.get_value(&RECOVERY_ACCOUNT_KEY.to_owned())?;
+    let message_ttl: Option<u64> = sdk.get_value(&MESSAGE_TTL_KEY.to_owned())?;
+    let bridge_limits: Option<BridgeLimits> = sdk.get_value(&BRIDGE_LIMITS_KEY.to_owned())?;
+    let fee_beneficiary: Option<FeeBeneficiary> = sdk.get_value(&FEE_BENEFICIARY_KEY.to_owned())?;
+    let fee_schedule: Option<FeeSchedule> = sdk.get_value(&FEE_SCHEDULE_KEY.to_owned())?;
+    let withdrawal_fee_config: Option<WithdrawalFeeConfig> =
+        sdk.get_value(&WITHDRAWAL_FEE_CONFIG_KEY.to_owned())?;
+    let burn_epoch_length: Option<u64> = sdk.get_value(&BURN_EPOCH_LENGTH_KEY.to_owned())?;
+    let burn_batch_window: Option<u64> = sdk.get_value(&BURN_BATCH_WINDOW_KEY.to_owned())?;
+
+    Ok(Params {
+        sudt_code_hash: sudt_code_hash.unwrap_or_else(default_sudt_code_hash),
+        max_pending_messages: max_pending_messages.unwrap_or(MAX_PENDING_MESSAGES as u64),
+        payout_cooldown: payout_cooldown.unwrap_or(PAYOUT_COOLDOWN),
+        max_outbound_payload_len: max_outbound_payload_len
+            .unwrap_or(MAX_OUTBOUND_PAYLOAD_LEN as u64),
+        max_staged_headers: max_staged_headers.unwrap_or(MAX_STAGED_HEADERS as u64),
+        staged_headers_per_block: staged_headers_per_block
+            .unwrap_or(STAGED_HEADERS_PER_BLOCK as u64),
+        mirror_asset_issuer: match mirror_asset_issuer {
+            Some(issuer) => issuer,
+            None => Address::from_hex(MIRROR_ASSET_ISSUER)?,
+        },
+        recovery_account: match recovery_account {
+            Some(account) => account,
+            None => Address::from_hex(RECOVERY_ACCOUNT)?,
+        },
+        message_ttl: message_ttl.unwrap_or(0),
+        bridge_limits: bridge_limits.unwrap_or_default(),
+        fee_beneficiary: fee_beneficiary.unwrap_or_default(),
+        fee_schedule: fee_schedule.unwrap_or_default(),
+        withdrawal_fee_config,
+        burn_epoch_length: burn_epoch_length.unwrap_or(BURN_EPOCH_LENGTH),
+        burn_batch_window: burn_batch_window.unwrap_or(0),
+    })
+}
+
+/// The first parameter the payload sets out of range. A bound of 0 would
+/// shut its path, so bounds must be positive, and fees must split and tier
+/// the way `deposit` expects.
+pub fn invalid(payload: &SetParamsPayload) -> Option<&'static str> {
+    if payload.max_pending_messages == Some(0) {
+        return Some("max_pending_messages");
+    }
+    if payload.max_outbound_payload_len == Some(0) {
+        return Some("max_outbound_payload_len");
+    }
+    if payload.max_staged_headers == Some(0) {
+        return Some("max_staged_headers");
+    }
+    if payload.staged_headers_per_block == Some(0) {
+        return Some("staged_headers_per_block");
+    }
+    if let Some(beneficiary) = &payload.fee_beneficiary {
+        if !deposit::valid_beneficiary(beneficiary) {
+            return Some("fee_beneficiary");
+        }
+    }
+    if let Some(schedule) = &payload.fee_schedule {
+        if !deposit::valid_schedule(schedule) {
+            return Some("fee_schedule");
+        }
+    }
+    None
+}
+
+/// Stores the parameters the payload sets and returns their names.
+pub fn store<SDK: ServiceSDK>(
+    sdk: &mut SDK,
+    payload: SetParamsPayload,
+) -> ProtocolResult<Vec<String>> {
+    let mut changed = vec![];
+    if let Some(hash) = payload.sudt_code_hash {
+        sdk.set_value(SUDT_CODE_HASH_KEY.to_owned(), hash)?;
+        changed.push("sudt_code_hash".to_owned());
+    }
+    if let Some(max) = payload.max_pending_messages {
+        sdk.set_value(MAX_PENDING_MESSAGES_KEY.to_owned(), max)?;
+        changed.push("max_pending_messages".to_owned());
+    }
+    if let Some(blocks) = payload.payout_cooldown {
+        sdk.set_value(PAYOUT_COOLDOWN_KEY.to_owned(), blocks)?;
+        changed.push("payout_cooldown".to_owned());
+    }
+    if let Some(max) = payload.max_outbound_payload_len {
+        sdk.set_value(MAX_OUTBOUND_PAYLOAD_LEN_KEY.to_owned(), max)?;
+        changed.push("max_outbound_payload_len".to_owned());
+    }
+    if let Some(max) = payload.max_staged_headers {
+        sdk.set_value(MAX_STAGED_HEADERS_KEY.to_owned(), max)?;
+        changed.push("max_staged_headers".to_owned());
+    }
+    if let Some(max) = payload.staged_headers_per_block {
+        sdk.set_value(STAGED_HEADERS_PER_BLOCK_KEY.to_owned(), max)?;
+        changed.push("staged_headers_per_block".to_owned());
+    }
+    if let Some(issuer) = payload.mirror_asset_issuer {
+        sdk.set_value(MIRROR_ASSET_ISSUER_KEY.to_owned(), issuer)?;
+        changed.push("mirror_asset_issuer".to_owned());
+    }
+    if let Some(blocks) = payload.message_ttl {
+        sdk.set_value(MESSAGE_TTL_KEY.to_owned(), blocks)?;
+        changed.push("message_ttl".to_owned());
+    }
+    if let Some(limits) = payload.bridge_limits {
+        sdk.set_value(BRIDGE_LIMITS_KEY.to_owned(), limits)?;
+        changed.push("bridge_limits".to_owned());
+    }
+    if let Some(beneficiary) = payload.fee_beneficiary {
+        sdk.set_value(FEE_BENEFICIARY_KEY.to_owned(), beneficiary)?;
+        changed.push("fee_beneficiary".to_owned());
+    }
+    if let Some(schedule) = payload.fee_schedule {
+        sdk.set_value(FEE_SCHEDULE_KEY.to_owned(), schedule)?;
+        changed.push("fee_schedule".to_owned());
+    }
+    if let Some(config) = payload.withdrawal_fee_config {
+        sdk.set_value(WITHDRAWAL_FEE_CONFIG_KEY.to_owned(), config)?;
+        changed.push("withdrawal_fee_config".to_owned());
+    }
+    if let Some(window) = payload.burn_batch_window {
+        sdk.set_value(BURN_BATCH_WINDOW_KEY.to_owned(), window)?;
+        changed.push("burn_batch_window".to_owned());
+    }
+    Ok(changed)
+}
+
+/// Stores the parameters only genesis sets, those left unset keeping their
+/// default.
+pub fn store_genesis<SDK: ServiceSDK>(
+    sdk: &mut SDK,
+    recovery_account: Option<Address>,
+    burn_epoch_length: u64,
+) -> ProtocolResult<()> {
+    if let Some(account) = recovery_account {
+        sdk.set_value(RECOVERY_ACCOUNT_KEY.to_owned(), account)?;
+    }
+    if burn_epoch_length > 0 {
+        sdk.set_value(BURN_EPOCH_LENGTH_KEY.to_owned(), burn_epoch_length)?;
+    }
+    Ok(())
+}
+
+pub fn default_sudt_code_hash() -> Hash {
+    Hash::from_hex(SUDT_CODE_HASH).expect("valid sudt code hash")
+}
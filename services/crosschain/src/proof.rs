@@ -0,0 +1,124 @@
+//! Ckb merkle proofs over muta hashes, on top of the `crosschain_proof`
+//! crate relayers and ckb scripts build them with.
+
+use bytes::Bytes;
+use crosschain_proof::{TxProof, H256};
+
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+use crate::molecule;
+use crate::types::{CkbHeaderInner, CkbTxProof};
+
+/// Blake2b-256 with ckb personalization, the hash function used across ckb.
+pub fn ckb_blake2b(data: &[u8]) -> ProtocolResult<Hash> {
+    to_hash(crosschain_proof::ckb_blake2b(data))
+}
+
+/// Merges two nodes of a ckb complete binary merkle tree.
+pub fn merge(left: &Hash, right: &Hash) -> ProtocolResult<Hash> {
+    to_hash(crosschain_proof::merge(&to_h256(left), &to_h256(right)))
+}
+
+/// Computes the root of a ckb complete binary merkle tree from a single leaf,
+/// its index in the tree (as returned by ckb `get_transaction_proof`) and the
+/// sibling lemmas ordered from the leaf up.
+pub fn cbmt_root(leaf: &Hash, tree_index: u32, lemmas: &[Hash]) -> ProtocolResult<Hash> {
+    to_hash(crosschain_proof::cbmt_root(
+        &to_h256(leaf),
+        tree_index,
+        &to_h256s(lemmas),
+    ))
+}
+
+/// Computes the root of the ckb complete binary merkle tree holding all of
+/// `leaves`, leaf `i` at tree index `i + leaves.len() - 1`. The zero hash for
+/// no leaves, as in ckb.
+pub fn cbmt_build_root(leaves: &[Hash]) -> ProtocolResult<Hash> {
+    to_hash(crosschain_proof::cbmt_build_root(&to_h256s(leaves)))
+}
+
+/// Computes the root of a ckb complete binary merkle tree from several leaves
+/// at once, as proven by ckb `get_transaction_proof` for several txs. `None`
+/// when the proof doesn't fit the leaves.
+pub fn cbmt_multi_root(leaves: &[(u32, Hash)], lemmas: &[Hash]) -> ProtocolResult<Option<Hash>> {
+    let leaves: Vec<(u32, H256)> = leaves
+        .iter()
+        .map(|(index, leaf)| (*index, to_h256(leaf)))
+        .collect();
+    crosschain_proof::cbmt_multi_root(&leaves, &to_h256s(lemmas))
+        .map(to_hash)
+        .transpose()
+}
+
+/// Packs a tx proof in the binary form of v2 `submit_messages` payloads, see
+/// `TxProof::pack`.
+pub fn pack_tx_proof(proof: &CkbTxProof) -> Vec<u8> {
+    to_tx_proof(proof).pack()
+}
+
+/// Reads a packed tx proof, `None` unless it has exactly the lemmas its tree
+/// index calls for.
+pub fn unpack_tx_proof(data: &[u8]) -> Option<CkbTxProof> {
+    from_tx_proof(TxProof::unpack(data)?).ok()
+}
+
+pub fn to_tx_proof(proof: &CkbTxProof) -> TxProof {
+    TxProof {
+        index:          proof.index,
+        lemmas:         to_h256s(&proof.lemmas),
+        witnesses_root: to_h256(&proof.witnesses_root),
+        witness_lemmas: proof.witness_lemmas.as_deref().map(to_h256s),
+    }
+}
+
+pub fn from_tx_proof(proof: TxProof) -> ProtocolResult<CkbTxProof> {
+    let witness_lemmas = match proof.witness_lemmas {
+        Some(lemmas) => Some(to_hashes(lemmas)?),
+        None => None,
+    };
+    Ok(CkbTxProof {
+        index: proof.index,
+        lemmas: to_hashes(proof.lemmas)?,
+        witnesses_root: to_hash(proof.witnesses_root)?,
+        witness_lemmas,
+    })
+}
+
+/// A ckb header commits to `merge(raw_transactions_root, witnesses_root)`.
+pub fn transactions_root(
+    tx_hash: &Hash,
+    tree_index: u32,
+    lemmas: &[Hash],
+    witnesses_root: &Hash,
+) -> ProtocolResult<Hash> {
+    to_hash(crosschain_proof::transactions_root(
+        &to_h256(tx_hash),
+        tree_index,
+        &to_h256s(lemmas),
+        &to_h256(witnesses_root),
+    ))
+}
+
+/// Hash of a ckb block, what its child header names as parent.
+pub fn header_hash(header: &CkbHeaderInner) -> ProtocolResult<Hash> {
+    ckb_blake2b(&molecule::encode_header(header))
+}
+
+pub fn to_h256(hash: &Hash) -> H256 {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(hash.as_bytes().as_ref());
+    out
+}
+
+pub fn to_hash(hash: H256) -> ProtocolResult<Hash> {
+    Hash::from_bytes(Bytes::from(hash.to_vec()))
+}
+
+fn to_h256s(hashes: &[Hash]) -> Vec<H256> {
+    hashes.iter().map(to_h256).collect()
+}
+
+fn to_hashes(hashes: Vec<H256>) -> ProtocolResult<Vec<Hash>> {
+    hashes.into_iter().map(to_hash).collect()
+}
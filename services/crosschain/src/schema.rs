@@ -0,0 +1,309 @@
+//! JSON schemas of the types in the service interface, derived from their
+//! definitions so clients can generate bindings instead of mirroring the
+//! Rust structs by hand.
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use common::view::{MigrationStatusView, ServiceInfoView, ValueView};
+
+use crate::types::{
+    AckBurnPayload, AssetVolume, BatchMessagePayload, BridgeLimits, BridgePausedEvent,
+    BridgeResumedEvent, BridgeStats, BridgedSupply, BurnAckedEvent, BurnBatch, BurnBatchEvent,
+    BurnCallAssetPayload, BurnCancelledEvent, BurnPayload, BurnQuote, BurnRecord, BurnStatus,
+    BurnToEthEvent, BurnToEthPayload, BurnToMutaEvent, BurnToMutaPayload, BurnTokenEvent,
+    CallTarget, CallTargetEvent, CallTargetPayload, CancelBurnPayload, CellDep, CellInput,
+    CellOutput, ChainBinding, CkbHeader, CkbHeaderInner, CkbMessage, CkbMultiProof, CkbTx,
+    CkbTxProof, CreateMirrorAssetPayload, CrossCallEvent, DepType, DepositQuote,
+    DepositReassignedEvent, DepositRecoveredEvent, DepositTag, DepositTagEvent, EpochAssetBurns,
+    EpochLockBurns, EpochSummary, EpochView, EthConfig, EthHeader, EthReceiptProof, EthSyncMode,
+    EventDigest, EventDigests, FeeBeneficiary, FeeSchedule, FeeTier, FeeWaiverEvent,
+    FeeWaiverPayload, ForceProcessMessagePayload, GetBurnBatchPayload, GetBurnLimitResponse,
+    GetBurnQuotaResponse, GetBurnRecordPayload, GetDepositByCkbTxPayload, GetDepositTagPayload,
+    GetEpochSummaryPayload, GetHeaderPayload, GetHeaderResponse, GetMirrorAssetByIdPayload,
+    GetMirrorAssetBySudtPayload, GetMirrorAssetIdResponse, GetMissingHeaderRangesPayload,
+    GetMmrResponse, GetMutaHeaderPayload, GetMutaValidatorSetPayload, GetOutboundBlockPayload,
+    GetOutboundMessagePayload, GetRecoveredDepositPayload, GetSchemaPayload, GetViewPayload,
+    HasRolePayload, HasRoleResponse, HeaderConflict, HeaderConflictEvent,
+    HeaderConflictResolvedEvent, HeightRange, InitGenesisPayload, MerkleStep, MessageForcedEvent,
+    MessagePayload, MessagePayloadV1, MessageRejectedEvent, MessageTtl, MessagesPendingEvent,
+    MigratePayload, MigrationEvent, MigrationStatus, MintCallbackEvent, MintCallbackFailedEvent,
+    MintCallbackPayload, MintTokenEvent, MintTokenPayload, MirrorAsset, MirrorAssetIssuer,
+    MissingHeaderRanges, MmrMessage, MmrMessagePayload, MmrProof, MmrUpdatedEvent, MutaConfig,
+    MutaHeader, MutaReceiptProof, MutaValidator, MutaValidatorSet, MutaValidatorSetEvent,
+    MutaValidatorUpdate, OnCrossCallPayload, OnCrossMintPayload, OutPoint, OutboundBlock,
+    OutboundMessage, OutboundMessageEvent, OutboundSender, OutboundSenderEvent,
+    OutboundSenderPayload, PackedCkbMessage, Params, ParamsEvent, PayoutAddress,
+    PayoutAddressEvent, PendingMessage, PendingMessages, ProcessPendingPayload, ProcessedDeposit,
+    QuotaExemptionEvent, QuotaExemptionPayload, QuoteBurnPayload, QuoteDepositPayload, ReadView,
+    ReassignDepositPayload, RecoveredDeposit, RegisterEthAssetPayload, RegisterMintCallbackPayload,
+    RegisterMirrorAssetEvent, RegisterMirrorAssetPayload, RegisterMutaAssetPayload, RelayerEvent,
+    RelayerPayload, ResolveHeaderConflictPayload, ResumeBridgePayload, Script, ScriptHashType,
+    SendMessageToCkbPayload, ServiceInfo, SetBurnLimitEvent, SetBurnLimitPayload,
+    SetBurnQuotaEvent, SetBurnQuotaPayload, SetParamsPayload, SetPayoutAddressPayload,
+    SetSupplyCapEvent, SetSupplyCapPayload, SetWithdrawalFeeEvent, SetWithdrawalFeePayload,
+    SubmitEthReceiptsPayload, SubmitMutaReceiptsPayload, SudtDustEvent, SupportedLocks,
+    SupportedLocksEvent, TransferCallAssetPayload, TruncatedField, Uint128,
+    UpdateEthHeadersPayload, UpdateHeadersPayload, UpdateMmrPayload, UpdateMutaHeadersPayload,
+    ViewField, WithdrawalFeeConfig, WithdrawalFeeEstimate, WithdrawalFeeEvent,
+};
+use crate::view::{
+    AssetVolumeView, BridgeLimitsView, BridgeStatsView, BridgedSupplyView, BurnBatchView,
+    BurnLimitView, BurnQuotaView, BurnQuoteView, BurnRecordView, CallTargetView, DepositQuoteView,
+    DepositTagView, EpochAssetBurnsView, EpochLockBurnsView, EpochSummaryView, EthConfigView,
+    EventDigestView, EventDigestsView, FeeBeneficiaryView, FeeScheduleView, FeeTierView,
+    HeaderConflictView, HeaderView, HeightRangeView, MessageTtlView, MirrorAssetIdView,
+    MirrorAssetIssuerView, MirrorAssetView, MissingHeaderRangesView, MmrView, MutaConfigView,
+    MutaHeaderView, MutaValidatorSetView, MutaValidatorView, OutboundBlockView,
+    OutboundMessageView, OutboundSenderView, ParamsView, PayoutAddressView, ProcessedDepositView,
+    RawHeaderView, RecoveredDepositView, SupportedLocksView, ValidatorView,
+    WithdrawalFeeConfigView, WithdrawalFeeEstimateView,
+};
+
+macro_rules! schemas {
+    ($($ty:ident),* $(,)?) => {
+        /// Names `schema` knows about.
+        pub const SCHEMA_TYPES: &[&str] = &[$(stringify!($ty)),*];
+
+        /// Schema of the named type, `None` for unknown names.
+        pub fn schema(type_name: &str) -> Option<RootSchema> {
+            match type_name {
+                $(stringify!($ty) => Some(schema_for!($ty)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+schemas! {
+    AckBurnPayload,
+    AssetVolume,
+    AssetVolumeView,
+    BatchMessagePayload,
+    BridgeLimits,
+    BridgeLimitsView,
+    BridgePausedEvent,
+    BridgeResumedEvent,
+    BridgeStats,
+    BridgeStatsView,
+    BridgedSupply,
+    BridgedSupplyView,
+    BurnAckedEvent,
+    BurnBatch,
+    BurnBatchEvent,
+    BurnBatchView,
+    BurnCallAssetPayload,
+    BurnCancelledEvent,
+    BurnLimitView,
+    BurnPayload,
+    BurnQuotaView,
+    BurnQuote,
+    BurnQuoteView,
+    BurnRecord,
+    BurnRecordView,
+    BurnStatus,
+    BurnToEthEvent,
+    BurnToEthPayload,
+    BurnToMutaEvent,
+    BurnToMutaPayload,
+    BurnTokenEvent,
+    CallTarget,
+    CallTargetEvent,
+    CallTargetPayload,
+    CallTargetView,
+    CancelBurnPayload,
+    CellDep,
+    CellInput,
+    CellOutput,
+    ChainBinding,
+    CkbHeader,
+    CkbHeaderInner,
+    CkbMessage,
+    CkbMultiProof,
+    CkbTx,
+    CkbTxProof,
+    CreateMirrorAssetPayload,
+    CrossCallEvent,
+    DepType,
+    DepositQuote,
+    DepositQuoteView,
+    DepositReassignedEvent,
+    DepositRecoveredEvent,
+    DepositTag,
+    DepositTagEvent,
+    DepositTagView,
+    EpochAssetBurns,
+    EpochAssetBurnsView,
+    EpochLockBurns,
+    EpochLockBurnsView,
+    EpochSummary,
+    EpochSummaryView,
+    EpochView,
+    EthConfig,
+    EthConfigView,
+    EthHeader,
+    EthReceiptProof,
+    EthSyncMode,
+    EventDigest,
+    EventDigestView,
+    EventDigests,
+    EventDigestsView,
+    FeeBeneficiary,
+    FeeBeneficiaryView,
+    FeeSchedule,
+    FeeScheduleView,
+    FeeTier,
+    FeeTierView,
+    FeeWaiverEvent,
+    FeeWaiverPayload,
+    ForceProcessMessagePayload,
+    GetBurnBatchPayload,
+    GetBurnLimitResponse,
+    GetBurnQuotaResponse,
+    GetBurnRecordPayload,
+    GetDepositByCkbTxPayload,
+    GetDepositTagPayload,
+    GetEpochSummaryPayload,
+    GetHeaderPayload,
+    GetHeaderResponse,
+    GetMirrorAssetByIdPayload,
+    GetMirrorAssetBySudtPayload,
+    GetMirrorAssetIdResponse,
+    GetMissingHeaderRangesPayload,
+    GetMmrResponse,
+    GetMutaHeaderPayload,
+    GetMutaValidatorSetPayload,
+    GetOutboundBlockPayload,
+    GetOutboundMessagePayload,
+    GetRecoveredDepositPayload,
+    GetSchemaPayload,
+    GetViewPayload,
+    HasRolePayload,
+    HasRoleResponse,
+    HeaderConflict,
+    HeaderConflictEvent,
+    HeaderConflictResolvedEvent,
+    HeaderConflictView,
+    HeaderView,
+    HeightRange,
+    HeightRangeView,
+    InitGenesisPayload,
+    MerkleStep,
+    MessageForcedEvent,
+    MessagePayload,
+    MessagePayloadV1,
+    MessageRejectedEvent,
+    MessageTtl,
+    MessageTtlView,
+    MessagesPendingEvent,
+    MigratePayload,
+    MigrationEvent,
+    MigrationStatus,
+    MigrationStatusView,
+    MintCallbackEvent,
+    MintCallbackFailedEvent,
+    MintCallbackPayload,
+    MintTokenEvent,
+    MintTokenPayload,
+    MirrorAsset,
+    MirrorAssetIdView,
+    MirrorAssetIssuer,
+    MirrorAssetIssuerView,
+    MirrorAssetView,
+    MissingHeaderRanges,
+    MissingHeaderRangesView,
+    MmrMessage,
+    MmrMessagePayload,
+    MmrProof,
+    MmrUpdatedEvent,
+    MmrView,
+    MutaConfig,
+    MutaConfigView,
+    MutaHeader,
+    MutaHeaderView,
+    MutaReceiptProof,
+    MutaValidator,
+    MutaValidatorSet,
+    MutaValidatorSetEvent,
+    MutaValidatorSetView,
+    MutaValidatorUpdate,
+    MutaValidatorView,
+    OnCrossCallPayload,
+    OnCrossMintPayload,
+    OutPoint,
+    OutboundBlock,
+    OutboundBlockView,
+    OutboundMessage,
+    OutboundMessageEvent,
+    OutboundMessageView,
+    OutboundSender,
+    OutboundSenderEvent,
+    OutboundSenderPayload,
+    OutboundSenderView,
+    PackedCkbMessage,
+    Params,
+    ParamsEvent,
+    ParamsView,
+    PayoutAddress,
+    PayoutAddressEvent,
+    PayoutAddressView,
+    PendingMessage,
+    PendingMessages,
+    ProcessPendingPayload,
+    ProcessedDeposit,
+    ProcessedDepositView,
+    QuotaExemptionEvent,
+    QuotaExemptionPayload,
+    QuoteBurnPayload,
+    QuoteDepositPayload,
+    RawHeaderView,
+    ReadView,
+    ReassignDepositPayload,
+    RecoveredDeposit,
+    RecoveredDepositView,
+    RegisterEthAssetPayload,
+    RegisterMintCallbackPayload,
+    RegisterMirrorAssetEvent,
+    RegisterMirrorAssetPayload,
+    RegisterMutaAssetPayload,
+    RelayerEvent,
+    RelayerPayload,
+    ResolveHeaderConflictPayload,
+    ResumeBridgePayload,
+    Script,
+    ScriptHashType,
+    SendMessageToCkbPayload,
+    ServiceInfo,
+    ServiceInfoView,
+    SetBurnLimitEvent,
+    SetBurnLimitPayload,
+    SetBurnQuotaEvent,
+    SetBurnQuotaPayload,
+    SetParamsPayload,
+    SetPayoutAddressPayload,
+    SetSupplyCapEvent,
+    SetSupplyCapPayload,
+    SetWithdrawalFeeEvent,
+    SetWithdrawalFeePayload,
+    SubmitEthReceiptsPayload,
+    SubmitMutaReceiptsPayload,
+    SudtDustEvent,
+    SupportedLocks,
+    SupportedLocksEvent,
+    SupportedLocksView,
+    TransferCallAssetPayload,
+    TruncatedField,
+    Uint128,
+    UpdateEthHeadersPayload,
+    UpdateHeadersPayload,
+    UpdateMmrPayload,
+    UpdateMutaHeadersPayload,
+    ValidatorView,
+    ValueView,
+    ViewField,
+    WithdrawalFeeConfig,
+    WithdrawalFeeConfigView,
+    WithdrawalFeeEstimate,
+    WithdrawalFeeEstimateView,
+    WithdrawalFeeEvent,
+}
@@ -0,0 +1,81 @@
+use bech32::ToBase32;
+
+use protocol::types::{Address, Bytes, Hash};
+
+use crate::address::{parse_muta_address, AddressError, CkbAddress, MUTA_HRP};
+use crate::deposit::parse_receiver;
+use crate::types::ScriptHashType;
+
+fn bech32(hrp: &str, payload: &[u8]) -> String {
+    bech32::encode(hrp, payload.to_base32()).unwrap()
+}
+
+#[test]
+fn test_ckb_short_address() {
+    let mut payload = vec![0x01, 0x00];
+    payload.extend_from_slice(&[7u8; 20]);
+
+    for address in &[bech32("ckb", &payload), bech32("ckt", &payload)] {
+        let lock = CkbAddress::parse(address).unwrap().lock;
+        assert_eq!(
+            lock.code_hash.as_hex(),
+            "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8"
+        );
+        assert_eq!(lock.args.as_string_trim0x(), hex::encode(&[7u8; 20]));
+    }
+
+    let hex_address = "0x".to_owned() + &hex::encode(&payload);
+    assert!(CkbAddress::parse(&hex_address).is_ok());
+
+    payload[1] = 0x09;
+    assert_eq!(
+        CkbAddress::parse(&bech32("ckb", &payload)).unwrap_err(),
+        AddressError::CodeHashIndex(0x09)
+    );
+    assert!(CkbAddress::parse(&bech32("ckb", &payload[..10])).is_err());
+}
+
+#[test]
+fn test_ckb_full_address() {
+    let code_hash = Hash::digest(Bytes::from("lock"));
+    let mut payload = vec![0x02];
+    payload.extend_from_slice(code_hash.as_bytes().as_ref());
+    payload.extend_from_slice(&[1, 2, 3]);
+
+    let lock = CkbAddress::parse(&bech32("ckb", &payload)).unwrap().lock;
+    assert_eq!(lock.code_hash, code_hash);
+    assert_eq!(lock.args.as_string_trim0x(), "010203");
+    match lock.hash_type {
+        ScriptHashType::data => (),
+        ScriptHashType::Type => panic!("full data format should use data hash type"),
+    }
+
+    payload[0] = 0x03;
+    assert_eq!(
+        CkbAddress::parse(&bech32("ckb", &payload)).unwrap_err(),
+        AddressError::Format(0x03)
+    );
+    assert_eq!(
+        CkbAddress::parse(&bech32("btc", &payload)).unwrap_err(),
+        AddressError::Prefix("btc".to_owned())
+    );
+    assert_eq!(
+        CkbAddress::parse("not an address").unwrap_err(),
+        AddressError::Encoding
+    );
+}
+
+#[test]
+fn test_muta_address() {
+    let address = Address::from_bytes(Bytes::from(vec![3u8; 20])).unwrap();
+
+    assert_eq!(parse_muta_address(&address.as_hex()).unwrap(), address);
+    let bech32_address = bech32(MUTA_HRP, address.as_bytes().as_ref());
+    assert_eq!(parse_muta_address(&bech32_address).unwrap(), address);
+    assert!(parse_muta_address(&bech32(MUTA_HRP, &[3u8; 19])).is_err());
+
+    let witness = bech32_address + " deposit 42";
+    let (receiver, memo) = parse_receiver(witness.as_bytes()).unwrap();
+    assert_eq!(receiver, Some(address));
+    assert_eq!(memo, "deposit 42");
+}
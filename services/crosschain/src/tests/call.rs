@@ -0,0 +1,90 @@
+use protocol::types::{Bytes, Hash, Hex};
+use testing::strategy::hex_bytes;
+
+use crate::call::{parse_call, parse_raw_call, CALL_PREFIX, MAX_CALL_PAYLOAD_LEN};
+use crate::molecule::{encode_transaction, TxView};
+use crate::types::{CellOutput, CkbTx, CrossCall, RejectReason, Script, ScriptHashType};
+
+fn sender() -> Hash {
+    Hash::digest(Bytes::from("governance"))
+}
+
+fn call_tx(data: &[u8]) -> CkbTx {
+    CkbTx {
+        version:      hex_bytes(&[0]),
+        cell_deps:    vec![],
+        header_deps:  vec![],
+        inputs:       vec![],
+        outputs:      vec![CellOutput {
+            capacity: hex_bytes(&[0]),
+            lock:     Script {
+                code_hash: Hash::digest(Bytes::from("lock")),
+                hash_type: ScriptHashType::data,
+                args:      hex_bytes(&[]),
+            },
+            type_:    Some(Script {
+                code_hash: sender(),
+                hash_type: ScriptHashType::data,
+                args:      hex_bytes(&[]),
+            }),
+        }],
+        outputs_data: vec![hex_bytes(data)],
+        witnesses:    vec![hex_bytes(&[])],
+    }
+}
+
+// Both encodings of a tx carry the same call.
+fn parsed(tx: &CkbTx) -> Option<Result<CrossCall, (RejectReason, Option<&'static str>)>> {
+    let data = encode_transaction(tx).unwrap();
+    let raw = parse_raw_call(&TxView::parse(&data).unwrap());
+    let call = parse_call(tx);
+    assert_eq!(raw, call);
+    call.map(|c| c.map_err(|e| (e.reason, e.field)))
+}
+
+#[test]
+fn test_parse_call() {
+    let call = parsed(&call_tx(b"call:oracle set_price {\"price\": \"7\"}"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(call, CrossCall {
+        service:   "oracle".to_owned(),
+        method:    "set_price".to_owned(),
+        payload:   "{\"price\": \"7\"}".to_owned(),
+        code_hash: sender(),
+        args:      hex_bytes(&[]),
+    });
+
+    let call = parsed(&call_tx(b"call:governance tick")).unwrap().unwrap();
+    assert_eq!((call.method.as_str(), call.payload.as_str()), ("tick", ""));
+
+    // a sudt amount is no call
+    assert_eq!(parsed(&call_tx(&1000u128.to_le_bytes())), None);
+}
+
+#[test]
+fn test_parse_malformed_call() {
+    let invalid = |field| Some(Err((RejectReason::InvalidTx, Some(field))));
+    assert_eq!(parsed(&call_tx(b"call:oracle")), invalid("outputs_data[0]"));
+    assert_eq!(
+        parsed(&call_tx(b"call: set_price")),
+        invalid("outputs_data[0]")
+    );
+    assert_eq!(
+        parsed(&call_tx(b"call:\xff set")),
+        invalid("outputs_data[0]")
+    );
+
+    let mut data = b"call:oracle set_price ".to_vec();
+    data.resize(data.len() + MAX_CALL_PAYLOAD_LEN + 1, b'0');
+    assert_eq!(parsed(&call_tx(&data)), invalid("outputs_data[0]"));
+
+    // without a type script nothing tells who sent the call
+    let mut tx = call_tx(CALL_PREFIX);
+    tx.outputs[0].type_ = None;
+    assert_eq!(parsed(&tx), invalid("outputs[0].type"));
+    let args: Hex = hex_bytes(&[1]);
+    let mut tx = call_tx(b"call:oracle set_price");
+    tx.outputs[0].type_.as_mut().unwrap().args = args.clone();
+    assert_eq!(parsed(&tx).unwrap().unwrap().args, args);
+}
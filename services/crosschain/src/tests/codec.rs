@@ -0,0 +1,484 @@
+use std::collections::BTreeMap;
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{Address, Bytes, Hash, Hex};
+
+use crate::types::{
+    AssetVolume, BlockMintUsage, BridgeLimits, BridgeStats, BurnBatch, BurnQuota, BurnQuotaUsage,
+    BurnRecord, BurnStatus, CallTarget, ChainBinding, CkbHeader, CkbHeaderInner, CkbMessage,
+    EpochSummary, FeeBeneficiary, FeeSchedule, FeeTier, HeaderBounds, MirrorAsset, MmrState,
+    OutboundBlock, OutboundMessage, PendingMessage, PendingMessages, ProcessedDeposit,
+    RecoveredDeposit, Script, ScriptHashType, SupplyCap, SupportedLocks, WithdrawalFeeConfig,
+    HEADER_CODEC_VERSION,
+};
+
+fn round_trip<T: FixedCodec>(value: &T) -> T {
+    let bytes = value.encode_fixed().unwrap();
+    let decoded = T::decode_fixed(bytes.clone()).unwrap();
+    assert_eq!(decoded.encode_fixed().unwrap(), bytes);
+    decoded
+}
+
+fn hash(s: &'static str) -> Hash {
+    Hash::digest(Bytes::from(s))
+}
+
+#[test]
+fn test_mirror_asset_round_trip() {
+    let mirror_asset = round_trip(&MirrorAsset {
+        sudt_args:       hash("sudt"),
+        asset_id:        hash("asset"),
+        name:            "mirror".to_owned(),
+        symbol:          "MRR".to_owned(),
+        decimals:        8,
+        scale_factor:    100,
+        min_burn_amount: u128::max_value(),
+    });
+
+    assert_eq!(mirror_asset.sudt_args, hash("sudt"));
+    assert_eq!(mirror_asset.scale_factor, 100);
+    assert_eq!(mirror_asset.min_burn_amount, u128::max_value());
+}
+
+#[test]
+fn test_burn_record_round_trip() {
+    let mut record = BurnRecord {
+        nonce:        3,
+        asset_id:     hash("asset"),
+        sudt_args:    hash("sudt"),
+        muta_sender:  Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap(),
+        ckb_receiver: "ckb1qyq".to_owned(),
+        amount:       u128::max_value(),
+        sudt_amount:  1,
+        status:       BurnStatus::Pending,
+        ckb_tx_hash:  None,
+    };
+    let decoded = round_trip(&record);
+    assert_eq!(decoded.amount, u128::max_value());
+    assert_eq!(decoded.sudt_amount, 1);
+    assert_eq!(decoded.status, BurnStatus::Pending);
+    assert!(decoded.ckb_tx_hash.is_none());
+
+    record.status = BurnStatus::Completed;
+    record.ckb_tx_hash = Some(hash("tx"));
+    let decoded = round_trip(&record);
+    assert_eq!(decoded.status, BurnStatus::Completed);
+    assert_eq!(decoded.ckb_tx_hash, Some(hash("tx")));
+}
+
+#[test]
+fn test_burn_quota_round_trip() {
+    let quota = round_trip(&BurnQuota {
+        quota:  u128::max_value(),
+        window: 100,
+    });
+    assert_eq!(quota.quota, u128::max_value());
+    assert_eq!(quota.window, 100);
+
+    let usage = round_trip(&BurnQuotaUsage {
+        burns: vec![(1, 10), (2, u128::max_value())],
+    });
+    assert_eq!(usage.burns, vec![(1, 10), (2, u128::max_value())]);
+}
+
+#[test]
+fn test_withdrawal_fee_config_round_trip() {
+    let config = WithdrawalFeeConfig {
+        native_asset_id: hash("native"),
+        treasury:        Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap(),
+    };
+    assert_eq!(round_trip(&config), config);
+}
+
+#[test]
+fn test_bridge_stats_round_trip() {
+    let mut rejected_messages = BTreeMap::new();
+    rejected_messages.insert("invalid_tx".to_owned(), 2);
+    let stats = BridgeStats {
+        total_mints: 1,
+        total_burns: 2,
+        headers_stored: 3,
+        rejected_messages,
+        volumes: vec![AssetVolume {
+            asset_id: hash("asset"),
+            minted:   u128::max_value(),
+            burned:   5,
+        }],
+    };
+
+    assert_eq!(round_trip(&stats), stats);
+    assert_eq!(round_trip(&BridgeStats::default()), BridgeStats::default());
+}
+
+#[test]
+fn test_header_round_trip() {
+    let header = round_trip(&CkbHeaderInner {
+        compact_target:    0x1a08_a97e,
+        version:           0,
+        timestamp:         1_557_311_767,
+        number:            1024,
+        epoch:             0x0700_0000_0001,
+        parent_hash:       hash("parent"),
+        transactions_root: hash("transactions"),
+        proposals_hash:    hash("proposals"),
+        uncles_hash:       hash("uncles"),
+        dao:               hash("dao"),
+        nonce:             u128::max_value(),
+    });
+
+    assert_eq!(header.number, 1024);
+    assert_eq!(header.transactions_root, hash("transactions"));
+    assert_eq!(header.nonce, u128::max_value());
+}
+
+#[test]
+fn test_raw_header_round_trip() {
+    let hex = |s: &str| serde_json::from_str::<Hex>(&format!("\"{}\"", s)).unwrap();
+    let header = round_trip(&CkbHeader {
+        compact_target:    hex("0x1a08a97e"),
+        version:           hex("0x0"),
+        timestamp:         hex("0x16e71002eff"),
+        number:            hex("0x400"),
+        epoch:             hex("0x7080291000032"),
+        parent_hash:       hash("parent"),
+        transactions_root: hash("transactions"),
+        proposals_hash:    hash("proposals"),
+        uncles_hash:       hash("uncles"),
+        dao:               hash("dao"),
+        nonce:             hex("0x58df949326a72a42000002db8d3b3ebb"),
+    });
+
+    // Kept verbatim, odd length included.
+    assert_eq!(header.version.as_string(), "0x0");
+    assert_eq!(header.number.as_string(), "0x400");
+    assert_eq!(header.dao, hash("dao"));
+}
+
+#[test]
+fn test_malformed_u128_rejected() {
+    for len in &[0usize, 15, 17] {
+        let mut s = rlp::RlpStream::new_list(2);
+        s.append(&vec![0u8; *len]).append(&100u64);
+
+        assert!(BurnQuota::decode_fixed(Bytes::from(s.out())).is_err());
+    }
+
+    let mut s = rlp::RlpStream::new_list(1);
+    s.begin_list(2).append(&1u64).append(&vec![0u8; 8]);
+    assert!(BurnQuotaUsage::decode_fixed(Bytes::from(s.out())).is_err());
+}
+
+#[test]
+fn test_legacy_header_decode() {
+    let mut s = rlp::RlpStream::new_list(11);
+    s.append(&0x1a08_a97eu32)
+        .append(&0u32)
+        .append(&1_557_311_767u64)
+        .append(&1024u64)
+        .append(&0x0700_0000_0001u64)
+        .append(&hash("parent"))
+        .append(&hash("transactions"))
+        .append(&hash("proposals"))
+        .append(&hash("uncles"))
+        .append(&hash("dao"))
+        .append(&vec![1u8; 16]);
+
+    let header = CkbHeaderInner::decode_fixed(Bytes::from(s.out())).unwrap();
+    assert_eq!(header.number, 1024);
+    assert_eq!(header.epoch, 0x0700_0000_0001);
+    assert_eq!(header.transactions_root, hash("transactions"));
+    // The legacy nonce slot held the epoch, it is not carried over.
+    assert_eq!(header.nonce, 0);
+
+    // Re-encoding upgrades the entry to the versioned layout.
+    let bytes = header.encode_fixed().unwrap();
+    assert_eq!(rlp::Rlp::new(bytes.as_ref()).item_count().unwrap(), 12);
+}
+
+#[test]
+fn test_unknown_header_version_rejected() {
+    let header = CkbHeaderInner {
+        compact_target:    0,
+        version:           0,
+        timestamp:         0,
+        number:            0,
+        epoch:             0,
+        parent_hash:       hash("parent"),
+        transactions_root: hash("transactions"),
+        proposals_hash:    hash("proposals"),
+        uncles_hash:       hash("uncles"),
+        dao:               hash("dao"),
+        nonce:             0,
+    };
+    let encoded = header.encode_fixed().unwrap();
+    let items = rlp::Rlp::new(encoded.as_ref());
+
+    let mut s = rlp::RlpStream::new_list(12);
+    s.append(&(HEADER_CODEC_VERSION + 1));
+    for i in 1..12 {
+        s.append_raw(items.at(i).unwrap().as_raw(), 1);
+    }
+    let bytes = s.out();
+
+    assert!(CkbHeaderInner::decode_fixed(Bytes::from(bytes)).is_err());
+}
+
+#[test]
+fn test_pending_messages_round_trip() {
+    let relayer = Address::from_hex("0xc4b0000000000000000000000000000000000001").unwrap();
+    let message: CkbMessage = serde_json::from_str(r#"{"raw_tx": "0x1234"}"#).unwrap();
+    // odd length and empty hex kept as submitted
+    let script =
+        serde_json::json!({ "code_hash": hash("code"), "hash_type": "type", "args": "0x" });
+    let tx_message = serde_json::json!({
+        "tx": {
+            "version": "0x0",
+            "cell_deps": [{
+                "out_point": { "tx_hash": hash("dep"), "index": "0x1" },
+                "dep_type": "depgroup",
+            }],
+            "header_deps": [hash("header")],
+            "inputs": [{
+                "since": "0x0",
+                "previous_output": { "tx_hash": hash("input"), "index": "0x0" },
+            }],
+            "outputs": [
+                { "capacity": "0x174876e800", "lock": script, "type": script },
+                { "capacity": "0x0", "lock": script, "type": null },
+            ],
+            "outputs_data": ["0x", "0x10"],
+            "witnesses": ["0x55"],
+        },
+        "raw_tx": null,
+        "proof": {
+            "index": 2,
+            "lemmas": [hash("lemma")],
+            "witnesses_root": hash("witnesses"),
+            "witness_lemmas": [],
+        },
+    });
+    let pending = round_trip(&PendingMessages {
+        messages: vec![
+            PendingMessage {
+                relayer: relayer.clone(),
+                message: message.clone(),
+            },
+            PendingMessage {
+                relayer: relayer.clone(),
+                message: CkbMessage {
+                    tx:     None,
+                    raw_tx: None,
+                    proof:  None,
+                },
+            },
+            PendingMessage {
+                relayer: relayer.clone(),
+                message: serde_json::from_value(tx_message.clone()).unwrap(),
+            },
+        ],
+    });
+
+    assert_eq!(pending.messages.len(), 3);
+    assert_eq!(pending.messages[0].relayer, relayer);
+    assert_eq!(pending.messages[0].message.raw_tx, message.raw_tx);
+    assert!(pending.messages[1].message.raw_tx.is_none());
+    assert_eq!(
+        serde_json::to_value(&pending.messages[2].message).unwrap(),
+        tx_message
+    );
+}
+
+#[test]
+fn test_recovered_deposit_round_trip() {
+    let deposit = RecoveredDeposit {
+        asset_id:      hash("asset"),
+        amount:        u128::max_value(),
+        reassigned_to: None,
+    };
+    assert_eq!(round_trip(&deposit), deposit);
+
+    let reassigned = RecoveredDeposit {
+        reassigned_to: Some(
+            Address::from_hex("0xc4b0000000000000000000000000000000000002").unwrap(),
+        ),
+        ..deposit
+    };
+    assert_eq!(round_trip(&reassigned), reassigned);
+}
+
+#[test]
+fn test_header_bounds_round_trip() {
+    let bounds = HeaderBounds {
+        lowest:  7,
+        highest: u64::max_value(),
+    };
+    assert_eq!(round_trip(&bounds), bounds);
+}
+
+#[test]
+fn test_supported_locks_round_trip() {
+    let locks = SupportedLocks {
+        code_hashes: vec![hash("secp256k1"), hash("multisig")],
+    };
+    assert_eq!(round_trip(&locks), locks);
+    assert!(round_trip(&SupportedLocks {
+        code_hashes: vec![],
+    })
+    .code_hashes
+    .is_empty());
+}
+
+#[test]
+fn test_bridge_limits_round_trip() {
+    let limits = BridgeLimits {
+        max_batch_messages: 64,
+        block_mint_budget:  u128::max_value(),
+    };
+    assert_eq!(round_trip(&limits), limits);
+    assert_eq!(
+        round_trip(&BridgeLimits::default()),
+        BridgeLimits::default()
+    );
+
+    let treasury = Address::from_hex("0xc4b0000000000000000000000000000000000002").unwrap();
+    for beneficiary in &[
+        FeeBeneficiary::TxCaller,
+        FeeBeneficiary::FixedAddress(treasury.clone()),
+        FeeBeneficiary::Split {
+            caller_bps: 2_500,
+            treasury,
+            treasury_bps: 7_500,
+        },
+    ] {
+        assert_eq!(&round_trip(beneficiary), beneficiary);
+    }
+
+    let schedule = FeeSchedule {
+        tiers: vec![
+            FeeTier {
+                threshold: 0,
+                bps:       100,
+            },
+            FeeTier {
+                threshold: u128::max_value(),
+                bps:       10_000,
+            },
+        ],
+    };
+    assert_eq!(round_trip(&schedule), schedule);
+
+    let mut summary = EpochSummary {
+        epoch:        3,
+        start_height: 3_000,
+        end_height:   3_999,
+        closed:       false,
+        burns:        2,
+        first_nonce:  7,
+        last_nonce:   8,
+        assets:       vec![],
+    };
+    let asset = summary.asset_mut(&hash("asset"), &hash("sudt"));
+    asset.amount = u128::max_value();
+    asset.lock_mut(&hash("secp256k1")).sudt_amount = 1 << 100;
+    assert_eq!(round_trip(&summary), summary);
+
+    let batch = BurnBatch {
+        batch:        2,
+        start_height: 10,
+        end_height:   19,
+        first_nonce:  5,
+        last_nonce:   6,
+        leaves:       vec![hash("burn 5"), hash("burn 6")],
+        root:         hash("root"),
+        sealed:       true,
+        announced:    true,
+    };
+    assert_eq!(round_trip(&batch), batch);
+
+    let binding = ChainBinding {
+        source_chain_id: hash("ckb"),
+        dest_chain_id:   hash("muta"),
+    };
+    assert_eq!(round_trip(&binding), binding);
+
+    let usage = BlockMintUsage {
+        height: 1024,
+        minted: 1 << 100,
+    };
+    assert_eq!(round_trip(&usage), usage);
+}
+
+#[test]
+fn test_supply_cap_round_trip() {
+    let supply_cap = SupplyCap {
+        cap:    u128::max_value(),
+        paused: true,
+    };
+    assert_eq!(round_trip(&supply_cap), supply_cap);
+    assert_eq!(round_trip(&SupplyCap::default()), SupplyCap::default());
+}
+
+#[test]
+fn test_processed_deposit_round_trip() {
+    let deposit = ProcessedDeposit {
+        asset_id:  hash("asset"),
+        receiver:  Address::from_hex("0xc4b0000000000000000000000000000000000002").unwrap(),
+        amount:    u128::max_value(),
+        relay_fee: 1,
+        relayer:   Address::from_hex("0xc4b0000000000000000000000000000000000003").unwrap(),
+        memo:      "order 42".to_owned(),
+    };
+    assert_eq!(round_trip(&deposit), deposit);
+}
+
+#[test]
+fn test_mmr_state_round_trip() {
+    let mmr = MmrState {
+        start:  100,
+        leaves: 3,
+        peaks:  vec![hash("peak 0"), hash("peak 1")],
+    };
+    assert_eq!(round_trip(&mmr), mmr);
+    assert_eq!(round_trip(&MmrState::default()), MmrState::default());
+}
+
+#[test]
+fn test_call_target_round_trip() {
+    let target = CallTarget {
+        service: "oracle".to_owned(),
+        methods: vec!["set_price".to_owned(), "pause".to_owned()],
+    };
+    assert_eq!(round_trip(&target), target);
+}
+
+#[test]
+fn test_outbound_message_round_trip() {
+    let hex = |s: &str| -> Hex {
+        serde_json::from_value(serde_json::Value::String(s.to_owned())).unwrap()
+    };
+    let message = OutboundMessage {
+        nonce:         3,
+        sender:        "oracle".to_owned(),
+        target_script: Script {
+            code_hash: hash("contract"),
+            hash_type: ScriptHashType::Type,
+            args:      hex("0x"),
+        },
+        payload:       hex("0x68656c6c6f"),
+        height:        12,
+    };
+    let decoded = round_trip(&message);
+    assert_eq!(
+        serde_json::to_value(&decoded).unwrap(),
+        serde_json::to_value(&message).unwrap()
+    );
+
+    let block = OutboundBlock {
+        height:      12,
+        first_nonce: 3,
+        last_nonce:  4,
+        leaves:      vec![hash("first"), hash("second")],
+        root:        hash("root"),
+    };
+    assert_eq!(round_trip(&block), block);
+}
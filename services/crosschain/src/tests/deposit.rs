@@ -0,0 +1,309 @@
+use protocol::types::{Address, Bytes, Hash, Hex};
+use testing::strategy::{hex_bytes, sudt_amount_data};
+
+use crate::deposit::{
+    deposit_tag, fee_bps, fee_shares, outcome, process, relay_fee, tag_witness, valid_beneficiary,
+    valid_schedule, DepositConfig, DepositOutcome, TAG_PREFIX,
+};
+use crate::types::{
+    CellOutput, CkbTx, Deposit, FeeBeneficiary, FeeSchedule, FeeTier, MessageError, MirrorAsset,
+    RejectReason, Script, ScriptHashType,
+};
+use crate::{parse_deposit, MAX_MEMO_LEN, SUDT_CODE_HASH};
+
+fn sudt_args() -> Hash {
+    Hash::digest(Bytes::from("sudt"))
+}
+
+fn receiver() -> Address {
+    Address::from_hex("0x0000000000000000000000000000000000000001").unwrap()
+}
+
+fn recovery_account() -> Address {
+    Address::from_hex("0xc4b0000000000000000000000000000000000001").unwrap()
+}
+
+fn mirror_asset(scale_factor: u64) -> MirrorAsset {
+    MirrorAsset {
+        sudt_args: sudt_args(),
+        asset_id: Hash::digest(Bytes::from("asset")),
+        name: "mirror".to_owned(),
+        symbol: "MRR".to_owned(),
+        decimals: 8,
+        scale_factor,
+        min_burn_amount: 0,
+    }
+}
+
+fn deposit_tx() -> CkbTx {
+    let type_ = Script {
+        code_hash: Hash::from_hex(SUDT_CODE_HASH).unwrap(),
+        hash_type: ScriptHashType::data,
+        args:      Hex::from_string(sudt_args().as_hex()).unwrap(),
+    };
+
+    CkbTx {
+        version:      hex_bytes(&[0]),
+        cell_deps:    vec![],
+        header_deps:  vec![],
+        inputs:       vec![],
+        outputs:      vec![CellOutput {
+            capacity: hex_bytes(&[0]),
+            lock:     Script {
+                code_hash: Hash::digest(Bytes::from("lock")),
+                hash_type: ScriptHashType::data,
+                args:      hex_bytes(&[]),
+            },
+            type_:    Some(type_),
+        }],
+        outputs_data: vec![sudt_amount_data(1000)],
+        witnesses:    vec![hex_bytes(receiver().as_bytes().as_ref())],
+    }
+}
+
+fn rejection(tx: &CkbTx) -> (RejectReason, Option<&'static str>) {
+    let MessageError { reason, field, .. } = parse_deposit(tx).unwrap_err();
+    (reason, field)
+}
+
+#[test]
+fn test_parse_deposit() {
+    let deposit = parse_deposit(&deposit_tx()).unwrap();
+
+    assert_eq!(deposit.sudt_args, sudt_args());
+    assert_eq!(deposit.sudt_amount, 1000);
+    assert_eq!(deposit.receiver, Some(receiver()));
+    assert_eq!(deposit.memo, "");
+}
+
+#[test]
+fn test_parse_tagged_deposit() {
+    let tag = deposit_tag(&receiver());
+    assert!(tag < 1 << 48);
+    assert_ne!(tag, deposit_tag(&recovery_account()));
+
+    let mut tx = deposit_tx();
+    let mut witness = tag_witness(tag);
+    witness.extend_from_slice(b"memo");
+    tx.witnesses = vec![hex_bytes(&witness)];
+    let deposit = parse_deposit(&tx).unwrap();
+    assert_eq!(deposit.receiver, None);
+    assert_eq!(deposit.tag, Some(tag));
+    assert_eq!(deposit.memo, "memo");
+
+    // a cut tag names no one, the deposit is recovered
+    tx.witnesses = vec![hex_bytes(&witness[..TAG_PREFIX.len() + 3])];
+    let deposit = parse_deposit(&tx).unwrap();
+    assert_eq!((deposit.receiver, deposit.tag), (None, None));
+
+    let deposit = parse_deposit(&deposit_tx()).unwrap();
+    assert_eq!(deposit.tag, None);
+}
+
+#[test]
+fn test_parse_malformed_deposit() {
+    let mut tx = deposit_tx();
+    tx.outputs.clear();
+    assert_eq!(
+        rejection(&tx),
+        (RejectReason::InvalidTx, Some("outputs[0]"))
+    );
+
+    let mut tx = deposit_tx();
+    tx.outputs[0].type_ = None;
+    assert_eq!(
+        rejection(&tx),
+        (RejectReason::InvalidTx, Some("outputs[0].type"))
+    );
+
+    let mut tx = deposit_tx();
+    tx.outputs[0].type_.as_mut().unwrap().code_hash = Hash::digest(Bytes::from("other"));
+    assert_eq!(
+        rejection(&tx),
+        (RejectReason::InvalidTx, Some("outputs[0].type.code_hash"))
+    );
+
+    let mut tx = deposit_tx();
+    tx.outputs[0].type_.as_mut().unwrap().args = hex_bytes(&[1; 20]);
+    assert_eq!(
+        rejection(&tx),
+        (RejectReason::InvalidTx, Some("outputs[0].type.args"))
+    );
+
+    let mut tx = deposit_tx();
+    tx.outputs_data.clear();
+    assert_eq!(
+        rejection(&tx),
+        (RejectReason::InvalidTx, Some("outputs_data[0]"))
+    );
+
+    let mut tx = deposit_tx();
+    tx.outputs_data[0] = hex_bytes(&[1; 8]);
+    assert_eq!(
+        rejection(&tx),
+        (RejectReason::InvalidAmount, Some("outputs_data[0]"))
+    );
+
+    let mut tx = deposit_tx();
+    tx.witnesses.clear();
+    assert_eq!(rejection(&tx), (RejectReason::InvalidTx, Some("witnesses")));
+}
+
+#[test]
+fn test_parse_unreadable_receiver() {
+    // short of an address
+    let mut tx = deposit_tx();
+    tx.witnesses[0] = hex_bytes(&[1; 19]);
+    assert_eq!(parse_deposit(&tx).unwrap().receiver, None);
+
+    // memo not utf8
+    let mut witness = receiver().as_bytes().to_vec();
+    witness.push(0xff);
+    tx.witnesses[0] = hex_bytes(&witness);
+    assert_eq!(parse_deposit(&tx).unwrap().receiver, None);
+
+    // memo too long
+    let mut witness = receiver().as_bytes().to_vec();
+    witness.extend(std::iter::repeat(b'm').take(MAX_MEMO_LEN + 1));
+    tx.witnesses[0] = hex_bytes(&witness);
+    let deposit = parse_deposit(&tx).unwrap();
+    assert_eq!(deposit.receiver, None);
+    assert_eq!(deposit.memo, "");
+}
+
+#[test]
+fn test_process_deposit() {
+    let mirror_asset = mirror_asset(10);
+    let recovery_account = recovery_account();
+    let config = DepositConfig {
+        mirror_asset:     &mirror_asset,
+        fee_schedule:     &FeeSchedule::default(),
+        recovery_account: &recovery_account,
+    };
+    let deposit = |receiver: Option<Address>| Deposit {
+        sudt_args: sudt_args(),
+        sudt_amount: 10_005,
+        receiver,
+        tag: None,
+        memo: "memo".to_owned(),
+    };
+
+    assert_eq!(
+        process(deposit(Some(receiver())), &config),
+        DepositOutcome {
+            receiver:  receiver(),
+            memo:      "memo".to_owned(),
+            recovered: false,
+            minted:    1000,
+            dust:      5,
+            relay_fee: 10,
+            credited:  990,
+        }
+    );
+
+    // the memo was meant for the receiver, it isn't kept for the recovery
+    let recovered = process(deposit(None), &config);
+    assert_eq!(recovered.receiver, recovery_account);
+    assert_eq!(recovered.memo, "");
+    assert!(recovered.recovered);
+    assert_eq!(recovered.credited, 990);
+}
+
+#[test]
+fn test_deposit_outcome_split() {
+    let mirror_asset = mirror_asset(1);
+    for &amount in &[0, 1, 99, 100, 12_345, u128::max_value()] {
+        let split = outcome(
+            &mirror_asset,
+            &FeeSchedule::default(),
+            receiver(),
+            String::new(),
+            amount,
+            false,
+        );
+        assert_eq!(split.dust, 0);
+        assert_eq!(split.credited + split.relay_fee, amount);
+    }
+
+    // less than one mirror unit mints nothing and is all dust
+    let split = outcome(
+        &mirror_asset(100),
+        &FeeSchedule::default(),
+        receiver(),
+        String::new(),
+        99,
+        false,
+    );
+    assert_eq!((split.minted, split.dust, split.credited), (0, 99, 0));
+}
+
+#[test]
+fn test_fee_shares() {
+    let relayer = receiver();
+    let treasury = recovery_account();
+    let split = |caller_bps, treasury_bps| FeeBeneficiary::Split {
+        caller_bps,
+        treasury: treasury.clone(),
+        treasury_bps,
+    };
+
+    assert_eq!(fee_shares(&FeeBeneficiary::TxCaller, &relayer, 10), vec![(
+        relayer.clone(),
+        10
+    )]);
+    assert_eq!(
+        fee_shares(
+            &FeeBeneficiary::FixedAddress(treasury.clone()),
+            &relayer,
+            10
+        ),
+        vec![(treasury.clone(), 10)]
+    );
+    assert_eq!(fee_shares(&split(2_500, 7_500), &relayer, 10), vec![
+        (relayer.clone(), 3),
+        (treasury.clone(), 7)
+    ]);
+
+    // shares of the largest fee neither overflow nor lose a unit
+    let fee = u128::max_value() / 100;
+    let shares = fee_shares(&split(1, 9_999), &relayer, fee);
+    assert_eq!(shares[0].1 + shares[1].1, fee);
+    assert_eq!(fee_shares(&split(0, 10_000), &relayer, fee)[1].1, fee);
+
+    assert!(valid_beneficiary(&split(0, 10_000)));
+    assert!(!valid_beneficiary(&split(5_000, 4_999)));
+    assert!(!valid_beneficiary(&split(u16::max_value(), 10_000)));
+    assert!(valid_beneficiary(&FeeBeneficiary::TxCaller));
+}
+
+#[test]
+fn test_fee_tiers() {
+    let tier = |threshold, bps| FeeTier { threshold, bps };
+    let schedule = FeeSchedule {
+        tiers: vec![tier(0, 100), tier(10_000, 50), tier(1_000_000, 10)],
+    };
+    assert!(valid_schedule(&schedule));
+
+    assert_eq!(fee_bps(&schedule, 0), 100);
+    assert_eq!(fee_bps(&schedule, 9_999), 100);
+    assert_eq!(fee_bps(&schedule, 10_000), 50);
+    assert_eq!(fee_bps(&schedule, u128::max_value()), 10);
+    assert_eq!(relay_fee(&schedule, 9_999), 99);
+    assert_eq!(relay_fee(&schedule, 10_000), 50);
+    assert_eq!(relay_fee(&schedule, 2_000_000), 2_000);
+    assert_eq!(
+        relay_fee(&schedule, u128::max_value()),
+        u128::max_value() / 1_000
+    );
+
+    let invalid = |tiers| !valid_schedule(&FeeSchedule { tiers });
+    assert!(invalid(vec![]));
+    // amounts below the first threshold would fall in no tier
+    assert!(invalid(vec![tier(1, 100)]));
+    assert!(invalid(vec![tier(0, 100), tier(10, 50), tier(10, 10)]));
+    assert!(invalid(vec![tier(0, 10_001)]));
+    assert!(invalid((0..17).map(|t| tier(t, 1)).collect()));
+    assert!(valid_schedule(&FeeSchedule {
+        tiers: vec![tier(0, 10_000)],
+    }));
+}
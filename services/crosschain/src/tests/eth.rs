@@ -0,0 +1,161 @@
+use protocol::types::{Address, Bytes, Hash};
+
+use crate::eth::{
+    decode_header, keccak256, lock_event_topic, lock_events, token_key, verify_proof,
+};
+use crate::light_client::{EthLightClient, LightClient};
+use crate::types::{EthHeader, EthReceiptProof, EthSyncMode};
+
+fn hash(data: &[u8]) -> Hash {
+    Hash::from_bytes(Bytes::from(data.to_vec())).unwrap()
+}
+
+fn word(data: &[u8]) -> Vec<u8> {
+    let mut word = vec![0u8; 32 - data.len()];
+    word.extend_from_slice(data);
+    word
+}
+
+fn receipt(contract: &[u8], token: &[u8], receiver: &[u8], amount: u128) -> Vec<u8> {
+    let mut data = word(receiver);
+    data.extend(word(&amount.to_be_bytes()));
+
+    let mut s = rlp::RlpStream::new_list(4);
+    s.append(&1u8).append(&21000u64).append(&vec![0u8; 256]);
+    s.begin_list(1).begin_list(3).append(&contract.to_vec());
+    s.begin_list(3)
+        .append(&lock_event_topic().to_vec())
+        .append(&word(token))
+        .append(&word(&[9u8; 20]));
+    s.append(&data);
+
+    // typed receipt of an EIP-1559 tx
+    let mut receipt = vec![2u8];
+    receipt.extend_from_slice(&s.out());
+    receipt
+}
+
+fn node(items: &[&[u8]]) -> Vec<u8> {
+    let mut s = rlp::RlpStream::new_list(items.len());
+    for item in items {
+        s.append(&item.to_vec());
+    }
+    s.out().to_vec()
+}
+
+#[test]
+fn test_verify_proof() {
+    // Keys 0x01 and 0x02 share the first nibble: an extension to a branch
+    // holding an embedded short leaf and a hashed long one.
+    let short_value = b"short".to_vec();
+    let long_value = vec![7u8; 64];
+    let short_leaf = node(&[&[0x20], &short_value]);
+    let long_leaf = node(&[&[0x20], &long_value]);
+    let long_leaf_hash = keccak256(&long_leaf);
+
+    let mut s = rlp::RlpStream::new_list(17);
+    s.append_empty_data();
+    s.append_raw(&short_leaf, 1);
+    s.append(&long_leaf_hash.to_vec());
+    for _ in 3..17 {
+        s.append_empty_data();
+    }
+    let branch = s.out().to_vec();
+    let extension = node(&[&[0x10], &keccak256(&branch)]);
+    let root = hash(&keccak256(&extension));
+
+    let nodes = vec![extension.clone(), branch.clone(), long_leaf];
+    assert_eq!(
+        verify_proof(&root, &[0x01], &nodes).unwrap(),
+        Some(short_value)
+    );
+    assert_eq!(
+        verify_proof(&root, &[0x02], &nodes).unwrap(),
+        Some(long_value)
+    );
+    assert_eq!(verify_proof(&root, &[0x03], &nodes).unwrap(), None);
+    assert_eq!(verify_proof(&root, &[0x11], &nodes).unwrap(), None);
+    // The long leaf is missing from the proof.
+    assert_eq!(
+        verify_proof(&root, &[0x02], &[extension, branch]).unwrap(),
+        None
+    );
+    assert_eq!(
+        verify_proof(&hash(&[0u8; 32]), &[0x01], &nodes).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_lock_events() {
+    let contract = [1u8; 20];
+    let token = [2u8; 20];
+    let receiver = [3u8; 20];
+    let receipt = receipt(&contract, &token, &receiver, 1000);
+
+    let locks = lock_events(&receipt, &contract).unwrap();
+    assert_eq!(locks.len(), 1);
+    assert_eq!(locks[0].token, token_key(&token));
+    assert_eq!(
+        locks[0].receiver,
+        Address::from_bytes(Bytes::from(receiver.to_vec())).unwrap()
+    );
+    assert_eq!(locks[0].amount, 1000);
+
+    // Events from other contracts are ignored.
+    assert!(lock_events(&receipt, &[4u8; 20]).unwrap().is_empty());
+    assert!(lock_events(&[0xc0], &contract).is_err());
+}
+
+#[test]
+fn test_eth_light_client() {
+    let contract = [1u8; 20];
+    let receipt = receipt(&contract, &[2u8; 20], &[3u8; 20], 1000);
+    // rlp(0) is 0x80, its two nibbles make up the leaf path.
+    let leaf = node(&[&[0x20, 0x80], &receipt]);
+
+    let mut s = rlp::RlpStream::new_list(15);
+    s.append(&vec![5u8; 32]); // parent hash
+    for _ in 0..4 {
+        s.append(&vec![0u8; 32]);
+    }
+    s.append(&keccak256(&leaf).to_vec()); // receipts root
+    s.append(&vec![0u8; 256]);
+    s.append(&0u8); // difficulty
+    s.append(&100u64); // number
+    for _ in 9..15 {
+        s.append(&0u8);
+    }
+    let raw = s.out();
+    let header = decode_header(&raw).unwrap();
+    assert_eq!(header.number, 100);
+    assert_eq!(header.difficulty, 0);
+    assert_eq!(header.hash, hash(&keccak256(&raw)));
+
+    let client = EthLightClient {
+        mode: EthSyncMode::SyncCommittee,
+    };
+    let proof = EthReceiptProof {
+        height:   100,
+        tx_index: 0,
+        receipt:  serde_json::from_str(&format!("\"0x{}\"", hex::encode(&receipt))).unwrap(),
+        nodes:    vec![serde_json::from_str(&format!("\"0x{}\"", hex::encode(&leaf))).unwrap()],
+    };
+    assert!(client.verify_inclusion(&header, &receipt, &proof).unwrap());
+    assert!(!client.verify_inclusion(&header, &leaf, &proof).unwrap());
+
+    let parent = EthHeader {
+        hash:          hash(&[5u8; 32]),
+        parent_hash:   hash(&[4u8; 32]),
+        receipts_root: hash(&[0u8; 32]),
+        difficulty:    0,
+        number:        99,
+    };
+    assert!(client.verify_header(&header, Some(&parent)).is_ok());
+    assert!(client.verify_header(&parent, Some(&header)).is_err());
+
+    let pow_client = EthLightClient {
+        mode: EthSyncMode::Pow,
+    };
+    assert!(pow_client.verify_header(&header, None).is_err());
+}
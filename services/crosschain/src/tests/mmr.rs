@@ -0,0 +1,91 @@
+use protocol::types::{Bytes, Hash};
+
+use crate::mmr::{append, bag_peaks, verify};
+use crate::proof::merge;
+use crate::types::MmrProof;
+
+fn leaf(i: u64) -> Hash {
+    Hash::digest(Bytes::from(i.to_le_bytes().to_vec()))
+}
+
+// Builds the range the slow way, each tree from its leaves, and proves `index`.
+fn naive(leaves: &[Hash], index: usize) -> (Hash, MmrProof) {
+    let mut peaks = vec![];
+    let mut siblings = vec![];
+    let mut offset = 0;
+    for height in (0..64).rev() {
+        let size = 1usize << height;
+        if leaves.len() & size == 0 {
+            continue;
+        }
+
+        let mut level = leaves[offset..offset + size].to_vec();
+        let mut pos = index.wrapping_sub(offset);
+        while level.len() > 1 {
+            if pos < level.len() {
+                siblings.push(level[pos ^ 1].clone());
+                pos >>= 1;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| merge(&pair[0], &pair[1]).unwrap())
+                .collect();
+        }
+        peaks.push(level[0].clone());
+        offset += size;
+    }
+
+    let root = bag_peaks(&peaks).unwrap().unwrap();
+    let proof = MmrProof {
+        leaves: leaves.len() as u64,
+        siblings,
+        peaks,
+    };
+    (root, proof)
+}
+
+#[test]
+fn test_append_matches_naive_root() {
+    let mut peaks = vec![];
+    let mut leaves = vec![];
+    assert!(bag_peaks(&peaks).unwrap().is_none());
+
+    for i in 0..40 {
+        append(&mut peaks, i, leaf(i)).unwrap();
+        leaves.push(leaf(i));
+
+        let (root, proof) = naive(&leaves, 0);
+        assert_eq!(peaks, proof.peaks);
+        assert_eq!(bag_peaks(&peaks).unwrap(), Some(root));
+    }
+}
+
+#[test]
+fn test_verify() {
+    for size in 1..34u64 {
+        let leaves: Vec<Hash> = (0..size).map(leaf).collect();
+        for index in 0..size {
+            let (root, proof) = naive(&leaves, index as usize);
+            assert!(verify(&root, &leaves[index as usize], index, &proof).unwrap());
+
+            // another leaf, position or root
+            let other = (index + 1) % size;
+            if other != index {
+                assert!(!verify(&root, &leaves[other as usize], index, &proof).unwrap());
+                assert!(!verify(&root, &leaves[index as usize], other, &proof).unwrap());
+            }
+            assert!(!verify(&leaf(size), &leaves[index as usize], index, &proof).unwrap());
+            assert!(!verify(&root, &leaves[index as usize], size, &proof).unwrap());
+
+            // a tampered sibling or peak
+            if !proof.siblings.is_empty() {
+                let mut tampered = proof.clone();
+                tampered.siblings[0] = leaf(size);
+                assert!(!verify(&root, &leaves[index as usize], index, &tampered).unwrap());
+            }
+            let mut tampered = proof.clone();
+            tampered.peaks.pop();
+            assert!(!verify(&root, &leaves[index as usize], index, &tampered).unwrap());
+        }
+    }
+}
@@ -0,0 +1,445 @@
+mod address;
+mod call;
+mod codec;
+mod deposit;
+mod eth;
+mod mmr;
+mod molecule;
+mod muta;
+mod prop;
+
+use common::view::flatten;
+use protocol::types::{Address, Bytes, Hash, Hex};
+
+use crate::light_client::{CkbLightClient, LightClient};
+use crate::math::{checked_add_u128, checked_sub_u128};
+use crate::proof;
+use crate::schema::{schema, SCHEMA_TYPES};
+use crate::types::{
+    AssetVolume, BridgeStats, BurnQuote, CkbHeader, CkbHeaderInner, CkbTxProof, EpochView,
+    FeeSchedule, FieldError, HeightRange, InitGenesisPayload, MessageError, RejectReason,
+};
+use crate::view::BridgeStatsView;
+use crate::{expired, header_gaps, relay_fee};
+
+#[test]
+fn test_checked_math() {
+    assert_eq!(
+        checked_add_u128(u128::max_value() - 1, 1).unwrap(),
+        u128::max_value()
+    );
+    assert!(checked_add_u128(u128::max_value(), 1).is_err());
+    assert_eq!(checked_sub_u128(1, 1).unwrap(), 0);
+    assert!(checked_sub_u128(0, 1).is_err());
+}
+
+#[test]
+fn test_relay_fee() {
+    let flat = FeeSchedule::default();
+    assert_eq!(relay_fee(&flat, 0), 0);
+    assert_eq!(relay_fee(&flat, 99), 0);
+    assert_eq!(relay_fee(&flat, 1000), 10);
+    assert_eq!(relay_fee(&flat, u128::max_value()), u128::max_value() / 100);
+}
+
+#[test]
+fn test_header_nonce_parsing() {
+    let hex = |s: &str| serde_json::from_str::<Hex>(&format!("\"{}\"", s)).unwrap();
+    let header = CkbHeader {
+        compact_target:    hex("0x1a08a97e"),
+        version:           hex("0x0"),
+        timestamp:         hex("0x16e71002eff"),
+        number:            hex("0x400"),
+        epoch:             hex("0x7080291000032"),
+        parent_hash:       Hash::digest(Bytes::from("parent")),
+        transactions_root: Hash::digest(Bytes::from("transactions")),
+        proposals_hash:    Hash::digest(Bytes::from("proposals")),
+        uncles_hash:       Hash::digest(Bytes::from("uncles")),
+        dao:               Hash::digest(Bytes::from("dao")),
+        nonce:             hex("0x58df949326a72a42000002db8d3b3ebb"),
+    };
+    let inner = CkbHeaderInner::from(header.clone()).unwrap();
+
+    assert_eq!(inner.epoch, 0x7_0802_9100_0032);
+    assert_eq!(inner.nonce, 0x58df_9493_26a7_2a42_0000_02db_8d3b_3ebb);
+
+    let mut bad = header.clone();
+    bad.timestamp = hex("0x1ffffffffffffffff");
+    assert_eq!(CkbHeaderInner::from(bad).unwrap_err(), FieldError {
+        field: "timestamp",
+    });
+}
+
+#[test]
+fn test_message_error_context() {
+    let error = RejectReason::InvalidAmount
+        .at("outputs_data[0]")
+        .in_message(3);
+    assert_eq!(
+        error.to_string(),
+        "invalid_amount at message 3 in outputs_data[0]"
+    );
+    assert_eq!(
+        MessageError::from(RejectReason::DuplicateTx).to_string(),
+        "duplicate_tx"
+    );
+}
+
+#[test]
+fn test_epoch_view() {
+    // epoch 50, block 41 of 1800
+    let epoch = EpochView::from_packed(0x7080_2900_0032);
+    assert_eq!(epoch, EpochView {
+        number: 50,
+        index:  41,
+        length: 1800,
+    });
+    assert_eq!(epoch.to_packed(), 0x7080_2900_0032);
+    assert!(epoch.is_valid());
+    assert!(!EpochView::from_packed(0).is_valid());
+
+    let next = EpochView::from_packed(0x7080_2a00_0032);
+    assert!(next.is_successor_of(&epoch));
+    assert!(!epoch.is_successor_of(&next));
+
+    let last = EpochView {
+        number: 50,
+        index:  1799,
+        length: 1800,
+    };
+    let first = EpochView {
+        number: 51,
+        index:  0,
+        length: 1750,
+    };
+    assert!(first.is_successor_of(&last));
+    assert!(!first.is_successor_of(&epoch));
+}
+
+#[test]
+fn test_ckb_light_client() {
+    let client = CkbLightClient;
+    let tx_hash = Hash::digest(Bytes::from("tx"));
+    let sibling = Hash::digest(Bytes::from("sibling"));
+    let witnesses_root = Hash::digest(Bytes::from("witnesses"));
+
+    // Two leaves, the tx at tree index 1 with its sibling at index 2.
+    let raw_root = proof::merge(&tx_hash, &sibling).unwrap();
+    let mut header = CkbHeaderInner {
+        compact_target:    0,
+        version:           0,
+        timestamp:         0,
+        number:            10,
+        epoch:             EpochView {
+            number: 1,
+            index:  10,
+            length: 100,
+        }
+        .to_packed(),
+        parent_hash:       Hash::digest(Bytes::from("parent")),
+        transactions_root: proof::merge(&raw_root, &witnesses_root).unwrap(),
+        proposals_hash:    Hash::digest(Bytes::from("proposals")),
+        uncles_hash:       Hash::digest(Bytes::from("uncles")),
+        dao:               Hash::digest(Bytes::from("dao")),
+        nonce:             0,
+    };
+    let tx_proof = CkbTxProof {
+        index:          1,
+        lemmas:         vec![sibling],
+        witnesses_root: witnesses_root.clone(),
+        witness_lemmas: None,
+    };
+
+    assert_eq!(client.height(&header), 10);
+    assert!(client
+        .verify_inclusion(&header, &tx_hash, &tx_proof)
+        .unwrap());
+    assert!(!client
+        .verify_inclusion(&header, &witnesses_root, &tx_proof)
+        .unwrap());
+    assert!(!client.verify_witnesses(&tx_hash, &tx_proof).unwrap());
+
+    let parent = header.clone();
+    assert!(client.verify_header(&parent, None).is_ok());
+    header.number = 11;
+    header.epoch = EpochView {
+        number: 1,
+        index:  11,
+        length: 100,
+    }
+    .to_packed();
+    assert!(client.verify_header(&header, Some(&parent)).is_ok());
+    assert!(client.verify_header(&parent, Some(&header)).is_err());
+}
+
+// A ckb merkle tree of the leaves in array layout, and the lemmas proving
+// the leaves at `indices` as ckb builds them.
+fn cbmt_multi_proof(leaves: &[Hash], indices: &[u32]) -> (Vec<Hash>, Vec<Hash>) {
+    let n = leaves.len();
+    let mut nodes = vec![leaves[0].clone(); 2 * n - 1];
+    nodes[n - 1..].clone_from_slice(leaves);
+    for i in (0..n - 1).rev() {
+        nodes[i] = proof::merge(&nodes[2 * i + 1], &nodes[2 * i + 2]).unwrap();
+    }
+
+    let mut queue: Vec<u32> = indices.to_vec();
+    queue.sort_by(|a, b| b.cmp(a));
+    let mut queue: std::collections::VecDeque<u32> = queue.into_iter().collect();
+    let mut lemmas = vec![];
+    while let Some(index) = queue.pop_front() {
+        if index == 0 {
+            break;
+        }
+        let sibling = ((index + 1) ^ 1) - 1;
+        if queue.front() == Some(&sibling) {
+            queue.pop_front();
+        } else {
+            lemmas.push(nodes[sibling as usize].clone());
+        }
+        let parent = (index - 1) / 2;
+        if parent != 0 {
+            queue.push_back(parent);
+        }
+    }
+
+    (nodes, lemmas)
+}
+
+#[test]
+fn test_cbmt_multi_root() {
+    for n in 1..12usize {
+        let leaves: Vec<Hash> = (0..n)
+            .map(|i| Hash::digest(Bytes::from(format!("tx {}", i))))
+            .collect();
+        let tree_index = |i: usize| (i + n - 1) as u32;
+
+        for picked in 1..(1u32 << n) {
+            let chosen: Vec<usize> = (0..n).filter(|i| picked & (1 << i) != 0).collect();
+            let indices: Vec<u32> = chosen.iter().map(|i| tree_index(*i)).collect();
+            let (nodes, lemmas) = cbmt_multi_proof(&leaves, &indices);
+            let proven: Vec<(u32, Hash)> = chosen
+                .iter()
+                .map(|i| (tree_index(*i), leaves[*i].clone()))
+                .collect();
+
+            let root = proof::cbmt_multi_root(&proven, &lemmas).unwrap();
+            assert_eq!(root.as_ref(), Some(&nodes[0]));
+            assert_eq!(proof::cbmt_build_root(&leaves).unwrap(), nodes[0]);
+            if chosen.len() == 1 {
+                assert_eq!(
+                    proof::cbmt_root(&leaves[chosen[0]], indices[0], &lemmas).unwrap(),
+                    nodes[0]
+                );
+            }
+
+            // a leaf swapped for another, a lemma too many or missing
+            let mut wrong = proven.clone();
+            wrong[0].1 = Hash::digest(Bytes::from("other"));
+            assert_ne!(proof::cbmt_multi_root(&wrong, &lemmas).unwrap(), root);
+            let mut extra = lemmas.clone();
+            extra.push(nodes[0].clone());
+            assert!(proof::cbmt_multi_root(&proven, &extra).unwrap().is_none());
+            if !lemmas.is_empty() {
+                let missing = &lemmas[..lemmas.len() - 1];
+                assert!(proof::cbmt_multi_root(&proven, missing).unwrap().is_none());
+            }
+        }
+    }
+
+    let leaf = Hash::digest(Bytes::from("tx"));
+    assert_eq!(
+        proof::cbmt_build_root(&[]).unwrap(),
+        Hash::from_bytes(Bytes::from(vec![0u8; 32])).unwrap()
+    );
+    assert!(proof::cbmt_multi_root(&[], &[]).unwrap().is_none());
+    assert!(proof::cbmt_multi_root(&[(1, leaf.clone()), (1, leaf)], &[])
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_packed_tx_proof() {
+    let hash = |i: u32| Hash::digest(Bytes::from(format!("node {}", i)));
+    for (index, depth) in [(0u32, 0usize), (1, 1), (2, 1), (3, 2), (6, 2), (7, 3)].iter() {
+        for with_witnesses in [false, true].iter() {
+            let tx_proof = CkbTxProof {
+                index:          *index,
+                lemmas:         (0..*depth as u32).map(hash).collect(),
+                witnesses_root: hash(100),
+                witness_lemmas: if *with_witnesses {
+                    Some((10..10 + *depth as u32).map(hash).collect())
+                } else {
+                    None
+                },
+            };
+            let packed = proof::pack_tx_proof(&tx_proof);
+            let unpacked = proof::unpack_tx_proof(&packed).unwrap();
+            assert_eq!(
+                serde_json::to_value(&unpacked).unwrap(),
+                serde_json::to_value(&tx_proof).unwrap()
+            );
+
+            // a lemma short or over, or an unknown flag
+            assert!(proof::unpack_tx_proof(&packed[..packed.len() - 1]).is_none());
+            let mut over = packed.clone();
+            over.extend_from_slice(hash(0).as_bytes().as_ref());
+            assert!(proof::unpack_tx_proof(&over).is_none());
+            let mut flag = packed.clone();
+            flag[4] = 2;
+            assert!(proof::unpack_tx_proof(&flag).is_none());
+        }
+    }
+    assert!(proof::unpack_tx_proof(&[]).is_none());
+}
+
+#[test]
+fn test_optional_u128_as_string() {
+    let mut quote = BurnQuote {
+        asset_id:         Hash::digest(Bytes::from("asset")),
+        amount:           1,
+        min_burn_amount:  0,
+        scale_factor:     1,
+        sudt_amount:      1,
+        quota_remaining:  Some(u128::max_value()),
+        paused:           false,
+        formatted_amount: None,
+    };
+    let json = serde_json::to_value(&quote).unwrap();
+    assert_eq!(json["amount"], "1");
+    assert_eq!(
+        json["quota_remaining"],
+        "340282366920938463463374607431768211455"
+    );
+    let decoded: BurnQuote = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded.quota_remaining, Some(u128::max_value()));
+
+    quote.quota_remaining = None;
+    let mut json = serde_json::to_value(&quote).unwrap();
+    assert!(json["quota_remaining"].is_null());
+    json.as_object_mut().unwrap().remove("quota_remaining");
+    json["amount"] = serde_json::json!(2);
+    let decoded: BurnQuote = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded.quota_remaining, None);
+    assert_eq!(decoded.amount, 2);
+}
+
+#[test]
+fn test_header_gaps() {
+    let stored = [10u64, 11, 14, 15, 20, 25];
+    let gaps = |from, to, limit, max_scan| {
+        header_gaps(from, to, limit, max_scan, |height| {
+            Ok(stored.contains(&height))
+        })
+        .unwrap()
+    };
+    let range = |start, end| HeightRange { start, end };
+
+    assert_eq!(
+        gaps(10, 25, 10, 100),
+        (vec![range(12, 13), range(16, 19), range(21, 24)], None)
+    );
+    assert_eq!(gaps(10, 15, 10, 100), (vec![range(12, 13)], None));
+    assert_eq!(gaps(10, 11, 10, 100), (vec![], None));
+
+    // stops at the limit, resuming from the header closing the last gap
+    assert_eq!(
+        gaps(10, 25, 2, 100),
+        (vec![range(12, 13), range(16, 19)], Some(20))
+    );
+    assert_eq!(gaps(20, 25, 2, 100), (vec![range(21, 24)], None));
+
+    // a gap cut by the scan bound is reported up to where the scan stopped
+    assert_eq!(
+        gaps(10, 25, 10, 8),
+        (vec![range(12, 13), range(16, 17)], Some(18))
+    );
+    assert_eq!(
+        gaps(18, 25, 10, 8),
+        (vec![range(18, 19), range(21, 24)], None)
+    );
+
+    assert_eq!(gaps(26, 25, 10, 100), (vec![], None));
+}
+
+#[test]
+fn test_expired() {
+    assert!(!expired(100, 100, 10));
+    assert!(!expired(90, 100, 10));
+    assert!(expired(89, 100, 10));
+    // headers ahead of the tip, as an mmr header may be, never expire
+    assert!(!expired(120, 100, 10));
+}
+
+#[test]
+fn test_genesis_mirror_asset_issuer() {
+    let admin = "0x755cdba6ae4f479f7164792b318b2a06c759833b";
+    let genesis: InitGenesisPayload =
+        serde_json::from_value(serde_json::json!({ "admin": admin })).unwrap();
+    assert!(genesis.mirror_asset_issuer.is_none());
+    assert_eq!(genesis.message_ttl, 0);
+
+    let issuer = "0xc4b0000000000000000000000000000000000002";
+    let genesis: InitGenesisPayload = serde_json::from_value(serde_json::json!({
+        "admin": admin,
+        "mirror_asset_issuer": issuer,
+    }))
+    .unwrap();
+    assert_eq!(
+        genesis.mirror_asset_issuer,
+        Some(Address::from_hex(issuer).unwrap())
+    );
+}
+
+#[test]
+fn test_schemas() {
+    for type_name in SCHEMA_TYPES.iter() {
+        assert!(schema(type_name).is_some(), "{}", type_name);
+    }
+    assert!(schema("Unknown").is_none());
+
+    let schema = serde_json::to_value(schema("BurnPayload").unwrap()).unwrap();
+    assert_eq!(schema["properties"]["receiver"]["type"], "string");
+}
+
+#[test]
+fn test_flatten_bridge_stats() {
+    let asset_id = Hash::digest(Bytes::from("asset"));
+    let mut stats = BridgeStats::default();
+    stats.total_mints = 3;
+    for reason in &["expired", "invalid_tx", "duplicate_tx"] {
+        stats.rejected_messages.insert(reason.to_string(), 1);
+    }
+    stats.volumes.push(AssetVolume {
+        asset_id: asset_id.clone(),
+        minted:   u128::max_value(),
+        burned:   0,
+    });
+
+    let value = serde_json::to_value(BridgeStatsView::from(stats)).unwrap();
+    let view = flatten("get_bridge_stats".to_owned(), &value, 2, 0);
+    let field = |path: &str| {
+        view.fields
+            .iter()
+            .find(|f| f.path == path)
+            .map(|f| f.value.as_str())
+    };
+    assert_eq!(field("total_mints"), Some("3"));
+    assert_eq!(
+        field("volumes.0.asset_id"),
+        Some(asset_id.as_hex().as_str())
+    );
+    assert_eq!(
+        field("volumes.0.minted"),
+        Some(u128::max_value().to_string().as_str())
+    );
+    assert_eq!(
+        view.fields
+            .iter()
+            .filter(|f| f.path.starts_with("rejected_messages."))
+            .count(),
+        2
+    );
+    assert_eq!(view.truncated.len(), 1);
+    assert_eq!(view.truncated[0].path, "rejected_messages");
+    assert_eq!(view.truncated[0].total, 3);
+}
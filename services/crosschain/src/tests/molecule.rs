@@ -0,0 +1,186 @@
+use byteorder::{ByteOrder, LittleEndian};
+
+use protocol::types::{Address, Bytes, Hash};
+
+use crate::molecule::{decode_transaction, encode_raw_transaction, encode_transaction, TxView};
+use crate::types::ScriptHashType;
+use crate::{parse_deposit, parse_raw_deposit, SUDT_CODE_HASH};
+
+fn number(n: u32) -> Vec<u8> {
+    let mut buf = [0u8; 4];
+    LittleEndian::write_u32(&mut buf, n);
+    buf.to_vec()
+}
+
+fn dynvec(items: Vec<Vec<u8>>) -> Vec<u8> {
+    let header_size = 4 * (items.len() + 1);
+    let total_size = header_size + items.iter().map(Vec::len).sum::<usize>();
+
+    let mut data = number(total_size as u32);
+    let mut offset = header_size;
+    for item in items.iter() {
+        data.extend(number(offset as u32));
+        offset += item.len();
+    }
+    for item in items {
+        data.extend(item);
+    }
+    data
+}
+
+fn fixvec(items: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut data = number(items.len() as u32);
+    for item in items {
+        data.extend(item);
+    }
+    data
+}
+
+fn bytes(b: &[u8]) -> Vec<u8> {
+    fixvec(b.iter().map(|b| vec![*b]).collect())
+}
+
+fn script(code_hash: &Hash, hash_type: u8, args: &[u8]) -> Vec<u8> {
+    dynvec(vec![
+        code_hash.as_bytes().to_vec(),
+        vec![hash_type],
+        bytes(args),
+    ])
+}
+
+fn deposit_tx(sudt_args: &Hash, amount: u128, receiver: &Address) -> Vec<u8> {
+    let lock_hash = Hash::digest(Bytes::from("lock"));
+    let sudt_hash = Hash::from_hex(SUDT_CODE_HASH).unwrap();
+
+    let mut capacity = [0u8; 8];
+    LittleEndian::write_u64(&mut capacity, 14_200_000_000);
+    let output = dynvec(vec![
+        capacity.to_vec(),
+        script(&lock_hash, 1, &[]),
+        script(&sudt_hash, 0, sudt_args.as_bytes().as_ref()),
+    ]);
+    let mut amount_data = [0u8; 16];
+    LittleEndian::write_u128(&mut amount_data, amount);
+
+    let mut input = vec![0u8; 8];
+    input.extend(Hash::digest(Bytes::from("prev")).as_bytes().to_vec());
+    input.extend(number(1));
+
+    let raw = dynvec(vec![
+        number(0),
+        fixvec(vec![]),
+        fixvec(vec![]),
+        fixvec(vec![input]),
+        dynvec(vec![output]),
+        dynvec(vec![bytes(&amount_data)]),
+    ]);
+    let receiver_hex = receiver.as_hex();
+    let witness = hex::decode(&receiver_hex[2..]).unwrap();
+
+    dynvec(vec![raw, dynvec(vec![bytes(&witness)])])
+}
+
+#[test]
+fn test_decode_deposit_transaction() {
+    let sudt_args = Hash::digest(Bytes::from("sudt"));
+    let receiver = Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap();
+    let data = deposit_tx(&sudt_args, 1000, &receiver);
+
+    let (tx, raw) = decode_transaction(&data).unwrap();
+    assert_eq!(tx.version.as_string(), "0x00");
+    assert_eq!(tx.inputs.len(), 1);
+    assert_eq!(tx.inputs[0].previous_output.index.as_string(), "0x01");
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].capacity.as_string(), "0x034e62ce00");
+    assert_eq!(tx.outputs[0].lock.args.as_string(), "0x");
+    match tx.outputs[0].lock.hash_type {
+        ScriptHashType::Type => (),
+        ScriptHashType::data => panic!("lock hash type should be type"),
+    }
+    // The raw tx is the first field of the transaction table.
+    assert_eq!(raw, &data[12..12 + raw.len()]);
+
+    let deposit = parse_deposit(&tx).unwrap();
+    assert_eq!(deposit.sudt_args, sudt_args);
+    assert_eq!(deposit.sudt_amount, 1000);
+    assert_eq!(deposit.receiver, Some(receiver));
+}
+
+#[test]
+fn test_decode_malformed_transaction() {
+    let sudt_args = Hash::digest(Bytes::from("sudt"));
+    let receiver = Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap();
+    let data = deposit_tx(&sudt_args, 1000, &receiver);
+
+    assert!(decode_transaction(&[]).is_err());
+    assert!(decode_transaction(&data[..data.len() - 1]).is_err());
+    for i in 0..data.len() {
+        // Must never panic, whatever byte is corrupted.
+        let mut corrupted = data.clone();
+        corrupted[i] = corrupted[i].wrapping_add(0x80);
+        let _ = decode_transaction(&corrupted);
+    }
+
+    // A table with a missing field.
+    assert!(decode_transaction(&dynvec(vec![dynvec(vec![])])).is_err());
+}
+
+#[test]
+fn test_tx_view() {
+    let sudt_args = Hash::digest(Bytes::from("sudt"));
+    let receiver = Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap();
+    let data = deposit_tx(&sudt_args, 1000, &receiver);
+
+    let (_, raw) = decode_transaction(&data).unwrap();
+    let view = TxView::parse(&data).unwrap();
+    assert_eq!(view.raw(), raw);
+    assert!(view.output_type(1).unwrap().is_none());
+    assert!(view.output_data(1).unwrap().is_none());
+
+    let deposit = parse_raw_deposit(&view).unwrap();
+    assert_eq!(deposit.sudt_args, sudt_args);
+    assert_eq!(deposit.sudt_amount, 1000);
+    assert_eq!(deposit.receiver, Some(receiver));
+
+    // Reads a corrupted tx like the decoded form does, and never panics.
+    assert!(TxView::parse(&[]).is_err());
+    for i in 0..data.len() {
+        let mut corrupted = data.clone();
+        corrupted[i] = corrupted[i].wrapping_add(0x80);
+        let view = match TxView::parse(&corrupted) {
+            Ok(view) => view,
+            Err(_) => continue,
+        };
+        let lazy = parse_raw_deposit(&view);
+        if let Ok((tx, _)) = decode_transaction(&corrupted) {
+            let eager = parse_deposit(&tx);
+            assert_eq!(lazy.is_ok(), eager.is_ok());
+            if let (Ok(lazy), Ok(eager)) = (lazy, eager) {
+                assert_eq!(lazy.sudt_args, eager.sudt_args);
+                assert_eq!(lazy.sudt_amount, eager.sudt_amount);
+                assert_eq!(lazy.receiver, eager.receiver);
+                assert_eq!(lazy.memo, eager.memo);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_encode_round_trip() {
+    let sudt_args = Hash::digest(Bytes::from("sudt"));
+    let receiver = Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap();
+    let data = deposit_tx(&sudt_args, 1000, &receiver);
+
+    let (tx, raw) = decode_transaction(&data).unwrap();
+    assert_eq!(encode_raw_transaction(&tx).unwrap(), raw.to_vec());
+    assert_eq!(encode_transaction(&tx).unwrap(), data);
+
+    // A json tx with odd length numbers encodes like its padded form.
+    let mut json_tx = tx.clone();
+    json_tx.version = serde_json::from_str("\"0x0\"").unwrap();
+    json_tx.outputs[0].capacity = serde_json::from_str("\"0x34e62ce00\"").unwrap();
+    assert_eq!(encode_transaction(&json_tx).unwrap(), data);
+
+    json_tx.outputs_data[0] = serde_json::from_str("\"0xzz\"").unwrap();
+    assert!(encode_transaction(&json_tx).is_err());
+}
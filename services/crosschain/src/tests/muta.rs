@@ -0,0 +1,231 @@
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{Address, Bytes, Hash, Hex, Proof, Validator};
+
+use crate::light_client::{LightClient, MutaLightClient};
+use crate::muta::{has_quorum, merkle_root, signers};
+use crate::types::{
+    MerkleStep, MutaConfig, MutaHeader, MutaReceiptProof, MutaValidator, MutaValidatorSet,
+    MutaValidatorUpdate,
+};
+
+fn validator(i: u8, vote_weight: u32) -> MutaValidator {
+    MutaValidator {
+        bls_pub_key: serde_json::from_str::<Hex>("\"0x04\"").unwrap(),
+        address: Address::from_bytes(Bytes::from(vec![i; 20])).unwrap(),
+        vote_weight,
+    }
+}
+
+fn header(height: u64, prev_hash: Hash, receipt_roots: Vec<Hash>) -> MutaHeader {
+    MutaHeader {
+        hash: Hash::digest(Bytes::from(format!("block {}", height))),
+        chain_id: Hash::digest(Bytes::from("chain")),
+        height,
+        exec_height: height,
+        prev_hash: prev_hash.clone(),
+        receipt_roots,
+        proof: Proof {
+            height:     height - 1,
+            round:      0,
+            block_hash: prev_hash,
+            signature:  Bytes::new(),
+            bitmap:     Bytes::from(vec![0b1110_0000]),
+        },
+        validator_version: 0,
+        validators: vec![],
+    }
+}
+
+fn config() -> MutaConfig {
+    MutaConfig {
+        chain_id:       Hash::digest(Bytes::from("chain")),
+        common_ref:     serde_json::from_str::<Hex>("\"0x7878\"").unwrap(),
+        bridge_service: "crosschain".to_owned(),
+    }
+}
+
+fn client(validators: Vec<MutaValidator>) -> MutaLightClient {
+    MutaLightClient {
+        config:        config(),
+        validator_set: MutaValidatorSet {
+            version: 0,
+            start_height: 0,
+            validators,
+        },
+    }
+}
+
+#[test]
+fn test_signers_quorum() {
+    let validators: Vec<_> = (0..4).map(|i| validator(i, 1)).collect();
+
+    let three = signers(&[0b1110_0000], &validators);
+    assert_eq!(three, vec![&validators[0], &validators[1], &validators[2]]);
+    assert!(has_quorum(&three, &validators));
+
+    let two = signers(&[0b1001_0000], &validators);
+    assert_eq!(two, vec![&validators[0], &validators[3]]);
+    assert!(!has_quorum(&two, &validators));
+
+    // A short bitmap selects nobody past its end.
+    assert!(signers(&[], &validators).is_empty());
+
+    let weighted = vec![
+        validator(0, 7),
+        validator(1, 1),
+        validator(2, 1),
+        validator(3, 1),
+    ];
+    assert!(has_quorum(&signers(&[0b1000_0000], &weighted), &weighted));
+    assert!(!has_quorum(&[], &[]));
+}
+
+#[test]
+fn test_merkle_root() {
+    let leaves: Vec<Hash> = (0..4)
+        .map(|i| Hash::digest(Bytes::from(format!("receipt {}", i))))
+        .collect();
+    let merge = |l: &Hash, r: &Hash| {
+        let mut data = l.as_bytes().to_vec();
+        data.extend_from_slice(r.as_bytes().as_ref());
+        Hash::digest(Bytes::from(data))
+    };
+    let root = merge(
+        &merge(&leaves[0], &leaves[1]),
+        &merge(&leaves[2], &leaves[3]),
+    );
+
+    let path = vec![
+        MerkleStep {
+            sibling: leaves[3].clone(),
+            left:    false,
+        },
+        MerkleStep {
+            sibling: merge(&leaves[0], &leaves[1]),
+            left:    true,
+        },
+    ];
+    assert_eq!(merkle_root(&leaves[2], &path), root);
+    assert_ne!(merkle_root(&leaves[3], &path), root);
+
+    let parent = header(9, Hash::digest(Bytes::from("block 8")), vec![]);
+    let block = header(10, parent.hash.clone(), vec![root]);
+    let client = client(vec![validator(0, 1)]);
+    let proof = MutaReceiptProof {
+        height:     10,
+        root_index: 0,
+        receipt:    serde_json::from_str::<Hex>("\"0x00\"").unwrap(),
+        path:       path.clone(),
+    };
+    assert!(client.verify_inclusion(&block, &leaves[2], &proof).unwrap());
+    assert!(!client.verify_inclusion(&block, &leaves[1], &proof).unwrap());
+    let out_of_range = MutaReceiptProof {
+        root_index: 1,
+        ..proof
+    };
+    assert!(!client
+        .verify_inclusion(&block, &leaves[2], &out_of_range)
+        .unwrap());
+}
+
+#[test]
+fn test_muta_header_rejected() {
+    let validators: Vec<_> = (0..4).map(|i| validator(i, 1)).collect();
+    let client = client(validators);
+    let parent = header(9, Hash::digest(Bytes::from("block 8")), vec![]);
+
+    // Not linked to the stored parent.
+    let unlinked = header(10, Hash::digest(Bytes::from("fork")), vec![]);
+    assert!(client.verify_header(&unlinked, Some(&parent)).is_err());
+
+    // Proof of another height.
+    let mut wrong_height = header(10, parent.hash.clone(), vec![]);
+    wrong_height.proof.height = 8;
+    assert!(client.verify_header(&wrong_height, Some(&parent)).is_err());
+
+    // Other chain.
+    let mut other_chain = header(10, parent.hash.clone(), vec![]);
+    other_chain.chain_id = Hash::digest(Bytes::from("other chain"));
+    assert!(client.verify_header(&other_chain, Some(&parent)).is_err());
+
+    // Signed by less than two thirds.
+    let mut no_quorum = header(10, parent.hash.clone(), vec![]);
+    no_quorum.proof.bitmap = Bytes::from(vec![0b1100_0000]);
+    assert!(client.verify_header(&no_quorum, Some(&parent)).is_err());
+
+    // Quorum but no valid aggregated signature.
+    let unsigned = header(10, parent.hash.clone(), vec![]);
+    assert!(client.verify_header(&unsigned, Some(&parent)).is_err());
+}
+
+#[test]
+fn test_muta_codec_round_trip() {
+    let config = config();
+    let decoded = MutaConfig::decode_fixed(config.encode_fixed().unwrap()).unwrap();
+    assert_eq!(decoded.chain_id, config.chain_id);
+    assert_eq!(decoded.common_ref, config.common_ref);
+    assert_eq!(decoded.bridge_service, config.bridge_service);
+    assert_eq!(config.common_ref_str().unwrap(), "xx");
+
+    let validator_set = MutaValidatorSet {
+        version:      2,
+        start_height: 100,
+        validators:   vec![validator(0, 3), validator(1, 5)],
+    };
+    let decoded = MutaValidatorSet::decode_fixed(validator_set.encode_fixed().unwrap()).unwrap();
+    assert_eq!(decoded, validator_set);
+
+    let mut header = header(10, Hash::digest(Bytes::from("block 9")), vec![
+        Hash::digest(Bytes::from("root")),
+    ]);
+    header.validator_version = 2;
+    header.validators = vec![Validator {
+        address:        Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap(),
+        propose_weight: 1,
+        vote_weight:    5,
+    }];
+    let decoded = MutaHeader::decode_fixed(header.encode_fixed().unwrap()).unwrap();
+    assert_eq!(decoded, header);
+}
+
+#[test]
+fn test_validator_update_rejected() {
+    let current: Vec<_> = (0..4).map(|i| validator(i, 1)).collect();
+    let client = client(current.clone());
+
+    let next = vec![validator(4, 1), validator(5, 1)];
+    let mut announcing = header(99, Hash::digest(Bytes::from("block 98")), vec![]);
+    announcing.validator_version = 1;
+    announcing.validators = next
+        .iter()
+        .map(|v| Validator {
+            address:        v.address.clone(),
+            propose_weight: 1,
+            vote_weight:    v.vote_weight,
+        })
+        .collect();
+    let update = |version, start_height, validators| MutaValidatorUpdate {
+        validator_set: MutaValidatorSet {
+            version,
+            start_height,
+            validators,
+        },
+        signature:     serde_json::from_str::<Hex>("\"0x00\"").unwrap(),
+        bitmap:        serde_json::from_str::<Hex>("\"0xf0\"").unwrap(),
+    };
+
+    // Not the set the header announced.
+    assert!(client
+        .verify_validator_update(&announcing, &update(1, 100, current))
+        .is_err());
+    assert!(client
+        .verify_validator_update(&announcing, &update(2, 100, next.clone()))
+        .is_err());
+    assert!(client
+        .verify_validator_update(&announcing, &update(1, 101, next.clone()))
+        .is_err());
+    // The announced set, but not signed by the current one.
+    assert!(client
+        .verify_validator_update(&announcing, &update(1, 100, next))
+        .is_err());
+}
@@ -0,0 +1,122 @@
+use proptest::prelude::*;
+
+use protocol::types::{Address, Bytes, Hash, Hex};
+use testing::strategy::{address, hash, hex_bytes, sudt_amount_data};
+
+use crate::types::{CellOutput, CkbTx, FeeSchedule, FeeTier, MirrorAsset, Script, ScriptHashType};
+use crate::{parse_deposit, relay_fee, MAX_MEMO_LEN, SUDT_CODE_HASH};
+
+fn deposit_tx(sudt_args: &Hash, outputs_data: Hex, witness: Hex) -> CkbTx {
+    let lock = Script {
+        code_hash: Hash::digest(Bytes::from("lock")),
+        hash_type: ScriptHashType::data,
+        args:      hex_bytes(&[]),
+    };
+    let type_ = Script {
+        code_hash: Hash::from_hex(SUDT_CODE_HASH).unwrap(),
+        hash_type: ScriptHashType::data,
+        args:      Hex::from_string(sudt_args.as_hex()).unwrap(),
+    };
+
+    CkbTx {
+        version:      hex_bytes(&[0]),
+        cell_deps:    vec![],
+        header_deps:  vec![],
+        inputs:       vec![],
+        outputs:      vec![CellOutput {
+            capacity: hex_bytes(&[0]),
+            lock,
+            type_: Some(type_),
+        }],
+        outputs_data: vec![outputs_data],
+        witnesses:    vec![witness],
+    }
+}
+
+fn deposit() -> impl Strategy<Value = (Hash, u128, Address)> {
+    (hash(), any::<u128>(), address())
+}
+
+fn mirror_asset(scale_factor: u64) -> MirrorAsset {
+    MirrorAsset {
+        sudt_args: Hash::digest(Bytes::from("sudt")),
+        asset_id: Hash::digest(Bytes::from("asset")),
+        name: "mirror".to_owned(),
+        symbol: "MRR".to_owned(),
+        decimals: 8,
+        scale_factor,
+        min_burn_amount: 0,
+    }
+}
+
+proptest! {
+    #[test]
+    fn prop_parse_deposit_round_trip((sudt_args, amount, receiver) in deposit()) {
+        let tx = deposit_tx(
+            &sudt_args,
+            sudt_amount_data(amount),
+            Hex::from_string(receiver.as_hex()).unwrap(),
+        );
+        let deposit = parse_deposit(&tx).unwrap();
+
+        prop_assert_eq!(deposit.sudt_args, sudt_args);
+        prop_assert_eq!(deposit.sudt_amount, amount);
+        prop_assert_eq!(deposit.receiver, Some(receiver));
+        prop_assert_eq!(deposit.memo, "");
+    }
+
+    #[test]
+    fn prop_parse_deposit_memo(receiver in address(), memo in "[a-zA-Z0-9 ]{0,80}") {
+        let mut witness = receiver.as_bytes().to_vec();
+        witness.extend_from_slice(memo.as_bytes());
+        let tx = deposit_tx(
+            &Hash::digest(Bytes::from("sudt")),
+            sudt_amount_data(1),
+            hex_bytes(&witness),
+        );
+        let deposit = parse_deposit(&tx).unwrap();
+
+        if memo.len() <= MAX_MEMO_LEN {
+            prop_assert_eq!(deposit.receiver, Some(receiver));
+            prop_assert_eq!(deposit.memo, memo);
+        } else {
+            prop_assert_eq!(deposit.receiver, None);
+        }
+    }
+
+    #[test]
+    fn prop_parse_deposit_never_panics(
+        sudt_args in hash(),
+        data in prop::collection::vec(any::<u8>(), 0..40),
+        witness in prop::collection::vec(any::<u8>(), 0..40),
+    ) {
+        let tx = deposit_tx(&sudt_args, hex_bytes(&data), hex_bytes(&witness));
+        let _ = parse_deposit(&tx);
+    }
+
+    #[test]
+    fn prop_rescale_conserves_sudt(
+        sudt_amount in any::<u128>(),
+        scale_factor in 1..u64::max_value(),
+    ) {
+        let mirror_asset = mirror_asset(scale_factor);
+        let (amount, dust) = mirror_asset.to_mirror_amount(sudt_amount);
+
+        prop_assert!(dust < u128::from(scale_factor));
+        prop_assert_eq!(mirror_asset.to_sudt_amount(amount).unwrap() + dust, sudt_amount);
+    }
+
+    #[test]
+    fn prop_relay_fee_split_conserves_amount(amount in any::<u128>(), bps in 0..=10_000u16) {
+        let schedule = FeeSchedule {
+            tiers: vec![FeeTier { threshold: 0, bps }],
+        };
+        let amount_relay = relay_fee(&schedule, amount);
+        let amount_receiver = amount - amount_relay;
+
+        if bps <= 5_000 {
+            prop_assert!(amount_relay <= amount_receiver);
+        }
+        prop_assert_eq!(amount_receiver + amount_relay, amount);
+    }
+}
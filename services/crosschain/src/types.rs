@@ -1,229 +1,3932 @@
-use std::mem;
-use std::num::ParseIntError;
+use std::collections::BTreeMap;
+use std::fmt;
 
-use byteorder::{ByteOrder, LittleEndian};
 use bytes::{Bytes, BytesMut};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use common::amount::{Amount, MAX_DECIMALS};
+use common::versioned::PayloadVersion;
 use protocol::fixed_codec::{FixedCodec, FixedCodecError};
-use protocol::types::{Address, Hash, Hex};
+use protocol::types::{Address, Hash, Hex, Proof, Validator};
 use protocol::ProtocolResult;
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+pub use common::chain::ChainBinding;
+pub use common::view::{GetViewPayload, ReadView, TruncatedField, ViewField};
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct InitGenesisPayload {
+    #[schemars(with = "String")]
+    pub admin:               Address,
+    // seeds the muta sidechain mode
+    #[serde(default)]
+    pub muta_config:         Option<MutaConfig>,
+    #[serde(default)]
+    pub muta_validator_set:  Option<MutaValidatorSet>,
+    // lock code hashes burns may pay to, the secp256k1 locks when empty
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub supported_locks:     Vec<Hash>,
+    // issuer of the assets the bridge creates, a fixed placeholder when unset
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub mirror_asset_issuer: Option<Address>,
+    // ckb blocks behind the tip after which deposits expire, 0 never expires
+    #[serde(default)]
+    pub message_ttl:         u64,
+    #[serde(default)]
+    pub bridge_limits:       Option<BridgeLimits>,
+    // who relay fees are paid to, the relayer when unset
+    #[serde(default)]
+    pub fee_beneficiary:     Option<FeeBeneficiary>,
+    // relay fee rates by deposit size, a flat 1% when unset
+    #[serde(default)]
+    pub fee_schedule:        Option<FeeSchedule>,
+    // holds deposits whose receiver is malformed, a fixed placeholder when
+    // unset
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub recovery_account:    Option<Address>,
+    // muta blocks in a burn epoch, `BURN_EPOCH_LENGTH` when 0
+    #[serde(default)]
+    pub burn_epoch_length:   u64,
+    // muta blocks a burn batch stays open, 0 leaves burns unbatched
+    #[serde(default)]
+    pub burn_batch_window:   u64,
+    // networks the bridge joins, unbound when unset
+    #[serde(default)]
+    pub chain_binding:       Option<ChainBinding>,
+    // checks admins and relayers against the rbac service's roles
+    #[serde(default)]
+    pub rbac:                bool,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct UpdateHeadersPayload {
     pub headers: Vec<CkbHeader>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// A header submitted at a height whose stored header differs. The stored
+/// one stays until `resolve_header_conflict` picks a branch, and messages of
+/// the height wait for it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HeaderConflict {
+    pub height:           u64,
+    #[schemars(with = "String")]
+    pub stored_hash:      Hash,
+    #[schemars(with = "String")]
+    pub conflicting_hash: Hash,
+    pub header:           CkbHeader,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HeaderConflictEvent {
+    pub height:           u64,
+    #[schemars(with = "String")]
+    pub stored_hash:      Hash,
+    #[schemars(with = "String")]
+    pub conflicting_hash: Hash,
+    #[schemars(with = "String")]
+    pub relayer:          Address,
+    pub topic:            String, // "header_conflict"
+}
+
+/// Fork choice of a conflict. `branch` is the conflicting header followed by
+/// its descendants, which replace the stored headers when the branch is
+/// longer than theirs. Left empty, the stored branch is kept if it is longer
+/// than the conflicting header alone.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ResolveHeaderConflictPayload {
+    pub height: u64,
+    #[serde(default)]
+    pub branch: Vec<CkbHeader>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HeaderConflictResolvedEvent {
+    pub height:       u64,
+    #[schemars(with = "String")]
+    pub kept_hash:    Hash,
+    #[schemars(with = "String")]
+    pub dropped_hash: Hash,
+    // headers of the kept branch from the height on, as far as counted
+    pub branch_len:   u64,
+    pub topic:        String, // "resolve_header_conflict"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CkbHeader {
+    #[schemars(with = "String")]
     pub compact_target:    Hex,
+    #[schemars(with = "String")]
     pub version:           Hex,
+    #[schemars(with = "String")]
     pub timestamp:         Hex,
+    #[schemars(with = "String")]
     pub number:            Hex,
+    #[schemars(with = "String")]
     pub epoch:             Hex,
+    #[schemars(with = "String")]
     pub parent_hash:       Hash,
+    #[schemars(with = "String")]
     pub transactions_root: Hash,
+    #[schemars(with = "String")]
     pub proposals_hash:    Hash,
+    #[schemars(with = "String")]
     pub uncles_hash:       Hash,
+    #[schemars(with = "String")]
     pub dao:               Hash,
+    #[schemars(with = "String")]
     pub nonce:             Hex,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// Version of the stored `CkbHeaderInner` layout, headers without one predate
+/// the nonce fix.
+pub const HEADER_CODEC_VERSION: u8 = 1;
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CkbHeaderInner {
     pub compact_target:    u32,
     pub version:           u32,
     pub timestamp:         u64,
     pub number:            u64,
     pub epoch:             u64,
+    #[schemars(with = "String")]
     pub parent_hash:       Hash,
+    #[schemars(with = "String")]
     pub transactions_root: Hash,
+    #[schemars(with = "String")]
     pub proposals_hash:    Hash,
+    #[schemars(with = "String")]
     pub uncles_hash:       Hash,
+    #[schemars(with = "String")]
     pub dao:               Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub nonce:             u128,
 }
 
 impl CkbHeaderInner {
-    pub fn from(h: CkbHeader) -> Result<Self, ParseIntError> {
+    pub fn from(h: CkbHeader) -> Result<Self, FieldError> {
+        let parse_u64 = |hex: &Hex, field| {
+            u64::from_str_radix(hex.as_string_trim0x().as_str(), 16)
+                .map_err(|_| FieldError { field })
+        };
+        let parse_u32 = |hex: &Hex, field| {
+            u32::from_str_radix(hex.as_string_trim0x().as_str(), 16)
+                .map_err(|_| FieldError { field })
+        };
+
         Ok(CkbHeaderInner {
-            compact_target:    u32::from_str_radix(
-                h.compact_target.as_string_trim0x().as_str(),
-                16,
-            )?,
-            version:           u32::from_str_radix(h.version.as_string_trim0x().as_str(), 16)?,
-            timestamp:         u64::from_str_radix(h.timestamp.as_string_trim0x().as_str(), 16)?,
-            number:            u64::from_str_radix(h.number.as_string_trim0x().as_str(), 16)?,
-            epoch:             u64::from_str_radix(h.epoch.as_string_trim0x().as_str(), 16)?,
+            compact_target:    parse_u32(&h.compact_target, "compact_target")?,
+            version:           parse_u32(&h.version, "version")?,
+            timestamp:         parse_u64(&h.timestamp, "timestamp")?,
+            number:            parse_u64(&h.number, "number")?,
+            epoch:             parse_u64(&h.epoch, "epoch")?,
             parent_hash:       h.parent_hash,
             transactions_root: h.transactions_root,
             proposals_hash:    h.proposals_hash,
             uncles_hash:       h.uncles_hash,
             dao:               h.dao,
-            nonce:             u128::from_str_radix(h.epoch.as_string_trim0x().as_str(), 16)?,
+            nonce:             u128::from_str_radix(h.nonce.as_string_trim0x().as_str(), 16)
+                .map_err(|_| FieldError { field: "nonce" })?,
         })
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct Uint128(pub u128);
+/// A field of a relayed header or tx that failed to parse.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FieldError {
+    pub field: &'static str,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {}", self.field)
+    }
+}
+
+impl CkbHeaderInner {
+    pub fn epoch_view(&self) -> EpochView {
+        EpochView::from_packed(self.epoch)
+    }
+}
+
+/// CKB header epoch field, which packs the epoch number (lowest 24 bits), the
+/// block index inside the epoch (next 16 bits) and the epoch length (next 16
+/// bits) into one u64.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EpochView {
+    pub number: u64,
+    pub index:  u64,
+    pub length: u64,
+}
+
+impl EpochView {
+    pub fn from_packed(epoch: u64) -> Self {
+        EpochView {
+            number: epoch & 0xff_ffff,
+            index:  (epoch >> 24) & 0xffff,
+            length: (epoch >> 40) & 0xffff,
+        }
+    }
+
+    pub fn to_packed(&self) -> u64 {
+        (self.length << 40) | (self.index << 24) | self.number
+    }
+
+    /// Every ckb block sits at some index of a non empty epoch.
+    pub fn is_valid(&self) -> bool {
+        self.length > 0 && self.index < self.length
+    }
+
+    /// Whether a block in this epoch position can directly follow a block at
+    /// `parent`.
+    pub fn is_successor_of(&self, parent: &EpochView) -> bool {
+        if parent.index + 1 < parent.length {
+            self.number == parent.number
+                && self.index == parent.index + 1
+                && self.length == parent.length
+        } else {
+            self.number == parent.number + 1 && self.index == 0
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetHeaderPayload {
+    pub height: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetHeaderResponse {
+    pub header: CkbHeaderInner,
+    pub epoch:  EpochView,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct Uint128(
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub u128,
+);
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct BurnPayload {
-    pub token_id: Hash,
-    pub receiver: String, // hex of ckb address
-    pub amount:   u128,   // amount of asset to cross-back to ckb
+    #[schemars(with = "String")]
+    pub token_id:        Hash,
+    pub receiver:        String, // ckb address, bech32 or the hex of its payload
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:          u128, // amount of asset to cross-back to ckb
+    // ckb network the burn withdraws to, required once the bridge is bound
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub source_chain_id: Option<Hash>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct BurnCallAssetPayload {
+    #[schemars(with = "String")]
     pub token_id: Hash,
+    #[schemars(with = "String")]
     pub user:     Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
     pub amount:   u128,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// The bridge side of a burn, the burner and the burned amount are in the
+/// `BurnEvent` the asset service emits just before.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct BurnTokenEvent {
+    #[schemars(with = "String")]
     pub asset_id:     Hash,
-    pub muta_sender:  Address,
+    #[schemars(with = "String")]
+    pub sudt_args:    Hash,
     pub ckb_receiver: String,
-    pub amount:       u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub sudt_amount:  u128,
     pub nonce:        u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain:        Option<ChainBinding>,
     pub kind:         String, // "cross_to_ckb"
     pub topic:        String, // "burn_asset"
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetBurnRecordPayload {
+    pub nonce: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CancelBurnPayload {
+    pub nonce: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnCancelledEvent {
+    pub nonce:       u64,
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub muta_sender: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:      u128,
+    pub topic:       String, // "burn_cancelled"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RelayerPayload {
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RegisterMintCallbackPayload {
+    #[schemars(with = "String")]
+    pub address: Address,
+    pub service: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MintCallbackPayload {
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MintCallbackEvent {
+    #[schemars(with = "String")]
+    pub address: Address,
+    pub service: String,
+    pub topic:   String, // "register_mint_callback" or "unregister_mint_callback"
+}
+
+/// Methods of a service calls relayed from ckb may call.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct CallTarget {
+    pub service: String,
+    pub methods: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CallTargetPayload {
+    pub service: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CallTargetEvent {
+    pub service: String,
+    pub methods: Vec<String>,
+    pub topic:   String, // "register_call_target" or "unregister_call_target"
+}
+
+/// What the method of a call relayed from ckb gets, the sender being the
+/// type script of the cell carrying the call.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OnCrossCallPayload {
+    #[schemars(with = "String")]
+    pub ckb_tx_hash: Hash,
+    #[schemars(with = "String")]
+    pub code_hash:   Hash,
+    #[schemars(with = "String")]
+    pub args:        Hex,
+    pub payload:     String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CrossCallEvent {
+    #[schemars(with = "String")]
+    pub ckb_tx_hash: Hash,
+    pub service:     String,
+    pub method:      String,
+    #[schemars(with = "String")]
+    pub code_hash:   Hash,
+    #[schemars(with = "String")]
+    pub args:        Hex,
+    // why the call failed, it is dropped rather than retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error:       Option<String>,
+    pub topic:       String, // "cross_call"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OutboundSenderPayload {
+    pub service: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OutboundSender {
+    pub service: String,
+    pub allowed: bool,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OutboundSenderEvent {
+    pub service: String,
+    pub topic:   String, // "add_outbound_sender" or "remove_outbound_sender"
+}
+
+/// What an allowed service passes to `send_message_to_ckb`, `payload` for
+/// the contract of `target_script` on ckb.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SendMessageToCkbPayload {
+    pub target_script: Script,
+    #[schemars(with = "String")]
+    pub payload:       Hex,
+}
+
+/// A message a muta service sent to ckb, numbered from 1 in sending order.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OutboundMessage {
+    pub nonce:         u64,
+    // the sending service
+    pub sender:        String,
+    pub target_script: Script,
+    #[schemars(with = "String")]
+    pub payload:       Hex,
+    pub height:        u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OutboundMessageEvent {
+    pub nonce:         u64,
+    pub sender:        String,
+    pub target_script: Script,
+    #[schemars(with = "String")]
+    pub payload:       Hex,
+    pub height:        u64,
+    pub topic:         String, // "send_message_to_ckb"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetOutboundMessagePayload {
+    pub nonce: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetOutboundBlockPayload {
+    pub height: u64,
+}
+
+/// Messages to ckb sent in the muta block at `height`. `root` is the ckb
+/// merkle root over `leaves`, the ckb blake2b hash of each message's
+/// `send_message_to_ckb` event json in nonce order, which the relayer proves
+/// a message against on ckb.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct OutboundBlock {
+    pub height:      u64,
+    pub first_nonce: u64,
+    pub last_nonce:  u64,
+    #[schemars(with = "Vec<String>")]
+    pub leaves:      Vec<Hash>,
+    #[schemars(with = "String")]
+    pub root:        Hash,
+}
+
+/// What a service registered as a mint callback gets in `on_cross_mint`,
+/// after `amount` of `asset_id` was minted to `receiver`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct OnCrossMintPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub receiver: Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+    pub memo:     String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MintCallbackFailedEvent {
+    #[schemars(with = "String")]
+    pub receiver: Address,
+    pub service:  String,
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+    pub error:    String,
+    pub topic:    String, // "mint_callback_failed"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RelayerEvent {
+    #[schemars(with = "String")]
+    pub address: Address,
+    pub topic:   String, // "add_relayer" or "remove_relayer"
+}
+
+/// Inclusion proof of a ckb transaction, as returned by ckb rpc
+/// `get_transaction_proof`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CkbTxProof {
+    pub index:          u32, // index of the tx in the merkle tree
+    #[schemars(with = "Vec<String>")]
+    pub lemmas:         Vec<Hash>,
+    #[schemars(with = "String")]
+    pub witnesses_root: Hash,
+    // lemmas of the tx witness hash in the witnesses tree, required when the
+    // tx witnesses are relied upon
+    #[serde(default)]
+    #[schemars(with = "Option<Vec<String>>")]
+    pub witness_lemmas: Option<Vec<Hash>>,
+}
+
+/// Proof of several txs of the same block at once, the shape ckb
+/// `get_transaction_proof` returns for several tx hashes.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CkbMultiProof {
+    pub indices:        Vec<u32>, // indices of the txs in the merkle tree
+    #[schemars(with = "Vec<String>")]
+    pub lemmas:         Vec<Hash>,
+    #[schemars(with = "String")]
+    pub witnesses_root: Hash,
+    // lemmas of the tx witness hashes in the witnesses tree, same indices
+    #[schemars(with = "Vec<String>")]
+    pub witness_lemmas: Vec<Hash>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct AckBurnPayload {
+    pub nonce:       u64,
+    #[schemars(with = "String")]
+    pub ckb_tx_hash: Hash, // ckb tx unlocking the sudt to the burn receiver
+    // molecule serialized ckb `Transaction` of `ckb_tx_hash`, one of its
+    // outputs holds the burn's sudt amount under the receiver's lock
+    #[schemars(with = "String")]
+    pub raw_tx:      Hex,
+    pub height:      u64, // ckb block height containing the tx
+    pub proof:       CkbTxProof,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnAckedEvent {
+    pub nonce:       u64,
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub ckb_tx_hash: Hash,
+    #[schemars(with = "String")]
+    pub relayer:     Address,
+    pub topic:       String, // "burn_acked"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub enum BurnStatus {
+    Pending,
+    Cancelled,
+    Completed,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnRecord {
+    pub nonce:        u64,
+    #[schemars(with = "String")]
+    pub asset_id:     Hash,
+    #[schemars(with = "String")]
+    pub sudt_args:    Hash,
+    #[schemars(with = "String")]
+    pub muta_sender:  Address,
+    pub ckb_receiver: String,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:       u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub sudt_amount:  u128,
+    pub status:       BurnStatus,
+    #[schemars(with = "Option<String>")]
+    pub ckb_tx_hash:  Option<Hash>,
+}
+
+/// Messages of a ckb block. From version 2 their proofs are packed, version
+/// 1 spelled them out as json and is still read.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct MessagePayload {
-    pub height:   u64, // ckb block height
-    pub messages: Vec<CkbMessage>,
+    pub height:          u64, // ckb block height
+    pub messages:        Vec<PackedCkbMessage>,
+    // ckb network of the messages, required once the bridge is bound
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub source_chain_id: Option<Hash>,
+}
+
+/// Version 1 of `MessagePayload`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MessagePayloadV1 {
+    pub height:          u64,
+    pub messages:        Vec<CkbMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub source_chain_id: Option<Hash>,
+}
+
+/// A `CkbMessage` with its proof packed by `proof::pack_tx_proof`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct PackedCkbMessage {
+    #[serde(default)]
+    pub tx:     Option<CkbTx>,
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub raw_tx: Option<Hex>,
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub proof:  Option<Hex>,
+}
+
+impl PackedCkbMessage {
+    pub fn pack(message: CkbMessage) -> ProtocolResult<Self> {
+        let proof = match message.proof {
+            Some(proof) => Some(Hex::from_string(format!(
+                "0x{}",
+                hex::encode(crate::proof::pack_tx_proof(&proof))
+            ))?),
+            None => None,
+        };
+        Ok(PackedCkbMessage {
+            tx: message.tx,
+            raw_tx: message.raw_tx,
+            proof,
+        })
+    }
+
+    /// The message with its proof unpacked, rejected when the proof isn't of
+    /// the shape its tree index calls for.
+    pub fn unpack(self) -> Result<CkbMessage, MessageError> {
+        let proof = match self.proof {
+            Some(proof) => {
+                let proof = hex::decode(proof.as_string_trim0x())
+                    .ok()
+                    .and_then(|data| crate::proof::unpack_tx_proof(&data))
+                    .ok_or_else(|| RejectReason::InvalidProof.at("proof"))?;
+                Some(proof)
+            }
+            None => None,
+        };
+        Ok(CkbMessage {
+            tx: self.tx,
+            raw_tx: self.raw_tx,
+            proof,
+        })
+    }
+}
+
+/// Messages of the same block under one proof, message `i` is the tx at
+/// `proof.indices[i]`. The proofs of the messages themselves are ignored.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BatchMessagePayload {
+    pub height:          u64, // ckb block height
+    pub messages:        Vec<CkbMessage>,
+    pub proof:           CkbMultiProof,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub source_chain_id: Option<Hash>,
+}
+
+/// Headers appended to the header range instead of being stored, with the
+/// root the relayer holds once they are.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct UpdateMmrPayload {
+    pub headers: Vec<CkbHeader>,
+    #[schemars(with = "String")]
+    pub root:    Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MmrUpdatedEvent {
+    pub leaves: u64,
+    #[schemars(with = "String")]
+    pub root:   Hash,
+    pub topic:  String, // "mmr_updated"
+}
+
+/// Proof that a header is part of the header range as it was at `leaves`
+/// leaves.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MmrProof {
+    pub leaves:   u64,
+    // siblings of the header hash in its tree, from the leaf up
+    #[schemars(with = "Vec<String>")]
+    pub siblings: Vec<Hash>,
+    // peaks of the whole range, from the highest tree
+    #[schemars(with = "Vec<String>")]
+    pub peaks:    Vec<Hash>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MmrMessage {
+    pub header:    CkbHeader,
+    pub mmr_proof: MmrProof,
+    pub message:   CkbMessage,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MmrMessagePayload {
+    pub messages: Vec<MmrMessage>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMmrResponse {
+    pub start:  u64, // height of the first header
+    pub leaves: u64,
+    #[schemars(with = "Option<String>")]
+    pub root:   Option<Hash>,
+}
+
+/// The header range as the chain keeps it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MmrState {
+    pub start:  u64,
+    pub leaves: u64,
+    pub peaks:  Vec<Hash>,
+}
+
+/// Lowest and highest heights of the stored headers, tracked from the first
+/// header stored by a version that keeps them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaderBounds {
+    pub lowest:  u64,
+    pub highest: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMissingHeaderRangesPayload {
+    // most ranges to return
+    pub limit: u32,
+    // height to resume the scan from, the lowest stored height when unset
+    #[serde(default)]
+    pub from:  Option<u64>,
+}
+
+/// Heights from `start` to `end`, both included.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct HeightRange {
+    pub start: u64,
+    pub end:   u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default)]
+pub struct MissingHeaderRanges {
+    pub lowest:  Option<u64>,
+    pub highest: Option<u64>,
+    pub ranges:  Vec<HeightRange>,
+    // where the next call resumes, `None` once the scan reached the highest
+    // stored height
+    pub next:    Option<u64>,
+}
+
+/// Code hashes of the ckb locks the bridge can pay burns to.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct SupportedLocks {
+    #[schemars(with = "Vec<String>")]
+    pub code_hashes: Vec<Hash>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SupportedLocksEvent {
+    #[schemars(with = "Vec<String>")]
+    pub code_hashes: Vec<Hash>,
+    pub topic:       String, // "set_supported_locks"
+}
+
+/// The account named issuer of the mirror assets the bridge creates, such as
+/// the governance or custody account of the chain.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MirrorAssetIssuer {
+    #[schemars(with = "String")]
+    pub issuer: Address,
+}
+
+/// How many ckb blocks behind the highest known header a deposit can still
+/// be submitted, so stale txs can't be replayed after a rollback. 0 lets
+/// deposits of any age through.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MessageTtl {
+    pub blocks: u64,
+}
+
+/// The bridge's tunables, each as stored or as the service shipped it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Params {
+    // type script code hash of the sudt deposits must lock
+    #[schemars(with = "String")]
+    pub sudt_code_hash:           Hash,
+    pub max_pending_messages:     u64,
+    pub payout_cooldown:          u64,
+    pub max_outbound_payload_len: u64,
+    pub max_staged_headers:       u64,
+    pub staged_headers_per_block: u64,
+    #[schemars(with = "String")]
+    pub mirror_asset_issuer:      Address,
+    #[schemars(with = "String")]
+    pub recovery_account:         Address,
+    // ckb blocks behind the tip after which deposits expire, 0 never expires
+    pub message_ttl:              u64,
+    pub bridge_limits:            BridgeLimits,
+    pub fee_beneficiary:          FeeBeneficiary,
+    pub fee_schedule:             FeeSchedule,
+    // burns pay no withdrawal fee while unset
+    pub withdrawal_fee_config:    Option<WithdrawalFeeConfig>,
+    pub burn_epoch_length:        u64,
+    // muta blocks a burn batch stays open, 0 leaves burns unbatched
+    pub burn_batch_window:        u64,
+}
+
+/// Parameters to change, those left unset keep their value.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default)]
+pub struct SetParamsPayload {
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub sudt_code_hash:           Option<Hash>,
+    #[serde(default)]
+    pub max_pending_messages:     Option<u64>,
+    #[serde(default)]
+    pub payout_cooldown:          Option<u64>,
+    #[serde(default)]
+    pub max_outbound_payload_len: Option<u64>,
+    #[serde(default)]
+    pub max_staged_headers:       Option<u64>,
+    #[serde(default)]
+    pub staged_headers_per_block: Option<u64>,
+    // names the issuer of mirror assets created from then on, those created
+    // already keep theirs
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub mirror_asset_issuer:      Option<Address>,
+    #[serde(default)]
+    pub message_ttl:              Option<u64>,
+    #[serde(default)]
+    pub bridge_limits:            Option<BridgeLimits>,
+    #[serde(default)]
+    pub fee_beneficiary:          Option<FeeBeneficiary>,
+    #[serde(default)]
+    pub fee_schedule:             Option<FeeSchedule>,
+    #[serde(default)]
+    pub withdrawal_fee_config:    Option<WithdrawalFeeConfig>,
+    // applies from the next batch opened on
+    #[serde(default)]
+    pub burn_batch_window:        Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ParamsEvent {
+    pub changed: Vec<String>,
+    pub params:  Params,
+    pub topic:   String, // "set_params"
+}
+
+/// Messages waiting for the header of their height, in arrival order. The
+/// relayer that submitted a message still earns its relay fee.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default)]
+pub struct PendingMessages {
+    pub messages: Vec<PendingMessage>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct PendingMessage {
+    #[schemars(with = "String")]
+    pub relayer: Address,
+    pub message: CkbMessage,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ProcessPendingPayload {
+    pub height: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MessagesPendingEvent {
+    pub height: u64,
+    pub count:  u64,
+    pub topic:  String, // "messages_pending"
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CkbMessage {
-    pub tx:    CkbTx,
-    pub proof: Vec<Hash>,
+    #[serde(default)]
+    pub tx:     Option<CkbTx>,
+    // molecule serialized ckb `Transaction`, takes precedence over `tx`
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub raw_tx: Option<Hex>,
+    // proof of both the tx and its witnesses against the header at the
+    // payload height
+    #[serde(default)]
+    pub proof:  Option<CkbTxProof>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CkbTx {
+    #[schemars(with = "String")]
     pub version:      Hex,
     pub cell_deps:    Vec<CellDep>,
+    #[schemars(with = "Vec<String>")]
     pub header_deps:  Vec<Hash>,
     pub inputs:       Vec<CellInput>,
     pub outputs:      Vec<CellOutput>,
+    #[schemars(with = "Vec<String>")]
     pub outputs_data: Vec<Hex>,
+    #[schemars(with = "Vec<String>")]
     pub witnesses:    Vec<Hex>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CellDep {
     pub out_point: OutPoint,
     pub dep_type:  DepType,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub enum DepType {
     code,
     depgroup,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct OutPoint {
+    #[schemars(with = "String")]
     pub tx_hash: Hash,
+    #[schemars(with = "String")]
     pub index:   Hex,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CellInput {
+    #[schemars(with = "String")]
     pub since:           Hex,
     pub previous_output: OutPoint,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CellOutput {
+    #[schemars(with = "String")]
     pub capacity: Hex,
     pub lock:     Script,
     #[serde(rename = "type")]
     pub type_:    Option<Script>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
 pub struct Script {
+    #[schemars(with = "String")]
     pub code_hash: Hash,
     pub hash_type: ScriptHashType,
+    #[schemars(with = "String")]
     pub args:      Hex,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
 pub enum ScriptHashType {
     data,
     #[serde(rename = "type")]
     Type,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct MintTokenPayload {
-    pub token_id: Hash,
-    pub receiver: Address,
-    pub amount:   u128,
+    #[schemars(with = "String")]
+    pub token_id:  Hash,
+    #[schemars(with = "String")]
+    pub receiver:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:    u128,
+    pub memo:      String,
+    // ckb tx of a deposit, for the asset's issuance log
+    #[schemars(with = "Option<String>")]
+    pub source_tx: Option<Hash>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// The bridge side of a mint, the receiver, amount and memo are in the
+/// `MintEvent` the asset service emits just before.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct MintTokenEvent {
-    pub asset_id:   Hash,   // ckb sudt type args
-    pub asset_name: String, //
-    pub receiver:   Address,
-    pub amount:     u128,
-    pub kind:       String, // "cross_to_muta"
-    pub topic:      String, // "mint_asset"
+    #[schemars(with = "String")]
+    pub asset_id:    Hash, // muta mirror asset id
+    #[schemars(with = "String")]
+    pub sudt_args:   Hash, // ckb sudt type args
+    pub asset_name:  String,
+    // ckb tx of the deposit, `None` for eth and muta deposits
+    #[schemars(with = "Option<String>")]
+    pub ckb_tx_hash: Option<Hash>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain:       Option<ChainBinding>,
+    pub kind:        String, // "cross_to_muta"
+    pub topic:       String, // "mint_asset"
 }
 
-impl rlp::Decodable for CkbHeaderInner {
-    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        let buf: Vec<u8> = rlp.at(10)?.as_val()?;
-        Ok(Self {
-            compact_target:    rlp.at(0)?.as_val()?,
-            version:           rlp.at(1)?.as_val()?,
-            timestamp:         rlp.at(2)?.as_val()?,
-            number:            rlp.at(3)?.as_val()?,
-            epoch:             rlp.at(4)?.as_val()?,
-            parent_hash:       rlp.at(5)?.as_val()?,
-            transactions_root: rlp.at(6)?.as_val()?,
-            proposals_hash:    rlp.at(7)?.as_val()?,
-            uncles_hash:       rlp.at(8)?.as_val()?,
-            dao:               rlp.at(9)?.as_val()?,
-            nonce:             LittleEndian::read_u128(&buf),
-        })
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SudtDustEvent {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    #[schemars(with = "String")]
+    pub sudt_args: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub dust:      u128,
+    pub topic:     String, // "sudt_dust"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MessageRejectedEvent {
+    pub reason: String,
+    // position of the message in the submitted payload, when known
+    #[serde(default)]
+    pub index:  Option<u64>,
+    // the field of the message that failed to parse
+    #[serde(default)]
+    pub field:  Option<String>,
+    pub topic:  String, // "message_rejected"
+}
+
+/// Why an inbound message was skipped instead of minted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RejectReason {
+    InvalidTx,
+    InvalidAmount,
+    InvalidReceiver,
+    UnregisteredSudt,
+    InvalidProof,
+    DuplicateTx,
+    UnregisteredToken,
+    BridgePaused,
+    Expired,
+    CallNotAllowed,
+    // the mint of a queued message failed as its header was stored
+    MintFailed,
+}
+
+impl RejectReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectReason::InvalidTx => "invalid_tx",
+            RejectReason::InvalidAmount => "invalid_amount",
+            RejectReason::InvalidReceiver => "invalid_receiver",
+            RejectReason::UnregisteredSudt => "unregistered_sudt",
+            RejectReason::InvalidProof => "invalid_proof",
+            RejectReason::DuplicateTx => "duplicate_tx",
+            RejectReason::UnregisteredToken => "unregistered_token",
+            RejectReason::BridgePaused => "bridge_paused",
+            RejectReason::Expired => "expired",
+            RejectReason::CallNotAllowed => "call_not_allowed",
+            RejectReason::MintFailed => "mint_failed",
+        }
+    }
+
+    /// The rejection of a message whose `field` failed to parse.
+    pub fn at(self, field: &'static str) -> MessageError {
+        MessageError {
+            reason: self,
+            index:  None,
+            field:  Some(field),
+        }
     }
 }
 
-impl rlp::Encodable for CkbHeaderInner {
-    fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(11)
-            .append(&self.compact_target)
-            .append(&self.version)
-            .append(&self.timestamp)
-            .append(&self.number)
-            .append(&self.epoch)
-            .append(&self.parent_hash)
-            .append(&self.transactions_root)
-            .append(&self.proposals_hash)
-            .append(&self.uncles_hash)
-            .append(&self.dao);
+/// A rejection along with where in the payload it arose, for the relayer to
+/// tell which message to fix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MessageError {
+    pub reason: RejectReason,
+    pub index:  Option<usize>,
+    pub field:  Option<&'static str>,
+}
 
-        let mut buf = [0u8; mem::size_of::<u128>()];
-        LittleEndian::write_u128(&mut buf, self.nonce);
-        s.append(&buf.to_vec());
+impl MessageError {
+    pub fn in_message(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
     }
 }
 
-impl FixedCodec for CkbHeaderInner {
-    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
-        Ok(Bytes::from(rlp::encode(self)))
+impl From<RejectReason> for MessageError {
+    fn from(reason: RejectReason) -> Self {
+        MessageError {
+            reason,
+            index: None,
+            field: None,
+        }
     }
+}
 
-    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
-        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.reason.as_str())?;
+        if let Some(index) = self.index {
+            write!(f, " at message {}", index)?;
+        }
+        if let Some(field) = self.field {
+            write!(f, " in {}", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// A message tx reduced to what the bridge reads of it.
+#[derive(Clone, Debug)]
+pub struct MessageTx {
+    pub tx_hash:      Hash,
+    pub witness_hash: Hash,
+    // parsed along with the tx, only acted upon once it is proven
+    pub deposit:      Result<Deposit, MessageError>,
+    // the call the tx carries instead of a deposit
+    pub call:         Option<Result<CrossCall, MessageError>>,
+}
+
+/// A call of a muta service parsed out of a ckb tx.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossCall {
+    pub service:   String,
+    pub method:    String,
+    pub payload:   String,
+    // type script of the cell carrying the call
+    pub code_hash: Hash,
+    pub args:      Hex,
+}
+
+/// A sudt deposit parsed out of a ckb tx.
+#[derive(Clone, Debug)]
+pub struct Deposit {
+    pub sudt_args:   Hash,
+    pub sudt_amount: u128,
+    // `None` when the witness isn't a valid muta address, the deposit then
+    // goes to the recovery account
+    pub receiver:    Option<Address>,
+    // deposit tag the witness names instead of the receiver
+    pub tag:         Option<u64>,
+    pub memo:        String,
+}
+
+/// A proven message the admin processes with corrected fields, the overrides
+/// left `None` keep what the message carries.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ForceProcessMessagePayload {
+    pub height:   u64,
+    pub message:  CkbMessage,
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub receiver: Option<Address>,
+    #[serde(default)]
+    pub memo:     Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MessageForcedEvent {
+    #[schemars(with = "String")]
+    pub tx_hash:  Hash,
+    pub height:   u64,
+    #[schemars(with = "Option<String>")]
+    pub receiver: Option<Address>,
+    pub memo:     Option<String>,
+    #[schemars(with = "String")]
+    pub admin:    Address,
+    pub topic:    String, // "force_process_message"
+}
+
+/// A deposit minted to the recovery account because its receiver was
+/// malformed, until the admin reassigns it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct RecoveredDeposit {
+    #[schemars(with = "String")]
+    pub asset_id:      Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:        u128,
+    #[schemars(with = "Option<String>")]
+    pub reassigned_to: Option<Address>,
+}
+
+/// A ckb deposit as it was credited, to the recovery account when its
+/// receiver was malformed.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ProcessedDeposit {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    #[schemars(with = "String")]
+    pub receiver:  Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:    u128, // credited to the receiver
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub relay_fee: u128,
+    #[schemars(with = "String")]
+    pub relayer:   Address,
+    pub memo:      String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetDepositByCkbTxPayload {
+    #[schemars(with = "String")]
+    pub tx_hash: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetRecoveredDepositPayload {
+    #[schemars(with = "String")]
+    pub tx_hash: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ReassignDepositPayload {
+    #[schemars(with = "String")]
+    pub tx_hash:  Hash,
+    #[schemars(with = "String")]
+    pub receiver: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct DepositRecoveredEvent {
+    #[schemars(with = "String")]
+    pub tx_hash:  Hash,
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+    pub topic:    String, // "deposit_recovered"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct DepositReassignedEvent {
+    #[schemars(with = "String")]
+    pub tx_hash:  Hash,
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+    #[schemars(with = "String")]
+    pub receiver: Address,
+    #[schemars(with = "String")]
+    pub admin:    Address,
+    pub topic:    String, // "deposit_reassigned"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AssetVolume {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub minted:   u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub burned:   u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+pub struct BridgeStats {
+    pub total_mints:       u64,
+    pub total_burns:       u64,
+    pub headers_stored:    u64,
+    pub rejected_messages: BTreeMap<String, u64>,
+    pub volumes:           Vec<AssetVolume>,
+}
+
+impl BridgeStats {
+    /// Minted minus burned by the bridge, zero for assets it never minted.
+    pub fn outstanding(&self, asset_id: &Hash) -> u128 {
+        self.volumes
+            .iter()
+            .find(|v| &v.asset_id == asset_id)
+            .map_or(0, |v| v.minted.saturating_sub(v.burned))
+    }
+
+    pub fn volume_mut(&mut self, asset_id: &Hash) -> &mut AssetVolume {
+        let pos = match self.volumes.iter().position(|v| &v.asset_id == asset_id) {
+            Some(pos) => pos,
+            None => {
+                self.volumes.push(AssetVolume {
+                    asset_id: asset_id.clone(),
+                    minted:   0,
+                    burned:   0,
+                });
+                self.volumes.len() - 1
+            }
+        };
+
+        &mut self.volumes[pos]
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct CreateMirrorAssetPayload {
+    #[schemars(with = "String")]
+    pub id:       Hash,
+    pub name:     String,
+    #[schemars(with = "String")]
+    pub issuer:   Address,
+    // stored as the asset's "decimals" metadata
+    #[serde(default)]
+    pub decimals: Option<u64>,
+}
+
+/// The rbac service's `has_role` read, by which the bridge checks its admins
+/// and relayers when deferring to it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HasRolePayload {
+    pub service: String,
+    pub role:    String,
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct HasRoleResponse {
+    pub has_role: bool,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RegisterMirrorAssetPayload {
+    #[schemars(with = "String")]
+    pub sudt_args:       Hash,
+    pub name:            String,
+    pub symbol:          String,
+    pub decimals:        u64,
+    // raw sudt units per mirror asset unit, 1 for a one-to-one mapping
+    pub scale_factor:    u64,
+    #[serde(default)]
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount: u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RegisterMirrorAssetEvent {
+    #[schemars(with = "String")]
+    pub asset_id:     Hash,
+    #[schemars(with = "String")]
+    pub sudt_args:    Hash,
+    pub name:         String,
+    pub symbol:       String,
+    pub decimals:     u64,
+    pub scale_factor: u64,
+    pub topic:        String, // "register_mirror_asset"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetBurnLimitPayload {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount: u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetBurnLimitEvent {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount: u128,
+    pub topic:           String, // "set_burn_limit"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetBurnLimitResponse {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount: u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetBurnQuotaPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub quota:    u128, // max amount burned per address within the window, 0 disables it
+    pub window:   u64, // window length in blocks
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetBurnQuotaEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub quota:    u128,
+    pub window:   u64,
+    pub topic:    String, // "set_burn_quota"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetBurnQuotaResponse {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub quota:    u128,
+    pub window:   u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct QuotaExemptionPayload {
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct QuotaExemptionEvent {
+    #[schemars(with = "String")]
+    pub address: Address,
+    pub topic:   String, // "add_quota_exemption" or "remove_quota_exemption"
+}
+
+/// Where withdrawal fees are paid, in which asset. Burns pay no fee until
+/// the admin sets it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct WithdrawalFeeConfig {
+    #[schemars(with = "String")]
+    pub native_asset_id: Hash,
+    #[schemars(with = "String")]
+    pub treasury:        Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetWithdrawalFeePayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub fee:      u128, // native asset units each burn_sudt pays, 0 disables it
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetWithdrawalFeeEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub fee:      u128,
+    pub topic:    String, // "set_withdrawal_fee"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct FeeWaiverPayload {
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct FeeWaiverEvent {
+    #[schemars(with = "String")]
+    pub address: Address,
+    pub topic:   String, // "add_fee_waiver" or "remove_fee_waiver"
+}
+
+/// What a burn of the asset by the caller would pay on top of the burned
+/// amount. `native_asset_id` and `treasury` are unset while no fee applies.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct WithdrawalFeeEstimate {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub fee:             u128,
+    #[schemars(with = "Option<String>")]
+    pub native_asset_id: Option<Hash>,
+    #[schemars(with = "Option<String>")]
+    pub treasury:        Option<Address>,
+    // the caller is whitelisted, the fee it would otherwise pay is `fee`
+    pub waived:          bool,
+}
+
+/// A withdrawal fee paid by a burn into the treasury.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct WithdrawalFeeEvent {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[schemars(with = "String")]
+    pub payer:           Address,
+    #[schemars(with = "String")]
+    pub native_asset_id: Hash,
+    #[schemars(with = "String")]
+    pub treasury:        Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub fee:             u128,
+    pub topic:           String, // "withdrawal_fee"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct TransferCallAssetPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub to:       Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub value:    u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetSupplyCapPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub cap:      u128, // most mirrored supply backed by collateral, 0 disables it
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetSupplyCapEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub cap:      u128,
+    pub topic:    String, // "set_supply_cap"
+}
+
+/// What the bridge minted of an asset and has not burned back yet.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BridgedSupply {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub minted:      u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub burned:      u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub outstanding: u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub cap:         u128,
+    pub paused:      bool,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BridgePausedEvent {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub outstanding: u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:      u128, // the mint that would have crossed the cap
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub cap:         u128,
+    pub topic:       String, // "bridge_paused"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ResumeBridgePayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BridgeResumedEvent {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    pub topic:    String, // "bridge_resumed"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct QuoteDepositPayload {
+    #[schemars(with = "String")]
+    pub sudt_args:   Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub sudt_amount: u128,
+    // adds the mirror amounts in whole tokens, by the asset's decimals
+    #[serde(default)]
+    pub formatted:   bool,
+}
+
+/// What a deposit of `sudt_amount` would credit its receiver.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct DepositQuote {
+    #[schemars(with = "String")]
+    pub asset_id:           Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub sudt_amount:        u128,
+    pub scale_factor:       u64,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub dust:               u128, // left locked on ckb by the rescaling
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:             u128, // mirror amount before the relay fee
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub relay_fee:          u128,
+    pub relay_fee_bps:      u16, // rate of the fee schedule tier `amount` falls in
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub credited:           u128,
+    // the deposit would be rejected until the admin resumes the asset
+    pub paused:             bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted_amount:   Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted_credited: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct QuoteBurnPayload {
+    #[schemars(with = "String")]
+    pub asset_id:  Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:    u128,
+    // adds the amount in whole tokens, by the asset's decimals
+    #[serde(default)]
+    pub formatted: bool,
+}
+
+/// What a burn of `amount` by the caller would unlock on ckb.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnQuote {
+    #[schemars(with = "String")]
+    pub asset_id:         Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:           u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount:  u128,
+    pub scale_factor:     u64,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub sudt_amount:      u128,
+    // left in the caller's quota window, `None` when no quota applies
+    #[serde(default, with = "common::u128_string::option")]
+    #[schemars(with = "Option<String>")]
+    pub quota_remaining:  Option<u128>,
+    pub paused:           bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted_amount: Option<String>,
+}
+
+/// Bounds on the messages one call takes and the volume one muta block
+/// mints, which bound the time a block spends in the bridge. 0 lifts a bound.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+pub struct BridgeLimits {
+    pub max_batch_messages: u32,
+    // mirror amounts minted in a block, summed over every asset; a mint is
+    // admitted only if the block stays within it
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub block_mint_budget:  u128,
+}
+
+/// Who the relay fee of a minted deposit is paid to.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeBeneficiary {
+    // the relayer that submitted the message
+    TxCaller,
+    // a treasury, the relayer gets nothing
+    FixedAddress(#[schemars(with = "String")] Address),
+    // shared in basis points, the shares sum to 10000; rounding favours the
+    // relayer
+    Split {
+        caller_bps:   u16,
+        #[schemars(with = "String")]
+        treasury:     Address,
+        treasury_bps: u16,
+    },
+}
+
+impl Default for FeeBeneficiary {
+    fn default() -> Self {
+        FeeBeneficiary::TxCaller
+    }
+}
+
+/// A deposit minting at least `threshold` mirror units pays `bps` of it as
+/// relay fee, unless it reaches a later tier.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct FeeTier {
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub threshold: u128,
+    pub bps:       u16,
+}
+
+/// Relay fee rates by deposit size. Tiers ascend strictly by threshold from
+/// 0, so every deposit falls in exactly one.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct FeeSchedule {
+    pub tiers: Vec<FeeTier>,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        FeeSchedule {
+            tiers: vec![FeeTier {
+                threshold: 0,
+                bps:       100,
+            }],
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetEpochSummaryPayload {
+    pub epoch: u64,
+}
+
+/// Burns of a mirror asset in a burn epoch paying to one lock code hash.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EpochLockBurns {
+    #[schemars(with = "String")]
+    pub code_hash:   Hash,
+    pub burns:       u64,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub sudt_amount: u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EpochAssetBurns {
+    #[schemars(with = "String")]
+    pub asset_id:    Hash,
+    #[schemars(with = "String")]
+    pub sudt_args:   Hash,
+    pub burns:       u64,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:      u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub sudt_amount: u128,
+    pub locks:       Vec<EpochLockBurns>,
+}
+
+/// Burns to ckb made in the muta blocks `start_height..=end_height`, which
+/// the ckb operators can withdraw in one tx once the epoch is closed. The
+/// burn records are the nonces `first_nonce..=last_nonce`, both 0 when there
+/// are none; cancelled burns are left out of the totals.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EpochSummary {
+    pub epoch:        u64,
+    pub start_height: u64,
+    pub end_height:   u64,
+    // no more burns land in the epoch
+    pub closed:       bool,
+    pub burns:        u64,
+    pub first_nonce:  u64,
+    pub last_nonce:   u64,
+    pub assets:       Vec<EpochAssetBurns>,
+}
+
+impl EpochSummary {
+    pub fn asset_mut(&mut self, asset_id: &Hash, sudt_args: &Hash) -> &mut EpochAssetBurns {
+        let pos = match self.assets.iter().position(|a| &a.asset_id == asset_id) {
+            Some(pos) => pos,
+            None => {
+                self.assets.push(EpochAssetBurns {
+                    asset_id:    asset_id.clone(),
+                    sudt_args:   sudt_args.clone(),
+                    burns:       0,
+                    amount:      0,
+                    sudt_amount: 0,
+                    locks:       vec![],
+                });
+                self.assets.len() - 1
+            }
+        };
+
+        &mut self.assets[pos]
+    }
+}
+
+impl EpochAssetBurns {
+    pub fn lock_mut(&mut self, code_hash: &Hash) -> &mut EpochLockBurns {
+        let pos = match self.locks.iter().position(|l| &l.code_hash == code_hash) {
+            Some(pos) => pos,
+            None => {
+                self.locks.push(EpochLockBurns {
+                    code_hash:   code_hash.clone(),
+                    burns:       0,
+                    sudt_amount: 0,
+                });
+                self.locks.len() - 1
+            }
+        };
+
+        &mut self.locks[pos]
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetBurnBatchPayload {
+    pub batch: u64,
+}
+
+/// Burns to ckb made while a batching window was open, which the relayer
+/// withdraws in one ckb tx. `root` is the ckb merkle root over `leaves`, the
+/// ckb blake2b hash of each burn's `burn_asset` event json in nonce order;
+/// it is final once the after block hook of `end_height` seals the batch.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BurnBatch {
+    pub batch:        u64,
+    pub start_height: u64,
+    pub end_height:   u64,
+    pub first_nonce:  u64,
+    pub last_nonce:   u64,
+    #[schemars(with = "Vec<String>")]
+    pub leaves:       Vec<Hash>,
+    #[schemars(with = "String")]
+    pub root:         Hash,
+    pub sealed:       bool,
+    // `BurnBatchEvent` was emitted
+    #[serde(skip)]
+    pub announced:    bool,
+}
+
+/// A sealed burn batch, emitted by the first burn after it was sealed or by
+/// `announce_burn_batches`, since the after block hook can't emit events.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnBatchEvent {
+    pub batch:       u64,
+    pub end_height:  u64,
+    pub first_nonce: u64,
+    pub last_nonce:  u64,
+    pub burns:       u64,
+    #[schemars(with = "String")]
+    pub root:        Hash,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain:       Option<ChainBinding>,
+    pub topic:       String, // "burn_batch"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetDepositTagPayload {
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+/// The deposit tag of an address, which a deposit witness can name instead
+/// of the address once the address registered it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct DepositTag {
+    #[schemars(with = "String")]
+    pub address:    Address,
+    pub tag:        u64,
+    pub registered: bool,
+    // the deposit witness naming the tag, before any memo
+    #[schemars(with = "String")]
+    pub witness:    Hex,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct DepositTagEvent {
+    #[schemars(with = "String")]
+    pub address: Address,
+    pub tag:     u64,
+    pub topic:   String, // "register_deposit_tag"
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SetPayoutAddressPayload {
+    #[schemars(with = "String")]
+    pub payout: Address,
+}
+
+/// Where the relay fees a relayer earns are minted, kept apart from the key
+/// it signs with so rotated keys can share one. Relayers that never set one
+/// are paid themselves.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct PayoutAddress {
+    #[schemars(with = "String")]
+    pub relayer:       Address,
+    #[schemars(with = "String")]
+    pub payout:        Address,
+    // first muta block the payout address can change again at
+    pub changeable_at: u64,
+}
+
+/// A payout address as stored, with the muta block it was set at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelayerPayout {
+    pub payout: Address,
+    pub set_at: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct PayoutAddressEvent {
+    #[schemars(with = "String")]
+    pub relayer: Address,
+    #[schemars(with = "String")]
+    pub payout:  Address,
+    pub topic:   String, // "set_payout_address"
+}
+
+/// What the bridge minted in the muta block at `height`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockMintUsage {
+    pub height: u64,
+    pub minted: u128,
+}
+
+/// Bridge events of the muta block at `height`: how many mints and burns it
+/// emitted, and the ckb merkle root over the ckb blake2b hash of each
+/// event's json, in emission order. Light clients rebuild the root from the
+/// events a full node serves to tell whether any is missing.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EventDigest {
+    pub height: u64,
+    pub mints:  u32,
+    pub burns:  u32,
+    #[schemars(with = "String")]
+    pub root:   Hash,
+}
+
+/// Digests of the blocks in a range that emitted bridge events, blocks
+/// without any having none. A call scans a bounded number of heights, `next`
+/// tells where to resume when it stopped early.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default)]
+pub struct EventDigests {
+    pub digests: Vec<EventDigest>,
+    pub next:    Option<u64>,
+}
+
+/// Leaves of the digest of the block being executed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockEventHashes {
+    pub height: u64,
+    pub hashes: Vec<Hash>,
+}
+
+/// Collateral cap of an asset and whether crossing it paused the asset.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SupplyCap {
+    pub cap:    u128,
+    pub paused: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct BurnQuota {
+    pub quota:  u128,
+    pub window: u64,
+}
+
+/// Burns of one address for one asset still inside the quota window, as
+/// (height, amount) pairs.
+#[derive(Clone, Debug)]
+pub struct BurnQuotaUsage {
+    pub burns: Vec<(u64, u128)>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMirrorAssetBySudtPayload {
+    #[schemars(with = "String")]
+    pub sudt_args: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMirrorAssetByIdPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMirrorAssetIdResponse {
+    #[schemars(with = "String")]
+    pub asset_id:   Hash,
+    pub registered: bool,
+}
+
+/// A ckb sudt bridged to muta, keyed by its type args.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MirrorAsset {
+    #[schemars(with = "String")]
+    pub sudt_args:       Hash,
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    pub name:            String,
+    pub symbol:          String,
+    pub decimals:        u64,
+    pub scale_factor:    u64,
+    // smallest mirror asset amount accepted by burn_sudt, so the unlocked ckb
+    // cell can always be redeemed
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount: u128,
+}
+
+impl MirrorAsset {
+    /// Converts a raw sudt amount to the mirror asset amount, returning the
+    /// converted amount and the dust left over by the rescaling.
+    pub fn to_mirror_amount(&self, sudt_amount: u128) -> (u128, u128) {
+        let scale = u128::from(self.scale_factor);
+        (sudt_amount / scale, sudt_amount % scale)
+    }
+
+    /// Converts a mirror asset amount back to raw sudt units, `None` on
+    /// overflow.
+    pub fn to_sudt_amount(&self, amount: u128) -> Option<u128> {
+        amount.checked_mul(u128::from(self.scale_factor))
+    }
+
+    /// Decimals mirror amounts are displayed with, `None` when registered
+    /// with more than `MAX_DECIMALS`.
+    pub fn display_decimals(&self) -> Option<u32> {
+        Some(self.decimals)
+            .filter(|decimals| *decimals <= u64::from(MAX_DECIMALS))
+            .map(|decimals| decimals as u32)
+    }
+
+    /// A mirror amount in whole tokens, `None` without display decimals.
+    pub fn format_amount(&self, amount: u128) -> Option<String> {
+        self.display_decimals()
+            .map(|decimals| Amount(amount).to_display(decimals))
+    }
+}
+
+impl rlp::Decodable for MirrorAsset {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            sudt_args:       rlp.at(0)?.as_val()?,
+            asset_id:        rlp.at(1)?.as_val()?,
+            name:            rlp.at(2)?.as_val()?,
+            symbol:          rlp.at(3)?.as_val()?,
+            decimals:        rlp.at(4)?.as_val()?,
+            scale_factor:    rlp.at(5)?.as_val()?,
+            min_burn_amount: rlp.val_at::<Amount>(6)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for MirrorAsset {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(7)
+            .append(&self.sudt_args)
+            .append(&self.asset_id)
+            .append(&self.name)
+            .append(&self.symbol)
+            .append(&self.decimals)
+            .append(&self.scale_factor);
+
+        s.append(&Amount(self.min_burn_amount));
+    }
+}
+
+impl FixedCodec for MirrorAsset {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for BurnStatus {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        match rlp.as_val::<u8>()? {
+            0 => Ok(BurnStatus::Pending),
+            1 => Ok(BurnStatus::Cancelled),
+            2 => Ok(BurnStatus::Completed),
+            _ => Err(rlp::DecoderError::Custom("invalid burn status")),
+        }
+    }
+}
+
+impl rlp::Encodable for BurnStatus {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let status: u8 = match self {
+            BurnStatus::Pending => 0,
+            BurnStatus::Cancelled => 1,
+            BurnStatus::Completed => 2,
+        };
+        s.append(&status);
+    }
+}
+
+impl rlp::Decodable for BurnRecord {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            nonce:        rlp.at(0)?.as_val()?,
+            asset_id:     rlp.at(1)?.as_val()?,
+            sudt_args:    rlp.at(2)?.as_val()?,
+            muta_sender:  rlp.at(3)?.as_val()?,
+            ckb_receiver: rlp.at(4)?.as_val()?,
+            amount:       rlp.val_at::<Amount>(5)?.0,
+            sudt_amount:  rlp.val_at::<Amount>(6)?.0,
+            status:       rlp.at(7)?.as_val()?,
+            ckb_tx_hash:  rlp.at(8)?.as_list::<Hash>()?.pop(),
+        })
+    }
+}
+
+impl rlp::Encodable for BurnRecord {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(9)
+            .append(&self.nonce)
+            .append(&self.asset_id)
+            .append(&self.sudt_args)
+            .append(&self.muta_sender)
+            .append(&self.ckb_receiver);
+
+        s.append(&Amount(self.amount));
+        s.append(&Amount(self.sudt_amount)).append(&self.status);
+
+        let ckb_tx_hash: Vec<Hash> = self.ckb_tx_hash.iter().cloned().collect();
+        s.append_list(&ckb_tx_hash);
+    }
+}
+
+impl FixedCodec for BurnRecord {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for BurnQuota {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            quota:  rlp.val_at::<Amount>(0)?.0,
+            window: rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for BurnQuota {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2);
+        s.append(&Amount(self.quota)).append(&self.window);
+    }
+}
+
+impl FixedCodec for BurnQuota {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for BridgeLimits {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            max_batch_messages: rlp.at(0)?.as_val()?,
+            block_mint_budget:  rlp.val_at::<Amount>(1)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for BridgeLimits {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.max_batch_messages)
+            .append(&Amount(self.block_mint_budget));
+    }
+}
+
+impl FixedCodec for BridgeLimits {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for FeeBeneficiary {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        match rlp.val_at::<u8>(0)? {
+            0 => Ok(FeeBeneficiary::TxCaller),
+            1 => Ok(FeeBeneficiary::FixedAddress(rlp.val_at(1)?)),
+            2 => Ok(FeeBeneficiary::Split {
+                caller_bps:   rlp.val_at(1)?,
+                treasury:     rlp.val_at(2)?,
+                treasury_bps: rlp.val_at(3)?,
+            }),
+            _ => Err(rlp::DecoderError::Custom("invalid fee beneficiary")),
+        }
+    }
+}
+
+impl rlp::Encodable for FeeBeneficiary {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        match self {
+            FeeBeneficiary::TxCaller => {
+                s.begin_list(1).append(&0u8);
+            }
+            FeeBeneficiary::FixedAddress(treasury) => {
+                s.begin_list(2).append(&1u8).append(treasury);
+            }
+            FeeBeneficiary::Split {
+                caller_bps,
+                treasury,
+                treasury_bps,
+            } => {
+                s.begin_list(4)
+                    .append(&2u8)
+                    .append(caller_bps)
+                    .append(treasury)
+                    .append(treasury_bps);
+            }
+        }
+    }
+}
+
+impl FixedCodec for FeeBeneficiary {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for FeeTier {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            threshold: rlp.val_at::<Amount>(0)?.0,
+            bps:       rlp.val_at(1)?,
+        })
+    }
+}
+
+impl rlp::Encodable for FeeTier {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&Amount(self.threshold))
+            .append(&self.bps);
+    }
+}
+
+impl rlp::Decodable for FeeSchedule {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            tiers: rlp.list_at(0)?,
+        })
+    }
+}
+
+impl rlp::Encodable for FeeSchedule {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(1).append_list(&self.tiers);
+    }
+}
+
+impl FixedCodec for FeeSchedule {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for EpochLockBurns {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            code_hash:   rlp.at(0)?.as_val()?,
+            burns:       rlp.at(1)?.as_val()?,
+            sudt_amount: rlp.val_at::<Amount>(2)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for EpochLockBurns {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.code_hash)
+            .append(&self.burns)
+            .append(&Amount(self.sudt_amount));
+    }
+}
+
+impl rlp::Decodable for EpochAssetBurns {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            asset_id:    rlp.at(0)?.as_val()?,
+            sudt_args:   rlp.at(1)?.as_val()?,
+            burns:       rlp.at(2)?.as_val()?,
+            amount:      rlp.val_at::<Amount>(3)?.0,
+            sudt_amount: rlp.val_at::<Amount>(4)?.0,
+            locks:       rlp.list_at(5)?,
+        })
+    }
+}
+
+impl rlp::Encodable for EpochAssetBurns {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(6)
+            .append(&self.asset_id)
+            .append(&self.sudt_args)
+            .append(&self.burns)
+            .append(&Amount(self.amount))
+            .append(&Amount(self.sudt_amount))
+            .append_list(&self.locks);
+    }
+}
+
+// `closed` depends on the height it is read at, it isn't stored.
+impl rlp::Decodable for EpochSummary {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            epoch:        rlp.at(0)?.as_val()?,
+            start_height: rlp.at(1)?.as_val()?,
+            end_height:   rlp.at(2)?.as_val()?,
+            closed:       false,
+            burns:        rlp.at(3)?.as_val()?,
+            first_nonce:  rlp.at(4)?.as_val()?,
+            last_nonce:   rlp.at(5)?.as_val()?,
+            assets:       rlp.list_at(6)?,
+        })
+    }
+}
+
+impl rlp::Encodable for EpochSummary {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(7)
+            .append(&self.epoch)
+            .append(&self.start_height)
+            .append(&self.end_height)
+            .append(&self.burns)
+            .append(&self.first_nonce)
+            .append(&self.last_nonce)
+            .append_list(&self.assets);
+    }
+}
+
+impl FixedCodec for EpochSummary {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for BurnBatch {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            batch:        rlp.at(0)?.as_val()?,
+            start_height: rlp.at(1)?.as_val()?,
+            end_height:   rlp.at(2)?.as_val()?,
+            first_nonce:  rlp.at(3)?.as_val()?,
+            last_nonce:   rlp.at(4)?.as_val()?,
+            leaves:       rlp.list_at(5)?,
+            root:         rlp.at(6)?.as_val()?,
+            sealed:       rlp.at(7)?.as_val()?,
+            announced:    rlp.at(8)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for BurnBatch {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(9)
+            .append(&self.batch)
+            .append(&self.start_height)
+            .append(&self.end_height)
+            .append(&self.first_nonce)
+            .append(&self.last_nonce)
+            .append_list(&self.leaves)
+            .append(&self.root)
+            .append(&self.sealed)
+            .append(&self.announced);
+    }
+}
+
+impl FixedCodec for BurnBatch {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for BlockMintUsage {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            height: rlp.at(0)?.as_val()?,
+            minted: rlp.val_at::<Amount>(1)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for BlockMintUsage {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.height)
+            .append(&Amount(self.minted));
+    }
+}
+
+impl FixedCodec for BlockMintUsage {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for EventDigest {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            height: rlp.val_at(0)?,
+            mints:  rlp.val_at(1)?,
+            burns:  rlp.val_at(2)?,
+            root:   rlp.val_at(3)?,
+        })
+    }
+}
+
+impl rlp::Encodable for EventDigest {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(4)
+            .append(&self.height)
+            .append(&self.mints)
+            .append(&self.burns)
+            .append(&self.root);
+    }
+}
+
+impl FixedCodec for EventDigest {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for BlockEventHashes {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            height: rlp.val_at(0)?,
+            hashes: rlp.list_at(1)?,
+        })
+    }
+}
+
+impl rlp::Encodable for BlockEventHashes {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.height)
+            .append_list(&self.hashes);
+    }
+}
+
+impl FixedCodec for BlockEventHashes {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for SupplyCap {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            cap:    rlp.val_at::<Amount>(0)?.0,
+            paused: rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for SupplyCap {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2);
+        s.append(&Amount(self.cap)).append(&self.paused);
+    }
+}
+
+impl FixedCodec for SupplyCap {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+struct QuotaUsageCodec {
+    pub height: u64,
+    pub amount: u128,
+}
+
+impl rlp::Decodable for QuotaUsageCodec {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            height: rlp.at(0)?.as_val()?,
+            amount: rlp.val_at::<Amount>(1)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for QuotaUsageCodec {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2).append(&self.height);
+        s.append(&Amount(self.amount));
+    }
+}
+
+impl rlp::Decodable for BurnQuotaUsage {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let codec_list: Vec<QuotaUsageCodec> = rlp.as_list()?;
+        Ok(Self {
+            burns: codec_list
+                .into_iter()
+                .map(|v| (v.height, v.amount))
+                .collect(),
+        })
+    }
+}
+
+impl rlp::Encodable for BurnQuotaUsage {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let codec_list: Vec<QuotaUsageCodec> = self
+            .burns
+            .iter()
+            .map(|(height, amount)| QuotaUsageCodec {
+                height: *height,
+                amount: *amount,
+            })
+            .collect();
+        s.append_list(&codec_list);
+    }
+}
+
+impl FixedCodec for BurnQuotaUsage {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+struct RejectedCodec {
+    pub reason: String,
+    pub count:  u64,
+}
+
+impl rlp::Decodable for RejectedCodec {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            reason: rlp.at(0)?.as_val()?,
+            count:  rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for RejectedCodec {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2).append(&self.reason).append(&self.count);
+    }
+}
+
+impl rlp::Decodable for AssetVolume {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            asset_id: rlp.at(0)?.as_val()?,
+            minted:   rlp.val_at::<Amount>(1)?.0,
+            burned:   rlp.val_at::<Amount>(2)?.0,
+        })
+    }
+}
+
+impl rlp::Encodable for AssetVolume {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3).append(&self.asset_id);
+
+        s.append(&Amount(self.minted));
+        s.append(&Amount(self.burned));
+    }
+}
+
+impl rlp::Decodable for BridgeStats {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let codec_list: Vec<RejectedCodec> = rlp.at(3)?.as_list()?;
+        let mut rejected_messages = BTreeMap::new();
+        for v in codec_list {
+            rejected_messages.insert(v.reason, v.count);
+        }
+
+        Ok(Self {
+            total_mints: rlp.at(0)?.as_val()?,
+            total_burns: rlp.at(1)?.as_val()?,
+            headers_stored: rlp.at(2)?.as_val()?,
+            rejected_messages,
+            volumes: rlp.at(4)?.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for BridgeStats {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5)
+            .append(&self.total_mints)
+            .append(&self.total_burns)
+            .append(&self.headers_stored);
+
+        let codec_list: Vec<RejectedCodec> = self
+            .rejected_messages
+            .iter()
+            .map(|(reason, count)| RejectedCodec {
+                reason: reason.clone(),
+                count:  *count,
+            })
+            .collect();
+        s.append_list(&codec_list).append_list(&self.volumes);
+    }
+}
+
+impl FixedCodec for BridgeStats {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for CkbHeaderInner {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        // Headers stored before the codec was versioned are an 11 item list
+        // whose nonce holds the epoch, so that value is dropped on read.
+        let (offset, nonce) = match rlp.item_count()? {
+            11 => (0, 0),
+            12 => {
+                let version: u8 = rlp.at(0)?.as_val()?;
+                if version != HEADER_CODEC_VERSION {
+                    return Err(rlp::DecoderError::Custom(
+                        "unsupported header codec version",
+                    ));
+                }
+                (1, rlp.val_at::<Amount>(11)?.0)
+            }
+            _ => return Err(rlp::DecoderError::RlpIncorrectListLen),
+        };
+
+        Ok(Self {
+            compact_target: rlp.at(offset)?.as_val()?,
+            version: rlp.at(offset + 1)?.as_val()?,
+            timestamp: rlp.at(offset + 2)?.as_val()?,
+            number: rlp.at(offset + 3)?.as_val()?,
+            epoch: rlp.at(offset + 4)?.as_val()?,
+            parent_hash: rlp.at(offset + 5)?.as_val()?,
+            transactions_root: rlp.at(offset + 6)?.as_val()?,
+            proposals_hash: rlp.at(offset + 7)?.as_val()?,
+            uncles_hash: rlp.at(offset + 8)?.as_val()?,
+            dao: rlp.at(offset + 9)?.as_val()?,
+            nonce,
+        })
+    }
+}
+
+impl rlp::Encodable for CkbHeaderInner {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(12)
+            .append(&HEADER_CODEC_VERSION)
+            .append(&self.compact_target)
+            .append(&self.version)
+            .append(&self.timestamp)
+            .append(&self.number)
+            .append(&self.epoch)
+            .append(&self.parent_hash)
+            .append(&self.transactions_root)
+            .append(&self.proposals_hash)
+            .append(&self.uncles_hash)
+            .append(&self.dao);
+
+        s.append(&Amount(self.nonce));
+    }
+}
+
+impl FixedCodec for CkbHeaderInner {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EthSyncMode {
+    // proof of work chain, headers linked by parent hash with non zero
+    // difficulty
+    Pow,
+    // beacon chain finalized headers, zero difficulty
+    SyncCommittee,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct EthConfig {
+    #[schemars(with = "String")]
+    pub bridge_contract: Hex, // address of the lock contract
+    pub mode:            EthSyncMode,
+}
+
+/// Fields of an ethereum block header the bridge relies on.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EthHeader {
+    #[schemars(with = "String")]
+    pub hash:          Hash,
+    #[schemars(with = "String")]
+    pub parent_hash:   Hash,
+    #[schemars(with = "String")]
+    pub receipts_root: Hash,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub difficulty:    u128,
+    pub number:        u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct UpdateEthHeadersPayload {
+    #[schemars(with = "Vec<String>")]
+    pub headers: Vec<Hex>, // rlp encoded block headers
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RegisterEthAssetPayload {
+    #[schemars(with = "String")]
+    pub token:           Hex, // erc20 contract address
+    pub name:            String,
+    pub symbol:          String,
+    pub decimals:        u64,
+    // erc20 units per mirror asset unit
+    pub scale_factor:    u64,
+    #[serde(default)]
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount: u128,
+}
+
+/// Inclusion proof of a receipt in the receipts trie of the header at
+/// `height`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct EthReceiptProof {
+    pub height:   u64,
+    pub tx_index: u64,
+    #[schemars(with = "String")]
+    pub receipt:  Hex, // encoded receipt, the trie value
+    #[schemars(with = "Vec<String>")]
+    pub nodes:    Vec<Hex>, // rlp encoded trie nodes from the root down
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SubmitEthReceiptsPayload {
+    pub receipts: Vec<EthReceiptProof>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnToEthPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub receiver: Hex, // ethereum address
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnToEthEvent {
+    #[schemars(with = "String")]
+    pub asset_id:     Hash,
+    #[schemars(with = "String")]
+    pub token:        Hash,
+    #[schemars(with = "String")]
+    pub muta_sender:  Address,
+    #[schemars(with = "String")]
+    pub eth_receiver: Hex,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:       u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub token_amount: u128,
+    pub nonce:        u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain:        Option<ChainBinding>,
+    pub topic:        String, // "burn_to_eth"
+}
+
+/// A validator of the counterpart muta chain, in the order of its metadata
+/// verifier list, which is the order of the proof bitmap.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MutaValidator {
+    #[schemars(with = "String")]
+    pub bls_pub_key: Hex,
+    #[schemars(with = "String")]
+    pub address:     Address,
+    pub vote_weight: u32,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MutaConfig {
+    #[schemars(with = "String")]
+    pub chain_id:       Hash,
+    #[schemars(with = "String")]
+    pub common_ref:     Hex, // bls common reference of the counterpart chain
+    pub bridge_service: String, // service emitting the locks
+}
+
+impl MutaConfig {
+    /// The common reference is hex encoded utf8, as in the muta metadata.
+    pub fn common_ref_str(&self) -> Option<String> {
+        hex::decode(self.common_ref.as_string_trim0x())
+            .ok()
+            .and_then(|common_ref| String::from_utf8(common_ref).ok())
+    }
+}
+
+/// The validators signing the counterpart blocks from `start_height` on,
+/// until the set of the next version starts.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MutaValidatorSet {
+    pub version:      u64,
+    pub start_height: u64,
+    pub validators:   Vec<MutaValidator>,
+}
+
+/// The next validator set, announced by the header at `start_height - 1` and
+/// signed by a quorum of the current set, which binds the BLS keys the header
+/// doesn't carry.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MutaValidatorUpdate {
+    pub validator_set: MutaValidatorSet,
+    #[schemars(with = "String")]
+    pub signature:     Hex, // aggregated over the hash of the encoded set
+    #[schemars(with = "String")]
+    pub bitmap:        Hex,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMutaValidatorSetPayload {
+    pub height: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MutaValidatorSetEvent {
+    pub version:      u64,
+    pub start_height: u64,
+    pub validators:   Vec<MutaValidator>,
+    pub topic:        String, // "muta_validator_set"
+}
+
+/// Fields of a muta block header the bridge relies on. `proof` is the BFT
+/// proof of the parent block, so a header is only final once its child is
+/// stored.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MutaHeader {
+    #[schemars(with = "String")]
+    pub hash:              Hash,
+    #[schemars(with = "String")]
+    pub chain_id:          Hash,
+    pub height:            u64,
+    pub exec_height:       u64,
+    #[schemars(with = "String")]
+    pub prev_hash:         Hash,
+    #[schemars(with = "Vec<String>")]
+    pub receipt_roots:     Vec<Hash>,
+    #[schemars(with = "serde_json::Value")]
+    pub proof:             Proof,
+    pub validator_version: u64,
+    // the validators of the following blocks, without their BLS keys
+    #[schemars(with = "serde_json::Value")]
+    pub validators:        Vec<Validator>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct UpdateMutaHeadersPayload {
+    #[schemars(with = "Vec<String>")]
+    pub headers: Vec<Hex>, // fixed encoded block headers
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetMutaHeaderPayload {
+    pub height: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct RegisterMutaAssetPayload {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash, // asset id on the counterpart chain
+    pub name:            String,
+    pub symbol:          String,
+    pub decimals:        u64,
+    // counterpart asset units per mirror asset unit
+    pub scale_factor:    u64,
+    #[serde(default)]
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub min_burn_amount: u128,
+}
+
+/// One level of a merkle path, `left` when the sibling is the left node.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MerkleStep {
+    #[schemars(with = "String")]
+    pub sibling: Hash,
+    pub left:    bool,
+}
+
+/// Inclusion proof of a receipt under the `root_index`th receipt root of the
+/// header at `height`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MutaReceiptProof {
+    pub height:     u64,
+    pub root_index: u64,
+    #[schemars(with = "String")]
+    pub receipt:    Hex, // fixed encoded receipt
+    pub path:       Vec<MerkleStep>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct SubmitMutaReceiptsPayload {
+    pub receipts: Vec<MutaReceiptProof>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnToMutaPayload {
+    #[schemars(with = "String")]
+    pub asset_id: Hash,
+    #[schemars(with = "String")]
+    pub receiver: Address, // address on the counterpart chain
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:   u128,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BurnToMutaEvent {
+    #[schemars(with = "String")]
+    pub asset_id:        Hash,
+    #[schemars(with = "String")]
+    pub origin_asset_id: Hash,
+    #[schemars(with = "String")]
+    pub sender:          Address,
+    #[schemars(with = "String")]
+    pub receiver:        Address,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub amount:          u128,
+    #[serde(with = "common::u128_string")]
+    #[schemars(with = "String")]
+    pub origin_amount:   u128,
+    pub nonce:           u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain:           Option<ChainBinding>,
+    pub topic:           String, // "burn_to_muta"
+}
+
+impl rlp::Decodable for EthSyncMode {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        match rlp.as_val::<u8>()? {
+            0 => Ok(EthSyncMode::Pow),
+            1 => Ok(EthSyncMode::SyncCommittee),
+            _ => Err(rlp::DecoderError::Custom("invalid eth sync mode")),
+        }
+    }
+}
+
+impl rlp::Encodable for EthSyncMode {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let mode: u8 = match self {
+            EthSyncMode::Pow => 0,
+            EthSyncMode::SyncCommittee => 1,
+        };
+        s.append(&mode);
+    }
+}
+
+impl rlp::Decodable for EthConfig {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let bridge_contract: String = rlp.at(0)?.as_val()?;
+        Ok(Self {
+            bridge_contract: Hex::from_string(bridge_contract)
+                .map_err(|_| rlp::DecoderError::Custom("invalid bridge contract"))?,
+            mode:            rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for EthConfig {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.bridge_contract.as_string())
+            .append(&self.mode);
+    }
+}
+
+impl FixedCodec for EthConfig {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for EthHeader {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            hash:          rlp.at(0)?.as_val()?,
+            parent_hash:   rlp.at(1)?.as_val()?,
+            receipts_root: rlp.at(2)?.as_val()?,
+            difficulty:    rlp.val_at::<Amount>(3)?.0,
+            number:        rlp.at(4)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for EthHeader {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5)
+            .append(&self.hash)
+            .append(&self.parent_hash)
+            .append(&self.receipts_root);
+
+        s.append(&Amount(self.difficulty)).append(&self.number);
+    }
+}
+
+impl FixedCodec for EthHeader {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for MutaValidator {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let bls_pub_key: String = rlp.at(0)?.as_val()?;
+        Ok(Self {
+            bls_pub_key: Hex::from_string(bls_pub_key)
+                .map_err(|_| rlp::DecoderError::Custom("invalid bls pub key"))?,
+            address:     rlp.at(1)?.as_val()?,
+            vote_weight: rlp.at(2)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for MutaValidator {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.bls_pub_key.as_string())
+            .append(&self.address)
+            .append(&self.vote_weight);
+    }
+}
+
+impl rlp::Decodable for MutaConfig {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let common_ref: String = rlp.at(1)?.as_val()?;
+        Ok(Self {
+            chain_id:       rlp.at(0)?.as_val()?,
+            common_ref:     Hex::from_string(common_ref)
+                .map_err(|_| rlp::DecoderError::Custom("invalid common ref"))?,
+            bridge_service: rlp.at(2)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for MutaConfig {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.chain_id)
+            .append(&self.common_ref.as_string())
+            .append(&self.bridge_service);
+    }
+}
+
+impl FixedCodec for MutaConfig {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for MutaValidatorSet {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            version:      rlp.at(0)?.as_val()?,
+            start_height: rlp.at(1)?.as_val()?,
+            validators:   rlp.at(2)?.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for MutaValidatorSet {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.version)
+            .append(&self.start_height)
+            .append_list(&self.validators);
+    }
+}
+
+impl FixedCodec for MutaValidatorSet {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for MutaHeader {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            hash:              rlp.at(0)?.as_val()?,
+            chain_id:          rlp.at(1)?.as_val()?,
+            height:            rlp.at(2)?.as_val()?,
+            exec_height:       rlp.at(3)?.as_val()?,
+            prev_hash:         rlp.at(4)?.as_val()?,
+            receipt_roots:     rlp.at(5)?.as_list()?,
+            proof:             rlp.at(6)?.as_val()?,
+            validator_version: rlp.at(7)?.as_val()?,
+            validators:        rlp.at(8)?.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for MutaHeader {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(9)
+            .append(&self.hash)
+            .append(&self.chain_id)
+            .append(&self.height)
+            .append(&self.exec_height)
+            .append(&self.prev_hash)
+            .append_list(&self.receipt_roots)
+            .append(&self.proof)
+            .append(&self.validator_version)
+            .append_list(&self.validators);
+    }
+}
+
+impl FixedCodec for MutaHeader {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for PendingMessage {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            relayer: rlp.val_at(0)?,
+            message: rlp.val_at(1)?,
+        })
+    }
+}
+
+impl rlp::Encodable for PendingMessage {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2).append(&self.relayer).append(&self.message);
+    }
+}
+
+// Optional fields are lists of zero or one item.
+impl rlp::Decodable for CkbMessage {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let raw_tx = match rlp.list_at::<String>(1)?.pop() {
+            Some(raw_tx) => Some(decode_hex(raw_tx)?),
+            None => None,
+        };
+        Ok(Self {
+            tx: rlp.list_at::<CkbTx>(0)?.pop(),
+            raw_tx,
+            proof: rlp.list_at::<CkbTxProof>(2)?.pop(),
+        })
+    }
+}
+
+impl rlp::Encodable for CkbMessage {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let tx: Vec<CkbTx> = self.tx.iter().cloned().collect();
+        let raw_tx: Vec<String> = self.raw_tx.iter().map(Hex::as_string).collect();
+        let proof: Vec<CkbTxProof> = self.proof.iter().cloned().collect();
+        s.begin_list(3)
+            .append_list(&tx)
+            .append_list::<String, String>(&raw_tx)
+            .append_list(&proof);
+    }
+}
+
+impl rlp::Decodable for CkbTxProof {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let witness_lemmas = if rlp.val_at(3)? {
+            Some(rlp.list_at(4)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            index: rlp.val_at(0)?,
+            lemmas: rlp.list_at(1)?,
+            witnesses_root: rlp.val_at(2)?,
+            witness_lemmas,
+        })
+    }
+}
+
+impl rlp::Encodable for CkbTxProof {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        // no witness lemmas differ from an empty list of them
+        let witness_lemmas = self.witness_lemmas.clone().unwrap_or_default();
+        s.begin_list(5)
+            .append(&self.index)
+            .append_list(&self.lemmas)
+            .append(&self.witnesses_root)
+            .append(&self.witness_lemmas.is_some())
+            .append_list(&witness_lemmas);
+    }
+}
+
+// Hex fields keep the exact strings the relayer submitted, the tx hash is
+// computed over them.
+impl rlp::Decodable for CkbTx {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let hex_list = |i: usize| -> Result<Vec<Hex>, rlp::DecoderError> {
+            rlp.list_at::<String>(i)?
+                .into_iter()
+                .map(decode_hex)
+                .collect()
+        };
+
+        Ok(Self {
+            version:      decode_hex(rlp.val_at(0)?)?,
+            cell_deps:    rlp.list_at(1)?,
+            header_deps:  rlp.list_at(2)?,
+            inputs:       rlp.list_at(3)?,
+            outputs:      rlp.list_at(4)?,
+            outputs_data: hex_list(5)?,
+            witnesses:    hex_list(6)?,
+        })
+    }
+}
+
+impl rlp::Encodable for CkbTx {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let outputs_data: Vec<String> = self.outputs_data.iter().map(Hex::as_string).collect();
+        let witnesses: Vec<String> = self.witnesses.iter().map(Hex::as_string).collect();
+        s.begin_list(7)
+            .append(&self.version.as_string())
+            .append_list(&self.cell_deps)
+            .append_list(&self.header_deps)
+            .append_list(&self.inputs)
+            .append_list(&self.outputs)
+            .append_list::<String, String>(&outputs_data)
+            .append_list::<String, String>(&witnesses);
+    }
+}
+
+impl rlp::Decodable for CellDep {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let dep_type = match rlp.val_at::<u8>(1)? {
+            0 => DepType::code,
+            1 => DepType::depgroup,
+            _ => return Err(rlp::DecoderError::Custom("invalid dep type")),
+        };
+        Ok(Self {
+            out_point: rlp.val_at(0)?,
+            dep_type,
+        })
+    }
+}
+
+impl rlp::Encodable for CellDep {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let dep_type: u8 = match self.dep_type {
+            DepType::code => 0,
+            DepType::depgroup => 1,
+        };
+        s.begin_list(2).append(&self.out_point).append(&dep_type);
+    }
+}
+
+impl rlp::Decodable for OutPoint {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            tx_hash: rlp.val_at(0)?,
+            index:   decode_hex(rlp.val_at(1)?)?,
+        })
+    }
+}
+
+impl rlp::Encodable for OutPoint {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.tx_hash)
+            .append(&self.index.as_string());
+    }
+}
+
+impl rlp::Decodable for CellInput {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            since:           decode_hex(rlp.val_at(0)?)?,
+            previous_output: rlp.val_at(1)?,
+        })
+    }
+}
+
+impl rlp::Encodable for CellInput {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.since.as_string())
+            .append(&self.previous_output);
+    }
+}
+
+impl rlp::Decodable for CellOutput {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            capacity: decode_hex(rlp.val_at(0)?)?,
+            lock:     rlp.val_at(1)?,
+            type_:    rlp.list_at::<Script>(2)?.pop(),
+        })
+    }
+}
+
+impl rlp::Encodable for CellOutput {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let type_: Vec<Script> = self.type_.iter().cloned().collect();
+        s.begin_list(3)
+            .append(&self.capacity.as_string())
+            .append(&self.lock)
+            .append_list(&type_);
+    }
+}
+
+impl rlp::Decodable for Script {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let hash_type = match rlp.val_at::<u8>(1)? {
+            0 => ScriptHashType::data,
+            1 => ScriptHashType::Type,
+            _ => return Err(rlp::DecoderError::Custom("invalid hash type")),
+        };
+        Ok(Self {
+            code_hash: rlp.val_at(0)?,
+            hash_type,
+            args: decode_hex(rlp.val_at(2)?)?,
+        })
+    }
+}
+
+impl rlp::Encodable for Script {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let hash_type: u8 = match self.hash_type {
+            ScriptHashType::data => 0,
+            ScriptHashType::Type => 1,
+        };
+        s.begin_list(3)
+            .append(&self.code_hash)
+            .append(&hash_type)
+            .append(&self.args.as_string());
+    }
+}
+
+// Through serde, which unlike `Hex::from_string` takes the empty and odd
+// length hex a ckb tx may hold.
+fn decode_hex(s: String) -> Result<Hex, rlp::DecoderError> {
+    serde_json::from_value(serde_json::Value::String(s))
+        .map_err(|_| rlp::DecoderError::Custom("invalid hex"))
+}
+
+impl rlp::Decodable for MmrState {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(MmrState {
+            start:  rlp.at(0)?.as_val()?,
+            leaves: rlp.at(1)?.as_val()?,
+            peaks:  rlp.at(2)?.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for MmrState {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.start)
+            .append(&self.leaves)
+            .append_list(&self.peaks);
+    }
+}
+
+impl FixedCodec for MmrState {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for HeaderBounds {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(HeaderBounds {
+            lowest:  rlp.at(0)?.as_val()?,
+            highest: rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for HeaderBounds {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2).append(&self.lowest).append(&self.highest);
+    }
+}
+
+impl FixedCodec for HeaderBounds {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for SupportedLocks {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(SupportedLocks {
+            code_hashes: rlp.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for SupportedLocks {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_list(&self.code_hashes);
+    }
+}
+
+impl FixedCodec for SupportedLocks {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for CallTarget {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(CallTarget {
+            service: rlp.val_at(0)?,
+            methods: rlp.list_at(1)?,
+        })
+    }
+}
+
+impl rlp::Encodable for CallTarget {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.service)
+            .append_list::<String, String>(&self.methods);
+    }
+}
+
+impl FixedCodec for CallTarget {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for OutboundMessage {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        // Through serde, which unlike `Hex::from_string` takes the empty args
+        // a script may have.
+        let hex = |i: usize| -> Result<Hex, rlp::DecoderError> {
+            let s: String = rlp.at(i)?.as_val()?;
+            serde_json::from_value(serde_json::Value::String(s))
+                .map_err(|_| rlp::DecoderError::Custom("invalid hex"))
+        };
+        let hash_type = match rlp.at(3)?.as_val::<u8>()? {
+            0 => ScriptHashType::data,
+            1 => ScriptHashType::Type,
+            _ => return Err(rlp::DecoderError::Custom("invalid hash type")),
+        };
+
+        Ok(Self {
+            nonce:         rlp.at(0)?.as_val()?,
+            sender:        rlp.at(1)?.as_val()?,
+            target_script: Script {
+                code_hash: rlp.at(2)?.as_val()?,
+                hash_type,
+                args: hex(4)?,
+            },
+            payload:       hex(5)?,
+            height:        rlp.at(6)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for OutboundMessage {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let hash_type: u8 = match self.target_script.hash_type {
+            ScriptHashType::data => 0,
+            ScriptHashType::Type => 1,
+        };
+
+        s.begin_list(7)
+            .append(&self.nonce)
+            .append(&self.sender)
+            .append(&self.target_script.code_hash)
+            .append(&hash_type)
+            .append(&self.target_script.args.as_string())
+            .append(&self.payload.as_string())
+            .append(&self.height);
+    }
+}
+
+impl FixedCodec for OutboundMessage {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for OutboundBlock {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            height:      rlp.at(0)?.as_val()?,
+            first_nonce: rlp.at(1)?.as_val()?,
+            last_nonce:  rlp.at(2)?.as_val()?,
+            leaves:      rlp.list_at(3)?,
+            root:        rlp.at(4)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for OutboundBlock {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5)
+            .append(&self.height)
+            .append(&self.first_nonce)
+            .append(&self.last_nonce)
+            .append_list(&self.leaves)
+            .append(&self.root);
+    }
+}
+
+impl FixedCodec for OutboundBlock {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for WithdrawalFeeConfig {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            native_asset_id: rlp.at(0)?.as_val()?,
+            treasury:        rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for WithdrawalFeeConfig {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.native_asset_id)
+            .append(&self.treasury);
+    }
+}
+
+impl FixedCodec for WithdrawalFeeConfig {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for RelayerPayout {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            payout: rlp.at(0)?.as_val()?,
+            set_at: rlp.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for RelayerPayout {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2).append(&self.payout).append(&self.set_at);
+    }
+}
+
+impl FixedCodec for RelayerPayout {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for PendingMessages {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            messages: rlp.at(0)?.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for PendingMessages {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(1).append_list(&self.messages);
+    }
+}
+
+impl FixedCodec for PendingMessages {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+// Hex fields keep the exact strings the relayer submitted.
+impl rlp::Decodable for CkbHeader {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        // Through serde, which like the payload accepts odd length hex such as
+        // "0x0".
+        let hex = |i: usize| -> Result<Hex, rlp::DecoderError> {
+            let s: String = rlp.at(i)?.as_val()?;
+            serde_json::from_value(serde_json::Value::String(s))
+                .map_err(|_| rlp::DecoderError::Custom("invalid hex"))
+        };
+
+        Ok(Self {
+            compact_target:    hex(0)?,
+            version:           hex(1)?,
+            timestamp:         hex(2)?,
+            number:            hex(3)?,
+            epoch:             hex(4)?,
+            parent_hash:       rlp.at(5)?.as_val()?,
+            transactions_root: rlp.at(6)?.as_val()?,
+            proposals_hash:    rlp.at(7)?.as_val()?,
+            uncles_hash:       rlp.at(8)?.as_val()?,
+            dao:               rlp.at(9)?.as_val()?,
+            nonce:             hex(10)?,
+        })
+    }
+}
+
+impl rlp::Encodable for CkbHeader {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(11)
+            .append(&self.compact_target.as_string())
+            .append(&self.version.as_string())
+            .append(&self.timestamp.as_string())
+            .append(&self.number.as_string())
+            .append(&self.epoch.as_string())
+            .append(&self.parent_hash)
+            .append(&self.transactions_root)
+            .append(&self.proposals_hash)
+            .append(&self.uncles_hash)
+            .append(&self.dao)
+            .append(&self.nonce.as_string());
+    }
+}
+
+impl FixedCodec for CkbHeader {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for HeaderConflict {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            height:           rlp.val_at(0)?,
+            stored_hash:      rlp.val_at(1)?,
+            conflicting_hash: rlp.val_at(2)?,
+            header:           rlp.val_at(3)?,
+        })
+    }
+}
+
+impl rlp::Encodable for HeaderConflict {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(4)
+            .append(&self.height)
+            .append(&self.stored_hash)
+            .append(&self.conflicting_hash)
+            .append(&self.header);
+    }
+}
+
+impl FixedCodec for HeaderConflict {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for RecoveredDeposit {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let reassigned_to: Vec<Address> = rlp.at(2)?.as_list()?;
+        Ok(Self {
+            asset_id:      rlp.at(0)?.as_val()?,
+            amount:        rlp.val_at::<Amount>(1)?.0,
+            reassigned_to: reassigned_to.into_iter().next(),
+        })
+    }
+}
+
+impl rlp::Encodable for RecoveredDeposit {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        // the receiver as a list of zero or one address
+        let reassigned_to: Vec<Address> = self.reassigned_to.iter().cloned().collect();
+        s.begin_list(3)
+            .append(&self.asset_id)
+            .append(&Amount(self.amount))
+            .append_list(&reassigned_to);
+    }
+}
+
+impl FixedCodec for RecoveredDeposit {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Decodable for ProcessedDeposit {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            asset_id:  rlp.at(0)?.as_val()?,
+            receiver:  rlp.at(1)?.as_val()?,
+            amount:    rlp.val_at::<Amount>(2)?.0,
+            relay_fee: rlp.val_at::<Amount>(3)?.0,
+            relayer:   rlp.at(4)?.as_val()?,
+            memo:      rlp.at(5)?.as_val()?,
+        })
+    }
+}
+
+impl rlp::Encodable for ProcessedDeposit {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(6)
+            .append(&self.asset_id)
+            .append(&self.receiver);
+
+        s.append(&Amount(self.amount));
+        s.append(&Amount(self.relay_fee));
+
+        s.append(&self.relayer).append(&self.memo);
+    }
+}
+
+impl FixedCodec for ProcessedDeposit {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MigratePayload {
+    // most entries a batched migration rewrites in this call
+    pub limit: u64,
+}
+
+/// Schema version of the stored state, and the migrations taking it to the
+/// version of the deployed service.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MigrationStatus {
+    pub schema_version: u64,
+    pub target_version: u64,
+    pub pending:        Vec<String>,
+    // where the first pending migration resumes, if batched
+    pub cursor:         u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct MigrationEvent {
+    pub from_version: u64,
+    pub version:      u64,
+    pub cursor:       u64,
+    pub topic:        String, // "migrate"
+}
+
+/// What the deployed service supports, for clients to adapt to it.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ServiceInfo {
+    pub version:          String,
+    pub features:         Vec<String>,
+    pub payload_versions: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain:            Option<ChainBinding>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct GetSchemaPayload {
+    pub type_name: String,
+}
+
+impl PayloadVersion for MessagePayload {
+    const VERSION: u32 = 2;
+
+    fn from_version(version: u32, payload: serde_json::Value) -> Result<Self, String> {
+        if version != 1 {
+            return Err(format!("unsupported payload version {}", version));
+        }
+        let payload: MessagePayloadV1 =
+            serde_json::from_value(payload).map_err(|e| e.to_string())?;
+        let messages = payload
+            .messages
+            .into_iter()
+            .map(PackedCkbMessage::pack)
+            .collect::<ProtocolResult<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+        Ok(MessagePayload {
+            height: payload.height,
+            messages,
+            source_chain_id: payload.source_chain_id,
+        })
     }
 }
+impl PayloadVersion for UpdateHeadersPayload {}
+impl PayloadVersion for BurnPayload {}
+impl PayloadVersion for CancelBurnPayload {}
+impl PayloadVersion for BurnToEthPayload {}
+impl PayloadVersion for BurnToMutaPayload {}
@@ -0,0 +1,1064 @@
+//! The views `get_view` flattens, one per read, with every field a string
+//! and nested structs folded into the fields of the view. Collections stay
+//! lists of flat items, for `get_view` to truncate.
+
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use common::view::{MigrationStatusView, ServiceInfoView};
+use protocol::types::{Hash, Validator};
+
+use crate::types::{
+    AssetVolume, BridgeLimits, BridgeStats, BridgedSupply, BurnBatch, BurnQuote, BurnRecord,
+    BurnStatus, CallTarget, CkbHeader, DepositQuote, DepositTag, EpochAssetBurns, EpochLockBurns,
+    EpochSummary, EthConfig, EthSyncMode, EventDigest, EventDigests, FeeBeneficiary, FeeSchedule,
+    FeeTier, GetBurnLimitResponse, GetBurnQuotaResponse, GetHeaderResponse,
+    GetMirrorAssetIdResponse, GetMmrResponse, HeaderConflict, HeightRange, MessageTtl,
+    MigrationStatus, MirrorAsset, MirrorAssetIssuer, MissingHeaderRanges, MutaConfig, MutaHeader,
+    MutaValidator, MutaValidatorSet, OutboundBlock, OutboundMessage, OutboundSender, Params,
+    PayoutAddress, ProcessedDeposit, RecoveredDeposit, ScriptHashType, ServiceInfo, SupportedLocks,
+    WithdrawalFeeConfig, WithdrawalFeeEstimate,
+};
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BridgeStatsView {
+    pub total_mints:       String,
+    pub total_burns:       String,
+    pub headers_stored:    String,
+    pub rejected_messages: BTreeMap<String, String>,
+    pub volumes:           Vec<AssetVolumeView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct AssetVolumeView {
+    pub asset_id: String,
+    pub minted:   String,
+    pub burned:   String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BridgedSupplyView {
+    pub asset_id:    String,
+    pub minted:      String,
+    pub burned:      String,
+    pub outstanding: String,
+    pub cap:         String,
+    pub paused:      String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct DepositQuoteView {
+    pub asset_id:           String,
+    pub sudt_amount:        String,
+    pub scale_factor:       String,
+    pub dust:               String,
+    pub amount:             String,
+    pub relay_fee:          String,
+    pub relay_fee_bps:      String,
+    pub credited:           String,
+    pub paused:             String,
+    pub formatted_amount:   Option<String>,
+    pub formatted_credited: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BurnQuoteView {
+    pub asset_id:         String,
+    pub amount:           String,
+    pub min_burn_amount:  String,
+    pub scale_factor:     String,
+    pub sudt_amount:      String,
+    pub quota_remaining:  Option<String>,
+    pub paused:           String,
+    pub formatted_amount: Option<String>,
+}
+
+/// View of `get_header`, the epoch unpacked into the `epoch_` fields.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct HeaderView {
+    pub compact_target:    String,
+    pub version:           String,
+    pub timestamp:         String,
+    pub number:            String,
+    pub epoch:             String,
+    pub parent_hash:       String,
+    pub transactions_root: String,
+    pub proposals_hash:    String,
+    pub uncles_hash:       String,
+    pub dao:               String,
+    pub nonce:             String,
+    pub epoch_number:      String,
+    pub epoch_index:       String,
+    pub epoch_length:      String,
+}
+
+/// View of `get_header_raw`, the fields hex as ckb encodes them.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct RawHeaderView {
+    pub compact_target:    String,
+    pub version:           String,
+    pub timestamp:         String,
+    pub number:            String,
+    pub epoch:             String,
+    pub parent_hash:       String,
+    pub transactions_root: String,
+    pub proposals_hash:    String,
+    pub uncles_hash:       String,
+    pub dao:               String,
+    pub nonce:             String,
+}
+
+/// View of `get_header_conflict`, with the fields of the conflicting header.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct HeaderConflictView {
+    pub height:            String,
+    pub stored_hash:       String,
+    pub conflicting_hash:  String,
+    pub compact_target:    String,
+    pub version:           String,
+    pub timestamp:         String,
+    pub number:            String,
+    pub epoch:             String,
+    pub parent_hash:       String,
+    pub transactions_root: String,
+    pub proposals_hash:    String,
+    pub uncles_hash:       String,
+    pub dao:               String,
+    pub nonce:             String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MissingHeaderRangesView {
+    pub lowest:  Option<String>,
+    pub highest: Option<String>,
+    pub ranges:  Vec<HeightRangeView>,
+    pub next:    Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct HeightRangeView {
+    pub start: String,
+    pub end:   String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EventDigestsView {
+    pub digests: Vec<EventDigestView>,
+    pub next:    Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EventDigestView {
+    pub height: String,
+    pub mints:  String,
+    pub burns:  String,
+    pub root:   String,
+}
+
+/// A fee beneficiary by its kind, with the fields the kind has.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct FeeBeneficiaryView {
+    pub kind:         String,
+    pub caller_bps:   Option<String>,
+    pub treasury:     Option<String>,
+    pub treasury_bps: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct FeeScheduleView {
+    pub tiers: Vec<FeeTierView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct FeeTierView {
+    pub threshold: String,
+    pub bps:       String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EpochSummaryView {
+    pub epoch:        String,
+    pub start_height: String,
+    pub end_height:   String,
+    pub closed:       String,
+    pub burns:        String,
+    pub first_nonce:  String,
+    pub last_nonce:   String,
+    pub assets:       Vec<EpochAssetBurnsView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EpochAssetBurnsView {
+    pub asset_id:    String,
+    pub sudt_args:   String,
+    pub burns:       String,
+    pub amount:      String,
+    pub sudt_amount: String,
+    pub locks:       Vec<EpochLockBurnsView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EpochLockBurnsView {
+    pub code_hash:   String,
+    pub burns:       String,
+    pub sudt_amount: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BurnBatchView {
+    pub batch:        String,
+    pub start_height: String,
+    pub end_height:   String,
+    pub first_nonce:  String,
+    pub last_nonce:   String,
+    pub leaves:       Vec<String>,
+    pub root:         String,
+    pub sealed:       String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct DepositTagView {
+    pub address:    String,
+    pub tag:        String,
+    pub registered: String,
+    pub witness:    String,
+}
+
+/// View of `get_mirror_asset_by_sudt` and `get_mirror_asset_by_id`.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MirrorAssetView {
+    pub sudt_args:       String,
+    pub asset_id:        String,
+    pub name:            String,
+    pub symbol:          String,
+    pub decimals:        String,
+    pub scale_factor:    String,
+    pub min_burn_amount: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MirrorAssetIdView {
+    pub asset_id:   String,
+    pub registered: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BurnLimitView {
+    pub asset_id:        String,
+    pub min_burn_amount: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BurnRecordView {
+    pub nonce:        String,
+    pub asset_id:     String,
+    pub sudt_args:    String,
+    pub muta_sender:  String,
+    pub ckb_receiver: String,
+    pub amount:       String,
+    pub sudt_amount:  String,
+    pub status:       String,
+    pub ckb_tx_hash:  Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BurnQuotaView {
+    pub asset_id: String,
+    pub quota:    String,
+    pub window:   String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct WithdrawalFeeConfigView {
+    pub native_asset_id: String,
+    pub treasury:        String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct WithdrawalFeeEstimateView {
+    pub asset_id:        String,
+    pub fee:             String,
+    pub native_asset_id: Option<String>,
+    pub treasury:        Option<String>,
+    pub waived:          String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct CallTargetView {
+    pub service: String,
+    pub methods: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct OutboundSenderView {
+    pub service: String,
+    pub allowed: String,
+}
+
+/// View of `get_outbound_message`, the target script in the `target_`
+/// fields.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct OutboundMessageView {
+    pub nonce:            String,
+    pub sender:           String,
+    pub target_code_hash: String,
+    pub target_hash_type: String,
+    pub target_args:      String,
+    pub payload:          String,
+    pub height:           String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct OutboundBlockView {
+    pub height:      String,
+    pub first_nonce: String,
+    pub last_nonce:  String,
+    pub leaves:      Vec<String>,
+    pub root:        String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct PayoutAddressView {
+    pub relayer:       String,
+    pub payout:        String,
+    pub changeable_at: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct SupportedLocksView {
+    pub code_hashes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MirrorAssetIssuerView {
+    pub issuer: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MessageTtlView {
+    pub blocks: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ParamsView {
+    pub sudt_code_hash:           String,
+    pub max_pending_messages:     String,
+    pub payout_cooldown:          String,
+    pub max_outbound_payload_len: String,
+    pub max_staged_headers:       String,
+    pub staged_headers_per_block: String,
+    pub mirror_asset_issuer:      String,
+    pub recovery_account:         String,
+    pub message_ttl:              String,
+    pub bridge_limits:            BridgeLimitsView,
+    pub fee_beneficiary:          FeeBeneficiaryView,
+    pub fee_schedule:             FeeScheduleView,
+    pub withdrawal_fee_config:    Option<WithdrawalFeeConfigView>,
+    pub burn_epoch_length:        String,
+    pub burn_batch_window:        String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct BridgeLimitsView {
+    pub max_batch_messages: String,
+    pub block_mint_budget:  String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MmrView {
+    pub start:  String,
+    pub leaves: String,
+    pub root:   Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct RecoveredDepositView {
+    pub asset_id:      String,
+    pub amount:        String,
+    pub reassigned_to: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ProcessedDepositView {
+    pub asset_id:  String,
+    pub receiver:  String,
+    pub amount:    String,
+    pub relay_fee: String,
+    pub relayer:   String,
+    pub memo:      String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct EthConfigView {
+    pub bridge_contract: String,
+    pub mode:            String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MutaConfigView {
+    pub chain_id:       String,
+    pub common_ref:     String,
+    pub bridge_service: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MutaValidatorSetView {
+    pub version:      String,
+    pub start_height: String,
+    pub validators:   Vec<MutaValidatorView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MutaValidatorView {
+    pub bls_pub_key: String,
+    pub address:     String,
+    pub vote_weight: String,
+}
+
+/// View of `get_muta_header`, the proof of the parent block in the `proof_`
+/// fields.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct MutaHeaderView {
+    pub hash:              String,
+    pub chain_id:          String,
+    pub height:            String,
+    pub exec_height:       String,
+    pub prev_hash:         String,
+    pub receipt_roots:     Vec<String>,
+    pub proof_height:      String,
+    pub proof_round:       String,
+    pub proof_block_hash:  String,
+    pub proof_signature:   String,
+    pub proof_bitmap:      String,
+    pub validator_version: String,
+    pub validators:        Vec<ValidatorView>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct ValidatorView {
+    pub address:        String,
+    pub propose_weight: String,
+    pub vote_weight:    String,
+}
+
+impl From<ServiceInfo> for ServiceInfoView {
+    fn from(info: ServiceInfo) -> Self {
+        ServiceInfoView::new(
+            info.version,
+            info.features,
+            &info.payload_versions,
+            info.chain.as_ref(),
+        )
+    }
+}
+
+impl From<MigrationStatus> for MigrationStatusView {
+    fn from(status: MigrationStatus) -> Self {
+        MigrationStatusView::new(
+            status.schema_version,
+            status.target_version,
+            status.pending,
+            status.cursor,
+        )
+    }
+}
+
+impl From<BridgeStats> for BridgeStatsView {
+    fn from(stats: BridgeStats) -> Self {
+        BridgeStatsView {
+            total_mints:       stats.total_mints.to_string(),
+            total_burns:       stats.total_burns.to_string(),
+            headers_stored:    stats.headers_stored.to_string(),
+            rejected_messages: stats
+                .rejected_messages
+                .into_iter()
+                .map(|(reason, count)| (reason, count.to_string()))
+                .collect(),
+            volumes:           stats.volumes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<AssetVolume> for AssetVolumeView {
+    fn from(volume: AssetVolume) -> Self {
+        AssetVolumeView {
+            asset_id: volume.asset_id.as_hex(),
+            minted:   volume.minted.to_string(),
+            burned:   volume.burned.to_string(),
+        }
+    }
+}
+
+impl From<BridgedSupply> for BridgedSupplyView {
+    fn from(supply: BridgedSupply) -> Self {
+        BridgedSupplyView {
+            asset_id:    supply.asset_id.as_hex(),
+            minted:      supply.minted.to_string(),
+            burned:      supply.burned.to_string(),
+            outstanding: supply.outstanding.to_string(),
+            cap:         supply.cap.to_string(),
+            paused:      supply.paused.to_string(),
+        }
+    }
+}
+
+impl From<DepositQuote> for DepositQuoteView {
+    fn from(quote: DepositQuote) -> Self {
+        DepositQuoteView {
+            asset_id:           quote.asset_id.as_hex(),
+            sudt_amount:        quote.sudt_amount.to_string(),
+            scale_factor:       quote.scale_factor.to_string(),
+            dust:               quote.dust.to_string(),
+            amount:             quote.amount.to_string(),
+            relay_fee:          quote.relay_fee.to_string(),
+            relay_fee_bps:      quote.relay_fee_bps.to_string(),
+            credited:           quote.credited.to_string(),
+            paused:             quote.paused.to_string(),
+            formatted_amount:   quote.formatted_amount,
+            formatted_credited: quote.formatted_credited,
+        }
+    }
+}
+
+impl From<BurnQuote> for BurnQuoteView {
+    fn from(quote: BurnQuote) -> Self {
+        BurnQuoteView {
+            asset_id:         quote.asset_id.as_hex(),
+            amount:           quote.amount.to_string(),
+            min_burn_amount:  quote.min_burn_amount.to_string(),
+            scale_factor:     quote.scale_factor.to_string(),
+            sudt_amount:      quote.sudt_amount.to_string(),
+            quota_remaining:  quote.quota_remaining.map(|quota| quota.to_string()),
+            paused:           quote.paused.to_string(),
+            formatted_amount: quote.formatted_amount,
+        }
+    }
+}
+
+impl From<GetHeaderResponse> for HeaderView {
+    fn from(resp: GetHeaderResponse) -> Self {
+        let header = resp.header;
+        HeaderView {
+            compact_target:    header.compact_target.to_string(),
+            version:           header.version.to_string(),
+            timestamp:         header.timestamp.to_string(),
+            number:            header.number.to_string(),
+            epoch:             header.epoch.to_string(),
+            parent_hash:       header.parent_hash.as_hex(),
+            transactions_root: header.transactions_root.as_hex(),
+            proposals_hash:    header.proposals_hash.as_hex(),
+            uncles_hash:       header.uncles_hash.as_hex(),
+            dao:               header.dao.as_hex(),
+            nonce:             header.nonce.to_string(),
+            epoch_number:      resp.epoch.number.to_string(),
+            epoch_index:       resp.epoch.index.to_string(),
+            epoch_length:      resp.epoch.length.to_string(),
+        }
+    }
+}
+
+impl From<CkbHeader> for RawHeaderView {
+    fn from(header: CkbHeader) -> Self {
+        RawHeaderView {
+            compact_target:    header.compact_target.as_string(),
+            version:           header.version.as_string(),
+            timestamp:         header.timestamp.as_string(),
+            number:            header.number.as_string(),
+            epoch:             header.epoch.as_string(),
+            parent_hash:       header.parent_hash.as_hex(),
+            transactions_root: header.transactions_root.as_hex(),
+            proposals_hash:    header.proposals_hash.as_hex(),
+            uncles_hash:       header.uncles_hash.as_hex(),
+            dao:               header.dao.as_hex(),
+            nonce:             header.nonce.as_string(),
+        }
+    }
+}
+
+impl From<HeaderConflict> for HeaderConflictView {
+    fn from(conflict: HeaderConflict) -> Self {
+        let header = RawHeaderView::from(conflict.header);
+        HeaderConflictView {
+            height:            conflict.height.to_string(),
+            stored_hash:       conflict.stored_hash.as_hex(),
+            conflicting_hash:  conflict.conflicting_hash.as_hex(),
+            compact_target:    header.compact_target,
+            version:           header.version,
+            timestamp:         header.timestamp,
+            number:            header.number,
+            epoch:             header.epoch,
+            parent_hash:       header.parent_hash,
+            transactions_root: header.transactions_root,
+            proposals_hash:    header.proposals_hash,
+            uncles_hash:       header.uncles_hash,
+            dao:               header.dao,
+            nonce:             header.nonce,
+        }
+    }
+}
+
+impl From<MissingHeaderRanges> for MissingHeaderRangesView {
+    fn from(missing: MissingHeaderRanges) -> Self {
+        MissingHeaderRangesView {
+            lowest:  missing.lowest.map(|height| height.to_string()),
+            highest: missing.highest.map(|height| height.to_string()),
+            ranges:  missing.ranges.into_iter().map(Into::into).collect(),
+            next:    missing.next.map(|height| height.to_string()),
+        }
+    }
+}
+
+impl From<HeightRange> for HeightRangeView {
+    fn from(range: HeightRange) -> Self {
+        HeightRangeView {
+            start: range.start.to_string(),
+            end:   range.end.to_string(),
+        }
+    }
+}
+
+impl From<EventDigests> for EventDigestsView {
+    fn from(digests: EventDigests) -> Self {
+        EventDigestsView {
+            digests: digests.digests.into_iter().map(Into::into).collect(),
+            next:    digests.next.map(|height| height.to_string()),
+        }
+    }
+}
+
+impl From<EventDigest> for EventDigestView {
+    fn from(digest: EventDigest) -> Self {
+        EventDigestView {
+            height: digest.height.to_string(),
+            mints:  digest.mints.to_string(),
+            burns:  digest.burns.to_string(),
+            root:   digest.root.as_hex(),
+        }
+    }
+}
+
+impl From<FeeBeneficiary> for FeeBeneficiaryView {
+    fn from(beneficiary: FeeBeneficiary) -> Self {
+        let mut view = FeeBeneficiaryView {
+            kind:         String::new(),
+            caller_bps:   None,
+            treasury:     None,
+            treasury_bps: None,
+        };
+        match beneficiary {
+            FeeBeneficiary::TxCaller => {
+                view.kind = "tx_caller".to_owned();
+            }
+            FeeBeneficiary::FixedAddress(treasury) => {
+                view.kind = "fixed_address".to_owned();
+                view.treasury = Some(treasury.as_hex());
+            }
+            FeeBeneficiary::Split {
+                caller_bps,
+                treasury,
+                treasury_bps,
+            } => {
+                view.kind = "split".to_owned();
+                view.caller_bps = Some(caller_bps.to_string());
+                view.treasury = Some(treasury.as_hex());
+                view.treasury_bps = Some(treasury_bps.to_string());
+            }
+        }
+        view
+    }
+}
+
+impl From<FeeSchedule> for FeeScheduleView {
+    fn from(schedule: FeeSchedule) -> Self {
+        FeeScheduleView {
+            tiers: schedule.tiers.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<FeeTier> for FeeTierView {
+    fn from(tier: FeeTier) -> Self {
+        FeeTierView {
+            threshold: tier.threshold.to_string(),
+            bps:       tier.bps.to_string(),
+        }
+    }
+}
+
+impl From<EpochSummary> for EpochSummaryView {
+    fn from(summary: EpochSummary) -> Self {
+        EpochSummaryView {
+            epoch:        summary.epoch.to_string(),
+            start_height: summary.start_height.to_string(),
+            end_height:   summary.end_height.to_string(),
+            closed:       summary.closed.to_string(),
+            burns:        summary.burns.to_string(),
+            first_nonce:  summary.first_nonce.to_string(),
+            last_nonce:   summary.last_nonce.to_string(),
+            assets:       summary.assets.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<EpochAssetBurns> for EpochAssetBurnsView {
+    fn from(asset: EpochAssetBurns) -> Self {
+        EpochAssetBurnsView {
+            asset_id:    asset.asset_id.as_hex(),
+            sudt_args:   asset.sudt_args.as_hex(),
+            burns:       asset.burns.to_string(),
+            amount:      asset.amount.to_string(),
+            sudt_amount: asset.sudt_amount.to_string(),
+            locks:       asset.locks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<EpochLockBurns> for EpochLockBurnsView {
+    fn from(lock: EpochLockBurns) -> Self {
+        EpochLockBurnsView {
+            code_hash:   lock.code_hash.as_hex(),
+            burns:       lock.burns.to_string(),
+            sudt_amount: lock.sudt_amount.to_string(),
+        }
+    }
+}
+
+impl From<BurnBatch> for BurnBatchView {
+    fn from(batch: BurnBatch) -> Self {
+        BurnBatchView {
+            batch:        batch.batch.to_string(),
+            start_height: batch.start_height.to_string(),
+            end_height:   batch.end_height.to_string(),
+            first_nonce:  batch.first_nonce.to_string(),
+            last_nonce:   batch.last_nonce.to_string(),
+            leaves:       hexes(&batch.leaves),
+            root:         batch.root.as_hex(),
+            sealed:       batch.sealed.to_string(),
+        }
+    }
+}
+
+impl From<DepositTag> for DepositTagView {
+    fn from(tag: DepositTag) -> Self {
+        DepositTagView {
+            address:    tag.address.as_hex(),
+            tag:        tag.tag.to_string(),
+            registered: tag.registered.to_string(),
+            witness:    tag.witness.as_string(),
+        }
+    }
+}
+
+impl From<MirrorAsset> for MirrorAssetView {
+    fn from(asset: MirrorAsset) -> Self {
+        MirrorAssetView {
+            sudt_args:       asset.sudt_args.as_hex(),
+            asset_id:        asset.asset_id.as_hex(),
+            name:            asset.name,
+            symbol:          asset.symbol,
+            decimals:        asset.decimals.to_string(),
+            scale_factor:    asset.scale_factor.to_string(),
+            min_burn_amount: asset.min_burn_amount.to_string(),
+        }
+    }
+}
+
+impl From<GetMirrorAssetIdResponse> for MirrorAssetIdView {
+    fn from(resp: GetMirrorAssetIdResponse) -> Self {
+        MirrorAssetIdView {
+            asset_id:   resp.asset_id.as_hex(),
+            registered: resp.registered.to_string(),
+        }
+    }
+}
+
+impl From<GetBurnLimitResponse> for BurnLimitView {
+    fn from(resp: GetBurnLimitResponse) -> Self {
+        BurnLimitView {
+            asset_id:        resp.asset_id.as_hex(),
+            min_burn_amount: resp.min_burn_amount.to_string(),
+        }
+    }
+}
+
+impl From<BurnRecord> for BurnRecordView {
+    fn from(record: BurnRecord) -> Self {
+        let status = match record.status {
+            BurnStatus::Pending => "Pending",
+            BurnStatus::Cancelled => "Cancelled",
+            BurnStatus::Completed => "Completed",
+        };
+        BurnRecordView {
+            nonce:        record.nonce.to_string(),
+            asset_id:     record.asset_id.as_hex(),
+            sudt_args:    record.sudt_args.as_hex(),
+            muta_sender:  record.muta_sender.as_hex(),
+            ckb_receiver: record.ckb_receiver,
+            amount:       record.amount.to_string(),
+            sudt_amount:  record.sudt_amount.to_string(),
+            status:       status.to_owned(),
+            ckb_tx_hash:  record.ckb_tx_hash.map(|tx| tx.as_hex()),
+        }
+    }
+}
+
+impl From<GetBurnQuotaResponse> for BurnQuotaView {
+    fn from(resp: GetBurnQuotaResponse) -> Self {
+        BurnQuotaView {
+            asset_id: resp.asset_id.as_hex(),
+            quota:    resp.quota.to_string(),
+            window:   resp.window.to_string(),
+        }
+    }
+}
+
+impl From<WithdrawalFeeConfig> for WithdrawalFeeConfigView {
+    fn from(config: WithdrawalFeeConfig) -> Self {
+        WithdrawalFeeConfigView {
+            native_asset_id: config.native_asset_id.as_hex(),
+            treasury:        config.treasury.as_hex(),
+        }
+    }
+}
+
+impl From<WithdrawalFeeEstimate> for WithdrawalFeeEstimateView {
+    fn from(estimate: WithdrawalFeeEstimate) -> Self {
+        WithdrawalFeeEstimateView {
+            asset_id:        estimate.asset_id.as_hex(),
+            fee:             estimate.fee.to_string(),
+            native_asset_id: estimate.native_asset_id.map(|id| id.as_hex()),
+            treasury:        estimate.treasury.map(|treasury| treasury.as_hex()),
+            waived:          estimate.waived.to_string(),
+        }
+    }
+}
+
+impl From<CallTarget> for CallTargetView {
+    fn from(target: CallTarget) -> Self {
+        CallTargetView {
+            service: target.service,
+            methods: target.methods,
+        }
+    }
+}
+
+impl From<OutboundSender> for OutboundSenderView {
+    fn from(sender: OutboundSender) -> Self {
+        OutboundSenderView {
+            service: sender.service,
+            allowed: sender.allowed.to_string(),
+        }
+    }
+}
+
+impl From<OutboundMessage> for OutboundMessageView {
+    fn from(message: OutboundMessage) -> Self {
+        let hash_type = match message.target_script.hash_type {
+            ScriptHashType::data => "data",
+            ScriptHashType::Type => "type",
+        };
+        OutboundMessageView {
+            nonce:            message.nonce.to_string(),
+            sender:           message.sender,
+            target_code_hash: message.target_script.code_hash.as_hex(),
+            target_hash_type: hash_type.to_owned(),
+            target_args:      message.target_script.args.as_string(),
+            payload:          message.payload.as_string(),
+            height:           message.height.to_string(),
+        }
+    }
+}
+
+impl From<OutboundBlock> for OutboundBlockView {
+    fn from(block: OutboundBlock) -> Self {
+        OutboundBlockView {
+            height:      block.height.to_string(),
+            first_nonce: block.first_nonce.to_string(),
+            last_nonce:  block.last_nonce.to_string(),
+            leaves:      hexes(&block.leaves),
+            root:        block.root.as_hex(),
+        }
+    }
+}
+
+impl From<PayoutAddress> for PayoutAddressView {
+    fn from(payout: PayoutAddress) -> Self {
+        PayoutAddressView {
+            relayer:       payout.relayer.as_hex(),
+            payout:        payout.payout.as_hex(),
+            changeable_at: payout.changeable_at.to_string(),
+        }
+    }
+}
+
+impl From<SupportedLocks> for SupportedLocksView {
+    fn from(locks: SupportedLocks) -> Self {
+        SupportedLocksView {
+            code_hashes: hexes(&locks.code_hashes),
+        }
+    }
+}
+
+impl From<MirrorAssetIssuer> for MirrorAssetIssuerView {
+    fn from(issuer: MirrorAssetIssuer) -> Self {
+        MirrorAssetIssuerView {
+            issuer: issuer.issuer.as_hex(),
+        }
+    }
+}
+
+impl From<MessageTtl> for MessageTtlView {
+    fn from(ttl: MessageTtl) -> Self {
+        MessageTtlView {
+            blocks: ttl.blocks.to_string(),
+        }
+    }
+}
+
+impl From<Params> for ParamsView {
+    fn from(params: Params) -> Self {
+        ParamsView {
+            sudt_code_hash:           params.sudt_code_hash.as_hex(),
+            max_pending_messages:     params.max_pending_messages.to_string(),
+            payout_cooldown:          params.payout_cooldown.to_string(),
+            max_outbound_payload_len: params.max_outbound_payload_len.to_string(),
+            max_staged_headers:       params.max_staged_headers.to_string(),
+            staged_headers_per_block: params.staged_headers_per_block.to_string(),
+            mirror_asset_issuer:      params.mirror_asset_issuer.as_hex(),
+            recovery_account:         params.recovery_account.as_hex(),
+            message_ttl:              params.message_ttl.to_string(),
+            bridge_limits:            params.bridge_limits.into(),
+            fee_beneficiary:          params.fee_beneficiary.into(),
+            fee_schedule:             params.fee_schedule.into(),
+            withdrawal_fee_config:    params.withdrawal_fee_config.map(Into::into),
+            burn_epoch_length:        params.burn_epoch_length.to_string(),
+            burn_batch_window:        params.burn_batch_window.to_string(),
+        }
+    }
+}
+
+impl From<BridgeLimits> for BridgeLimitsView {
+    fn from(limits: BridgeLimits) -> Self {
+        BridgeLimitsView {
+            max_batch_messages: limits.max_batch_messages.to_string(),
+            block_mint_budget:  limits.block_mint_budget.to_string(),
+        }
+    }
+}
+
+impl From<GetMmrResponse> for MmrView {
+    fn from(resp: GetMmrResponse) -> Self {
+        MmrView {
+            start:  resp.start.to_string(),
+            leaves: resp.leaves.to_string(),
+            root:   resp.root.map(|root| root.as_hex()),
+        }
+    }
+}
+
+impl From<RecoveredDeposit> for RecoveredDepositView {
+    fn from(deposit: RecoveredDeposit) -> Self {
+        RecoveredDepositView {
+            asset_id:      deposit.asset_id.as_hex(),
+            amount:        deposit.amount.to_string(),
+            reassigned_to: deposit.reassigned_to.map(|receiver| receiver.as_hex()),
+        }
+    }
+}
+
+impl From<ProcessedDeposit> for ProcessedDepositView {
+    fn from(deposit: ProcessedDeposit) -> Self {
+        ProcessedDepositView {
+            asset_id:  deposit.asset_id.as_hex(),
+            receiver:  deposit.receiver.as_hex(),
+            amount:    deposit.amount.to_string(),
+            relay_fee: deposit.relay_fee.to_string(),
+            relayer:   deposit.relayer.as_hex(),
+            memo:      deposit.memo,
+        }
+    }
+}
+
+impl From<EthConfig> for EthConfigView {
+    fn from(config: EthConfig) -> Self {
+        let mode = match config.mode {
+            EthSyncMode::Pow => "pow",
+            EthSyncMode::SyncCommittee => "sync_committee",
+        };
+        EthConfigView {
+            bridge_contract: config.bridge_contract.as_string(),
+            mode:            mode.to_owned(),
+        }
+    }
+}
+
+impl From<MutaConfig> for MutaConfigView {
+    fn from(config: MutaConfig) -> Self {
+        MutaConfigView {
+            chain_id:       config.chain_id.as_hex(),
+            common_ref:     config.common_ref.as_string(),
+            bridge_service: config.bridge_service,
+        }
+    }
+}
+
+impl From<MutaValidatorSet> for MutaValidatorSetView {
+    fn from(set: MutaValidatorSet) -> Self {
+        MutaValidatorSetView {
+            version:      set.version.to_string(),
+            start_height: set.start_height.to_string(),
+            validators:   set.validators.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<MutaValidator> for MutaValidatorView {
+    fn from(validator: MutaValidator) -> Self {
+        MutaValidatorView {
+            bls_pub_key: validator.bls_pub_key.as_string(),
+            address:     validator.address.as_hex(),
+            vote_weight: validator.vote_weight.to_string(),
+        }
+    }
+}
+
+impl From<MutaHeader> for MutaHeaderView {
+    fn from(header: MutaHeader) -> Self {
+        MutaHeaderView {
+            hash:              header.hash.as_hex(),
+            chain_id:          header.chain_id.as_hex(),
+            height:            header.height.to_string(),
+            exec_height:       header.exec_height.to_string(),
+            prev_hash:         header.prev_hash.as_hex(),
+            receipt_roots:     hexes(&header.receipt_roots),
+            proof_height:      header.proof.height.to_string(),
+            proof_round:       header.proof.round.to_string(),
+            proof_block_hash:  header.proof.block_hash.as_hex(),
+            proof_signature:   bytes_hex(&header.proof.signature),
+            proof_bitmap:      bytes_hex(&header.proof.bitmap),
+            validator_version: header.validator_version.to_string(),
+            validators:        header.validators.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Validator> for ValidatorView {
+    fn from(validator: Validator) -> Self {
+        ValidatorView {
+            address:        validator.address.as_hex(),
+            propose_weight: validator.propose_weight.to_string(),
+            vote_weight:    validator.vote_weight.to_string(),
+        }
+    }
+}
+
+fn hexes(hashes: &[Hash]) -> Vec<String> {
+    hashes.iter().map(Hash::as_hex).collect()
+}
+
+fn bytes_hex(bytes: &Bytes) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
@@ -0,0 +1,225 @@
+//! Ckb blocks of sudt deposits and calls, with the headers and merkle proofs a
+//! relayer would submit for them.
+
+use serde_json::{json, Value};
+
+use crosschain::molecule::{encode_raw_transaction, encode_transaction};
+use crosschain::proof::{header_hash, to_hash, unpack_tx_proof};
+use crosschain::types::{CkbHeader, CkbHeaderInner, CkbTx, EpochView};
+use crosschain_proof::{build_tx_proofs, ckb_blake2b, merge, CbmtTree};
+use protocol::types::{Address, Bytes, Hash};
+
+// The sudt type script code hash the bridge accepts.
+const SUDT_CODE_HASH: &str = "0x57dd0067814dab356e05c6def0d094bb79776711e68ffdfad2df6a7f877f7db6";
+
+/// A deposit of `amount` raw sudt units to `receiver`, `nonce` telling
+/// deposits of the same amount apart.
+pub fn deposit_tx(sudt_args: &Hash, amount: u128, receiver: &Address, nonce: u64) -> CkbTx {
+    let tx = json!({
+        "version": "0x0",
+        "cell_deps": [],
+        "header_deps": [],
+        "inputs": [{
+            "since": "0x0",
+            "previous_output": {
+                "tx_hash": Hash::digest(Bytes::from("prev")),
+                "index": format!("0x{:x}", nonce),
+            },
+        }],
+        "outputs": [{
+            "capacity": "0x34e62ce00",
+            "lock": {
+                "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+                "hash_type": "type",
+                "args": "0x",
+            },
+            "type": {
+                "code_hash": SUDT_CODE_HASH,
+                "hash_type": "data",
+                "args": sudt_args,
+            },
+        }],
+        "outputs_data": [format!("0x{}", hex::encode(amount.to_le_bytes()))],
+        "witnesses": [receiver],
+    });
+    serde_json::from_value(tx).unwrap()
+}
+
+/// A tx unlocking `amount` raw sudt units to the secp256k1 lock of
+/// `lock_args`, as a relayer pays out a burn, `nonce` telling equal unlocks
+/// apart.
+pub fn unlock_tx(sudt_args: &Hash, amount: u128, lock_args: &[u8], nonce: u64) -> CkbTx {
+    let tx = json!({
+        "version": "0x0",
+        "cell_deps": [],
+        "header_deps": [],
+        "inputs": [{
+            "since": "0x0",
+            "previous_output": {
+                "tx_hash": Hash::digest(Bytes::from("locked")),
+                "index": format!("0x{:x}", nonce),
+            },
+        }],
+        "outputs": [{
+            "capacity": "0x34e62ce00",
+            "lock": {
+                "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+                "hash_type": "type",
+                "args": format!("0x{}", hex::encode(lock_args)),
+            },
+            "type": {
+                "code_hash": SUDT_CODE_HASH,
+                "hash_type": "data",
+                "args": sudt_args,
+            },
+        }],
+        "outputs_data": [format!("0x{}", hex::encode(amount.to_le_bytes()))],
+        "witnesses": ["0x"],
+    });
+    serde_json::from_value(tx).unwrap()
+}
+
+/// Hash of a tx, the one its inclusion is proven for.
+pub fn tx_hash(tx: &CkbTx) -> Hash {
+    to_hash(ckb_blake2b(&encode_raw_transaction(tx).unwrap())).unwrap()
+}
+
+/// A call of `method` of `service` sent by the cell type script with
+/// `code_hash`, `nonce` telling equal calls apart.
+pub fn call_tx(code_hash: &Hash, service: &str, method: &str, payload: &str, nonce: u64) -> CkbTx {
+    let data = format!("call:{} {} {}", service, method, payload);
+    let tx = json!({
+        "version": "0x0",
+        "cell_deps": [],
+        "header_deps": [],
+        "inputs": [{
+            "since": "0x0",
+            "previous_output": {
+                "tx_hash": Hash::digest(Bytes::from("prev")),
+                "index": format!("0x{:x}", nonce),
+            },
+        }],
+        "outputs": [{
+            "capacity": "0x34e62ce00",
+            "lock": {
+                "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+                "hash_type": "type",
+                "args": "0x",
+            },
+            "type": {
+                "code_hash": code_hash,
+                "hash_type": "data",
+                "args": "0x01",
+            },
+        }],
+        "outputs_data": [format!("0x{}", hex::encode(data))],
+        "witnesses": ["0x"],
+    });
+    serde_json::from_value(tx).unwrap()
+}
+
+/// A ckb header whose transactions commit to `transactions_root`.
+pub fn header(number: u64, transactions_root: &Hash) -> Value {
+    let epoch = EpochView {
+        number: 0,
+        index:  number,
+        length: 1_000,
+    };
+    let zero = Hash::digest(Bytes::from("zero"));
+    json!({
+        "compact_target": "0x1",
+        "version": "0x0",
+        "timestamp": "0x0",
+        "number": format!("0x{:x}", number),
+        "epoch": format!("0x{:x}", epoch.to_packed()),
+        "parent_hash": zero,
+        "transactions_root": transactions_root,
+        "proposals_hash": zero,
+        "uncles_hash": zero,
+        "dao": zero,
+        "nonce": "0x0",
+    })
+}
+
+/// Hash of a header, the one its child names as parent.
+pub fn hash(header: &Value) -> Hash {
+    let raw: CkbHeader = serde_json::from_value(header.clone()).unwrap();
+    header_hash(&CkbHeaderInner::from(raw).unwrap()).unwrap()
+}
+
+/// The header following `parent` and linked to it, committing to
+/// `transactions_root`.
+pub fn child(parent: &Value, transactions_root: &Hash) -> Value {
+    let raw: CkbHeader = serde_json::from_value(parent.clone()).unwrap();
+    let number = CkbHeaderInner::from(raw).unwrap().number;
+    let mut header = header(number + 1, transactions_root);
+    header["parent_hash"] = json!(hash(parent));
+    header
+}
+
+/// The header of block `number` holding `txs`, and a message proving each
+/// of them.
+pub fn block(number: u64, txs: &[CkbTx]) -> (Value, Vec<Value>) {
+    let mut raws = vec![];
+    let mut hashes = vec![];
+    for tx in txs {
+        let raw = encode_transaction(tx).unwrap();
+        let tx_hash = ckb_blake2b(&encode_raw_transaction(tx).unwrap());
+        hashes.push((tx_hash, ckb_blake2b(&raw)));
+        raws.push(raw);
+    }
+
+    let (transactions_root, proofs) = build_tx_proofs(&hashes);
+    let messages = raws
+        .iter()
+        .zip(proofs.iter())
+        .map(|(raw, proof)| {
+            json!({
+                "raw_tx": format!("0x{}", hex::encode(raw)),
+                "proof": format!("0x{}", hex::encode(proof.pack())),
+            })
+        })
+        .collect();
+
+    (
+        header(number, &to_hash(transactions_root).unwrap()),
+        messages,
+    )
+}
+
+/// The header of block `number` holding `txs`, their messages without a
+/// proof of their own, and the proof of all of them at once.
+pub fn batch(number: u64, txs: &[CkbTx]) -> (Value, Vec<Value>, Value) {
+    let mut tx_hashes = vec![];
+    let mut witness_hashes = vec![];
+    let mut messages = vec![];
+    for tx in txs {
+        let raw = encode_transaction(tx).unwrap();
+        tx_hashes.push(ckb_blake2b(&encode_raw_transaction(tx).unwrap()));
+        witness_hashes.push(ckb_blake2b(&raw));
+        messages.push(json!({ "raw_tx": format!("0x{}", hex::encode(raw)) }));
+    }
+
+    let tx_tree = CbmtTree::build(&tx_hashes);
+    let witnesses_root = CbmtTree::build(&witness_hashes).root();
+    let indices: Vec<u32> = (0..txs.len())
+        .map(|leaf| tx_tree.tree_index(leaf).unwrap())
+        .collect();
+    // every leaf is proven, the trees need no other node
+    let proof = json!({
+        "indices": indices,
+        "lemmas": [],
+        "witnesses_root": to_hash(witnesses_root).unwrap(),
+        "witness_lemmas": [],
+    });
+    let transactions_root = to_hash(merge(&tx_tree.root(), &witnesses_root)).unwrap();
+
+    (header(number, &transactions_root), messages, proof)
+}
+
+/// A message of `block` in the version 1 shape, its proof spelled out.
+pub fn unpacked(message: &Value) -> Value {
+    let packed = message["proof"].as_str().unwrap().trim_start_matches("0x");
+    let proof = unpack_tx_proof(&hex::decode(packed).unwrap()).unwrap();
+    json!({ "raw_tx": message["raw_tx"], "proof": proof })
+}
@@ -0,0 +1,255 @@
+//! Runs the asset and crosschain services on one executor, as a node does,
+//! so tests can drive the bridge through both of them: relayed ckb, ethereum
+//! and muta blocks minting on the asset service, burns paying back to their
+//! chain. The rbac service runs beside them for the chains whose services
+//! check roles against it, and a shop service to be paid through the asset
+//! service or the bridge.
+
+pub mod ckb;
+pub mod eth;
+pub mod muta;
+pub mod shop;
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use cita_trie::MemoryDB;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use asset::AssetService;
+use crosschain::CrosschainService;
+use framework::executor::ServiceExecutor;
+use protocol::traits::{Executor, ExecutorParams, Service, ServiceMapping, ServiceSDK};
+use protocol::types::{
+    Address, Hash, RawTransaction, Receipt, ServiceParam, SignedTransaction, TransactionRequest,
+};
+use protocol::ProtocolResult;
+use rbac::RbacService;
+use testing::MockStorage;
+
+use crate::shop::ShopService;
+
+pub struct BridgeServiceMapping;
+
+impl ServiceMapping for BridgeServiceMapping {
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        let service = match name {
+            "asset" => Box::new(AssetService::new(sdk)?) as Box<dyn Service>,
+            "crosschain" => Box::new(CrosschainService::new(sdk)?) as Box<dyn Service>,
+            "rbac" => Box::new(RbacService::new(sdk)?) as Box<dyn Service>,
+            "shop" => Box::new(ShopService::new(sdk)?) as Box<dyn Service>,
+            _ => panic!("not found service {}", name),
+        };
+
+        Ok(service)
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        vec![
+            "asset".to_owned(),
+            "crosschain".to_owned(),
+            "rbac".to_owned(),
+            "shop".to_owned(),
+        ]
+    }
+}
+
+/// A muta account. The executor takes the caller from the pubkey and never
+/// checks signatures, so any bytes will do.
+#[derive(Clone, Debug)]
+pub struct Account {
+    pub pubkey:  Bytes,
+    pub address: Address,
+}
+
+impl Account {
+    pub fn new(n: u8) -> Self {
+        let mut pubkey = vec![n; 33];
+        pubkey[0] = 0x02;
+        let pubkey = Bytes::from(pubkey);
+        let address = Address::from_pubkey_bytes(pubkey.clone()).unwrap();
+
+        Account { pubkey, address }
+    }
+}
+
+/// A chain of both services, one transaction per block.
+pub struct Chain {
+    memdb:  Arc<MemoryDB>,
+    root:   Hash,
+    height: u64,
+}
+
+impl Chain {
+    pub fn new(asset_genesis: Value, crosschain_genesis: Value) -> Self {
+        Self::with_services(vec![
+            ServiceParam {
+                name:    "asset".to_owned(),
+                payload: asset_genesis.to_string(),
+            },
+            ServiceParam {
+                name:    "crosschain".to_owned(),
+                payload: crosschain_genesis.to_string(),
+            },
+        ])
+    }
+
+    /// `new` with the rbac service too, which the other two check roles
+    /// against when their genesis sets `rbac`.
+    pub fn with_rbac(asset_genesis: Value, crosschain_genesis: Value, rbac_genesis: Value) -> Self {
+        Self::with_services(vec![
+            ServiceParam {
+                name:    "asset".to_owned(),
+                payload: asset_genesis.to_string(),
+            },
+            ServiceParam {
+                name:    "crosschain".to_owned(),
+                payload: crosschain_genesis.to_string(),
+            },
+            ServiceParam {
+                name:    "rbac".to_owned(),
+                payload: rbac_genesis.to_string(),
+            },
+        ])
+    }
+
+    fn with_services(services: Vec<ServiceParam>) -> Self {
+        let memdb = Arc::new(MemoryDB::new(false));
+        let root = ServiceExecutor::create_genesis(
+            services,
+            Arc::clone(&memdb),
+            Arc::new(MockStorage {}),
+            Arc::new(BridgeServiceMapping {}),
+        )
+        .unwrap();
+
+        Chain {
+            memdb,
+            root,
+            height: 0,
+        }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Executes a block of the single call, which commits even when the
+    /// call fails, as it would on a node.
+    pub fn exec(
+        &mut self,
+        caller: &Account,
+        service: &str,
+        method: &str,
+        payload: Value,
+    ) -> Receipt {
+        self.height += 1;
+        let params = self.params();
+        let raw = RawTransaction {
+            chain_id:     Hash::from_empty(),
+            nonce:        Hash::digest(Bytes::from(self.height.to_string())),
+            timeout:      0,
+            cycles_price: 1,
+            cycles_limit: std::u64::MAX,
+            request:      request(service, method, payload),
+        };
+        let stx = SignedTransaction {
+            raw,
+            tx_hash: Hash::from_empty(),
+            pubkey: caller.pubkey.clone(),
+            signature: Bytes::new(),
+        };
+
+        let resp = self.executor().exec(&params, &[stx]).unwrap();
+        self.root = resp.state_root;
+        resp.receipts.into_iter().next().unwrap()
+    }
+
+    /// Like `exec`, but panics with the error of a failing call.
+    pub fn write(
+        &mut self,
+        caller: &Account,
+        service: &str,
+        method: &str,
+        payload: Value,
+    ) -> Receipt {
+        let receipt = self.exec(caller, service, method, payload);
+        assert!(
+            !receipt.response.is_error,
+            "{}.{} failed: {}",
+            service, method, receipt.response.ret
+        );
+        receipt
+    }
+
+    pub fn read<T: DeserializeOwned>(&self, service: &str, method: &str, payload: Value) -> T {
+        let resp = self
+            .executor()
+            .read(
+                &self.params(),
+                &Address::from_hex(READER).unwrap(),
+                1,
+                &request(service, method, payload),
+            )
+            .unwrap();
+        assert!(
+            !resp.is_error,
+            "{}.{} failed: {}",
+            service, method, resp.ret
+        );
+        serde_json::from_str(&resp.ret).unwrap()
+    }
+
+    fn params(&self) -> ExecutorParams {
+        ExecutorParams {
+            state_root:   self.root.clone(),
+            height:       self.height,
+            timestamp:    0,
+            cycles_limit: std::u64::MAX,
+        }
+    }
+
+    fn executor(&self) -> ServiceExecutor<MockStorage, MemoryDB, BridgeServiceMapping> {
+        ServiceExecutor::with_root(
+            self.root.clone(),
+            Arc::clone(&self.memdb),
+            Arc::new(MockStorage {}),
+            Arc::new(BridgeServiceMapping {}),
+        )
+        .unwrap()
+    }
+}
+
+const READER: &str = "0x0000000000000000000000000000000000000000";
+
+fn request(service: &str, method: &str, payload: Value) -> TransactionRequest {
+    TransactionRequest {
+        service_name: service.to_owned(),
+        method:       method.to_owned(),
+        payload:      payload.to_string(),
+    }
+}
+
+/// The events `service` emitted in a receipt that parse as `T`, which sorts
+/// them out for services whose events carry no topic.
+pub fn events_of<T: DeserializeOwned>(receipt: &Receipt, service: &str) -> Vec<T> {
+    receipt
+        .events
+        .iter()
+        .filter(|event| event.service == service)
+        .filter_map(|event| serde_json::from_str(&event.data).ok())
+        .collect()
+}
+
+/// The events of a receipt carrying `topic`.
+pub fn events_with_topic(receipt: &Receipt, topic: &str) -> Vec<Value> {
+    events_of::<Value>(receipt, "crosschain")
+        .into_iter()
+        .filter(|event| event["topic"] == topic)
+        .collect()
+}
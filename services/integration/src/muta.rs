@@ -0,0 +1,239 @@
+//! Blocks of a counterpart muta chain, signed by its validators, holding the
+//! locks of its bridge service, with the headers and receipt proofs a relayer
+//! would submit for them.
+
+use std::convert::TryFrom;
+
+use serde_json::{json, Value};
+
+use common_crypto::{
+    BlsCommonReference, BlsPrivateKey, BlsSignature, HashValue, PrivateKey, PublicKey, Signature,
+    ToBlsPublicKey,
+};
+use crosschain::muta::{validator_set_hash, vote_hash, LOCK_TO_MUTA_TOPIC};
+use crosschain::types::{MutaValidator, MutaValidatorSet};
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{
+    Address, BlockHeader, Bytes, Event, Hash, Proof, Receipt, ReceiptResponse, Validator,
+};
+
+/// The bls common reference of the counterpart chain.
+pub const COMMON_REF: &str = "muta";
+/// The service of the counterpart chain emitting the locks.
+pub const BRIDGE_SERVICE: &str = "crosschain";
+
+fn hex_value(data: &[u8]) -> Value {
+    json!(format!("0x{}", hex::encode(data)))
+}
+
+pub fn chain_id() -> Hash {
+    Hash::digest(Bytes::from("counterpart"))
+}
+
+/// The crosschain muta config for the counterpart chain.
+pub fn config() -> Value {
+    json!({
+        "chain_id": chain_id(),
+        "common_ref": hex_value(COMMON_REF.as_bytes()),
+        "bridge_service": BRIDGE_SERVICE,
+    })
+}
+
+/// Validators of the counterpart chain, of equal vote weight.
+pub struct Validators {
+    keys:       Vec<BlsPrivateKey>,
+    validators: Vec<MutaValidator>,
+}
+
+impl Validators {
+    /// `count` validators, `seed` telling the keys of sets apart.
+    pub fn new(seed: u8, count: u8) -> Self {
+        let common_ref: BlsCommonReference = COMMON_REF.into();
+        let mut keys = vec![];
+        let mut validators = vec![];
+        for i in 0..count {
+            // a bls private key is a 32 bytes secret left padded to 48
+            let mut secret = vec![0u8; 16];
+            secret.extend_from_slice(&[seed + i; 32]);
+            let key = BlsPrivateKey::try_from(secret.as_slice()).unwrap();
+            let pub_key = key.pub_key(&common_ref).to_bytes();
+
+            validators.push(MutaValidator {
+                bls_pub_key: serde_json::from_value(hex_value(&pub_key)).unwrap(),
+                address:     Address::from_bytes(Bytes::from(vec![seed + i; 20])).unwrap(),
+                vote_weight: 1,
+            });
+            keys.push(key);
+        }
+
+        Validators { keys, validators }
+    }
+
+    /// The set of `version`, signing the blocks from `start_height` on.
+    pub fn set(&self, version: u64, start_height: u64) -> MutaValidatorSet {
+        MutaValidatorSet {
+            version,
+            start_height,
+            validators: self.validators.clone(),
+        }
+    }
+
+    /// The signature of the first `signers` validators aggregated over
+    /// `hash`, and the bitmap flagging them.
+    pub fn sign(&self, hash: &Hash, signers: usize) -> (Bytes, Bytes) {
+        let common_ref: BlsCommonReference = COMMON_REF.into();
+        let hash = HashValue::try_from(hash.as_bytes().as_ref()).unwrap();
+        let signatures = self.keys[..signers]
+            .iter()
+            .map(|key| (key.sign_message(&hash), key.pub_key(&common_ref)))
+            .collect();
+        let signature = BlsSignature::combine(signatures).to_bytes();
+
+        let mut bitmap = vec![0u8; (self.keys.len() + 7) / 8];
+        for i in 0..signers {
+            bitmap[i / 8] |= 0x80 >> (i % 8);
+        }
+        (Bytes::from(signature.to_vec()), Bytes::from(bitmap))
+    }
+
+    /// The update to `next` signed by the first `signers` validators.
+    pub fn update(&self, next: &MutaValidatorSet, signers: usize) -> Value {
+        let (signature, bitmap) = self.sign(&validator_set_hash(next), signers);
+        json!({
+            "validator_set": next,
+            "signature": hex_value(&signature),
+            "bitmap": hex_value(&bitmap),
+        })
+    }
+}
+
+fn block_header(
+    height: u64,
+    prev_hash: &Hash,
+    receipt_roots: Vec<Hash>,
+    validators: &Validators,
+    signers: usize,
+) -> BlockHeader {
+    let mut proof = Proof {
+        height:     height - 1,
+        round:      0,
+        block_hash: prev_hash.clone(),
+        signature:  Bytes::new(),
+        bitmap:     Bytes::new(),
+    };
+    let (signature, bitmap) = validators.sign(&vote_hash(&proof), signers);
+    proof.signature = signature;
+    proof.bitmap = bitmap;
+
+    BlockHeader {
+        chain_id: chain_id(),
+        height,
+        exec_height: height,
+        prev_hash: prev_hash.clone(),
+        timestamp: 0,
+        order_root: Hash::from_empty(),
+        confirm_root: vec![],
+        state_root: Hash::from_empty(),
+        receipt_root: receipt_roots,
+        cycles_used: vec![],
+        proposer: validators.validators[0].address.clone(),
+        proof,
+        validator_version: 0,
+        validators: vec![],
+    }
+}
+
+fn encode(header: BlockHeader) -> Value {
+    hex_value(&header.encode_fixed().unwrap())
+}
+
+/// The fixed encoded header of block `height`, a child of the block hashed
+/// `prev_hash`, committing to `receipt_roots`. It carries the proof of its
+/// parent signed by the first `signers` of `validators`.
+pub fn header(
+    height: u64,
+    prev_hash: &Hash,
+    receipt_roots: Vec<Hash>,
+    validators: &Validators,
+    signers: usize,
+) -> Value {
+    encode(block_header(
+        height,
+        prev_hash,
+        receipt_roots,
+        validators,
+        signers,
+    ))
+}
+
+/// `header`, also announcing the validators of `next`, which starts at the
+/// following block.
+pub fn announcing(
+    height: u64,
+    prev_hash: &Hash,
+    validators: &Validators,
+    signers: usize,
+    next: &MutaValidatorSet,
+) -> Value {
+    let mut header = block_header(height, prev_hash, vec![], validators, signers);
+    header.validator_version = next.version;
+    header.validators = next
+        .validators
+        .iter()
+        .map(|v| Validator {
+            address:        v.address.clone(),
+            propose_weight: 1,
+            vote_weight:    v.vote_weight,
+        })
+        .collect();
+    encode(header)
+}
+
+/// Hash of a fixed encoded header, the one its child names as parent.
+pub fn hash(header: &Value) -> Hash {
+    let raw = header.as_str().unwrap().trim_start_matches("0x");
+    Hash::digest(Bytes::from(hex::decode(raw).unwrap()))
+}
+
+/// The fixed encoded receipt of a tx locking `amount` of the counterpart
+/// asset `asset_id` for `receiver`, `nonce` telling equal locks apart.
+pub fn lock_receipt(asset_id: &Hash, receiver: &Address, amount: u128, nonce: u64) -> Vec<u8> {
+    let lock = json!({
+        "asset_id": asset_id,
+        "receiver": receiver,
+        "amount": amount.to_string(),
+        "topic": LOCK_TO_MUTA_TOPIC,
+    });
+    let receipt = Receipt {
+        state_root:  Hash::from_empty(),
+        height:      1,
+        tx_hash:     Hash::digest(Bytes::from(format!("lock {}", nonce))),
+        cycles_used: 0,
+        events:      vec![Event {
+            service: BRIDGE_SERVICE.to_owned(),
+            data:    lock.to_string(),
+        }],
+        response:    ReceiptResponse {
+            service_name: BRIDGE_SERVICE.to_owned(),
+            method:       "lock_to_muta".to_owned(),
+            ret:          String::new(),
+            is_error:     false,
+        },
+    };
+    receipt.encode_fixed().unwrap().to_vec()
+}
+
+/// The root of a block whose only receipt is `receipt`.
+pub fn receipt_root(receipt: &[u8]) -> Hash {
+    Hash::digest(Bytes::from(receipt.to_vec()))
+}
+
+/// The proof of `receipt`, the only one of block `height`.
+pub fn receipt_proof(height: u64, receipt: &[u8]) -> Value {
+    json!({
+        "height": height,
+        "root_index": 0,
+        "receipt": hex_value(receipt),
+        "path": [],
+    })
+}
@@ -0,0 +1,85 @@
+//! A service selling items paid through `transfer_and_call` of the asset
+//! service or by deposits the bridge mints to it, to test what a called
+//! service gets on a transfer or a mint.
+
+use bytes::Bytes;
+use derive_more::{Display, From};
+
+use asset::types::OnTransferPayload;
+use binding_macro::{cycles, service};
+use crosschain::types::OnCrossMintPayload;
+use protocol::traits::{ExecutorParams, ServiceSDK};
+use protocol::types::ServiceContext;
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+/// An item the shop never has, buying it fails the call.
+pub const SOLD_OUT: &str = "sold_out";
+
+static ASSET_SERVICE: Bytes = Bytes::from_static(b"asset");
+static CROSSCHAIN_SERVICE: Bytes = Bytes::from_static(b"crosschain");
+
+pub struct ShopService<SDK> {
+    sdk: SDK,
+}
+
+#[service]
+impl<SDK: ServiceSDK> ShopService<SDK> {
+    pub fn new(sdk: SDK) -> ProtocolResult<Self> {
+        Ok(Self { sdk })
+    }
+
+    /// Sells the item named by `payload.payload`, only when the asset service
+    /// calls on a transfer to the shop. Emits the payment it was paid with.
+    #[cycles(210_00)]
+    #[write]
+    fn on_transfer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: OnTransferPayload,
+    ) -> ProtocolResult<()> {
+        if ctx.get_extra() != Some(ASSET_SERVICE.clone()) {
+            return Err(ShopError::NotPaid.into());
+        }
+        if payload.payload == SOLD_OUT {
+            return Err(ShopError::SoldOut.into());
+        }
+
+        let event_str = serde_json::to_string(&payload).map_err(ShopError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    /// Takes a deposit the bridge minted to an account registered with the
+    /// shop as its mint callback. Emits the deposit.
+    #[cycles(210_00)]
+    #[write]
+    fn on_cross_mint(
+        &mut self,
+        ctx: ServiceContext,
+        payload: OnCrossMintPayload,
+    ) -> ProtocolResult<()> {
+        if ctx.get_extra() != Some(CROSSCHAIN_SERVICE.clone()) {
+            return Err(ShopError::NotPaid.into());
+        }
+
+        let event_str = serde_json::to_string(&payload).map_err(ShopError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+}
+
+#[derive(Debug, Display, From)]
+pub enum ShopError {
+    #[display(fmt = "Parsing payload to json failed {:?}", _0)]
+    JsonParse(serde_json::Error),
+
+    NotPaid,
+
+    SoldOut,
+}
+
+impl std::error::Error for ShopError {}
+
+impl From<ShopError> for ProtocolError {
+    fn from(err: ShopError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Service, Box::new(err))
+    }
+}
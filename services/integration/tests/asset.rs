@@ -0,0 +1,92 @@
+use serde_json::{json, Value};
+
+use asset::service_address;
+use asset::types::{GetBalanceResponse, OnTransferPayload, TransferEvent};
+use integration::shop::SOLD_OUT;
+use integration::{events_of, Account, Chain};
+use protocol::types::{Address, Bytes, Hash};
+
+fn native_asset_id() -> Hash {
+    Hash::digest(Bytes::from("native"))
+}
+
+/// Both services at genesis, `issuer` holding the native asset.
+fn chain(issuer: &Account) -> Chain {
+    Chain::new(
+        json!({
+            "id": native_asset_id(),
+            "name": "MutaToken",
+            "supply": "1000000",
+            "issuer": issuer.address,
+        }),
+        json!({ "admin": issuer.address }),
+    )
+}
+
+fn balance(chain: &Chain, address: &Address) -> u128 {
+    let resp: GetBalanceResponse = chain.read(
+        "asset",
+        "get_balance",
+        json!({ "asset_id": native_asset_id(), "user": address }),
+    );
+    resp.balance
+}
+
+fn buy(item: &str) -> Value {
+    json!({
+        "asset_id": native_asset_id(),
+        "to_service": "shop",
+        "value": "100",
+        "payload": item,
+    })
+}
+
+#[test]
+fn test_transfer_and_call() {
+    let alice = Account::new(1);
+    let mut chain = chain(&alice);
+    let shop = service_address("shop").unwrap();
+
+    let receipt = chain.write(&alice, "asset", "transfer_and_call", buy("apple"));
+    let transfers: Vec<TransferEvent> = events_of(&receipt, "asset");
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(
+        (&transfers[0].from, &transfers[0].to),
+        (&alice.address, &shop)
+    );
+    // the shop is told who paid what, and that the asset service moved it
+    let paid: Vec<OnTransferPayload> = events_of(&receipt, "shop");
+    assert_eq!(paid.len(), 1);
+    assert_eq!(paid[0].asset_id, native_asset_id());
+    assert_eq!(paid[0].sender, alice.address);
+    assert_eq!(paid[0].value, 100);
+    assert_eq!(paid[0].payload, "apple");
+    assert_eq!(balance(&chain, &shop), 100);
+    assert_eq!(balance(&chain, &alice.address), 999_900);
+
+    // a user can't claim a payment calling the shop directly
+    let receipt = chain.exec(
+        &alice,
+        "shop",
+        "on_transfer",
+        json!({
+            "asset_id": native_asset_id(),
+            "sender": alice.address,
+            "value": "100",
+            "payload": "apple",
+        }),
+    );
+    assert!(receipt.response.is_error);
+
+    // nor have the asset service call one trusting it in its name
+    let mut reserved = buy("apple");
+    reserved["to_service"] = json!("crosschain");
+    let receipt = chain.exec(&alice, "asset", "transfer_and_call", reserved);
+    assert!(receipt.response.is_error);
+
+    // a failing call takes the transfer back with it
+    let receipt = chain.exec(&alice, "asset", "transfer_and_call", buy(SOLD_OUT));
+    assert!(receipt.response.is_error);
+    assert_eq!(balance(&chain, &shop), 100);
+    assert_eq!(balance(&chain, &alice.address), 999_900);
+}
@@ -0,0 +1,2308 @@
+use serde_json::{json, Value};
+
+use asset::types::{Asset, BurnEvent, GetBalanceResponse, MintEvent};
+use crosschain::eth::token_key;
+use crosschain::proof::{cbmt_build_root, ckb_blake2b, merge};
+use crosschain::types::{
+    BridgeStats, BurnBatch, BurnRecord, BurnStatus, BurnToEthEvent, BurnToMutaEvent, CkbHeader,
+    CkbTx, DepositQuote, DepositTag, EpochSummary, EventDigests, GetHeaderResponse, GetMmrResponse,
+    HeaderConflict, MigrationStatus, MutaValidatorSet, OnCrossMintPayload, Params, PayoutAddress,
+    SupportedLocks, WithdrawalFeeEstimate,
+};
+use crosschain::{
+    eth_mirror_asset_id, mirror_asset_id, muta_mirror_asset_id, PAYOUT_COOLDOWN, SCHEMA_VERSION,
+};
+use integration::{ckb, eth, events_of, events_with_topic, muta, Account, Chain};
+use protocol::types::{Bytes, Hash, Receipt};
+
+struct Bridge {
+    chain:     Chain,
+    admin:     Account,
+    relayer:   Account,
+    sudt_args: Hash,
+    asset_id:  Hash,
+}
+
+/// Both services at genesis, with a relayer and a mirror asset one to one
+/// with its sudt.
+fn bridge() -> Bridge {
+    bridge_with(json!({}))
+}
+
+/// `bridge` with the `genesis` fields added to the crosschain genesis.
+fn bridge_with(genesis: Value) -> Bridge {
+    let admin = Account::new(1);
+    let relayer = Account::new(2);
+    let mut crosschain_genesis = json!({ "admin": admin.address });
+    crosschain_genesis
+        .as_object_mut()
+        .unwrap()
+        .extend(genesis.as_object().unwrap().clone());
+    let mut chain = Chain::new(
+        json!({
+            "id": Hash::digest(Bytes::from("native")),
+            "name": "MutaToken",
+            "supply": "1000000",
+            "issuer": admin.address,
+        }),
+        crosschain_genesis,
+    );
+
+    chain.write(
+        &admin,
+        "crosschain",
+        "add_relayer",
+        json!({ "address": relayer.address }),
+    );
+    let sudt_args = Hash::digest(Bytes::from("sudt"));
+    chain.write(
+        &admin,
+        "crosschain",
+        "register_mirror_asset",
+        json!({
+            "sudt_args": sudt_args,
+            "name": "Mirror",
+            "symbol": "MIR",
+            "decimals": 8,
+            "scale_factor": 1,
+        }),
+    );
+
+    Bridge {
+        chain,
+        admin,
+        relayer,
+        asset_id: mirror_asset_id(&sudt_args),
+        sudt_args,
+    }
+}
+
+impl Bridge {
+    /// Relays block `number` holding `txs`, its header first.
+    fn relay(&mut self, number: u64, txs: &[CkbTx]) -> Receipt {
+        let (header, messages) = ckb::block(number, txs);
+        self.chain.write(
+            &self.relayer,
+            "crosschain",
+            "update_headers",
+            json!({ "headers": [header] }),
+        );
+        self.submit(number, messages)
+    }
+
+    fn submit(&mut self, number: u64, messages: Vec<Value>) -> Receipt {
+        self.chain.write(
+            &self.relayer,
+            "crosschain",
+            "submit_messages",
+            json!({ "height": number, "messages": messages }),
+        )
+    }
+
+    fn balance(&self, account: &Account) -> u128 {
+        let resp: GetBalanceResponse = self.chain.read(
+            "asset",
+            "get_balance",
+            json!({ "asset_id": self.asset_id, "user": account.address }),
+        );
+        resp.balance
+    }
+
+    fn supply(&self) -> u128 {
+        let asset: Asset = self
+            .chain
+            .read("asset", "get_asset", json!({ "id": self.asset_id }));
+        asset.supply.0
+    }
+
+    fn epoch_summary(&self, epoch: u64) -> EpochSummary {
+        self.chain
+            .read("crosschain", "get_epoch_summary", json!({ "epoch": epoch }))
+    }
+
+    fn stats(&self) -> BridgeStats {
+        self.chain
+            .read("crosschain", "get_bridge_stats", json!(null))
+    }
+
+    /// The mirror asset's supply is what the bridge minted less what it
+    /// burned, and all of it is held by `holders`.
+    fn assert_backed(&self, holders: &[&Account]) {
+        let supply = self.supply();
+        assert_eq!(supply, self.stats().outstanding(&self.asset_id));
+        assert_eq!(
+            supply,
+            holders.iter().map(|a| self.balance(a)).sum::<u128>()
+        );
+    }
+}
+
+#[test]
+fn test_register_creates_asset() {
+    let bridge = bridge();
+    let asset: Asset = bridge
+        .chain
+        .read("asset", "get_asset", json!({ "id": bridge.asset_id }));
+    assert_eq!(asset.name, "Mirror");
+    assert_eq!(asset.supply.0, 0);
+    assert_ne!(asset.issuer, bridge.admin.address);
+}
+
+#[test]
+fn test_deposit_mints_on_asset() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 2000, &bob.address, 1),
+    ];
+
+    let receipt = bridge.relay(1, &txs);
+    // each deposit mints to its receiver and the relay fee to the relayer
+    assert_eq!(events_with_topic(&receipt, "mint_asset").len(), 4);
+    let mints: Vec<MintEvent> = events_of(&receipt, "asset");
+    assert_eq!(mints.len(), 4);
+    assert!(mints.iter().all(|m| m.minter == "crosschain"));
+    assert!(mints.iter().all(|m| m.asset_id == bridge.asset_id));
+
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bob), 1980);
+    assert_eq!(bridge.balance(&bridge.relayer), 30);
+    assert_eq!(bridge.supply(), 3000);
+    let stats = bridge.stats();
+    assert_eq!(stats.total_mints, 2);
+    assert_eq!(stats.headers_stored, 1);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_relay_fee_split_with_treasury() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let treasury = Account::new(5);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "fee_beneficiary": { "split": {
+            "caller_bps": 2500,
+            "treasury": treasury.address,
+            "treasury_bps": 7500,
+        }}}),
+    );
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+
+    let receipt = bridge.relay(1, &txs);
+    assert_eq!(events_with_topic(&receipt, "mint_asset").len(), 3);
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bridge.relayer), 3);
+    assert_eq!(bridge.balance(&treasury), 7);
+    bridge.assert_backed(&[&alice, &bridge.relayer, &treasury]);
+}
+
+#[test]
+fn test_relay_fee_tiers() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "fee_schedule": { "tiers": [
+            { "threshold": "0", "bps": 100 },
+            { "threshold": "10000", "bps": 10 },
+        ]}}),
+    );
+    let quote: DepositQuote = bridge.chain.read(
+        "crosschain",
+        "quote_deposit",
+        json!({ "sudt_args": bridge.sudt_args, "sudt_amount": "20000" }),
+    );
+    assert_eq!((quote.relay_fee, quote.relay_fee_bps), (20, 10));
+
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 20000, &bob.address, 1),
+    ];
+    bridge.relay(1, &txs);
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bob), quote.credited);
+    assert_eq!(bridge.balance(&bridge.relayer), 10 + 20);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_formatted_amounts() {
+    let bridge = bridge();
+    let quote: DepositQuote = bridge.chain.read(
+        "crosschain",
+        "quote_deposit",
+        json!({ "sudt_args": bridge.sudt_args, "sudt_amount": "150000000", "formatted": true }),
+    );
+    assert_eq!(quote.formatted_amount.as_deref(), Some("1.50000000"));
+    assert_eq!(quote.formatted_credited.as_deref(), Some("1.48500000"));
+
+    // the mirror asset carries the decimals it was registered with
+    let resp: GetBalanceResponse = bridge.chain.read(
+        "asset",
+        "get_balance",
+        json!({ "asset_id": bridge.asset_id, "user": bridge.admin.address, "formatted": true }),
+    );
+    assert_eq!(resp.formatted_balance.as_deref(), Some("0.00000000"));
+}
+
+#[test]
+fn test_relay_fee_to_payout_address() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let payout = Account::new(6);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "set_payout_address",
+        json!({ "payout": payout.address }),
+    );
+    let alias: PayoutAddress = bridge.chain.read(
+        "crosschain",
+        "get_payout_address",
+        json!({ "address": bridge.relayer.address }),
+    );
+    assert_eq!(alias.payout, payout.address);
+
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&payout), 10);
+    assert_eq!(bridge.balance(&bridge.relayer), 0);
+    bridge.assert_backed(&[&alice, &payout]);
+
+    // the alias can't move again before the cooldown is over
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "set_payout_address",
+        json!({ "payout": bridge.relayer.address }),
+    );
+    assert!(receipt.response.is_error);
+}
+
+#[test]
+fn test_message_proof_versions() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 2000, &alice.address, 1),
+    ];
+    let (header, mut messages) = ckb::block(1, &txs);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+
+    // a packed proof short of the lemmas its index calls for is rejected
+    let proof = messages[1]["proof"].as_str().unwrap().to_owned();
+    messages[1]["proof"] = json!(proof[..proof.len() - 64]);
+    let receipt = bridge.submit(1, messages.clone());
+    let rejected = events_with_topic(&receipt, "message_rejected");
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0]["reason"], "invalid_proof");
+    assert_eq!(bridge.balance(&alice), 990);
+
+    // version 1 payloads spell the proof out
+    let (_, messages) = ckb::block(1, &txs);
+    let v1 = json!({
+        "version": 1,
+        "payload": { "height": 1, "messages": [ckb::unpacked(&messages[1])] },
+    });
+    bridge
+        .chain
+        .write(&bridge.relayer, "crosschain", "submit_messages", v1);
+    assert_eq!(bridge.balance(&alice), 990 + 1980);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_replayed_deposit_rejected() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+
+    let (_, messages) = ckb::block(1, &txs);
+    let receipt = bridge.submit(1, messages);
+    let rejected = events_with_topic(&receipt, "message_rejected");
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0]["reason"], "duplicate_tx");
+    assert!(events_of::<MintEvent>(&receipt, "asset").is_empty());
+
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.stats().rejected_messages["duplicate_tx"], 1);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_unregistered_sudt_not_minted() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let unknown = Hash::digest(Bytes::from("unknown"));
+    let txs = vec![ckb::deposit_tx(&unknown, 1000, &alice.address, 0)];
+
+    let receipt = bridge.relay(1, &txs);
+    let rejected = events_with_topic(&receipt, "message_rejected");
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0]["reason"], "unregistered_sudt");
+    assert!(events_of::<MintEvent>(&receipt, "asset").is_empty());
+    assert_eq!(bridge.stats().total_mints, 0);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_force_process_message() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let (header, messages) = ckb::block(1, &txs);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+    // the payload takes the message with its proof spelled out
+    let force = json!({
+        "height": 1,
+        "message": ckb::unpacked(&messages[0]),
+        "receiver": bob.address,
+    });
+
+    // only the admin forces a message
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "force_process_message",
+        force.clone(),
+    );
+    assert!(receipt.response.is_error);
+
+    // a mint over the supply cap fails the forced message as a whole
+    let asset_id = bridge.asset_id.clone();
+    let set_cap = |cap: &str| json!({ "asset_id": asset_id, "cap": cap });
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_supply_cap",
+        set_cap("500"),
+    );
+    let receipt = bridge.chain.exec(
+        &bridge.admin,
+        "crosschain",
+        "force_process_message",
+        force.clone(),
+    );
+    assert!(receipt.response.is_error);
+    assert_eq!(bridge.supply(), 0);
+
+    bridge
+        .chain
+        .write(&bridge.admin, "crosschain", "set_supply_cap", set_cap("0"));
+    let receipt = bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "force_process_message",
+        force.clone(),
+    );
+    assert_eq!(
+        events_with_topic(&receipt, "force_process_message").len(),
+        1
+    );
+    assert!(events_with_topic(&receipt, "message_rejected").is_empty());
+    // the receiver and the relay fee, minted once each
+    assert_eq!(events_of::<MintEvent>(&receipt, "asset").len(), 2);
+    assert_eq!(bridge.balance(&alice), 0);
+    assert_eq!(bridge.balance(&bob), 990);
+    assert_eq!(bridge.balance(&bridge.admin), 10);
+
+    let receipt = bridge
+        .chain
+        .exec(&bridge.admin, "crosschain", "force_process_message", force);
+    assert!(receipt.response.is_error);
+    bridge.assert_backed(&[&bob, &bridge.admin]);
+}
+
+#[test]
+fn test_withdrawal_burns_on_asset() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+
+    // a short format secp256k1 address
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let receipt = bridge.chain.write(
+        &alice,
+        "crosschain",
+        "burn_sudt",
+        json!({ "token_id": bridge.asset_id, "receiver": ckb_receiver, "amount": "600" }),
+    );
+    let burns = events_with_topic(&receipt, "burn_asset");
+    assert_eq!(burns.len(), 1);
+    assert_eq!(burns[0]["nonce"], 1);
+    assert_eq!(burns[0]["ckb_receiver"], ckb_receiver);
+    let asset_burns: Vec<BurnEvent> = events_of(&receipt, "asset");
+    assert_eq!(asset_burns.len(), 1);
+    assert_eq!(asset_burns[0].user, alice.address);
+    assert_eq!(asset_burns[0].amount, 600);
+    assert_eq!(asset_burns[0].burner, "crosschain");
+
+    let record: BurnRecord =
+        bridge
+            .chain
+            .read("crosschain", "get_burn_record", json!({ "nonce": 1 }));
+    assert_eq!(record.status, BurnStatus::Pending);
+    assert_eq!(record.muta_sender, alice.address);
+    assert_eq!(record.sudt_amount, 600);
+
+    assert_eq!(bridge.balance(&alice), 390);
+    assert_eq!(bridge.supply(), 400);
+    assert_eq!(bridge.stats().total_burns, 1);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_burn_quota() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &bob.address, 1),
+    ];
+    bridge.relay(1, &txs);
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let asset_id = bridge.asset_id.clone();
+    let burn =
+        |amount: &str| json!({ "token_id": asset_id, "receiver": ckb_receiver, "amount": amount });
+
+    let quota = json!({ "asset_id": bridge.asset_id, "quota": "500", "window": 100 });
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "set_burn_quota", quota.clone());
+    assert!(receipt.response.is_error);
+    bridge
+        .chain
+        .write(&bridge.admin, "crosschain", "set_burn_quota", quota);
+
+    // each address burns up to the quota within the window
+    bridge
+        .chain
+        .write(&alice, "crosschain", "burn_sudt", burn("300"));
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "burn_sudt", burn("300"));
+    assert!(receipt.response.is_error);
+    let receipt = bridge
+        .chain
+        .exec(&bob, "crosschain", "burn_sudt", burn("600"));
+    assert!(receipt.response.is_error);
+
+    // an exempt address isn't held to it, and its burns don't count
+    let exemption = json!({ "address": alice.address });
+    let receipt = bridge.chain.exec(
+        &alice,
+        "crosschain",
+        "add_quota_exemption",
+        exemption.clone(),
+    );
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "add_quota_exemption",
+        exemption.clone(),
+    );
+    bridge
+        .chain
+        .write(&alice, "crosschain", "burn_sudt", burn("300"));
+    assert_eq!(bridge.balance(&alice), 390);
+
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "remove_quota_exemption",
+        exemption,
+    );
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "burn_sudt", burn("300"));
+    assert!(receipt.response.is_error);
+    bridge
+        .chain
+        .write(&alice, "crosschain", "burn_sudt", burn("200"));
+    assert_eq!(bridge.balance(&alice), 190);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_supported_locks() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+    let code_hash = Hash::digest(Bytes::from("custom lock"));
+    let secp_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let custom_receiver = format!(
+        "0x04{}{}",
+        hex::encode(code_hash.as_bytes()),
+        hex::encode([7u8; 20])
+    );
+    let asset_id = bridge.asset_id.clone();
+    let burn =
+        |receiver: &str| json!({ "token_id": asset_id, "receiver": receiver, "amount": "100" });
+
+    // the secp256k1 locks are the only ones by default
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "burn_sudt", burn(&custom_receiver));
+    assert!(receipt.response.is_error);
+
+    let locks = json!({ "code_hashes": [code_hash] });
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "set_supported_locks", locks.clone());
+    assert!(receipt.response.is_error);
+    let receipt = bridge
+        .chain
+        .write(&bridge.admin, "crosschain", "set_supported_locks", locks);
+    assert_eq!(events_with_topic(&receipt, "set_supported_locks").len(), 1);
+    let supported: SupportedLocks =
+        bridge
+            .chain
+            .read("crosschain", "get_supported_locks", json!(null));
+    assert_eq!(supported.code_hashes, vec![code_hash]);
+
+    // the list replaces the default one
+    bridge
+        .chain
+        .write(&alice, "crosschain", "burn_sudt", burn(&custom_receiver));
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "burn_sudt", burn(&secp_receiver));
+    assert!(receipt.response.is_error);
+    assert_eq!(bridge.balance(&alice), 890);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_ack_burn() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+    let lock_args = [7u8; 20];
+    let ckb_receiver = format!("0x0100{}", hex::encode(lock_args));
+    for amount in &["600", "300"] {
+        bridge.chain.write(
+            &alice,
+            "crosschain",
+            "burn_sudt",
+            json!({ "token_id": bridge.asset_id, "receiver": ckb_receiver, "amount": amount }),
+        );
+    }
+
+    let unlocks = vec![
+        ckb::unlock_tx(&bridge.sudt_args, 600, &lock_args, 0),
+        ckb::unlock_tx(&bridge.sudt_args, 600, &[8u8; 20], 1),
+    ];
+    let (header, messages) = ckb::block(2, &unlocks);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+    let ack = |nonce: u64, index: usize| {
+        json!({
+            "nonce": nonce,
+            "ckb_tx_hash": ckb::tx_hash(&unlocks[index]),
+            "raw_tx": messages[index]["raw_tx"],
+            "height": 2,
+            "proof": ckb::unpacked(&messages[index])["proof"],
+        })
+    };
+    let record = |bridge: &Bridge, nonce: u64| -> BurnRecord {
+        bridge
+            .chain
+            .read("crosschain", "get_burn_record", json!({ "nonce": nonce }))
+    };
+
+    // an included tx paying someone else doesn't complete the burn
+    let receipt = bridge
+        .chain
+        .exec(&bridge.relayer, "crosschain", "ack_burn", ack(1, 1));
+    assert!(receipt.response.is_error);
+    assert_eq!(record(&bridge, 1).status, BurnStatus::Pending);
+
+    // nor does one paying another amount
+    let receipt = bridge
+        .chain
+        .exec(&bridge.relayer, "crosschain", "ack_burn", ack(2, 0));
+    assert!(receipt.response.is_error);
+    assert_eq!(record(&bridge, 2).status, BurnStatus::Pending);
+
+    let receipt = bridge
+        .chain
+        .write(&bridge.relayer, "crosschain", "ack_burn", ack(1, 0));
+    assert_eq!(events_with_topic(&receipt, "burn_acked").len(), 1);
+    let acked = record(&bridge, 1);
+    assert_eq!(acked.status, BurnStatus::Completed);
+    assert_eq!(acked.ckb_tx_hash, Some(ckb::tx_hash(&unlocks[0])));
+
+    // the ack can't be replayed, nor its tx used for another burn
+    for nonce in &[1, 2] {
+        let receipt = bridge
+            .chain
+            .exec(&bridge.relayer, "crosschain", "ack_burn", ack(*nonce, 0));
+        assert!(receipt.response.is_error);
+    }
+    assert_eq!(record(&bridge, 2).status, BurnStatus::Pending);
+}
+
+#[test]
+fn test_withdrawal_fee() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let treasury = Account::new(7);
+    let native_asset_id = Hash::digest(Bytes::from("native"));
+    let native_balance = |bridge: &Bridge, account: &Account| {
+        let resp: GetBalanceResponse = bridge.chain.read(
+            "asset",
+            "get_balance",
+            json!({ "asset_id": native_asset_id, "user": account.address }),
+        );
+        resp.balance
+    };
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &bob.address, 1),
+    ];
+    bridge.relay(1, &txs);
+    bridge.chain.write(
+        &bridge.admin,
+        "asset",
+        "transfer",
+        json!({ "asset_id": native_asset_id, "to": alice.address, "value": "100" }),
+    );
+
+    // a fee needs somewhere to go first
+    let set_fee = json!({ "asset_id": bridge.asset_id, "fee": "5" });
+    let receipt = bridge.chain.exec(
+        &bridge.admin,
+        "crosschain",
+        "set_withdrawal_fee",
+        set_fee.clone(),
+    );
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "withdrawal_fee_config": {
+            "native_asset_id": native_asset_id,
+            "treasury": treasury.address,
+        }}),
+    );
+    bridge
+        .chain
+        .write(&bridge.admin, "crosschain", "set_withdrawal_fee", set_fee);
+    let estimate: WithdrawalFeeEstimate = bridge.chain.read(
+        "crosschain",
+        "estimate_withdrawal_fee",
+        json!({ "asset_id": bridge.asset_id }),
+    );
+    assert_eq!(estimate.fee, 5);
+    assert!(!estimate.waived);
+    assert_eq!(estimate.treasury, Some(treasury.address.clone()));
+
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let asset_id = bridge.asset_id.clone();
+    let burn =
+        |amount: &str| json!({ "token_id": asset_id, "receiver": ckb_receiver, "amount": amount });
+    let receipt = bridge
+        .chain
+        .write(&alice, "crosschain", "burn_sudt", burn("600"));
+    let fees = events_with_topic(&receipt, "withdrawal_fee");
+    assert_eq!(fees.len(), 1);
+    assert_eq!(fees[0]["fee"], "5");
+    assert_eq!(native_balance(&bridge, &alice), 95);
+    assert_eq!(native_balance(&bridge, &treasury), 5);
+    assert_eq!(bridge.balance(&alice), 390);
+
+    // without the native asset to pay the fee nothing is burned
+    let receipt = bridge
+        .chain
+        .exec(&bob, "crosschain", "burn_sudt", burn("600"));
+    assert!(receipt.response.is_error);
+    assert_eq!(bridge.balance(&bob), 990);
+
+    // whitelisted addresses burn for free
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "add_fee_waiver",
+        json!({ "address": bob.address }),
+    );
+    bridge
+        .chain
+        .write(&bob, "crosschain", "burn_sudt", burn("600"));
+    assert_eq!(bridge.balance(&bob), 390);
+    assert_eq!(native_balance(&bridge, &treasury), 5);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_failed_burn_leaves_state() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+
+    // the asset service refuses the burn, which reverts the whole call
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let receipt = bridge.chain.exec(
+        &alice,
+        "crosschain",
+        "burn_sudt",
+        json!({ "token_id": bridge.asset_id, "receiver": ckb_receiver, "amount": "5000" }),
+    );
+    assert!(receipt.response.is_error);
+
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.stats().total_burns, 0);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_failed_message_reverts_batch() {
+    let limits = json!({ "max_batch_messages": 0, "block_mint_budget": "1000" });
+    let mut bridge = bridge_with(json!({ "bridge_limits": limits }));
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 2000, &bob.address, 1),
+    ];
+    let (header, messages) = ckb::block(1, &txs);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+
+    // alice's deposit is minted on the asset service before bob's exceeds
+    // the block budget, and the failure takes her mint back with it
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "submit_messages",
+        json!({ "height": 1, "messages": messages }),
+    );
+    assert!(receipt.response.is_error);
+    assert_eq!(bridge.balance(&alice), 0);
+    assert_eq!(bridge.balance(&bridge.relayer), 0);
+    assert_eq!(bridge.supply(), 0);
+    assert_eq!(bridge.stats().total_mints, 0);
+
+    // nor was her proof marked used, the batch goes through once admitted
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "bridge_limits": { "max_batch_messages": 0, "block_mint_budget": "0" } }),
+    );
+    bridge.submit(1, messages);
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bob), 1980);
+    assert_eq!(bridge.supply(), 3000);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_message_batch() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 2000, &bob.address, 1),
+    ];
+    let (header, messages, proof) = ckb::batch(1, &txs);
+    let batch = |proof: &Value| json!({ "height": 1, "messages": messages, "proof": proof });
+
+    // the header of a batch must be stored already
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "submit_message_batch",
+        batch(&proof),
+    );
+    assert!(receipt.response.is_error);
+
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+    // a proof that doesn't hold rejects every message of the batch
+    let mut swapped = proof.clone();
+    swapped["indices"] = json!([proof["indices"][1], proof["indices"][0]]);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_message_batch",
+        batch(&swapped),
+    );
+    assert_eq!(bridge.stats().rejected_messages["invalid_proof"], 2);
+    assert_eq!(bridge.supply(), 0);
+
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_message_batch",
+        batch(&proof),
+    );
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bob), 1980);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_mint_over_remaining_budget() {
+    let limits = json!({ "max_batch_messages": 0, "block_mint_budget": "1500" });
+    let mut bridge = bridge_with(json!({ "bridge_limits": limits }));
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &bob.address, 1),
+    ];
+    let (header, messages) = ckb::block(1, &txs);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+
+    // the block is still under its budget after alice's mint, but bob's
+    // alone would take it over
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "submit_messages",
+        json!({ "height": 1, "messages": messages }),
+    );
+    assert!(receipt.response.is_error);
+    assert_eq!(bridge.supply(), 0);
+
+    // each fits a block of its own
+    bridge.submit(1, vec![messages[0].clone()]);
+    bridge.submit(1, vec![messages[1].clone()]);
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bob), 990);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_mint_callback() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let register = json!({ "address": alice.address, "service": "shop" });
+
+    let receipt = bridge.chain.exec(
+        &alice,
+        "crosschain",
+        "register_mint_callback",
+        register.clone(),
+    );
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "register_mint_callback",
+        register,
+    );
+    let service: String = bridge.chain.read(
+        "crosschain",
+        "get_mint_callback",
+        json!({ "address": alice.address }),
+    );
+    assert_eq!(service, "shop");
+
+    // the shop hears of what alice was credited, bob's deposit calls nothing
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &bob.address, 1),
+    ];
+    let receipt = bridge.relay(1, &txs);
+    let calls: Vec<OnCrossMintPayload> = events_of(&receipt, "shop");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].asset_id, bridge.asset_id);
+    assert_eq!(calls[0].receiver, alice.address);
+    assert_eq!(calls[0].amount, 990);
+
+    // a failing callback leaves the mint, the asset service has no
+    // on_cross_mint
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "register_mint_callback",
+        json!({ "address": bob.address, "service": "asset" }),
+    );
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &bob.address, 2)];
+    let receipt = bridge.relay(2, &txs);
+    let failed = events_with_topic(&receipt, "mint_callback_failed");
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0]["service"], "asset");
+    assert_eq!(bridge.balance(&bob), 1980);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_supply_cap() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+    let asset_id = bridge.asset_id.clone();
+    let set_cap = |cap: &str| json!({ "asset_id": asset_id, "cap": cap });
+    let burn = json!({
+        "token_id": bridge.asset_id,
+        "receiver": format!("0x0100{}", hex::encode([7u8; 20])),
+        "amount": "100",
+    });
+
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "set_supply_cap", set_cap("1500"));
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_supply_cap",
+        set_cap("1500"),
+    );
+
+    // a deposit crossing the cap is rejected and pauses the asset
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &bob.address, 1)];
+    let (header, messages) = ckb::block(2, &txs);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+    let receipt = bridge.submit(2, messages.clone());
+    assert_eq!(events_with_topic(&receipt, "bridge_paused").len(), 1);
+    let rejected = events_with_topic(&receipt, "message_rejected");
+    assert_eq!(rejected[0]["reason"], "bridge_paused");
+    assert_eq!(bridge.balance(&bob), 0);
+
+    // nothing is burned while paused
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "burn_sudt", burn.clone());
+    assert!(receipt.response.is_error);
+
+    let resume = json!({ "asset_id": bridge.asset_id });
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "resume_bridge", resume.clone());
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_supply_cap",
+        set_cap("3000"),
+    );
+    bridge
+        .chain
+        .write(&bridge.admin, "crosschain", "resume_bridge", resume);
+
+    // the rejected message was left to be submitted again
+    bridge.submit(2, messages);
+    assert_eq!(bridge.balance(&bob), 990);
+    bridge.chain.write(&alice, "crosschain", "burn_sudt", burn);
+    assert_eq!(bridge.balance(&alice), 890);
+    bridge.assert_backed(&[&alice, &bob, &bridge.relayer]);
+}
+
+#[test]
+fn test_batch_reads_its_own_mints() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    // each mint is a call into a fresh asset service instance, which must
+    // read the balance the previous one wrote in the same tx
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 2000, &alice.address, 1),
+    ];
+    let receipt = bridge.relay(1, &txs);
+    let mints: Vec<MintEvent> = events_of(&receipt, "asset");
+    assert_eq!(mints.len(), 4);
+
+    assert_eq!(bridge.balance(&alice), 2970);
+    assert_eq!(bridge.balance(&bridge.relayer), 30);
+    assert_eq!(bridge.supply(), 3000);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_burn_epoch_summary() {
+    // heights 4 to 7 make up epoch 1, the deposit lands at height 4
+    let mut bridge = bridge_with(json!({ "burn_epoch_length": 4 }));
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+    let asset_id = bridge.asset_id.clone();
+    let burn = |receiver: &str, amount: &str| json!({ "token_id": asset_id, "receiver": receiver, "amount": amount });
+    let secp256k1 = format!("0x0100{}", hex::encode([7u8; 20]));
+    let multisig = format!("0x0101{}", hex::encode([8u8; 20]));
+
+    for payload in [burn(&secp256k1, "100"), burn(&multisig, "200")].iter() {
+        bridge
+            .chain
+            .write(&alice, "crosschain", "burn_sudt", payload);
+    }
+    bridge
+        .chain
+        .write(&alice, "crosschain", "cancel_burn", json!({ "nonce": 2 }));
+    let summary = bridge.epoch_summary(1);
+    assert_eq!((summary.start_height, summary.end_height), (4, 7));
+    assert!(!summary.closed);
+    assert_eq!(summary.burns, 1);
+    assert_eq!((summary.first_nonce, summary.last_nonce), (1, 2));
+    assert_eq!(summary.assets.len(), 1);
+    assert_eq!(summary.assets[0].asset_id, bridge.asset_id);
+    assert_eq!(summary.assets[0].sudt_amount, 100);
+    let locks: Vec<(u64, u128)> = summary.assets[0]
+        .locks
+        .iter()
+        .map(|l| (l.burns, l.sudt_amount))
+        .collect();
+    assert_eq!(locks, vec![(1, 100), (0, 0)]);
+
+    // the next burn opens epoch 2 and closes epoch 1
+    bridge
+        .chain
+        .write(&alice, "crosschain", "burn_sudt", burn(&secp256k1, "300"));
+    let summary = bridge.epoch_summary(1);
+    assert!(summary.closed);
+    assert_eq!(summary.burns, 1);
+    let summary = bridge.epoch_summary(2);
+    assert_eq!((summary.burns, summary.first_nonce), (1, 3));
+    assert_eq!(summary.assets[0].amount, 300);
+}
+
+/// Leaves of a block's event digest, the bridge mints and burns of its
+/// receipt.
+fn digest_leaves(receipt: &Receipt) -> Vec<Hash> {
+    receipt
+        .events
+        .iter()
+        .filter(|e| e.service == "crosschain")
+        .filter(|e| {
+            let topic = serde_json::from_str::<Value>(&e.data).unwrap()["topic"].clone();
+            topic == "mint_asset" || topic == "burn_asset"
+        })
+        .map(|e| ckb_blake2b(e.data.as_bytes()).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_event_digests() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![
+        ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0),
+        ckb::deposit_tx(&bridge.sudt_args, 2000, &alice.address, 1),
+    ];
+    let deposit = bridge.relay(1, &txs);
+    let deposit_height = bridge.chain.height();
+
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let burn = json!({ "token_id": bridge.asset_id, "receiver": ckb_receiver, "amount": "600" });
+    let withdrawal = bridge
+        .chain
+        .write(&alice, "crosschain", "burn_sudt", burn.clone());
+    let burn_height = bridge.chain.height();
+    // a burn over the balance reverts, its block has no digest
+    let mut failed = burn;
+    failed["amount"] = json!("100000");
+    assert!(
+        bridge
+            .chain
+            .exec(&alice, "crosschain", "burn_sudt", failed)
+            .response
+            .is_error
+    );
+
+    let digests: EventDigests = bridge.chain.read(
+        "crosschain",
+        "get_event_digest",
+        json!({ "start": 0, "end": bridge.chain.height() }),
+    );
+    assert_eq!(digests.next, None);
+    assert_eq!(digests.digests.len(), 2);
+
+    let minted = &digests.digests[0];
+    assert_eq!(minted.height, deposit_height);
+    // the relay fee is minted apart from each deposit
+    assert_eq!((minted.mints, minted.burns), (4, 0));
+    assert_eq!(
+        minted.root,
+        cbmt_build_root(&digest_leaves(&deposit)).unwrap()
+    );
+
+    let burned = &digests.digests[1];
+    assert_eq!(burned.height, burn_height);
+    assert_eq!((burned.mints, burned.burns), (0, 1));
+    assert_eq!(
+        burned.root,
+        cbmt_build_root(&digest_leaves(&withdrawal)).unwrap()
+    );
+
+    let digests: EventDigests = bridge.chain.read(
+        "crosschain",
+        "get_event_digest",
+        json!({ "start": burn_height, "end": burn_height }),
+    );
+    assert_eq!(digests.digests, vec![burned.clone()]);
+}
+
+#[test]
+fn test_burn_batches() {
+    let mut bridge = bridge_with(json!({ "burn_batch_window": 2 }));
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let burn = json!({ "token_id": bridge.asset_id, "receiver": ckb_receiver, "amount": "100" });
+
+    // both burns land in the window of batch 1, the hook of its last block
+    // seals it
+    let mut leaves = vec![];
+    for _ in 0..2 {
+        let receipt = bridge
+            .chain
+            .write(&alice, "crosschain", "burn_sudt", burn.clone());
+        leaves.extend(digest_leaves(&receipt));
+    }
+    let batch: BurnBatch = bridge
+        .chain
+        .read("crosschain", "get_burn_batch", json!({ "batch": 1 }));
+    assert!(batch.sealed);
+    assert_eq!((batch.first_nonce, batch.last_nonce), (1, 2));
+    assert_eq!(batch.leaves, leaves);
+    assert_eq!(batch.root, cbmt_build_root(&leaves).unwrap());
+
+    // batched burns are final
+    let cancel = bridge
+        .chain
+        .exec(&alice, "crosschain", "cancel_burn", json!({ "nonce": 1 }));
+    assert!(cancel.response.is_error);
+
+    // the next burn announces batch 1 and opens batch 2
+    let receipt = bridge.chain.write(&alice, "crosschain", "burn_sudt", burn);
+    let announced = events_with_topic(&receipt, "burn_batch");
+    assert_eq!(announced.len(), 1);
+    assert_eq!(announced[0]["batch"], 1);
+    assert_eq!(announced[0]["burns"], 2);
+    assert_eq!(announced[0]["root"], json!(batch.root));
+    let batch: BurnBatch = bridge
+        .chain
+        .read("crosschain", "get_burn_batch", json!({ "batch": 2 }));
+    assert!(!batch.sealed);
+    assert_eq!(batch.first_nonce, 3);
+}
+
+#[test]
+fn test_burn_batch_announced_without_next_burn() {
+    let mut bridge = bridge_with(json!({ "burn_batch_window": 2 }));
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    bridge.relay(1, &txs);
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let burn = json!({ "token_id": bridge.asset_id, "receiver": ckb_receiver, "amount": "100" });
+    for _ in 0..2 {
+        bridge
+            .chain
+            .write(&alice, "crosschain", "burn_sudt", burn.clone());
+    }
+
+    // no burn follows the sealed batch, a relayer announces it
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "announce_burn_batches", json!(null));
+    assert!(receipt.response.is_error);
+    let receipt = bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "announce_burn_batches",
+        json!(null),
+    );
+    let announced = events_with_topic(&receipt, "burn_batch");
+    assert_eq!(announced.len(), 1);
+    assert_eq!(announced[0]["batch"], 1);
+    assert_eq!(announced[0]["burns"], 2);
+
+    // and only once, nor is it announced again by the next burn
+    let receipt = bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "announce_burn_batches",
+        json!(null),
+    );
+    assert!(events_with_topic(&receipt, "burn_batch").is_empty());
+    let receipt = bridge.chain.write(&alice, "crosschain", "burn_sudt", burn);
+    assert!(events_with_topic(&receipt, "burn_batch").is_empty());
+}
+
+#[test]
+fn test_chain_binding() {
+    let ckb_chain = Hash::digest(Bytes::from("ckb testnet"));
+    let muta_chain = Hash::digest(Bytes::from("muta testnet"));
+    let mut bridge = bridge_with(json!({ "chain_binding": {
+        "source_chain_id": ckb_chain,
+        "dest_chain_id": muta_chain,
+    }}));
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let (header, messages) = ckb::block(1, &txs);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+
+    // messages of another network, or of none, are refused
+    for chain_id in &[json!(null), json!(Hash::digest(Bytes::from("ckb mainnet")))] {
+        let payload = json!({ "height": 1, "messages": messages, "source_chain_id": chain_id });
+        let receipt = bridge
+            .chain
+            .exec(&bridge.relayer, "crosschain", "submit_messages", payload);
+        assert!(receipt.response.is_error);
+    }
+    let receipt = bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_messages",
+        json!({ "height": 1, "messages": messages, "source_chain_id": ckb_chain }),
+    );
+    let mints = events_with_topic(&receipt, "mint_asset");
+    assert_eq!(mints.len(), 2);
+    assert!(mints
+        .iter()
+        .all(|m| m["chain"]["source_chain_id"] == json!(ckb_chain)));
+    assert!(mints
+        .iter()
+        .all(|m| m["chain"]["dest_chain_id"] == json!(muta_chain)));
+
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let mut burn =
+        json!({ "token_id": bridge.asset_id, "receiver": ckb_receiver, "amount": "100" });
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "burn_sudt", burn.clone());
+    assert!(receipt.response.is_error);
+    burn["source_chain_id"] = json!(ckb_chain);
+    let receipt = bridge.chain.write(&alice, "crosschain", "burn_sudt", burn);
+    let burns = events_with_topic(&receipt, "burn_asset");
+    assert_eq!(burns[0]["chain"]["dest_chain_id"], json!(muta_chain));
+    assert_eq!(bridge.balance(&alice), 890);
+}
+
+#[test]
+fn test_deposit_tags() {
+    let mut bridge = bridge();
+    let (alice, bob) = (Account::new(3), Account::new(4));
+    let tag_of = |bridge: &Bridge, account: &Account| -> DepositTag {
+        bridge.chain.read(
+            "crosschain",
+            "get_deposit_tag",
+            json!({ "address": account.address }),
+        )
+    };
+    let receipt = bridge
+        .chain
+        .write(&alice, "crosschain", "register_deposit_tag", json!(null));
+    let tag = tag_of(&bridge, &alice);
+    assert!(tag.registered);
+    assert_eq!(
+        events_with_topic(&receipt, "register_deposit_tag")[0]["tag"],
+        json!(tag.tag)
+    );
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "register_deposit_tag", json!(null));
+    assert!(receipt.response.is_error);
+
+    // a deposit naming alice's tag is minted to her, one naming bob's
+    // unregistered tag goes to the recovery account
+    let tagged = |account: &Account, nonce| {
+        let mut tx = ckb::deposit_tx(&bridge.sudt_args, 1000, &account.address, nonce);
+        tx.witnesses = vec![tag_of(&bridge, account).witness];
+        tx
+    };
+    let txs = [tagged(&alice, 0), tagged(&bob, 1)];
+    bridge.relay(1, &txs);
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bob), 0);
+    assert!(!tag_of(&bridge, &bob).registered);
+}
+
+#[test]
+fn test_migration_status() {
+    let mut bridge = bridge();
+    let status: MigrationStatus =
+        bridge
+            .chain
+            .read("crosschain", "get_migration_status", json!(null));
+    assert_eq!(status.schema_version, SCHEMA_VERSION);
+    assert!(status.pending.is_empty());
+
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "migrate",
+        json!({ "limit": 10 }),
+    );
+    assert!(receipt.response.is_error);
+    let receipt = bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "migrate",
+        json!({ "limit": 10 }),
+    );
+    let event = &events_with_topic(&receipt, "migrate")[0];
+    assert_eq!(event["from_version"], json!(SCHEMA_VERSION));
+    assert_eq!(event["version"], json!(SCHEMA_VERSION));
+}
+
+#[test]
+fn test_cross_call_targets() {
+    let mut bridge = bridge();
+    let sender = Hash::digest(Bytes::from("governance"));
+    let target =
+        |service: &str, methods: &[&str]| json!({ "service": service, "methods": methods });
+
+    // calls into the bridge's own services would carry its rights
+    for service in &["asset", "crosschain"] {
+        let receipt = bridge.chain.exec(
+            &bridge.admin,
+            "crosschain",
+            "register_call_target",
+            target(service, &["mint_token"]),
+        );
+        assert!(receipt.response.is_error);
+    }
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "register_call_target",
+        target("oracle", &["set_price"]),
+    );
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "register_call_target",
+        target("oracle", &["set_price"]),
+    );
+    let registered: Value = bridge.chain.read(
+        "crosschain",
+        "get_call_target",
+        json!({ "service": "oracle" }),
+    );
+    assert_eq!(registered, target("oracle", &["set_price"]));
+
+    // a call of a method left out is rejected and stays unspent
+    let txs = [ckb::call_tx(&sender, "oracle", "set_admin", "{}", 0)];
+    let receipt = bridge.relay(1, &txs);
+    assert!(events_with_topic(&receipt, "cross_call").is_empty());
+    assert_eq!(bridge.stats().rejected_messages["call_not_allowed"], 1);
+
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "unregister_call_target",
+        json!({ "service": "oracle" }),
+    );
+    let registered: Value = bridge.chain.read(
+        "crosschain",
+        "get_call_target",
+        json!({ "service": "oracle" }),
+    );
+    assert_eq!(registered, target("oracle", &[]));
+}
+
+#[test]
+fn test_outbound_senders() {
+    let mut bridge = bridge();
+    let message = json!({
+        "target_script": {
+            "code_hash": Hash::digest(Bytes::from("ckb contract")),
+            "hash_type": "type",
+            "args": "0x01",
+        },
+        "payload": "0x68656c6c6f",
+    });
+
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "add_outbound_sender",
+        json!({ "service": "oracle" }),
+    );
+    let sender: Value = bridge.chain.read(
+        "crosschain",
+        "get_outbound_sender",
+        json!({ "service": "oracle" }),
+    );
+    assert_eq!(sender, json!({ "service": "oracle", "allowed": true }));
+
+    // only services name themselves, an account can't send even as admin
+    let receipt = bridge
+        .chain
+        .exec(&bridge.admin, "crosschain", "send_message_to_ckb", message);
+    assert!(receipt.response.is_error);
+
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "remove_outbound_sender",
+        json!({ "service": "oracle" }),
+    );
+    let sender: Value = bridge.chain.read(
+        "crosschain",
+        "get_outbound_sender",
+        json!({ "service": "oracle" }),
+    );
+    assert_eq!(sender["allowed"], false);
+}
+
+#[test]
+fn test_params() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let params: Params = bridge.chain.read("crosschain", "get_params", json!(null));
+    assert_eq!(params.payout_cooldown, PAYOUT_COOLDOWN);
+
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "set_params",
+        json!({ "payout_cooldown": 0 }),
+    );
+    assert!(receipt.response.is_error);
+    let receipt = bridge.chain.exec(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "max_pending_messages": 0 }),
+    );
+    assert!(receipt.response.is_error);
+    let receipt = bridge.chain.exec(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "fee_schedule": { "tiers": [] } }),
+    );
+    assert!(receipt.response.is_error);
+
+    // a payout address may move right away without a cooldown
+    let receipt = bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "payout_cooldown": 0 }),
+    );
+    let changed = events_with_topic(&receipt, "set_params");
+    assert_eq!(changed[0]["changed"], json!(["payout_cooldown"]));
+    for payout in &[Account::new(6), Account::new(7)] {
+        bridge.chain.write(
+            &bridge.relayer,
+            "crosschain",
+            "set_payout_address",
+            json!({ "payout": payout.address }),
+        );
+    }
+
+    // deposits of another sudt than the one the parameters name are rejected
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "sudt_code_hash": Hash::digest(Bytes::from("other sudt")) }),
+    );
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let receipt = bridge.relay(1, &txs);
+    let rejected = events_with_topic(&receipt, "message_rejected");
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(bridge.balance(&alice), 0);
+}
+
+#[test]
+fn test_genesis_recovery_account() {
+    let vault = Account::new(6);
+    let mut bridge = bridge_with(json!({ "recovery_account": vault.address }));
+    let alice = Account::new(3);
+    let bob = Account::new(4);
+    let params: Params = bridge.chain.read("crosschain", "get_params", json!(null));
+    assert_eq!(params.recovery_account, vault.address);
+
+    // bob's unregistered tag sends his deposit to the vault, which the admin
+    // moves it out of
+    let tag: DepositTag = bridge.chain.read(
+        "crosschain",
+        "get_deposit_tag",
+        json!({ "address": bob.address }),
+    );
+    let mut tx = ckb::deposit_tx(&bridge.sudt_args, 1000, &bob.address, 0);
+    tx.witnesses = vec![tag.witness];
+    let receipt = bridge.relay(1, &[tx]);
+    assert_eq!(bridge.balance(&vault), 990);
+    let recovered = &events_with_topic(&receipt, "deposit_recovered")[0];
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "reassign_deposit",
+        json!({ "tx_hash": recovered["tx_hash"], "receiver": alice.address }),
+    );
+    assert_eq!(bridge.balance(&vault), 0);
+    assert_eq!(bridge.balance(&alice), 990);
+}
+
+#[test]
+fn test_stage_headers() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let header_relayer = Account::new(5);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let (header, messages) = ckb::block(1, &txs);
+    let stage = json!({ "headers": [header] });
+
+    // only the header relayer stages headers, not the message relayers
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "stage_headers",
+        stage.clone(),
+    );
+    assert!(receipt.response.is_error);
+
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_header_relayer",
+        json!({ "address": header_relayer.address }),
+    );
+    // a header that doesn't parse stages none of the call
+    let mut malformed = ckb::child(&header, &Hash::digest(Bytes::from("empty")));
+    malformed["compact_target"] = json!("0x1ffffffff");
+    let receipt = bridge.chain.exec(
+        &header_relayer,
+        "crosschain",
+        "stage_headers",
+        json!({ "headers": [header, malformed] }),
+    );
+    assert!(receipt.response.is_error);
+    assert_eq!(bridge.stats().headers_stored, 0);
+
+    // staged headers are stored as the block ends, and prove its messages
+    bridge
+        .chain
+        .write(&header_relayer, "crosschain", "stage_headers", stage);
+    assert_eq!(bridge.stats().headers_stored, 1);
+    let stored: GetHeaderResponse =
+        bridge
+            .chain
+            .read("crosschain", "get_header", json!({ "height": 1 }));
+    assert_eq!(stored.header.number, 1);
+    bridge.submit(1, messages);
+    assert_eq!(bridge.balance(&alice), 990);
+}
+
+#[test]
+fn test_staged_header_limits() {
+    let mut bridge = bridge();
+    let header_relayer = Account::new(5);
+    let empty = Hash::digest(Bytes::from("empty"));
+    let first = ckb::header(1, &empty);
+    let second = ckb::child(&first, &empty);
+    let third = ckb::child(&second, &empty);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_header_relayer",
+        json!({ "address": header_relayer.address }),
+    );
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_params",
+        json!({ "max_staged_headers": 2, "staged_headers_per_block": 1 }),
+    );
+
+    // no more headers wait than the limit
+    let receipt = bridge.chain.exec(
+        &header_relayer,
+        "crosschain",
+        "stage_headers",
+        json!({ "headers": [first, second, third] }),
+    );
+    assert!(receipt.response.is_error);
+
+    // each block stores one of those waiting, a failing call's included
+    bridge.chain.write(
+        &header_relayer,
+        "crosschain",
+        "stage_headers",
+        json!({ "headers": [first, second] }),
+    );
+    assert_eq!(bridge.stats().headers_stored, 1);
+    let receipt = bridge.chain.exec(
+        &header_relayer,
+        "crosschain",
+        "stage_headers",
+        json!({ "headers": [third, third] }),
+    );
+    assert!(receipt.response.is_error);
+    assert_eq!(bridge.stats().headers_stored, 2);
+    bridge.chain.write(
+        &header_relayer,
+        "crosschain",
+        "stage_headers",
+        json!({ "headers": [third] }),
+    );
+    assert_eq!(bridge.stats().headers_stored, 3);
+}
+
+#[test]
+fn test_process_pending() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let header_relayer = Account::new(5);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let (header, messages) = ckb::block(1, &txs);
+    let process = json!({ "height": 1 });
+
+    // messages ahead of their header wait for it
+    let receipt = bridge.submit(1, messages);
+    assert_eq!(events_with_topic(&receipt, "messages_pending").len(), 1);
+    assert_eq!(bridge.balance(&alice), 0);
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "process_pending", process.clone());
+    assert!(receipt.response.is_error);
+
+    // a header stored as the block ends mints none of them
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_header_relayer",
+        json!({ "address": header_relayer.address }),
+    );
+    bridge.chain.write(
+        &header_relayer,
+        "crosschain",
+        "stage_headers",
+        json!({ "headers": [header] }),
+    );
+    assert_eq!(bridge.stats().headers_stored, 1);
+    assert_eq!(bridge.balance(&alice), 0);
+
+    // anyone runs them, the relayer who queued them earning the fee
+    bridge
+        .chain
+        .write(&alice, "crosschain", "process_pending", process.clone());
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bridge.relayer), 10);
+    bridge
+        .chain
+        .write(&alice, "crosschain", "process_pending", process);
+    assert_eq!(bridge.balance(&alice), 990);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_pending_mint_failure() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let (header, messages) = ckb::block(1, &txs);
+    let submit = json!({ "height": 1, "messages": messages });
+
+    // only relayers queue messages
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "submit_messages", submit.clone());
+    assert!(receipt.response.is_error);
+    bridge.submit(1, messages.clone());
+
+    // a mint failing as the header comes rejects the message, the header
+    // being stored all the same
+    let minters = |services: Value| json!({ "minters": services });
+    bridge
+        .chain
+        .write(&bridge.admin, "asset", "set_params", minters(json!([])));
+    let receipt = bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+    let rejected = events_with_topic(&receipt, "message_rejected");
+    assert_eq!(rejected[0]["reason"], "mint_failed");
+    assert_eq!(bridge.stats().headers_stored, 1);
+    assert_eq!(bridge.stats().total_mints, 0);
+    assert_eq!(bridge.stats().rejected_messages["mint_failed"], 1);
+    assert_eq!(bridge.balance(&alice), 0);
+
+    // and leaves it to be submitted again
+    bridge.chain.write(
+        &bridge.admin,
+        "asset",
+        "set_params",
+        minters(json!(["crosschain"])),
+    );
+    bridge.submit(1, messages);
+    assert_eq!(bridge.balance(&alice), 990);
+    bridge.assert_backed(&[&alice, &bridge.relayer]);
+}
+
+#[test]
+fn test_mmr_messages() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let (first, messages) = ckb::block(1, &txs);
+    let second = ckb::child(&first, &Hash::digest(Bytes::from("empty")));
+    let (leaf, sibling) = (ckb::hash(&first), ckb::hash(&second));
+    let root = merge(&leaf, &sibling).unwrap();
+    let update = json!({ "headers": [first, second], "root": root });
+
+    // only relayers extend the range
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "update_mmr", update.clone());
+    assert!(receipt.response.is_error);
+    // a root other than the one of the headers stores none of them
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "update_mmr",
+        json!({ "headers": [first, second], "root": leaf }),
+    );
+    assert!(receipt.response.is_error);
+    let range: GetMmrResponse = bridge.chain.read("crosschain", "get_mmr", json!(null));
+    assert_eq!((range.leaves, range.root), (0, None));
+
+    let receipt = bridge
+        .chain
+        .write(&bridge.relayer, "crosschain", "update_mmr", update);
+    assert_eq!(events_with_topic(&receipt, "mmr_updated").len(), 1);
+    let range: GetMmrResponse = bridge.chain.read("crosschain", "get_mmr", json!(null));
+    assert_eq!((range.start, range.leaves), (1, 2));
+    assert_eq!(range.root, Some(root.clone()));
+
+    // a deposit is proven against a header of the range, which comes with it
+    let submit = |siblings: Vec<Hash>| {
+        json!({ "messages": [{
+            "header": first,
+            "mmr_proof": { "leaves": 2, "siblings": siblings, "peaks": [root] },
+            "message": ckb::unpacked(&messages[0]),
+        }] })
+    };
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_mmr_messages",
+        submit(vec![leaf.clone()]),
+    );
+    assert_eq!(bridge.stats().rejected_messages["invalid_proof"], 1);
+    assert_eq!(bridge.balance(&alice), 0);
+
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_mmr_messages",
+        submit(vec![sibling]),
+    );
+    assert_eq!(bridge.balance(&alice), 990);
+    assert_eq!(bridge.balance(&bridge.relayer), 10);
+}
+
+#[test]
+fn test_header_conflicts() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let txs = vec![ckb::deposit_tx(&bridge.sudt_args, 1000, &alice.address, 0)];
+    let (header, messages) = ckb::block(1, &txs);
+    let forged = ckb::header(1, &Hash::digest(Bytes::from("forged")));
+    let update = |headers: Vec<&Value>| json!({ "headers": headers });
+
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        update(vec![&header]),
+    );
+    // the same header again is no conflict
+    let receipt = bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        update(vec![&header]),
+    );
+    assert!(events_with_topic(&receipt, "header_conflict").is_empty());
+
+    let receipt = bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        update(vec![&forged]),
+    );
+    let conflicts = events_with_topic(&receipt, "header_conflict");
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0]["stored_hash"], json!(ckb::hash(&header)));
+    assert_eq!(conflicts[0]["conflicting_hash"], json!(ckb::hash(&forged)));
+    let conflict: HeaderConflict =
+        bridge
+            .chain
+            .read("crosschain", "get_header_conflict", json!({ "height": 1 }));
+    assert_eq!(conflict.conflicting_hash, ckb::hash(&forged));
+
+    // messages of the disputed height wait for the resolution
+    let receipt = bridge.submit(1, messages);
+    assert!(events_with_topic(&receipt, "mint_asset").is_empty());
+    assert_eq!(bridge.balance(&alice), 0);
+
+    // both branches are one header long
+    let resolve = json!({ "height": 1 });
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "resolve_header_conflict",
+        resolve.clone(),
+    );
+    assert!(receipt.response.is_error);
+    let receipt = bridge.chain.exec(
+        &bridge.admin,
+        "crosschain",
+        "resolve_header_conflict",
+        resolve.clone(),
+    );
+    assert!(receipt.response.is_error);
+
+    // the stored branch grows and is kept
+    let next = ckb::child(&header, &Hash::digest(Bytes::from("empty")));
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        update(vec![&next]),
+    );
+    let receipt = bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "resolve_header_conflict",
+        resolve,
+    );
+    let resolved = events_with_topic(&receipt, "resolve_header_conflict");
+    assert_eq!(resolved[0]["kept_hash"], json!(ckb::hash(&header)));
+    assert_eq!(resolved[0]["branch_len"], 2);
+    assert_eq!(events_with_topic(&receipt, "mint_asset").len(), 2);
+    assert_eq!(bridge.balance(&alice), 990);
+}
+
+#[test]
+fn test_longer_conflicting_branch_replaces_headers() {
+    let mut bridge = bridge();
+    let header = ckb::header(1, &Hash::digest(Bytes::from("stored")));
+    let forged = ckb::header(1, &Hash::digest(Bytes::from("forged")));
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header, forged] }),
+    );
+
+    // a branch must start with the conflicting header and link up
+    let unlinked = ckb::header(2, &Hash::digest(Bytes::from("next")));
+    for branch in vec![vec![&header], vec![&forged, &unlinked]] {
+        let receipt = bridge.chain.exec(
+            &bridge.admin,
+            "crosschain",
+            "resolve_header_conflict",
+            json!({ "height": 1, "branch": branch }),
+        );
+        assert!(receipt.response.is_error);
+    }
+
+    let next = ckb::child(&forged, &Hash::digest(Bytes::from("next")));
+    let receipt = bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "resolve_header_conflict",
+        json!({ "height": 1, "branch": [forged, next] }),
+    );
+    let resolved = events_with_topic(&receipt, "resolve_header_conflict");
+    assert_eq!(resolved[0]["kept_hash"], json!(ckb::hash(&forged)));
+    assert_eq!(resolved[0]["dropped_hash"], json!(ckb::hash(&header)));
+
+    let stored: CkbHeader =
+        bridge
+            .chain
+            .read("crosschain", "get_header_raw", json!({ "height": 2 }));
+    assert_eq!(ckb::hash(&json!(stored)), ckb::hash(&next));
+    let receipt = bridge.chain.exec(
+        &bridge.admin,
+        "crosschain",
+        "resolve_header_conflict",
+        json!({ "height": 1 }),
+    );
+    assert!(receipt.response.is_error);
+}
+
+#[test]
+fn test_eth_bridge() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let contract = [1u8; 20];
+    let token = [2u8; 20];
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_eth_config",
+        json!({
+            "bridge_contract": format!("0x{}", hex::encode(contract)),
+            "mode": "sync_committee",
+        }),
+    );
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "register_eth_asset",
+        json!({
+            "token": format!("0x{}", hex::encode(token)),
+            "name": "Wrapped",
+            "symbol": "WR",
+            "decimals": 18,
+            "scale_factor": 1,
+        }),
+    );
+    let asset_id = eth_mirror_asset_id(&token_key(&token));
+    let balance = |chain: &Chain, account: &Account| {
+        let resp: GetBalanceResponse = chain.read(
+            "asset",
+            "get_balance",
+            json!({ "asset_id": asset_id, "user": account.address }),
+        );
+        resp.balance
+    };
+
+    let lock = eth::lock_receipt(&contract, &token, &alice.address, 1000);
+    let (first, _) = eth::block(1, &[5u8; 32], &[0xc0]);
+    let (second, proof) = eth::block(2, &eth::hash(&first), &lock);
+    let (orphan, _) = eth::block(2, &[5u8; 32], &lock);
+
+    // only relayers store headers, and a header must follow its parent
+    let receipt = bridge.chain.exec(
+        &alice,
+        "crosschain",
+        "update_eth_headers",
+        json!({ "headers": [first] }),
+    );
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_eth_headers",
+        json!({ "headers": [first] }),
+    );
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "update_eth_headers",
+        json!({ "headers": [orphan] }),
+    );
+    assert!(receipt.response.is_error);
+
+    // a receipt of a header not stored yet is rejected, not minted
+    let submit = json!({ "receipts": [proof] });
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_eth_receipts",
+        submit.clone(),
+    );
+    assert_eq!(bridge.stats().rejected_messages["invalid_proof"], 1);
+    assert_eq!(balance(&bridge.chain, &alice), 0);
+
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_eth_headers",
+        json!({ "headers": [second] }),
+    );
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_eth_receipts",
+        submit.clone(),
+    );
+    assert_eq!(balance(&bridge.chain, &alice), 990);
+    assert_eq!(balance(&bridge.chain, &bridge.relayer), 10);
+    bridge
+        .chain
+        .write(&bridge.relayer, "crosschain", "submit_eth_receipts", submit);
+    assert_eq!(bridge.stats().rejected_messages["duplicate_tx"], 1);
+    assert_eq!(balance(&bridge.chain, &alice), 990);
+
+    // a burn pays out to an ethereum address only
+    let burn =
+        |receiver: &str| json!({ "asset_id": asset_id, "receiver": receiver, "amount": "500" });
+    let receipt = bridge.chain.exec(
+        &alice,
+        "crosschain",
+        "burn_to_eth",
+        burn(&format!("0x{}", hex::encode([7u8; 32]))),
+    );
+    assert!(receipt.response.is_error);
+    assert_eq!(balance(&bridge.chain, &alice), 990);
+
+    let receiver = format!("0x{}", hex::encode([7u8; 20]));
+    let receipt = bridge
+        .chain
+        .write(&alice, "crosschain", "burn_to_eth", burn(&receiver));
+    let burns: Vec<BurnToEthEvent> = events_of(&receipt, "crosschain");
+    assert_eq!(burns.len(), 1);
+    assert_eq!(burns[0].muta_sender, alice.address);
+    assert_eq!(burns[0].token, token_key(&token));
+    assert_eq!(burns[0].token_amount, 500);
+    assert_eq!(balance(&bridge.chain, &alice), 490);
+    assert_eq!(bridge.stats().outstanding(&asset_id), 500);
+}
+
+#[test]
+fn test_muta_bridge() {
+    let mut bridge = bridge();
+    let alice = Account::new(3);
+    let validators = muta::Validators::new(1, 4);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_muta_config",
+        muta::config(),
+    );
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_muta_validator_set",
+        json!(validators.set(0, 0)),
+    );
+    let origin = Hash::digest(Bytes::from("origin"));
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "register_muta_asset",
+        json!({
+            "asset_id": origin,
+            "name": "Counterpart",
+            "symbol": "CP",
+            "decimals": 8,
+            "scale_factor": 1,
+        }),
+    );
+    let asset_id = muta_mirror_asset_id(&origin);
+    let balance = |chain: &Chain, account: &Account| {
+        let resp: GetBalanceResponse = chain.read(
+            "asset",
+            "get_balance",
+            json!({ "asset_id": asset_id, "user": account.address }),
+        );
+        resp.balance
+    };
+
+    let lock = muta::lock_receipt(&origin, &alice.address, 1000, 0);
+    let genesis = Hash::digest(Bytes::from("genesis"));
+    let first = muta::header(1, &genesis, vec![muta::receipt_root(&lock)], &validators, 3);
+    let second = muta::header(2, &muta::hash(&first), vec![], &validators, 3);
+    let unsigned = muta::header(2, &muta::hash(&first), vec![], &validators, 2);
+
+    // anyone may submit headers, but only with their parent signed by a
+    // quorum of the validators
+    let receipt = bridge.chain.exec(
+        &alice,
+        "crosschain",
+        "update_muta_headers",
+        json!({ "headers": [first, unsigned] }),
+    );
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &alice,
+        "crosschain",
+        "update_muta_headers",
+        json!({ "headers": [first] }),
+    );
+
+    // the receipts of a header wait for the child confirming it
+    let submit = json!({ "receipts": [muta::receipt_proof(1, &lock)] });
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_muta_receipts",
+        submit.clone(),
+    );
+    assert_eq!(bridge.stats().rejected_messages["invalid_proof"], 1);
+    assert_eq!(balance(&bridge.chain, &alice), 0);
+
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_headers",
+        json!({ "headers": [second] }),
+    );
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "submit_muta_receipts",
+        submit,
+    );
+    assert_eq!(balance(&bridge.chain, &alice), 990);
+    assert_eq!(balance(&bridge.chain, &bridge.relayer), 10);
+
+    // only the mirror assets of the counterpart chain burn back to it
+    let burn = |asset_id: &Hash| json!({ "asset_id": asset_id, "receiver": alice.address, "amount": "500" });
+    let receipt = bridge
+        .chain
+        .exec(&alice, "crosschain", "burn_to_muta", burn(&bridge.asset_id));
+    assert!(receipt.response.is_error);
+
+    let receipt = bridge
+        .chain
+        .write(&alice, "crosschain", "burn_to_muta", burn(&asset_id));
+    let burns: Vec<BurnToMutaEvent> = events_of(&receipt, "crosschain");
+    assert_eq!(burns.len(), 1);
+    assert_eq!(burns[0].sender, alice.address);
+    assert_eq!(burns[0].origin_asset_id, origin);
+    assert_eq!(burns[0].origin_amount, 500);
+    assert_eq!(balance(&bridge.chain, &alice), 490);
+    assert_eq!(bridge.stats().outstanding(&asset_id), 500);
+}
+
+#[test]
+fn test_muta_validator_update() {
+    let mut bridge = bridge();
+    let current = muta::Validators::new(1, 4);
+    let next = muta::Validators::new(11, 3);
+    let next_set = next.set(1, 2);
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_muta_config",
+        muta::config(),
+    );
+    bridge.chain.write(
+        &bridge.admin,
+        "crosschain",
+        "set_muta_validator_set",
+        json!(current.set(0, 0)),
+    );
+
+    let genesis = Hash::digest(Bytes::from("genesis"));
+    let first = muta::announcing(1, &genesis, &current, 3, &next_set);
+    let second = muta::header(2, &muta::hash(&first), vec![], &current, 3);
+    let update_headers = |headers: Vec<&Value>| json!({ "headers": headers });
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_headers",
+        update_headers(vec![&first]),
+    );
+
+    // the announcing header must be confirmed by its child first
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_validators",
+        current.update(&next_set, 3),
+    );
+    assert!(receipt.response.is_error);
+
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_headers",
+        update_headers(vec![&second]),
+    );
+    // and the update signed by a quorum of the current set
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_validators",
+        current.update(&next_set, 2),
+    );
+    assert!(receipt.response.is_error);
+    // to the set the header announced
+    let other = muta::Validators::new(21, 3);
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_validators",
+        current.update(&other.set(1, 2), 3),
+    );
+    assert!(receipt.response.is_error);
+
+    let receipt = bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_validators",
+        current.update(&next_set, 3),
+    );
+    assert_eq!(events_with_topic(&receipt, "muta_validator_set").len(), 1);
+    let stored: MutaValidatorSet = bridge.chain.read(
+        "crosschain",
+        "get_muta_validator_set",
+        json!({ "height": 2 }),
+    );
+    assert_eq!(stored, next_set);
+
+    // the blocks from then on are signed by the new set only
+    let parent = muta::hash(&second);
+    let receipt = bridge.chain.exec(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_headers",
+        update_headers(vec![&muta::header(3, &parent, vec![], &current, 4)]),
+    );
+    assert!(receipt.response.is_error);
+    bridge.chain.write(
+        &bridge.relayer,
+        "crosschain",
+        "update_muta_headers",
+        update_headers(vec![&muta::header(3, &parent, vec![], &next, 3)]),
+    );
+}
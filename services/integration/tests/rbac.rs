@@ -0,0 +1,98 @@
+use serde_json::json;
+
+use integration::{ckb, Account, Chain};
+use protocol::types::{Bytes, Hash};
+
+/// Asset and crosschain deferring to the rbac service, whose admin is
+/// neither the asset's nor the bridge's own admin.
+fn rbac_chain(rbac_admin: &Account, local_admin: &Account) -> Chain {
+    Chain::with_rbac(
+        json!({
+            "id": Hash::digest(Bytes::from("native")),
+            "name": "MutaToken",
+            "supply": "1000000",
+            "issuer": local_admin.address,
+            "rbac": true,
+        }),
+        json!({ "admin": local_admin.address, "rbac": true }),
+        json!({ "admin": rbac_admin.address }),
+    )
+}
+
+#[test]
+fn test_admin_role_gates_both_services() {
+    let rbac_admin = Account::new(1);
+    let local_admin = Account::new(2);
+    let mut chain = rbac_chain(&rbac_admin, &local_admin);
+
+    let minters = json!({ "minters": ["crosschain"] });
+    let receipt = chain.exec(&local_admin, "asset", "set_params", minters.clone());
+    assert!(receipt.response.is_error);
+    chain.write(&rbac_admin, "asset", "set_params", minters);
+
+    let register = json!({
+        "sudt_args": Hash::digest(Bytes::from("sudt")),
+        "name": "Mirror",
+        "symbol": "MIR",
+        "decimals": 8,
+        "scale_factor": 1,
+    });
+    let receipt = chain.exec(
+        &local_admin,
+        "crosschain",
+        "register_mirror_asset",
+        register.clone(),
+    );
+    assert!(receipt.response.is_error);
+
+    // an admin of the bridge alone, granted by the rbac admin
+    chain.write(
+        &rbac_admin,
+        "rbac",
+        "grant_role",
+        json!({ "service": "crosschain", "role": "admin", "address": local_admin.address }),
+    );
+    chain.write(
+        &local_admin,
+        "crosschain",
+        "register_mirror_asset",
+        register,
+    );
+    let receipt = chain.exec(
+        &local_admin,
+        "asset",
+        "set_params",
+        json!({ "minters": [] }),
+    );
+    assert!(receipt.response.is_error);
+}
+
+#[test]
+fn test_relayer_role_gates_headers() {
+    let rbac_admin = Account::new(1);
+    let relayer = Account::new(3);
+    let mut chain = rbac_chain(&rbac_admin, &Account::new(2));
+    let relayer_role = json!({
+        "service": "crosschain",
+        "role": "relayer",
+        "address": relayer.address,
+    });
+
+    let (header, _) = ckb::block(1, &[]);
+    let headers = json!({ "headers": [header] });
+    let receipt = chain.exec(&relayer, "crosschain", "update_headers", headers.clone());
+    assert!(receipt.response.is_error);
+
+    chain.write(&rbac_admin, "rbac", "grant_role", relayer_role.clone());
+    chain.write(&relayer, "crosschain", "update_headers", headers);
+
+    chain.write(&rbac_admin, "rbac", "revoke_role", relayer_role);
+    let (header, _) = ckb::block(2, &[]);
+    let receipt = chain.exec(
+        &relayer,
+        "crosschain",
+        "update_headers",
+        json!({ "headers": [header] }),
+    );
+    assert!(receipt.response.is_error);
+}
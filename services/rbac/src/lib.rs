@@ -0,0 +1,265 @@
+//! Roles accounts hold on other services, which those services check through
+//! the `has_role` read instead of keeping admins and relayers of their own.
+
+#[cfg(test)]
+mod tests;
+pub mod types;
+
+use bytes::Bytes;
+use derive_more::{Display, From};
+
+use binding_macro::{cycles, genesis, read, service, write};
+use protocol::traits::{ExecutorParams, ServiceSDK, StoreMap};
+use protocol::types::{Address, Hash, ServiceContext};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::types::{
+    GetRoleMembersPayload, GetRoleMembersResponse, Grant, HasRolePayload, HasRoleResponse,
+    InitGenesisPayload, RenounceRolePayload, Role, RoleEvent, RoleMembers, SetAdminEvent,
+    SetAdminPayload,
+};
+
+const ADMIN_KEY: &str = "admin";
+/// Most accounts holding one role on one service.
+pub const MAX_ROLE_MEMBERS: usize = 64;
+
+pub struct RbacService<SDK> {
+    sdk:     SDK,
+    // role members by `role_key`
+    members: Box<dyn StoreMap<Hash, RoleMembers>>,
+}
+
+#[service]
+impl<SDK: ServiceSDK> RbacService<SDK> {
+    pub fn new(mut sdk: SDK) -> ProtocolResult<Self> {
+        let members: Box<dyn StoreMap<Hash, RoleMembers>> =
+            sdk.alloc_or_recover_map("role_members")?;
+
+        Ok(Self { sdk, members })
+    }
+
+    #[genesis]
+    fn init_genesis(&mut self, payload: InitGenesisPayload) -> ProtocolResult<()> {
+        self.sdk.set_value(ADMIN_KEY.to_owned(), payload.admin)?;
+        for grant in payload.grants {
+            self.add_member(&grant)?;
+        }
+        Ok(())
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_admin(&self, ctx: ServiceContext) -> ProtocolResult<Address> {
+        self.admin()
+    }
+
+    /// Whether `payload.address` holds the role on the service. The rbac
+    /// admin holds the admin role of every service.
+    #[cycles(100_00)]
+    #[read]
+    fn has_role(
+        &self,
+        ctx: ServiceContext,
+        payload: HasRolePayload,
+    ) -> ProtocolResult<HasRoleResponse> {
+        let has_role = (payload.role == Role::Admin && payload.address == self.admin()?)
+            || self
+                .role_members(&payload.service, payload.role)?
+                .members
+                .contains(&payload.address);
+
+        Ok(HasRoleResponse { has_role })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_role_members(
+        &self,
+        ctx: ServiceContext,
+        payload: GetRoleMembersPayload,
+    ) -> ProtocolResult<GetRoleMembersResponse> {
+        let members = self.role_members(&payload.service, payload.role)?.members;
+
+        Ok(GetRoleMembersResponse {
+            service: payload.service,
+            role: payload.role,
+            members,
+        })
+    }
+
+    /// Grants a role on a service, for the rbac admin or an admin of the
+    /// service.
+    #[cycles(210_00)]
+    #[write]
+    fn grant_role(&mut self, ctx: ServiceContext, payload: Grant) -> ProtocolResult<()> {
+        self.verify_service_admin(&ctx, &payload.service)?;
+        self.add_member(&payload)?;
+        self.emit_role_event(&ctx, payload, "grant_role")
+    }
+
+    /// Revokes a role on a service, for the rbac admin or an admin of the
+    /// service.
+    #[cycles(210_00)]
+    #[write]
+    fn revoke_role(&mut self, ctx: ServiceContext, payload: Grant) -> ProtocolResult<()> {
+        self.verify_service_admin(&ctx, &payload.service)?;
+        self.remove_member(&payload)?;
+        self.emit_role_event(&ctx, payload, "revoke_role")
+    }
+
+    /// Drops a role the caller holds, say of a compromised key.
+    #[cycles(210_00)]
+    #[write]
+    fn renounce_role(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RenounceRolePayload,
+    ) -> ProtocolResult<()> {
+        let grant = Grant {
+            service: payload.service,
+            role:    payload.role,
+            address: ctx.get_caller(),
+        };
+        self.remove_member(&grant)?;
+        self.emit_role_event(&ctx, grant, "renounce_role")
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn set_admin(&mut self, ctx: ServiceContext, payload: SetAdminPayload) -> ProtocolResult<()> {
+        if ctx.get_caller() != self.admin()? {
+            return Err(ServiceError::NonAuthorized.into());
+        }
+
+        self.sdk
+            .set_value(ADMIN_KEY.to_owned(), payload.admin.clone())?;
+
+        let event = SetAdminEvent {
+            admin: payload.admin,
+            topic: "set_admin".to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+
+    fn admin(&self) -> ProtocolResult<Address> {
+        let admin: Address = self
+            .sdk
+            .get_value(&ADMIN_KEY.to_owned())?
+            .expect("Admin should not be none");
+        Ok(admin)
+    }
+
+    fn verify_service_admin(&self, ctx: &ServiceContext, service: &str) -> ProtocolResult<()> {
+        let caller = ctx.get_caller();
+        if caller == self.admin()?
+            || self
+                .role_members(service, Role::Admin)?
+                .members
+                .contains(&caller)
+        {
+            return Ok(());
+        }
+        Err(ServiceError::NonAuthorized.into())
+    }
+
+    fn role_members(&self, service: &str, role: Role) -> ProtocolResult<RoleMembers> {
+        let key = role_key(service, role);
+        if !self.members.contains(&key)? {
+            return Ok(RoleMembers::default());
+        }
+        self.members.get(&key)
+    }
+
+    fn add_member(&mut self, grant: &Grant) -> ProtocolResult<()> {
+        if grant.service.is_empty() {
+            return Err(ServiceError::EmptyService.into());
+        }
+        let mut role_members = self.role_members(&grant.service, grant.role)?;
+        if role_members.members.contains(&grant.address) {
+            return Ok(());
+        }
+        if role_members.members.len() >= MAX_ROLE_MEMBERS {
+            return Err(ServiceError::TooManyMembers {
+                max: MAX_ROLE_MEMBERS,
+            }
+            .into());
+        }
+
+        role_members.members.push(grant.address.clone());
+        self.members
+            .insert(role_key(&grant.service, grant.role), role_members)
+    }
+
+    fn remove_member(&mut self, grant: &Grant) -> ProtocolResult<()> {
+        let key = role_key(&grant.service, grant.role);
+        let mut role_members = self.role_members(&grant.service, grant.role)?;
+        if !role_members.members.contains(&grant.address) {
+            return Err(ServiceError::RoleNotHeld {
+                service: grant.service.clone(),
+                role:    grant.role,
+            }
+            .into());
+        }
+
+        role_members
+            .members
+            .retain(|member| member != &grant.address);
+        if role_members.members.is_empty() {
+            self.members.remove(&key)
+        } else {
+            self.members.insert(key, role_members)
+        }
+    }
+
+    fn emit_role_event(
+        &self,
+        ctx: &ServiceContext,
+        grant: Grant,
+        topic: &str,
+    ) -> ProtocolResult<()> {
+        let event = RoleEvent {
+            service: grant.service,
+            role:    grant.role,
+            address: grant.address,
+            sender:  ctx.get_caller(),
+            topic:   topic.to_owned(),
+        };
+        let event_str = serde_json::to_string(&event).map_err(ServiceError::JsonParse)?;
+        ctx.emit_event(event_str)
+    }
+}
+
+fn role_key(service: &str, role: Role) -> Hash {
+    Hash::digest(Bytes::from(format!("{}/{}", service, role)))
+}
+
+#[derive(Debug, Display, From)]
+pub enum ServiceError {
+    NonAuthorized,
+
+    #[display(fmt = "Roles need a service")]
+    EmptyService,
+
+    #[display(fmt = "Role {} on {:?} is not held", role, service)]
+    RoleNotHeld {
+        service: String,
+        role:    Role,
+    },
+
+    #[display(fmt = "A role holds at most {} members", max)]
+    TooManyMembers {
+        max: usize,
+    },
+
+    #[display(fmt = "Parsing payload to json failed {:?}", _0)]
+    JsonParse(serde_json::Error),
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<ServiceError> for ProtocolError {
+    fn from(err: ServiceError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Service, Box::new(err))
+    }
+}
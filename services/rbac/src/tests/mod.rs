@@ -0,0 +1,203 @@
+use protocol::types::{Address, ServiceContext};
+use testing::{new_sdk, ContextBuilder, MockServiceSDK};
+
+use crate::types::{
+    GetRoleMembersPayload, Grant, HasRolePayload, InitGenesisPayload, RenounceRolePayload, Role,
+    SetAdminPayload,
+};
+use crate::{RbacService, MAX_ROLE_MEMBERS};
+
+const ADMIN: &str = "0x755cdba6ae4f479f7164792b318b2a06c759833b";
+const ALICE: &str = "0x0000000000000000000000000000000000000001";
+const BOB: &str = "0x0000000000000000000000000000000000000002";
+
+#[test]
+fn test_genesis_grants() {
+    let service = new_rbac_service(vec![grant("crosschain", Role::Relayer, ALICE)]);
+    let ctx = mock_context(ADMIN);
+
+    assert!(has_role(&service, &ctx, "crosschain", Role::Relayer, ALICE));
+    assert!(!has_role(&service, &ctx, "asset", Role::Relayer, ALICE));
+    assert!(!has_role(&service, &ctx, "crosschain", Role::Pauser, ALICE));
+    // the rbac admin is an admin of every service
+    assert!(has_role(&service, &ctx, "asset", Role::Admin, ADMIN));
+    assert_eq!(service.get_admin(ctx).unwrap(), address(ADMIN));
+}
+
+#[test]
+fn test_grant_and_revoke() {
+    let mut service = new_rbac_service(vec![]);
+    let ctx = mock_context(ADMIN);
+
+    service
+        .grant_role(ctx.clone(), grant("asset", Role::Pauser, ALICE))
+        .unwrap();
+    service
+        .grant_role(ctx.clone(), grant("asset", Role::Pauser, BOB))
+        .unwrap();
+    // granting twice leaves one member
+    service
+        .grant_role(ctx.clone(), grant("asset", Role::Pauser, ALICE))
+        .unwrap();
+
+    let members = service
+        .get_role_members(ctx.clone(), GetRoleMembersPayload {
+            service: "asset".to_owned(),
+            role:    Role::Pauser,
+        })
+        .unwrap();
+    assert_eq!(members.members, vec![address(ALICE), address(BOB)]);
+
+    service
+        .revoke_role(ctx.clone(), grant("asset", Role::Pauser, ALICE))
+        .unwrap();
+    assert!(!has_role(&service, &ctx, "asset", Role::Pauser, ALICE));
+    assert!(has_role(&service, &ctx, "asset", Role::Pauser, BOB));
+
+    assert!(service
+        .revoke_role(ctx, grant("asset", Role::Pauser, ALICE))
+        .is_err());
+}
+
+#[test]
+fn test_service_admin_grants_its_service_only() {
+    let mut service = new_rbac_service(vec![grant("asset", Role::Admin, ALICE)]);
+    let alice_ctx = mock_context(ALICE);
+
+    service
+        .grant_role(alice_ctx.clone(), grant("asset", Role::Minter, BOB))
+        .unwrap();
+    assert!(has_role(&service, &alice_ctx, "asset", Role::Minter, BOB));
+
+    assert!(service
+        .grant_role(alice_ctx.clone(), grant("crosschain", Role::Relayer, BOB))
+        .is_err());
+
+    let bob_ctx = mock_context(BOB);
+    assert!(service
+        .grant_role(bob_ctx.clone(), grant("asset", Role::Pauser, BOB))
+        .is_err());
+    assert!(service
+        .revoke_role(bob_ctx, grant("asset", Role::Admin, ALICE))
+        .is_err());
+    assert!(service
+        .set_admin(alice_ctx, SetAdminPayload {
+            admin: address(ALICE),
+        })
+        .is_err());
+}
+
+#[test]
+fn test_renounce_role() {
+    let mut service = new_rbac_service(vec![grant("crosschain", Role::Relayer, ALICE)]);
+    let alice_ctx = mock_context(ALICE);
+
+    service
+        .renounce_role(alice_ctx.clone(), RenounceRolePayload {
+            service: "crosschain".to_owned(),
+            role:    Role::Relayer,
+        })
+        .unwrap();
+    assert!(!has_role(
+        &service,
+        &alice_ctx,
+        "crosschain",
+        Role::Relayer,
+        ALICE
+    ));
+
+    assert!(service
+        .renounce_role(alice_ctx, RenounceRolePayload {
+            service: "crosschain".to_owned(),
+            role:    Role::Relayer,
+        })
+        .is_err());
+}
+
+#[test]
+fn test_set_admin() {
+    let mut service = new_rbac_service(vec![]);
+
+    service
+        .set_admin(mock_context(ADMIN), SetAdminPayload {
+            admin: address(ALICE),
+        })
+        .unwrap();
+
+    let ctx = mock_context(ALICE);
+    assert_eq!(service.get_admin(ctx.clone()).unwrap(), address(ALICE));
+    assert!(!has_role(&service, &ctx, "asset", Role::Admin, ADMIN));
+    assert!(service
+        .grant_role(mock_context(ADMIN), grant("asset", Role::Pauser, BOB))
+        .is_err());
+    service
+        .grant_role(ctx, grant("asset", Role::Pauser, BOB))
+        .unwrap();
+}
+
+#[test]
+fn test_grant_bounds() {
+    let mut service = new_rbac_service(vec![]);
+    let ctx = mock_context(ADMIN);
+
+    assert!(service
+        .grant_role(ctx.clone(), grant("", Role::Pauser, ALICE))
+        .is_err());
+
+    for i in 0..MAX_ROLE_MEMBERS {
+        let member = format!("0x{:040x}", i + 1);
+        service
+            .grant_role(ctx.clone(), grant("asset", Role::Minter, &member))
+            .unwrap();
+    }
+    let member = format!("0x{:040x}", MAX_ROLE_MEMBERS + 1);
+    assert!(service
+        .grant_role(ctx, grant("asset", Role::Minter, &member))
+        .is_err());
+}
+
+fn new_rbac_service(grants: Vec<Grant>) -> RbacService<MockServiceSDK> {
+    let mut service = RbacService::new(new_sdk()).unwrap();
+    service
+        .init_genesis(InitGenesisPayload {
+            admin: address(ADMIN),
+            grants,
+        })
+        .unwrap();
+    service
+}
+
+fn has_role(
+    service: &RbacService<MockServiceSDK>,
+    ctx: &ServiceContext,
+    target: &str,
+    role: Role,
+    addr: &str,
+) -> bool {
+    service
+        .has_role(ctx.clone(), HasRolePayload {
+            service: target.to_owned(),
+            role,
+            address: address(addr),
+        })
+        .unwrap()
+        .has_role
+}
+
+fn grant(service: &str, role: Role, addr: &str) -> Grant {
+    Grant {
+        service: service.to_owned(),
+        role,
+        address: address(addr),
+    }
+}
+
+fn address(hex: &str) -> Address {
+    Address::from_hex(hex).unwrap()
+}
+
+fn mock_context(caller: &str) -> ServiceContext {
+    ContextBuilder::new(address(caller))
+        .cycles_limit(1024 * 1024 * 1024)
+        .build()
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::Address;
+use protocol::ProtocolResult;
+
+/// What a grant lets an account do on its target service, which checks the
+/// role itself.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    // changes the service's configuration and grants its roles
+    Admin,
+    // halts the service, or parts of it, in an emergency
+    Pauser,
+    // mints on behalf of the service
+    Minter,
+    // submits the other chain's headers and messages
+    Relayer,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Role::Admin => "admin",
+            Role::Pauser => "pauser",
+            Role::Minter => "minter",
+            Role::Relayer => "relayer",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct InitGenesisPayload {
+    // grants any role on any service, and holds the admin role of every one
+    pub admin:  Address,
+    #[serde(default)]
+    pub grants: Vec<Grant>,
+}
+
+/// `role` on `service` held by `address`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Grant {
+    pub service: String,
+    pub role:    Role,
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RenounceRolePayload {
+    pub service: String,
+    pub role:    Role,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RoleEvent {
+    pub service: String,
+    pub role:    Role,
+    pub address: Address,
+    pub sender:  Address,
+    pub topic:   String, // "grant_role", "revoke_role" or "renounce_role"
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SetAdminPayload {
+    pub admin: Address,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SetAdminEvent {
+    pub admin: Address,
+    pub topic: String, // "set_admin"
+}
+
+/// The read other services call to check a caller's permission.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HasRolePayload {
+    pub service: String,
+    pub role:    Role,
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HasRoleResponse {
+    pub has_role: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GetRoleMembersPayload {
+    pub service: String,
+    pub role:    Role,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GetRoleMembersResponse {
+    pub service: String,
+    pub role:    Role,
+    pub members: Vec<Address>,
+}
+
+/// Holders of one role on one service, in the order they were granted it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoleMembers {
+    pub members: Vec<Address>,
+}
+
+impl rlp::Decodable for RoleMembers {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(Self {
+            members: rlp.as_list()?,
+        })
+    }
+}
+
+impl rlp::Encodable for RoleMembers {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_list(&self.members);
+    }
+}
+
+impl FixedCodec for RoleMembers {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use protocol::types::{Address, Bytes, ServiceContext, ServiceContextParams};
+
+pub struct ContextBuilder {
+    caller:       Address,
+    cycles_limit: u64,
+    height:       u64,
+    timestamp:    u64,
+    extra:        Option<Bytes>,
+    method:       String,
+    payload:      String,
+}
+
+impl ContextBuilder {
+    pub fn new(caller: Address) -> Self {
+        ContextBuilder {
+            caller,
+            cycles_limit: 1024 * 1024 * 1024,
+            height: 1,
+            timestamp: 0,
+            extra: None,
+            method: "service_method".to_owned(),
+            payload: "service_payload".to_owned(),
+        }
+    }
+
+    pub fn cycles_limit(mut self, cycles_limit: u64) -> Self {
+        self.cycles_limit = cycles_limit;
+        self
+    }
+
+    pub fn height(mut self, height: u64) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Extra is how inter-service calls name their calling service.
+    pub fn extra(mut self, extra: Bytes) -> Self {
+        self.extra = Some(extra);
+        self
+    }
+
+    /// Method and payload the service dispatches on, for calls going through
+    /// `Service::write_` and `Service::read_` rather than the method itself.
+    pub fn method(mut self, method: &str, payload: String) -> Self {
+        self.method = method.to_owned();
+        self.payload = payload;
+        self
+    }
+
+    pub fn build(self) -> ServiceContext {
+        let params = ServiceContextParams {
+            tx_hash:         None,
+            nonce:           None,
+            cycles_limit:    self.cycles_limit,
+            cycles_price:    1,
+            cycles_used:     Rc::new(RefCell::new(0)),
+            caller:          self.caller,
+            height:          self.height,
+            timestamp:       self.timestamp,
+            service_name:    "service_name".to_owned(),
+            service_method:  self.method,
+            service_payload: self.payload,
+            extra:           self.extra,
+            events:          Rc::new(RefCell::new(vec![])),
+        };
+
+        ServiceContext::new(params)
+    }
+}
@@ -0,0 +1,9 @@
+//! Helpers shared by the service tests: an in-memory service sdk, a service
+//! context builder and proptest strategies for common payload fields.
+
+mod context;
+mod sdk;
+pub mod strategy;
+
+pub use context::ContextBuilder;
+pub use sdk::{new_sdk, MockServiceSDK, MockStorage};
@@ -0,0 +1,95 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::MemoryDB;
+
+use framework::binding::sdk::{DefalutServiceSDK, DefaultChainQuerier};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{NoopDispatcher, Storage};
+use protocol::types::{Block, Hash, Proof, Receipt, SignedTransaction};
+use protocol::{types::Bytes, ProtocolResult};
+
+/// Service sdk backed by an in-memory trie. Calls to other services go to a
+/// `NoopDispatcher`, so each service is exercised on its own. Writes stay in
+/// the state cache as nothing commits it, which keeps the sdk cheap enough for
+/// the benches too.
+pub type MockServiceSDK = DefalutServiceSDK<
+    GeneralServiceState<MemoryDB>,
+    DefaultChainQuerier<MockStorage>,
+    NoopDispatcher,
+>;
+
+pub fn new_sdk() -> MockServiceSDK {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    DefalutServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        NoopDispatcher {},
+    )
+}
+
+pub struct MockStorage;
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(&self, _: Vec<SignedTransaction>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(&self, _: Hash) -> ProtocolResult<SignedTransaction> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(&self, _: Vec<Hash>) -> ProtocolResult<Vec<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block_by_height(&self, _: u64) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block_by_hash(&self, _: Hash) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_receipt(&self, _: Hash) -> ProtocolResult<Receipt> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(&self, _: Vec<Hash>) -> ProtocolResult<Vec<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,41 @@
+use byteorder::{ByteOrder, LittleEndian};
+use proptest::prelude::*;
+
+use protocol::types::{Address, Bytes, Hash, Hex};
+
+pub fn address() -> impl Strategy<Value = Address> {
+    prop::array::uniform20(any::<u8>())
+        .prop_map(|bytes| Address::from_bytes(Bytes::from(bytes.to_vec())).unwrap())
+}
+
+/// Addresses drawn from a small fixed pool, so generated operations keep
+/// touching the same accounts.
+pub fn address_from_pool(size: usize) -> impl Strategy<Value = Address> {
+    (0..size).prop_map(pool_address)
+}
+
+/// The `index`th address of the pool used by `address_from_pool`.
+pub fn pool_address(index: usize) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[19] = index as u8 + 1;
+    Address::from_bytes(Bytes::from(bytes.to_vec())).unwrap()
+}
+
+pub fn hash() -> impl Strategy<Value = Hash> {
+    prop::array::uniform32(any::<u8>())
+        .prop_map(|bytes| Hash::from_bytes(Bytes::from(bytes.to_vec())).unwrap())
+}
+
+/// Builds the hex the way a json payload is deserialized, since
+/// `Hex::from_string` rejects the empty "0x".
+pub fn hex_bytes(bytes: &[u8]) -> Hex {
+    let s = "0x".to_owned() + &hex::encode(bytes);
+    serde_json::from_value(serde_json::Value::String(s)).unwrap()
+}
+
+/// Little endian u128 as stored in sudt cell data.
+pub fn sudt_amount_data(amount: u128) -> Hex {
+    let mut buf = [0u8; 16];
+    LittleEndian::write_u128(&mut buf, amount);
+    hex_bytes(&buf)
+}
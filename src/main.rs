@@ -6,6 +6,7 @@ use muta::MutaBuilder;
 use node_manager::NodeManagerService;
 use protocol::traits::{Service, ServiceMapping, ServiceSDK};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+use rbac::RbacService;
 
 struct DefaultServiceMapping;
 
@@ -20,6 +21,7 @@ impl ServiceMapping for DefaultServiceMapping {
             "crosschain" => Box::new(CrosschainService::new(sdk)?) as Box<dyn Service>,
             "metadata" => Box::new(MetadataService::new(sdk)?) as Box<dyn Service>,
             "node_manager" => Box::new(NodeManagerService::new(sdk)?) as Box<dyn Service>,
+            "rbac" => Box::new(RbacService::new(sdk)?) as Box<dyn Service>,
             _ => {
                 return Err(MappingError::NotFoundService {
                     service: name.to_owned(),
@@ -37,6 +39,7 @@ impl ServiceMapping for DefaultServiceMapping {
             "crosschain".to_owned(),
             "metadata".to_owned(),
             "node_manager".to_owned(),
+            "rbac".to_owned(),
         ]
     }
 }
@@ -0,0 +1,286 @@
+//! Signs and sends transactions to a muta node over its graphql api and
+//! waits for them to be committed.
+
+use std::convert::TryFrom;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use common_crypto::{
+    Crypto, PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey, Signature, ToPublicKey,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{Address, Bytes, Hash, RawTransaction, TransactionRequest};
+
+use crate::E2eError;
+
+const SEND_TRANSACTION: &str = "mutation($inputRaw: InputRawTransaction!, $inputEncryption: \
+                                InputTransactionEncryption!) { sendTransaction(inputRaw: \
+                                $inputRaw, inputEncryption: $inputEncryption) }";
+
+const GET_RECEIPT: &str = "query($txHash: Hash!) { getReceipt(txHash: $txHash) { height events \
+                           { service data } response { ret isError } } }";
+
+const GET_HEIGHT: &str = "query { getBlock { header { height } } }";
+
+const QUERY_SERVICE: &str = "query($caller: Address!, $serviceName: String!, $method: String!, \
+                             $payload: String!) { queryService(caller: $caller, serviceName: \
+                             $serviceName, method: $method, payload: $payload) { ret isError } }";
+
+// Blocks a transaction stays valid for, within the genesis `timeout_gap`.
+const TX_TIMEOUT: u64 = 15;
+const TX_CYCLES_LIMIT: u64 = 100_000_000_000;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Deserialize)]
+struct GraphqlResponse {
+    data:   Option<Value>,
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Event {
+    pub service: String,
+    pub data:    String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecResp {
+    pub ret:      String,
+    pub is_error: bool,
+}
+
+/// The committed result of a transaction.
+#[derive(Deserialize, Debug)]
+pub struct Receipt {
+    pub height:   String,
+    pub events:   Vec<Event>,
+    pub response: ExecResp,
+}
+
+impl Receipt {
+    /// The events `service` emitted carrying `topic`.
+    pub fn events_with_topic(&self, service: &str, topic: &str) -> Vec<Value> {
+        self.events
+            .iter()
+            .filter(|event| event.service == service)
+            .filter_map(|event| serde_json::from_str::<Value>(&event.data).ok())
+            .filter(|event| event["topic"] == topic)
+            .collect()
+    }
+}
+
+/// A muta account holding its secp256k1 key, which signs what it sends.
+pub struct Signer {
+    privkey:     Secp256k1PrivateKey,
+    pub pubkey:  Bytes,
+    pub address: Address,
+}
+
+impl Signer {
+    pub fn from_hex(privkey: &str) -> Result<Self, E2eError> {
+        let bytes = hex::decode(privkey.trim_start_matches("0x"))
+            .map_err(|_| E2eError::Key(privkey.to_owned()))?;
+        let privkey = Secp256k1PrivateKey::try_from(bytes.as_slice())
+            .map_err(|_| E2eError::Key(privkey.to_owned()))?;
+        let pubkey = privkey.pub_key().to_bytes();
+        let address = Address::from_pubkey_bytes(pubkey.clone())?;
+        Ok(Signer {
+            privkey,
+            pubkey,
+            address,
+        })
+    }
+
+    fn sign(&self, tx_hash: &Hash) -> Result<Bytes, E2eError> {
+        let signature = Secp256k1::sign_message(&tx_hash.as_bytes(), &self.privkey.to_bytes())
+            .map_err(|e| E2eError::Sign(e.to_string()))?;
+        Ok(signature.to_bytes())
+    }
+}
+
+pub struct Devnet {
+    url:      String,
+    chain_id: Hash,
+    client:   reqwest::blocking::Client,
+    sent:     u64,
+}
+
+impl Devnet {
+    /// Waits up to `wait` for the node at `url` to answer, a node started
+    /// along with the tests takes a while to produce its first block.
+    pub fn connect(url: &str, chain_id: Hash, wait: Duration) -> Result<Self, E2eError> {
+        let devnet = Devnet {
+            url: url.to_owned(),
+            chain_id,
+            client: reqwest::blocking::Client::new(),
+            sent: 0,
+        };
+        let start = Instant::now();
+        loop {
+            match devnet.height() {
+                Ok(_) => return Ok(devnet),
+                Err(e) if start.elapsed() > wait => return Err(e),
+                Err(_) => thread::sleep(POLL_INTERVAL),
+            }
+        }
+    }
+
+    pub fn height(&self) -> Result<u64, E2eError> {
+        let data = self.graphql(GET_HEIGHT, json!({}))?;
+        let height = data["getBlock"]["header"]["height"]
+            .as_str()
+            .ok_or_else(|| E2eError::Query("block without height".to_owned()))?;
+        parse_uint64(height)
+    }
+
+    /// Sends the call and waits for its receipt, failing when the call does.
+    pub fn write(
+        &mut self,
+        signer: &Signer,
+        service: &str,
+        method: &str,
+        payload: Value,
+    ) -> Result<Receipt, E2eError> {
+        let receipt = self.exec(signer, service, method, payload)?;
+        if receipt.response.is_error {
+            return Err(E2eError::Failed {
+                call:  format!("{}.{}", service, method),
+                error: receipt.response.ret,
+            });
+        }
+        Ok(receipt)
+    }
+
+    /// Sends the call and waits for its receipt, failed or not.
+    pub fn exec(
+        &mut self,
+        signer: &Signer,
+        service: &str,
+        method: &str,
+        payload: Value,
+    ) -> Result<Receipt, E2eError> {
+        let tx_hash = self.send(signer, service, method, payload)?;
+        self.wait_receipt(&tx_hash)
+    }
+
+    pub fn send(
+        &mut self,
+        signer: &Signer,
+        service: &str,
+        method: &str,
+        payload: Value,
+    ) -> Result<Hash, E2eError> {
+        let raw = RawTransaction {
+            chain_id:     self.chain_id.clone(),
+            nonce:        self.nonce(),
+            timeout:      self.height()? + TX_TIMEOUT,
+            cycles_price: 1,
+            cycles_limit: TX_CYCLES_LIMIT,
+            request:      TransactionRequest {
+                service_name: service.to_owned(),
+                method:       method.to_owned(),
+                payload:      payload.to_string(),
+            },
+        };
+        let tx_hash = Hash::digest(raw.encode_fixed()?);
+        let signature = format!("0x{}", hex::encode(signer.sign(&tx_hash)?));
+
+        let variables = json!({
+            "inputRaw": {
+                "chainId": raw.chain_id.as_hex(),
+                "cyclesLimit": format!("0x{:x}", raw.cycles_limit),
+                "cyclesPrice": format!("0x{:x}", raw.cycles_price),
+                "nonce": raw.nonce.as_hex(),
+                "timeout": format!("0x{:x}", raw.timeout),
+                "serviceName": raw.request.service_name,
+                "method": raw.request.method,
+                "payload": raw.request.payload,
+            },
+            "inputEncryption": {
+                "txHash": tx_hash.as_hex(),
+                "pubkey": format!("0x{}", hex::encode(&signer.pubkey)),
+                "signature": signature,
+            },
+        });
+        self.graphql(SEND_TRANSACTION, variables)?;
+        Ok(tx_hash)
+    }
+
+    /// Polls for the receipt until the transaction times out.
+    pub fn wait_receipt(&self, tx_hash: &Hash) -> Result<Receipt, E2eError> {
+        let deadline = self.height()? + TX_TIMEOUT + 1;
+        loop {
+            if let Ok(data) = self.graphql(GET_RECEIPT, json!({ "txHash": tx_hash.as_hex() })) {
+                if !data["getReceipt"].is_null() {
+                    return Ok(serde_json::from_value(data["getReceipt"].clone())?);
+                }
+            }
+            if self.height()? > deadline {
+                return Err(E2eError::Timeout(tx_hash.as_hex()));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    pub fn read<T: DeserializeOwned>(
+        &self,
+        service: &str,
+        method: &str,
+        payload: Value,
+    ) -> Result<T, E2eError> {
+        let variables = json!({
+            "caller": "0x0000000000000000000000000000000000000000",
+            "serviceName": service,
+            "method": method,
+            "payload": payload.to_string(),
+        });
+        let data = self.graphql(QUERY_SERVICE, variables)?;
+        let resp: ExecResp = serde_json::from_value(data["queryService"].clone())?;
+        if resp.is_error {
+            return Err(E2eError::Failed {
+                call:  format!("{}.{}", service, method),
+                error: resp.ret,
+            });
+        }
+        Ok(serde_json::from_str(&resp.ret)?)
+    }
+
+    fn graphql(&self, query: &str, variables: Value) -> Result<Value, E2eError> {
+        let body = json!({ "query": query, "variables": variables });
+        let resp: GraphqlResponse = self.client.post(&self.url).json(&body).send()?.json()?;
+        match resp.data {
+            Some(data) if resp.errors.is_empty() => Ok(data),
+            _ => {
+                let messages: Vec<String> = resp.errors.into_iter().map(|e| e.message).collect();
+                Err(E2eError::Query(messages.join("; ")))
+            }
+        }
+    }
+
+    // Nonces only need to be unique, the devnet outlives a test run.
+    fn nonce(&mut self) -> Hash {
+        self.sent += 1;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        Hash::digest(Bytes::from(format!("{}-{}", now, self.sent)))
+    }
+}
+
+fn parse_uint64(value: &str) -> Result<u64, E2eError> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16)
+        .map_err(|_| E2eError::Query(format!("{} is not a Uint64", value)))
+}
@@ -0,0 +1,70 @@
+//! End to end tests of the bridge against a running muta devnet, relaying
+//! blocks of a mock ckb chain through consensus and the executor of a real
+//! node.
+//!
+//! The tests are ignored by default, `make e2e-test` runs them against a
+//! node it expects at `MUTA_E2E_URL`, `make e2e-test-via-docker` brings one
+//! up with them.
+
+pub mod devnet;
+pub mod mock_ckb;
+#[cfg(test)]
+mod tests;
+
+use std::env;
+use std::time::Duration;
+
+use derive_more::{Display, From};
+
+use protocol::types::Hash;
+use protocol::ProtocolError;
+
+use crate::devnet::{Devnet, Signer};
+
+pub const DEFAULT_URL: &str = "http://127.0.0.1:8000/graphql";
+
+// Chain id and admin key of tests/e2e/genesis.toml.
+pub const CHAIN_ID: &str = "0xb6a4d7da21443f5e816e8700eea87610e6d769657d6b8ec73028457bf2ca4036";
+pub const ADMIN_PRIVKEY: &str =
+    "0x2b672bb959fa7a852d7259b129b65aee9c83b39f427d6f7bded1f58c4c9310c2";
+
+// How long to wait for a freshly started node to serve requests.
+const CONNECT_WAIT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Display, From)]
+pub enum E2eError {
+    #[display(fmt = "requesting the node failed {:?}", _0)]
+    Http(reqwest::Error),
+
+    #[display(fmt = "parsing json failed {:?}", _0)]
+    Json(serde_json::Error),
+
+    #[display(fmt = "{}", _0)]
+    Protocol(ProtocolError),
+
+    #[display(fmt = "query failed: {}", _0)]
+    Query(String),
+
+    #[display(fmt = "{} failed: {}", call, error)]
+    Failed { call: String, error: String },
+
+    #[display(fmt = "tx {} was not committed before its timeout", _0)]
+    Timeout(String),
+
+    #[display(fmt = "invalid private key {}", _0)]
+    Key(String),
+
+    #[display(fmt = "signing failed: {}", _0)]
+    Sign(String),
+}
+
+impl std::error::Error for E2eError {}
+
+/// The devnet at `MUTA_E2E_URL`, and the admin of its genesis.
+pub fn devnet() -> (Devnet, Signer) {
+    let url = env::var("MUTA_E2E_URL").unwrap_or_else(|_| DEFAULT_URL.to_owned());
+    let chain_id = Hash::from_hex(CHAIN_ID).unwrap();
+    let devnet = Devnet::connect(&url, chain_id, CONNECT_WAIT)
+        .unwrap_or_else(|e| panic!("devnet at {} is unreachable: {}", url, e));
+    (devnet, Signer::from_hex(ADMIN_PRIVKEY).unwrap())
+}
@@ -0,0 +1,82 @@
+//! A ckb chain mined on demand: each block links to its parent and commits
+//! to its txs, with the merkle proofs a relayer would fetch for them.
+
+use serde_json::Value;
+
+use crosschain::proof::header_hash;
+use crosschain::types::{CkbHeader, CkbHeaderInner, CkbTx, EpochView};
+use integration::ckb;
+use protocol::types::{Address, Bytes, Hash};
+
+// Ckb targets a block every 8 seconds.
+const BLOCK_INTERVAL_MS: u64 = 8_000;
+const EPOCH_LENGTH: u64 = 1_000;
+
+/// A mined block, its header and messages in the json relayers submit.
+pub struct MockBlock {
+    pub number:   u64,
+    pub hash:     Hash,
+    pub header:   Value,
+    pub messages: Vec<Value>,
+}
+
+pub struct MockCkb {
+    sudt_args: Hash,
+    number:    u64,
+    parent:    Hash,
+    timestamp: u64,
+    nonce:     u64,
+}
+
+impl MockCkb {
+    /// A chain whose next block is `number`, depositing the sudt with
+    /// `sudt_args`.
+    pub fn new(number: u64, sudt_args: Hash) -> Self {
+        MockCkb {
+            sudt_args,
+            number,
+            parent: Hash::digest(Bytes::from("genesis")),
+            timestamp: 0,
+            nonce: 0,
+        }
+    }
+
+    pub fn sudt_args(&self) -> &Hash {
+        &self.sudt_args
+    }
+
+    /// A deposit of `amount` raw sudt units to `receiver`, each one spending
+    /// a cell of its own.
+    pub fn deposit(&mut self, amount: u128, receiver: &Address) -> CkbTx {
+        self.nonce += 1;
+        ckb::deposit_tx(&self.sudt_args, amount, receiver, self.nonce)
+    }
+
+    /// Mines the next block holding `txs`.
+    pub fn mine(&mut self, txs: &[CkbTx]) -> MockBlock {
+        let number = self.number;
+        let (mut header, messages) = ckb::block(number, txs);
+        header["parent_hash"] = serde_json::to_value(&self.parent).unwrap();
+        header["timestamp"] = Value::String(format!("0x{:x}", self.timestamp));
+        // a devnet outlives a test run, its mock chain may go on for epochs
+        let epoch = EpochView {
+            number: number / EPOCH_LENGTH,
+            index:  number % EPOCH_LENGTH,
+            length: EPOCH_LENGTH,
+        };
+        header["epoch"] = Value::String(format!("0x{:x}", epoch.to_packed()));
+
+        let raw: CkbHeader = serde_json::from_value(header.clone()).unwrap();
+        let hash = header_hash(&CkbHeaderInner::from(raw).unwrap()).unwrap();
+
+        self.number += 1;
+        self.parent = hash.clone();
+        self.timestamp += BLOCK_INTERVAL_MS;
+        MockBlock {
+            number,
+            hash,
+            header,
+            messages,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use serde_json::Value;
+
+use crosschain::molecule::{decode_transaction, encode_raw_transaction};
+use crosschain::proof::{ckb_blake2b, transactions_root, unpack_tx_proof};
+use crosschain::types::{CkbHeader, CkbHeaderInner};
+use protocol::types::{Address, Bytes, Hash};
+
+use crate::mock_ckb::MockCkb;
+
+fn inner(header: &Value) -> CkbHeaderInner {
+    let raw: CkbHeader = serde_json::from_value(header.clone()).unwrap();
+    CkbHeaderInner::from(raw).unwrap()
+}
+
+#[test]
+fn test_mock_ckb_links_blocks() {
+    let receiver = Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap();
+    let mut ckb = MockCkb::new(999, Hash::digest(Bytes::from("sudt")));
+    let tx = ckb.deposit(10, &receiver);
+    let first = ckb.mine(&[tx]);
+    let tx = ckb.deposit(20, &receiver);
+    let second = ckb.mine(&[tx]);
+
+    assert_eq!((first.number, second.number), (999, 1000));
+    let (first, second) = (inner(&first.header), inner(&second.header));
+    assert_eq!(
+        second.parent_hash,
+        crosschain::proof::header_hash(&first).unwrap()
+    );
+    assert!(second.timestamp > first.timestamp);
+    // the chain crosses into the next epoch
+    assert!(second.epoch_view().is_valid());
+    assert!(second.epoch_view().is_successor_of(&first.epoch_view()));
+}
+
+#[test]
+fn test_mock_ckb_proofs_verify() {
+    let receiver = Address::from_bytes(Bytes::from(vec![1u8; 20])).unwrap();
+    let mut ckb = MockCkb::new(1, Hash::digest(Bytes::from("sudt")));
+    let txs: Vec<_> = (1..=3).map(|n| ckb.deposit(n, &receiver)).collect();
+    let block = ckb.mine(&txs);
+    let header = inner(&block.header);
+
+    assert_eq!(block.messages.len(), 3);
+    for message in block.messages.iter() {
+        let raw =
+            hex::decode(message["raw_tx"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+        let (tx, _) = decode_transaction(&raw).unwrap();
+        let tx_hash = ckb_blake2b(&encode_raw_transaction(&tx).unwrap()).unwrap();
+        let proof = message["proof"].as_str().unwrap().trim_start_matches("0x");
+        let proof = unpack_tx_proof(&hex::decode(proof).unwrap()).unwrap();
+
+        let root =
+            transactions_root(&tx_hash, proof.index, &proof.lemmas, &proof.witnesses_root).unwrap();
+        assert_eq!(root, header.transactions_root);
+    }
+}
@@ -0,0 +1,216 @@
+//! Scenarios run against a live devnet, ignored unless asked for with
+//! `cargo test -p e2e -- --ignored`. The devnet outlives a run, so each
+//! scenario bridges a sudt of its own and relays above the stored headers.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use asset::types::{Asset, GetBalanceResponse};
+use crosschain::mirror_asset_id;
+use crosschain::types::{GetHeaderResponse, MissingHeaderRanges};
+use e2e::devnet::{Devnet, Signer};
+use e2e::mock_ckb::{MockBlock, MockCkb};
+use protocol::types::{Address, Bytes, Hash};
+
+struct Scenario {
+    devnet:   Devnet,
+    admin:    Signer,
+    ckb:      MockCkb,
+    asset_id: Hash,
+}
+
+/// The admin relays for a newly registered sudt, mined on a mock ckb chain
+/// continuing from the highest header the devnet stored.
+fn scenario(name: &str) -> Scenario {
+    let (mut devnet, admin) = e2e::devnet();
+    devnet
+        .write(
+            &admin,
+            "crosschain",
+            "add_relayer",
+            json!({ "address": admin.address }),
+        )
+        .unwrap();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let sudt_args = Hash::digest(Bytes::from(format!("{}-{}", name, now)));
+    devnet
+        .write(
+            &admin,
+            "crosschain",
+            "register_mirror_asset",
+            json!({
+                "sudt_args": sudt_args,
+                "name": "Mirror",
+                "symbol": "MIR",
+                "decimals": 8,
+                "scale_factor": 1,
+            }),
+        )
+        .unwrap();
+
+    let ranges: MissingHeaderRanges = devnet
+        .read(
+            "crosschain",
+            "get_missing_header_ranges",
+            json!({ "limit": 1 }),
+        )
+        .unwrap();
+    let next = ranges.highest.map_or(1, |highest| highest + 1);
+
+    Scenario {
+        devnet,
+        admin,
+        ckb: MockCkb::new(next, sudt_args.clone()),
+        asset_id: mirror_asset_id(&sudt_args),
+    }
+}
+
+impl Scenario {
+    fn relay(&mut self, block: &MockBlock) -> e2e::devnet::Receipt {
+        self.devnet
+            .write(
+                &self.admin,
+                "crosschain",
+                "update_headers",
+                json!({ "headers": [block.header] }),
+            )
+            .unwrap();
+        self.devnet
+            .write(
+                &self.admin,
+                "crosschain",
+                "submit_messages",
+                json!({ "height": block.number, "messages": block.messages }),
+            )
+            .unwrap()
+    }
+
+    fn balance(&self, address: &Address) -> u128 {
+        let resp: GetBalanceResponse = self
+            .devnet
+            .read(
+                "asset",
+                "get_balance",
+                json!({ "asset_id": self.asset_id, "user": address }),
+            )
+            .unwrap();
+        resp.balance
+    }
+
+    fn supply(&self) -> u128 {
+        let asset: Asset = self
+            .devnet
+            .read("asset", "get_asset", json!({ "id": self.asset_id }))
+            .unwrap();
+        asset.supply.0
+    }
+}
+
+fn alice() -> Signer {
+    let privkey = Hash::digest(Bytes::from("alice"));
+    Signer::from_hex(&privkey.as_hex()).unwrap()
+}
+
+#[test]
+#[ignore]
+fn test_deposit_mints_through_consensus() {
+    let mut s = scenario("deposit");
+    let alice = alice();
+    let txs = vec![s.ckb.deposit(1000, &alice.address)];
+    let block = s.ckb.mine(&txs);
+
+    let receipt = s.relay(&block);
+    // the deposit mints to alice and the relay fee to the admin relaying
+    assert_eq!(
+        receipt.events_with_topic("crosschain", "mint_asset").len(),
+        2
+    );
+    assert_eq!(s.balance(&alice.address), 990);
+    assert_eq!(s.balance(&s.admin.address), 10);
+    assert_eq!(s.supply(), 1000);
+
+    let header: GetHeaderResponse = s
+        .devnet
+        .read(
+            "crosschain",
+            "get_header",
+            json!({ "height": block.number }),
+        )
+        .unwrap();
+    assert_eq!(header.header.number, block.number);
+
+    // relaying the block again mints nothing
+    let receipt = s
+        .devnet
+        .write(
+            &s.admin,
+            "crosschain",
+            "submit_messages",
+            json!({ "height": block.number, "messages": block.messages }),
+        )
+        .unwrap();
+    let rejected = receipt.events_with_topic("crosschain", "message_rejected");
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0]["reason"], "duplicate_tx");
+    assert_eq!(s.supply(), 1000);
+}
+
+#[test]
+#[ignore]
+fn test_blocks_relayed_in_sequence() {
+    let mut s = scenario("sequence");
+    let alice = alice();
+    for amount in [100, 200, 300].iter() {
+        let txs = vec![s.ckb.deposit(*amount, &alice.address)];
+        let block = s.ckb.mine(&txs);
+        s.relay(&block);
+    }
+
+    assert_eq!(s.supply(), 600);
+    assert_eq!(s.balance(&alice.address) + s.balance(&s.admin.address), 600);
+}
+
+#[test]
+#[ignore]
+fn test_burn_sudt_pays_back_to_ckb() {
+    let mut s = scenario("burn");
+    let alice = alice();
+    let txs = vec![s.ckb.deposit(1000, &alice.address)];
+    let block = s.ckb.mine(&txs);
+    s.relay(&block);
+
+    let ckb_receiver = format!("0x0100{}", hex::encode([7u8; 20]));
+    let receipt = s
+        .devnet
+        .write(
+            &alice,
+            "crosschain",
+            "burn_sudt",
+            json!({ "token_id": s.asset_id, "receiver": ckb_receiver, "amount": "600" }),
+        )
+        .unwrap();
+    let burns = receipt.events_with_topic("crosschain", "burn_asset");
+    assert_eq!(burns.len(), 1);
+    assert_eq!(burns[0]["ckb_receiver"], ckb_receiver);
+
+    assert_eq!(s.balance(&alice.address), 390);
+    assert_eq!(s.supply(), 400);
+
+    // more than alice holds fails in the block, leaving her balance alone
+    let receipt = s
+        .devnet
+        .exec(
+            &alice,
+            "crosschain",
+            "burn_sudt",
+            json!({ "token_id": s.asset_id, "receiver": ckb_receiver, "amount": "5000" }),
+        )
+        .unwrap();
+    assert!(receipt.response.is_error);
+    assert_eq!(s.balance(&alice.address), 390);
+}
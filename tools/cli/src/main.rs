@@ -0,0 +1,183 @@
+//! Builds the payloads of the asset and crosschain services from friendly
+//! arguments and decodes what they emit, for relayers and operators poking
+//! at a chain by hand.
+//!
+//! Payloads and decoded values are printed as pretty json, file arguments
+//! read `-` as stdin.
+
+mod payload;
+#[cfg(test)]
+mod tests;
+
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+use derive_more::{Display, From};
+use serde::Serialize;
+
+use crosschain::molecule::MoleculeError;
+use crosschain::types::MessageError;
+use protocol::types::Hash;
+use protocol::ProtocolError;
+
+#[derive(Debug, Display, From)]
+pub enum CliError {
+    #[display(fmt = "reading the input failed {:?}", _0)]
+    Io(io::Error),
+
+    #[display(fmt = "parsing json failed {:?}", _0)]
+    Json(serde_json::Error),
+
+    #[display(fmt = "{}", _0)]
+    Protocol(ProtocolError),
+
+    #[display(fmt = "decoding the tx failed: {}", _0)]
+    Molecule(MoleculeError),
+
+    #[display(fmt = "message rejected: {}", _0)]
+    Message(MessageError),
+
+    #[display(fmt = "{}", _0)]
+    Invalid(String),
+}
+
+impl std::error::Error for CliError {}
+
+fn read_input(path: &str) -> Result<String, CliError> {
+    if path == "-" {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        return Ok(content);
+    }
+    Ok(fs::read_to_string(path)?)
+}
+
+fn print<T: Serialize>(value: &T) -> Result<(), CliError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn run(matches: &clap::ArgMatches) -> Result<(), CliError> {
+    match matches.subcommand() {
+        ("transfer", Some(m)) => print(&payload::transfer(
+            m.value_of("asset").unwrap(),
+            m.value_of("to").unwrap(),
+            m.value_of("value").unwrap(),
+        )?),
+        ("messages", Some(m)) => {
+            let height = m
+                .value_of("HEIGHT")
+                .unwrap()
+                .parse()
+                .map_err(|_| CliError::Invalid("height is not a number".to_owned()))?;
+            print(&payload::messages(
+                height,
+                &read_input(m.value_of("FILE").unwrap())?,
+                m.value_of("chain-id"),
+            )?)
+        }
+        ("headers", Some(m)) => print(&payload::headers(&read_input(
+            m.value_of("FILE").unwrap(),
+        )?)?),
+        ("decode-event", Some(m)) => {
+            let event = m.value_of("EVENT").unwrap();
+            let event = if event == "-" {
+                read_input(event)?
+            } else {
+                event.to_owned()
+            };
+            print(&payload::decode_event(event.trim())?)
+        }
+        ("asset-id", Some(m)) => print(&payload::asset_id(
+            m.value_of("issuer").unwrap(),
+            m.value_of("name").unwrap(),
+            m.value_of("supply").unwrap(),
+            m.value_of("max-supply"),
+            !m.is_present("soulbound"),
+        )?),
+        ("mirror-asset-id", Some(m)) => {
+            let sudt_args = Hash::from_hex(m.value_of("SUDT_ARGS").unwrap())?;
+            print(&crosschain::mirror_asset_id(&sudt_args))
+        }
+        ("tx", Some(m)) => {
+            let tx = m.value_of("TX").unwrap();
+            let tx = if tx.starts_with("0x") {
+                tx.to_owned()
+            } else {
+                read_input(tx)?
+            };
+            print(&payload::describe_tx(&tx)?)
+        }
+        _ => Err(CliError::Invalid(matches.usage().to_owned())),
+    }
+}
+
+fn main() {
+    let matches = clap::App::new("muta-cross-cli")
+        .version("v0.2.0")
+        .author("Muta Dev <muta@nervos.org>")
+        .about("Builds and decodes the payloads of the asset and crosschain services")
+        .setting(clap::AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            clap::SubCommand::with_name("transfer")
+                .about("Builds an asset TransferPayload")
+                .arg(clap::Arg::from_usage("--asset=<ASSET_ID> 'asset id, hex'"))
+                .arg(clap::Arg::from_usage("--to=<ADDRESS> 'receiver, hex'"))
+                .arg(clap::Arg::from_usage("--value=<VALUE> 'amount, decimal'")),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("messages")
+                .about("Builds a crosschain MessagePayload, packing the proofs")
+                .arg(clap::Arg::from_usage(
+                    "<HEIGHT> 'ckb height the messages are proven at'",
+                ))
+                .arg(clap::Arg::from_usage(
+                    "<FILE> 'json array of messages, each { tx | raw_tx, proof }'",
+                ))
+                .arg(clap::Arg::from_usage(
+                    "--chain-id=[CHAIN_ID] 'ckb network of the messages, hex'",
+                )),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("headers")
+                .about("Builds a crosschain UpdateHeadersPayload")
+                .arg(clap::Arg::from_usage(
+                    "<FILE> 'ckb rpc header or json array of headers'",
+                )),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("decode-event")
+                .about("Decodes the data of an emitted event")
+                .arg(clap::Arg::from_usage("<EVENT> 'event data, json'")),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("asset-id")
+                .about("Computes the id create_asset gives an asset")
+                .arg(clap::Arg::from_usage("--issuer=<ADDRESS> 'creator, hex'"))
+                .arg(clap::Arg::from_usage("--name=<NAME> 'asset name'"))
+                .arg(clap::Arg::from_usage("--supply=<SUPPLY> 'initial supply'"))
+                .arg(clap::Arg::from_usage(
+                    "--max-supply=[MAX_SUPPLY] 'cap on the supply'",
+                ))
+                .arg(clap::Arg::from_usage("--soulbound 'not transferable'")),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("mirror-asset-id")
+                .about("Computes the id of the asset mirroring a sudt")
+                .arg(clap::Arg::from_usage("<SUDT_ARGS> 'sudt type args, hex'")),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("tx")
+                .about("Pretty prints a ckb tx with its hashes and deposit")
+                .arg(clap::Arg::from_usage(
+                    "<TX> 'molecule serialized tx hex, or a file of hex or rpc json'",
+                )),
+        )
+        .get_matches();
+
+    if let Err(e) = run(&matches) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
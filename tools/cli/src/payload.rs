@@ -0,0 +1,141 @@
+use serde_json::{json, Value};
+
+use asset::types::{CreateAssetPayload, TransferPayload};
+use common::amount::Amount;
+use crosschain::deposit::parse_deposit;
+use crosschain::molecule::{decode_transaction, encode_raw_transaction, encode_transaction};
+use crosschain::proof::ckb_blake2b;
+use crosschain::types::{
+    CkbHeader, CkbMessage, CkbTx, MessagePayload, PackedCkbMessage, UpdateHeadersPayload,
+};
+use protocol::types::{Address, Hash};
+
+use crate::CliError;
+
+pub fn parse_amount(value: &str) -> Result<u128, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::Invalid(format!("{} is not an amount", value)))
+}
+
+pub fn transfer(asset_id: &str, to: &str, value: &str) -> Result<TransferPayload, CliError> {
+    Ok(TransferPayload {
+        asset_id: Hash::from_hex(asset_id)?,
+        to:       Address::from_hex(to)?,
+        value:    parse_amount(value)?,
+    })
+}
+
+/// Packs messages in the v1 shape, each `{ "tx" | "raw_tx", "proof" }` with
+/// the proof as json, into a v2 `MessagePayload`.
+pub fn messages(
+    height: u64,
+    messages: &str,
+    source_chain_id: Option<&str>,
+) -> Result<MessagePayload, CliError> {
+    let messages: Vec<CkbMessage> = serde_json::from_str(messages)?;
+    let messages = messages
+        .into_iter()
+        .map(PackedCkbMessage::pack)
+        .collect::<Result<Vec<_>, _>>()?;
+    let source_chain_id = match source_chain_id {
+        Some(id) => Some(Hash::from_hex(id)?),
+        None => None,
+    };
+    Ok(MessagePayload {
+        height,
+        messages,
+        source_chain_id,
+    })
+}
+
+/// Takes the headers as the ckb rpc returns them, a single header or an
+/// array, the `hash` the rpc adds is ignored.
+pub fn headers(headers: &str) -> Result<UpdateHeadersPayload, CliError> {
+    let headers = match serde_json::from_str(headers)? {
+        Value::Array(headers) => headers,
+        header => vec![header],
+    };
+    let headers = headers
+        .into_iter()
+        .map(serde_json::from_value::<CkbHeader>)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(UpdateHeadersPayload { headers })
+}
+
+/// Receipts carry the event data as a json string, which exports quote once
+/// more, so strings are unwrapped until the event object is reached.
+pub fn decode_event(event: &str) -> Result<Value, CliError> {
+    let mut value: Value = match serde_json::from_str(event) {
+        Ok(value) => value,
+        Err(_) => Value::String(event.to_owned()),
+    };
+    while let Value::String(inner) = value {
+        value = serde_json::from_str(&inner)
+            .map_err(|_| CliError::Invalid(format!("{} is not an event", inner)))?;
+    }
+    if value.get("topic").is_none() {
+        return Err(CliError::Invalid("event has no topic".to_owned()));
+    }
+    Ok(value)
+}
+
+pub fn asset_id(
+    issuer: &str,
+    name: &str,
+    supply: &str,
+    max_supply: Option<&str>,
+    transferable: bool,
+) -> Result<Hash, CliError> {
+    let max_supply = match max_supply {
+        Some(max) => Some(Amount(parse_amount(max)?)),
+        None => None,
+    };
+    let payload = CreateAssetPayload {
+        name: name.to_owned(),
+        supply: parse_amount(supply)?,
+        max_supply,
+        transferable,
+    };
+    Ok(asset::asset_id(&payload, &Address::from_hex(issuer)?)?)
+}
+
+/// Reads a ckb tx, molecule serialized hex or rpc json, along with its
+/// hashes and the deposit it carries if any.
+pub fn describe_tx(tx: &str) -> Result<Value, CliError> {
+    let tx = tx.trim();
+    let tx: CkbTx = if tx.starts_with('{') {
+        serde_json::from_str(tx)?
+    } else {
+        let data = hex::decode(tx.trim_start_matches("0x"))
+            .map_err(|_| CliError::Invalid("tx is neither json nor hex".to_owned()))?;
+        let (tx, rest) = decode_transaction(&data)?;
+        if !rest.is_empty() {
+            return Err(CliError::Invalid(format!(
+                "{} bytes follow the tx",
+                rest.len()
+            )));
+        }
+        tx
+    };
+
+    let tx_hash = ckb_blake2b(&encode_raw_transaction(&tx)?)?;
+    let witness_hash = ckb_blake2b(&encode_transaction(&tx)?)?;
+    let deposit = match parse_deposit(&tx) {
+        Ok(deposit) => json!({
+            "sudt_args": deposit.sudt_args,
+            "sudt_amount": deposit.sudt_amount.to_string(),
+            "mirror_asset_id": crosschain::mirror_asset_id(&deposit.sudt_args),
+            "receiver": deposit.receiver,
+            "tag": deposit.tag,
+            "memo": deposit.memo,
+        }),
+        Err(e) => json!({ "rejected": e.to_string() }),
+    };
+    Ok(json!({
+        "tx_hash": tx_hash,
+        "witness_hash": witness_hash,
+        "tx": tx,
+        "deposit": deposit,
+    }))
+}
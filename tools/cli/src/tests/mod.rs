@@ -0,0 +1,177 @@
+use serde_json::{json, Value};
+
+use asset::types::CreateAssetPayload;
+use crosschain::molecule::encode_transaction;
+use crosschain::types::{CkbTx, PackedCkbMessage};
+use protocol::types::{Address, Bytes, Hash};
+
+use crate::payload;
+
+const SUDT_CODE_HASH: &str = "0x57dd0067814dab356e05c6def0d094bb79776711e68ffdfad2df6a7f877f7db6";
+
+fn address(n: u8) -> Address {
+    Address::from_bytes(Bytes::from(vec![n; 20])).unwrap()
+}
+
+fn deposit_tx(amount: u128, receiver: &Address) -> CkbTx {
+    let tx = json!({
+        "version": "0x0",
+        "cell_deps": [],
+        "header_deps": [],
+        "inputs": [{
+            "since": "0x0",
+            "previous_output": {
+                "tx_hash": Hash::digest(Bytes::from("prev")),
+                "index": "0x0",
+            },
+        }],
+        "outputs": [{
+            "capacity": "0x34e62ce00",
+            "lock": {
+                "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+                "hash_type": "type",
+                "args": "0x",
+            },
+            "type": {
+                "code_hash": SUDT_CODE_HASH,
+                "hash_type": "data",
+                "args": Hash::digest(Bytes::from("sudt")),
+            },
+        }],
+        "outputs_data": [format!("0x{}", hex::encode(amount.to_le_bytes()))],
+        "witnesses": [receiver],
+    });
+    serde_json::from_value(tx).unwrap()
+}
+
+#[test]
+fn test_transfer() {
+    let asset_id = Hash::digest(Bytes::from("asset"));
+    let to = address(1);
+    let transfer = payload::transfer(&asset_id.as_hex(), &to.as_hex(), "1000").unwrap();
+    assert_eq!(
+        serde_json::to_value(&transfer).unwrap(),
+        json!({ "asset_id": asset_id, "to": to, "value": "1000" })
+    );
+
+    assert!(payload::transfer(&asset_id.as_hex(), &to.as_hex(), "-1").is_err());
+    assert!(payload::transfer(&asset_id.as_hex(), "0x01", "1").is_err());
+}
+
+#[test]
+fn test_messages_pack_proofs() {
+    let proof = json!({
+        "index": 2,
+        "lemmas": [Hash::digest(Bytes::from("lemma"))],
+        "witnesses_root": Hash::digest(Bytes::from("witnesses")),
+    });
+    let messages = json!([
+        { "tx": deposit_tx(10, &address(1)), "proof": proof },
+        { "tx": deposit_tx(20, &address(2)) },
+    ]);
+    let chain_id = Hash::digest(Bytes::from("ckb"));
+    let payload = payload::messages(7, &messages.to_string(), Some(&chain_id.as_hex())).unwrap();
+
+    assert_eq!(payload.height, 7);
+    assert_eq!(payload.source_chain_id, Some(chain_id));
+    assert!(payload.messages[1].proof.is_none());
+    let unpacked = PackedCkbMessage::unpack(payload.messages[0].clone()).unwrap();
+    assert_eq!(
+        serde_json::to_value(unpacked.proof).unwrap()["lemmas"],
+        proof["lemmas"]
+    );
+
+    // a proof missing its lemmas is refused
+    let messages = json!([{ "tx": deposit_tx(10, &address(1)), "proof": { "index": 2 } }]);
+    assert!(payload::messages(7, &messages.to_string(), None).is_err());
+}
+
+#[test]
+fn test_headers_from_rpc() {
+    let header = json!({
+        "compact_target": "0x1a08a97e",
+        "version": "0x0",
+        "timestamp": "0x16e71002eff",
+        "number": "0x400",
+        "epoch": "0x7080291000049",
+        "parent_hash": Hash::digest(Bytes::from("parent")),
+        "transactions_root": Hash::digest(Bytes::from("txs")),
+        "proposals_hash": Hash::digest(Bytes::from("proposals")),
+        "uncles_hash": Hash::digest(Bytes::from("uncles")),
+        "dao": Hash::digest(Bytes::from("dao")),
+        "nonce": "0x2",
+        "hash": Hash::digest(Bytes::from("hash")),
+    });
+
+    assert_eq!(
+        payload::headers(&header.to_string()).unwrap().headers.len(),
+        1
+    );
+    let headers = json!([header, header]).to_string();
+    assert_eq!(payload::headers(&headers).unwrap().headers.len(), 2);
+    assert!(payload::headers("[{}]").is_err());
+}
+
+#[test]
+fn test_decode_event() {
+    let event = json!({ "topic": "transfer", "value": "10" });
+
+    assert_eq!(payload::decode_event(&event.to_string()).unwrap(), event);
+    // as the receipt carries it, and as an export quotes that again
+    let quoted = Value::String(event.to_string()).to_string();
+    assert_eq!(payload::decode_event(&quoted).unwrap(), event);
+    let twice = Value::String(quoted).to_string();
+    assert_eq!(payload::decode_event(&twice).unwrap(), event);
+
+    assert!(payload::decode_event("{\"value\":\"10\"}").is_err());
+    assert!(payload::decode_event("not json").is_err());
+}
+
+#[test]
+fn test_asset_id() {
+    let issuer = address(3);
+    let create = CreateAssetPayload {
+        name:         "Test".to_owned(),
+        supply:       1000,
+        max_supply:   None,
+        transferable: true,
+    };
+    let expect = asset::asset_id(&create, &issuer).unwrap();
+
+    assert_eq!(
+        payload::asset_id(&issuer.as_hex(), "Test", "1000", None, true).unwrap(),
+        expect
+    );
+    // the optional fields change the id only when set
+    assert_ne!(
+        payload::asset_id(&issuer.as_hex(), "Test", "1000", Some("2000"), true).unwrap(),
+        expect
+    );
+    assert_ne!(
+        payload::asset_id(&issuer.as_hex(), "Test", "1000", None, false).unwrap(),
+        expect
+    );
+}
+
+#[test]
+fn test_describe_tx() {
+    let receiver = address(4);
+    let tx = deposit_tx(100, &receiver);
+    let raw = format!("0x{}", hex::encode(encode_transaction(&tx).unwrap()));
+
+    let from_hex = payload::describe_tx(&raw).unwrap();
+    let from_json = payload::describe_tx(&serde_json::to_string(&tx).unwrap()).unwrap();
+    assert_eq!(from_hex["tx_hash"], from_json["tx_hash"]);
+    assert_eq!(from_hex["deposit"], from_json["deposit"]);
+    assert_eq!(from_hex["deposit"]["sudt_amount"], json!("100"));
+    assert_eq!(from_hex["deposit"]["receiver"], json!(receiver));
+    assert_eq!(
+        from_hex["deposit"]["mirror_asset_id"],
+        json!(crosschain::mirror_asset_id(&Hash::digest(Bytes::from(
+            "sudt"
+        ))))
+    );
+
+    assert!(payload::describe_tx(&format!("{}00", raw)).is_err());
+    assert!(payload::describe_tx("0xzz").is_err());
+}
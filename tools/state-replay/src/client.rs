@@ -0,0 +1,110 @@
+//! Reads the live state through a node's graphql api.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+use asset::types::{Asset, GetBalanceResponse};
+use crosschain::types::{AssetVolume, BridgeStats};
+
+use crate::ledger::ChainState;
+use crate::ReplayError;
+
+const QUERY_SERVICE: &str = "query($caller: Address!, $serviceName: String!, $method: String!, \
+                             $payload: String!) { queryService(caller: $caller, serviceName: \
+                             $serviceName, method: $method, payload: $payload) { ret isError } }";
+
+// Reads need a caller, none of the ones queried here looks at it.
+const CALLER: &str = "0x0000000000000000000000000000000000000000";
+
+#[derive(Deserialize)]
+struct GraphqlResponse {
+    data:   Option<QueryData>,
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryData {
+    query_service: ExecResp,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecResp {
+    ret:      String,
+    is_error: bool,
+}
+
+pub struct GraphqlClient {
+    url:    String,
+    client: reqwest::blocking::Client,
+}
+
+impl GraphqlClient {
+    pub fn new(url: &str) -> Self {
+        GraphqlClient {
+            url:    url.to_owned(),
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    fn query<T: DeserializeOwned>(
+        &self,
+        service: &str,
+        method: &str,
+        payload: serde_json::Value,
+    ) -> Result<T, ReplayError> {
+        let body = json!({
+            "query": QUERY_SERVICE,
+            "variables": {
+                "caller": CALLER,
+                "serviceName": service,
+                "method": method,
+                "payload": payload.to_string(),
+            },
+        });
+        let resp: GraphqlResponse = self.client.post(&self.url).json(&body).send()?.json()?;
+        let exec = match resp.data {
+            Some(data) if resp.errors.is_empty() => data.query_service,
+            _ => {
+                let messages: Vec<String> = resp.errors.into_iter().map(|e| e.message).collect();
+                return Err(ReplayError::Query(messages.join("; ")));
+            }
+        };
+        if exec.is_error {
+            return Err(ReplayError::Query(format!(
+                "{}.{}: {}",
+                service, method, exec.ret
+            )));
+        }
+        Ok(serde_json::from_str(&exec.ret)?)
+    }
+}
+
+impl ChainState for GraphqlClient {
+    fn balance(&self, asset_id: &str, user: &str) -> Result<u128, ReplayError> {
+        let resp: GetBalanceResponse = self.query(
+            "asset",
+            "get_balance",
+            json!({ "asset_id": asset_id, "user": user }),
+        )?;
+        Ok(resp.balance)
+    }
+
+    fn supply(&self, asset_id: &str) -> Result<u128, ReplayError> {
+        let asset: Asset = self.query("asset", "get_asset", json!({ "id": asset_id }))?;
+        Ok(asset.supply.0)
+    }
+
+    fn bridge_volumes(&self) -> Result<Vec<AssetVolume>, ReplayError> {
+        let stats: BridgeStats = self.query("crosschain", "get_bridge_stats", json!(null))?;
+        Ok(stats.volumes)
+    }
+}
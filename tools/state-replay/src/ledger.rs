@@ -0,0 +1,434 @@
+//! The state a log of service events implies: balances, supply and what the
+//! bridge minted and burned of each asset, replayed in log order.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use asset::types::{
+    BurnEvent, ClaimDistributionEvent, ClaimUnlockedEvent, DistributeEvent, HoldEvent,
+    MetaTransferEvent, MintEvent, SessionTransferEvent, TransferEvent, TransferFeeChargedEvent,
+    TransferFromEvent, TransferLockedEvent,
+};
+use asset::{distribution_pool, hold_pool, lock_pool};
+use crosschain::types::{
+    AssetVolume, BurnCancelledEvent, BurnTokenEvent, DepositReassignedEvent, MintTokenEvent,
+};
+
+use crate::ReplayError;
+
+/// The service minting and burning mirror assets.
+pub const BRIDGE_SERVICE: &str = "crosschain";
+
+/// An event as receipts carry it, `data` being its json, either as a string
+/// or inline.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ExportedEvent {
+    pub service: String,
+    pub data:    Value,
+}
+
+// The older asset service events carry no topic, they are told apart by
+// their fields. Events moving no balance match none of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AssetEvent {
+    Mint(MintEvent),
+    Burn(BurnEvent),
+    TransferFrom(TransferFromEvent),
+    SessionTransfer(SessionTransferEvent),
+    MetaTransfer(MetaTransferEvent),
+    Transfer(TransferEvent),
+}
+
+// A transfer fee, charged before the event of the transfer paying it, which
+// carries the value before the fee.
+#[derive(Debug)]
+struct PendingFee {
+    asset_id: String,
+    from:     String,
+    to:       String,
+    fee:      u128,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Volume {
+    pub minted: u128,
+    pub burned: u128,
+}
+
+impl Volume {
+    pub fn outstanding(&self) -> u128 {
+        self.minted.saturating_sub(self.burned)
+    }
+}
+
+/// A difference between the replayed state and the chain's.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    pub what:     String,
+    pub expected: u128,
+    pub actual:   u128,
+}
+
+/// The state the replay checks against.
+pub trait ChainState {
+    fn balance(&self, asset_id: &str, user: &str) -> Result<u128, ReplayError>;
+
+    fn supply(&self, asset_id: &str) -> Result<u128, ReplayError>;
+
+    fn bridge_volumes(&self) -> Result<Vec<AssetVolume>, ReplayError>;
+}
+
+#[derive(Default, Debug)]
+pub struct Ledger {
+    /// Balances by asset id then user, both hex.
+    pub balances:     BTreeMap<String, BTreeMap<String, u128>>,
+    /// Mints and burns by any minter, by asset id.
+    pub supplies:     BTreeMap<String, Volume>,
+    /// Mints and burns by the bridge, by asset id.
+    pub bridged:      BTreeMap<String, Volume>,
+    pub bridge_mints: u64,
+    pub bridge_burns: u64,
+    last_burn_nonce:  Option<u64>,
+    pending_fees:     Vec<PendingFee>,
+    // funding asset of each distribution, which its claims don't carry
+    distributions:    BTreeMap<u64, String>,
+}
+
+impl Ledger {
+    pub fn replay<'a>(
+        events: impl IntoIterator<Item = &'a ExportedEvent>,
+    ) -> Result<Self, ReplayError> {
+        let mut ledger = Ledger::default();
+        for (index, event) in events.into_iter().enumerate() {
+            ledger
+                .apply(event)
+                .map_err(|e| ReplayError::AtEvent(index, Box::new(e)))?;
+        }
+        Ok(ledger)
+    }
+
+    pub fn apply(&mut self, event: &ExportedEvent) -> Result<(), ReplayError> {
+        let data = match &event.data {
+            Value::String(s) => serde_json::from_str(s)?,
+            data => data.clone(),
+        };
+        match event.service.as_str() {
+            "asset" => self.apply_asset(data),
+            BRIDGE_SERVICE => self.apply_bridge(data),
+            _ => Ok(()),
+        }
+    }
+
+    fn apply_asset(&mut self, data: Value) -> Result<(), ReplayError> {
+        if data["topic"].is_string() {
+            return self.apply_asset_topic(data);
+        }
+        let event = match serde_json::from_value(data) {
+            Ok(event) => event,
+            Err(_) => return Ok(()),
+        };
+        match event {
+            AssetEvent::Mint(e) => {
+                let asset_id = e.asset_id.as_hex();
+                self.credit(&asset_id, &e.receiver.as_hex(), e.amount)?;
+                add(
+                    &mut self.supplies.entry(asset_id.clone()).or_default().minted,
+                    e.amount,
+                )?;
+                if e.minter == BRIDGE_SERVICE {
+                    add(
+                        &mut self.bridged.entry(asset_id).or_default().minted,
+                        e.amount,
+                    )?;
+                }
+            }
+            AssetEvent::Burn(e) => {
+                let asset_id = e.asset_id.as_hex();
+                self.debit(&asset_id, &e.user.as_hex(), e.amount)?;
+                add(
+                    &mut self.supplies.entry(asset_id.clone()).or_default().burned,
+                    e.amount,
+                )?;
+                if e.burner == BRIDGE_SERVICE {
+                    add(
+                        &mut self.bridged.entry(asset_id).or_default().burned,
+                        e.amount,
+                    )?;
+                }
+            }
+            AssetEvent::TransferFrom(e) => {
+                let asset_id = e.asset_id.as_hex();
+                self.transfer(
+                    &asset_id,
+                    &e.sender.as_hex(),
+                    &e.recipient.as_hex(),
+                    e.value,
+                )?;
+            }
+            AssetEvent::SessionTransfer(e) => {
+                let asset_id = e.asset_id.as_hex();
+                self.transfer(&asset_id, &e.authorizer.as_hex(), &e.to.as_hex(), e.value)?;
+            }
+            // the relayer is paid its fee by a transfer of its own
+            AssetEvent::MetaTransfer(e) => {
+                let (asset_id, from) = (e.asset_id.as_hex(), e.from.as_hex());
+                self.transfer(&asset_id, &from, &e.to.as_hex(), e.value)?;
+                if e.fee > 0 && e.relayer != e.from {
+                    self.transfer(&asset_id, &from, &e.relayer.as_hex(), e.fee)?;
+                }
+            }
+            AssetEvent::Transfer(e) => {
+                let asset_id = e.asset_id.as_hex();
+                self.transfer(&asset_id, &e.from.as_hex(), &e.to.as_hex(), e.value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_asset_topic(&mut self, data: Value) -> Result<(), ReplayError> {
+        match data["topic"].as_str() {
+            // the collector is paid here, the sender and the recipient by the
+            // transfer event following
+            Some("transfer_fee") => {
+                let event: TransferFeeChargedEvent = serde_json::from_value(data)?;
+                let asset_id = event.asset_id.as_hex();
+                self.credit(&asset_id, &event.collector.as_hex(), event.fee)?;
+                self.pending_fees.push(PendingFee {
+                    asset_id,
+                    from: event.from.as_hex(),
+                    to: event.to.as_hex(),
+                    fee: event.fee,
+                });
+            }
+            // the locked value sits in the lock pool until claimed, the event
+            // carrying it net of the fee
+            Some("transfer_locked") => {
+                let event: TransferLockedEvent = serde_json::from_value(data)?;
+                let (asset_id, from) = (event.asset_id.as_hex(), event.from.as_hex());
+                let fee = self.take_fee(&asset_id, &from, &event.to.as_hex());
+                let value = event.value.checked_add(fee).ok_or(ReplayError::Overflow)?;
+                self.debit(&asset_id, &from, value)?;
+                self.credit(&asset_id, &lock_pool()?.as_hex(), event.value)?;
+            }
+            Some("claim_unlocked") => {
+                let event: ClaimUnlockedEvent = serde_json::from_value(data)?;
+                let asset_id = event.asset_id.as_hex();
+                self.debit(&asset_id, &lock_pool()?.as_hex(), event.value)?;
+                self.credit(&asset_id, &event.holder.as_hex(), event.value)?;
+            }
+            Some("hold") => {
+                let event: HoldEvent = serde_json::from_value(data)?;
+                let asset_id = event.asset_id.as_hex();
+                self.debit(&asset_id, &event.owner.as_hex(), event.amount)?;
+                self.credit(&asset_id, &hold_pool()?.as_hex(), event.amount)?;
+            }
+            // an executed hold goes back to its owner, then on as a transfer
+            // paying the fee
+            Some("release_hold") | Some("execute_hold") => {
+                let event: HoldEvent = serde_json::from_value(data)?;
+                let (asset_id, owner) = (event.asset_id.as_hex(), event.owner.as_hex());
+                self.debit(&asset_id, &hold_pool()?.as_hex(), event.amount)?;
+                self.credit(&asset_id, &owner, event.amount)?;
+                if let Some(to) = event.to {
+                    self.transfer(&asset_id, &owner, &to.as_hex(), event.amount)?;
+                }
+            }
+            Some("distribute") => {
+                let event: DistributeEvent = serde_json::from_value(data)?;
+                let funding_asset = event.funding_asset.as_hex();
+                self.debit(&funding_asset, &event.funder.as_hex(), event.total_amount)?;
+                self.credit(
+                    &funding_asset,
+                    &distribution_pool()?.as_hex(),
+                    event.total_amount,
+                )?;
+                self.distributions.insert(event.id, funding_asset);
+            }
+            Some("claim_distribution") => {
+                let event: ClaimDistributionEvent = serde_json::from_value(data)?;
+                let funding_asset = self
+                    .distributions
+                    .get(&event.id)
+                    .cloned()
+                    .ok_or(ReplayError::UnknownDistribution { id: event.id })?;
+                self.debit(&funding_asset, &distribution_pool()?.as_hex(), event.amount)?;
+                self.credit(&funding_asset, &event.holder.as_hex(), event.amount)?;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn apply_bridge(&mut self, data: Value) -> Result<(), ReplayError> {
+        match data["topic"].as_str() {
+            Some("mint_asset") => {
+                let _: MintTokenEvent = serde_json::from_value(data)?;
+                self.bridge_mints += 1;
+            }
+            Some("burn_asset") => {
+                let event: BurnTokenEvent = serde_json::from_value(data)?;
+                // nonces are handed out one by one, a gap means the log lost
+                // a burn
+                let expected = self.last_burn_nonce.map_or(event.nonce, |n| n + 1);
+                if event.nonce != expected {
+                    return Err(ReplayError::BurnNonceGap {
+                        expected,
+                        found: event.nonce,
+                    });
+                }
+                self.last_burn_nonce = Some(event.nonce);
+                self.bridge_burns += 1;
+            }
+            // The bridge stats take a cancelled burn back out of the burned
+            // volume and never count the re-mint paying it back.
+            Some("burn_cancelled") => {
+                let event: BurnCancelledEvent = serde_json::from_value(data)?;
+                self.net_out(&event.asset_id.as_hex(), event.amount)?;
+                self.bridge_burns = self.bridge_burns.saturating_sub(1);
+            }
+            // A reassigned deposit is burned from the recovery account and
+            // minted to its receiver, neither of which the stats count.
+            Some("deposit_reassigned") => {
+                let event: DepositReassignedEvent = serde_json::from_value(data)?;
+                self.net_out(&event.asset_id.as_hex(), event.amount)?;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Takes a bridge burn of `amount` and the mint matching it out of the
+    /// bridged volume of `asset_id`, the supply keeping both.
+    fn net_out(&mut self, asset_id: &str, amount: u128) -> Result<(), ReplayError> {
+        let volume = self.bridged.entry(asset_id.to_owned()).or_default();
+        match (
+            volume.minted.checked_sub(amount),
+            volume.burned.checked_sub(amount),
+        ) {
+            (Some(minted), Some(burned)) => {
+                volume.minted = minted;
+                volume.burned = burned;
+                Ok(())
+            }
+            _ => Err(ReplayError::VolumeUnderflow {
+                asset_id: asset_id.to_owned(),
+            }),
+        }
+    }
+
+    /// Moves `value` from `from` to `to`, who gets it less the transfer fee
+    /// charged on it.
+    fn transfer(
+        &mut self,
+        asset_id: &str,
+        from: &str,
+        to: &str,
+        value: u128,
+    ) -> Result<(), ReplayError> {
+        let fee = self.take_fee(asset_id, from, to);
+        self.debit(asset_id, from, value)?;
+        let received = value
+            .checked_sub(fee)
+            .ok_or_else(|| ReplayError::Underflow {
+                asset_id: asset_id.to_owned(),
+                user:     to.to_owned(),
+            })?;
+        self.credit(asset_id, to, received)
+    }
+
+    // The oldest fee charged on a transfer from `from` to `to` not yet paid,
+    // zero when there is none.
+    fn take_fee(&mut self, asset_id: &str, from: &str, to: &str) -> u128 {
+        let index = self
+            .pending_fees
+            .iter()
+            .position(|f| f.asset_id == asset_id && f.from == from && f.to == to);
+        index.map_or(0, |i| self.pending_fees.remove(i).fee)
+    }
+
+    fn credit(&mut self, asset_id: &str, user: &str, amount: u128) -> Result<(), ReplayError> {
+        let balance = self.balance_mut(asset_id, user);
+        add(balance, amount)
+    }
+
+    fn debit(&mut self, asset_id: &str, user: &str, amount: u128) -> Result<(), ReplayError> {
+        let balance = self.balance_mut(asset_id, user);
+        *balance = balance
+            .checked_sub(amount)
+            .ok_or_else(|| ReplayError::Underflow {
+                asset_id: asset_id.to_owned(),
+                user:     user.to_owned(),
+            })?;
+        Ok(())
+    }
+
+    fn balance_mut(&mut self, asset_id: &str, user: &str) -> &mut u128 {
+        self.balances
+            .entry(asset_id.to_owned())
+            .or_default()
+            .entry(user.to_owned())
+            .or_default()
+    }
+
+    /// Compares the assets the bridge minted or burned with the chain. Other
+    /// assets start from genesis balances the log doesn't hold, so they are
+    /// left out.
+    pub fn diff(&self, chain: &impl ChainState) -> Result<Vec<Mismatch>, ReplayError> {
+        let mut mismatches = vec![];
+        let mut check = |what: String, expected: u128, actual: u128| {
+            if expected != actual {
+                mismatches.push(Mismatch {
+                    what,
+                    expected,
+                    actual,
+                });
+            }
+        };
+
+        let volumes = chain.bridge_volumes()?;
+        for (asset_id, bridged) in self.bridged.iter() {
+            let supply = self.supplies[asset_id].outstanding();
+            check(
+                format!("supply of {}", asset_id),
+                supply,
+                chain.supply(asset_id)?,
+            );
+
+            let volume = volumes
+                .iter()
+                .find(|v| &v.asset_id.as_hex() == asset_id)
+                .map_or_else(Volume::default, |v| Volume {
+                    minted: v.minted,
+                    burned: v.burned,
+                });
+            check(
+                format!("bridge minted of {}", asset_id),
+                bridged.minted,
+                volume.minted,
+            );
+            check(
+                format!("bridge burned of {}", asset_id),
+                bridged.burned,
+                volume.burned,
+            );
+
+            for (user, balance) in self.balances.get(asset_id).into_iter().flatten() {
+                check(
+                    format!("balance of {} in {}", user, asset_id),
+                    *balance,
+                    chain.balance(asset_id, user)?,
+                );
+            }
+        }
+
+        Ok(mismatches)
+    }
+}
+
+fn add(value: &mut u128, amount: u128) -> Result<(), ReplayError> {
+    *value = value.checked_add(amount).ok_or(ReplayError::Overflow)?;
+    Ok(())
+}
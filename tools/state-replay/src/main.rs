@@ -0,0 +1,128 @@
+//! Replays an exported log of asset and crosschain events into the balances
+//! and bridged supply it implies, then diffs them against a live node, to
+//! audit that the bridge accounting matches the chain.
+//!
+//! The log holds the events of the receipts in block order, as a json array
+//! or one event per line, each `{ "service": ..., "data": ... }`.
+
+mod client;
+mod ledger;
+#[cfg(test)]
+mod tests;
+
+use std::fs;
+use std::process;
+
+use derive_more::{Display, From};
+use protocol::ProtocolError;
+
+use crate::client::GraphqlClient;
+use crate::ledger::{ExportedEvent, Ledger};
+
+#[derive(Debug, Display, From)]
+pub enum ReplayError {
+    #[display(fmt = "reading the event log failed {:?}", _0)]
+    Io(std::io::Error),
+
+    #[display(fmt = "parsing json failed {:?}", _0)]
+    Json(serde_json::Error),
+
+    #[display(fmt = "querying the node failed {:?}", _0)]
+    Http(reqwest::Error),
+
+    #[display(fmt = "{}", _0)]
+    Protocol(ProtocolError),
+
+    #[display(fmt = "query failed: {}", _0)]
+    Query(String),
+
+    #[display(fmt = "event {}: {}", _0, _1)]
+    AtEvent(usize, Box<ReplayError>),
+
+    #[display(fmt = "balance of {} in {} goes negative", user, asset_id)]
+    Underflow { asset_id: String, user: String },
+
+    #[display(fmt = "bridged volume of {} goes negative", asset_id)]
+    VolumeUnderflow { asset_id: String },
+
+    #[display(fmt = "claim of distribution {} the log never funded", id)]
+    UnknownDistribution { id: u64 },
+
+    #[display(fmt = "amount overflows u128")]
+    Overflow,
+
+    #[display(fmt = "burn nonce {} follows where {} was expected", found, expected)]
+    BurnNonceGap { expected: u64, found: u64 },
+}
+
+impl std::error::Error for ReplayError {}
+
+fn read_events(path: &str) -> Result<Vec<ExportedEvent>, ReplayError> {
+    let content = fs::read_to_string(path)?;
+    if content.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(&content)?);
+    }
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(ReplayError::from))
+        .collect()
+}
+
+fn run(events_path: &str, url: Option<&str>) -> Result<bool, ReplayError> {
+    let events = read_events(events_path)?;
+    let ledger = Ledger::replay(&events)?;
+
+    println!(
+        "replayed {} events, {} bridge mints, {} bridge burns",
+        events.len(),
+        ledger.bridge_mints,
+        ledger.bridge_burns
+    );
+    for (asset_id, volume) in ledger.bridged.iter() {
+        println!(
+            "{}: bridge minted {}, burned {}, supply {}",
+            asset_id,
+            volume.minted,
+            volume.burned,
+            ledger.supplies[asset_id].outstanding()
+        );
+    }
+
+    let url = match url {
+        Some(url) => url,
+        None => return Ok(true),
+    };
+    let mismatches = ledger.diff(&GraphqlClient::new(url))?;
+    for m in mismatches.iter() {
+        println!(
+            "mismatch {}: replayed {}, chain {}",
+            m.what, m.expected, m.actual
+        );
+    }
+    if mismatches.is_empty() {
+        println!("chain state matches the log");
+    }
+    Ok(mismatches.is_empty())
+}
+
+fn main() {
+    let matches = clap::App::new("state-replay")
+        .version("v0.2.0")
+        .author("Muta Dev <muta@nervos.org>")
+        .about("Rebuilds the bridge accounting from an event log and diffs it against a node")
+        .arg(clap::Arg::from_usage("<EVENTS> 'exported event log, json'"))
+        .arg(clap::Arg::from_usage(
+            "-u --url=[URL] 'graphql endpoint of the node to diff against'",
+        ))
+        .get_matches();
+
+    match run(matches.value_of("EVENTS").unwrap(), matches.value_of("url")) {
+        Ok(true) => (),
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+}
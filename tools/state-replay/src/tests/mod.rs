@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crosschain::types::AssetVolume;
+use protocol::types::{Address, Bytes, Hash};
+
+use crate::ledger::{ChainState, ExportedEvent, Ledger, Mismatch};
+use crate::ReplayError;
+
+fn asset_id() -> Hash {
+    Hash::digest(Bytes::from("mirror"))
+}
+
+fn address(n: u8) -> Address {
+    Address::from_bytes(Bytes::from(vec![n; 20])).unwrap()
+}
+
+fn event(service: &str, data: Value) -> ExportedEvent {
+    // receipts carry the data as a string
+    ExportedEvent {
+        service: service.to_owned(),
+        data:    Value::String(data.to_string()),
+    }
+}
+
+fn mint(receiver: &Address, amount: u128, minter: &str) -> ExportedEvent {
+    event(
+        "asset",
+        json!({
+            "asset_id": asset_id(),
+            "receiver": receiver,
+            "amount": amount.to_string(),
+            "memo": "",
+            "minter": minter,
+        }),
+    )
+}
+
+fn burn(user: &Address, amount: u128) -> ExportedEvent {
+    event(
+        "asset",
+        json!({
+            "asset_id": asset_id(),
+            "user": user,
+            "amount": amount.to_string(),
+            "burner": "crosschain",
+        }),
+    )
+}
+
+fn transfer(from: &Address, to: &Address, value: u128) -> ExportedEvent {
+    event(
+        "asset",
+        json!({ "asset_id": asset_id(), "from": from, "to": to, "value": value.to_string() }),
+    )
+}
+
+fn session_transfer(authorizer: &Address, to: &Address, value: u128) -> ExportedEvent {
+    event(
+        "asset",
+        json!({
+            "asset_id": asset_id(),
+            "session_key": address(9),
+            "authorizer": authorizer,
+            "to": to,
+            "value": value.to_string(),
+            "remaining_budget": "0",
+        }),
+    )
+}
+
+fn transfer_fee(from: &Address, to: &Address, collector: &Address, fee: u128) -> ExportedEvent {
+    event(
+        "asset",
+        json!({
+            "asset_id": asset_id(),
+            "from": from,
+            "to": to,
+            "collector": collector,
+            "fee": fee.to_string(),
+            "topic": "transfer_fee",
+        }),
+    )
+}
+
+fn hold_event(
+    id: u64,
+    owner: &Address,
+    amount: u128,
+    to: Option<&Address>,
+    topic: &str,
+) -> ExportedEvent {
+    event(
+        "asset",
+        json!({
+            "id": id,
+            "asset_id": asset_id(),
+            "owner": owner,
+            "service": "shop",
+            "amount": amount.to_string(),
+            "to": to,
+            "topic": topic,
+        }),
+    )
+}
+
+fn burn_asset(nonce: u64) -> ExportedEvent {
+    event(
+        "crosschain",
+        json!({
+            "asset_id": asset_id(),
+            "sudt_args": Hash::digest(Bytes::from("sudt")),
+            "ckb_receiver": "0x",
+            "sudt_amount": "1",
+            "nonce": nonce,
+            "kind": "cross_to_ckb",
+            "topic": "burn_asset",
+        }),
+    )
+}
+
+fn burn_cancelled(nonce: u64, muta_sender: &Address, amount: u128) -> ExportedEvent {
+    event(
+        "crosschain",
+        json!({
+            "nonce": nonce,
+            "asset_id": asset_id(),
+            "muta_sender": muta_sender,
+            "amount": amount.to_string(),
+            "topic": "burn_cancelled",
+        }),
+    )
+}
+
+fn deposit_reassigned(receiver: &Address, amount: u128) -> ExportedEvent {
+    event(
+        "crosschain",
+        json!({
+            "tx_hash": Hash::digest(Bytes::from("deposit")),
+            "asset_id": asset_id(),
+            "amount": amount.to_string(),
+            "receiver": receiver,
+            "admin": address(9),
+            "topic": "deposit_reassigned",
+        }),
+    )
+}
+
+struct MockChain {
+    balances: HashMap<String, u128>,
+    supply:   u128,
+    volume:   (u128, u128),
+}
+
+impl ChainState for MockChain {
+    fn balance(&self, _: &str, user: &str) -> Result<u128, ReplayError> {
+        Ok(self.balances.get(user).cloned().unwrap_or(0))
+    }
+
+    fn supply(&self, _: &str) -> Result<u128, ReplayError> {
+        Ok(self.supply)
+    }
+
+    fn bridge_volumes(&self) -> Result<Vec<AssetVolume>, ReplayError> {
+        Ok(vec![AssetVolume {
+            asset_id: asset_id(),
+            minted:   self.volume.0,
+            burned:   self.volume.1,
+        }])
+    }
+}
+
+#[test]
+fn test_replay() {
+    let (alice, bob, relayer) = (address(1), address(2), address(3));
+    let events = vec![
+        mint(&alice, 990, "crosschain"),
+        mint(&relayer, 10, "crosschain"),
+        transfer(&alice, &bob, 250),
+        session_transfer(&alice, &bob, 50),
+        burn(&bob, 100),
+        burn_asset(1),
+        // neither moves a balance
+        event(
+            "asset",
+            json!({ "id": asset_id(), "name": "Mirror", "supply": "0", "issuer": alice }),
+        ),
+        event("crosschain", json!({ "topic": "headers_updated" })),
+    ];
+    let ledger = Ledger::replay(&events).unwrap();
+
+    let balances = &ledger.balances[&asset_id().as_hex()];
+    assert_eq!(balances[&alice.as_hex()], 690);
+    assert_eq!(balances[&bob.as_hex()], 200);
+    assert_eq!(balances[&relayer.as_hex()], 10);
+    let bridged = ledger.bridged[&asset_id().as_hex()];
+    assert_eq!((bridged.minted, bridged.burned), (1000, 100));
+    assert_eq!(ledger.supplies[&asset_id().as_hex()].outstanding(), 900);
+    assert_eq!(ledger.bridge_burns, 1);
+}
+
+#[test]
+fn test_replay_transfer_fee() {
+    let (alice, bob, collector, relayer) = (address(1), address(2), address(3), address(4));
+    let events = vec![
+        mint(&alice, 1000, "crosschain"),
+        // the fee event comes first, the transfer one carries the full value
+        transfer_fee(&alice, &bob, &collector, 10),
+        transfer(&alice, &bob, 100),
+        // a meta transfer pays the fee on both the value and the relayer fee
+        transfer_fee(&alice, &bob, &collector, 5),
+        transfer_fee(&alice, &relayer, &collector, 2),
+        event(
+            "asset",
+            json!({
+                "asset_id": asset_id(),
+                "from": alice,
+                "to": bob,
+                "value": "50",
+                "relayer": relayer,
+                "fee": "20",
+                "nonce": 0,
+            }),
+        ),
+    ];
+    let ledger = Ledger::replay(&events).unwrap();
+
+    let balances = &ledger.balances[&asset_id().as_hex()];
+    assert_eq!(balances[&alice.as_hex()], 830);
+    assert_eq!(balances[&bob.as_hex()], 135);
+    assert_eq!(balances[&relayer.as_hex()], 18);
+    assert_eq!(balances[&collector.as_hex()], 17);
+}
+
+#[test]
+fn test_replay_locks() {
+    let (alice, bob, collector) = (address(1), address(2), address(3));
+    let pool = asset::lock_pool().unwrap();
+    let events = vec![
+        mint(&alice, 1000, "crosschain"),
+        transfer_fee(&alice, &bob, &collector, 10),
+        event(
+            "asset",
+            json!({
+                "asset_id": asset_id(),
+                "from": alice,
+                "to": bob,
+                "value": "90",
+                "unlock_height": 100,
+                "topic": "transfer_locked",
+            }),
+        ),
+    ];
+    let mut ledger = Ledger::replay(&events).unwrap();
+
+    let balances = &ledger.balances[&asset_id().as_hex()];
+    assert_eq!(balances[&alice.as_hex()], 900);
+    assert_eq!(balances[&collector.as_hex()], 10);
+    assert_eq!(balances[&pool.as_hex()], 90);
+    assert_eq!(balances.get(&bob.as_hex()), None);
+
+    ledger
+        .apply(&event(
+            "asset",
+            json!({
+                "asset_id": asset_id(),
+                "holder": bob,
+                "value": "90",
+                "topic": "claim_unlocked",
+            }),
+        ))
+        .unwrap();
+    let balances = &ledger.balances[&asset_id().as_hex()];
+    assert_eq!(balances[&pool.as_hex()], 0);
+    assert_eq!(balances[&bob.as_hex()], 90);
+}
+
+#[test]
+fn test_replay_holds() {
+    let (alice, bob, collector) = (address(1), address(2), address(3));
+    let pool = asset::hold_pool().unwrap().as_hex();
+    let events = vec![
+        mint(&alice, 1000, "crosschain"),
+        hold_event(1, &alice, 300, None, "hold"),
+        hold_event(2, &alice, 200, None, "hold"),
+    ];
+    let mut ledger = Ledger::replay(&events).unwrap();
+    let balances = &ledger.balances[&asset_id().as_hex()];
+    assert_eq!(balances[&alice.as_hex()], 500);
+    assert_eq!(balances[&pool], 500);
+
+    let events = vec![
+        hold_event(1, &alice, 300, None, "release_hold"),
+        transfer_fee(&alice, &bob, &collector, 20),
+        hold_event(2, &alice, 200, Some(&bob), "execute_hold"),
+    ];
+    for event in events.iter() {
+        ledger.apply(event).unwrap();
+    }
+    let balances = &ledger.balances[&asset_id().as_hex()];
+    assert_eq!(balances[&pool], 0);
+    assert_eq!(balances[&alice.as_hex()], 800);
+    assert_eq!(balances[&bob.as_hex()], 180);
+    assert_eq!(balances[&collector.as_hex()], 20);
+}
+
+#[test]
+fn test_replay_distributions() {
+    let (alice, bob, carol) = (address(1), address(2), address(3));
+    let pool = asset::distribution_pool().unwrap().as_hex();
+    let claim = |holder: &Address, amount: u128| {
+        event(
+            "asset",
+            json!({
+                "id": 1,
+                "holder": holder,
+                "amount": amount.to_string(),
+                "topic": "claim_distribution",
+            }),
+        )
+    };
+    let events = vec![
+        mint(&alice, 1000, "crosschain"),
+        event(
+            "asset",
+            json!({
+                "id": 1,
+                "asset_id": Hash::digest(Bytes::from("shares")),
+                "funding_asset": asset_id(),
+                "funder": alice,
+                "total_amount": "600",
+                "snapshot_height": 10,
+                "topic": "distribute",
+            }),
+        ),
+        claim(&bob, 400),
+        claim(&carol, 200),
+    ];
+    let ledger = Ledger::replay(&events).unwrap();
+
+    let balances = &ledger.balances[&asset_id().as_hex()];
+    assert_eq!(balances[&alice.as_hex()], 400);
+    assert_eq!(balances[&bob.as_hex()], 400);
+    assert_eq!(balances[&carol.as_hex()], 200);
+    assert_eq!(balances[&pool], 0);
+
+    // a claim of a distribution missing from the log
+    match Ledger::replay(&[claim(&bob, 400)]) {
+        Err(ReplayError::AtEvent(0, e)) => match *e {
+            ReplayError::UnknownDistribution { id: 1 } => (),
+            e => panic!("unexpected error {}", e),
+        },
+        _ => panic!("should fail"),
+    }
+}
+
+#[test]
+fn test_replay_inconsistent_log() {
+    let alice = address(1);
+
+    // a transfer before the mint funding it
+    let events = vec![
+        transfer(&alice, &address(2), 1),
+        mint(&alice, 1, "crosschain"),
+    ];
+    match Ledger::replay(&events) {
+        Err(ReplayError::AtEvent(0, e)) => match *e {
+            ReplayError::Underflow { .. } => (),
+            e => panic!("unexpected error {}", e),
+        },
+        _ => panic!("should fail"),
+    }
+
+    // a burn missing from the log
+    let events = vec![burn_asset(1), burn_asset(3)];
+    match Ledger::replay(&events) {
+        Err(ReplayError::AtEvent(1, e)) => match *e {
+            ReplayError::BurnNonceGap {
+                expected: 2,
+                found: 3,
+            } => (),
+            e => panic!("unexpected error {}", e),
+        },
+        _ => panic!("should fail"),
+    }
+}
+
+#[test]
+fn test_diff() {
+    let (alice, bob) = (address(1), address(2));
+    let events = vec![
+        mint(&alice, 1000, "crosschain"),
+        // minted by another service, in the supply but not the bridge volume
+        mint(&bob, 50, "other"),
+        burn(&alice, 400),
+    ];
+    let ledger = Ledger::replay(&events).unwrap();
+
+    let mut chain = MockChain {
+        balances: vec![(alice.as_hex(), 600), (bob.as_hex(), 50)]
+            .into_iter()
+            .collect(),
+        supply:   650,
+        volume:   (1000, 400),
+    };
+    assert!(ledger.diff(&chain).unwrap().is_empty());
+
+    chain.balances.insert(bob.as_hex(), 60);
+    chain.volume = (1000, 300);
+    let mismatches = ledger.diff(&chain).unwrap();
+    assert_eq!(mismatches, vec![
+        Mismatch {
+            what:     format!("bridge burned of {}", asset_id().as_hex()),
+            expected: 400,
+            actual:   300,
+        },
+        Mismatch {
+            what:     format!("balance of {} in {}", bob.as_hex(), asset_id().as_hex()),
+            expected: 50,
+            actual:   60,
+        },
+    ]);
+}
+
+#[test]
+fn test_diff_cancel_and_reassign() {
+    let (alice, bob, recovery) = (address(1), address(2), address(4));
+    let events = vec![
+        mint(&alice, 1000, "crosschain"),
+        burn(&alice, 300),
+        burn_asset(1),
+        // cancel_burn mints the burn back
+        mint(&alice, 300, "crosschain"),
+        burn_cancelled(1, &alice, 300),
+        // a deposit recovered then reassigned to bob
+        mint(&recovery, 200, "crosschain"),
+        burn(&recovery, 200),
+        mint(&bob, 200, "crosschain"),
+        deposit_reassigned(&bob, 200),
+    ];
+    let ledger = Ledger::replay(&events).unwrap();
+
+    let bridged = ledger.bridged[&asset_id().as_hex()];
+    assert_eq!((bridged.minted, bridged.burned), (1200, 0));
+    assert_eq!(ledger.bridge_burns, 0);
+
+    let chain = MockChain {
+        balances: vec![(alice.as_hex(), 1000), (bob.as_hex(), 200)]
+            .into_iter()
+            .collect(),
+        supply:   1200,
+        volume:   (1200, 0),
+    };
+    assert!(ledger.diff(&chain).unwrap().is_empty());
+
+    // a cancel the log holds no burn for
+    let events = vec![burn_cancelled(1, &alice, 300)];
+    match Ledger::replay(&events) {
+        Err(ReplayError::AtEvent(0, e)) => match *e {
+            ReplayError::VolumeUnderflow { .. } => (),
+            e => panic!("unexpected error {}", e),
+        },
+        _ => panic!("should fail"),
+    }
+}